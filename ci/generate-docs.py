@@ -456,6 +456,7 @@ TOC = [
         children=[
             Gen("wezterm cli", "cli/cli"),
             Page("wezterm connect", "cli/connect.md"),
+            Page("wezterm doctor", "cli/doctor.md"),
             Page("wezterm imgcat", "cli/imgcat.md"),
             Page("wezterm ls-fonts", "cli/ls-fonts.md"),
             Page("wezterm record", "cli/record.md"),
@@ -464,6 +465,7 @@ TOC = [
             Page("wezterm set-working-directory", "cli/set-working-directory.md"),
             Page("wezterm show-keys", "cli/show-keys.md"),
             Page("wezterm ssh", "cli/ssh.md"),
+            Page("wezterm ssh-keys", "cli/ssh-keys.md"),
             Page("wezterm start", "cli/start.md"),
         ],
     ),
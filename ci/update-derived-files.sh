@@ -27,7 +27,7 @@ trim_file() {
 
 cargo run --example narrow $PWD/target/debug/wezterm --help | ./target/debug/strip-ansi-escapes | trim_file > docs/examples/cmd-synopsis-wezterm--help.txt
 
-for cmd in start ssh serial connect ls-fonts show-keys imgcat set-working-directory record replay  ; do
+for cmd in start ssh ssh-keys serial connect ls-fonts show-keys doctor imgcat set-working-directory record replay  ; do
   fname="docs/examples/cmd-synopsis-wezterm-${cmd}--help.txt"
   cargo run --example narrow $PWD/target/debug/wezterm $cmd --help | ./target/debug/strip-ansi-escapes | trim_file > $fname
 done
@@ -36,7 +36,10 @@ for cmd in \
     activate-pane \
     activate-pane-direction \
     adjust-pane-size \
+    dump-config-schema \
+    dump-pane-state \
     activate-tab \
+    get-config \
     get-pane-direction \
     get-text \
     kill-pane \
@@ -44,11 +47,16 @@ for cmd in \
     list-clients \
     move-pane-to-new-tab \
     rename-workspace \
+    screenshot \
     send-text \
+    set-config \
+    set-config-profile \
+    set-log-level \
     set-tab-title \
     set-window-title \
     spawn \
     split-pane \
+    tag-pane \
     zoom-pane \
     ; do
   fname="docs/examples/cmd-synopsis-wezterm-cli-${cmd}--help.txt"
@@ -34,7 +34,9 @@ use termwiz::image::{ImageData, TextureCoordinate};
 use termwiz::surface::{Line, SequenceNo};
 use thiserror::Error;
 use wezterm_term::color::ColorPalette;
-use wezterm_term::{Alert, ClipboardSelection, StableRowIndex, TerminalSize};
+use wezterm_term::{
+    Alert, ClipboardSelection, CommandRecord, StableRowIndex, TerminalSize, TerminalSnapshot,
+};
 
 #[derive(Error, Debug)]
 #[error("Corrupt Response")]
@@ -375,6 +377,33 @@ macro_rules! pdu {
                 }
             }
 
+            /// Constructs the Pdu whose name, as returned by `pdu_name`,
+            /// is `name`, deserializing its payload from `de`.
+            /// This allows Pdus to be expressed in formats other than
+            /// the wire format, such as JSON.
+            pub fn deserialize_named<'de, D: serde::Deserializer<'de>>(
+                name: &str,
+                de: D,
+            ) -> Result<Pdu, D::Error> {
+                match name {
+                    $(
+                        stringify!($name) => Ok(Pdu::$name(Deserialize::deserialize(de)?))
+                    ,)*
+                    _ => Err(serde::de::Error::custom(format!("unknown Pdu {name}"))),
+                }
+            }
+
+            /// Serializes the payload of the Pdu to `ser`; the counterpart
+            /// of `deserialize_named`
+            pub fn serialize_payload<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Pdu::Invalid{..} => Err(serde::ser::Error::custom("attempted to serialize Pdu::Invalid")),
+                    $(
+                        Pdu::$name(s) => s.serialize(ser)
+                    ,)*
+                }
+            }
+
             pub fn decode<R: std::io::Read>(r: R) -> Result<DecodedPdu, Error> {
                 let decoded = decode_raw(r).context("decoding a PDU")?;
                 match decoded.ident {
@@ -431,7 +460,7 @@ macro_rules! pdu {
 /// The overall version of the codec.
 /// This must be bumped when backwards incompatible changes
 /// are made to the types and protocol.
-pub const CODEC_VERSION: usize = 42;
+pub const CODEC_VERSION: usize = 49;
 
 // Defines the Pdu enum.
 // Each struct has an explicit identifying number.
@@ -492,6 +521,22 @@ pdu! {
     GetPaneDirection: 60,
     GetPaneDirectionResponse: 61,
     AdjustPaneSize: 62,
+    SetLogLevel: 63,
+    SetLogLevelResponse: 64,
+    GetPaneSnapshot: 65,
+    GetPaneSnapshotResponse: 66,
+    CapturePane: 67,
+    CapturePaneResponse: 68,
+    GetCommandHistory: 69,
+    GetCommandHistoryResponse: 70,
+    GetWindowConfig: 71,
+    SetWindowConfig: 72,
+    WindowConfigResponse: 73,
+    SetWindowConfigProfile: 74,
+    SendTextToPanes: 75,
+    SendTextToPanesResponse: 76,
+    PaneTagsChanged: 77,
+    SetUserVar: 78,
 }
 
 impl Pdu {
@@ -504,6 +549,7 @@ impl Pdu {
             | Self::SendKeyDown(_)
             | Self::SendMouseEvent(_)
             | Self::SendPaste(_)
+            | Self::SendTextToPanes(_)
             | Self::Resize(_)
             | Self::SetClipboard(_)
             | Self::SetPaneZoomed(_)
@@ -585,6 +631,7 @@ impl Pdu {
             | Pdu::NotifyAlert(NotifyAlert { pane_id, .. })
             | Pdu::SetClipboard(SetClipboard { pane_id, .. })
             | Pdu::PaneFocused(PaneFocused { pane_id })
+            | Pdu::PaneTagsChanged(PaneTagsChanged { pane_id, .. })
             | Pdu::PaneRemoved(PaneRemoved { pane_id }) => Some(*pane_id),
             _ => None,
         }
@@ -703,6 +750,8 @@ pub struct WriteToPane {
 pub struct SendPaste {
     pub pane_id: PaneId,
     pub data: String,
+    /// Toggles the slow_paste setting of the pane for this paste
+    pub toggle_slow_paste: bool,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug)]
@@ -865,6 +914,126 @@ pub struct AdjustPaneSize {
     pub amount: usize,
 }
 
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SetLogLevel {
+    /// Log level directives, in `WEZTERM_LOG` syntax
+    pub spec: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SetLogLevelResponse {
+    /// The complete set of directives now in effect
+    pub spec: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct GetPaneSnapshot {
+    pub pane_id: PaneId,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct GetPaneSnapshotResponse {
+    pub snapshot: TerminalSnapshot,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct CapturePane {
+    pub pane_id: PaneId,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct CapturePaneResponse {
+    /// The rendered pane, encoded as PNG
+    pub png: Vec<u8>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct GetCommandHistory {
+    pub pane_id: PaneId,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct GetCommandHistoryResponse {
+    pub commands: Vec<CommandRecord>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct GetWindowConfig {
+    pub window_id: WindowId,
+    pub key: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SetWindowConfig {
+    pub window_id: WindowId,
+    pub key: String,
+    /// The new value, either as JSON or as a plain string.
+    /// None removes the override.
+    pub value: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SetWindowConfigProfile {
+    pub window_id: WindowId,
+    /// The name of the profile to activate.
+    /// None deactivates the active profile.
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct WindowConfigResponse {
+    /// The effective value of the option, formatted as JSON
+    pub value: String,
+}
+
+/// Sends the same text to several panes
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SendTextToPanes {
+    /// Only the panes of the domains whose names match this pattern,
+    /// in which `*` matches any text and `?` any character, ignoring
+    /// case.  None matches all panes.
+    pub match_domain: Option<String>,
+    /// Only the panes that have this tag
+    pub match_tag: Option<String>,
+    /// Of the panes that match, only these, such as the ones that the
+    /// user confirmed after a dry run
+    pub pane_ids: Option<Vec<PaneId>>,
+    pub data: String,
+    /// Send the text as a bracketed paste, rather than as typed input
+    pub paste: bool,
+    /// Only report the panes that match, without sending anything
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SendTextToPanesResponse {
+    /// The panes that the text was sent to, or would be sent to
+    pub panes: Vec<MatchedPane>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct MatchedPane {
+    pub pane_id: PaneId,
+    pub domain_name: String,
+    pub title: String,
+}
+
+/// Replaces the tags of a pane.  This is sent by clients to change the
+/// tags, and by the server when they have changed.
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct PaneTagsChanged {
+    pub pane_id: PaneId,
+    pub tags: Vec<String>,
+}
+
+/// Sets a user var of a pane, as though the process in it had set it
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct SetUserVar {
+    pub pane_id: PaneId,
+    pub name: String,
+    pub value: String,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug)]
 pub struct GetPaneDirectionResponse {
     pub pane_id: Option<PaneId>,
@@ -1225,6 +1394,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_pdu_set_user_var() {
+        let pdu = || {
+            Pdu::SetUserVar(SetUserVar {
+                pane_id: 3,
+                name: "OUTPUT".to_string(),
+                value: "total 0\n".to_string(),
+            })
+        };
+        let mut encoded = Vec::new();
+        pdu().encode(&mut encoded, 0x43).unwrap();
+        assert_eq!(
+            DecodedPdu {
+                serial: 0x43,
+                pdu: pdu()
+            },
+            Pdu::decode(encoded.as_slice()).unwrap()
+        );
+    }
+
     #[test]
     fn test_pdu_pong() {
         let mut encoded = Vec::new();
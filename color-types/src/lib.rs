@@ -891,11 +891,90 @@ impl LinearRgba {
             self.3,
         )
     }
+
+    /// Returns the relative luminance of the color, as defined by
+    /// <https://www.w3.org/TR/WCAG21/#dfn-relative-luminance>
+    pub fn relative_luminance(self) -> f32 {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
+
+    /// Returns the contrast ratio between two colors, as defined by
+    /// <https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio>.
+    /// The result ranges from 1.0 (no contrast) to 21.0 (black and white).
+    pub fn wcag_contrast_ratio(self, other: Self) -> f32 {
+        let a = self.relative_luminance() + 0.05;
+        let b = other.relative_luminance() + 0.05;
+        if a > b {
+            a / b
+        } else {
+            b / a
+        }
+    }
+
+    /// Returns self, when used as a foreground color, adjusted
+    /// towards either black or white by the smallest amount that
+    /// yields a contrast ratio of at least `min_ratio` against `bg`.
+    /// If that ratio cannot be achieved, returns whichever of black
+    /// or white has the greatest contrast with `bg`.
+    pub fn ensure_contrast(self, bg: Self, min_ratio: f32) -> Self {
+        if self.wcag_contrast_ratio(bg) >= min_ratio {
+            return self;
+        }
+
+        let black = Self(0., 0., 0., self.3);
+        let white = Self(1., 1., 1., self.3);
+        let target = if white.wcag_contrast_ratio(bg) > black.wcag_contrast_ratio(bg) {
+            white
+        } else {
+            black
+        };
+        if target.wcag_contrast_ratio(bg) <= min_ratio {
+            return target;
+        }
+
+        let mix = |k: f32| {
+            Self(
+                self.0 + (target.0 - self.0) * k,
+                self.1 + (target.1 - self.1) * k,
+                self.2 + (target.2 - self.2) * k,
+                self.3,
+            )
+        };
+
+        let mut lower = 0.0f32;
+        let mut upper = 1.0f32;
+        for _ in 0..16 {
+            let k = (lower + upper) / 2.;
+            if mix(k).wcag_contrast_ratio(bg) >= min_ratio {
+                upper = k;
+            } else {
+                lower = k;
+            }
+        }
+        mix(upper)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn contrast() {
+        let black = LinearRgba::with_srgba(0, 0, 0, 255);
+        let white = LinearRgba::with_srgba(255, 255, 255, 255);
+        assert!((black.wcag_contrast_ratio(white) - 21.).abs() < 0.01);
+        assert_eq!(white.ensure_contrast(black, 4.5), white);
+
+        let navy = LinearRgba::with_srgba(0, 0, 0x80, 255);
+        assert!(navy.wcag_contrast_ratio(black) < 4.5);
+        let adjusted = navy.ensure_contrast(black, 4.5);
+        assert!(adjusted.wcag_contrast_ratio(black) >= 4.5);
+        assert!(adjusted.wcag_contrast_ratio(black) < 4.6);
+
+        // Impossible to satisfy, so we get the best that we can do
+        assert_eq!(navy.ensure_contrast(black, 30.), white);
+    }
     #[test]
     fn named_rgb() {
         let dark_green = SrgbaTuple::from_named("DarkGreen").unwrap();
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+use std::path::Path;
+
+/// Extracts the tags from the frontmatter of a doc page
+fn parse_tags(doc: &str) -> Vec<String> {
+    let mut tags = vec![];
+    let mut lines = doc.lines();
+    if lines.next() != Some("---") {
+        return tags;
+    }
+    let mut in_tags = false;
+    for line in lines {
+        if line == "---" {
+            break;
+        }
+        if line.starts_with("tags:") {
+            in_tags = true;
+        } else if let Some(tag) = line.trim().strip_prefix("- ") {
+            if in_tags {
+                tags.push(tag.trim().to_string());
+            }
+        } else {
+            in_tags = false;
+        }
+    }
+    tags
+}
+
+/// Extracts the version from the `{{since('...')}}` that immediately
+/// follows the heading of a doc page, which records when the option
+/// was introduced.  Later mentions describe changes to the option.
+fn parse_since(doc: &str) -> Option<String> {
+    let line = doc
+        .lines()
+        .skip_while(|line| !line.starts_with('#'))
+        .skip(1)
+        .find(|line| !line.trim().is_empty())?;
+    let version = line.trim().strip_prefix("{{since('")?;
+    let len = version.find('\'')?;
+    Some(version[..len].to_string())
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // The docs for each config option record the version in which it
+    // was introduced, and tags that categorize it; gather those up so
+    // that they can be included in the config schema.
+    // When building from a source package without the docs, the
+    // schema simply omits this information.
+    let docs_dir = Path::new("../docs/config/lua/config");
+    let mut entries = vec![];
+    if let Ok(dir) = std::fs::read_dir(docs_dir) {
+        println!("cargo:rerun-if-changed={}", docs_dir.display());
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if let Ok(doc) = std::fs::read_to_string(&path) {
+                entries.push((name, parse_since(&doc), parse_tags(&doc)));
+            }
+        }
+    }
+    entries.sort();
+
+    let mut code = String::new();
+    code.push_str("static OPTION_DOCS: &[OptionDocs] = &[\n");
+    for (name, since, tags) in entries {
+        writeln!(
+            code,
+            "    OptionDocs {{ name: {name:?}, since: {since:?}, tags: &{tags:?} }},"
+        )
+        .unwrap();
+    }
+    code.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(Path::new(&out_dir).join("option_docs.rs"), code).unwrap();
+}
@@ -0,0 +1,96 @@
+use crate::bell::EasingFunction;
+use crate::config::Config;
+use std::collections::BTreeMap;
+use wezterm_dynamic::{FromDynamic, ToDynamic, Value};
+
+/// Controls whether an accessibility mode is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum AccessibilityMode {
+    /// Follow the corresponding setting in the operating system
+    FollowSystem,
+    Always,
+    Never,
+}
+
+impl Default for AccessibilityMode {
+    fn default() -> Self {
+        Self::FollowSystem
+    }
+}
+
+impl AccessibilityMode {
+    /// Resolve whether the mode is active, given the
+    /// current state of the system setting
+    pub fn is_enabled(self, system_setting: bool) -> bool {
+        match self {
+            Self::FollowSystem => system_setting,
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// Returns config overrides that stop the cursor and text from blinking,
+/// and that replace the fading of the visual bell, the easing of blinking
+/// and the opacity animation with instantaneous transitions
+pub fn reduced_motion_overrides(config: &Config) -> Value {
+    Value::Object(reduced_motion_map(config).into())
+}
+
+pub(crate) fn reduced_motion_map(config: &Config) -> BTreeMap<Value, Value> {
+    let mut map = BTreeMap::new();
+    for key in [
+        "cursor_blink_ease_in",
+        "cursor_blink_ease_out",
+        "text_blink_ease_in",
+        "text_blink_ease_out",
+        "text_blink_rapid_ease_in",
+        "text_blink_rapid_ease_out",
+    ] {
+        map.insert(
+            Value::String(key.to_string()),
+            EasingFunction::Constant.to_dynamic(),
+        );
+    }
+
+    for key in [
+        "cursor_blink_rate",
+        "text_blink_rate",
+        "text_blink_rate_rapid",
+        "opacity_animation_duration_ms",
+    ] {
+        map.insert(Value::String(key.to_string()), 0u64.to_dynamic());
+    }
+
+    let mut visual_bell = config.visual_bell.clone();
+    visual_bell.fade_in_function = EasingFunction::Constant;
+    visual_bell.fade_out_function = EasingFunction::Constant;
+    map.insert(
+        Value::String("visual_bell".to_string()),
+        visual_bell.to_dynamic(),
+    );
+
+    map
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reduced_motion_stops_blinking() {
+        let map = reduced_motion_map(&Config::default_config());
+        for key in [
+            "cursor_blink_rate",
+            "text_blink_rate",
+            "text_blink_rate_rapid",
+            "opacity_animation_duration_ms",
+        ] {
+            assert_eq!(
+                map.get(&Value::String(key.to_string())),
+                Some(&Value::U64(0)),
+                "{key}"
+            );
+        }
+    }
+}
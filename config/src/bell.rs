@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 
 /// <https://developer.mozilla.org/en-US/docs/Web/CSS/easing-function>
@@ -81,3 +82,29 @@ impl Default for AudibleBell {
         Self::SystemBeep
     }
 }
+
+/// A sound file that is played in place of the system beep when
+/// the bell rings in a pane that matches `domain` and `workspace`.
+/// Fields that are not set match any pane.
+#[derive(Debug, Clone, FromDynamic, ToDynamic)]
+pub struct BellSound {
+    pub path: PathBuf,
+    /// The volume in the range 0.0 to 1.0
+    #[dynamic(default = "default_volume")]
+    pub volume: f32,
+    #[dynamic(default)]
+    pub domain: Option<String>,
+    #[dynamic(default)]
+    pub workspace: Option<String>,
+}
+
+impl BellSound {
+    pub fn matches(&self, domain: &str, workspace: &str) -> bool {
+        self.domain.as_deref().map_or(true, |d| d == domain)
+            && self.workspace.as_deref().map_or(true, |w| w == workspace)
+    }
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
@@ -1,21 +1,26 @@
+use crate::accessibility::AccessibilityMode;
 use crate::background::{BackgroundLayer, Gradient};
-use crate::bell::{AudibleBell, EasingFunction, VisualBell};
+use crate::bell::{AudibleBell, BellSound, EasingFunction, VisualBell};
 use crate::color::{
     ColorSchemeFile, HsbTransform, Palette, SrgbaTuple, TabBarStyle, WindowFrameConfig,
 };
 use crate::daemon::DaemonOptions;
+use crate::encoding::CharacterEncoding;
 use crate::exec_domain::ExecDomain;
 use crate::font::{
     AllowSquareGlyphOverflow, DisplayPixelGeometry, FontLocatorSelection, FontRasterizerSelection,
-    FontShaperSelection, FreeTypeLoadFlags, FreeTypeLoadTarget, StyleRule, TextStyle,
+    FontShaperSelection, FreeTypeLoadFlags, FreeTypeLoadTarget, NerdFontMapping, StyleRule,
+    TextStyle,
 };
 use crate::frontend::FrontEndSelection;
 use crate::keyassignment::{
-    KeyAssignment, KeyTable, KeyTableEntry, KeyTables, MouseEventTrigger, SpawnCommand,
+    KeyAssignment, KeyTable, KeyTableEntry, KeyTables, MouseEventTrigger, QuickSelectCommand,
+    SpawnCommand,
 };
-use crate::keys::{Key, LeaderKey, Mouse};
+use crate::keys::{Key, KeyPreset, LeaderKey, Mouse};
 use crate::lua::make_lua_context;
-use crate::ssh::{SshBackend, SshDomain};
+use crate::power::PowerSavingMode;
+use crate::ssh::{SshBackend, SshDomain, SshHostProfile};
 use crate::tls::{TlsDomainClient, TlsDomainServer};
 use crate::units::Dimension;
 use crate::unix::UnixDomain;
@@ -23,15 +28,15 @@ use crate::wsl::WslDomain;
 use crate::{
     default_config_with_overrides_applied, default_one_point_oh, default_one_point_oh_f64,
     default_true, default_win32_acrylic_accent_color, GpuInfo, IntegratedTitleButtonColor,
-    KeyMapPreference, LoadedConfig, MouseEventTriggerMods, RgbaColor, SerialDomain, SystemBackdrop,
-    WebGpuPowerPreference, CONFIG_DIRS, CONFIG_FILE_OVERRIDE, CONFIG_OVERRIDES, CONFIG_SKIP,
-    HOME_DIR,
+    KeyMapPreference, LoadedConfig, MouseEventTriggerMods, RgbaColor, SerialDomain, Service,
+    SystemBackdrop, WebGpuPowerPreference, CONFIG_DIRS, CONFIG_FILE_OVERRIDE, CONFIG_OVERRIDES,
+    CONFIG_SKIP, HOME_DIR,
 };
 use anyhow::Context;
 use luahelper::impl_lua_conversion_dynamic;
 use mlua::FromLua;
 use portable_pty::CommandBuilder;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -48,6 +53,10 @@ use wezterm_input_types::{
 };
 use wezterm_term::TerminalSize;
 
+/// The name under which the color scheme derived from the background
+/// image is registered, when `color_scheme_from_background_image` is set
+pub const BACKGROUND_IMAGE_SCHEME_NAME: &str = "Background Image (generated)";
+
 #[derive(Debug, Clone, FromDynamic, ToDynamic, ConfigMeta)]
 pub struct Config {
     /// The font size, measured in points
@@ -139,6 +148,36 @@ pub struct Config {
     #[dynamic(default)]
     pub prefer_to_spawn_tabs: bool,
 
+    /// When true, launching wezterm while a GUI instance is already
+    /// running asks that instance to spawn the program, rather than
+    /// starting another GUI process.
+    #[dynamic(default = "default_true")]
+    pub gui_single_instance: bool,
+
+    /// Rules that customize the windows of GUI processes that
+    /// were started with a particular window class.
+    /// The first rule whose class matches is used.
+    #[dynamic(default)]
+    pub window_rules: Vec<crate::window::WindowRule>,
+
+    /// Named bundles of config overrides, such as "demo" or
+    /// "low-power", that can be switched on for a window at runtime
+    /// via `ActivateConfigProfile` or `wezterm cli set-config-profile`
+    #[dynamic(default)]
+    pub profiles: HashMap<String, wezterm_dynamic::Value>,
+
+    /// If set, windows that have not received input for a while are
+    /// obscured and ignore input until the user authenticates again
+    #[dynamic(default)]
+    pub idle_lock: Option<crate::window::IdleLock>,
+
+    /// When set to the name of one of the unix_domains, every
+    /// invocation of the GUI that doesn't explicitly specify a domain
+    /// connects to that domain, starting its mux server if needed,
+    /// so that all GUI processes share the same tabs and workspaces.
+    #[dynamic(default)]
+    pub gui_shared_mux_domain: Option<String>,
+
     #[dynamic(default)]
     pub window_frame: WindowFrameConfig,
 
@@ -170,6 +209,32 @@ pub struct Config {
     #[dynamic(default = "default_pane_select_bg_color")]
     pub pane_select_bg_color: RgbaColor,
 
+    /// Whether to show recently pressed keys, and the actions
+    /// that they triggered, in an overlay
+    #[dynamic(default)]
+    pub show_key_display: bool,
+
+    #[dynamic(default = "default_key_display_font_size")]
+    pub key_display_font_size: f64,
+
+    #[dynamic(default = "default_key_display_fg_color")]
+    pub key_display_fg_color: RgbaColor,
+
+    #[dynamic(default = "default_key_display_bg_color")]
+    pub key_display_bg_color: RgbaColor,
+
+    /// How long each key press remains visible in the key display
+    #[dynamic(default = "default_key_display_duration_ms")]
+    pub key_display_duration_ms: u64,
+
+    /// How much to scale the font size when
+    /// `TogglePresentationMode` is activated
+    #[dynamic(
+        default = "default_presentation_mode_font_scale",
+        validate = "validate_presentation_mode_font_scale"
+    )]
+    pub presentation_mode_font_scale: f64,
+
     #[dynamic(default)]
     pub tab_bar_style: TabBarStyle,
 
@@ -188,6 +253,12 @@ pub struct Config {
     #[dynamic(default = "default_scrollback_lines")]
     pub scrollback_lines: usize,
 
+    /// When true, the contents of the alternate screen are kept in
+    /// the scrollback once the application using it switches back
+    /// to the primary screen
+    #[dynamic(default)]
+    pub alternate_screen_capture: bool,
+
     /// If no `prog` is specified on the command line, use this
     /// instead of running the user's shell.
     /// For example, to have `wezterm` always run `top` by default,
@@ -202,6 +273,11 @@ pub struct Config {
     /// as the positional arguments to that command.
     pub default_prog: Option<Vec<String>>,
 
+    /// Whether the shell is run as a login shell when no program
+    /// is specified.  Domains can override this.
+    #[dynamic(default = "default_true")]
+    pub login_shell: bool,
+
     #[dynamic(default = "default_gui_startup_args")]
     pub default_gui_startup_args: Vec<String>,
 
@@ -210,6 +286,11 @@ pub struct Config {
     /// info!)
     pub default_cwd: Option<PathBuf>,
 
+    /// The character encoding used to exchange data with the programs
+    /// running in new panes.  Serial domains can override this.
+    #[dynamic(default)]
+    pub default_pane_encoding: CharacterEncoding,
+
     #[dynamic(default)]
     pub exit_behavior: ExitBehavior,
 
@@ -343,16 +424,31 @@ pub struct Config {
     #[dynamic(default)]
     pub serial_ports: Vec<SerialDomain>,
 
+    /// Background programs that the GUI keeps running
+    #[dynamic(default)]
+    pub services: Vec<Service>,
+
     /// The set of unix domains
     #[dynamic(default = "UnixDomain::default_unix_domains")]
     pub unix_domains: Vec<UnixDomain>,
 
+    /// When set, the GUI and the mux server listen for JSON-RPC
+    /// requests on a unix domain socket at this path, so that
+    /// external tools can control wezterm and subscribe to events
+    #[dynamic(default)]
+    pub rpc_socket_path: Option<PathBuf>,
+
     #[dynamic(default)]
     pub ssh_domains: Option<Vec<SshDomain>>,
 
     #[dynamic(default)]
     pub ssh_backend: SshBackend,
 
+    /// Config overrides that are applied to a window while its
+    /// active pane is connected to a matching ssh host
+    #[dynamic(default)]
+    pub ssh_host_profiles: Vec<SshHostProfile>,
+
     /// When running in server mode, defines configuration for
     /// each of the endpoints that we'll listen for connections
     #[dynamic(default)]
@@ -395,6 +491,21 @@ pub struct Config {
     #[dynamic(default = "default_bypass_mouse_reporting_modifiers")]
     pub bypass_mouse_reporting_modifiers: Modifiers,
 
+    /// Mouse reporting is disabled while the foreground process
+    /// of a pane has one of these names
+    #[dynamic(default)]
+    pub disable_mouse_reporting_for_processes_named: Vec<String>,
+
+    /// While exactly these modifiers are held down, the view of the
+    /// active pane is frozen rather than following its output
+    #[dynamic(default)]
+    pub pause_output_scrolling_mods: Modifiers,
+
+    /// Holding down these modifiers while pasting toggles whether
+    /// `slow_paste` is used for that paste
+    #[dynamic(default)]
+    pub slow_paste_toggle_mods: Modifiers,
+
     #[dynamic(default)]
     pub debug_key_events: bool,
 
@@ -404,11 +515,15 @@ pub struct Config {
     #[dynamic(default)]
     pub disable_default_key_bindings: bool,
     pub leader: Option<LeaderKey>,
+    #[dynamic(default)]
+    pub key_preset: Option<KeyPreset>,
 
     #[dynamic(default)]
     pub disable_default_quick_select_patterns: bool,
     #[dynamic(default)]
     pub quick_select_patterns: Vec<String>,
+    #[dynamic(default)]
+    pub quick_select_commands: Vec<QuickSelectCommand>,
     #[dynamic(default = "default_alphabet")]
     pub quick_select_alphabet: String,
 
@@ -504,6 +619,23 @@ pub struct Config {
     pub custom_block_glyphs: bool,
     #[dynamic(default = "default_true")]
     pub anti_alias_custom_block_glyphs: bool,
+    /// Ranges of codepoints that are rendered using the font,
+    /// even though custom_block_glyphs is enabled
+    #[dynamic(default)]
+    pub custom_block_glyph_exclusions: Vec<CodepointRange>,
+    /// Scales the thickness of the lines of the box drawing
+    /// glyphs drawn by wezterm
+    #[dynamic(default = "default_one_point_oh_f64")]
+    pub custom_block_glyph_line_weight: f64,
+    /// Whether to align the lines of the box drawing glyphs
+    /// drawn by wezterm to whole pixels
+    #[dynamic(default)]
+    pub custom_block_glyph_pixel_snapping: bool,
+
+    /// How to translate Nerd Font symbols that moved between
+    /// Nerd Fonts v2 and v3
+    #[dynamic(default)]
+    pub nerd_font_mapping: NerdFontMapping,
 
     /// Controls the amount of padding to use around the terminal cell area
     #[dynamic(default)]
@@ -518,6 +650,9 @@ pub struct Config {
     /// The image will be scaled to fit the window.
     #[dynamic(default)]
     pub window_background_image: Option<PathBuf>,
+    /// When true, the colors are derived from window_background_image
+    #[dynamic(default)]
+    pub color_scheme_from_background_image: bool,
     #[dynamic(default)]
     pub window_background_gradient: Option<Gradient>,
     #[dynamic(default)]
@@ -552,6 +687,15 @@ pub struct Config {
     #[dynamic(default = "default_one_point_oh")]
     pub window_background_opacity: f32,
 
+    /// How long it takes to transition between opacity values
+    /// when the opacity of a window is changed at runtime, for
+    /// example, by the AdjustOpacity action.
+    /// Set to 0 to change the opacity immediately.
+    #[dynamic(default = "default_opacity_animation_duration_ms")]
+    pub opacity_animation_duration_ms: u64,
+    #[dynamic(default = "ease_out")]
+    pub opacity_animation_function: EasingFunction,
+
     /// inactive_pane_hue, inactive_pane_saturation and
     /// inactive_pane_brightness allow for transforming the color
     /// of inactive panes.
@@ -584,9 +728,30 @@ pub struct Config {
     #[dynamic(default = "default_inactive_pane_hsb")]
     pub inactive_pane_hsb: HsbTransform,
 
+    /// Multiplies the window_background_opacity while the window
+    /// doesn't have keyboard focus.  The default of 1.0 leaves the
+    /// opacity unchanged.
+    #[dynamic(default = "default_one_point_oh")]
+    pub inactive_window_opacity: f32,
+
+    /// Specifies the alternative HSB transform to apply to the whole
+    /// window while it doesn't have keyboard focus.  The default of
+    /// 1.0 for each component leaves the colors unchanged.
+    #[dynamic(default)]
+    pub inactive_window_hsb: HsbTransform,
+
     #[dynamic(default = "default_one_point_oh")]
     pub text_background_opacity: f32,
 
+    /// Text whose contrast ratio with its background is lower than
+    /// this value will have its foreground color adjusted until it
+    /// reaches this ratio.  The default of 1.0 disables adjustment.
+    #[dynamic(
+        default = "default_one_point_oh",
+        validate = "validate_minimum_contrast_ratio"
+    )]
+    pub minimum_contrast_ratio: f32,
+
     /// Specifies how often a blinking cursor transitions between visible
     /// and invisible, expressed in milliseconds.
     /// Setting this to 0 disables blinking.
@@ -603,6 +768,16 @@ pub struct Config {
     #[dynamic(default = "default_anim_fps")]
     pub animation_fps: u8,
 
+    /// Whether to replace animated transitions, such as the
+    /// cursor blink easing, with instantaneous ones
+    #[dynamic(default)]
+    pub reduce_motion: AccessibilityMode,
+
+    /// Whether to adjust text colors that have poor
+    /// contrast with their background
+    #[dynamic(default)]
+    pub high_contrast: AccessibilityMode,
+
     #[dynamic(default)]
     pub force_reverse_video_cursor: bool,
 
@@ -696,6 +871,14 @@ pub struct Config {
     #[dynamic(default = "default_update_interval")]
     pub check_for_updates_interval_seconds: u64,
 
+    #[dynamic(default)]
+    pub update_channel: UpdateChannel,
+
+    /// When true, panics and GPU failures write a crash report
+    /// to the local data directory
+    #[dynamic(default)]
+    pub enable_crash_reports: bool,
+
     /// When set to true, use the CSI-U encoding scheme as described
     /// in http://www.leonerd.org.uk/hacks/fixterms/
     /// This is off by default because @wez and @jsgf find the shift-space
@@ -712,9 +895,54 @@ pub struct Config {
     #[dynamic(default = "default_word_boundary")]
     pub selection_word_boundary: String,
 
+    /// Replaces `selection_word_boundary` while the foreground process
+    /// of a pane has the name, or path, of one of the keys
+    #[dynamic(default)]
+    pub selection_word_boundary_by_process: HashMap<String, String>,
+
+    /// The string sent in response to ENQ.  Serial and ssh domains
+    /// can override it with their `answerback` field.
     #[dynamic(default = "default_enq_answerback")]
     pub enq_answerback: String,
 
+    /// When true, replies to the application use 8-bit C1 controls
+    /// (eg: 0x9b rather than ESC [) until it selects 7-bit controls
+    #[dynamic(default)]
+    pub send_8bit_c1_controls: bool,
+
+    /// Where the print jobs of the media copy escape sequences are sent
+    /// when the `print-requested` event doesn't handle them
+    #[dynamic(default)]
+    pub print_destination: PrintDestination,
+
+    /// When set, pastes are sent to panes in chunks with a delay in
+    /// between rather than all at once.  Serial and ssh domains can
+    /// override this with their own `slow_paste` setting.
+    #[dynamic(default)]
+    pub slow_paste: Option<SlowPaste>,
+
+    /// The command that is run to receive the files when a program in
+    /// a pane, such as `sz`, starts to send them with ZMODEM
+    #[dynamic(default)]
+    pub zmodem_receive_command: Option<Vec<String>>,
+
+    /// The command that is run to send files when a program in a pane,
+    /// such as `rz`, waits to receive them with ZMODEM
+    #[dynamic(default)]
+    pub zmodem_send_command: Option<Vec<String>>,
+
+    /// The directory in which the ZMODEM commands are run, and so
+    /// where the files that are received are saved.  Defaults to the
+    /// home directory.
+    #[dynamic(default)]
+    pub zmodem_download_dir: Option<String>,
+
+    /// The ZMODEM command is stopped, and the transfer cancelled, once
+    /// no data has been exchanged with the remote end for this many
+    /// seconds.  0 disables the timeout.
+    #[dynamic(default = "default_zmodem_idle_timeout_seconds")]
+    pub zmodem_idle_timeout_seconds: u64,
+
     #[dynamic(default)]
     pub adjust_window_size_when_changing_font_size: Option<bool>,
 
@@ -775,6 +1003,14 @@ pub struct Config {
     #[dynamic(default = "default_max_fps")]
     pub max_fps: u8,
 
+    /// When to switch to a rendering profile that uses less power
+    #[dynamic(default)]
+    pub power_saving: PowerSavingMode,
+
+    /// The max_fps that applies while power saving is active
+    #[dynamic(default = "default_power_saving_max_fps")]
+    pub power_saving_max_fps: u8,
+
     #[dynamic(default = "default_shape_cache_size")]
     pub shape_cache_size: usize,
     #[dynamic(default = "default_line_state_cache_size")]
@@ -792,6 +1028,24 @@ pub struct Config {
     #[dynamic(default)]
     pub audible_bell: AudibleBell,
 
+    /// The names of the domains whose panes don't sound the
+    /// audible_bell, although the visual_bell and the bell event
+    /// still apply to them
+    #[dynamic(default)]
+    pub audible_bell_muted_domains: Vec<String>,
+
+    /// Sound files to play rather than the system beep, selected
+    /// by the domain and workspace of the pane in which the bell rang
+    #[dynamic(default)]
+    pub audible_bell_sounds: Vec<BellSound>,
+
+    /// Text that is shown before the title of a tab in the tab bar
+    /// when the bell rang in one of its panes while the tab wasn't
+    /// active.  It is cleared when the tab is activated.
+    /// An empty string disables the badge.
+    #[dynamic(default = "default_tab_bell_badge")]
+    pub tab_bell_badge: String,
+
     #[dynamic(default)]
     pub canonicalize_pasted_newlines: Option<NewlineCanon>,
 
@@ -804,6 +1058,36 @@ pub struct Config {
     #[dynamic(default = "default_true")]
     pub allow_download_protocols: bool,
 
+    /// Restricts the escape sequences that programs in particular
+    /// domains or on particular hosts may use to reach outside of
+    /// the terminal
+    #[dynamic(default)]
+    pub escape_sequence_policy: Vec<EscapeSequencePolicy>,
+
+    /// Prefixes the titles that are set by programs on other hosts
+    /// with the name of their domain or host
+    #[dynamic(default)]
+    pub label_remote_titles: bool,
+
+    /// How to highlight characters that can disguise what the text
+    /// in a pane says, such as bidi overrides and zero-width spaces
+    #[dynamic(default)]
+    pub suspicious_character_highlight: SuspiciousCharacterHighlight,
+
+    /// Renders non-breaking spaces, soft hyphens and zero-width
+    /// characters as dim pictures
+    #[dynamic(default)]
+    pub show_invisible_characters: bool,
+
+    /// Marks trailing spaces, tabs and carriage returns that aren't
+    /// followed by a line feed in the output of panes
+    #[dynamic(default)]
+    pub show_whitespace: bool,
+
+    /// Downloads larger than this number of bytes are discarded
+    #[dynamic(default = "default_max_download_size")]
+    pub max_download_size: usize,
+
     #[dynamic(default = "default_true")]
     pub allow_win32_input_mode: bool,
 
@@ -883,6 +1167,12 @@ impl Config {
         }
     }
 
+    /// Returns the first entry in `ssh_host_profiles` that
+    /// matches the specified host
+    pub fn ssh_host_profile(&self, host: &str) -> Option<&SshHostProfile> {
+        self.ssh_host_profiles.iter().find(|p| p.matches(host))
+    }
+
     pub fn wsl_domains(&self) -> Vec<WslDomain> {
         if let Some(domains) = &self.wsl_domains {
             domains.clone()
@@ -1089,7 +1379,15 @@ impl Config {
                 }
                 Ok(cfg)
             });
-        let cfg = config?;
+
+        // Point at the place in the file where the option that
+        // a problem relates to was set
+        let annotate = |message: String| match crate::meta::locate_config_message(&message, p, &s) {
+            Some(location) => format!("{message}\n{location}"),
+            None => message,
+        };
+        let warnings = warnings.into_iter().map(&annotate).collect();
+        let cfg = config.map_err(|err| anyhow::anyhow!(annotate(format!("{err:#}"))))?;
 
         Ok(Some(LoadedConfig {
             config: Ok(cfg.compute_extra_defaults(Some(p))),
@@ -1170,6 +1468,24 @@ impl Config {
     /// Check for logical conflicts in the config
     pub fn check_consistency(&self) -> anyhow::Result<()> {
         self.check_domain_consistency()?;
+        self.check_service_consistency()?;
+        Ok(())
+    }
+
+    fn check_service_consistency(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        for service in &self.services {
+            if !names.insert(service.name.as_str()) {
+                anyhow::bail!(
+                    "service with name \"{}\" conflicts with another \
+                     service with the same name",
+                    service.name
+                );
+            }
+            if service.args.is_empty() {
+                anyhow::bail!("service \"{}\" has no args to run", service.name);
+            }
+        }
         Ok(())
     }
 
@@ -1206,6 +1522,15 @@ impl Config {
         for d in &self.tls_clients {
             check_domain(&d.name, "tls domain")?;
         }
+
+        if let Some(name) = &self.gui_shared_mux_domain {
+            if !self.unix_domains.iter().any(|d| &d.name == name) {
+                anyhow::bail!(
+                    "gui_shared_mux_domain \"{name}\" does not match \
+                     the name of any of the configured unix_domains"
+                );
+            }
+        }
         Ok(())
     }
 
@@ -1213,6 +1538,20 @@ impl Config {
         Self::default().compute_extra_defaults(None)
     }
 
+    /// Returns the window rule that applies to windows with the
+    /// specified class, if any
+    pub fn window_rule_for_class(&self, class: &str) -> Option<&crate::window::WindowRule> {
+        self.window_rules.iter().find(|rule| rule.class == class)
+    }
+
+    /// Returns the first of the audible_bell_sounds that applies to
+    /// a pane in the specified domain and workspace, if any
+    pub fn bell_sound_for(&self, domain: &str, workspace: &str) -> Option<&BellSound> {
+        self.audible_bell_sounds
+            .iter()
+            .find(|sound| sound.matches(domain, workspace))
+    }
+
     pub fn key_bindings(&self) -> KeyTables {
         let mut tables = KeyTables::default();
 
@@ -1349,6 +1688,25 @@ impl Config {
             }
         }
 
+        if cfg.color_scheme_from_background_image {
+            if let Some(path) = &cfg.window_background_image {
+                match crate::palette_from_image(path) {
+                    Ok(palette) => {
+                        cfg.resolved_palette = cfg.resolved_palette.overlay_with(&palette);
+                        cfg.color_schemes
+                            .insert(BACKGROUND_IMAGE_SCHEME_NAME.to_string(), palette);
+                    }
+                    Err(err) => {
+                        log::error!(
+                            "Failed to derive a color scheme from {}: {:#}",
+                            path.display(),
+                            err
+                        );
+                    }
+                }
+            }
+        }
+
         if let Some(colors) = &cfg.colors {
             cfg.resolved_palette = cfg.resolved_palette.overlay_with(colors);
         }
@@ -1378,17 +1736,20 @@ impl Config {
 
     fn load_color_schemes(&mut self, paths: &[PathBuf]) -> anyhow::Result<()> {
         fn extract_scheme_name(name: &str) -> Option<&str> {
-            if name.ends_with(".toml") {
-                let len = name.len();
-                Some(&name[..len - 5])
-            } else {
-                None
-            }
+            // TOML is our native format; base16/base24 YAML and kitty
+            // themes are converted as they are loaded
+            [".toml", ".yaml", ".yml", ".conf"]
+                .iter()
+                .find_map(|ext| name.strip_suffix(ext))
         }
 
         fn load_scheme(path: &Path) -> anyhow::Result<ColorSchemeFile> {
-            let s = std::fs::read_to_string(path)?;
-            ColorSchemeFile::from_toml_str(&s).context("parsing TOML")
+            if path.extension().map_or(false, |ext| ext == "toml") {
+                let s = std::fs::read_to_string(path)?;
+                return ColorSchemeFile::from_toml_str(&s).context("parsing TOML");
+            }
+            crate::import_color_scheme(path)?
+                .ok_or_else(|| anyhow::anyhow!("unsupported color scheme format"))
         }
 
         for colors_dir in paths {
@@ -1562,6 +1923,44 @@ fn default_pane_select_font_size() -> f64 {
     36.0
 }
 
+fn default_key_display_font_size() -> f64 {
+    24.0
+}
+
+fn default_key_display_fg_color() -> RgbaColor {
+    SrgbaTuple(0.95, 0.95, 0.95, 1.0).into()
+}
+
+fn default_key_display_bg_color() -> RgbaColor {
+    SrgbaTuple(0., 0., 0., 0.75).into()
+}
+
+fn default_zmodem_idle_timeout_seconds() -> u64 {
+    60
+}
+
+fn default_key_display_duration_ms() -> u64 {
+    3000
+}
+
+fn default_opacity_animation_duration_ms() -> u64 {
+    150
+}
+
+fn default_presentation_mode_font_scale() -> f64 {
+    1.5
+}
+
+fn validate_presentation_mode_font_scale(value: &f64) -> Result<(), String> {
+    if *value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Illegal value {value} for presentation_mode_font_scale; it must be positive"
+        ))
+    }
+}
+
 fn default_integrated_title_buttons() -> Vec<IntegratedTitleButton> {
     use IntegratedTitleButton::*;
     vec![Hide, Maximize, Close]
@@ -1712,6 +2111,10 @@ fn default_unicode_version() -> u8 {
     9
 }
 
+fn default_max_download_size() -> usize {
+    100 * 1024 * 1024
+}
+
 fn default_mux_env_remove() -> Vec<String> {
     vec![
         "SSH_AUTH_SOCK".to_string(),
@@ -1728,6 +2131,10 @@ fn default_max_fps() -> u8 {
     60
 }
 
+fn default_power_saving_max_fps() -> u8 {
+    20
+}
+
 fn default_tiling_desktop_environments() -> Vec<String> {
     ["X11 LG3D", "X11 bspwm", "X11 i3", "X11 dwm", "X11 awesome"]
         .iter()
@@ -1789,6 +2196,11 @@ fn default_clean_exits() -> Vec<u32> {
     vec![]
 }
 
+fn default_tab_bell_badge() -> String {
+    // md_bell from the nerd font symbols
+    "\u{f009a} ".to_string()
+}
+
 fn default_inactive_pane_hsb() -> HsbTransform {
     HsbTransform {
         brightness: 0.8,
@@ -1828,6 +2240,10 @@ const fn linear_ease() -> EasingFunction {
     EasingFunction::Linear
 }
 
+const fn ease_out() -> EasingFunction {
+    EasingFunction::EaseOut
+}
+
 const fn default_one_cell() -> Dimension {
     Dimension::Cells(1.)
 }
@@ -1859,6 +2275,19 @@ impl Default for WindowPadding {
     }
 }
 
+/// An inclusive range of unicode codepoints
+#[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodepointRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl CodepointRange {
+    pub fn contains(&self, c: char) -> bool {
+        (self.first..=self.last).contains(&(c as u32))
+    }
+}
+
 #[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NewlineCanon {
     // FIXME: also allow deserialziing from bool
@@ -2026,6 +2455,17 @@ pub enum ImePreeditRendering {
     System,
 }
 
+#[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SuspiciousCharacterHighlight {
+    /// Text is rendered as-is
+    #[default]
+    None,
+    /// Suspicious characters are shown with a red curly underline
+    Underline,
+    /// As with Underline, and the cells are also shown in reverse video
+    Reverse,
+}
+
 #[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
 pub enum NotificationHandling {
     #[default]
@@ -2036,6 +2476,58 @@ pub enum NotificationHandling {
     SuppressFromFocusedWindow,
 }
 
+/// Which releases the update checker considers
+#[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateChannel {
+    /// Tagged releases
+    #[default]
+    Stable,
+    /// The nightly builds
+    Nightly,
+}
+
+/// Where the output that programs send to the printer with the
+/// media copy escape sequences ends up
+#[derive(Debug, FromDynamic, ToDynamic, Clone, PartialEq, Eq, Default)]
+pub enum PrintDestination {
+    /// Printing is ignored
+    #[default]
+    Disabled,
+    /// The print jobs are appended to the named file
+    File(String),
+    /// Each print job is piped to the stdin of the command
+    Command(Vec<String>),
+}
+
+/// Controls how a paste is trickled into a pane in chunks, for
+/// devices and programs that drop input that arrives too quickly
+#[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq)]
+pub struct SlowPaste {
+    /// How many characters are sent at a time
+    #[dynamic(default = "default_slow_paste_chunk_size")]
+    pub chunk_size: usize,
+    /// How long to wait between chunks, in milliseconds
+    #[dynamic(default = "default_slow_paste_delay_ms")]
+    pub delay_ms: u64,
+}
+
+impl Default for SlowPaste {
+    fn default() -> Self {
+        Self {
+            chunk_size: default_slow_paste_chunk_size(),
+            delay_ms: default_slow_paste_delay_ms(),
+        }
+    }
+}
+
+fn default_slow_paste_chunk_size() -> usize {
+    32
+}
+
+fn default_slow_paste_delay_ms() -> u64 {
+    50
+}
+
 fn validate_row_or_col(value: &u16) -> Result<(), String> {
     if *value < 1 {
         Err("initial_cols and initial_rows must be non-zero".to_string())
@@ -2044,6 +2536,16 @@ fn validate_row_or_col(value: &u16) -> Result<(), String> {
     }
 }
 
+fn validate_minimum_contrast_ratio(value: &f32) -> Result<(), String> {
+    if !(1.0..=21.0).contains(value) {
+        Err(format!(
+            "Illegal value {value} for minimum_contrast_ratio; it must be between 1.0 and 21.0"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 fn validate_line_height(value: &f64) -> Result<(), String> {
     if *value <= 0.0 {
         Err(format!(
@@ -0,0 +1,168 @@
+use std::convert::TryFrom;
+use wezterm_dynamic::{FromDynamic, ToDynamic};
+
+/// The character encoding of the data exchanged with the process
+/// running in a pane.  Legacy encodings are transcoded to and from
+/// the UTF-8 used internally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, FromDynamic, ToDynamic)]
+#[dynamic(into = "String", try_from = "String")]
+pub enum CharacterEncoding {
+    #[default]
+    Utf8,
+    Iso8859_1,
+    Iso8859_2,
+    Iso8859_3,
+    Iso8859_4,
+    Iso8859_5,
+    Iso8859_6,
+    Iso8859_7,
+    Iso8859_8,
+    Iso8859_10,
+    Iso8859_13,
+    Iso8859_14,
+    Iso8859_15,
+    Iso8859_16,
+    Koi8R,
+    Koi8U,
+    ShiftJis,
+    EucJp,
+    Gbk,
+    Gb18030,
+    Big5,
+    EucKr,
+}
+
+impl CharacterEncoding {
+    pub const ALL: &'static [Self] = &[
+        Self::Utf8,
+        Self::Iso8859_1,
+        Self::Iso8859_2,
+        Self::Iso8859_3,
+        Self::Iso8859_4,
+        Self::Iso8859_5,
+        Self::Iso8859_6,
+        Self::Iso8859_7,
+        Self::Iso8859_8,
+        Self::Iso8859_10,
+        Self::Iso8859_13,
+        Self::Iso8859_14,
+        Self::Iso8859_15,
+        Self::Iso8859_16,
+        Self::Koi8R,
+        Self::Koi8U,
+        Self::ShiftJis,
+        Self::EucJp,
+        Self::Gbk,
+        Self::Gb18030,
+        Self::Big5,
+        Self::EucKr,
+    ];
+
+    /// Returns the IANA name of the encoding
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Iso8859_1 => "ISO-8859-1",
+            Self::Iso8859_2 => "ISO-8859-2",
+            Self::Iso8859_3 => "ISO-8859-3",
+            Self::Iso8859_4 => "ISO-8859-4",
+            Self::Iso8859_5 => "ISO-8859-5",
+            Self::Iso8859_6 => "ISO-8859-6",
+            Self::Iso8859_7 => "ISO-8859-7",
+            Self::Iso8859_8 => "ISO-8859-8",
+            Self::Iso8859_10 => "ISO-8859-10",
+            Self::Iso8859_13 => "ISO-8859-13",
+            Self::Iso8859_14 => "ISO-8859-14",
+            Self::Iso8859_15 => "ISO-8859-15",
+            Self::Iso8859_16 => "ISO-8859-16",
+            Self::Koi8R => "KOI8-R",
+            Self::Koi8U => "KOI8-U",
+            Self::ShiftJis => "Shift_JIS",
+            Self::EucJp => "EUC-JP",
+            Self::Gbk => "GBK",
+            Self::Gb18030 => "GB18030",
+            Self::Big5 => "Big5",
+            Self::EucKr => "EUC-KR",
+        }
+    }
+
+    pub fn is_utf8(self) -> bool {
+        self == Self::Utf8
+    }
+}
+
+impl From<&CharacterEncoding> for String {
+    fn from(val: &CharacterEncoding) -> Self {
+        val.name().to_string()
+    }
+}
+
+impl From<CharacterEncoding> for String {
+    fn from(val: CharacterEncoding) -> Self {
+        val.name().to_string()
+    }
+}
+
+impl TryFrom<String> for CharacterEncoding {
+    type Error = anyhow::Error;
+    fn try_from(s: String) -> anyhow::Result<CharacterEncoding> {
+        // Be lenient about case and punctuation, so that eg:
+        // "shift-jis", "SJIS" and "latin1" are all accepted
+        fn normalize(s: &str) -> String {
+            s.chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        }
+        let wanted = match normalize(&s).as_str() {
+            "utf8" => Some(Self::Utf8),
+            "latin1" => Some(Self::Iso8859_1),
+            "latin2" => Some(Self::Iso8859_2),
+            "latin9" => Some(Self::Iso8859_15),
+            "sjis" => Some(Self::ShiftJis),
+            wanted => Self::ALL
+                .iter()
+                .copied()
+                .find(|encoding| normalize(encoding.name()) == wanted),
+        };
+        wanted.ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid character encoding {:?}; expected one of {}",
+                s,
+                Self::ALL
+                    .iter()
+                    .map(|encoding| format!("{:?}", encoding.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        for &encoding in CharacterEncoding::ALL {
+            assert_eq!(
+                CharacterEncoding::try_from(encoding.name().to_string()).unwrap(),
+                encoding
+            );
+        }
+        assert_eq!(
+            CharacterEncoding::try_from("shift-jis".to_string()).unwrap(),
+            CharacterEncoding::ShiftJis
+        );
+        assert_eq!(
+            CharacterEncoding::try_from("iso8859_15".to_string()).unwrap(),
+            CharacterEncoding::Iso8859_15
+        );
+        assert_eq!(
+            CharacterEncoding::try_from("latin1".to_string()).unwrap(),
+            CharacterEncoding::Iso8859_1
+        );
+        assert!(CharacterEncoding::try_from("EBCDIC".to_string()).is_err());
+    }
+}
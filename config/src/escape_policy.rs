@@ -0,0 +1,137 @@
+use wezterm_dynamic::{FromDynamic, ToDynamic};
+use wezterm_term::config::EscapeSequencePermissions;
+
+/// A rule in `escape_sequence_policy`.  The permissions that are set
+/// by a rule apply to panes in the domain and on the host that it
+/// matches; the permissions it leaves unset are unchanged.
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct EscapeSequencePolicy {
+    /// The name of the domain of the pane; any domain if unset
+    #[dynamic(default)]
+    pub domain: Option<String>,
+    /// The host name reported by the program in the pane with OSC 7;
+    /// any host if unset.  `*.example.com` matches example.com and
+    /// all of its subdomains.
+    #[dynamic(default)]
+    pub host: Option<String>,
+    #[dynamic(default)]
+    pub clipboard_write: Option<bool>,
+    #[dynamic(default)]
+    pub set_title: Option<bool>,
+    #[dynamic(default)]
+    pub notifications: Option<bool>,
+    #[dynamic(default)]
+    pub file_transfer: Option<bool>,
+    #[dynamic(default)]
+    pub user_vars: Option<bool>,
+    #[dynamic(default)]
+    pub hyperlinks: Option<bool>,
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+        None => host == pattern,
+    }
+}
+
+impl EscapeSequencePolicy {
+    fn matches(&self, domain: Option<&str>, host: Option<&str>) -> bool {
+        if let Some(wanted) = &self.domain {
+            if domain != Some(wanted.as_str()) {
+                return false;
+            }
+        }
+        match (&self.host, host) {
+            (None, _) => true,
+            (Some(pattern), Some(host)) => host_matches(pattern, host),
+            (Some(_), None) => false,
+        }
+    }
+
+    fn apply(&self, permissions: &mut EscapeSequencePermissions) {
+        fn set(permission: &mut bool, value: Option<bool>) {
+            if let Some(value) = value {
+                *permission = value;
+            }
+        }
+        set(&mut permissions.clipboard_write, self.clipboard_write);
+        set(&mut permissions.set_title, self.set_title);
+        set(&mut permissions.notifications, self.notifications);
+        set(&mut permissions.file_transfer, self.file_transfer);
+        set(&mut permissions.user_vars, self.user_vars);
+        set(&mut permissions.hyperlinks, self.hyperlinks);
+    }
+}
+
+/// Computes the permissions of a pane in `domain` whose program has
+/// reported `host`, by applying each matching rule of `policy` in
+/// order, starting from everything being permitted
+pub fn escape_sequence_permissions(
+    policy: &[EscapeSequencePolicy],
+    domain: Option<&str>,
+    host: Option<&str>,
+) -> EscapeSequencePermissions {
+    let mut permissions = EscapeSequencePermissions::default();
+    for rule in policy {
+        if rule.matches(domain, host) {
+            rule.apply(&mut permissions);
+        }
+    }
+    permissions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn policy() {
+        let policy = vec![
+            EscapeSequencePolicy {
+                domain: Some("SSH:prod".to_string()),
+                clipboard_write: Some(false),
+                file_transfer: Some(false),
+                ..Default::default()
+            },
+            EscapeSequencePolicy {
+                host: Some("*.example.com".to_string()),
+                clipboard_write: Some(false),
+                set_title: Some(false),
+                ..Default::default()
+            },
+            EscapeSequencePolicy {
+                host: Some("trusted.example.com".to_string()),
+                clipboard_write: Some(true),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            escape_sequence_permissions(&policy, Some("local"), None),
+            EscapeSequencePermissions::default()
+        );
+
+        let prod = escape_sequence_permissions(&policy, Some("SSH:prod"), None);
+        assert!(!prod.clipboard_write);
+        assert!(!prod.file_transfer);
+        assert!(prod.set_title);
+
+        let remote = escape_sequence_permissions(&policy, Some("local"), Some("Build.Example.com"));
+        assert!(!remote.clipboard_write);
+        assert!(!remote.set_title);
+        assert!(remote.file_transfer);
+
+        let trusted =
+            escape_sequence_permissions(&policy, Some("local"), Some("trusted.example.com"));
+        assert!(trusted.clipboard_write);
+        assert!(!trusted.set_title);
+
+        let apex = escape_sequence_permissions(&policy, None, Some("example.com"));
+        assert!(!apex.set_title);
+        let other = escape_sequence_permissions(&policy, None, Some("notexample.com"));
+        assert!(other.set_title);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::config::validate_domain_name;
 use luahelper::impl_lua_conversion_dynamic;
+use std::collections::HashMap;
 use wezterm_dynamic::{FromDynamic, ToDynamic, Value};
 
 #[derive(Debug, Clone, FromDynamic, ToDynamic)]
@@ -15,5 +16,12 @@ pub struct ExecDomain {
     pub name: String,
     pub fixup_command: String,
     pub label: Option<ValueOrFunc>,
+    /// Overrides the global default_prog for this domain
+    #[dynamic(default)]
+    pub default_prog: Option<Vec<String>>,
+    /// Environment variables to set in addition to those from
+    /// the global set_environment_variables
+    #[dynamic(default)]
+    pub set_environment_variables: HashMap<String, String>,
 }
 impl_lua_conversion_dynamic!(ExecDomain);
@@ -662,6 +662,24 @@ impl Default for AllowSquareGlyphOverflow {
     }
 }
 
+/// Controls how Nerd Font symbols whose codepoints changed between
+/// Nerd Fonts v2 and v3 are translated before they are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum NerdFontMapping {
+    /// Render the codepoints as-is
+    None,
+    /// Translate v2 codepoints to their v3 equivalents
+    ToV3,
+    /// Translate v3 codepoints to their v2 equivalents
+    ToV2,
+}
+
+impl Default for NerdFontMapping {
+    fn default() -> Self {
+        Self::ToV3
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum FontLocatorSelection {
     /// Use fontconfig APIs to resolve fonts (!macos, posix systems)
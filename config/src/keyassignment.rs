@@ -1,6 +1,6 @@
 use crate::default_true;
 use crate::keys::KeyNoAction;
-use crate::window::WindowLevel;
+use crate::window::{WindowLevel, WindowSnapPosition};
 use luahelper::impl_lua_conversion_dynamic;
 use ordered_float::NotNan;
 use portable_pty::CommandBuilder;
@@ -133,6 +133,15 @@ impl Default for Pattern {
     }
 }
 
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct SearchAllPanesArgs {
+    #[dynamic(default)]
+    pub pattern: Pattern,
+    /// Only search the panes that have this tag
+    #[dynamic(default)]
+    pub tag: Option<String>,
+}
+
 /// A mouse event that can trigger an action
 #[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, FromDynamic, ToDynamic)]
 pub enum MouseEventTrigger {
@@ -195,6 +204,9 @@ pub struct SpawnCommand {
     pub domain: SpawnTabDomain,
 
     pub position: Option<crate::GuiPosition>,
+
+    /// Overrides the exit_behavior config for the spawned pane
+    pub exit_behavior: Option<crate::ExitBehavior>,
 }
 impl_lua_conversion_dynamic!(SpawnCommand);
 
@@ -259,6 +271,7 @@ impl SpawnCommand {
             set_environment_variables,
             cwd,
             position: None,
+            exit_behavior: None,
         })
     }
 }
@@ -318,6 +331,76 @@ impl Default for ClipboardCopyDestination {
     }
 }
 
+/// The arguments of CopyTo, which are either just the destination,
+/// or a table that can also ask for the selection to be appended to
+/// the text that the destination already holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyToArguments {
+    pub destination: ClipboardCopyDestination,
+    pub append: bool,
+    /// Inserted between the text that the destination already holds
+    /// and the selection, when appending
+    pub separator: String,
+}
+
+#[derive(FromDynamic, ToDynamic)]
+struct CopyToTable {
+    #[dynamic(default)]
+    destination: ClipboardCopyDestination,
+    #[dynamic(default)]
+    append: bool,
+    #[dynamic(default = "default_append_separator")]
+    separator: String,
+}
+
+fn default_append_separator() -> String {
+    "\n".to_string()
+}
+
+impl From<ClipboardCopyDestination> for CopyToArguments {
+    fn from(destination: ClipboardCopyDestination) -> Self {
+        Self {
+            destination,
+            append: false,
+            separator: default_append_separator(),
+        }
+    }
+}
+
+impl FromDynamic for CopyToArguments {
+    fn from_dynamic(
+        value: &Value,
+        options: FromDynamicOptions,
+    ) -> Result<Self, wezterm_dynamic::Error> {
+        match value {
+            Value::String(_) => Ok(ClipboardCopyDestination::from_dynamic(value, options)?.into()),
+            _ => {
+                let table = CopyToTable::from_dynamic(value, options)?;
+                Ok(Self {
+                    destination: table.destination,
+                    append: table.append,
+                    separator: table.separator,
+                })
+            }
+        }
+    }
+}
+
+impl ToDynamic for CopyToArguments {
+    fn to_dynamic(&self) -> Value {
+        if self.append {
+            CopyToTable {
+                destination: self.destination,
+                append: self.append,
+                separator: self.separator.clone(),
+            }
+            .to_dynamic()
+        } else {
+            self.destination.to_dynamic()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum ClipboardPasteSource {
     Clipboard,
@@ -436,6 +519,78 @@ impl Default for CharSelectArguments {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct CaptureArguments {
+    /// Where to save the recording.  The default is a file named
+    /// after the current time in the downloads directory.
+    #[dynamic(default)]
+    pub path: Option<String>,
+    /// The maximum number of frames to record per second
+    #[dynamic(default = "default_capture_fps")]
+    pub fps: u32,
+    /// Record the whole window rather than just the active pane
+    #[dynamic(default)]
+    pub whole_window: bool,
+}
+
+fn default_capture_fps() -> u32 {
+    15
+}
+
+impl Default for CaptureArguments {
+    fn default() -> Self {
+        Self {
+            path: None,
+            fps: default_capture_fps(),
+            whole_window: false,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct ExportToPdfArguments {
+    /// Where to save the document.  The default is a file named
+    /// after the current time in the downloads directory.
+    #[dynamic(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
+pub struct MagnifierArguments {
+    /// How much to enlarge the text, relative to the font size
+    /// of the window
+    #[dynamic(default = "default_magnifier_scale")]
+    pub scale: f64,
+    /// How many columns around the mouse pointer to show
+    #[dynamic(default = "default_magnifier_cols")]
+    pub cols: usize,
+    /// How many rows around the mouse pointer to show
+    #[dynamic(default = "default_magnifier_rows")]
+    pub rows: usize,
+}
+
+fn default_magnifier_scale() -> f64 {
+    3.0
+}
+
+fn default_magnifier_cols() -> usize {
+    12
+}
+
+fn default_magnifier_rows() -> usize {
+    3
+}
+
+impl Default for MagnifierArguments {
+    fn default() -> Self {
+        Self {
+            scale: default_magnifier_scale(),
+            cols: default_magnifier_cols(),
+            rows: default_magnifier_rows(),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
 pub struct QuickSelectArguments {
     /// Overrides the main quick_select_alphabet config
@@ -452,6 +607,29 @@ pub struct QuickSelectArguments {
     /// How man lines before and how many lines after the viewport to
     /// search to produce the quickselect results
     pub scope_lines: Option<usize>,
+    /// Run the matching quick_select_commands entry for the selected
+    /// text rather than copying it
+    #[dynamic(default)]
+    pub run_command: bool,
+}
+
+/// A command that can be run on the text selected by quick select
+#[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
+pub struct QuickSelectCommand {
+    /// The regex that the selected text must match
+    pub pattern: String,
+    /// The command line to run in a new split.  `$0` is replaced by
+    /// the text that matched the pattern, and `$1`, `$name` and so on
+    /// by its capture groups
+    pub args: Vec<String>,
+    #[dynamic(default = "default_quick_select_command_direction")]
+    pub direction: PaneDirection,
+    #[dynamic(default)]
+    pub size: SplitSize,
+}
+
+fn default_quick_select_command_direction() -> PaneDirection {
+    PaneDirection::Down
 }
 
 #[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
@@ -506,14 +684,32 @@ pub enum KeyAssignment {
     SpawnTab(SpawnTabDomain),
     SpawnWindow,
     ToggleFullScreen,
+    TogglePresentationMode,
+    ActivateConfigProfile(String),
+    DeactivateConfigProfile,
+    ToggleKeyDisplay,
+    ToggleShowInvisibleCharacters,
+    ToggleShowWhitespace,
     ToggleAlwaysOnTop,
     ToggleAlwaysOnBottom,
+    ToggleStickyWindow,
     SetWindowLevel(WindowLevel),
-    CopyTo(ClipboardCopyDestination),
+    MoveWindow {
+        dx: isize,
+        dy: isize,
+    },
+    ResizeWindow {
+        dx: isize,
+        dy: isize,
+    },
+    SnapWindow(WindowSnapPosition),
+    CopyTo(CopyToArguments),
     CopyTextTo {
         text: String,
         destination: ClipboardCopyDestination,
     },
+    CaptureSelectionToUserVar(String),
+    CaptureLastCommandOutputToUserVar(String),
     PasteFrom(ClipboardPasteSource),
     ActivateTabRelative(isize),
     ActivateTabRelativeNoWrap(isize),
@@ -521,6 +717,9 @@ pub enum KeyAssignment {
     DecreaseFontSize,
     ResetFontSize,
     ResetFontAndWindowSize,
+    IncreasePaneFontSize,
+    DecreasePaneFontSize,
+    ResetPaneFontSize,
     ActivateTab(isize),
     ActivateLastTab,
     SendString(String),
@@ -541,8 +740,12 @@ pub enum KeyAssignment {
     ScrollToPrompt(isize),
     ScrollToTop,
     ScrollToBottom,
+    ToggleScrollbackSplit,
+    PauseOutputScrolling,
     ShowTabNavigator,
     ShowDebugOverlay,
+    ShowVtInspector,
+    ShowReleaseNotes,
     HideApplication,
     QuitApplication,
     SpawnCommandInNewTab(SpawnCommand),
@@ -553,10 +756,16 @@ pub enum KeyAssignment {
     ShowLauncherArgs(LauncherActionArgs),
     ClearScrollback(ScrollbackEraseMode),
     Search(Pattern),
+    SearchAllPanes(Pattern),
+    SearchAllPanesArgs(SearchAllPanesArgs),
+    ShowCommandJournal,
+    ShowDownloads,
+    RevealSuspiciousCharacters,
     ActivateCopyMode,
 
     SelectTextAtMouseCursor(SelectionMode),
     ExtendSelectionToMouseCursor(SelectionMode),
+    ExtendSelectionToSemanticUnit,
     OpenLinkAtMouseCursor,
     ClearSelection,
     CompleteSelection(ClipboardCopyDestination),
@@ -567,6 +776,7 @@ pub enum KeyAssignment {
     ActivatePaneDirection(PaneDirection),
     ActivatePaneByIndex(usize),
     TogglePaneZoomState,
+    RespawnPane,
     SetPaneZoomState(bool),
     CloseCurrentPane {
         confirm: bool,
@@ -574,6 +784,7 @@ pub enum KeyAssignment {
     EmitEvent(String),
     QuickSelect,
     QuickSelectArgs(QuickSelectArguments),
+    ShowLinkHints,
 
     Multiple(Vec<KeyAssignment>),
 
@@ -608,6 +819,7 @@ pub enum KeyAssignment {
     CharSelect(CharSelectArguments),
 
     ResetTerminal,
+    CancelZmodemTransfer,
     OpenUri(String),
     ActivateCommandPalette,
     ActivateWindow(usize),
@@ -615,6 +827,13 @@ pub enum KeyAssignment {
     ActivateWindowRelativeNoWrap(isize),
     PromptInputLine(PromptInputLine),
     InputSelector(InputSelector),
+    StartCapture(CaptureArguments),
+    StopCapture,
+    ExportToPdf(ExportToPdfArguments),
+    MirrorPane,
+    ShowMagnifier(MagnifierArguments),
+    ShowColorSchemeEditor,
+    AdjustOpacity(NotNan<f64>),
 }
 impl_lua_conversion_dynamic!(KeyAssignment);
 
@@ -703,3 +922,35 @@ pub struct KeyTables {
 pub struct KeyTableEntry {
     pub action: KeyAssignment,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copy_to_destination() {
+        let args = CopyToArguments::from_dynamic(
+            &Value::String("Clipboard".to_string()),
+            FromDynamicOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(args, ClipboardCopyDestination::Clipboard.into());
+        // A plain copy is still represented by just its destination
+        assert_eq!(args.to_dynamic(), Value::String("Clipboard".to_string()));
+    }
+
+    #[test]
+    fn copy_to_append() {
+        let args = CopyToArguments {
+            destination: ClipboardCopyDestination::PrimarySelection,
+            append: true,
+            separator: ", ".to_string(),
+        };
+        let value = args.to_dynamic();
+        assert!(matches!(value, Value::Object(_)));
+        assert_eq!(
+            CopyToArguments::from_dynamic(&value, FromDynamicOptions::default()).unwrap(),
+            args
+        );
+    }
+}
@@ -125,6 +125,53 @@ pub struct Key {
     pub action: KeyAssignment,
 }
 
+/// A built in set of key bindings that emulates another program,
+/// layered between the configured keys and the default keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+#[dynamic(into = "String", try_from = "String")]
+pub enum KeyPreset {
+    Tmux,
+    Screen,
+    ITerm2,
+}
+
+impl KeyPreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tmux => "tmux",
+            Self::Screen => "screen",
+            Self::ITerm2 => "iTerm2",
+        }
+    }
+}
+
+impl From<&KeyPreset> for String {
+    fn from(val: &KeyPreset) -> Self {
+        val.name().to_string()
+    }
+}
+
+impl From<KeyPreset> for String {
+    fn from(val: KeyPreset) -> Self {
+        val.name().to_string()
+    }
+}
+
+impl TryFrom<String> for KeyPreset {
+    type Error = anyhow::Error;
+    fn try_from(s: String) -> anyhow::Result<KeyPreset> {
+        for preset in [Self::Tmux, Self::Screen, Self::ITerm2] {
+            if preset.name().eq_ignore_ascii_case(&s) {
+                return Ok(preset);
+            }
+        }
+        anyhow::bail!(
+            "invalid key_preset {:?}; expected one of \"tmux\", \"screen\" or \"iTerm2\"",
+            s
+        )
+    }
+}
+
 #[derive(Debug, Clone, FromDynamic, ToDynamic)]
 pub struct LeaderKey {
     #[dynamic(flatten)]
@@ -19,11 +19,14 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use wezterm_dynamic::{FromDynamic, FromDynamicOptions, ToDynamic, UnknownFieldAction, Value};
 
+mod accessibility;
 mod background;
 mod bell;
 mod color;
 mod config;
 mod daemon;
+mod encoding;
+mod escape_policy;
 mod exec_domain;
 mod font;
 mod frontend;
@@ -31,8 +34,10 @@ pub mod keyassignment;
 mod keys;
 pub mod lua;
 pub mod meta;
+mod power;
 mod scheme_data;
 mod serial;
+mod service;
 mod ssh;
 mod terminal;
 mod tls;
@@ -43,15 +48,20 @@ pub mod window;
 mod wsl;
 
 pub use crate::config::*;
+pub use accessibility::*;
 pub use background::*;
 pub use bell::*;
 pub use color::*;
 pub use daemon::*;
+pub use encoding::*;
+pub use escape_policy::*;
 pub use exec_domain::*;
 pub use font::*;
 pub use frontend::*;
 pub use keys::*;
+pub use power::*;
 pub use serial::*;
+pub use service::*;
 pub use ssh::*;
 pub use terminal::*;
 pub use tls::*;
@@ -61,6 +71,8 @@ pub use version::*;
 pub use wsl::*;
 
 type ErrorCallback = fn(&str);
+type PaletteFromImageFunc = fn(&Path) -> anyhow::Result<Palette>;
+type ColorSchemeImporterFunc = fn(&Path) -> anyhow::Result<Option<ColorSchemeFile>>;
 
 lazy_static! {
     pub static ref HOME_DIR: PathBuf = dirs_next::home_dir().expect("can't find HOME dir");
@@ -74,6 +86,8 @@ lazy_static! {
     static ref SHOW_ERROR: Mutex<Option<ErrorCallback>> =
         Mutex::new(Some(|e| log::error!("{}", e)));
     static ref LUA_PIPE: LuaPipe = LuaPipe::new();
+    static ref PALETTE_FROM_IMAGE: Mutex<Option<PaletteFromImageFunc>> = Mutex::new(None);
+    static ref COLOR_SCHEME_IMPORTER: Mutex<Option<ColorSchemeImporterFunc>> = Mutex::new(None);
     pub static ref COLOR_SCHEMES: HashMap<String, Palette> = build_default_schemes();
 }
 
@@ -358,6 +372,35 @@ pub fn assign_error_callback(cb: ErrorCallback) {
     factory.replace(cb);
 }
 
+/// Assigns the function used to derive a palette from the background
+/// image when `color_scheme_from_background_image` is enabled.
+/// The image decoding lives outside of this crate.
+pub fn assign_palette_from_image_func(func: PaletteFromImageFunc) {
+    PALETTE_FROM_IMAGE.lock().unwrap().replace(func);
+}
+
+pub(crate) fn palette_from_image(path: &Path) -> anyhow::Result<Palette> {
+    let func = *PALETTE_FROM_IMAGE.lock().unwrap();
+    match func {
+        Some(func) => func(path),
+        None => anyhow::bail!("palette_from_image is not available"),
+    }
+}
+
+pub fn assign_color_scheme_importer_func(func: ColorSchemeImporterFunc) {
+    COLOR_SCHEME_IMPORTER.lock().unwrap().replace(func);
+}
+
+/// Converts a color scheme file in a format other than our own TOML,
+/// returning `Ok(None)` if the format isn't recognized
+pub(crate) fn import_color_scheme(path: &Path) -> anyhow::Result<Option<ColorSchemeFile>> {
+    let func = *COLOR_SCHEME_IMPORTER.lock().unwrap();
+    match func {
+        Some(func) => func(path),
+        None => Ok(None),
+    }
+}
+
 pub fn show_error(err: &str) {
     let factory = SHOW_ERROR.lock().unwrap();
     if let Some(cb) = factory.as_ref() {
@@ -411,6 +454,25 @@ pub fn set_config_overrides(items: &[(String, String)]) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Returns the path of the config file that was loaded, or the path
+/// at which the user would typically create one if there is none
+pub fn config_file_path() -> PathBuf {
+    std::env::var_os("WEZTERM_CONFIG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| HOME_DIR.join(".wezterm.lua"))
+}
+
+/// Returns the path of the log file written by this process
+pub fn log_file_path() -> PathBuf {
+    let base_name = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.file_name().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "wezterm".to_string());
+    RUNTIME_DIR.join(format!("{}-log-{}.txt", base_name, unsafe {
+        libc::getpid()
+    }))
+}
+
 pub fn is_config_overridden() -> bool {
     CONFIG_SKIP.load(Ordering::Relaxed)
         || !CONFIG_OVERRIDES.lock().unwrap().is_empty()
@@ -457,6 +519,13 @@ pub fn configuration_result() -> Result<ConfigHandle, Error> {
     Ok(CONFIG.get())
 }
 
+/// Returns the path of the config file that was loaded, or None
+/// if the default configuration is in use because there is no
+/// config file
+pub fn loaded_config_file() -> Option<PathBuf> {
+    CONFIG.get_file_name()
+}
+
 /// Returns the combined set of errors + warnings encountered
 /// while loading the preferred configuration
 pub fn configuration_warnings_and_errors() -> Vec<String> {
@@ -467,6 +536,8 @@ struct ConfigInner {
     config: Arc<Config>,
     error: Option<String>,
     warnings: Vec<String>,
+    /// The config file that was most recently loaded, if any
+    file_name: Option<PathBuf>,
     generation: usize,
     watcher: Option<notify::RecommendedWatcher>,
     subscribers: HashMap<usize, Box<dyn Fn() -> bool + Send>>,
@@ -478,6 +549,7 @@ impl ConfigInner {
             config: Arc::new(Config::default_config()),
             error: None,
             warnings: vec![],
+            file_name: None,
             generation: 0,
             watcher: None,
             subscribers: HashMap::new(),
@@ -580,6 +652,7 @@ impl ConfigInner {
         } = Config::load();
 
         self.warnings = warnings;
+        self.file_name = file_name.clone();
 
         // Before we process the success/failure, extract and update
         // any paths that we should be watching
@@ -751,6 +824,13 @@ impl Configuration {
         inner.error.as_ref().cloned()
     }
 
+    /// Returns the path of the config file that was most recently
+    /// loaded, or None if there is no config file
+    pub fn get_file_name(&self) -> Option<PathBuf> {
+        let inner = self.inner.lock().unwrap();
+        inner.file_name.clone()
+    }
+
     pub fn get_warnings_and_errors(&self) -> Vec<String> {
         let mut result = vec![];
         let inner = self.inner.lock().unwrap();
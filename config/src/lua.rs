@@ -9,6 +9,7 @@ use luahelper::{from_lua_value_dynamic, lua_value_to_dynamic, to_lua};
 use mlua::{FromLua, IntoLuaMulti, Lua, Table, Value, Variadic};
 use ordered_float::NotNan;
 use portable_pty::CommandBuilder;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::Path;
 use std::sync::Mutex;
@@ -683,6 +684,8 @@ fn exec_domain<'lua>(
         name,
         fixup_command,
         label,
+        default_prog: None,
+        set_environment_variables: HashMap::new(),
     })
 }
 
@@ -813,6 +816,16 @@ where
     }
 }
 
+/// Returns true if a handler has been registered for the event `name`
+pub fn has_event_handler<'lua>(lua: &'lua Lua, name: &str) -> mlua::Result<bool> {
+    let decorated_name = format!("wezterm-event-{}", name);
+    let tbl: mlua::Value = lua.named_registry_value(&decorated_name)?;
+    Ok(match tbl {
+        mlua::Value::Table(tbl) => tbl.raw_len() > 0,
+        _ => false,
+    })
+}
+
 pub async fn emit_async_callback<'lua, A>(
     lua: &'lua Lua,
     (name, args): (String, A),
@@ -31,3 +31,322 @@ pub struct ConfigOption {
     /// TODO: For struct types, the fields in the child struct
     pub fields: &'static [ConfigOption],
 }
+
+/// Information about a config option that is recorded in its docs
+struct OptionDocs {
+    name: &'static str,
+    /// The version in which the option was introduced
+    since: Option<&'static str>,
+    tags: &'static [&'static str],
+}
+
+include!(concat!(env!("OUT_DIR"), "/option_docs.rs"));
+
+const DOCS_URL: &str = "https://wezfurlong.org/wezterm/config/lua/config";
+
+fn option_docs(name: &str) -> Option<&'static OptionDocs> {
+    OPTION_DOCS.iter().find(|docs| docs.name == name)
+}
+
+fn dynamic_to_json(value: Value) -> serde_json::Value {
+    use serde_json::Value as JValue;
+    match value {
+        Value::Null => JValue::Null,
+        Value::Bool(b) => JValue::Bool(b),
+        Value::String(s) => JValue::String(s),
+        Value::Array(a) => JValue::Array(a.into_iter().map(dynamic_to_json).collect()),
+        Value::Object(o) => JValue::Object(
+            o.into_iter()
+                .map(|(k, v)| {
+                    let k = match k {
+                        Value::String(s) => s,
+                        Value::U64(n) => n.to_string(),
+                        Value::I64(n) => n.to_string(),
+                        other => format!("{other:?}"),
+                    };
+                    (k, dynamic_to_json(v))
+                })
+                .collect(),
+        ),
+        Value::U64(n) => JValue::Number(n.into()),
+        Value::I64(n) => JValue::Number(n.into()),
+        Value::F64(n) => serde_json::Number::from_f64(*n)
+            .map(JValue::Number)
+            .unwrap_or(JValue::Null),
+    }
+}
+
+fn container_name(container: ConfigContainer) -> Option<&'static str> {
+    match container {
+        ConfigContainer::None => None,
+        ConfigContainer::Option => Some("Option"),
+        ConfigContainer::Vec => Some("Vec"),
+        ConfigContainer::Map => Some("Map"),
+    }
+}
+
+/// Describes the type of an option in the form used by the schema
+fn describe_type(option: &ConfigOption) -> String {
+    match option.container {
+        ConfigContainer::None => option.type_name.to_string(),
+        ConfigContainer::Option => format!("optional {}", option.type_name),
+        ConfigContainer::Vec => format!("list of {}", option.type_name),
+        ConfigContainer::Map => format!("map of {}", option.type_name),
+    }
+}
+
+/// Returns a machine readable description of all of the config
+/// options, including their types, defaults and documentation
+pub fn config_schema() -> serde_json::Value {
+    let config = crate::Config::default_config();
+    let options = config
+        .get_config_options()
+        .iter()
+        .map(|option| {
+            let docs = option_docs(option.name);
+            serde_json::json!({
+                "name": option.name,
+                "type": option.type_name,
+                "container": container_name(option.container),
+                "default": option.default_value.map(|default| dynamic_to_json(default())),
+                "doc": option.doc.lines().map(str::trim).collect::<Vec<_>>().join("\n"),
+                "since": docs.and_then(|docs| docs.since),
+                "tags": docs.map(|docs| docs.tags).unwrap_or(option.tags),
+                "url": docs.map(|_| format!("{DOCS_URL}/{}.html", option.name)),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(options)
+}
+
+/// Returns the 1-based number of the first line in `source` that
+/// assigns to `field`, either as `field = ...`, `config.field = ...`
+/// or `['field'] = ...`
+fn locate_field_assignment(source: &str, field: &str) -> Option<usize> {
+    fn is_ident(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    for (idx, line) in source.lines().enumerate() {
+        let code = match line.find("--") {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        for (start, _) in code.match_indices(field) {
+            let before =
+                code[..start].trim_end_matches(|c: char| c == '[' || c == '"' || c == '\'');
+            let after = code[start + field.len()..]
+                .trim_start_matches(|c: char| c == ']' || c == '"' || c == '\'')
+                .trim_start();
+            if before.chars().next_back().map(is_ident).unwrap_or(false) {
+                continue;
+            }
+            if code[start + field.len()..]
+                .chars()
+                .next()
+                .map(is_ident)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            if after.starts_with('=') && !after.starts_with("==") {
+                return Some(idx + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Extracts the name of the top level config option that an error
+/// or warning produced while converting the config refers to
+fn option_in_message(message: &str) -> Option<&str> {
+    fn ident_prefix(s: &str) -> &str {
+        let len = s
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(s.len());
+        &s[..len]
+    }
+
+    let name = if let Some(idx) = message.find("` is not a valid Config field") {
+        let start = message[..idx].rfind('`')? + 1;
+        &message[start..idx]
+    } else if let Some(idx) = message.find("Error processing Config::") {
+        ident_prefix(&message[idx + "Error processing Config::".len()..])
+    } else if let Some(idx) = message.find("Error processing ") {
+        let rest = &message[idx + "Error processing ".len()..];
+        if !rest.contains("(types: Config") {
+            return None;
+        }
+        ident_prefix(rest)
+    } else {
+        return None;
+    };
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Given an error or warning produced while converting the config
+/// that was loaded from `file_name`, returns a description of where
+/// the option that it relates to is set in `source`, along with the
+/// expected type of the option, if known
+pub(crate) fn locate_config_message(
+    message: &str,
+    file_name: &std::path::Path,
+    source: &str,
+) -> Option<String> {
+    let name = option_in_message(message)?;
+    let line = locate_field_assignment(source, name)?;
+    let text = source.lines().nth(line - 1)?.trim();
+    let mut location = format!("  --> {}:{line}\n   | {text}", file_name.display());
+
+    let config = crate::Config::default_config();
+    if let Some(option) = config
+        .get_config_options()
+        .iter()
+        .find(|option| option.name == name)
+    {
+        location.push_str(&format!(
+            "\n   = `{name}` expects {}",
+            describe_type(option)
+        ));
+        if option_docs(name).is_some() {
+            location.push_str(&format!(", see {DOCS_URL}/{name}.html"));
+        }
+    }
+    Some(location)
+}
+
+/// The options that can be overridden at runtime for an individual
+/// window via `wezterm cli set-config`.  These are limited to those
+/// that only affect the appearance of the window, and that are applied
+/// as soon as the overrides of the window change.
+pub const RUNTIME_CONFIG_OPTIONS: &[&str] = &[
+    "adjust_window_size_when_changing_font_size",
+    "bold_brightens_ansi_colors",
+    "cell_width",
+    "color_scheme",
+    "cursor_blink_rate",
+    "default_cursor_style",
+    "enable_scroll_bar",
+    "enable_tab_bar",
+    "font_size",
+    "foreground_text_hsb",
+    "hide_tab_bar_if_only_one_tab",
+    "inactive_pane_hsb",
+    "inactive_window_opacity",
+    "line_height",
+    "macos_window_background_blur",
+    "minimum_contrast_ratio",
+    "tab_bar_at_bottom",
+    "text_background_opacity",
+    "use_fancy_tab_bar",
+    "window_background_opacity",
+    "window_padding",
+];
+
+/// Returns an error if `name` is not one of the RUNTIME_CONFIG_OPTIONS
+pub fn check_runtime_option(name: &str) -> anyhow::Result<()> {
+    if !RUNTIME_CONFIG_OPTIONS.contains(&name) {
+        anyhow::bail!(
+            "`{name}` cannot be changed at runtime. The options that can be changed are: {}",
+            RUNTIME_CONFIG_OPTIONS.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Returns the value of the option `name` in `config`, as JSON
+pub fn config_option_value(
+    config: &crate::Config,
+    name: &str,
+) -> anyhow::Result<serde_json::Value> {
+    use wezterm_dynamic::ToDynamic;
+    match config.to_dynamic() {
+        Value::Object(options) => options
+            .get_by_str(name)
+            .cloned()
+            .map(dynamic_to_json)
+            .ok_or_else(|| anyhow::anyhow!("`{name}` is not a valid Config field")),
+        _ => anyhow::bail!("`{name}` is not a valid Config field"),
+    }
+}
+
+/// Parses `value` as the runtime override for the option `name`.
+/// The value is parsed as JSON, so that numbers, booleans and tables
+/// can be specified, falling back to treating it as a string so that
+/// eg: a color scheme can be named without quoting it.
+pub fn parse_runtime_override(name: &str, value: &str) -> anyhow::Result<Value> {
+    check_runtime_option(name)?;
+    Ok(match serde_json::from_str(value) {
+        Ok(value) => crate::json_to_dynamic(&value),
+        Err(_) => Value::String(value.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runtime_override() {
+        use wezterm_dynamic::ToDynamic;
+        assert_eq!(
+            parse_runtime_override("font_size", "14.5").unwrap(),
+            14.5f64.to_dynamic()
+        );
+        assert_eq!(
+            parse_runtime_override("color_scheme", "Batman").unwrap(),
+            Value::String("Batman".to_string())
+        );
+        assert_eq!(
+            parse_runtime_override("color_scheme", "\"Batman\"").unwrap(),
+            Value::String("Batman".to_string())
+        );
+        assert!(parse_runtime_override("default_prog", "[\"bash\"]").is_err());
+    }
+
+    #[test]
+    fn locate() {
+        let source = r#"
+local config = {}
+-- config.font_size = 10
+config.line_height = 1.2
+config.font_size = 'big'
+return {
+  ["color_scheme"] = 'Batman',
+  if font_size == 12 then end
+}
+"#;
+        assert_eq!(locate_field_assignment(source, "font_size"), Some(5));
+        assert_eq!(locate_field_assignment(source, "line_height"), Some(4));
+        assert_eq!(locate_field_assignment(source, "color_scheme"), Some(7));
+        assert_eq!(locate_field_assignment(source, "height"), None);
+    }
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            option_in_message("`fnt_size` is not a valid Config field. Did you mean `font_size`?"),
+            Some("fnt_size")
+        );
+        assert_eq!(
+            option_in_message(
+                "Error processing Config::font_size: Cannot convert `String` to `f64`"
+            ),
+            Some("font_size")
+        );
+        assert_eq!(
+            option_in_message(
+                "Error processing colors.ansi (types: Config, Palette) Cannot convert"
+            ),
+            Some("colors")
+        );
+        assert_eq!(
+            option_in_message("Error processing Palette::ansi: oops"),
+            None
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::accessibility::reduced_motion_map;
+use crate::config::Config;
+use wezterm_dynamic::{FromDynamic, ToDynamic, Value};
+
+/// Controls when the power saving rendering profile is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum PowerSavingMode {
+    Never,
+    Always,
+    /// While running on battery power, or when the operating
+    /// system has been asked to save power
+    OnBattery,
+    /// Only when the operating system has been asked to save power
+    LowPowerMode,
+}
+
+impl Default for PowerSavingMode {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+impl PowerSavingMode {
+    /// Resolve whether power saving is active, given the
+    /// current state of the power supply
+    pub fn is_enabled(self, on_battery: bool, low_power_mode: bool) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Always => true,
+            Self::OnBattery => on_battery || low_power_mode,
+            Self::LowPowerMode => low_power_mode,
+        }
+    }
+}
+
+/// Returns config overrides that reduce the frame rate and turn off
+/// animations and background blur, in addition to the overrides
+/// that implement `reduce_motion`
+pub fn power_saving_overrides(config: &Config) -> Value {
+    let mut map = reduced_motion_map(config);
+
+    let max_fps = config.power_saving_max_fps.min(config.max_fps).max(1);
+    for (key, value) in [
+        ("max_fps", max_fps.to_dynamic()),
+        ("animation_fps", 1u8.to_dynamic()),
+        ("macos_window_background_blur", 0i64.to_dynamic()),
+    ] {
+        map.insert(Value::String(key.to_string()), value);
+    }
+
+    Value::Object(map.into())
+}
@@ -1,4 +1,5 @@
-use crate::config::validate_domain_name;
+use crate::config::{validate_domain_name, SlowPaste};
+use crate::encoding::CharacterEncoding;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 
 #[derive(Default, Debug, Clone, FromDynamic, ToDynamic)]
@@ -16,4 +17,16 @@ pub struct SerialDomain {
 
     /// Set the baud rate.  The default is 9600 baud.
     pub baud: Option<usize>,
+
+    /// The character encoding used by the device.  If omitted,
+    /// `default_pane_encoding` is used.
+    pub encoding: Option<CharacterEncoding>,
+
+    /// The string sent to the device in response to ENQ.  If omitted,
+    /// `enq_answerback` is used.
+    pub answerback: Option<String>,
+
+    /// How pastes are trickled to the device.  If omitted,
+    /// `slow_paste` is used.
+    pub slow_paste: Option<SlowPaste>,
 }
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use wezterm_dynamic::{FromDynamic, ToDynamic};
+
+/// When a service is restarted after its program exits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum ServiceRestart {
+    /// Restart the program whenever it exits
+    Always,
+    /// Restart the program if it exits with a failure status, or is
+    /// killed by a signal
+    #[default]
+    OnFailure,
+    /// Leave the program stopped once it exits
+    Never,
+}
+
+/// A background program that the GUI starts when it is launched,
+/// restarts when it exits and stops along with the mux
+#[derive(Default, Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
+pub struct Service {
+    /// The name of the service, which must be unique amongst the
+    /// services.  It is used to report the status of the service.
+    #[dynamic(validate = "validate_service_name")]
+    pub name: String,
+
+    /// The argument vector of the program, starting with the program
+    pub args: Vec<String>,
+
+    /// The directory to run the program in.  If omitted, the home
+    /// directory of the user is used.
+    pub cwd: Option<PathBuf>,
+
+    /// Environment variables to set for the program, in addition to
+    /// the environment of wezterm
+    #[dynamic(default)]
+    pub set_environment_variables: HashMap<String, String>,
+
+    #[dynamic(default)]
+    pub restart: ServiceRestart,
+
+    /// How long to wait before restarting the program the first time
+    /// that it exits.  The delay doubles each time the program exits
+    /// again soon after being restarted, up to `max_restart_delay_ms`.
+    #[dynamic(default = "default_restart_delay_ms")]
+    pub restart_delay_ms: u64,
+
+    #[dynamic(default = "default_max_restart_delay_ms")]
+    pub max_restart_delay_ms: u64,
+}
+
+fn default_restart_delay_ms() -> u64 {
+    1000
+}
+
+fn default_max_restart_delay_ms() -> u64 {
+    60_000
+}
+
+fn validate_service_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err("the empty string is an invalid service name".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Config;
+
+    fn service(name: &str, args: &[&str]) -> Service {
+        Service {
+            name: name.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn consistency() {
+        let mut config = Config::default_config();
+        config.services = vec![
+            service("agent", &["ssh-agent", "-D"]),
+            service("sync", &["syncthing"]),
+        ];
+        assert!(config.check_consistency().is_ok());
+
+        config.services.push(service("agent", &["gpg-agent"]));
+        let err = config.check_consistency().unwrap_err().to_string();
+        assert!(err.contains("\"agent\" conflicts"), "{err}");
+
+        config.services = vec![service("empty", &[])];
+        let err = config.check_consistency().unwrap_err().to_string();
+        assert!(err.contains("has no args"), "{err}");
+    }
+}
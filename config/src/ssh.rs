@@ -99,8 +99,26 @@ pub struct SshDomain {
 
     pub default_prog: Option<Vec<String>>,
 
+    /// Environment variables to set on the remote host
+    #[dynamic(default)]
+    pub set_environment_variables: HashMap<String, String>,
+
+    /// Overrides the global login_shell for this domain.
+    /// Only has an effect when assume_shell is Posix.
+    pub login_shell: Option<bool>,
+
     #[dynamic(default)]
     pub assume_shell: Shell,
+
+    /// The string sent in response to ENQ.  If omitted,
+    /// `enq_answerback` is used.
+    /// Only has an effect when multiplexing is None.
+    pub answerback: Option<String>,
+
+    /// How pastes are trickled to the remote host.  If omitted,
+    /// `slow_paste` is used.
+    /// Only has an effect when multiplexing is None.
+    pub slow_paste: Option<SlowPaste>,
 }
 impl_lua_conversion_dynamic!(SshDomain);
 
@@ -130,6 +148,84 @@ impl SshDomain {
         plain_ssh.append(&mut mux_ssh);
         plain_ssh
     }
+
+    /// Returns the host portion of `remote_address`, without the port
+    pub fn remote_host_name(&self) -> &str {
+        match self.remote_address.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => host,
+            _ => &self.remote_address,
+        }
+    }
+}
+
+/// A named set of configuration overrides that are applied to a window
+/// while its active pane is connected to a matching ssh host.
+#[derive(Default, Debug, Clone, FromDynamic, ToDynamic)]
+pub struct SshHostProfile {
+    /// A name for the profile; used only for logging purposes
+    #[dynamic(default)]
+    pub name: String,
+
+    /// The list of host patterns that activate this profile.
+    /// `*` and `?` wildcards are supported, and a pattern with
+    /// a leading `!` prevents the profile from matching that host.
+    pub hosts: Vec<String>,
+
+    /// The config overrides to apply, in the same form as
+    /// `window:set_config_overrides`
+    #[dynamic(default)]
+    pub overrides: wezterm_dynamic::Value,
+}
+impl_lua_conversion_dynamic!(SshHostProfile);
+
+impl SshHostProfile {
+    /// Returns true if this profile should be applied to `host`.
+    /// Negated patterns take precedence over positive matches,
+    /// in the same way as `Host` patterns in ssh_config.
+    pub fn matches(&self, host: &str) -> bool {
+        let mut matched = false;
+        for pattern in &self.hosts {
+            if let Some(pattern) = pattern.strip_prefix('!') {
+                if wildcard_match(pattern, host) {
+                    return false;
+                }
+            } else if wildcard_match(pattern, host) {
+                matched = true;
+            }
+        }
+        matched
+    }
+}
+
+/// Case-insensitive matching of `text` against `pattern`, where `*`
+/// matches any sequence of characters and `?` matches a single character
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 #[derive(Clone, Debug)]
@@ -178,3 +274,39 @@ impl FromStr for SshParameters {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn host_profile_matching() {
+        let profile = SshHostProfile {
+            name: "prod".to_string(),
+            hosts: vec![
+                "*.prod.example.com".to_string(),
+                "db?".to_string(),
+                "!canary.prod.example.com".to_string(),
+            ],
+            overrides: wezterm_dynamic::Value::default(),
+        };
+
+        assert!(profile.matches("web1.prod.example.com"));
+        assert!(profile.matches("WEB1.Prod.Example.com"));
+        assert!(profile.matches("db1"));
+        assert!(!profile.matches("db10"));
+        assert!(!profile.matches("canary.prod.example.com"));
+        assert!(!profile.matches("staging.example.com"));
+    }
+
+    #[test]
+    fn remote_host_name() {
+        let mut dom = SshDomain {
+            remote_address: "example.com:2222".to_string(),
+            ..SshDomain::default()
+        };
+        assert_eq!(dom.remote_host_name(), "example.com");
+        dom.remote_address = "example.com".to_string();
+        assert_eq!(dom.remote_host_name(), "example.com");
+    }
+}
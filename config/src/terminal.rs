@@ -1,15 +1,20 @@
 //! Bridge our gui config into the terminal crate configuration
 
-use crate::{configuration, ConfigHandle, NewlineCanon};
+use crate::{
+    configuration, escape_sequence_permissions, ConfigHandle, NewlineCanon,
+    SuspiciousCharacterHighlight,
+};
 use std::sync::Mutex;
 use termwiz::cell::UnicodeVersion;
 use wezterm_term::color::ColorPalette;
-use wezterm_term::config::BidiMode;
+use wezterm_term::config::{BidiMode, EscapeSequencePermissions};
 
 #[derive(Debug)]
 pub struct TermConfig {
     config: Mutex<Option<ConfigHandle>>,
     client_palette: Mutex<Option<ColorPalette>>,
+    /// The domain of the pane, which selects its escape_sequence_policy
+    domain_name: Option<String>,
 }
 
 impl TermConfig {
@@ -17,6 +22,7 @@ impl TermConfig {
         Self {
             config: Mutex::new(None),
             client_palette: Mutex::new(None),
+            domain_name: None,
         }
     }
 
@@ -24,6 +30,27 @@ impl TermConfig {
         Self {
             config: Mutex::new(Some(config)),
             client_palette: Mutex::new(None),
+            domain_name: None,
+        }
+    }
+
+    /// Creates the configuration of a pane in the domain `domain_name`
+    pub fn for_domain(domain_name: &str) -> Self {
+        Self {
+            config: Mutex::new(None),
+            client_palette: Mutex::new(None),
+            domain_name: Some(domain_name.to_string()),
+        }
+    }
+
+    /// Returns a copy of this configuration for a pane in the domain
+    /// `domain_name`, so that a configuration that replaces the one
+    /// that the domain gave to the pane keeps its escape_sequence_policy
+    pub fn with_domain_name(&self, domain_name: Option<&str>) -> Self {
+        Self {
+            config: Mutex::new(self.config.lock().unwrap().clone()),
+            client_palette: Mutex::new(self.client_palette.lock().unwrap().clone()),
+            domain_name: domain_name.map(|name| name.to_string()),
         }
     }
 
@@ -71,7 +98,11 @@ impl wezterm_term::TerminalConfiguration for TermConfig {
     }
 
     fn enq_answerback(&self) -> String {
-        configuration().enq_answerback.clone()
+        self.configuration().enq_answerback.clone()
+    }
+
+    fn send_8bit_c1_controls(&self) -> bool {
+        self.configuration().send_8bit_c1_controls
     }
 
     fn enable_kitty_graphics(&self) -> bool {
@@ -120,6 +151,36 @@ impl wezterm_term::TerminalConfiguration for TermConfig {
         self.configuration().normalize_output_to_unicode_nfc
     }
 
+    fn alternate_screen_capture(&self) -> bool {
+        self.configuration().alternate_screen_capture
+    }
+
+    fn keep_hidden_format_characters(&self) -> bool {
+        let config = self.configuration();
+        config.suspicious_character_highlight != SuspiciousCharacterHighlight::None
+            || config.show_invisible_characters
+    }
+
+    fn record_whitespace(&self) -> bool {
+        self.configuration().show_whitespace
+    }
+
+    fn max_osc_len(&self) -> usize {
+        // Downloads are sent base64 encoded, preceded by their parameters
+        let config = self.configuration();
+        (config.max_download_size / 3 + 1)
+            .saturating_mul(4)
+            .saturating_add(4096)
+    }
+
+    fn escape_sequence_permissions(&self, host: Option<&str>) -> EscapeSequencePermissions {
+        escape_sequence_permissions(
+            &self.configuration().escape_sequence_policy,
+            self.domain_name.as_deref(),
+            host,
+        )
+    }
+
     fn bidi_mode(&self) -> BidiMode {
         let config = self.configuration();
         BidiMode {
@@ -128,3 +189,37 @@ impl wezterm_term::TerminalConfiguration for TermConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Config, EscapeSequencePolicy};
+    use std::sync::Arc;
+    use wezterm_term::TerminalConfiguration;
+
+    #[test]
+    fn domain_policy_after_reload() {
+        let mut config = Config::default_config();
+        config.escape_sequence_policy = vec![EscapeSequencePolicy {
+            domain: Some("SSH:prod".to_string()),
+            clipboard_write: Some(false),
+            ..Default::default()
+        }];
+        let handle = ConfigHandle {
+            config: Arc::new(config),
+            generation: 1,
+        };
+
+        // Reloading replaces the configuration that the domain gave
+        // to the pane with one that has the new config
+        let reloaded = TermConfig::with_config(handle.clone());
+        assert!(reloaded.escape_sequence_permissions(None).clipboard_write);
+
+        let reloaded = reloaded.with_domain_name(Some("SSH:prod"));
+        assert_eq!(reloaded.generation(), 1);
+        assert!(!reloaded.escape_sequence_permissions(None).clipboard_write);
+
+        let other = TermConfig::with_config(handle).with_domain_name(Some("local"));
+        assert!(other.escape_sequence_permissions(None).clipboard_write);
+    }
+}
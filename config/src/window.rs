@@ -7,3 +7,80 @@ pub enum WindowLevel {
     Normal = 0,
     AlwaysOnTop = 3,
 }
+
+/// A region of the screen to which a window can be snapped by the
+/// `SnapWindow` action, relative to the usable area of the screen
+/// that the window is on
+#[derive(Debug, Clone, Copy, ToDynamic, PartialEq, Eq, FromDynamic)]
+pub enum WindowSnapPosition {
+    LeftHalf,
+    RightHalf,
+    TopHalf,
+    BottomHalf,
+    TopLeftQuarter,
+    TopRightQuarter,
+    BottomLeftQuarter,
+    BottomRightQuarter,
+    /// Centers the window without changing its size
+    Center,
+}
+
+impl WindowSnapPosition {
+    /// Returns the region as (left, top, width, height) fractions of
+    /// the screen, or None for `Center`, which keeps the current size
+    pub fn region(self) -> Option<(f64, f64, f64, f64)> {
+        match self {
+            Self::LeftHalf => Some((0., 0., 0.5, 1.)),
+            Self::RightHalf => Some((0.5, 0., 0.5, 1.)),
+            Self::TopHalf => Some((0., 0., 1., 0.5)),
+            Self::BottomHalf => Some((0., 0.5, 1., 0.5)),
+            Self::TopLeftQuarter => Some((0., 0., 0.5, 0.5)),
+            Self::TopRightQuarter => Some((0.5, 0., 0.5, 0.5)),
+            Self::BottomLeftQuarter => Some((0., 0.5, 0.5, 0.5)),
+            Self::BottomRightQuarter => Some((0.5, 0.5, 0.5, 0.5)),
+            Self::Center => None,
+        }
+    }
+}
+
+/// Customizes the windows of GUI processes that were started with
+/// a particular window class, eg: via `wezterm start --class`
+#[derive(Debug, Clone, ToDynamic, FromDynamic)]
+pub struct WindowRule {
+    /// The window class to which this rule applies
+    pub class: String,
+    /// The position of the initial window, unless `--position`
+    /// was specified
+    #[dynamic(default)]
+    pub position: Option<crate::GuiPosition>,
+    /// The workspace to use, unless `--workspace` was specified
+    #[dynamic(default)]
+    pub workspace: Option<String>,
+    /// Config overrides that are applied to the windows, in the
+    /// same way as `window:set_config_overrides`
+    #[dynamic(default)]
+    pub config_overrides: wezterm_dynamic::Value,
+}
+
+/// Locks the windows that have been idle for a while, until the
+/// user has authenticated again
+#[derive(Debug, Clone, ToDynamic, FromDynamic)]
+pub struct IdleLock {
+    /// How long a window must go without input before it is locked
+    pub timeout_minutes: u64,
+    /// If not empty, only windows showing one of these
+    /// workspaces are locked
+    #[dynamic(default)]
+    pub workspaces: Vec<String>,
+    /// A command that authenticates the user and exits with status 0
+    /// on success, used in place of the authentication provided
+    /// by the operating system
+    #[dynamic(default)]
+    pub unlock_command: Option<Vec<String>>,
+}
+
+impl IdleLock {
+    pub fn applies_to_workspace(&self, workspace: &str) -> bool {
+        self.workspaces.is_empty() || self.workspaces.iter().any(|w| w == workspace)
+    }
+}
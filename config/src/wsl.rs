@@ -12,6 +12,10 @@ pub struct WslDomain {
     pub username: Option<String>,
     pub default_cwd: Option<PathBuf>,
     pub default_prog: Option<Vec<String>>,
+    /// Environment variables to set in the distribution, in addition
+    /// to those from the global set_environment_variables
+    #[dynamic(default)]
+    pub set_environment_variables: HashMap<String, String>,
 }
 impl_lua_conversion_dynamic!(WslDomain);
 
@@ -29,6 +33,7 @@ impl WslDomain {
                     username: None,
                     default_cwd: Some("~".into()),
                     default_prog: None,
+                    set_environment_variables: HashMap::new(),
                 });
             }
         }
@@ -206,9 +206,12 @@ fn register_lua_modules() {
         share_data::register,
         time_funcs::register,
         url_funcs::register,
+        ringlog::register,
     ] {
         config::lua::add_context_setup_func(func);
     }
+    config::assign_palette_from_image_func(color_funcs::palette_from_image);
+    config::assign_color_scheme_importer_func(color_funcs::schemes::import_scheme_file);
 }
 
 pub fn bootstrap() {
@@ -3,7 +3,12 @@
 //! a pretty logger on stderr.
 //! This allows other code to collect the ring buffer and display it
 //! within the application.
+//! The log levels can be adjusted at runtime via `set_log_level`,
+//! and the log file is rotated once it grows too large.
+use anyhow::{anyhow, Context};
 use chrono::prelude::*;
+use config::lua::get_or_create_module;
+use config::lua::mlua::{self, Lua};
 use env_logger::filter::{Builder as FilterBuilder, Filter};
 use log::{Level, LevelFilter, Log, Record};
 use std::collections::HashMap;
@@ -11,11 +16,198 @@ use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use termwiz::istty::IsTty;
 
+/// Once the log file grows beyond this size, it is rotated
+const MAX_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// How many rotated log files to keep, in addition to the current one
+const ROTATED_LOG_FILES: usize = 2;
+
 lazy_static::lazy_static! {
     static ref RINGS: Mutex<Rings> = Mutex::new(Rings::new());
+    static ref DIRECTIVES: Mutex<Directives> = Mutex::new(Directives::default());
+    static ref FILTER: RwLock<Filter> = RwLock::new(FilterBuilder::new().build());
+}
+
+/// The log level directives that are in effect.
+/// A directive with no module sets the default level for modules
+/// that have no more specific directive.
+#[derive(Default)]
+struct Directives {
+    levels: Vec<(Option<String>, LevelFilter)>,
+    /// As with `env_logger`, a spec may end with `/regex` to only log
+    /// messages that match the regex.  An empty regex removes it.
+    message_filter: Option<String>,
+}
+
+impl Directives {
+    /// Parses a spec in `WEZTERM_LOG` syntax, such as `info,mux=debug`
+    fn parse(spec: &str) -> anyhow::Result<Self> {
+        let (levels, message_filter) = Self::split(spec);
+        Ok(Self {
+            levels: levels
+                .map(Self::parse_item)
+                .collect::<anyhow::Result<_>>()?,
+            message_filter,
+        })
+    }
+
+    /// Parses a spec, skipping the items that are not valid rather
+    /// than rejecting the whole spec.
+    /// Returns the directives along with a description of each
+    /// problematic item.
+    fn parse_lenient(spec: &str) -> (Self, Vec<String>) {
+        let (levels, message_filter) = Self::split(spec);
+        let mut problems = vec![];
+        let levels = levels
+            .filter_map(|item| match Self::parse_item(item) {
+                Ok(directive) => Some(directive),
+                Err(err) => {
+                    problems.push(format!("{err:#}"));
+                    None
+                }
+            })
+            .collect();
+        (
+            Self {
+                levels,
+                message_filter,
+            },
+            problems,
+        )
+    }
+
+    /// Splits a spec into its items and its message filter
+    fn split(spec: &str) -> (impl Iterator<Item = &str>, Option<String>) {
+        let (levels, message_filter) = match spec.split_once('/') {
+            Some((levels, message_filter)) => (levels, Some(message_filter.to_string())),
+            None => (spec, None),
+        };
+        (
+            levels
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty()),
+            message_filter,
+        )
+    }
+
+    fn parse_item(item: &str) -> anyhow::Result<(Option<String>, LevelFilter)> {
+        match item.split_once('=') {
+            Some((module, level)) => {
+                let module = module.trim();
+                if module.is_empty() {
+                    return Err(anyhow!("missing module name in `{item}`"));
+                }
+                let level = level
+                    .trim()
+                    .parse::<LevelFilter>()
+                    .with_context(|| format!("invalid log level in `{item}`"))?;
+                Ok((Some(module.to_string()), level))
+            }
+            None => match item.parse::<LevelFilter>() {
+                Ok(level) => Ok((None, level)),
+                // A bare module name enables all of its logging
+                Err(_) => Ok((Some(item.to_string()), LevelFilter::Trace)),
+            },
+        }
+    }
+
+    /// Merges in the directives, replacing any existing directive
+    /// for the same module, and the message filter if one is given
+    fn apply(&mut self, directives: Self) {
+        for (module, level) in directives.levels {
+            match self.levels.iter_mut().find(|(m, _)| *m == module) {
+                Some(existing) => existing.1 = level,
+                None => self.levels.push((module, level)),
+            }
+        }
+        match directives.message_filter {
+            Some(filter) if filter.is_empty() => self.message_filter = None,
+            Some(filter) => self.message_filter = Some(filter),
+            None => {}
+        }
+    }
+
+    fn build(&self) -> Filter {
+        let mut filters = FilterBuilder::new();
+        for (module, level) in [
+            ("wgpu_core", LevelFilter::Error),
+            ("wgpu_hal", LevelFilter::Error),
+            ("gfx_backend_metal", LevelFilter::Error),
+        ] {
+            filters.filter_module(module, level);
+        }
+        for (module, level) in &self.levels {
+            filters.filter(module.as_deref(), *level);
+        }
+        if let Some(message_filter) = &self.message_filter {
+            // Let env_logger compile the regex; it warns if it is invalid
+            filters.parse(&format!("/{message_filter}"));
+        }
+        filters.build()
+    }
+
+    fn to_spec(&self) -> String {
+        let spec = self
+            .levels
+            .iter()
+            .map(|(module, level)| match module {
+                Some(module) => format!("{module}={}", level.as_str().to_lowercase()),
+                None => level.as_str().to_lowercase(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        match &self.message_filter {
+            Some(message_filter) => format!("{spec}/{message_filter}"),
+            None => spec,
+        }
+    }
+}
+
+fn install_filter(directives: &Directives) {
+    let filter = directives.build();
+    log::set_max_level(filter.filter());
+    *FILTER.write().unwrap() = filter;
+}
+
+/// Adjusts the log levels at runtime.
+/// `spec` uses the same syntax as the `WEZTERM_LOG` environment variable,
+/// for example `mux=debug` or `info,wezterm_gui=trace`.  The directives
+/// are merged with those already in effect, so `mux=debug` changes only
+/// the level of the `mux` module.
+/// Returns the complete set of directives that are now in effect.
+pub fn set_log_level(spec: &str) -> anyhow::Result<String> {
+    let parsed = Directives::parse(spec)?;
+    let mut directives = DIRECTIVES.lock().unwrap();
+    directives.apply(parsed);
+    install_filter(&directives);
+    let spec = directives.to_spec();
+    drop(directives);
+    log::info!("log level is now {spec}");
+    Ok(spec)
+}
+
+/// Returns the log level directives that are in effect,
+/// in `WEZTERM_LOG` syntax
+pub fn get_log_level() -> String {
+    DIRECTIVES.lock().unwrap().to_spec()
+}
+
+pub fn register(lua: &Lua) -> anyhow::Result<()> {
+    let wezterm_mod = get_or_create_module(lua, "wezterm")?;
+    wezterm_mod.set(
+        "set_log_level",
+        lua.create_function(|_, spec: String| {
+            set_log_level(&spec).map_err(|err| mlua::Error::external(format!("{err:#}")))
+        })?,
+    )?;
+    wezterm_mod.set(
+        "get_log_level",
+        lua.create_function(|_, _: ()| Ok(get_log_level()))?,
+    )?;
+    Ok(())
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -131,14 +323,35 @@ impl Rings {
     }
 }
 
+struct LogFile {
+    writer: BufWriter<File>,
+    size: u64,
+}
+
 struct Logger {
     file_name: PathBuf,
-    file: Mutex<Option<BufWriter<File>>>,
-    filter: Filter,
+    file: Mutex<Option<LogFile>>,
     padding: AtomicUsize,
     is_tty: bool,
 }
 
+impl Logger {
+    fn rotated_file_name(&self, n: usize) -> PathBuf {
+        let mut name = self.file_name.clone().into_os_string();
+        name.push(format!(".{n}"));
+        name.into()
+    }
+
+    /// Shifts the existing log files along by one, discarding the
+    /// eldest, so that a fresh file will be opened by the next write
+    fn rotate(&self) {
+        for n in (1..ROTATED_LOG_FILES).rev() {
+            let _ = std::fs::rename(self.rotated_file_name(n), self.rotated_file_name(n + 1));
+        }
+        let _ = std::fs::rename(&self.file_name, self.rotated_file_name(1));
+    }
+}
+
 impl Drop for Logger {
     fn drop(&mut self) {
         self.flush();
@@ -147,18 +360,18 @@ impl Drop for Logger {
 
 impl log::Log for Logger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        self.filter.enabled(metadata)
+        FILTER.read().unwrap().enabled(metadata)
     }
 
     fn flush(&self) {
         if let Some(file) = self.file.lock().unwrap().as_mut() {
-            let _ = file.flush();
+            let _ = file.writer.flush();
         }
         let _ = std::io::stderr().flush();
     }
 
     fn log(&self, record: &Record) {
-        if self.filter.matches(record) {
+        if FILTER.read().unwrap().matches(record) {
             RINGS.lock().unwrap().log(record);
             let ts = Local::now().format("%H:%M:%S%.3f").to_string();
             let level = record.level().as_str();
@@ -210,20 +423,29 @@ impl log::Log for Logger {
                     .create(true)
                     .open(&self.file_name)
                 {
-                    file.replace(BufWriter::new(f));
+                    let size = f.metadata().map(|m| m.len()).unwrap_or(0);
+                    file.replace(LogFile {
+                        writer: BufWriter::new(f),
+                        size,
+                    });
                 }
             }
-            if let Some(file) = file.as_mut() {
-                let _ = writeln!(
-                    file,
-                    "{}  {:6} {:padding$} > {}",
+            if let Some(log_file) = file.as_mut() {
+                let line = format!(
+                    "{}  {:6} {:padding$} > {}\n",
                     ts,
                     level,
                     target,
                     msg,
                     padding = padding
                 );
-                let _ = file.flush();
+                let _ = log_file.writer.write_all(line.as_bytes());
+                let _ = log_file.writer.flush();
+                log_file.size += line.len() as u64;
+                if log_file.size > MAX_LOG_FILE_SIZE {
+                    file.take();
+                    self.rotate();
+                }
             }
         }
     }
@@ -272,33 +494,26 @@ fn setup_pretty() -> (LevelFilter, Logger) {
         prune_old_logs();
     }
 
-    let log_file_name = config::RUNTIME_DIR.join(format!("{}-log-{}.txt", base_name, unsafe {
-        libc::getpid()
-    }));
-
-    let mut filters = FilterBuilder::new();
-    for (module, level) in [
-        ("wgpu_core", LevelFilter::Error),
-        ("wgpu_hal", LevelFilter::Error),
-        ("gfx_backend_metal", LevelFilter::Error),
-    ] {
-        filters.filter_module(module, level);
-    }
+    let log_file_name = config::log_file_path();
 
+    let mut directives = DIRECTIVES.lock().unwrap();
+    directives.levels.push((None, LevelFilter::Info));
     if let Ok(s) = std::env::var("WEZTERM_LOG") {
-        filters.parse(&s);
-    } else {
-        filters.filter_level(LevelFilter::Info);
+        let (parsed, problems) = Directives::parse_lenient(&s);
+        for problem in problems {
+            eprintln!("Ignoring part of WEZTERM_LOG={s}: {problem}");
+        }
+        directives.apply(parsed);
     }
-    let filter = filters.build();
+    let filter = directives.build();
     let max_level = filter.filter();
+    *FILTER.write().unwrap() = filter;
 
     (
         max_level,
         Logger {
             file_name: log_file_name,
             file: Mutex::new(None),
-            filter,
             padding: AtomicUsize::new(0),
             is_tty: std::io::stderr().is_tty(),
         },
@@ -311,3 +526,54 @@ pub fn setup_logger() {
         log::set_max_level(max_level);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_directives() {
+        let mut directives = Directives::default();
+        directives.apply(Directives::parse("info,mux=warn").unwrap());
+        directives.apply(Directives::parse(" mux=debug , wezterm_gui ").unwrap());
+        assert_eq!(
+            directives.to_spec(),
+            "info,mux=debug,wezterm_gui=trace".to_string()
+        );
+
+        directives.apply(Directives::parse("error").unwrap());
+        assert_eq!(
+            directives.to_spec(),
+            "error,mux=debug,wezterm_gui=trace".to_string()
+        );
+
+        assert!(Directives::parse("mux=loud").is_err());
+        assert!(Directives::parse("=debug").is_err());
+    }
+
+    #[test]
+    fn message_filter() {
+        let mut directives = Directives::default();
+        directives.apply(Directives::parse("info,mux=debug/pane [0-9]+").unwrap());
+        assert_eq!(
+            directives.to_spec(),
+            "info,mux=debug/pane [0-9]+".to_string()
+        );
+
+        // Changing a level keeps the filter, while an empty one removes it
+        directives.apply(Directives::parse("mux=warn").unwrap());
+        assert_eq!(
+            directives.to_spec(),
+            "info,mux=warn/pane [0-9]+".to_string()
+        );
+        directives.apply(Directives::parse("/").unwrap());
+        assert_eq!(directives.to_spec(), "info,mux=warn".to_string());
+    }
+
+    #[test]
+    fn lenient_parse() {
+        let (directives, problems) = Directives::parse_lenient("info,mux=loud,config=debug/reload");
+        assert_eq!(directives.to_spec(), "info,config=debug/reload".to_string());
+        assert_eq!(problems.len(), 1);
+    }
+}
@@ -12,13 +12,15 @@
 //! The results are cached to avoid recomputing on each
 //! evaluation of the config file.
 use crate::ColorWrap;
+use anyhow::Context;
 use config::lua::mlua::{self, Lua};
-use config::SrgbaTuple;
+use config::{Palette, RgbaColor, SrgbaTuple, TabBarColor, TabBarColors};
 use deltae::LabValue;
 use image::Pixel;
 use lru::LruCache;
 use luahelper::impl_lua_conversion_dynamic;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Mutex;
 use std::time::SystemTime;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
@@ -124,7 +126,7 @@ impl Default for ExtractColorParams {
 
 struct CachedAnalysis {
     modified: SystemTime,
-    colors: Vec<ColorWrap>,
+    colors: Vec<SrgbaTuple>,
 }
 
 lazy_static::lazy_static! {
@@ -182,29 +184,42 @@ pub fn extract_colors_from_image<'lua>(
     (file_name, params): (String, Option<ExtractColorParams>),
 ) -> mlua::Result<Vec<ColorWrap>> {
     let params = params.unwrap_or_default();
+    let colors = extract_colors(&file_name, &params)
+        .map_err(|err| mlua::Error::external(format!("{err:#}")))?;
 
-    let modified = std::fs::metadata(&file_name)
+    if colors.len() < params.num_colors {
+        return Err(mlua::Error::external(format!(
+            "extract_colors_from_image: only found {} out of requested {} colors.",
+            colors.len(),
+            params.num_colors
+        )));
+    }
+
+    Ok(colors
+        .into_iter()
+        .map(|tuple| ColorWrap(tuple.into()))
+        .collect())
+}
+
+/// Returns up to `params.num_colors` distinct colors from the image,
+/// most frequently used first
+fn extract_colors(file_name: &str, params: &ExtractColorParams) -> anyhow::Result<Vec<SrgbaTuple>> {
+    let modified = std::fs::metadata(file_name)
         .and_then(|m| m.modified())
-        .map_err(|err| {
-            mlua::Error::external(format!(
-                "error getting modified time for {file_name}: {err:#}"
-            ))
-        })?;
+        .with_context(|| format!("error getting modified time for {file_name}"))?;
 
     let mut cache = IMG_COLOR_CACHE.lock().unwrap();
-    if let Some(hit) = cache.get(&(file_name.clone(), params)) {
+    if let Some(hit) = cache.get(&(file_name.to_string(), *params)) {
         if hit.modified == modified {
             return Ok(hit.colors.clone());
         }
     }
 
     log::trace!("loading image {file_name}");
-    let im = image::io::Reader::open(&file_name)
-        .map_err(|err| mlua::Error::external(format!("{err:#} while loading {file_name}")))?
+    let im = image::io::Reader::open(file_name)
+        .with_context(|| format!("while loading {file_name}"))?
         .decode()
-        .map_err(|err| {
-            mlua::Error::external(format!("{err:#} while decoding image from {file_name}"))
-        })?
+        .with_context(|| format!("while decoding image from {file_name}"))?
         .resize(
             params.max_width.into(),
             params.max_height.into(),
@@ -279,7 +294,7 @@ pub fn extract_colors_from_image<'lua>(
     }
 
     log::trace!("converting colors to correct type");
-    let colors: Vec<ColorWrap> = result
+    let colors: Vec<SrgbaTuple> = result
         .into_iter()
         .map(|color| {
             let color = csscolorparser::Color::from_lab(
@@ -288,13 +303,12 @@ pub fn extract_colors_from_image<'lua>(
                 color.b.into(),
                 1.0,
             );
-            let tuple = SrgbaTuple(
+            SrgbaTuple(
                 color.r as f32,
                 color.g as f32,
                 color.b as f32,
                 color.a as f32,
-            );
-            ColorWrap(tuple.into())
+            )
         })
         .collect();
     log::trace!("colors are now correctly typed");
@@ -308,16 +322,8 @@ pub fn extract_colors_from_image<'lua>(
         );
     }
 
-    if colors.len() < params.num_colors {
-        return Err(mlua::Error::external(format!(
-            "extract_colors_from_image: only found {} out of requested {} colors.",
-            colors.len(),
-            params.num_colors
-        )));
-    }
-
     cache.put(
-        (file_name, params),
+        (file_name.to_string(), *params),
         CachedAnalysis {
             modified,
             colors: colors.clone(),
@@ -326,3 +332,121 @@ pub fn extract_colors_from_image<'lua>(
 
     Ok(colors)
 }
+
+/// Returns the hue `hue` rotated by `amount` of the way towards `target`
+fn hue_towards(hue: f64, target: f64, amount: f64) -> f64 {
+    let diff = (target - hue + 540.) % 360. - 180.;
+    (hue + diff * amount + 360.) % 360.
+}
+
+/// Derives a dark palette from the dominant colors of an image, so that
+/// the terminal harmonizes with it when it is used as the background.
+/// The most frequent color tints the background, foreground and the
+/// grey ANSI colors, the most colorful one is used as an accent for the
+/// cursor, selection and active tab, and the hues of the remaining
+/// ANSI colors are nudged towards the dominant hue.
+pub fn palette_from_image(path: &Path) -> anyhow::Result<Palette> {
+    let file_name = path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("{} is not a valid UTF-8 path", path.display()))?;
+    let params = ExtractColorParams {
+        num_colors: 8,
+        max_brightness: 100.,
+        ..Default::default()
+    };
+    let colors = extract_colors(file_name, &params)?;
+    let dominant = colors
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("found no colors in {file_name}"))?;
+    let accent = colors
+        .iter()
+        .copied()
+        .max_by(|a, b| {
+            let chroma = |c: &SrgbaTuple| {
+                let (_h, s, l, _a) = c.to_hsla();
+                s * (1. - (2. * l - 1.).abs())
+            };
+            chroma(a)
+                .partial_cmp(&chroma(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(dominant);
+
+    let (hue, saturation, _, _) = dominant.to_hsla();
+    let (accent_hue, accent_saturation, _, _) = accent.to_hsla();
+    let tint = |max_saturation: f64, lightness: f64| -> RgbaColor {
+        SrgbaTuple::from_hsla(hue, saturation.min(max_saturation), lightness, 1.).into()
+    };
+    let accent = |lightness: f64| -> RgbaColor {
+        SrgbaTuple::from_hsla(accent_hue, accent_saturation.max(0.4), lightness, 1.).into()
+    };
+    let ansi_color = |base_hue: f64, lightness: f64| -> RgbaColor {
+        SrgbaTuple::from_hsla(hue_towards(base_hue, hue, 0.15), 0.55, lightness, 1.).into()
+    };
+
+    // red, green, yellow, blue, magenta, cyan
+    const HUES: [f64; 6] = [0., 120., 60., 220., 300., 180.];
+    let mut ansi = [tint(0.3, 0.2); 8];
+    let mut brights = [tint(0.3, 0.4); 8];
+    for (idx, base_hue) in HUES.iter().enumerate() {
+        ansi[idx + 1] = ansi_color(*base_hue, 0.55);
+        brights[idx + 1] = ansi_color(*base_hue, 0.68);
+    }
+    ansi[7] = tint(0.1, 0.75);
+    brights[7] = tint(0.1, 0.95);
+
+    let background = tint(0.35, 0.08);
+    let foreground = tint(0.15, 0.88);
+    let inactive_tab = TabBarColor {
+        bg_color: tint(0.3, 0.12),
+        fg_color: tint(0.1, 0.6),
+        ..TabBarColor::default()
+    };
+    let inactive_tab_hover = TabBarColor {
+        bg_color: tint(0.3, 0.18),
+        fg_color: foreground,
+        ..TabBarColor::default()
+    };
+
+    Ok(Palette {
+        foreground: Some(foreground),
+        background: Some(background),
+        cursor_fg: Some(background),
+        cursor_bg: Some(accent(0.65)),
+        cursor_border: Some(accent(0.65)),
+        selection_fg: Some(foreground),
+        selection_bg: Some(accent(0.3)),
+        ansi: Some(ansi),
+        brights: Some(brights),
+        split: Some(accent(0.45)),
+        scrollbar_thumb: Some(tint(0.3, 0.25)),
+        tab_bar: Some(TabBarColors {
+            background: Some(tint(0.35, 0.05)),
+            active_tab: Some(TabBarColor {
+                bg_color: accent(0.35),
+                fg_color: foreground,
+                ..TabBarColor::default()
+            }),
+            inactive_tab: Some(inactive_tab.clone()),
+            inactive_tab_hover: Some(inactive_tab_hover.clone()),
+            new_tab: Some(inactive_tab),
+            new_tab_hover: Some(inactive_tab_hover),
+            ..TabBarColors::default()
+        }),
+        ..Palette::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hue_rotation() {
+        assert_eq!(hue_towards(0., 100., 0.5), 50.);
+        // Rotates the short way around the color wheel
+        assert_eq!(hue_towards(10., 350., 0.5), 0.);
+        assert_eq!(hue_towards(350., 10., 0.5), 0.);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::schemes::base16::Base16Scheme;
+use crate::schemes::kitty::KittyTheme;
 use crate::schemes::sexy::Sexy;
 use config::lua::mlua::{self, Lua, MetaMethod, UserData, UserDataMethods, UserDataRef};
 use config::lua::{get_or_create_module, get_or_create_sub_module};
@@ -7,6 +8,8 @@ use config::{ColorSchemeFile, ColorSchemeMetaData, Gradient, Palette, RgbaColor,
 mod image_colors;
 pub mod schemes;
 
+pub use image_colors::palette_from_image;
+
 #[derive(Clone)]
 pub struct ColorWrap(RgbaColor);
 
@@ -118,6 +121,13 @@ pub fn register(lua: &Lua) -> anyhow::Result<()> {
         "extract_colors_from_image",
         lua.create_function(image_colors::extract_colors_from_image)?,
     )?;
+    color.set(
+        "scheme_from_image",
+        lua.create_function(|_, file_name: String| {
+            image_colors::palette_from_image(std::path::Path::new(&file_name))
+                .map_err(|err| mlua::Error::external(format!("{err:#}")))
+        })?,
+    )?;
     color.set(
         "get_default_colors",
         lua.create_function(|_, _: ()| {
@@ -165,6 +175,14 @@ pub fn register(lua: &Lua) -> anyhow::Result<()> {
             Ok((scheme.colors, scheme.metadata))
         })?,
     )?;
+    color.set(
+        "load_kitty_scheme",
+        lua.create_function(|_, file_name: String| {
+            let scheme = KittyTheme::load_file(file_name)
+                .map_err(|err| mlua::Error::external(format!("{err:#}")))?;
+            Ok((scheme.colors, scheme.metadata))
+        })?,
+    )?;
 
     let wezterm_mod = get_or_create_module(lua, "wezterm")?;
     wezterm_mod.set("gradient_colors", lua.create_function(gradient_colors)?)?;
@@ -4,54 +4,78 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 
+/// A base16 or base24 scheme, in either the original format, where the
+/// colors are top level keys, or the tinted-theming format, where they
+/// are nested under `palette`.
 #[derive(Deserialize, Debug)]
-#[allow(non_snake_case, dead_code)]
 pub struct Base16Scheme {
+    #[serde(alias = "name")]
     scheme: String,
+    #[serde(default)]
     author: String,
-    base00: String,
-    base01: String,
-    base02: String,
-    base03: String,
-    base04: String,
-    base05: String,
-    base06: String,
-    base07: String,
-    base08: String,
-    base09: String,
-    base0A: String,
-    base0B: String,
-    base0C: String,
-    base0D: String,
-    base0E: String,
-    base0F: String,
+    #[serde(default)]
+    palette: HashMap<String, String>,
+    #[serde(flatten)]
+    other: HashMap<String, serde_yaml::Value>,
 }
 
 impl Base16Scheme {
+    fn color(&self, key: &str) -> anyhow::Result<Option<RgbaColor>> {
+        let value = match self.palette.get(key) {
+            Some(value) => Some(value.to_string()),
+            None => match self.other.get(key) {
+                Some(serde_yaml::Value::String(value)) => Some(value.to_string()),
+                // An unquoted value such as 181818 is parsed as a number
+                Some(serde_yaml::Value::Number(value)) => Some(format!("{value:0>6}")),
+                _ => None,
+            },
+        };
+        match value {
+            Some(value) => {
+                // The original format omits the leading `#`
+                let value = if value.starts_with('#') {
+                    value.to_string()
+                } else {
+                    format!("#{value}")
+                };
+                Ok(Some(RgbaColor::try_from(value)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn required_color(&self, key: &str) -> anyhow::Result<RgbaColor> {
+        self.color(key)?
+            .ok_or_else(|| anyhow::anyhow!("scheme has no {key} color"))
+    }
+
     pub fn load_file<P: AsRef<Path>>(path: P) -> anyhow::Result<ColorSchemeFile>
     where
         P: std::fmt::Debug,
     {
         let data = std::fs::read_to_string(&path).context(format!("read file {path:?}"))?;
+        Self::from_yaml_str(&data)
+    }
 
-        let scheme: Self = serde_yaml::from_str(&data)?;
+    pub fn from_yaml_str(data: &str) -> anyhow::Result<ColorSchemeFile> {
+        let scheme: Self = serde_yaml::from_str(data)?;
 
-        let base_0 = RgbaColor::try_from(scheme.base00)?;
-        let base_1 = RgbaColor::try_from(scheme.base01)?;
-        let base_2 = RgbaColor::try_from(scheme.base02)?;
-        let base_3 = RgbaColor::try_from(scheme.base03)?;
-        let base_4 = RgbaColor::try_from(scheme.base04)?;
-        let base_5 = RgbaColor::try_from(scheme.base05)?;
-        let base_6 = RgbaColor::try_from(scheme.base06)?;
-        let base_7 = RgbaColor::try_from(scheme.base07)?;
-        let base_8 = RgbaColor::try_from(scheme.base08)?;
-        let base_9 = RgbaColor::try_from(scheme.base09)?;
-        let base_a = RgbaColor::try_from(scheme.base0A)?;
-        let base_b = RgbaColor::try_from(scheme.base0B)?;
-        let base_c = RgbaColor::try_from(scheme.base0C)?;
-        let base_d = RgbaColor::try_from(scheme.base0D)?;
-        let base_e = RgbaColor::try_from(scheme.base0E)?;
-        let base_f = RgbaColor::try_from(scheme.base0F)?;
+        let base_0 = scheme.required_color("base00")?;
+        let base_1 = scheme.required_color("base01")?;
+        let base_2 = scheme.required_color("base02")?;
+        let base_3 = scheme.required_color("base03")?;
+        let base_4 = scheme.required_color("base04")?;
+        let base_5 = scheme.required_color("base05")?;
+        let base_6 = scheme.required_color("base06")?;
+        let base_7 = scheme.required_color("base07")?;
+        let base_8 = scheme.required_color("base08")?;
+        let base_9 = scheme.required_color("base09")?;
+        let base_a = scheme.required_color("base0A")?;
+        let base_b = scheme.required_color("base0B")?;
+        let base_c = scheme.required_color("base0C")?;
+        let base_d = scheme.required_color("base0D")?;
+        let base_e = scheme.required_color("base0E")?;
+        let base_f = scheme.required_color("base0F")?;
 
         let mut indexed = HashMap::new();
         indexed.insert(16, base_9);
@@ -61,6 +85,21 @@ impl Base16Scheme {
         indexed.insert(20, base_4);
         indexed.insert(21, base_6);
 
+        // base24 adds distinct bright colors; base16 reuses the normal ones
+        let bright = |key: &str, fallback: RgbaColor| -> anyhow::Result<RgbaColor> {
+            Ok(scheme.color(key)?.unwrap_or(fallback))
+        };
+        let brights = [
+            base_3,
+            bright("base12", base_8)?,
+            bright("base14", base_b)?,
+            bright("base13", base_a)?,
+            bright("base16", base_d)?,
+            bright("base17", base_e)?,
+            bright("base15", base_c)?,
+            base_7,
+        ];
+
         Ok(ColorSchemeFile {
             colors: Palette {
                 foreground: Some(base_5),
@@ -73,15 +112,13 @@ impl Base16Scheme {
                 ansi: Some([
                     base_0, base_8, base_b, base_a, base_d, base_e, base_c, base_5,
                 ]),
-                brights: Some([
-                    base_3, base_8, base_b, base_a, base_d, base_e, base_c, base_7,
-                ]),
+                brights: Some(brights),
                 indexed,
                 ..Default::default()
             },
             metadata: ColorSchemeMetaData {
                 name: Some(scheme.scheme),
-                author: Some(scheme.author),
+                author: Some(scheme.author).filter(|author| !author.is_empty()),
                 origin_url: None,
                 wezterm_version: None,
                 aliases: vec![],
@@ -89,3 +126,82 @@ impl Base16Scheme {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base16_formats() {
+        let original = r#"
+scheme: "Example"
+author: "Someone"
+base00: "181818"
+base01: "282828"
+base02: "383838"
+base03: "585858"
+base04: "b8b8b8"
+base05: "d8d8d8"
+base06: "e8e8e8"
+base07: "f8f8f8"
+base08: "ab4642"
+base09: "dc9656"
+base0A: "f7ca88"
+base0B: "a1b56c"
+base0C: "86c1b9"
+base0D: "7cafc2"
+base0E: "ba8baf"
+base0F: "a16946"
+"#;
+        let scheme = Base16Scheme::from_yaml_str(original).unwrap();
+        assert_eq!(scheme.metadata.name.as_deref(), Some("Example"));
+        let brights = scheme.colors.brights.unwrap();
+        assert_eq!(
+            brights[1],
+            RgbaColor::try_from("#ab4642".to_string()).unwrap()
+        );
+
+        let tinted = r##"
+system: "base24"
+name: "Example 24"
+author: "Someone"
+variant: "dark"
+palette:
+  base00: "#181818"
+  base01: "#282828"
+  base02: "#383838"
+  base03: "#585858"
+  base04: "#b8b8b8"
+  base05: "#d8d8d8"
+  base06: "#e8e8e8"
+  base07: "#f8f8f8"
+  base08: "#ab4642"
+  base09: "#dc9656"
+  base0A: "#f7ca88"
+  base0B: "#a1b56c"
+  base0C: "#86c1b9"
+  base0D: "#7cafc2"
+  base0E: "#ba8baf"
+  base0F: "#a16946"
+  base10: "#101010"
+  base11: "#080808"
+  base12: "#ff5555"
+  base13: "#ffff55"
+  base14: "#55ff55"
+  base15: "#55ffff"
+  base16: "#5555ff"
+  base17: "#ff55ff"
+"##;
+        let scheme = Base16Scheme::from_yaml_str(tinted).unwrap();
+        assert_eq!(scheme.metadata.name.as_deref(), Some("Example 24"));
+        let brights = scheme.colors.brights.unwrap();
+        assert_eq!(
+            brights[1],
+            RgbaColor::try_from("#ff5555".to_string()).unwrap()
+        );
+        assert_eq!(
+            brights[4],
+            RgbaColor::try_from("#5555ff".to_string()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::Context;
+use config::{ColorSchemeFile, ColorSchemeMetaData, Palette, RgbaColor, TabBarColor, TabBarColors};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A kitty theme, as found in <https://github.com/kovidgoyal/kitty-themes>.
+/// These are kitty config files that contain only color settings, with
+/// the metadata in `## name: value` comments.
+pub struct KittyTheme;
+
+impl KittyTheme {
+    pub fn load_file<P: AsRef<Path>>(path: P) -> anyhow::Result<ColorSchemeFile>
+    where
+        P: std::fmt::Debug,
+    {
+        let data = std::fs::read_to_string(&path).context(format!("read file {path:?}"))?;
+        let mut scheme = Self::from_conf_str(&data)?;
+        if scheme.metadata.name.is_none() {
+            scheme.metadata.name = path
+                .as_ref()
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string());
+        }
+        Ok(scheme)
+    }
+
+    pub fn from_conf_str(data: &str) -> anyhow::Result<ColorSchemeFile> {
+        let mut metadata = ColorSchemeMetaData::default();
+        let mut settings = HashMap::new();
+
+        for line in data.lines() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix("##") {
+                if let Some((key, value)) = comment.split_once(':') {
+                    let value = value.trim().to_string();
+                    match key.trim() {
+                        "name" => metadata.name = Some(value),
+                        "author" => metadata.author = Some(value),
+                        "upstream" => metadata.origin_url = Some(value),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(char::is_whitespace) {
+                settings.insert(key, value.trim());
+            }
+        }
+
+        let color = |key: &str| -> anyhow::Result<Option<RgbaColor>> {
+            match settings.get(key) {
+                // kitty uses `none` and `background` to mean that a
+                // color isn't set, and is derived from the others
+                Some(&"none") | Some(&"background") | None => Ok(None),
+                Some(value) => Ok(Some(
+                    RgbaColor::try_from(value.to_string())
+                        .with_context(|| format!("parsing {key}"))?,
+                )),
+            }
+        };
+
+        let mut ansi = [RgbaColor::default(); 8];
+        let mut brights = [RgbaColor::default(); 8];
+        let mut indexed = HashMap::new();
+        for idx in 0..=255u8 {
+            let value = match color(&format!("color{idx}"))? {
+                Some(value) => value,
+                None if idx < 16 => anyhow::bail!("theme has no color{idx}"),
+                None => continue,
+            };
+            match idx {
+                0..=7 => ansi[idx as usize] = value,
+                8..=15 => brights[idx as usize - 8] = value,
+                _ => {
+                    indexed.insert(idx, value);
+                }
+            }
+        }
+
+        let foreground = color("foreground")?;
+        let background = color("background")?;
+        let tab = |fg: &str, bg: &str| -> anyhow::Result<Option<TabBarColor>> {
+            match (color(fg)?, color(bg)?) {
+                (Some(fg_color), Some(bg_color)) => Ok(Some(TabBarColor {
+                    fg_color,
+                    bg_color,
+                    ..TabBarColor::default()
+                })),
+                _ => Ok(None),
+            }
+        };
+        let active_tab = tab("active_tab_foreground", "active_tab_background")?;
+        let inactive_tab = tab("inactive_tab_foreground", "inactive_tab_background")?;
+        let tab_bar_background = color("tab_bar_background")?;
+        let tab_bar =
+            if active_tab.is_some() || inactive_tab.is_some() || tab_bar_background.is_some() {
+                Some(TabBarColors {
+                    background: tab_bar_background,
+                    active_tab,
+                    new_tab: inactive_tab.clone(),
+                    inactive_tab,
+                    ..TabBarColors::default()
+                })
+            } else {
+                None
+            };
+
+        Ok(ColorSchemeFile {
+            colors: Palette {
+                foreground,
+                background,
+                cursor_bg: color("cursor")?,
+                cursor_border: color("cursor")?,
+                cursor_fg: color("cursor_text_color")?.or(background),
+                selection_fg: color("selection_foreground")?,
+                selection_bg: color("selection_background")?,
+                ansi: Some(ansi),
+                brights: Some(brights),
+                indexed,
+                tab_bar,
+                ..Default::default()
+            },
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn kitty_theme() {
+        let theme = r#"
+# vim:ft=kitty
+
+## name: Example
+## author: Someone
+## upstream: https://example.com/example.conf
+
+foreground   #c5c8c6
+background   #1d1f21
+cursor       #c5c8c6
+cursor_text_color background
+selection_foreground none
+selection_background #373b41
+
+color0  #1d1f21
+color1  #cc6666
+color2  #b5bd68
+color3  #f0c674
+color4  #81a2be
+color5  #b294bb
+color6  #8abeb7
+color7  #c5c8c6
+color8  #666666
+color9  #d54e53
+color10 #b9ca4a
+color11 #e7c547
+color12 #7aa6da
+color13 #c397d8
+color14 #70c0b1
+color15 #eaeaea
+color16 #de935f
+
+active_tab_foreground   #1d1f21
+active_tab_background   #81a2be
+"#;
+        let scheme = KittyTheme::from_conf_str(theme).unwrap();
+        assert_eq!(scheme.metadata.name.as_deref(), Some("Example"));
+        assert_eq!(scheme.metadata.author.as_deref(), Some("Someone"));
+        let background = RgbaColor::try_from("#1d1f21".to_string()).unwrap();
+        assert_eq!(scheme.colors.background, Some(background));
+        assert_eq!(scheme.colors.cursor_fg, Some(background));
+        assert_eq!(scheme.colors.selection_fg, None);
+        assert_eq!(
+            scheme.colors.brights.unwrap()[1],
+            RgbaColor::try_from("#d54e53".to_string()).unwrap()
+        );
+        assert_eq!(
+            scheme.colors.indexed.get(&16),
+            Some(&RgbaColor::try_from("#de935f".to_string()).unwrap())
+        );
+        let tab_bar = scheme.colors.tab_bar.unwrap();
+        assert_eq!(
+            tab_bar.active_tab.unwrap().bg_color,
+            RgbaColor::try_from("#81a2be".to_string()).unwrap()
+        );
+        assert!(tab_bar.inactive_tab.is_none());
+    }
+}
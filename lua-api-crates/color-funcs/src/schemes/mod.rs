@@ -1,4 +1,19 @@
+use config::ColorSchemeFile;
+use std::path::Path;
+
 pub mod base16;
 pub mod gogh;
 pub mod iterm2;
+pub mod kitty;
 pub mod sexy;
+
+/// Converts a color scheme file in a foreign format into a
+/// `ColorSchemeFile`, based on its extension.
+/// Returns `Ok(None)` if the format isn't one that can be imported.
+pub fn import_scheme_file(path: &Path) -> anyhow::Result<Option<ColorSchemeFile>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml") | Some("yml") => Ok(Some(base16::Base16Scheme::load_file(path)?)),
+        Some("conf") => Ok(Some(kitty::KittyTheme::load_file(path)?)),
+        _ => Ok(None),
+    }
+}
@@ -47,6 +47,14 @@ pub fn register(lua: &Lua) -> anyhow::Result<()> {
         })?,
     )?;
 
+    mux_mod.set(
+        "get_services",
+        lua.create_function(|lua, _: ()| {
+            let mux = get_mux()?;
+            luahelper::to_lua(lua, mux.service_statuses())
+        })?,
+    )?;
+
     mux_mod.set(
         "set_active_workspace",
         lua.create_function(|_, workspace: String| {
@@ -178,6 +186,7 @@ struct CommandBuilderFrag {
     #[dynamic(default)]
     set_environment_variables: HashMap<String, String>,
 }
+impl_lua_conversion_dynamic!(CommandBuilderFrag);
 
 impl CommandBuilderFrag {
     fn to_command_builder(&self) -> (Option<CommandBuilder>, Option<String>) {
@@ -190,6 +199,14 @@ impl CommandBuilderFrag {
                 builder.cwd(cwd);
             }
             (Some(builder), None)
+        } else if !self.set_environment_variables.is_empty() {
+            // Run the default program of the domain, with the
+            // additional environment
+            let mut builder = CommandBuilder::new_default_prog();
+            for (k, v) in self.set_environment_variables.iter() {
+                builder.env(k, v);
+            }
+            (Some(builder), self.cwd.clone())
         } else {
             (None, self.cwd.clone())
         }
@@ -248,6 +265,7 @@ impl SpawnWindow {
                 self.domain,
                 cmd_builder,
                 cwd,
+                None, // optional exit_behavior override
                 size,
                 None,
                 self.workspace.unwrap_or_else(|| mux.active_workspace()),
@@ -299,6 +317,7 @@ impl SpawnTab {
                 self.domain,
                 cmd_builder,
                 cwd,
+                None, // optional exit_behavior override
                 size,
                 pane,
                 String::new(),
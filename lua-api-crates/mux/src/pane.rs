@@ -1,4 +1,5 @@
 use super::*;
+use config::CharacterEncoding;
 use luahelper::{dynamic_to_lua_value, from_lua, to_lua};
 use mlua::Value;
 use mux::pane::CachePolicy;
@@ -18,7 +19,7 @@ impl MuxPane {
             .ok_or_else(|| mlua::Error::external(format!("pane id {} not found in mux", self.0)))
     }
 
-    fn get_text_from_semantic_zone(&self, zone: SemanticZone) -> mlua::Result<String> {
+    pub fn get_text_from_semantic_zone(&self, zone: SemanticZone) -> mlua::Result<String> {
         let mux = get_mux()?;
         let pane = self.resolve(&mux)?;
 
@@ -96,6 +97,22 @@ impl UserData for MuxPane {
             args.unwrap_or_default().run(this).await
         });
 
+        methods.add_async_method(
+            "respawn",
+            |_, this, args: Option<CommandBuilderFrag>| async move {
+                let mux = get_mux()?;
+                let pane = this.resolve(&mux)?;
+                let domain = mux.get_domain(pane.domain_id()).ok_or_else(|| {
+                    mlua::Error::external(format!("domain of pane {} not found", this.0))
+                })?;
+                let (command, command_dir) = args.unwrap_or_default().to_command_builder();
+                domain
+                    .respawn_pane(this.0, command, command_dir)
+                    .await
+                    .map_err(|e| mlua::Error::external(format!("{:#}", e)))
+            },
+        );
+
         methods.add_method("send_paste", |_, this, text: String| {
             let mux = get_mux()?;
             let pane = this.resolve(&mux)?;
@@ -190,6 +207,44 @@ impl UserData for MuxPane {
             Ok(pane.copy_user_vars())
         });
 
+        methods.add_method("get_tags", |_, this, _: ()| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(mux.get_pane_tags(pane.pane_id()))
+        });
+
+        methods.add_method("has_tag", |_, this, tag: String| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(mux.pane_has_tag(pane.pane_id(), &tag))
+        });
+
+        methods.add_method("add_tag", |_, this, tag: String| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            mux.add_pane_tag(pane.pane_id(), &tag)
+                .map_err(mlua::Error::external)
+        });
+
+        methods.add_method("remove_tag", |_, this, tag: String| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            mux.remove_pane_tag(pane.pane_id(), &tag);
+            Ok(())
+        });
+
+        methods.add_method("get_title_origin", |_, this, _: ()| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(pane.get_title_origin())
+        });
+
+        methods.add_method("get_user_var_origin", |_, this, name: String| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(pane.get_user_var_origin(&name))
+        });
+
         methods.add_method("has_unseen_output", |_, this, _: ()| {
             let mux = get_mux()?;
             let pane = this.resolve(&mux)?;
@@ -425,6 +480,46 @@ impl UserData for MuxPane {
             let pane = this.resolve(&mux)?;
             Ok(pane.tty_name())
         });
+
+        methods.add_method(
+            "set_alternate_screen_capture",
+            |_, this, enabled: Option<bool>| {
+                let mux = get_mux()?;
+                let pane = this.resolve(&mux)?;
+                pane.set_alt_screen_capture(enabled);
+                Ok(())
+            },
+        );
+
+        methods.add_method("get_encoding", |_, this, _: ()| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(pane.get_encoding().name())
+        });
+
+        methods.add_method("set_encoding", |_, this, name: String| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            let encoding = CharacterEncoding::try_from(name).map_err(mlua::Error::external)?;
+            pane.set_encoding(encoding).map_err(mlua::Error::external)
+        });
+
+        methods.add_method("set_vt_inspector_enabled", |_, this, enabled: bool| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            mux::vtinspect::set_enabled(pane.pane_id(), enabled);
+            Ok(())
+        });
+
+        methods.add_method(
+            "get_vt_inspector_entries",
+            |lua, this, since: Option<usize>| {
+                let mux = get_mux()?;
+                let pane = this.resolve(&mux)?;
+                let entries = mux::vtinspect::get_entries(pane.pane_id(), since.unwrap_or(0));
+                to_lua(lua, entries)
+            },
+        );
     }
 }
 
@@ -453,6 +548,7 @@ impl SplitPane {
         let source = SplitSource::Spawn {
             command,
             command_dir,
+            exit_behavior: None,
         };
 
         let size = if self.size == 0.0 {
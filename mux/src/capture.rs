@@ -0,0 +1,33 @@
+//! Allows the gui to provide rendered images of panes, so that they
+//! can be requested via the mux protocol by `wezterm cli screenshot`.
+//! The mux has no renderer of its own, so capturing is only possible
+//! when the gui is running in the same process.
+use crate::pane::PaneId;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type CaptureFuture = Pin<Box<dyn Future<Output = anyhow::Result<Vec<u8>>>>>;
+type CaptureHandler = Arc<dyn Fn(PaneId) -> CaptureFuture + Send + Sync>;
+
+lazy_static::lazy_static! {
+    static ref HANDLER: Mutex<Option<CaptureHandler>> = Mutex::new(None);
+}
+
+/// Registers the function that renders a pane and encodes it as PNG.
+/// The function is called on the main thread.
+pub fn set_capture_handler<F>(handler: F)
+where
+    F: Fn(PaneId) -> CaptureFuture + Send + Sync + 'static,
+{
+    HANDLER.lock().replace(Arc::new(handler));
+}
+
+/// Renders the specified pane and returns it encoded as PNG
+pub async fn capture_pane_png(pane_id: PaneId) -> anyhow::Result<Vec<u8>> {
+    let handler = HANDLER.lock().clone().ok_or_else(|| {
+        anyhow::anyhow!("capturing panes requires the wezterm gui to be running in this process")
+    })?;
+    handler(pane_id).await
+}
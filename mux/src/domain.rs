@@ -5,15 +5,19 @@
 //! container or actually remote, running on the other end
 //! of an ssh session somewhere.
 
+use crate::encoding::{EncodingWriter, SharedEncoding};
 use crate::localpane::LocalPane;
 use crate::pane::{alloc_pane_id, Pane, PaneId};
 use crate::tab::{SplitRequest, Tab, TabId};
 use crate::window::WindowId;
-use crate::Mux;
+use crate::{Mux, MuxNotification};
 use anyhow::{bail, Context, Error};
 use async_trait::async_trait;
 use config::keyassignment::{SpawnCommand, SpawnTabDomain};
-use config::{configuration, ExecDomain, SerialDomain, ValueOrFunc, WslDomain};
+use config::{
+    configuration, CharacterEncoding, ExecDomain, ExitBehavior, SerialDomain, SlowPaste,
+    ValueOrFunc, WslDomain,
+};
 use downcast_rs::{impl_downcast, Downcast};
 use parking_lot::Mutex;
 use portable_pty::{native_pty_system, CommandBuilder, ExitStatus, MasterPty, PtySize, PtySystem};
@@ -42,6 +46,8 @@ pub enum SplitSource {
     Spawn {
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        /// Overrides the exit_behavior config for the new pane
+        exit_behavior: Option<ExitBehavior>,
     },
     MovePane(PaneId),
 }
@@ -54,10 +60,11 @@ pub trait Domain: Downcast + Send + Sync {
         size: TerminalSize,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        exit_behavior: Option<ExitBehavior>,
         window: WindowId,
     ) -> anyhow::Result<Arc<Tab>> {
         let pane = self
-            .spawn_pane(size, command, command_dir)
+            .spawn_pane(size, command, command_dir, exit_behavior)
             .await
             .context("spawn")?;
 
@@ -102,8 +109,9 @@ pub trait Domain: Downcast + Send + Sync {
             SplitSource::Spawn {
                 command,
                 command_dir,
+                exit_behavior,
             } => {
-                self.spawn_pane(split_size.second, command, command_dir)
+                self.spawn_pane(split_size.second, command, command_dir, exit_behavior)
                     .await?
             }
             SplitSource::MovePane(src_pane_id) => {
@@ -131,13 +139,34 @@ pub trait Domain: Downcast + Send + Sync {
         Ok(pane)
     }
 
+    /// Spawn a new command in a pane of its own.
+    /// `exit_behavior` overrides the exit_behavior config for the pane;
+    /// it is applied before the output of the pane is read, so that it
+    /// takes effect even if the command exits right away.
     async fn spawn_pane(
         &self,
         size: TerminalSize,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        exit_behavior: Option<ExitBehavior>,
     ) -> anyhow::Result<Arc<dyn Pane>>;
 
+    /// Runs a command in an existing pane of this domain, once the
+    /// process that was running in it has exited, keeping its scrollback.
+    /// If `command` is None, the command that was previously running
+    /// in the pane is run again.
+    async fn respawn_pane(
+        &self,
+        _pane_id: PaneId,
+        _command: Option<CommandBuilder>,
+        _command_dir: Option<String>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "respawning panes is not supported by domain {}",
+            self.domain_name()
+        );
+    }
+
     /// The mux will call this method on the domain of the pane that
     /// is being moved to give the domain a chance to handle the movement.
     /// If this method returns Ok(None), then the mux will handle the
@@ -193,6 +222,12 @@ pub struct LocalDomain {
     pty_system: Mutex<Box<dyn PtySystem + Send>>,
     id: DomainId,
     name: String,
+    /// Overrides the default_pane_encoding config for this domain
+    encoding: Option<CharacterEncoding>,
+    /// Overrides the enq_answerback config for this domain
+    answerback: Option<String>,
+    /// Overrides the slow_paste config for this domain
+    slow_paste: Option<SlowPaste>,
 }
 
 impl LocalDomain {
@@ -208,6 +243,12 @@ impl LocalDomain {
             .cloned()
     }
 
+    /// Returns the character encoding of new panes in this domain
+    fn pane_encoding(&self) -> CharacterEncoding {
+        self.encoding
+            .unwrap_or_else(|| configuration().default_pane_encoding)
+    }
+
     fn resolve_wsl_domain(&self) -> Option<WslDomain> {
         config::configuration()
             .wsl_domains()
@@ -222,6 +263,9 @@ impl LocalDomain {
             pty_system: Mutex::new(pty_system),
             id,
             name: name.to_string(),
+            encoding: None,
+            answerback: None,
+            slow_paste: None,
         }
     }
 
@@ -240,7 +284,12 @@ impl LocalDomain {
             serial.set_baud_rate(serial::BaudRate::from_speed(baud));
         }
         let pty_system = Box::new(serial);
-        Ok(Self::with_pty_system(&serial_domain.name, pty_system))
+        Ok(Self {
+            encoding: serial_domain.encoding,
+            answerback: serial_domain.answerback,
+            slow_paste: serial_domain.slow_paste,
+            ..Self::with_pty_system(&serial_domain.name, pty_system)
+        })
     }
 
     #[cfg(unix)]
@@ -324,6 +373,7 @@ impl LocalDomain {
                 set_environment_variables,
                 cwd,
                 position: None,
+                exit_behavior: None,
             };
 
             let spawn_command = config::with_lua_config_on_main_thread(|lua| async {
@@ -361,7 +411,7 @@ impl LocalDomain {
             if let Some(cwd) = &spawn_command.cwd {
                 cmd.cwd(cwd);
             }
-        } else if Path::new("/.flatpak-info").exists() {
+        } else if is_flatpak() {
             // We're running inside a flatpak sandbox.
             // Run the command outside the sandbox via flatpak-spawn
             let mut args = vec![
@@ -399,8 +449,10 @@ impl LocalDomain {
                 let shell = String::from_utf8_lossy(&output.stdout);
 
                 args.push(shell.trim().to_string());
-                // Assume we can pass `-l` for a login shell
-                args.push("-l".to_string());
+                if configuration().login_shell {
+                    // Assume we can pass `-l` for a login shell
+                    args.push("-l".to_string());
+                }
             }
 
             // Avoid setting up the controlling tty as that is not compatible
@@ -436,6 +488,21 @@ impl LocalDomain {
         Ok(())
     }
 
+    fn describe_command(&self, cmd: &CommandBuilder) -> String {
+        let command_line = cmd
+            .as_unix_command_line()
+            .unwrap_or_else(|err| format!("error rendering command line: {:?}", err));
+        format!(
+            "\"{}\" in domain \"{}\"",
+            if command_line.is_empty() {
+                cmd.get_shell()
+            } else {
+                command_line
+            },
+            self.name
+        )
+    }
+
     async fn build_command(
         &self,
         command: Option<CommandBuilder>,
@@ -443,24 +510,59 @@ impl LocalDomain {
         pane_id: PaneId,
     ) -> anyhow::Result<CommandBuilder> {
         let config = configuration();
+        let wsl = self.resolve_wsl_domain();
+        let exec_domain = self.resolve_exec_domain();
+        let (default_prog, default_cwd, domain_env) = match (&wsl, &exec_domain) {
+            (Some(wsl), _) => (
+                wsl.default_prog.as_ref(),
+                wsl.default_cwd.as_ref(),
+                Some(&wsl.set_environment_variables),
+            ),
+            (None, Some(ed)) => (
+                ed.default_prog.as_ref().or(config.default_prog.as_ref()),
+                config.default_cwd.as_ref(),
+                Some(&ed.set_environment_variables),
+            ),
+            (None, None) => (
+                config.default_prog.as_ref(),
+                config.default_cwd.as_ref(),
+                None,
+            ),
+        };
+
         let mut cmd = match command {
             Some(mut cmd) => {
-                config.apply_cmd_defaults(&mut cmd, config.default_cwd.as_ref());
+                // The environment that was specified when spawning takes
+                // precedence over that of the domain and the config
+                let spawn_env: Vec<(String, String)> = cmd
+                    .iter_extra_env_as_str()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                if cmd.is_default_prog() {
+                    if let Some(prog) = default_prog {
+                        cmd.get_argv_mut().extend(prog.iter().map(OsString::from));
+                    }
+                }
+                config.apply_cmd_defaults(&mut cmd, default_cwd);
+                if let Some(env) = domain_env {
+                    apply_domain_environment(&mut cmd, env, wsl.is_some());
+                }
+                for (k, v) in spawn_env {
+                    cmd.env(k, v);
+                }
                 cmd
             }
             None => {
-                let wsl = self.resolve_wsl_domain();
-                config.build_prog(
-                    None,
-                    wsl.as_ref()
-                        .map(|wsl| wsl.default_prog.as_ref())
-                        .unwrap_or(config.default_prog.as_ref()),
-                    wsl.as_ref()
-                        .map(|wsl| wsl.default_cwd.as_ref())
-                        .unwrap_or(config.default_cwd.as_ref()),
-                )?
+                let mut cmd = config.build_prog(None, default_prog, default_cwd)?;
+                if let Some(env) = domain_env {
+                    apply_domain_environment(&mut cmd, env, wsl.is_some());
+                }
+                cmd
             }
         };
+        if wsl.is_none() && exec_domain.is_none() && !is_flatpak() {
+            apply_login_shell(&mut cmd, config.login_shell);
+        }
         if let Some(dir) = command_dir {
             cmd.cwd(dir);
         }
@@ -473,20 +575,65 @@ impl LocalDomain {
     }
 }
 
+fn is_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// Sets the environment variables of a domain on `cmd`.
+/// For WSL domains, their names are added to WSLENV so that
+/// they are passed through to the distribution.
+fn apply_domain_environment(cmd: &mut CommandBuilder, env: &HashMap<String, String>, wsl: bool) {
+    if env.is_empty() {
+        return;
+    }
+    for (k, v) in env {
+        cmd.env(k, v);
+    }
+    if wsl {
+        let mut wsl_env = cmd
+            .get_env("WSLENV")
+            .and_then(|v| v.to_str())
+            .unwrap_or("")
+            .to_string();
+        for k in env.keys() {
+            if !wsl_env
+                .split(':')
+                .any(|name| name.split('/').next() == Some(k.as_str()))
+            {
+                if !wsl_env.is_empty() {
+                    wsl_env.push(':');
+                }
+                wsl_env.push_str(k);
+            }
+        }
+        cmd.env("WSLENV", wsl_env);
+    }
+}
+
+/// The shell is run as a login shell when `cmd` is the default program.
+/// If that isn't wanted, run the shell explicitly instead.
+fn apply_login_shell(cmd: &mut CommandBuilder, login_shell: bool) {
+    if cfg!(unix) && !login_shell && cmd.is_default_prog() {
+        let shell = cmd.get_shell();
+        cmd.get_argv_mut().push(shell.into());
+    }
+}
+
 /// Allows sharing the writer between the Pane and the Terminal.
 /// This could potentially be eliminated in the future if we can
 /// teach the Pane impl to reference the writer in the Termninal,
 /// but the Pane trait returns a RefMut and that makes it a bit
 /// awkward at the moment.
+/// The data written is encoded in the encoding of the pane.
 #[derive(Clone)]
 pub(crate) struct WriterWrapper {
-    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    writer: Arc<Mutex<EncodingWriter>>,
 }
 
 impl WriterWrapper {
-    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+    pub fn new(writer: Box<dyn Write + Send>, encoding: SharedEncoding) -> Self {
         Self {
-            writer: Arc::new(Mutex::new(writer)),
+            writer: Arc::new(Mutex::new(EncodingWriter::new(writer, encoding))),
         }
     }
 }
@@ -505,6 +652,10 @@ impl std::io::Write for WriterWrapper {
 /// the spawn attempt failed in order to hold the pane open
 pub(crate) struct FailedSpawnPty {
     inner: Mutex<Box<dyn MasterPty>>,
+    /// The command and directory that were requested, so that
+    /// the spawn can be retried, eg: after fixing the config
+    pub command: Option<CommandBuilder>,
+    pub command_dir: Option<String>,
 }
 
 impl portable_pty::MasterPty for FailedSpawnPty {
@@ -570,6 +721,52 @@ impl portable_pty::ChildKiller for FailedProcessSpawn {
     }
 }
 
+/// Renders the error from a failed spawn attempt, along with the
+/// command that was being spawned and the ways to recover from it
+fn spawn_error_banner(err: &Error, cmd: Option<&CommandBuilder>) -> String {
+    fn link(url: Option<url::Url>, label: &str) -> String {
+        match url {
+            Some(url) => format!("\x1b]8;;{url}\x1b\\\x1b[4m{label}\x1b[24m\x1b]8;;\x1b\\"),
+            None => label.to_string(),
+        }
+    }
+
+    let mut banner = String::new();
+    let mut causes = err.chain();
+    if let Some(top) = causes.next() {
+        banner.push_str(&format!("\x1b[1;31m⚠️  Unable to spawn: {top}\x1b[0m\r\n"));
+    }
+    for cause in causes {
+        banner.push_str(&format!("   caused by: {cause}\r\n"));
+    }
+    if let Some(cmd) = cmd {
+        banner.push_str("\r\n");
+        if let Ok(command_line) = cmd.as_unix_command_line() {
+            if !command_line.is_empty() {
+                banner.push_str(&format!("   \x1b[2mcommand:\x1b[0m   {command_line}\r\n"));
+            }
+        }
+        if let Some(cwd) = cmd.get_cwd() {
+            banner.push_str(&format!(
+                "   \x1b[2mdirectory:\x1b[0m {}\r\n",
+                cwd.to_string_lossy()
+            ));
+        }
+    }
+    banner.push_str(&format!(
+        "\r\n   Press Enter to retry  \u{2022}  {}  \u{2022}  {}\r\n",
+        link(
+            url::Url::from_file_path(config::config_file_path()).ok(),
+            "Edit config"
+        ),
+        link(
+            url::Url::from_file_path(config::log_file_path()).ok(),
+            "Open log"
+        ),
+    ));
+    banner
+}
+
 #[async_trait(?Send)]
 impl Domain for LocalDomain {
     async fn spawn_pane(
@@ -577,35 +774,35 @@ impl Domain for LocalDomain {
         size: TerminalSize,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        exit_behavior: Option<ExitBehavior>,
     ) -> anyhow::Result<Arc<dyn Pane>> {
         let pane_id = alloc_pane_id();
-        let cmd = self
-            .build_command(command, command_dir, pane_id)
-            .await
-            .context("build_command")?;
         let pair = self
             .pty_system
             .lock()
             .openpty(crate::terminal_size_to_pty_size(size)?)?;
 
-        let command_line = cmd
-            .as_unix_command_line()
-            .unwrap_or_else(|err| format!("error rendering command line: {:?}", err));
-        let command_description = format!(
-            "\"{}\" in domain \"{}\"",
-            if command_line.is_empty() {
-                cmd.get_shell()
-            } else {
-                command_line
-            },
-            self.name
-        );
-        let child_result = pair.slave.spawn_command(cmd);
-        let mut writer = WriterWrapper::new(pair.master.take_writer()?);
+        let (cmd, child_result) = match self
+            .build_command(command.clone(), command_dir.clone(), pane_id)
+            .await
+            .context("build_command")
+        {
+            Ok(cmd) => {
+                let child_result = pair.slave.spawn_command(cmd.clone());
+                (Some(cmd), child_result)
+            }
+            Err(err) => (None, Err(err)),
+        };
+        let command_description = match &cmd {
+            Some(cmd) => self.describe_command(cmd),
+            None => format!("in domain \"{}\"", self.name),
+        };
+        let encoding = SharedEncoding::new(self.pane_encoding());
+        let writer = WriterWrapper::new(pair.master.take_writer()?, encoding.clone());
 
         let mut terminal = wezterm_term::Terminal::new(
             size,
-            std::sync::Arc::new(config::TermConfig::new()),
+            std::sync::Arc::new(config::TermConfig::for_domain(&self.name)),
             "WezTerm",
             config::wezterm_version(),
             Box::new(writer.clone()),
@@ -613,20 +810,32 @@ impl Domain for LocalDomain {
         if self.is_conpty() {
             terminal.enable_conpty_quirks();
         }
+        terminal.set_enq_answerback(self.answerback.clone());
+
+        let pane: Arc<dyn Pane> = match (child_result, cmd) {
+            (Ok(child), Some(cmd)) => {
+                let pane = LocalPane::new(
+                    pane_id,
+                    terminal,
+                    child,
+                    pair.master,
+                    Box::new(writer),
+                    encoding,
+                    self.id,
+                    command_description,
+                );
+                pane.set_command(cmd);
+                pane.set_slow_paste(self.slow_paste);
+                Arc::new(pane)
+            }
+            (child_result, cmd) => {
+                let err = child_result
+                    .err()
+                    .unwrap_or_else(|| anyhow::anyhow!("no command to spawn"));
+                log::error!("Failed to spawn {command_description}: {err:#}");
 
-        let pane: Arc<dyn Pane> = match child_result {
-            Ok(child) => Arc::new(LocalPane::new(
-                pane_id,
-                terminal,
-                child,
-                pair.master,
-                Box::new(writer),
-                self.id,
-                command_description,
-            )),
-            Err(err) => {
                 // Show the error to the user in the new pane
-                write!(writer, "{err:#}").ok();
+                terminal.advance_bytes(spawn_error_banner(&err, cmd.as_ref()));
 
                 // and return a dummy pane that has exited
                 Arc::new(LocalPane::new(
@@ -635,13 +844,17 @@ impl Domain for LocalDomain {
                     Box::new(FailedProcessSpawn {}),
                     Box::new(FailedSpawnPty {
                         inner: Mutex::new(pair.master),
+                        command,
+                        command_dir,
                     }),
                     Box::new(writer),
+                    encoding,
                     self.id,
                     command_description,
                 ))
             }
         };
+        pane.set_exit_behavior(exit_behavior);
 
         let mux = Mux::get();
         mux.add_pane(&pane)?;
@@ -649,6 +862,69 @@ impl Domain for LocalDomain {
         Ok(pane)
     }
 
+    async fn respawn_pane(
+        &self,
+        pane_id: PaneId,
+        command: Option<CommandBuilder>,
+        command_dir: Option<String>,
+    ) -> anyhow::Result<()> {
+        let mux = Mux::get();
+        let pane = mux
+            .get_pane(pane_id)
+            .ok_or_else(|| anyhow::anyhow!("pane {pane_id} not found"))?;
+        let local_pane = pane
+            .downcast_ref::<LocalPane>()
+            .ok_or_else(|| anyhow::anyhow!("pane {pane_id} is not a local pane"))?;
+        if local_pane.is_process_running() {
+            bail!("the process in pane {pane_id} is still running");
+        }
+
+        // When retrying a spawn that failed, start over from the command
+        // that was originally requested, as the config that it is built
+        // from may since have been fixed
+        let (command, command_dir) = match (command, command_dir) {
+            (None, None) => match local_pane.failed_spawn_request() {
+                Some(request) => request,
+                None => (None, None),
+            },
+            request => request,
+        };
+
+        let cmd = match (command, local_pane.get_command()) {
+            (None, Some(previous)) if command_dir.is_none() => Ok(previous),
+            (command, _) => self
+                .build_command(command, command_dir, pane_id)
+                .await
+                .context("build_command"),
+        };
+
+        let pair = self
+            .pty_system
+            .lock()
+            .openpty(crate::terminal_size_to_pty_size(
+                local_pane.terminal_size(),
+            )?)?;
+        let spawned = cmd.and_then(|cmd| {
+            let child = pair.slave.spawn_command(cmd.clone())?;
+            Ok((child, cmd))
+        });
+        let (child, cmd) = match spawned {
+            Ok(spawned) => spawned,
+            Err(err) => {
+                local_pane.show_spawn_error(&spawn_error_banner(&err, None));
+                mux.notify(MuxNotification::PaneOutput(pane_id));
+                return Err(err);
+            }
+        };
+        let command_description = self.describe_command(&cmd);
+        let writer = pair.master.take_writer()?;
+
+        local_pane.respawn(child, pair.master, writer, cmd, command_description);
+        mux.start_reading_from_pane(&pane, None)?;
+        mux.notify(MuxNotification::PaneOutput(pane_id));
+        Ok(())
+    }
+
     fn domain_id(&self) -> DomainId {
         self.id
     }
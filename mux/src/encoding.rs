@@ -0,0 +1,426 @@
+//! Transcodes the data exchanged with the process running in a pane
+//! between UTF-8 and the legacy character encoding used by that process.
+//!
+//! Output from the process is decoded before it reaches the escape
+//! sequence parser, and input to it, which includes keyboard input,
+//! pastes and replies to queries, is encoded as it is written to the pty.
+//!
+//! The ECMA-35 "designate other coding system" sequences are
+//! recognized in the output, so that a program can switch between UTF-8
+//! (`ESC % G`) and the legacy encoding of the pane (`ESC % @`).
+use config::CharacterEncoding;
+use encoding_rs::{CoderResult, EncoderResult, Encoding};
+use parking_lot::Mutex;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::sync::Arc;
+
+const ESC: u8 = 0x1b;
+
+/// The encoding of a pane, shared between the pane, the writer that
+/// encodes its input and the thread that decodes its output
+#[derive(Clone, Default)]
+pub struct SharedEncoding(Arc<Mutex<CharacterEncoding>>);
+
+impl SharedEncoding {
+    pub fn new(encoding: CharacterEncoding) -> Self {
+        Self(Arc::new(Mutex::new(encoding)))
+    }
+
+    pub fn get(&self) -> CharacterEncoding {
+        *self.0.lock()
+    }
+
+    pub fn set(&self, encoding: CharacterEncoding) {
+        *self.0.lock() = encoding;
+    }
+}
+
+/// Returns the encoding_rs implementation of `encoding`.
+/// UTF-8 needs no transcoding, and ISO-8859-1 is handled by the callers
+/// as encoding_rs follows the WHATWG in treating it as windows-1252,
+/// which assigns printable characters to the C1 control range.
+fn implementation(encoding: CharacterEncoding) -> Option<&'static Encoding> {
+    match encoding {
+        CharacterEncoding::Utf8 | CharacterEncoding::Iso8859_1 => None,
+        CharacterEncoding::Iso8859_2 => Some(encoding_rs::ISO_8859_2),
+        CharacterEncoding::Iso8859_3 => Some(encoding_rs::ISO_8859_3),
+        CharacterEncoding::Iso8859_4 => Some(encoding_rs::ISO_8859_4),
+        CharacterEncoding::Iso8859_5 => Some(encoding_rs::ISO_8859_5),
+        CharacterEncoding::Iso8859_6 => Some(encoding_rs::ISO_8859_6),
+        CharacterEncoding::Iso8859_7 => Some(encoding_rs::ISO_8859_7),
+        CharacterEncoding::Iso8859_8 => Some(encoding_rs::ISO_8859_8),
+        CharacterEncoding::Iso8859_10 => Some(encoding_rs::ISO_8859_10),
+        CharacterEncoding::Iso8859_13 => Some(encoding_rs::ISO_8859_13),
+        CharacterEncoding::Iso8859_14 => Some(encoding_rs::ISO_8859_14),
+        CharacterEncoding::Iso8859_15 => Some(encoding_rs::ISO_8859_15),
+        CharacterEncoding::Iso8859_16 => Some(encoding_rs::ISO_8859_16),
+        CharacterEncoding::Koi8R => Some(encoding_rs::KOI8_R),
+        CharacterEncoding::Koi8U => Some(encoding_rs::KOI8_U),
+        CharacterEncoding::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+        CharacterEncoding::EucJp => Some(encoding_rs::EUC_JP),
+        CharacterEncoding::Gbk => Some(encoding_rs::GBK),
+        CharacterEncoding::Gb18030 => Some(encoding_rs::GB18030),
+        CharacterEncoding::Big5 => Some(encoding_rs::BIG5),
+        CharacterEncoding::EucKr => Some(encoding_rs::EUC_KR),
+    }
+}
+
+fn is_c1_control(c: char) -> bool {
+    ('\u{80}'..='\u{9f}').contains(&c)
+}
+
+/// Encodes `text` in `encoding`, appending it to `out`.
+/// C1 controls, which the terminal uses for its replies when 8-bit
+/// controls are enabled, are sent as their single byte values.
+/// Characters that can't be represented are replaced by `?`.
+fn encode(encoding: CharacterEncoding, text: &str, out: &mut Vec<u8>) {
+    if encoding.is_utf8() {
+        out.extend_from_slice(text.as_bytes());
+        return;
+    }
+
+    let mut rest = text;
+    while let Some(idx) = rest.find(is_c1_control) {
+        encode_text(encoding, &rest[..idx], out);
+        let mut chars = rest[idx..].chars();
+        if let Some(c) = chars.next() {
+            out.push(c as u32 as u8);
+        }
+        rest = chars.as_str();
+    }
+    encode_text(encoding, rest, out);
+}
+
+/// Encodes `text` in `encoding`, appending it to `out`.
+/// Characters that can't be represented are replaced by `?`.
+fn encode_text(encoding: CharacterEncoding, text: &str, out: &mut Vec<u8>) {
+    let implementation = match implementation(encoding) {
+        Some(implementation) => implementation,
+        None if encoding.is_utf8() => {
+            out.extend_from_slice(text.as_bytes());
+            return;
+        }
+        None => {
+            out.extend(text.chars().map(|c| u8::try_from(c).unwrap_or(b'?')));
+            return;
+        }
+    };
+
+    let mut encoder = implementation.new_encoder();
+    let mut src = text;
+    loop {
+        let start = out.len();
+        let room = encoder
+            .max_buffer_length_from_utf8_without_replacement(src.len())
+            .unwrap_or(src.len());
+        out.resize(start + room.max(4), 0);
+        let (result, read, written) =
+            encoder.encode_from_utf8_without_replacement(src, &mut out[start..], true);
+        out.truncate(start + written);
+        src = &src[read..];
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => {}
+            EncoderResult::Unmappable(_) => out.push(b'?'),
+        }
+    }
+}
+
+/// Wraps the writer of a pty, encoding the UTF-8 written to it
+/// in the encoding of its pane
+pub struct EncodingWriter {
+    inner: Box<dyn Write + Send>,
+    encoding: SharedEncoding,
+    /// An incomplete UTF-8 sequence at the end of the last write
+    pending: Vec<u8>,
+}
+
+impl EncodingWriter {
+    pub fn new(inner: Box<dyn Write + Send>, encoding: SharedEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            pending: vec![],
+        }
+    }
+}
+
+impl Write for EncodingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let encoding = self.encoding.get();
+        if encoding.is_utf8() && self.pending.is_empty() {
+            return self.inner.write(buf);
+        }
+
+        self.pending.extend_from_slice(buf);
+        let data = std::mem::take(&mut self.pending);
+        let mut rest = data.as_slice();
+        let mut encoded = vec![];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    encode(encoding, text, &mut encoded);
+                    break;
+                }
+                Err(err) => {
+                    let (valid, invalid) = rest.split_at(err.valid_up_to());
+                    encode(encoding, &String::from_utf8_lossy(valid), &mut encoded);
+                    match err.error_len() {
+                        // Not everything written is text; eg: the legacy
+                        // mouse reporting encodes coordinates as raw bytes.
+                        // Pass those through unchanged
+                        Some(len) => {
+                            encoded.extend_from_slice(&invalid[..len]);
+                            rest = &invalid[len..];
+                        }
+                        // The rest of the sequence will arrive in a later write
+                        None => {
+                            self.pending = invalid.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.inner.write_all(&encoded)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes the output of the process in a pane to UTF-8
+pub struct OutputDecoder {
+    /// A possibly incomplete DOCS sequence at the end of the last read
+    pending: Vec<u8>,
+    decoder: Option<(CharacterEncoding, encoding_rs::Decoder)>,
+    /// The encoding selected by `ESC % @`
+    legacy: CharacterEncoding,
+}
+
+impl Default for OutputDecoder {
+    fn default() -> Self {
+        Self {
+            pending: vec![],
+            decoder: None,
+            legacy: CharacterEncoding::Iso8859_1,
+        }
+    }
+}
+
+impl OutputDecoder {
+    /// Decodes `data`, which is in `encoding`, to UTF-8.
+    /// If `data` selects a different encoding, `encoding` is updated
+    /// to reflect it.
+    pub fn decode<'a>(
+        &mut self,
+        encoding: &mut CharacterEncoding,
+        data: &'a [u8],
+    ) -> Cow<'a, [u8]> {
+        if !encoding.is_utf8() {
+            self.legacy = *encoding;
+        }
+
+        if encoding.is_utf8()
+            && self.pending.is_empty()
+            && data.last() != Some(&ESC)
+            && !data.windows(2).any(|w| w == [ESC, b'%'])
+        {
+            return Cow::Borrowed(data);
+        }
+
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(data);
+
+        let mut out = vec![];
+        let mut start = 0;
+        let mut pos = 0;
+        while let Some(offset) = input[pos..].iter().position(|&b| b == ESC) {
+            let esc = pos + offset;
+            let selected = match &input[esc + 1..] {
+                [] | [b'%'] => {
+                    // Wait for the rest of the sequence
+                    self.decode_segment(*encoding, &input[start..esc], false, &mut out);
+                    self.pending = input[esc..].to_vec();
+                    return Cow::Owned(out);
+                }
+                [b'%', b'G', ..] => CharacterEncoding::Utf8,
+                [b'%', b'@', ..] => self.legacy,
+                _ => {
+                    pos = esc + 1;
+                    continue;
+                }
+            };
+            self.decode_segment(*encoding, &input[start..esc], true, &mut out);
+            *encoding = selected;
+            start = esc + 3;
+            pos = start;
+        }
+        self.decode_segment(*encoding, &input[start..], false, &mut out);
+        Cow::Owned(out)
+    }
+
+    /// Decodes `data` from `encoding`, appending it to `out`.
+    /// When `last` is true, any incomplete character at the end of
+    /// `data` is decoded as a replacement character rather than being
+    /// held for the next call.
+    fn decode_segment(
+        &mut self,
+        encoding: CharacterEncoding,
+        data: &[u8],
+        last: bool,
+        out: &mut Vec<u8>,
+    ) {
+        let implementation = match implementation(encoding) {
+            Some(implementation) => implementation,
+            None if encoding.is_utf8() => {
+                out.extend_from_slice(data);
+                return;
+            }
+            None => {
+                let mut buf = [0u8; 4];
+                for &b in data {
+                    out.extend_from_slice(char::from(b).encode_utf8(&mut buf).as_bytes());
+                }
+                return;
+            }
+        };
+
+        let mut decoder = match self.decoder.take() {
+            Some((current, decoder)) if current == encoding => decoder,
+            _ => implementation.new_decoder_without_bom_handling(),
+        };
+
+        let mut src = data;
+        loop {
+            let start = out.len();
+            let room = decoder
+                .max_utf8_buffer_length(src.len())
+                .unwrap_or(src.len());
+            out.resize(start + room.max(4), 0);
+            let (result, read, written, _had_errors) =
+                decoder.decode_to_utf8(src, &mut out[start..], last);
+            out.truncate(start + written);
+            src = &src[read..];
+            if result == CoderResult::InputEmpty {
+                break;
+            }
+        }
+
+        if !last {
+            self.decoder = Some((encoding, decoder));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct SharedVec(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedVec {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn decode_all(encoding: &mut CharacterEncoding, chunks: &[&[u8]]) -> String {
+        let mut decoder = OutputDecoder::default();
+        let mut out = vec![];
+        for chunk in chunks {
+            out.extend_from_slice(&decoder.decode(encoding, chunk));
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn decode_legacy() {
+        let mut encoding = CharacterEncoding::ShiftJis;
+        // "日本" split across reads in the middle of a character
+        assert_eq!(
+            decode_all(&mut encoding, &[b"\x93\xfa\x96", b"\x7b\x1b[0m"]),
+            "日本\x1b[0m"
+        );
+
+        let mut encoding = CharacterEncoding::Iso8859_1;
+        assert_eq!(decode_all(&mut encoding, &[b"caf\xe9"]), "café");
+
+        let mut encoding = CharacterEncoding::Iso8859_2;
+        assert_eq!(decode_all(&mut encoding, &[b"\xb1"]), "ą");
+    }
+
+    #[test]
+    fn decode_utf8_is_unchanged() {
+        let mut decoder = OutputDecoder::default();
+        let mut encoding = CharacterEncoding::Utf8;
+        let data = "héllo\x1b[1m".as_bytes();
+        assert!(matches!(
+            decoder.decode(&mut encoding, data),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn designate_coding_system() {
+        let mut encoding = CharacterEncoding::Gbk;
+        // GBK, then UTF-8, then back to GBK, with a sequence split across reads
+        assert_eq!(
+            decode_all(
+                &mut encoding,
+                &[b"\xc4\xe3\x1b%G\xe4\xbd\xa0\x1b", b"%@\xc4\xe3"]
+            ),
+            "你你你"
+        );
+        assert_eq!(encoding, CharacterEncoding::Gbk);
+
+        let mut encoding = CharacterEncoding::Utf8;
+        assert_eq!(decode_all(&mut encoding, &[b"\x1b%@\xe9"]), "é");
+        assert_eq!(encoding, CharacterEncoding::Iso8859_1);
+
+        // Other escape sequences pass through
+        let mut encoding = CharacterEncoding::Utf8;
+        assert_eq!(
+            decode_all(&mut encoding, &[b"\x1b%5\x1b[m"]),
+            "\x1b%5\x1b[m"
+        );
+    }
+
+    #[test]
+    fn encode_input() {
+        let output = Arc::new(Mutex::new(vec![]));
+        let encoding = SharedEncoding::new(CharacterEncoding::ShiftJis);
+        let mut writer =
+            EncodingWriter::new(Box::new(SharedVec(Arc::clone(&output))), encoding.clone());
+
+        let text = "日本€".as_bytes();
+        // Split in the middle of a character
+        writer.write_all(&text[..4]).unwrap();
+        writer.write_all(&text[4..]).unwrap();
+        // Raw bytes, as used by legacy mouse reporting
+        writer.write_all(b"\x1b[M\xa0").unwrap();
+        assert_eq!(output.lock().as_slice(), b"\x93\xfa\x96\x7b?\x1b[M\xa0");
+
+        // 8-bit controls
+        output.lock().clear();
+        writer
+            .write_all("\u{9b}?65c\u{90}1$r\u{9c}".as_bytes())
+            .unwrap();
+        assert_eq!(output.lock().as_slice(), b"\x9b?65c\x901$r\x9c");
+
+        output.lock().clear();
+        encoding.set(CharacterEncoding::Utf8);
+        writer.write_all("日".as_bytes()).unwrap();
+        assert_eq!(output.lock().as_slice(), "日".as_bytes());
+    }
+}
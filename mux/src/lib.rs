@@ -3,7 +3,7 @@ use crate::pane::{CachePolicy, Pane, PaneId};
 use crate::tab::{SplitRequest, Tab, TabId};
 use crate::window::{Window, WindowId};
 use anyhow::{anyhow, Context, Error};
-use config::keyassignment::SpawnTabDomain;
+use config::keyassignment::{SpawnCommand, SpawnTabDomain};
 use config::{configuration, ExitBehavior, GuiPosition};
 use domain::{Domain, DomainId, DomainState, SplitSource};
 use filedescriptor::{poll, pollfd, socketpair, AsRawSocketDescriptor, FileDescriptor, POLLIN};
@@ -16,9 +16,10 @@ use parking_lot::{
 };
 use percent_encoding::percent_decode_str;
 use portable_pty::{CommandBuilder, ExitStatus, PtySize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
 use std::io::{Read, Write};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::thread;
@@ -26,29 +27,163 @@ use std::time::{Duration, Instant};
 use termwiz::escape::csi::{DecPrivateMode, DecPrivateModeCode, Device, Mode};
 use termwiz::escape::{Action, CSI};
 use thiserror::*;
+use wezterm_dynamic::FromDynamic;
 use wezterm_term::{Clipboard, ClipboardSelection, DownloadHandler, TerminalSize};
 #[cfg(windows)]
 use winapi::um::winsock2::{SOL_SOCKET, SO_RCVBUF, SO_SNDBUF};
 
 pub mod activity;
+pub mod capture;
 pub mod client;
 pub mod connui;
 pub mod domain;
+pub mod encoding;
 pub mod localpane;
+pub mod mirror;
 pub mod pane;
 pub mod renderable;
+pub mod services;
 pub mod ssh;
 pub mod tab;
 pub mod termwiztermtab;
 pub mod tmux;
 pub mod tmux_commands;
 mod tmux_pty;
+pub mod vtinspect;
 pub mod window;
+pub mod windowconfig;
+mod zmodem;
 
 use crate::activity::Activity;
 
 pub const DEFAULT_WORKSPACE: &str = "default";
 
+/// Programs running in a pane set its tags by setting this user var
+/// to the tags, separated by commas or spaces
+pub const PANE_TAGS_USER_VAR: &str = "WEZTERM_PANE_TAGS";
+
+fn is_pane_tag_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+/// Checks that `tag` may be used as the tag of a pane.  Tags are case
+/// sensitive, and may not be empty nor contain commas or whitespace.
+pub fn validate_pane_tag(tag: &str) -> anyhow::Result<()> {
+    if tag.is_empty() || tag.contains(is_pane_tag_separator) {
+        anyhow::bail!("invalid tag {tag:?}: tags may not be empty nor contain commas or spaces");
+    }
+    Ok(())
+}
+
+/// Returns the tags in `tags`, separated by commas or spaces
+pub fn parse_pane_tags(tags: &str) -> BTreeSet<String> {
+    tags.split(is_pane_tag_separator)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| tag.to_string())
+        .collect()
+}
+
+/// Describes a spawn as the `SpawnCommand` that is passed to the
+/// `spawn-command-filter` event, or returns None if its program,
+/// environment or directory is not utf8
+fn spawn_command_for_filter(
+    domain_name: &str,
+    command: &Option<CommandBuilder>,
+    command_dir: &Option<String>,
+) -> Option<SpawnCommand> {
+    let mut args = vec![];
+    let mut set_environment_variables = HashMap::new();
+    if let Some(cmd) = command {
+        if !cmd.is_default_prog() {
+            for arg in cmd.get_argv() {
+                args.push(arg.to_str()?.to_string());
+            }
+        }
+        for (k, v) in cmd.iter_extra_env_as_str() {
+            set_environment_variables.insert(k.to_string(), v.to_string());
+        }
+    }
+    Some(SpawnCommand {
+        label: None,
+        domain: SpawnTabDomain::DomainName(domain_name.to_string()),
+        args: if args.is_empty() { None } else { Some(args) },
+        set_environment_variables,
+        cwd: command_dir.as_ref().map(PathBuf::from),
+        position: None,
+        exit_behavior: None,
+    })
+}
+
+/// Interprets the value returned by the `spawn-command-filter` event
+/// for `spawn_command`, which describes `original`.  Returns what to
+/// spawn, which is `original` itself unless the filter changed it.
+fn apply_spawn_command_filter(
+    original: (SpawnTabDomain, Option<CommandBuilder>, Option<String>),
+    spawn_command: &SpawnCommand,
+    filtered: wezterm_dynamic::Value,
+) -> anyhow::Result<(SpawnTabDomain, Option<CommandBuilder>, Option<String>)> {
+    let filtered: SpawnCommand = match filtered {
+        wezterm_dynamic::Value::Null => return Ok(original),
+        wezterm_dynamic::Value::Bool(false) => {
+            anyhow::bail!(
+                "spawn-command-filter event blocked spawning {}",
+                match &spawn_command.args {
+                    Some(args) => shell_words::join(args),
+                    None => "the default program".to_string(),
+                }
+            );
+        }
+        value => SpawnCommand::from_dynamic(&value, Default::default())
+            .context("interpreting SpawnCommand result from spawn-command-filter")?,
+    };
+
+    let (original_domain, original_command, original_command_dir) = original;
+    let domain = if filtered.domain == spawn_command.domain {
+        original_domain
+    } else {
+        filtered.domain
+    };
+
+    // Only these fields are used; keep the original command, and
+    // whatever it holds that SpawnCommand can't describe, unless
+    // one of them was changed
+    if filtered.args == spawn_command.args
+        && filtered.cwd == spawn_command.cwd
+        && filtered.set_environment_variables == spawn_command.set_environment_variables
+    {
+        return Ok((domain, original_command, original_command_dir));
+    }
+
+    let command = if let Some(args) = &filtered.args {
+        Some(CommandBuilder::from_argv(
+            args.iter().map(Into::into).collect(),
+        ))
+    } else if !filtered.set_environment_variables.is_empty() {
+        Some(CommandBuilder::new_default_prog())
+    } else {
+        None
+    };
+    let command = command.map(|mut cmd| {
+        for (k, v) in &filtered.set_environment_variables {
+            cmd.env(k, v);
+        }
+        if let Some(cwd) = &filtered.cwd {
+            cmd.cwd(cwd);
+        }
+        cmd
+    });
+    let command_dir = match &filtered.cwd {
+        Some(cwd) => Some(
+            cwd.to_str()
+                .ok_or_else(|| anyhow!("cwd {cwd:?} is not utf8"))?
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    Ok((domain, command, command_dir))
+}
+
 #[derive(Clone, Debug)]
 pub enum MuxNotification {
     PaneOutput(PaneId),
@@ -70,6 +205,7 @@ pub enum MuxNotification {
         clipboard: Option<String>,
     },
     SaveToDownloads {
+        pane_id: PaneId,
         name: Option<String>,
         data: Arc<Vec<u8>>,
     },
@@ -91,6 +227,10 @@ pub enum MuxNotification {
         old_workspace: String,
         new_workspace: String,
     },
+    PaneTagsChanged {
+        pane_id: PaneId,
+        tags: Vec<String>,
+    },
 }
 
 static SUB_ID: AtomicUsize = AtomicUsize::new(0);
@@ -107,6 +247,8 @@ pub struct Mux {
     clients: RwLock<HashMap<ClientId, ClientInfo>>,
     identity: RwLock<Option<Arc<ClientId>>>,
     num_panes_by_workspace: RwLock<HashMap<String, usize>>,
+    pane_tags: RwLock<HashMap<PaneId, BTreeSet<String>>>,
+    services: services::Services,
     main_thread_id: std::thread::ThreadId,
 }
 
@@ -118,6 +260,7 @@ fn send_actions_to_mux(pane: &Weak<dyn Pane>, dead: &Arc<AtomicBool>, actions: V
     let start = Instant::now();
     match pane.upgrade() {
         Some(pane) => {
+            vtinspect::record(pane.pane_id(), &actions);
             pane.perform_actions(actions);
             histogram!(
                 "send_actions_to_mux.perform_actions.latency",
@@ -175,7 +318,12 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
                             hold = false;
                             flush = true;
                         }
-                        Action::CSI(CSI::Device(dev)) if matches!(**dev, Device::SoftReset) => {
+                        Action::CSI(CSI::Device(dev))
+                            if matches!(
+                                **dev,
+                                Device::SoftReset | Device::SelectConformanceLevel { .. }
+                            ) =>
+                        {
                             hold = false;
                             flush = true;
                         }
@@ -302,6 +450,7 @@ fn read_from_pane_pty(
 
     std::thread::spawn({
         let dead = Arc::clone(&dead);
+        let pane = pane.clone();
         move || parse_buffered_data(pane, &dead, rx)
     });
 
@@ -309,6 +458,10 @@ fn read_from_pane_pty(
         tx.write_all(banner.as_bytes()).ok();
     }
 
+    let mut decoder = encoding::OutputDecoder::default();
+    let mut zmodem = zmodem::Detector::default();
+    let mut transfer: Option<zmodem::Transfer> = None;
+
     while !dead.load(Ordering::Relaxed) {
         match reader.read(&mut buf) {
             Ok(size) if size == 0 => {
@@ -322,13 +475,49 @@ fn read_from_pane_pty(
             Ok(size) => {
                 histogram!("read_from_pane_pty.bytes.rate", size as f64);
                 log::trace!("read_pty pane {pane_id} read {size} bytes");
-                if let Err(err) = tx.write_all(&buf[..size]) {
+
+                // While a ZMODEM helper is running, the output is its input
+                if let Some(mut active) = transfer.take() {
+                    if active.forward(&buf[..size]) {
+                        transfer.replace(active);
+                        continue;
+                    }
+                    active.finish(pane_id, &mut tx);
+                }
+                let start = if zmodem::is_enabled() {
+                    zmodem
+                        .scan(&buf[..size])
+                        .filter(|start| zmodem::has_helper(&pane, pane_id, start.direction))
+                } else {
+                    None
+                };
+                let output_len = start.as_ref().map(|s| s.offset).unwrap_or(size);
+
+                let data = match pane.upgrade() {
+                    Some(pane) => {
+                        let encoding = pane.get_encoding();
+                        let mut selected = encoding;
+                        let data = decoder.decode(&mut selected, &buf[..output_len]);
+                        if selected != encoding {
+                            if let Err(err) = pane.set_encoding(selected) {
+                                log::warn!("pane {pane_id}: {err:#}");
+                            }
+                        }
+                        data
+                    }
+                    None => break,
+                };
+                if let Err(err) = tx.write_all(&data) {
                     error!(
                         "read_pty failed to write to parser: pane {} {:?}",
                         pane_id, err
                     );
                     break;
                 }
+
+                if let Some(start) = start {
+                    transfer = zmodem::Transfer::start(&pane, pane_id, start, &mut tx);
+                }
             }
         }
     }
@@ -433,6 +622,8 @@ impl Mux {
             clients: RwLock::new(HashMap::new()),
             identity: RwLock::new(None),
             num_panes_by_workspace: RwLock::new(HashMap::new()),
+            pane_tags: RwLock::new(HashMap::new()),
+            services: services::Services::default(),
             main_thread_id: std::thread::current().id(),
         }
     }
@@ -735,7 +926,10 @@ impl Mux {
     }
 
     pub fn shutdown() {
-        MUX.lock().take();
+        let mux = MUX.lock().take();
+        if let Some(mux) = mux {
+            mux.services.stop_all();
+        }
     }
 
     pub fn get() -> Arc<Mux> {
@@ -746,6 +940,23 @@ impl Mux {
         MUX.lock().as_ref().map(Arc::clone)
     }
 
+    /// Starts and stops the supervised background programs to match
+    /// the `services` configuration
+    pub fn update_services(&self, services: &[config::Service]) {
+        self.services.update(services);
+    }
+
+    pub fn service_statuses(&self) -> Vec<services::ServiceStatus> {
+        self.services.statuses()
+    }
+
+    /// Stops the ZMODEM transfer that is in progress in the pane, if any,
+    /// and shows its output in the terminal again.
+    /// Returns false if there is no transfer in the pane.
+    pub fn cancel_zmodem_transfer(&self, pane_id: PaneId) -> bool {
+        zmodem::cancel(pane_id)
+    }
+
     pub fn get_pane(&self, pane_id: PaneId) -> Option<Arc<dyn Pane>> {
         self.panes.read().get(&pane_id).map(Arc::clone)
     }
@@ -764,18 +975,30 @@ impl Mux {
         });
         pane.set_clipboard(&clipboard);
 
-        let downloader: Arc<dyn DownloadHandler> = Arc::new(MuxDownloader {});
+        let downloader: Arc<dyn DownloadHandler> = Arc::new(MuxDownloader {
+            pane_id: pane.pane_id(),
+        });
         pane.set_download_handler(&downloader);
 
         self.panes.write().insert(pane.pane_id(), Arc::clone(pane));
         let pane_id = pane.pane_id();
+        self.start_reading_from_pane(pane, self.banner.read().clone())?;
+        self.recompute_pane_count();
+        self.notify(MuxNotification::PaneAdded(pane_id));
+        Ok(())
+    }
+
+    /// Starts the thread that reads the output of the process
+    /// running in `pane` and applies it to the pane
+    pub(crate) fn start_reading_from_pane(
+        &self,
+        pane: &Arc<dyn Pane>,
+        banner: Option<String>,
+    ) -> Result<(), Error> {
         if let Some(reader) = pane.reader()? {
-            let banner = self.banner.read().clone();
             let pane = Arc::downgrade(pane);
             thread::spawn(move || read_from_pane_pty(pane, banner, reader));
         }
-        self.recompute_pane_count();
-        self.notify(MuxNotification::PaneAdded(pane_id));
         Ok(())
     }
 
@@ -797,6 +1020,8 @@ impl Mux {
         if let Some(pane) = self.panes.write().remove(&pane_id).clone() {
             log::debug!("killing pane {}", pane_id);
             pane.kill();
+            vtinspect::set_enabled(pane_id, false);
+            self.pane_tags.write().remove(&pane_id);
             self.recompute_pane_count();
             self.notify(MuxNotification::PaneRemoved(pane_id));
         }
@@ -1017,6 +1242,106 @@ impl Mux {
             .collect()
     }
 
+    /// Returns the tags of the pane, in order
+    pub fn get_pane_tags(&self, pane_id: PaneId) -> Vec<String> {
+        self.pane_tags
+            .read()
+            .get(&pane_id)
+            .map(|tags| tags.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn pane_has_tag(&self, pane_id: PaneId, tag: &str) -> bool {
+        self.pane_tags
+            .read()
+            .get(&pane_id)
+            .map_or(false, |tags| tags.contains(tag))
+    }
+
+    /// Replaces the tags of the pane, notifying subscribers if they
+    /// changed
+    pub fn set_pane_tags(&self, pane_id: PaneId, tags: BTreeSet<String>) {
+        if !self.panes.read().contains_key(&pane_id) {
+            return;
+        }
+        {
+            let mut pane_tags = self.pane_tags.write();
+            let existing = pane_tags.entry(pane_id).or_default();
+            if *existing == tags {
+                return;
+            }
+            *existing = tags.clone();
+        }
+        self.notify(MuxNotification::PaneTagsChanged {
+            pane_id,
+            tags: tags.into_iter().collect(),
+        });
+    }
+
+    /// Applies the side effects of a pane setting the user var `name`
+    pub fn pane_user_var_changed(&self, pane_id: PaneId, name: &str, value: &str) {
+        if name == PANE_TAGS_USER_VAR {
+            self.set_pane_tags(pane_id, parse_pane_tags(value));
+        }
+    }
+
+    /// Adds `tag` to the tags of the pane; see `validate_pane_tag`
+    pub fn add_pane_tag(&self, pane_id: PaneId, tag: &str) -> anyhow::Result<()> {
+        validate_pane_tag(tag)?;
+        if !self.panes.read().contains_key(&pane_id) {
+            anyhow::bail!("pane {pane_id} not found");
+        }
+        let mut tags: BTreeSet<String> = self.get_pane_tags(pane_id).into_iter().collect();
+        tags.insert(tag.to_string());
+        self.set_pane_tags(pane_id, tags);
+        Ok(())
+    }
+
+    pub fn remove_pane_tag(&self, pane_id: PaneId, tag: &str) {
+        let mut tags: BTreeSet<String> = self.get_pane_tags(pane_id).into_iter().collect();
+        if tags.remove(tag) {
+            self.set_pane_tags(pane_id, tags);
+        }
+    }
+
+    /// Returns the panes of the domains whose names match `pattern`,
+    /// in which `*` matches any text and `?` any character, ignoring
+    /// case, or all of the panes if there is no pattern
+    pub fn panes_in_domains_matching(&self, pattern: Option<&str>) -> Vec<Arc<dyn Pane>> {
+        let mut panes: Vec<Arc<dyn Pane>> = self
+            .iter_panes()
+            .into_iter()
+            .filter(|pane| match pattern {
+                Some(pattern) => self.get_domain(pane.domain_id()).map_or(false, |domain| {
+                    config::wildcard_match(pattern, domain.domain_name())
+                }),
+                None => true,
+            })
+            .collect();
+        panes.sort_by_key(|pane| pane.pane_id());
+        panes
+    }
+
+    /// Returns the panes of the domains whose names match `match_domain`
+    /// that have the tag `match_tag`, keeping only those in `pane_ids`,
+    /// such as the ones that the user confirmed after a dry run.
+    /// Filters that are `None` match all of the panes.
+    pub fn panes_matching(
+        &self,
+        match_domain: Option<&str>,
+        match_tag: Option<&str>,
+        pane_ids: Option<&[PaneId]>,
+    ) -> Vec<Arc<dyn Pane>> {
+        self.panes_in_domains_matching(match_domain)
+            .into_iter()
+            .filter(|pane| {
+                let pane_id = pane.pane_id();
+                pane_ids.map_or(true, |ids| ids.contains(&pane_id))
+                    && match_tag.map_or(true, |tag| self.pane_has_tag(pane_id, tag))
+            })
+            .collect()
+    }
+
     pub fn iter_windows_in_workspace(&self, workspace: &str) -> Vec<WindowId> {
         let mut windows: Vec<WindowId> = self
             .windows
@@ -1126,6 +1451,62 @@ impl Mux {
         Ok(domain)
     }
 
+    /// Emits the `spawn-command-filter` event, which can rewrite the
+    /// program, environment, directory and domain of a spawn, or block
+    /// it by returning false.  Returns what to spawn.
+    async fn filter_spawn_command(
+        &self,
+        pane_id: Option<PaneId>,
+        domain: SpawnTabDomain,
+        command: Option<CommandBuilder>,
+        command_dir: Option<String>,
+    ) -> anyhow::Result<(SpawnTabDomain, Option<CommandBuilder>, Option<String>)> {
+        let resolved = self.resolve_spawn_tab_domain(pane_id, &domain)?;
+        // Detachable domains hand the spawn to a multiplexer server,
+        // which filters it with its own configuration
+        if resolved.detachable() {
+            return Ok((domain, command, command_dir));
+        }
+
+        let filtered = config::with_lua_config_on_main_thread(|lua| async {
+            let lua = match lua {
+                Some(lua) => lua,
+                None => return Ok(None),
+            };
+            if !config::lua::has_event_handler(&*lua, "spawn-command-filter")? {
+                return Ok(None);
+            }
+            let spawn_command =
+                match spawn_command_for_filter(resolved.domain_name(), &command, &command_dir) {
+                    Some(spawn_command) => spawn_command,
+                    None => {
+                        log::debug!(
+                            "not emitting spawn-command-filter for a command that is not utf8"
+                        );
+                        return Ok(None);
+                    }
+                };
+            let value = config::lua::emit_async_callback(
+                &*lua,
+                ("spawn-command-filter".to_string(), spawn_command.clone()),
+            )
+            .await?;
+            Ok(Some((
+                spawn_command,
+                luahelper::lua_value_to_dynamic(value)?,
+            )))
+        })
+        .await
+        .context("calling spawn-command-filter event")?;
+
+        match filtered {
+            Some((spawn_command, filtered)) => {
+                apply_spawn_command_filter((domain, command, command_dir), &spawn_command, filtered)
+            }
+            None => Ok((domain, command, command_dir)),
+        }
+    }
+
     fn resolve_cwd(
         &self,
         command_dir: Option<String>,
@@ -1171,6 +1552,27 @@ impl Mux {
             .resolve_pane_id(pane_id)
             .ok_or_else(|| anyhow!("pane_id {} invalid", pane_id))?;
 
+        let (source, domain) = match source {
+            SplitSource::Spawn {
+                command,
+                command_dir,
+                exit_behavior,
+            } => {
+                let (domain, command, command_dir) = self
+                    .filter_spawn_command(Some(pane_id), domain, command, command_dir)
+                    .await?;
+                (
+                    SplitSource::Spawn {
+                        command,
+                        command_dir,
+                        exit_behavior,
+                    },
+                    domain,
+                )
+            }
+            other => (other, domain),
+        };
+
         let domain = self
             .resolve_spawn_tab_domain(Some(pane_id), &domain)
             .context("resolve_spawn_tab_domain")?;
@@ -1188,6 +1590,7 @@ impl Mux {
             SplitSource::Spawn {
                 command,
                 command_dir,
+                exit_behavior,
             } => SplitSource::Spawn {
                 command,
                 command_dir: self.resolve_cwd(
@@ -1196,6 +1599,7 @@ impl Mux {
                     domain.domain_id(),
                     CachePolicy::FetchImmediate,
                 ),
+                exit_behavior,
             },
             other => other,
         };
@@ -1279,17 +1683,50 @@ impl Mux {
         Ok((tab, window_id))
     }
 
+    /// Creates a new window whose only tab holds a read-only mirror
+    /// of the specified pane; see [MirrorPane](mirror::MirrorPane).
+    pub fn mirror_pane_in_new_window(
+        &self,
+        pane_id: PaneId,
+        workspace_for_new_window: Option<String>,
+    ) -> anyhow::Result<(Arc<Tab>, Arc<dyn Pane>, WindowId)> {
+        let source = self
+            .get_pane(pane_id)
+            .ok_or_else(|| anyhow!("pane {} not found", pane_id))?;
+        let size = match self.resolve_pane_id(pane_id) {
+            Some((_domain_id, _window_id, tab_id)) => self
+                .get_tab(tab_id)
+                .ok_or_else(|| anyhow!("tab {} not found", tab_id))?
+                .get_size(),
+            None => TerminalSize::default(),
+        };
+
+        let window_builder = self.new_empty_window(workspace_for_new_window, None);
+        let pane: Arc<dyn Pane> = mirror::MirrorPane::new(self, &source);
+
+        let tab = Arc::new(Tab::new(&size));
+        tab.assign_pane(&pane);
+        self.add_tab_and_active_pane(&tab)?;
+        self.add_tab_to_window(&tab, *window_builder)?;
+
+        Ok((tab, pane, *window_builder))
+    }
+
     pub async fn spawn_tab_or_window(
         &self,
         window_id: Option<WindowId>,
         domain: SpawnTabDomain,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        exit_behavior: Option<ExitBehavior>,
         size: TerminalSize,
         current_pane_id: Option<PaneId>,
         workspace_for_new_window: String,
         window_position: Option<GuiPosition>,
     ) -> anyhow::Result<(Arc<Tab>, Arc<dyn Pane>, WindowId)> {
+        let (domain, command, command_dir) = self
+            .filter_spawn_command(current_pane_id, domain, command, command_dir)
+            .await?;
         let domain = self
             .resolve_spawn_tab_domain(current_pane_id, &domain)
             .context("resolve_spawn_tab_domain")?;
@@ -1344,7 +1781,7 @@ impl Mux {
         );
 
         let tab = domain
-            .spawn(size, command.clone(), cwd.clone(), window_id)
+            .spawn(size, command.clone(), cwd.clone(), exit_behavior, window_id)
             .await
             .with_context(|| {
                 format!(
@@ -1427,15 +1864,263 @@ impl Clipboard for MuxClipboard {
     }
 }
 
-struct MuxDownloader {}
+struct MuxDownloader {
+    pane_id: PaneId,
+}
 
 impl wezterm_term::DownloadHandler for MuxDownloader {
     fn save_to_downloads(&self, name: Option<String>, data: Vec<u8>) {
         if let Some(mux) = Mux::try_get() {
             mux.notify(MuxNotification::SaveToDownloads {
+                pane_id: self.pane_id,
                 name,
                 data: Arc::new(data),
             });
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::LocalDomain;
+    use crate::tab::test::FakePane;
+    use wezterm_dynamic::ToDynamic;
+
+    fn tags(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    /// Returns a mux holding a single pane with id 1, and a counter
+    /// of the PaneTagsChanged notifications that it sent
+    fn mux_with_pane() -> (Mux, Arc<AtomicUsize>) {
+        let mux = Mux::new(None);
+        let pane = FakePane::new(1, TerminalSize::default());
+        mux.panes.write().insert(pane.pane_id(), pane);
+
+        let changes = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&changes);
+        mux.subscribe(move |n| {
+            if let MuxNotification::PaneTagsChanged { .. } = n {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            true
+        });
+        (mux, changes)
+    }
+
+    #[test]
+    fn validate_tags() {
+        assert!(validate_pane_tag("db").is_ok());
+        assert!(validate_pane_tag("prod-eu_1").is_ok());
+        assert!(validate_pane_tag("").is_err());
+        assert!(validate_pane_tag("a,b").is_err());
+        assert!(validate_pane_tag("a b").is_err());
+        assert!(validate_pane_tag("a\tb").is_err());
+    }
+
+    #[test]
+    fn parse_tags() {
+        assert_eq!(parse_pane_tags(""), tags(&[]));
+        assert_eq!(parse_pane_tags("db"), tags(&["db"]));
+        assert_eq!(
+            parse_pane_tags(" db, prod  x,,\tdb "),
+            tags(&["db", "prod", "x"])
+        );
+        for tag in parse_pane_tags("a,b c\td,,") {
+            assert!(validate_pane_tag(&tag).is_ok());
+        }
+    }
+
+    #[test]
+    fn set_tags_notifies_on_change() {
+        let (mux, changes) = mux_with_pane();
+
+        mux.set_pane_tags(1, tags(&["db"]));
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+        assert!(mux.pane_has_tag(1, "db"));
+
+        mux.set_pane_tags(1, tags(&["db"]));
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+
+        mux.add_pane_tag(1, "db").unwrap();
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+
+        mux.add_pane_tag(1, "prod").unwrap();
+        assert_eq!(changes.load(Ordering::Relaxed), 2);
+
+        mux.remove_pane_tag(1, "db");
+        assert_eq!(changes.load(Ordering::Relaxed), 3);
+        assert_eq!(mux.get_pane_tags(1), vec!["prod"]);
+
+        assert!(mux.add_pane_tag(1, "a b").is_err());
+        assert_eq!(changes.load(Ordering::Relaxed), 3);
+
+        // Unknown panes are ignored
+        mux.set_pane_tags(2, tags(&["db"]));
+        assert_eq!(changes.load(Ordering::Relaxed), 3);
+        assert!(!mux.pane_has_tag(2, "db"));
+    }
+
+    /// Returns a mux holding panes 1 and 2 in the domain "local", and
+    /// panes 3 and 4 in the domain "SSH:prod"
+    fn mux_with_domains() -> Mux {
+        let mux = Mux::new(None);
+        let mut pane_id = 0;
+        for name in ["local", "SSH:prod"] {
+            let domain: Arc<dyn Domain> = Arc::new(LocalDomain::new(name).unwrap());
+            mux.add_domain(&domain);
+            for _ in 0..2 {
+                pane_id += 1;
+                let pane =
+                    FakePane::in_domain(pane_id, TerminalSize::default(), domain.domain_id());
+                mux.panes.write().insert(pane_id, pane);
+            }
+        }
+        mux
+    }
+
+    fn pane_ids(panes: Vec<Arc<dyn Pane>>) -> Vec<PaneId> {
+        panes.iter().map(|pane| pane.pane_id()).collect()
+    }
+
+    #[test]
+    fn panes_in_matching_domains() {
+        let mux = mux_with_domains();
+        assert_eq!(
+            pane_ids(mux.panes_in_domains_matching(None)),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            pane_ids(mux.panes_in_domains_matching(Some("local"))),
+            vec![1, 2]
+        );
+        assert_eq!(
+            pane_ids(mux.panes_in_domains_matching(Some("ssh:*"))),
+            vec![3, 4]
+        );
+        assert_eq!(
+            pane_ids(mux.panes_in_domains_matching(Some("?ocal"))),
+            vec![1, 2]
+        );
+        assert!(mux.panes_in_domains_matching(Some("ssh")).is_empty());
+    }
+
+    #[test]
+    fn panes_matching_confirmed_ids() {
+        let mux = mux_with_domains();
+        mux.set_pane_tags(2, tags(&["db"]));
+        mux.set_pane_tags(4, tags(&["db"]));
+
+        assert_eq!(
+            pane_ids(mux.panes_matching(None, None, None)),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            pane_ids(mux.panes_matching(None, Some("db"), None)),
+            vec![2, 4]
+        );
+
+        // Only the confirmed panes that still match are kept
+        assert_eq!(
+            pane_ids(mux.panes_matching(Some("ssh:*"), None, Some(&[1, 3][..]))),
+            vec![3]
+        );
+        assert_eq!(
+            pane_ids(mux.panes_matching(None, Some("db"), Some(&[1, 2, 3][..]))),
+            vec![2]
+        );
+        assert!(mux.panes_matching(None, None, Some(&[][..])).is_empty());
+        assert!(mux.panes_matching(None, None, Some(&[5][..])).is_empty());
+    }
+
+    fn spawn_command_original() -> (SpawnTabDomain, Option<CommandBuilder>, Option<String>) {
+        let mut cmd = CommandBuilder::new("vim");
+        cmd.arg("notes.txt");
+        cmd.env("EDITOR", "vim");
+        // Not described by SpawnCommand, so it is lost if the
+        // command has to be rebuilt
+        cmd.set_controlling_tty(false);
+        (
+            SpawnTabDomain::CurrentPaneDomain,
+            Some(cmd),
+            Some("/tmp".to_string()),
+        )
+    }
+
+    #[test]
+    fn spawn_command_filter_round_trip() {
+        let original = spawn_command_original();
+        let spawn_command = spawn_command_for_filter("local", &original.1, &original.2).unwrap();
+        assert_eq!(
+            spawn_command.args,
+            Some(vec!["vim".to_string(), "notes.txt".to_string()])
+        );
+
+        // Returning the command that was passed in, or nothing,
+        // spawns the original command
+        for filtered in [spawn_command.to_dynamic(), wezterm_dynamic::Value::Null] {
+            let (domain, command, command_dir) =
+                apply_spawn_command_filter(spawn_command_original(), &spawn_command, filtered)
+                    .unwrap();
+            assert_eq!(domain, original.0);
+            assert_eq!(command, original.1);
+            assert_eq!(command_dir, original.2);
+        }
+
+        // Changing it rebuilds the command
+        let mut changed = spawn_command.clone();
+        changed.args = Some(vec!["nvim".to_string()]);
+        let (_domain, command, command_dir) = apply_spawn_command_filter(
+            spawn_command_original(),
+            &spawn_command,
+            changed.to_dynamic(),
+        )
+        .unwrap();
+        let command = command.unwrap();
+        assert_eq!(command.get_argv(), &vec![std::ffi::OsString::from("nvim")]);
+        assert_eq!(command.get_env("EDITOR"), Some(std::ffi::OsStr::new("vim")));
+        assert_eq!(command_dir, Some("/tmp".to_string()));
+    }
+
+    #[test]
+    fn spawn_command_filter_blocks() {
+        let original = spawn_command_original();
+        let spawn_command = spawn_command_for_filter("local", &original.1, &original.2).unwrap();
+        let err = apply_spawn_command_filter(
+            original,
+            &spawn_command,
+            wezterm_dynamic::Value::Bool(false),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("blocked spawning vim notes.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn spawn_command_filter_skips_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let cmd = CommandBuilder::new(std::ffi::OsStr::from_bytes(b"\xff"));
+        assert!(spawn_command_for_filter("local", &Some(cmd), &None).is_none());
+    }
+
+    #[test]
+    fn tags_user_var() {
+        let (mux, changes) = mux_with_pane();
+
+        mux.pane_user_var_changed(1, "OTHER", "db");
+        assert_eq!(changes.load(Ordering::Relaxed), 0);
+        assert!(mux.get_pane_tags(1).is_empty());
+
+        mux.pane_user_var_changed(1, PANE_TAGS_USER_VAR, "db,prod");
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+        assert_eq!(mux.get_pane_tags(1), vec!["db", "prod"]);
+
+        mux.pane_user_var_changed(1, PANE_TAGS_USER_VAR, "prod db");
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+
+        mux.pane_user_var_changed(1, PANE_TAGS_USER_VAR, "");
+        assert_eq!(changes.load(Ordering::Relaxed), 2);
+        assert!(mux.get_pane_tags(1).is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use crate::domain::DomainId;
+use crate::domain::{DomainId, WriterWrapper};
+use crate::encoding::SharedEncoding;
 use crate::pane::{
-    CachePolicy, CloseReason, ForEachPaneLogicalLine, LogicalLine, Pane, PaneId, Pattern,
+    CachePolicy, CloseReason, ForEachPaneLogicalLine, LogicalLine, Origin, Pane, PaneId, Pattern,
     SearchResult, WithPaneLines,
 };
 use crate::renderable::*;
@@ -9,10 +10,10 @@ use crate::{Domain, Mux, MuxNotification};
 use anyhow::Error;
 use async_trait::async_trait;
 use config::keyassignment::ScrollbackEraseMode;
-use config::{configuration, ExitBehavior, ExitBehaviorMessaging};
+use config::{configuration, CharacterEncoding, ExitBehavior, ExitBehaviorMessaging, SlowPaste};
 use fancy_regex::Regex;
 use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
-use portable_pty::{Child, ChildKiller, ExitStatus, MasterPty, PtySize};
+use portable_pty::{Child, ChildKiller, CommandBuilder, ExitStatus, MasterPty, PtySize};
 use procinfo::LocalProcessInfo;
 use rangeset::RangeSet;
 use smol::channel::{bounded, Receiver, TryRecvError};
@@ -31,12 +32,36 @@ use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Alert, AlertHandler, Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent,
-    SemanticZone, StableRowIndex, Terminal, TerminalConfiguration, TerminalSize,
+    Alert, AlertHandler, Clipboard, CommandRecord, DownloadHandler, KeyCode, KeyModifiers,
+    MouseEvent, SemanticZone, StableRowIndex, Terminal, TerminalConfiguration, TerminalSize,
+    TerminalSnapshot,
 };
 
 const PROC_INFO_CACHE_TTL: Duration = Duration::from_millis(300);
 
+lazy_static::lazy_static! {
+    static ref LOCAL_HOSTNAME: Option<String> = hostname::get()
+        .ok()
+        .map(|name| name.to_string_lossy().to_string());
+}
+
+/// Returns true if `host`, as reported by a program with OSC 7, names
+/// the host called `local`.  Shells may report either the short or
+/// the fully qualified name, so only the first labels are compared.
+fn is_same_host(host: &str, local: &str) -> bool {
+    fn short(host: &str) -> &str {
+        host.split('.').next().unwrap_or(host)
+    }
+    host.eq_ignore_ascii_case("localhost") || short(host).eq_ignore_ascii_case(short(local))
+}
+
+fn is_local_host(host: &str) -> bool {
+    match LOCAL_HOSTNAME.as_deref() {
+        Some(local) => is_same_host(host, local),
+        None => host.eq_ignore_ascii_case("localhost"),
+    }
+}
+
 #[derive(Debug)]
 enum ProcessState {
     Running {
@@ -127,12 +152,29 @@ pub struct LocalPane {
     process: Mutex<ProcessState>,
     pty: Mutex<Box<dyn MasterPty>>,
     writer: Mutex<Box<dyn Write + Send>>,
+    /// The character encoding used to exchange data with the process
+    encoding: SharedEncoding,
     domain_id: DomainId,
     tmux_domain: Mutex<Option<Arc<TmuxDomainState>>>,
     proc_list: Mutex<Option<CachedProcInfo>>,
     #[cfg(unix)]
     leader: Arc<Mutex<Option<CachedLeaderInfo>>>,
-    command_description: String,
+    command_description: Mutex<String>,
+    /// The command that was spawned in the pane, which is
+    /// run again when the pane is respawned
+    command: Mutex<Option<CommandBuilder>>,
+    /// Overrides the exit_behavior config for this pane
+    exit_behavior: Mutex<Option<ExitBehavior>>,
+    /// Overrides the slow_paste config for this pane
+    slow_paste: Mutex<Option<SlowPaste>>,
+    /// True if the program runs on another host, such as one spawned
+    /// by an ssh domain
+    remote: Mutex<bool>,
+    /// True if the text before the cursor looked like a password prompt
+    /// when the output of the program was last processed.  This is
+    /// computed while the terminal is locked for that output, so that
+    /// it can be consulted while the terminal is locked for rendering.
+    password_prompt: Mutex<bool>,
 }
 
 #[async_trait(?Send)]
@@ -142,23 +184,12 @@ impl Pane for LocalPane {
     }
 
     fn get_metadata(&self) -> Value {
-        #[allow(unused_mut)]
         let mut map: BTreeMap<Value, Value> = BTreeMap::new();
 
-        #[cfg(unix)]
-        if let Some(tio) = self.pty.lock().get_termios() {
-            use nix::sys::termios::LocalFlags;
-            // Detect whether we might be in password input mode.
-            // If local echo is disabled and canonical input mode
-            // is enabled, then we assume that we're in some kind
-            // of password-entry mode.
-            let pw_input = !tio.local_flags.contains(LocalFlags::ECHO)
-                && tio.local_flags.contains(LocalFlags::ICANON);
-            map.insert(
-                Value::String("password_input".to_string()),
-                Value::Bool(pw_input),
-            );
-        }
+        map.insert(
+            Value::String("password_input".to_string()),
+            Value::Bool(self.is_password_input()),
+        );
 
         Value::Object(map.into())
     }
@@ -219,6 +250,33 @@ impl Pane for LocalPane {
         self.terminal.lock().user_vars().clone()
     }
 
+    fn set_user_var(&self, name: &str, value: &str) -> anyhow::Result<()> {
+        self.terminal
+            .lock()
+            .set_user_var(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn get_title_origin(&self) -> Option<Origin> {
+        let host = self
+            .terminal
+            .lock()
+            .get_title_host()
+            .map(|host| host.to_string());
+        Some(self.origin(host))
+    }
+
+    fn get_user_var_origin(&self, name: &str) -> Option<Origin> {
+        let host = {
+            let terminal = self.terminal.lock();
+            if !terminal.user_vars().contains_key(name) {
+                return None;
+            }
+            terminal.user_var_host(name).map(|host| host.to_string())
+        };
+        Some(self.origin(host))
+    }
+
     fn exit_behavior(&self) -> Option<ExitBehavior> {
         // If we are ssh, and we've not yet fully connected,
         // then override exit_behavior so that we can show
@@ -233,10 +291,23 @@ impl Pane for LocalPane {
         if is_ssh_connecting || is_failed_spawn {
             Some(ExitBehavior::CloseOnCleanExit)
         } else {
-            None
+            *self.exit_behavior.lock()
         }
     }
 
+    fn set_exit_behavior(&self, behavior: Option<ExitBehavior>) {
+        *self.exit_behavior.lock() = behavior;
+    }
+
+    fn get_encoding(&self) -> CharacterEncoding {
+        self.encoding.get()
+    }
+
+    fn set_encoding(&self, encoding: CharacterEncoding) -> anyhow::Result<()> {
+        self.encoding.set(encoding);
+        Ok(())
+    }
+
     fn kill(&self) {
         let mut proc = self.process.lock();
         log::debug!(
@@ -269,7 +340,13 @@ impl Pane for LocalPane {
         let mut terse = String::new();
         let mut brief = String::new();
         let mut trailer = String::new();
-        let cmd = &self.command_description;
+        let mut hint = String::new();
+        let cmd = self.command_description.lock().clone();
+        let respawn_hint = if self.command.lock().is_some() {
+            "\x1b[2mPress Enter to respawn it.\x1b[0m"
+        } else {
+            ""
+        };
 
         match &mut *proc {
             ProcessState::Running {
@@ -302,12 +379,14 @@ impl Pane for LocalPane {
                             brief = format!("⚠️  Process {cmd} didn't exit cleanly");
                             terse = format!("{status}.");
                             trailer = format!("{EXIT_BEHAVIOR}=\"CloseOnCleanExit\"");
+                            hint = respawn_hint.to_string();
 
                             *proc = ProcessState::DeadPendingClose { killed: false }
                         }
                         (ExitBehavior::CloseOnCleanExit, ..) => *proc = ProcessState::Dead,
                         (ExitBehavior::Hold, success, false) => {
                             trailer = format!("{EXIT_BEHAVIOR}=\"Hold\"");
+                            hint = respawn_hint.to_string();
 
                             if success {
                                 brief = format!("👍 Process {cmd} completed.");
@@ -333,20 +412,24 @@ impl Pane for LocalPane {
         }
 
         let mut notify = None;
-        if !terse.is_empty() {
+        // A failed spawn has already rendered its error into the pane
+        if !terse.is_empty() && !self.is_failed_spawn() {
+            if !hint.is_empty() {
+                hint = format!("\r\n{hint}");
+            }
             match configuration().exit_behavior_messaging {
                 ExitBehaviorMessaging::Verbose => {
                     if terse == "done" {
-                        notify = Some(format!("\r\n{brief}\r\n{trailer}"));
+                        notify = Some(format!("\r\n{brief}{hint}\r\n{trailer}"));
                     } else {
-                        notify = Some(format!("\r\n{brief}\r\n{terse}\r\n{trailer}"));
+                        notify = Some(format!("\r\n{brief}\r\n{terse}{hint}\r\n{trailer}"));
                     }
                 }
                 ExitBehaviorMessaging::Brief => {
                     if terse == "done" {
-                        notify = Some(format!("\r\n{brief}"));
+                        notify = Some(format!("\r\n{brief}{hint}"));
                     } else {
-                        notify = Some(format!("\r\n{brief}\r\n{terse}"));
+                        notify = Some(format!("\r\n{brief}\r\n{terse}{hint}"));
                     }
                 }
                 ExitBehaviorMessaging::Terse => {
@@ -384,7 +467,9 @@ impl Pane for LocalPane {
     }
 
     fn perform_actions(&self, actions: Vec<termwiz::escape::Action>) {
-        self.terminal.lock().perform_actions(actions)
+        let mut term = self.terminal.lock();
+        term.perform_actions(actions);
+        self.update_password_prompt(&term);
     }
 
     fn mouse_event(&self, event: MouseEvent) -> Result<(), Error> {
@@ -400,6 +485,19 @@ impl Pane for LocalPane {
                 self.terminal.lock().send_paste("detach\n")?;
             }
             return Ok(());
+        } else if key == KeyCode::Enter && mods == KeyModifiers::NONE && self.can_respawn() {
+            let pane_id = self.pane_id;
+            let domain_id = self.domain_id;
+            promise::spawn::spawn_into_main_thread(async move {
+                let mux = Mux::get();
+                if let Some(domain) = mux.get_domain(domain_id) {
+                    if let Err(err) = domain.respawn_pane(pane_id, None, None).await {
+                        log::error!("Unable to respawn pane {pane_id}: {err:#}");
+                    }
+                }
+            })
+            .detach();
+            Ok(())
         } else {
             self.terminal.lock().key_down(key, mods)
         }
@@ -417,7 +515,9 @@ impl Pane for LocalPane {
             pixel_width: size.pixel_width.try_into()?,
             pixel_height: size.pixel_height.try_into()?,
         })?;
-        self.terminal.lock().resize(size);
+        let mut term = self.terminal.lock();
+        term.resize(size);
+        self.update_password_prompt(&term);
         Ok(())
     }
 
@@ -434,28 +534,30 @@ impl Pane for LocalPane {
     }
 
     fn send_paste(&self, text: &str) -> Result<(), Error> {
-        Mux::get().record_input_for_current_identity();
-        if self.tmux_domain.lock().is_some() {
-            Ok(())
-        } else {
-            self.terminal.lock().send_paste(text)
-        }
+        self.paste(text, false)
+    }
+
+    fn send_paste_toggling_slow_paste(&self, text: &str) -> Result<(), Error> {
+        self.paste(text, true)
     }
 
     fn get_title(&self) -> String {
-        let title = self.terminal.lock().get_title().to_string();
-        // If the title is the default pane title, then try to spice
-        // things up a bit by returning the process basename instead
-        if title == "wezterm" {
-            if let Some(proc_name) = self.get_foreground_process_name(CachePolicy::AllowStale) {
-                let proc_name = std::path::Path::new(&proc_name);
-                if let Some(name) = proc_name.file_name() {
-                    return name.to_string_lossy().to_string();
-                }
+        let title = self.unlabelled_title();
+        if !configuration().label_remote_titles {
+            return title;
+        }
+        match self.get_title_origin() {
+            Some(origin) if origin.remote => {
+                // The domain can't be spoofed by the program, so it is
+                // preferred over the host that the program claims
+                let label = match origin.host {
+                    Some(host) if !*self.remote.lock() => host,
+                    _ => origin.domain,
+                };
+                format!("[{label}] {title}")
             }
+            _ => title,
         }
-
-        title
     }
 
     fn palette(&self) -> ColorPalette {
@@ -501,6 +603,10 @@ impl Pane for LocalPane {
         }
     }
 
+    fn set_alt_screen_capture(&self, enabled: Option<bool>) {
+        self.terminal.lock().set_alt_screen_capture(enabled);
+    }
+
     fn get_current_working_dir(&self, policy: CachePolicy) -> Option<Url> {
         self.terminal
             .lock()
@@ -635,6 +741,14 @@ impl Pane for LocalPane {
         term.get_semantic_zones()
     }
 
+    fn get_terminal_snapshot(&self) -> anyhow::Result<TerminalSnapshot> {
+        Ok(self.terminal.lock().snapshot())
+    }
+
+    async fn get_command_history(&self) -> anyhow::Result<Vec<CommandRecord>> {
+        Ok(self.terminal.lock().get_command_history())
+    }
+
     async fn search(
         &self,
         pattern: Pattern,
@@ -822,6 +936,14 @@ struct LocalPaneDCSHandler {
     tmux_domain: Option<Arc<TmuxDomainState>>,
 }
 
+/// Resets the modes that the previous process may have left behind
+/// and draws a line that separates its output from that of the new one
+fn respawn_marker(command_description: &str, cols: usize) -> String {
+    let label = format!("── Restarted {command_description} ");
+    let fill = cols.saturating_sub(wezterm_term::unicode_column_width(&label, None));
+    format!("\x1b[!p\r\n\x1b[2m{label}{}\x1b[0m\r\n", "─".repeat(fill))
+}
+
 pub(crate) fn emit_output_for_pane(pane_id: PaneId, message: &str) {
     let mut parser = termwiz::escape::parser::Parser::new();
     let mut actions = vec![Action::CSI(CSI::Sgr(Sgr::Reset))];
@@ -934,6 +1056,9 @@ impl AlertHandler for LocalPaneNotifHandler {
                         }
                     }
                 }
+                Alert::SetUserVar { name, value } => {
+                    mux.pane_user_var_changed(pane_id, name, value);
+                }
                 _ => {}
             }
 
@@ -943,6 +1068,44 @@ impl AlertHandler for LocalPaneNotifHandler {
     }
 }
 
+/// Splits a paste into pieces of at most `chunk_size` characters
+fn paste_chunks(data: &str, chunk_size: usize) -> Vec<String> {
+    let chars: Vec<char> = data.chars().collect();
+    chars
+        .chunks(chunk_size.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// Sends a paste to the pane a piece at a time, pausing between the
+/// pieces so that slow devices and programs can keep up.
+/// Writes to the pty may block, so this happens on a separate thread.
+fn trickle_paste(pane_id: PaneId, data: String, slow_paste: SlowPaste) {
+    let delay = Duration::from_millis(slow_paste.delay_ms);
+    std::thread::spawn(move || {
+        for (idx, chunk) in paste_chunks(&data, slow_paste.chunk_size)
+            .into_iter()
+            .enumerate()
+        {
+            if idx > 0 {
+                std::thread::sleep(delay);
+            }
+            let pane = match Mux::try_get().and_then(|mux| mux.get_pane(pane_id)) {
+                Some(pane) => pane,
+                None => return,
+            };
+            let mut writer = pane.writer();
+            if let Err(err) = writer
+                .write_all(chunk.as_bytes())
+                .and_then(|_| writer.flush())
+            {
+                log::error!("while pasting into pane {}: {:#}", pane_id, err);
+                return;
+            }
+        }
+    });
+}
+
 /// This is a little gross; on some systems, our pipe reader will continue
 /// to be blocked in read even after the child process has died.
 /// We need to wake up and notice that the child terminated in order
@@ -983,6 +1146,7 @@ impl LocalPane {
         process: Box<dyn Child + Send>,
         pty: Box<dyn MasterPty>,
         writer: Box<dyn Write + Send>,
+        encoding: SharedEncoding,
         domain_id: DomainId,
         command_description: String,
     ) -> Self {
@@ -1005,15 +1169,171 @@ impl LocalPane {
             }),
             pty: Mutex::new(pty),
             writer: Mutex::new(writer),
+            encoding,
             domain_id,
             tmux_domain: Mutex::new(None),
             proc_list: Mutex::new(None),
             #[cfg(unix)]
             leader: Arc::new(Mutex::new(None)),
-            command_description,
+            command_description: Mutex::new(command_description),
+            command: Mutex::new(None),
+            exit_behavior: Mutex::new(None),
+            slow_paste: Mutex::new(None),
+            remote: Mutex::new(false),
+            password_prompt: Mutex::new(false),
+        }
+    }
+
+    /// Marks the pane as running its program on another host, so that
+    /// the title and user vars that it sets are considered remote
+    pub(crate) fn set_remote(&self, remote: bool) {
+        *self.remote.lock() = remote;
+    }
+
+    fn origin(&self, host: Option<String>) -> Origin {
+        let domain = Mux::try_get()
+            .and_then(|mux| mux.get_domain(self.domain_id))
+            .map(|domain| domain.domain_name().to_string())
+            .unwrap_or_default();
+        let remote = *self.remote.lock() || host.as_deref().map_or(false, |h| !is_local_host(h));
+        Origin {
+            pane_id: self.pane_id,
+            domain,
+            host,
+            remote,
+        }
+    }
+
+    fn unlabelled_title(&self) -> String {
+        let title = self.terminal.lock().get_title().to_string();
+        // If the title is the default pane title, then try to spice
+        // things up a bit by returning the process basename instead
+        if title == "wezterm" {
+            if let Some(proc_name) = self.get_foreground_process_name(CachePolicy::AllowStale) {
+                let proc_name = std::path::Path::new(&proc_name);
+                if let Some(name) = proc_name.file_name() {
+                    return name.to_string_lossy().to_string();
+                }
+            }
+        }
+
+        title
+    }
+
+    /// Overrides the slow_paste config for this pane, such as with
+    /// the setting of the domain that spawned it
+    pub(crate) fn set_slow_paste(&self, slow_paste: Option<SlowPaste>) {
+        *self.slow_paste.lock() = slow_paste;
+    }
+
+    fn paste(&self, text: &str, toggle_slow_paste: bool) -> Result<(), Error> {
+        Mux::get().record_input_for_current_identity();
+        if self.tmux_domain.lock().is_some() {
+            return Ok(());
+        }
+
+        let configured = self.slow_paste.lock().or(configuration().slow_paste);
+        let slow_paste = match (configured, toggle_slow_paste) {
+            (Some(slow_paste), false) => Some(slow_paste),
+            (None, true) => Some(SlowPaste::default()),
+            _ => None,
+        };
+
+        match slow_paste {
+            Some(slow_paste) => {
+                let data = self.terminal.lock().paste_data(text);
+                trickle_paste(self.pane_id, data, slow_paste);
+                Ok(())
+            }
+            None => self.terminal.lock().send_paste(text),
+        }
+    }
+
+    /// Remembers the command that was spawned in the pane
+    pub(crate) fn set_command(&self, command: CommandBuilder) {
+        self.command.lock().replace(command);
+    }
+
+    /// Returns the command that was spawned in the pane, if known
+    pub(crate) fn get_command(&self) -> Option<CommandBuilder> {
+        self.command.lock().clone()
+    }
+
+    /// Returns true if the pane is being held open after its process
+    /// exited, and knows which command to run again
+    fn can_respawn(&self) -> bool {
+        matches!(
+            &*self.process.lock(),
+            ProcessState::DeadPendingClose { killed: false }
+        ) && (self.command.lock().is_some() || self.is_failed_spawn())
+    }
+
+    fn is_failed_spawn(&self) -> bool {
+        self.pty.lock().is::<crate::domain::FailedSpawnPty>()
+    }
+
+    /// If the command failed to spawn, returns the command and
+    /// directory that were originally requested
+    pub(crate) fn failed_spawn_request(&self) -> Option<(Option<CommandBuilder>, Option<String>)> {
+        let pty = self.pty.lock();
+        let failed = pty.downcast_ref::<crate::domain::FailedSpawnPty>()?;
+        Some((failed.command.clone(), failed.command_dir.clone()))
+    }
+
+    /// Renders the error from a failed attempt to spawn a command
+    pub(crate) fn show_spawn_error(&self, banner: &str) {
+        let mut term = self.terminal.lock();
+        term.advance_bytes(format!("\r\n{banner}"));
+        self.update_password_prompt(&term);
+    }
+
+    pub(crate) fn terminal_size(&self) -> TerminalSize {
+        self.terminal.lock().get_size()
+    }
+
+    /// Returns true if the process in the pane has not yet exited
+    pub(crate) fn is_process_running(&self) -> bool {
+        match &*self.process.lock() {
+            ProcessState::Running { child_waiter, .. } => child_waiter.is_empty(),
+            _ => false,
         }
     }
 
+    /// Replaces the process that has exited with `process`, running in
+    /// `pty`, while keeping the contents of the terminal.  The caller
+    /// is responsible for reading the output from the new pty.
+    pub(crate) fn respawn(
+        &self,
+        process: Box<dyn Child + Send>,
+        pty: Box<dyn MasterPty>,
+        writer: Box<dyn Write + Send>,
+        command: CommandBuilder,
+        command_description: String,
+    ) {
+        let (process, signaller, pid) = split_child(process);
+        let writer = WriterWrapper::new(writer, self.encoding.clone());
+
+        *self.pty.lock() = pty;
+        *self.writer.lock() = Box::new(writer.clone());
+        {
+            let mut terminal = self.terminal.lock();
+            terminal.set_writer(Box::new(writer));
+            let cols = terminal.get_size().cols;
+            terminal.advance_bytes(respawn_marker(&command_description, cols));
+        }
+        self.proc_list.lock().take();
+        #[cfg(unix)]
+        self.leader.lock().take();
+        *self.command_description.lock() = command_description;
+        self.set_command(command);
+        *self.process.lock() = ProcessState::Running {
+            child_waiter: process,
+            pid,
+            signaller,
+            killed: false,
+        };
+    }
+
     #[cfg(unix)]
     fn get_leader(&self, policy: CachePolicy) -> CachedLeaderInfo {
         let mut leader = self.leader.lock();
@@ -1040,6 +1360,38 @@ impl LocalPane {
         (*leader).clone().unwrap()
     }
 
+    /// Detect whether we might be in password input mode.
+    fn is_password_input(&self) -> bool {
+        #[cfg(unix)]
+        if let Some(tio) = self.pty.lock().get_termios() {
+            use nix::sys::termios::LocalFlags;
+            // If local echo is disabled and canonical input mode
+            // is enabled, then we assume that we're in some kind
+            // of password-entry mode.
+            return !tio.local_flags.contains(LocalFlags::ECHO)
+                && tio.local_flags.contains(LocalFlags::ICANON);
+        }
+
+        // We can't query the pty, which is the case for ssh sessions,
+        // so fall back to examining the text before the cursor.
+        // The terminal may already be locked by our caller, so we
+        // use the result from when the output was last processed.
+        *self.password_prompt.lock()
+    }
+
+    fn update_password_prompt(&self, term: &Terminal) {
+        let cursor = term.cursor_pos();
+        let screen = term.screen();
+        let phys = screen.phys_row(cursor.y);
+        let prompt = match screen.lines_in_phys_range(phys..phys + 1).pop() {
+            Some(line) => {
+                crate::pane::looks_like_password_prompt(&line.columns_as_str(0..cursor.x))
+            }
+            None => false,
+        };
+        *self.password_prompt.lock() = prompt;
+    }
+
     fn divine_current_working_dir(&self, policy: CachePolicy) -> Option<Url> {
         #[cfg(unix)]
         {
@@ -1135,3 +1487,154 @@ impl Drop for LocalPane {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paste_chunks_split_on_chars() {
+        assert_eq!(paste_chunks("abcde", 2), vec!["ab", "cd", "e"]);
+        assert_eq!(paste_chunks("h\u{e9}llo", 3), vec!["h\u{e9}l", "lo"]);
+        assert_eq!(paste_chunks("ab", 0), vec!["a", "b"]);
+        assert!(paste_chunks("", 4).is_empty());
+    }
+
+    #[test]
+    fn same_host() {
+        assert!(is_same_host("devbox", "devbox"));
+        assert!(is_same_host("DevBox.example.com", "devbox"));
+        assert!(is_same_host("devbox", "devbox.local"));
+        assert!(is_same_host("localhost", "devbox"));
+        assert!(!is_same_host("prod", "devbox"));
+        assert!(!is_same_host("devbox2.example.com", "devbox"));
+    }
+
+    /// A child that runs until the test process exits
+    #[derive(Debug)]
+    struct TestChild;
+
+    impl Child for TestChild {
+        fn try_wait(&mut self) -> IoResult<Option<ExitStatus>> {
+            Ok(None)
+        }
+
+        fn wait(&mut self) -> IoResult<ExitStatus> {
+            loop {
+                std::thread::park();
+            }
+        }
+
+        fn process_id(&self) -> Option<u32> {
+            None
+        }
+
+        #[cfg(windows)]
+        fn as_raw_handle(&self) -> Option<std::os::windows::io::RawHandle> {
+            None
+        }
+    }
+
+    impl ChildKiller for TestChild {
+        fn kill(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+
+        fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+            Box::new(TestChild)
+        }
+    }
+
+    /// A pty whose termios can't be queried, like that of an ssh session
+    struct TestPty;
+
+    impl MasterPty for TestPty {
+        fn resize(&self, _size: PtySize) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn get_size(&self) -> anyhow::Result<PtySize> {
+            Ok(PtySize::default())
+        }
+
+        fn try_clone_reader(&self) -> anyhow::Result<Box<dyn std::io::Read + Send>> {
+            Ok(Box::new(std::io::empty()))
+        }
+
+        fn take_writer(&self) -> anyhow::Result<Box<dyn Write + Send>> {
+            Ok(Box::new(std::io::sink()))
+        }
+
+        #[cfg(unix)]
+        fn process_group_leader(&self) -> Option<libc::pid_t> {
+            None
+        }
+
+        #[cfg(unix)]
+        fn as_raw_fd(&self) -> Option<std::os::fd::RawFd> {
+            None
+        }
+
+        #[cfg(unix)]
+        fn tty_name(&self) -> Option<std::path::PathBuf> {
+            None
+        }
+    }
+
+    fn password_input(metadata: &Value) -> bool {
+        match metadata {
+            Value::Object(obj) => {
+                obj.get(&Value::String("password_input".to_string())) == Some(&Value::Bool(true))
+            }
+            _ => false,
+        }
+    }
+
+    struct MetadataWhileRendering<'a> {
+        pane: &'a LocalPane,
+        metadata: Option<Value>,
+    }
+
+    impl<'a> WithPaneLines for MetadataWhileRendering<'a> {
+        fn with_lines_mut(&mut self, _first_row: StableRowIndex, _lines: &mut [&mut Line]) {
+            self.metadata.replace(self.pane.get_metadata());
+        }
+    }
+
+    #[test]
+    fn password_prompt_while_rendering() {
+        let terminal = Terminal::new(
+            TerminalSize::default(),
+            Arc::new(config::TermConfig::new()),
+            "WezTerm",
+            "test",
+            Box::new(std::io::sink()),
+        );
+        let pane = LocalPane::new(
+            0,
+            terminal,
+            Box::new(TestChild),
+            Box::new(TestPty),
+            Box::new(std::io::sink()),
+            SharedEncoding::new(CharacterEncoding::Utf8),
+            0,
+            String::new(),
+        );
+
+        let mut render = MetadataWhileRendering {
+            pane: &pane,
+            metadata: None,
+        };
+        pane.with_lines_mut(0..1, &mut render);
+        assert!(!password_input(&render.metadata.take().unwrap()));
+
+        let mut parser = termwiz::escape::parser::Parser::new();
+        pane.perform_actions(parser.parse_as_vec(b"[sudo] password for wez: "));
+        pane.with_lines_mut(0..1, &mut render);
+        assert!(password_input(&render.metadata.take().unwrap()));
+
+        pane.perform_actions(parser.parse_as_vec(b"\r\n$ "));
+        pane.with_lines_mut(0..2, &mut render);
+        assert!(!password_input(&render.metadata.take().unwrap()));
+    }
+}
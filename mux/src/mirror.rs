@@ -0,0 +1,293 @@
+//! A mirror is a read-only view of another pane, so that its content
+//! can be shown in a second window, for example on a projector, while
+//! it continues to be used from its original window.
+//! The mirror shares the terminal model of the pane that it follows,
+//! and turns the output notifications for that pane into notifications
+//! for itself, so that the gui repaints it through the usual machinery.
+//! Input sent to the mirror is discarded.
+use crate::domain::DomainId;
+use crate::pane::{
+    alloc_pane_id, CachePolicy, CloseReason, ForEachPaneLogicalLine, LogicalLine, Pane, PaneId,
+    Pattern, SearchResult, WithPaneLines,
+};
+use crate::renderable::*;
+use crate::{Mux, MuxNotification};
+use async_trait::async_trait;
+use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
+use rangeset::RangeSet;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use termwiz::surface::{Line, SequenceNo};
+use url::Url;
+use wezterm_dynamic::Value;
+use wezterm_term::color::ColorPalette;
+use wezterm_term::{
+    CommandRecord, KeyCode, KeyModifiers, MouseEvent, SemanticZone, StableRowIndex,
+    TerminalConfiguration, TerminalSize,
+};
+
+pub struct MirrorPane {
+    pane_id: PaneId,
+    source: Arc<dyn Pane>,
+    /// Set once the source pane has been removed from the mux
+    source_removed: AtomicBool,
+    writer: Mutex<std::io::Sink>,
+}
+
+impl MirrorPane {
+    /// Creates a mirror of `source` that follows its notifications
+    /// from `mux`.
+    /// Mirroring a mirror follows the pane that it mirrors.
+    pub fn new(mux: &Mux, source: &Arc<dyn Pane>) -> Arc<Self> {
+        let source = match source.downcast_ref::<MirrorPane>() {
+            Some(mirror) => Arc::clone(&mirror.source),
+            None => Arc::clone(source),
+        };
+        let mirror = Arc::new(Self {
+            pane_id: alloc_pane_id(),
+            source,
+            source_removed: AtomicBool::new(false),
+            writer: Mutex::new(std::io::sink()),
+        });
+
+        let weak = Arc::downgrade(&mirror);
+        mux.subscribe(move |n| {
+            let mirror = match weak.upgrade() {
+                Some(mirror) => mirror,
+                None => return false,
+            };
+            if let Some(notification) = mirror.follow_source(&n) {
+                // We're called with the subscriber list locked, so
+                // the notification has to be deferred
+                promise::spawn::spawn_into_main_thread(async move {
+                    if let Some(mux) = Mux::try_get() {
+                        mux.notify(notification);
+                    }
+                })
+                .detach();
+            }
+            !mirror.source_removed.load(Ordering::Relaxed)
+        });
+
+        mirror
+    }
+
+    /// Tracks the notifications about the source pane, returning the
+    /// notification that is due for the mirror in turn, if any
+    fn follow_source(&self, n: &MuxNotification) -> Option<MuxNotification> {
+        let source_id = self.source.pane_id();
+        match n {
+            MuxNotification::PaneOutput(pane_id) if *pane_id == source_id => {
+                Some(MuxNotification::PaneOutput(self.pane_id))
+            }
+            MuxNotification::PaneRemoved(pane_id) if *pane_id == source_id => {
+                self.source_removed.store(true, Ordering::Relaxed);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the pane that is being mirrored
+    pub fn source(&self) -> &Arc<dyn Pane> {
+        &self.source
+    }
+}
+
+#[async_trait(?Send)]
+impl Pane for MirrorPane {
+    fn pane_id(&self) -> PaneId {
+        self.pane_id
+    }
+
+    fn get_cursor_position(&self) -> StableCursorPosition {
+        self.source.get_cursor_position()
+    }
+
+    fn get_current_seqno(&self) -> SequenceNo {
+        self.source.get_current_seqno()
+    }
+
+    fn get_metadata(&self) -> Value {
+        self.source.get_metadata()
+    }
+
+    fn get_changed_since(
+        &self,
+        lines: Range<StableRowIndex>,
+        seqno: SequenceNo,
+    ) -> RangeSet<StableRowIndex> {
+        self.source.get_changed_since(lines, seqno)
+    }
+
+    fn get_lines(&self, lines: Range<StableRowIndex>) -> (StableRowIndex, Vec<Line>) {
+        self.source.get_lines(lines)
+    }
+
+    fn with_lines_mut(&self, lines: Range<StableRowIndex>, with_lines: &mut dyn WithPaneLines) {
+        self.source.with_lines_mut(lines, with_lines)
+    }
+
+    fn for_each_logical_line_in_stable_range_mut(
+        &self,
+        lines: Range<StableRowIndex>,
+        for_line: &mut dyn ForEachPaneLogicalLine,
+    ) {
+        self.source
+            .for_each_logical_line_in_stable_range_mut(lines, for_line)
+    }
+
+    fn get_logical_lines(&self, lines: Range<StableRowIndex>) -> Vec<LogicalLine> {
+        self.source.get_logical_lines(lines)
+    }
+
+    fn get_dimensions(&self) -> RenderableDimensions {
+        self.source.get_dimensions()
+    }
+
+    fn get_title(&self) -> String {
+        format!("Mirror: {}", self.source.get_title())
+    }
+
+    fn send_paste(&self, _text: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn reader(&self) -> anyhow::Result<Option<Box<dyn std::io::Read + Send>>> {
+        Ok(None)
+    }
+
+    fn writer(&self) -> MappedMutexGuard<dyn std::io::Write> {
+        MutexGuard::map(self.writer.lock(), |writer| {
+            let w: &mut dyn std::io::Write = writer;
+            w
+        })
+    }
+
+    fn resize(&self, _size: TerminalSize) -> anyhow::Result<()> {
+        // The size is determined by the source pane
+        Ok(())
+    }
+
+    fn key_down(&self, _key: KeyCode, _mods: KeyModifiers) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn key_up(&self, _key: KeyCode, _mods: KeyModifiers) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn mouse_event(&self, _event: MouseEvent) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_dead(&self) -> bool {
+        self.source_removed.load(Ordering::Relaxed) || self.source.is_dead()
+    }
+
+    fn palette(&self) -> ColorPalette {
+        self.source.palette()
+    }
+
+    fn domain_id(&self) -> DomainId {
+        // Panes spawned from the mirror, and the domain related features
+        // of the gui, use the domain of the pane that it mirrors
+        self.source.domain_id()
+    }
+
+    fn copy_user_vars(&self) -> HashMap<String, String> {
+        self.source.copy_user_vars()
+    }
+
+    fn set_user_var(&self, name: &str, value: &str) -> anyhow::Result<()> {
+        self.source.set_user_var(name, value)
+    }
+
+    fn can_close_without_prompting(&self, _reason: CloseReason) -> bool {
+        true
+    }
+
+    async fn search(
+        &self,
+        pattern: Pattern,
+        range: Range<StableRowIndex>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        self.source.search(pattern, range, limit).await
+    }
+
+    fn get_semantic_zones(&self) -> anyhow::Result<Vec<SemanticZone>> {
+        self.source.get_semantic_zones()
+    }
+
+    async fn get_command_history(&self) -> anyhow::Result<Vec<CommandRecord>> {
+        self.source.get_command_history().await
+    }
+
+    fn is_mouse_grabbed(&self) -> bool {
+        // Let the gui handle selection and scrolling locally
+        false
+    }
+
+    fn is_alt_screen_active(&self) -> bool {
+        self.source.is_alt_screen_active()
+    }
+
+    fn get_config(&self) -> Option<Arc<dyn TerminalConfiguration>> {
+        self.source.get_config()
+    }
+
+    fn get_current_working_dir(&self, policy: CachePolicy) -> Option<Url> {
+        self.source.get_current_working_dir(policy)
+    }
+
+    fn get_foreground_process_name(&self, policy: CachePolicy) -> Option<String> {
+        self.source.get_foreground_process_name(policy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tab::test::FakePane;
+
+    #[test]
+    fn follows_the_source_pane() {
+        let mux = Mux::new(None);
+        let source = FakePane::in_domain(1, TerminalSize::default(), 7);
+        let mirror = MirrorPane::new(&mux, &source);
+        assert_eq!(mirror.domain_id(), 7);
+
+        // Mirroring the mirror mirrors the source pane
+        let mirror_pane: Arc<dyn Pane> = mirror.clone();
+        let second = MirrorPane::new(&mux, &mirror_pane);
+        assert_eq!(second.source().pane_id(), 1);
+
+        let mirror_id = mirror.pane_id();
+        assert!(matches!(
+            mirror.follow_source(&MuxNotification::PaneOutput(1)),
+            Some(MuxNotification::PaneOutput(id)) if id == mirror_id
+        ));
+        assert!(mirror
+            .follow_source(&MuxNotification::PaneOutput(2))
+            .is_none());
+        assert!(mirror
+            .follow_source(&MuxNotification::PaneOutput(mirror_id))
+            .is_none());
+    }
+
+    #[test]
+    fn dies_with_the_source_pane() {
+        let mux = Mux::new(None);
+        let source = FakePane::new(1, TerminalSize::default());
+        let mirror = MirrorPane::new(&mux, &source);
+        let other = FakePane::new(2, TerminalSize::default());
+        let other_mirror = MirrorPane::new(&mux, &other);
+
+        mux.notify(MuxNotification::PaneRemoved(1));
+        assert!(mirror.is_dead());
+        assert!(!other_mirror.is_dead());
+    }
+}
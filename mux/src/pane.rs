@@ -3,7 +3,9 @@ use crate::renderable::*;
 use crate::ExitBehavior;
 use async_trait::async_trait;
 use config::keyassignment::{KeyAssignment, ScrollbackEraseMode};
+use config::CharacterEncoding;
 use downcast_rs::{impl_downcast, Downcast};
+use luahelper::impl_lua_conversion_dynamic;
 use parking_lot::MappedMutexGuard;
 use rangeset::RangeSet;
 use serde::{Deserialize, Serialize};
@@ -14,11 +16,11 @@ use termwiz::hyperlink::Rule;
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::{Line, SequenceNo};
 use url::Url;
-use wezterm_dynamic::Value;
+use wezterm_dynamic::{FromDynamic, ToDynamic, Value};
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent, SemanticZone, StableRowIndex,
-    TerminalConfiguration, TerminalSize,
+    Clipboard, CommandRecord, DownloadHandler, KeyCode, KeyModifiers, MouseEvent, SemanticZone,
+    StableRowIndex, TerminalConfiguration, TerminalSize, TerminalSnapshot,
 };
 
 static PANE_ID: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
@@ -216,6 +218,12 @@ pub trait Pane: Downcast + Send + Sync {
 
     fn get_title(&self) -> String;
     fn send_paste(&self, text: &str) -> anyhow::Result<()>;
+    /// Sends a paste with the slow_paste setting of the pane toggled
+    /// for this paste only.  Panes that don't support slow paste
+    /// send it as usual.
+    fn send_paste_toggling_slow_paste(&self, text: &str) -> anyhow::Result<()> {
+        self.send_paste(text)
+    }
     fn reader(&self) -> anyhow::Result<Option<Box<dyn std::io::Read + Send>>>;
     fn writer(&self) -> MappedMutexGuard<dyn std::io::Write>;
     fn resize(&self, size: TerminalSize) -> anyhow::Result<()>;
@@ -242,6 +250,22 @@ pub trait Pane: Downcast + Send + Sync {
         HashMap::new()
     }
 
+    /// Sets the user var `name` of this pane, as though the process in
+    /// it had set it
+    fn set_user_var(&self, _name: &str, _value: &str) -> anyhow::Result<()> {
+        anyhow::bail!("the user vars of this pane cannot be set")
+    }
+
+    /// Returns where the title of the pane was set
+    fn get_title_origin(&self) -> Option<Origin> {
+        None
+    }
+
+    /// Returns where the user var `name` of the pane was set
+    fn get_user_var_origin(&self, _name: &str) -> Option<Origin> {
+        None
+    }
+
     fn erase_scrollback(&self, _erase_mode: ScrollbackEraseMode) {}
 
     /// Called to advise on whether this tab has focus
@@ -284,6 +308,20 @@ pub trait Pane: Downcast + Send + Sync {
         Ok(vec![])
     }
 
+    /// Returns the commands that the shell has reported running in
+    /// this pane via OSC 133 shell integration, oldest first
+    async fn get_command_history(&self) -> anyhow::Result<Vec<CommandRecord>> {
+        Ok(vec![])
+    }
+
+    /// Captures the state of the terminal model so that it can be
+    /// restored elsewhere to reproduce a problem.
+    /// Only panes whose terminal model is held by this process
+    /// support this.
+    fn get_terminal_snapshot(&self) -> anyhow::Result<TerminalSnapshot> {
+        anyhow::bail!("pane {} does not support snapshots", self.pane_id())
+    }
+
     /// Returns true if the terminal has grabbed the mouse and wants to
     /// give the embedded application a chance to process events.
     /// In practice this controls whether the gui will perform local
@@ -291,6 +329,10 @@ pub trait Pane: Downcast + Send + Sync {
     fn is_mouse_grabbed(&self) -> bool;
     fn is_alt_screen_active(&self) -> bool;
 
+    /// Overrides the alternate_screen_capture config for this pane,
+    /// or restores it if `enabled` is None
+    fn set_alt_screen_capture(&self, _enabled: Option<bool>) {}
+
     fn set_clipboard(&self, _clipboard: &Arc<dyn Clipboard>) {}
     fn set_download_handler(&self, _handler: &Arc<dyn DownloadHandler>) {}
     fn set_config(&self, _config: Arc<dyn TerminalConfiguration>) {}
@@ -316,9 +358,43 @@ pub trait Pane: Downcast + Send + Sync {
     fn exit_behavior(&self) -> Option<ExitBehavior> {
         None
     }
+
+    /// Overrides the exit_behavior config for this pane,
+    /// or restores it if `behavior` is None
+    fn set_exit_behavior(&self, _behavior: Option<ExitBehavior>) {}
+
+    /// Returns the character encoding used to exchange data with
+    /// the process in this pane
+    fn get_encoding(&self) -> CharacterEncoding {
+        CharacterEncoding::Utf8
+    }
+
+    /// Changes the character encoding used to exchange data with
+    /// the process in this pane
+    fn set_encoding(&self, _encoding: CharacterEncoding) -> anyhow::Result<()> {
+        anyhow::bail!("the encoding of this pane cannot be changed")
+    }
 }
 impl_downcast!(Pane);
 
+/// Describes where the title or a user var of a pane was set, so that
+/// a program on a remote host can't pass itself off as a local one
+#[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct Origin {
+    pub pane_id: PaneId,
+    /// The name of the domain of the pane
+    pub domain: String,
+    /// The host that the program had reported with OSC 7 when it set
+    /// the value.  This is claimed by the program, so it may be false.
+    #[dynamic(default)]
+    pub host: Option<String>,
+    /// True if the value was set by a program on another host, either
+    /// because the pane belongs to a remote domain or because the
+    /// program reported a host other than this one
+    pub remote: bool,
+}
+impl_lua_conversion_dynamic!(Origin);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CachePolicy {
     FetchImmediate,
@@ -520,6 +596,23 @@ pub fn impl_get_lines_via_with_lines<P: Pane + ?Sized>(
     (collector.first, collector.lines)
 }
 
+/// Heuristically determines whether `text`, the portion of the cursor
+/// line that precedes the cursor, looks like a prompt for a password.
+/// This is used for panes where the termios state of the pty cannot be
+/// queried, such as those connected via ssh or the mux protocol.
+pub fn looks_like_password_prompt(text: &str) -> bool {
+    let text = text.trim_end();
+    let prompt = match text.strip_suffix(':') {
+        Some(prompt) => prompt,
+        None => return false,
+    };
+    prompt.split(|c: char| !c.is_alphanumeric()).any(|word| {
+        ["password", "passphrase", "passcode", "pin"]
+            .iter()
+            .any(|candidate| word.eq_ignore_ascii_case(candidate))
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1063,4 +1156,17 @@ mod test {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn password_prompt_heuristic() {
+        assert!(looks_like_password_prompt("Password: "));
+        assert!(looks_like_password_prompt("[sudo] password for wez:"));
+        assert!(looks_like_password_prompt(
+            "Enter passphrase for key '/home/wez/.ssh/id_ed25519': "
+        ));
+        assert!(looks_like_password_prompt("Enter PIN for token:"));
+        assert!(!looks_like_password_prompt("$ echo password"));
+        assert!(!looks_like_password_prompt("Pinned commits:"));
+        assert!(!looks_like_password_prompt("Username: "));
+    }
 }
@@ -0,0 +1,379 @@
+//! Supervises the background programs of the `services` configuration.
+//! Each program runs under a thread of its own that restarts it when it
+//! exits, waiting longer each time that it exits soon after starting.
+use config::{Service, ServiceRestart};
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use wezterm_dynamic::{FromDynamic, ToDynamic};
+
+/// How often a running program is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// A program that ran for at least this long before exiting is
+/// restarted after the initial delay rather than a longer one
+const STABLE_RUN: Duration = Duration::from_secs(60);
+/// How long a program is given to exit after being asked to stop,
+/// before it is killed
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum ServiceState {
+    /// The program is running
+    Running,
+    /// The program exited, or couldn't be started, and is waiting to
+    /// be restarted
+    Restarting,
+    /// The program exited and won't be restarted
+    Exited,
+}
+
+#[derive(Debug, Clone, FromDynamic, ToDynamic)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub state: ServiceState,
+    /// The process id of the program while it is running
+    pub pid: Option<u32>,
+    /// How many times the program was restarted
+    pub restarts: usize,
+    /// How the program last exited, or why it couldn't be started
+    pub last_exit: Option<String>,
+}
+
+struct Shared {
+    status: ServiceStatus,
+    stopping: bool,
+}
+
+type SharedState = Arc<(Mutex<Shared>, Condvar)>;
+
+/// A service whose program is kept running until this is stopped
+/// or dropped
+struct Supervised {
+    service: Service,
+    shared: SharedState,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Supervised {
+    /// Starts supervising `service`.  If `predecessor` is given, the
+    /// program isn't started until that thread, which is stopping a
+    /// previous version of the service, has finished.
+    fn start(service: Service, predecessor: Option<JoinHandle<()>>) -> Self {
+        let shared: SharedState = Arc::new((
+            Mutex::new(Shared {
+                status: ServiceStatus {
+                    name: service.name.clone(),
+                    state: ServiceState::Restarting,
+                    pid: None,
+                    restarts: 0,
+                    last_exit: None,
+                },
+                stopping: false,
+            }),
+            Condvar::new(),
+        ));
+
+        let thread = std::thread::Builder::new()
+            .name(format!("service {}", service.name))
+            .spawn({
+                let service = service.clone();
+                let shared = Arc::clone(&shared);
+                move || {
+                    if let Some(predecessor) = predecessor {
+                        predecessor.join().ok();
+                    }
+                    supervise(service, shared)
+                }
+            });
+        let thread = match thread {
+            Ok(thread) => Some(thread),
+            Err(err) => {
+                log::error!("Failed to start service {}: {:#}", service.name, err);
+                None
+            }
+        };
+
+        Self {
+            service,
+            shared,
+            thread,
+        }
+    }
+
+    /// Asks the thread to stop the program without waiting for it,
+    /// returning the thread so that the caller can wait if it wants to
+    fn stop(&mut self) -> Option<JoinHandle<()>> {
+        let (lock, cvar) = &*self.shared;
+        lock.lock().stopping = true;
+        cvar.notify_all();
+        self.thread.take()
+    }
+}
+
+impl Drop for Supervised {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The services that are being supervised
+#[derive(Default)]
+pub struct Services {
+    supervised: Mutex<Vec<Supervised>>,
+}
+
+impl Services {
+    /// Stops the services that are no longer configured, or whose
+    /// configuration changed, and starts the new ones.  This doesn't
+    /// wait for the programs of the stale services to exit.
+    pub fn update(&self, services: &[Service]) {
+        let mut supervised = self.supervised.lock();
+        let (mut kept, stale): (Vec<Supervised>, Vec<Supervised>) =
+            std::mem::take(&mut *supervised)
+                .into_iter()
+                .partition(|s| services.contains(&s.service));
+
+        // A replacement with the same name waits for the stale service
+        // to stop, so that the two programs don't compete for things
+        // such as a socket
+        let mut stopping = HashMap::new();
+        for mut s in stale {
+            if let Some(thread) = s.stop() {
+                stopping.insert(s.service.name.clone(), thread);
+            }
+        }
+
+        for service in services {
+            match kept.iter().position(|s| &s.service == service) {
+                Some(idx) => supervised.push(kept.remove(idx)),
+                None => {
+                    log::info!("Starting service {}", service.name);
+                    supervised.push(Supervised::start(
+                        service.clone(),
+                        stopping.remove(&service.name),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Stops all of the services, waiting for their programs to exit
+    pub fn stop_all(&self) {
+        let mut stopped = std::mem::take(&mut *self.supervised.lock());
+        // Ask all of them to stop before waiting, so that their grace
+        // periods overlap
+        let threads: Vec<JoinHandle<()>> = stopped.iter_mut().filter_map(|s| s.stop()).collect();
+        for thread in threads {
+            thread.join().ok();
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<ServiceStatus> {
+        self.supervised
+            .lock()
+            .iter()
+            .map(|s| s.shared.0.lock().status.clone())
+            .collect()
+    }
+}
+
+fn spawn(service: &Service) -> anyhow::Result<Child> {
+    let (prog, args) = service
+        .args
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("service has no args to run"))?;
+    let mut cmd = Command::new(prog);
+    cmd.args(args)
+        .envs(&service.set_environment_variables)
+        .current_dir(service.cwd.as_deref().unwrap_or(config::HOME_DIR.as_path()))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Put the program into a process group of its own, so that it
+    // can be stopped along with any processes that it starts
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    Ok(cmd.spawn()?)
+}
+
+/// Sends `signal` to the process group of `child`
+#[cfg(unix)]
+fn signal_group(child: &Child, signal: libc::c_int) {
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, signal);
+    }
+}
+
+/// Asks `child` to exit, killing it if it is still running after
+/// the grace period.  On unix the whole process group of the program
+/// is asked to exit with SIGTERM, so that it can clean up things
+/// such as sockets, and then killed with SIGKILL.
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        signal_group(child, libc::SIGTERM);
+        let deadline = Instant::now() + STOP_GRACE_PERIOD;
+        while Instant::now() < deadline {
+            match child.try_wait() {
+                Ok(None) => std::thread::sleep(POLL_INTERVAL),
+                _ => return,
+            }
+        }
+        signal_group(child, libc::SIGKILL);
+    }
+    child.kill().ok();
+    child.wait().ok();
+}
+
+/// Waits for `child` to exit, returning None if the service was
+/// stopped first, in which case the program is stopped too
+fn wait(child: &mut Child, shared: &SharedState) -> Option<std::io::Result<ExitStatus>> {
+    let (lock, cvar) = &**shared;
+    let mut guard = lock.lock();
+    loop {
+        if guard.stopping {
+            drop(guard);
+            stop(child);
+            return None;
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(Ok(status)),
+            Ok(None) => {}
+            Err(err) => return Some(Err(err)),
+        }
+        cvar.wait_for(&mut guard, POLL_INTERVAL);
+    }
+}
+
+/// The delays before restarting a program that keeps exiting
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    fn new(service: &Service) -> Self {
+        let initial = Duration::from_millis(service.restart_delay_ms);
+        Self {
+            initial,
+            max: Duration::from_millis(service.max_restart_delay_ms).max(initial),
+            next: initial,
+        }
+    }
+
+    /// Returns how long to wait before restarting a program that ran
+    /// for `ran` before it exited.  The delay doubles with each restart,
+    /// up to the maximum, and goes back to the initial delay once the
+    /// program has run for long enough.
+    fn delay(&mut self, ran: Duration) -> Duration {
+        if ran >= STABLE_RUN {
+            self.next = self.initial;
+        }
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+fn supervise(service: Service, shared: SharedState) {
+    let mut backoff = Backoff::new(&service);
+
+    loop {
+        if shared.0.lock().stopping {
+            return;
+        }
+        let started = Instant::now();
+        let outcome = match spawn(&service) {
+            Ok(mut child) => {
+                {
+                    let mut guard = shared.0.lock();
+                    guard.status.state = ServiceState::Running;
+                    guard.status.pid = Some(child.id());
+                }
+                match wait(&mut child, &shared) {
+                    Some(outcome) => outcome.map_err(Into::into),
+                    None => return,
+                }
+            }
+            Err(err) => Err(err),
+        };
+
+        let (success, last_exit) = match outcome {
+            Ok(status) => (status.success(), status.to_string()),
+            Err(err) => (false, format!("{err:#}")),
+        };
+        let restart = match service.restart {
+            ServiceRestart::Always => true,
+            ServiceRestart::OnFailure => !success,
+            ServiceRestart::Never => false,
+        };
+        if success {
+            log::info!("Service {} exited: {last_exit}", service.name);
+        } else {
+            log::warn!("Service {} failed: {last_exit}", service.name);
+        }
+
+        let (lock, cvar) = &*shared;
+        let mut guard = lock.lock();
+        guard.status.pid = None;
+        guard.status.last_exit = Some(last_exit);
+        if !restart {
+            guard.status.state = ServiceState::Exited;
+            return;
+        }
+        guard.status.state = ServiceState::Restarting;
+
+        let deadline = Instant::now() + backoff.delay(started.elapsed());
+        loop {
+            if guard.stopping {
+                return;
+            }
+            if cvar.wait_until(&mut guard, deadline).timed_out() {
+                break;
+            }
+        }
+        guard.status.restarts += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff() {
+        let service = Service {
+            restart_delay_ms: 1000,
+            max_restart_delay_ms: 5000,
+            ..Default::default()
+        };
+        let mut backoff = Backoff::new(&service);
+        let quick = Duration::from_millis(10);
+        let secs = |n| Duration::from_secs(n);
+        assert_eq!(backoff.delay(quick), secs(1));
+        assert_eq!(backoff.delay(quick), secs(2));
+        assert_eq!(backoff.delay(quick), secs(4));
+        assert_eq!(backoff.delay(quick), secs(5));
+        assert_eq!(backoff.delay(quick), secs(5));
+
+        // A program that ran for a while starts over
+        assert_eq!(backoff.delay(STABLE_RUN), secs(1));
+        assert_eq!(backoff.delay(quick), secs(2));
+    }
+
+    #[test]
+    fn backoff_max_below_initial() {
+        let service = Service {
+            restart_delay_ms: 3000,
+            max_restart_delay_ms: 1000,
+            ..Default::default()
+        };
+        let mut backoff = Backoff::new(&service);
+        assert_eq!(backoff.delay(Duration::ZERO), Duration::from_secs(3));
+        assert_eq!(backoff.delay(Duration::ZERO), Duration::from_secs(3));
+    }
+}
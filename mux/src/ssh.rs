@@ -1,11 +1,12 @@
 use crate::connui::ConnectionUI;
 use crate::domain::{alloc_domain_id, Domain, DomainId, DomainState, WriterWrapper};
+use crate::encoding::SharedEncoding;
 use crate::localpane::LocalPane;
 use crate::pane::{alloc_pane_id, Pane, PaneId};
 use crate::Mux;
 use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
-use config::{Shell, SshBackend, SshDomain};
+use config::{ExitBehavior, Shell, SshBackend, SshDomain};
 use filedescriptor::{poll, pollfd, socketpair, AsRawSocketDescriptor, FileDescriptor, POLLIN};
 use portable_pty::cmdbuilder::CommandBuilder;
 use portable_pty::{ChildKiller, ExitStatus, MasterPty, PtySize};
@@ -244,6 +245,10 @@ impl RemoteSshDomain {
         ssh_domain_to_ssh_config(&self.dom)
     }
 
+    pub fn ssh_domain(&self) -> &SshDomain {
+        &self.dom
+    }
+
     fn build_command(
         &self,
         pane_id: PaneId,
@@ -251,8 +256,20 @@ impl RemoteSshDomain {
         command_dir: Option<String>,
     ) -> anyhow::Result<(Option<String>, HashMap<String, String>)> {
         let config = config::configuration();
+        let mut spawn_env = HashMap::new();
         let cmd = match command {
             Some(mut cmd) => {
+                // The environment that was specified when spawning takes
+                // precedence over that of the domain and the config
+                spawn_env = cmd
+                    .iter_extra_env_as_str()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                if cmd.is_default_prog() {
+                    if let Some(prog) = &self.dom.default_prog {
+                        cmd.get_argv_mut().extend(prog.iter().map(Into::into));
+                    }
+                }
                 config.apply_cmd_defaults(&mut cmd, None);
                 cmd
             }
@@ -262,6 +279,9 @@ impl RemoteSshDomain {
             .iter_extra_env_as_str()
             .map(|(k, v)| (k.to_string(), v.to_string()))
             .collect();
+        env.extend(self.dom.set_environment_variables.clone());
+        env.extend(spawn_env);
+        let login_shell = self.dom.login_shell.unwrap_or(config.login_shell);
 
         // FIXME: this isn't useful without a way to talk to the remote mux.
         // One option is to forward the mux via unix domain, another is to
@@ -273,6 +293,7 @@ impl RemoteSshDomain {
             dir: Option<String>,
             cmd: &CommandBuilder,
             env: &HashMap<String, String>,
+            login_shell: bool,
         ) -> anyhow::Result<String> {
             // "Soft" chdir: if it doesn't exist then it doesn't matter
             let cd_cmd = if let Some(dir) = dir {
@@ -290,7 +311,9 @@ impl RemoteSshDomain {
                 env_cmd.push(format!("{}={}", k, v));
             }
 
-            let cmd = if cmd.is_default_prog() {
+            let cmd = if cmd.is_default_prog() && !login_shell {
+                "$SHELL".to_string()
+            } else if cmd.is_default_prog() {
                 // We'd like to spawn a login shell, but since we are invoking env
                 // we end up in a regular shell.
                 // This guff tries to find a reasonably portable way to execute
@@ -316,7 +339,7 @@ impl RemoteSshDomain {
         }
 
         let command_line = match (cmd.is_default_prog(), self.dom.assume_shell, command_dir) {
-            (_, Shell::Posix, dir) => Some(build_env_command(dir, &cmd, &env)?),
+            (_, Shell::Posix, dir) => Some(build_env_command(dir, &cmd, &env, login_shell)?),
             (true, _, _) => None,
             (false, _, _) => Some(cmd.as_unix_command_line()?),
         };
@@ -704,6 +727,7 @@ impl Domain for RemoteSshDomain {
         size: TerminalSize,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        exit_behavior: Option<ExitBehavior>,
     ) -> anyhow::Result<Arc<dyn Pane>> {
         let pane_id = alloc_pane_id();
 
@@ -757,25 +781,32 @@ impl Domain for RemoteSshDomain {
         // eg: tmux integration to be tunnelled via the remote
         // session without duplicating a lot of logic over here.
 
-        let writer = WriterWrapper::new(writer);
+        let encoding = SharedEncoding::new(config::configuration().default_pane_encoding);
+        let writer = WriterWrapper::new(writer, encoding.clone());
 
-        let terminal = wezterm_term::Terminal::new(
+        let mut terminal = wezterm_term::Terminal::new(
             size,
-            std::sync::Arc::new(config::TermConfig::new()),
+            std::sync::Arc::new(config::TermConfig::for_domain(&self.name)),
             "WezTerm",
             config::wezterm_version(),
             Box::new(writer.clone()),
         );
+        terminal.set_enq_answerback(self.dom.answerback.clone());
 
-        let pane: Arc<dyn Pane> = Arc::new(LocalPane::new(
+        let pane = LocalPane::new(
             pane_id,
             terminal,
             child,
             pty,
             Box::new(writer),
+            encoding,
             self.id,
             "RemoteSshDomain".to_string(),
-        ));
+        );
+        pane.set_slow_paste(self.dom.slow_paste);
+        pane.set_remote(true);
+        pane.set_exit_behavior(exit_behavior);
+        let pane: Arc<dyn Pane> = Arc::new(pane);
         let mux = Mux::get();
         mux.add_pane(&pane)?;
 
@@ -278,6 +278,9 @@ fn pane_tree(
                 left_col,
                 top_row,
                 tty_name: pane.tty_name(),
+                tags: Mux::try_get()
+                    .map(|mux| mux.get_pane_tags(pane.pane_id()))
+                    .unwrap_or_default(),
             })
         }
     }
@@ -2149,6 +2152,7 @@ pub struct PaneEntry {
     pub top_row: usize,
     pub left_col: usize,
     pub tty_name: Option<String>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Deserialize, Clone, Serialize, PartialEq, Debug)]
@@ -2184,7 +2188,7 @@ impl Into<String> for SerdeUrl {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
     use crate::renderable::*;
     use parking_lot::{MappedMutexGuard, Mutex};
@@ -2195,16 +2199,26 @@ mod test {
     use wezterm_term::color::ColorPalette;
     use wezterm_term::{KeyCode, KeyModifiers, Line, MouseEvent, StableRowIndex};
 
-    struct FakePane {
+    pub(crate) struct FakePane {
         id: PaneId,
         size: Mutex<TerminalSize>,
+        domain_id: DomainId,
     }
 
     impl FakePane {
-        fn new(id: PaneId, size: TerminalSize) -> Arc<dyn Pane> {
+        pub(crate) fn new(id: PaneId, size: TerminalSize) -> Arc<dyn Pane> {
+            Self::in_domain(id, size, 1)
+        }
+
+        pub(crate) fn in_domain(
+            id: PaneId,
+            size: TerminalSize,
+            domain_id: DomainId,
+        ) -> Arc<dyn Pane> {
             Arc::new(Self {
                 id,
                 size: Mutex::new(size),
+                domain_id,
             })
         }
     }
@@ -2291,7 +2305,7 @@ mod test {
             unimplemented!()
         }
         fn domain_id(&self) -> DomainId {
-            1
+            self.domain_id
         }
         fn is_mouse_grabbed(&self) -> bool {
             false
@@ -15,6 +15,7 @@ use crate::Mux;
 use anyhow::bail;
 use async_trait::async_trait;
 use config::keyassignment::ScrollbackEraseMode;
+use config::ExitBehavior;
 use crossbeam::channel::{unbounded as channel, Receiver, Sender};
 use filedescriptor::{FileDescriptor, Pipe};
 use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
@@ -53,6 +54,7 @@ impl Domain for TermWizTerminalDomain {
         _size: TerminalSize,
         _command: Option<CommandBuilder>,
         _command_dir: Option<String>,
+        _exit_behavior: Option<ExitBehavior>,
     ) -> anyhow::Result<Arc<dyn Pane>> {
         bail!("cannot spawn panes in a TermWizTerminalPane");
     }
@@ -1,4 +1,5 @@
 use crate::domain::{DomainId, WriterWrapper};
+use crate::encoding::SharedEncoding;
 use crate::localpane::LocalPane;
 use crate::pane::alloc_pane_id;
 use crate::tab::{Tab, TabId};
@@ -128,7 +129,8 @@ impl TmuxDomainState {
                 cmd_queue: self.cmd_queue.clone(),
                 master_pane: ref_pane,
             };
-            let writer = WriterWrapper::new(pane_pty.take_writer()?);
+            let encoding = SharedEncoding::default();
+            let writer = WriterWrapper::new(pane_pty.take_writer()?, encoding.clone());
             let mux = Mux::get();
             let size = TerminalSize {
                 rows: pane.pane_height as usize,
@@ -142,9 +144,13 @@ impl TmuxDomainState {
                 active_lock: active_lock.clone(),
             };
 
+            let term_config = match mux.get_domain(self.domain_id) {
+                Some(domain) => config::TermConfig::for_domain(domain.domain_name()),
+                None => config::TermConfig::new(),
+            };
             let terminal = wezterm_term::Terminal::new(
                 size,
-                std::sync::Arc::new(config::TermConfig::new()),
+                std::sync::Arc::new(term_config),
                 "WezTerm",
                 config::wezterm_version(),
                 Box::new(writer.clone()),
@@ -156,6 +162,7 @@ impl TmuxDomainState {
                 Box::new(child),
                 Box::new(pane_pty),
                 Box::new(writer),
+                encoding,
                 self.domain_id,
                 "tmux pane".to_string(),
             ));
@@ -0,0 +1,248 @@
+//! Records the escape sequences that were parsed from the output of
+//! a pane, so that they can be examined by the VT inspector and from lua.
+//! Recording is off until it is enabled for a given pane, and the cost
+//! when no pane is being inspected is a single atomic load per batch of
+//! parsed actions.
+use crate::pane::PaneId;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use termwiz::escape::Action;
+use wezterm_dynamic::ToDynamic;
+
+/// How many entries are retained for each pane
+const MAX_ENTRIES: usize = 10_000;
+/// Runs of printable text are coalesced into entries of up to this size
+const MAX_PRINT_LEN: usize = 256;
+
+lazy_static::lazy_static! {
+    static ref INSPECTORS: Mutex<HashMap<PaneId, Inspector>> = Mutex::new(HashMap::new());
+}
+static ANY_ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, PartialEq, Eq, ToDynamic)]
+pub struct InspectedAction {
+    /// Increases by one for each entry recorded for a pane
+    pub seqno: usize,
+    /// The decoded name of the action, such as `CSI::Cursor::Position`
+    pub name: String,
+    /// The parameters of the action, if any
+    pub params: String,
+    /// The sequence as it would be sent to the terminal, with
+    /// control characters escaped
+    pub raw: String,
+}
+
+impl InspectedAction {
+    /// Returns true if `filter` is found in the name, parameters or
+    /// raw text of this entry, ignoring case
+    pub fn matches(&self, filter: &str) -> bool {
+        if filter.is_empty() {
+            return true;
+        }
+        let filter = filter.to_lowercase();
+        self.name.to_lowercase().contains(&filter)
+            || self.params.to_lowercase().contains(&filter)
+            || self.raw.to_lowercase().contains(&filter)
+    }
+}
+
+#[derive(Default)]
+struct Inspector {
+    entries: VecDeque<InspectedAction>,
+    next_seqno: usize,
+}
+
+impl Inspector {
+    fn record(&mut self, action: &Action) {
+        let text = match action {
+            Action::Print(c) => Some(c.to_string()),
+            Action::PrintString(s) => Some(s.to_string()),
+            _ => None,
+        };
+
+        if let Some(text) = &text {
+            if let Some(last) = self.entries.back_mut() {
+                if last.name == "Print" && last.params.len() < MAX_PRINT_LEN {
+                    last.params.push_str(text);
+                    last.raw.push_str(&text.escape_debug().to_string());
+                    return;
+                }
+            }
+        }
+
+        let (name, params) = match text {
+            Some(text) => ("Print".to_string(), text),
+            None => decode_action(&format!("{action:?}")),
+        };
+
+        self.entries.push_back(InspectedAction {
+            seqno: self.next_seqno,
+            name,
+            params,
+            raw: action.to_string().escape_debug().to_string(),
+        });
+        self.next_seqno += 1;
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Splits the Debug representation of an action into the chain of
+/// enum variant names that identify it and the remaining parameters.
+/// `CSI(Cursor(Position { line: .., col: .. }))` becomes
+/// `("CSI::Cursor::Position", "line: .., col: ..")`.
+fn decode_action(debug: &str) -> (String, String) {
+    let mut names = vec![];
+    let mut rest = debug.trim();
+
+    loop {
+        let ident_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let ident = &rest[..ident_len];
+        if ident.is_empty() || !ident.starts_with(|c: char| c.is_ascii_uppercase()) {
+            break;
+        }
+        let after = &rest[ident_len..];
+
+        if after.is_empty() {
+            names.push(ident);
+            rest = "";
+            break;
+        }
+        if after.starts_with('(') && after.ends_with(')') {
+            names.push(ident);
+            let inner = after[1..after.len() - 1].trim();
+            rest = inner;
+            if is_single_variant(inner) {
+                continue;
+            }
+            break;
+        }
+        if after.starts_with(" {") && after.ends_with('}') {
+            names.push(ident);
+            rest = after[2..after.len() - 1].trim();
+            break;
+        }
+        break;
+    }
+
+    (names.join("::"), rest.to_string())
+}
+
+/// Returns true if `text` is a single enum variant, possibly with
+/// its own parameters, rather than a list of values
+fn is_single_variant(text: &str) -> bool {
+    if !text.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return false;
+    }
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' => depth -= 1,
+            ',' if depth == 0 => return false,
+            '"' | '\'' if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Starts or stops recording the actions parsed from the output of
+/// the specified pane.  Stopping discards the recorded entries.
+pub fn set_enabled(pane_id: PaneId, enabled: bool) {
+    let mut inspectors = INSPECTORS.lock();
+    if enabled {
+        inspectors.entry(pane_id).or_default();
+    } else {
+        inspectors.remove(&pane_id);
+    }
+    ANY_ENABLED.store(!inspectors.is_empty(), Ordering::Relaxed);
+}
+
+pub fn is_enabled(pane_id: PaneId) -> bool {
+    ANY_ENABLED.load(Ordering::Relaxed) && INSPECTORS.lock().contains_key(&pane_id)
+}
+
+pub(crate) fn record(pane_id: PaneId, actions: &[Action]) {
+    if !ANY_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Some(inspector) = INSPECTORS.lock().get_mut(&pane_id) {
+        for action in actions {
+            inspector.record(action);
+        }
+    }
+}
+
+/// Returns the recorded entries for the specified pane whose
+/// seqno is at least `since`
+pub fn get_entries(pane_id: PaneId, since: usize) -> Vec<InspectedAction> {
+    match INSPECTORS.lock().get(&pane_id) {
+        Some(inspector) => inspector
+            .entries
+            .iter()
+            .filter(|entry| entry.seqno >= since)
+            .cloned()
+            .collect(),
+        None => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::escape::csi::{Cursor, Sgr, CSI};
+    use termwiz::escape::ControlCode;
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            decode_action(&format!(
+                "{:?}",
+                Action::CSI(CSI::Sgr(Sgr::Intensity(termwiz::cell::Intensity::Bold)))
+            )),
+            ("CSI::Sgr::Intensity::Bold".to_string(), String::new())
+        );
+        assert_eq!(
+            decode_action(&format!("{:?}", Action::Control(ControlCode::LineFeed))),
+            ("Control::LineFeed".to_string(), String::new())
+        );
+        let (name, params) = decode_action(&format!(
+            "{:?}",
+            Action::CSI(CSI::Cursor(Cursor::Position {
+                line: termwiz::escape::OneBased::new(2),
+                col: termwiz::escape::OneBased::new(3),
+            }))
+        ));
+        assert_eq!(name, "CSI::Cursor::Position");
+        assert!(params.starts_with("line:"), "{params}");
+    }
+
+    #[test]
+    fn record_coalesces_text() {
+        let mut inspector = Inspector::default();
+        inspector.record(&Action::Print('h'));
+        inspector.record(&Action::PrintString("ello".to_string()));
+        inspector.record(&Action::Control(ControlCode::CarriageReturn));
+        inspector.record(&Action::Print('!'));
+
+        let entries: Vec<_> = inspector
+            .entries
+            .iter()
+            .map(|e| (e.seqno, e.name.as_str(), e.params.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (0, "Print", "hello"),
+                (1, "Control::CarriageReturn", ""),
+                (2, "Print", "!"),
+            ]
+        );
+        assert_eq!(inspector.entries[1].raw, "\\r");
+    }
+}
@@ -0,0 +1,53 @@
+//! Allows the gui to report the effective configuration of its windows
+//! and to accept per-window overrides, so that they can be requested
+//! via the mux protocol by `wezterm cli get-config` and `set-config`.
+//! The mux has no notion of configuration overrides of its own, so
+//! this is only possible when the gui is running in the same process.
+use crate::window::WindowId;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub enum WindowConfigRequest {
+    /// Returns the effective value of `key`
+    Get { key: String },
+    /// Overrides `key` with `value`, which is either JSON or a plain
+    /// string, or removes the override if `value` is None, and then
+    /// returns the effective value of `key`
+    Set { key: String, value: Option<String> },
+    /// Activates the named entry of `config.profiles`, or
+    /// deactivates the active profile if `profile` is None,
+    /// and then returns the name of the active profile
+    SetProfile { profile: Option<String> },
+}
+
+/// Resolves to the result of the request, formatted as JSON
+pub type WindowConfigFuture = Pin<Box<dyn Future<Output = anyhow::Result<String>>>>;
+type WindowConfigHandler =
+    Arc<dyn Fn(WindowId, WindowConfigRequest) -> WindowConfigFuture + Send + Sync>;
+
+lazy_static::lazy_static! {
+    static ref HANDLER: Mutex<Option<WindowConfigHandler>> = Mutex::new(None);
+}
+
+/// Registers the function that handles requests for the configuration
+/// of a window.  The function is called on the main thread.
+pub fn set_window_config_handler<F>(handler: F)
+where
+    F: Fn(WindowId, WindowConfigRequest) -> WindowConfigFuture + Send + Sync + 'static,
+{
+    HANDLER.lock().replace(Arc::new(handler));
+}
+
+pub async fn window_config(
+    window_id: WindowId,
+    request: WindowConfigRequest,
+) -> anyhow::Result<String> {
+    let handler = HANDLER.lock().clone().ok_or_else(|| {
+        anyhow::anyhow!(
+            "configuring windows requires the wezterm gui to be running in this process"
+        )
+    })?;
+    handler(window_id, request).await
+}
@@ -0,0 +1,493 @@
+//! Detects the start of a ZMODEM transfer in the output of a pane and
+//! hands the transfer over to a helper program, such as `rz` or `sz`
+//! from lrzsz, that is connected to the pty in place of the terminal
+//! until it exits, is cancelled, or is idle for too long.
+use crate::pane::{CachePolicy, Pane, PaneId};
+use crate::Mux;
+use anyhow::Context;
+use config::{configuration, escape_sequence_permissions, HOME_DIR};
+use filedescriptor::FileDescriptor;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// ZPAD ZPAD ZDLE 'B' starts a hex header, which continues with the
+/// frame type as two hex digits
+const HEX_HEADER: &[u8] = b"**\x18B0";
+/// The last digit of the frame type of ZRQINIT, sent by `sz` as it
+/// starts to send files
+const ZRQINIT: u8 = b'0';
+/// The last digit of the frame type of ZRINIT, sent by `rz` as it
+/// waits to receive files
+const ZRINIT: u8 = b'1';
+/// Sent to the remote end to make it abort the transfer when the helper
+/// is stopped: CAN characters, followed by backspaces to erase them in
+/// case they reach a shell prompt rather than `sz` or `rz`
+const CANCEL: &[u8] = b"\x18\x18\x18\x18\x18\x18\x18\x18\x18\x18\
+    \x08\x08\x08\x08\x08\x08\x08\x08\x08\x08";
+/// How often the helper is checked for being idle
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+lazy_static::lazy_static! {
+    /// The transfers that are in progress, keyed by pane, so that
+    /// they can be cancelled
+    static ref TRANSFERS: Mutex<HashMap<PaneId, Arc<Control>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// The remote end is sending files
+    Receive,
+    /// The remote end is waiting for files to be sent to it
+    Send,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Start {
+    /// How much of the data passed to `Detector::scan` precedes the
+    /// transfer and is output for the terminal
+    pub offset: usize,
+    /// The data of the transfer, which may begin with the end of the
+    /// data passed to the previous call to `Detector::scan`
+    pub data: Vec<u8>,
+    pub direction: Direction,
+}
+
+/// Scans the output of a pane for the header that starts a transfer
+#[derive(Default)]
+pub(crate) struct Detector {
+    /// The end of the previous output, which may be the start of a
+    /// header that is split across reads
+    tail: Vec<u8>,
+}
+
+impl Detector {
+    pub fn scan(&mut self, buf: &[u8]) -> Option<Start> {
+        let mut data = std::mem::take(&mut self.tail);
+        let carried = data.len();
+        data.extend_from_slice(buf);
+
+        let header_len = HEX_HEADER.len() + 1;
+        for (pos, header) in data.windows(header_len).enumerate() {
+            if !header.starts_with(HEX_HEADER) {
+                continue;
+            }
+            let direction = match header[HEX_HEADER.len()] {
+                ZRQINIT => Direction::Receive,
+                ZRINIT => Direction::Send,
+                _ => continue,
+            };
+            return Some(Start {
+                offset: pos.saturating_sub(carried),
+                data: data.split_off(pos),
+                direction,
+            });
+        }
+
+        let keep = data.len().min(header_len - 1);
+        self.tail = data.split_off(data.len() - keep);
+        None
+    }
+}
+
+/// Returns true if transfers are to be detected in the output of panes
+pub(crate) fn is_enabled() -> bool {
+    let config = configuration();
+    config.zmodem_receive_command.is_some() || config.zmodem_send_command.is_some()
+}
+
+fn helper_command(direction: Direction) -> Option<Vec<String>> {
+    let config = configuration();
+    match direction {
+        Direction::Receive => config.zmodem_receive_command.clone(),
+        Direction::Send => config.zmodem_send_command.clone(),
+    }
+}
+
+/// Returns true if a transfer in `direction` is to be handed to a
+/// helper; if not, the transfer is output for the terminal as usual
+pub(crate) fn has_helper(pane: &Weak<dyn Pane>, pane_id: PaneId, direction: Direction) -> bool {
+    if helper_command(direction).is_none() {
+        return false;
+    }
+    let pane = match pane.upgrade() {
+        Some(pane) => pane,
+        None => return false,
+    };
+    // The data sent by the helper must reach the remote end as-is
+    if !pane.get_encoding().is_utf8() {
+        log::warn!("pane {pane_id}: ignoring ZMODEM transfer as the pane encoding isn't UTF-8");
+        return false;
+    }
+    if !file_transfer_permitted(&pane) {
+        log::warn!("pane {pane_id}: ignoring ZMODEM transfer as file_transfer isn't permitted");
+        return false;
+    }
+    true
+}
+
+/// Checks the escape_sequence_policy for the domain of the pane and
+/// the host reported by its program
+fn file_transfer_permitted(pane: &Arc<dyn Pane>) -> bool {
+    let config = configuration();
+    if config.escape_sequence_policy.is_empty() {
+        return true;
+    }
+    let domain = Mux::try_get()
+        .and_then(|mux| mux.get_domain(pane.domain_id()))
+        .map(|domain| domain.domain_name().to_string());
+    let host = pane
+        .get_current_working_dir(CachePolicy::AllowStale)
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .filter(|host| !host.is_empty());
+    escape_sequence_permissions(
+        &config.escape_sequence_policy,
+        domain.as_deref(),
+        host.as_deref(),
+    )
+    .file_transfer
+}
+
+/// Stops the transfer that is in progress in the pane, if any.
+/// The helper is killed and the remote end is asked to abort, so that
+/// the output of the pane is shown in the terminal again.
+/// Returns false if there is no transfer in the pane.
+pub(crate) fn cancel(pane_id: PaneId) -> bool {
+    let control = TRANSFERS.lock().get(&pane_id).cloned();
+    match control {
+        Some(control) => {
+            control.stop("cancelled");
+            true
+        }
+        None => false,
+    }
+}
+
+/// The state of a transfer that is shared with the threads that copy
+/// the output of the helper and that check whether it is idle
+struct Control {
+    pane: Weak<dyn Pane>,
+    pane_id: PaneId,
+    child: Mutex<Child>,
+    /// Where messages about the transfer are shown in the pane
+    output: Mutex<FileDescriptor>,
+    /// When data was last passed between the helper and the remote end
+    last_activity: Mutex<Instant>,
+    /// Set once the helper has been stopped by `stop`
+    stopped: Mutex<bool>,
+}
+
+impl Control {
+    fn touch(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.child.lock().try_wait(), Ok(None))
+    }
+
+    /// Kills the helper and asks the remote end to abort the transfer
+    fn stop(&self, reason: &str) {
+        let mut stopped = self.stopped.lock();
+        if *stopped || !self.is_running() {
+            return;
+        }
+        *stopped = true;
+
+        let pane_id = self.pane_id;
+        log::info!("pane {pane_id}: ZMODEM transfer {reason}");
+        if let Err(err) = self.child.lock().kill() {
+            log::error!("pane {pane_id}: killing ZMODEM helper: {err:#}");
+        }
+        if let Some(pane) = self.pane.upgrade() {
+            let mut writer = pane.writer();
+            writer.write_all(CANCEL).and_then(|_| writer.flush()).ok();
+        }
+        self.output
+            .lock()
+            .write_all(format!("\r\nwezterm: ZMODEM transfer {reason}\r\n").as_bytes())
+            .ok();
+    }
+}
+
+/// Stops the helper once no data has been passed between it and
+/// the remote end for `timeout`
+fn stop_when_idle(control: Arc<Control>, timeout: Duration) {
+    while control.is_running() {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+        if control.last_activity.lock().elapsed() >= timeout {
+            control.stop(&format!(
+                "timed out after {} seconds without any data",
+                timeout.as_secs()
+            ));
+            break;
+        }
+    }
+}
+
+/// A helper program that is exchanging data with the remote end
+pub(crate) struct Transfer {
+    control: Arc<Control>,
+    stdin: ChildStdin,
+}
+
+impl Transfer {
+    /// Starts the helper that is configured for the direction of the
+    /// transfer.  The output of the pane is passed to the helper with
+    /// `forward` until it exits, while its progress and any errors are
+    /// written to `output` to be shown in the pane.
+    pub fn start(
+        pane: &Weak<dyn Pane>,
+        pane_id: PaneId,
+        start: Start,
+        output: &mut FileDescriptor,
+    ) -> Option<Self> {
+        let argv = helper_command(start.direction)?;
+        match Self::spawn(pane, pane_id, &argv, &start.data, output) {
+            Ok(transfer) => {
+                log::info!("pane {pane_id}: started {argv:?} for ZMODEM transfer");
+                Some(transfer)
+            }
+            Err(err) => {
+                log::error!("pane {pane_id}: {err:#}");
+                output
+                    .write_all(format!("\r\n⚠️  wezterm: {err:#}\r\n").as_bytes())
+                    .ok();
+                None
+            }
+        }
+    }
+
+    fn spawn(
+        pane: &Weak<dyn Pane>,
+        pane_id: PaneId,
+        argv: &[String],
+        data: &[u8],
+        output: &FileDescriptor,
+    ) -> anyhow::Result<Self> {
+        let (prog, args) = argv
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("the ZMODEM helper command is empty"))?;
+        let dir = match &configuration().zmodem_download_dir {
+            Some(dir) => dir.into(),
+            None => HOME_DIR.clone(),
+        };
+        let mut child = Command::new(prog)
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("spawning ZMODEM helper {argv:?}"))?;
+
+        let child_stdin = child.stdin.take().context("helper stdin")?;
+        let stdout = child.stdout.take().context("helper stdout")?;
+        let stderr = child.stderr.take().context("helper stderr")?;
+        let control = Arc::new(Control {
+            pane: pane.clone(),
+            pane_id,
+            child: Mutex::new(child),
+            output: Mutex::new(output.try_clone()?),
+            last_activity: Mutex::new(Instant::now()),
+            stopped: Mutex::new(false),
+        });
+        // Dropping the transfer kills the helper if anything fails below
+        let mut transfer = Self {
+            control: Arc::clone(&control),
+            stdin: child_stdin,
+        };
+        TRANSFERS.lock().insert(pane_id, Arc::clone(&control));
+
+        let output = output.try_clone()?;
+        std::thread::spawn({
+            let control = Arc::clone(&control);
+            move || copy_to_pane(control, stdout)
+        });
+        std::thread::spawn(move || copy_progress(stderr, output));
+
+        let timeout = Duration::from_secs(configuration().zmodem_idle_timeout_seconds);
+        if !timeout.is_zero() {
+            std::thread::spawn(move || stop_when_idle(control, timeout));
+        }
+
+        transfer.stdin.write_all(data)?;
+        transfer.stdin.flush()?;
+        Ok(transfer)
+    }
+
+    /// Passes output of the pane to the helper, returning false
+    /// once the helper has exited and the output is for the terminal
+    /// again
+    pub fn forward(&mut self, data: &[u8]) -> bool {
+        if !self.control.is_running() {
+            return false;
+        }
+        self.control.touch();
+        self.stdin
+            .write_all(data)
+            .and_then(|_| self.stdin.flush())
+            .is_ok()
+    }
+
+    /// Reports how the helper exited once `forward` returns false.
+    /// If it was stopped by wezterm, that has already been reported.
+    pub fn finish(self, pane_id: PaneId, output: &mut FileDescriptor) {
+        let status = self.control.child.lock().wait();
+        if *self.control.stopped.lock() {
+            return;
+        }
+        let message = match status {
+            Ok(status) if status.success() => "ZMODEM transfer finished".to_string(),
+            Ok(status) => format!("ZMODEM transfer failed: {status}"),
+            Err(err) => format!("ZMODEM transfer failed: {err:#}"),
+        };
+        log::info!("pane {pane_id}: {message}");
+        output
+            .write_all(format!("\r\nwezterm: {message}\r\n").as_bytes())
+            .ok();
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        TRANSFERS.lock().remove(&self.control.pane_id);
+        // The pane has gone away while the helper is still running
+        let mut child = self.control.child.lock();
+        if let Ok(None) = child.try_wait() {
+            child.kill().ok();
+            child.wait().ok();
+        }
+    }
+}
+
+/// Sends the data written by the helper to the remote end
+fn copy_to_pane(control: Arc<Control>, mut stdout: ChildStdout) {
+    let mut buf = vec![0; 8192];
+    loop {
+        let size = match stdout.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(size) => size,
+        };
+        let pane = match control.pane.upgrade() {
+            Some(pane) => pane,
+            None => break,
+        };
+        control.touch();
+        let mut writer = pane.writer();
+        if writer
+            .write_all(&buf[..size])
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Shows the progress that the helper reports on its stderr in the pane
+fn copy_progress(mut stderr: ChildStderr, mut output: FileDescriptor) {
+    let mut buf = vec![0; 4096];
+    loop {
+        let size = match stderr.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(size) => size,
+        };
+        let mut text = Vec::with_capacity(size);
+        for &b in &buf[..size] {
+            if b == b'\n' {
+                text.push(b'\r');
+            }
+            text.push(b);
+        }
+        if output.write_all(&text).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let mut detector = Detector::default();
+        assert_eq!(detector.scan(b"$ sz file\r\n"), None);
+        assert_eq!(
+            detector.scan(b"rz\r**\x18B00000000000000\r\x8a"),
+            Some(Start {
+                offset: 3,
+                data: b"**\x18B00000000000000\r\x8a".to_vec(),
+                direction: Direction::Receive,
+            })
+        );
+
+        let mut detector = Detector::default();
+        assert_eq!(detector.scan(b"$ rz\r\n**\x18"), None);
+        assert_eq!(
+            detector.scan(b"B0100000023be50\r\x8a"),
+            Some(Start {
+                offset: 0,
+                data: b"**\x18B0100000023be50\r\x8a".to_vec(),
+                direction: Direction::Send,
+            })
+        );
+
+        let mut detector = Detector::default();
+        assert_eq!(detector.scan(b"**\x18B0200000000"), None);
+        assert_eq!(detector.scan(b"a ** b"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_idle_and_cancelled_helpers() {
+        fn start_helper(pane_id: PaneId) -> (Arc<Control>, FileDescriptor) {
+            let (output, messages) = filedescriptor::socketpair().unwrap();
+            let child = Command::new("sleep")
+                .arg("30")
+                .stdin(Stdio::null())
+                .spawn()
+                .unwrap();
+            let pane: Weak<dyn Pane> = Weak::<crate::localpane::LocalPane>::new();
+            let control = Arc::new(Control {
+                pane,
+                pane_id,
+                child: Mutex::new(child),
+                output: Mutex::new(output),
+                last_activity: Mutex::new(Instant::now()),
+                stopped: Mutex::new(false),
+            });
+            (control, messages)
+        }
+
+        fn message(messages: &mut FileDescriptor) -> String {
+            let mut buf = vec![0; 256];
+            let size = messages.read(&mut buf).unwrap();
+            String::from_utf8_lossy(&buf[..size]).into_owned()
+        }
+
+        let (control, mut messages) = start_helper(1000);
+        stop_when_idle(Arc::clone(&control), Duration::from_secs(1));
+        assert!(*control.stopped.lock());
+        assert!(!control.child.lock().wait().unwrap().success());
+        assert_eq!(
+            message(&mut messages),
+            "\r\nwezterm: ZMODEM transfer timed out after 1 seconds without any data\r\n"
+        );
+
+        let (control, mut messages) = start_helper(1001);
+        TRANSFERS.lock().insert(1001, Arc::clone(&control));
+        assert!(!cancel(1002));
+        assert!(cancel(1001));
+        assert!(!control.child.lock().wait().unwrap().success());
+        assert_eq!(
+            message(&mut messages),
+            "\r\nwezterm: ZMODEM transfer cancelled\r\n"
+        );
+        TRANSFERS.lock().remove(&1001);
+    }
+}
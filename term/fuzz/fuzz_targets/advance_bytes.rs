@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use wezterm_term::harness::TermHarness;
+
+fuzz_target!(|data: &[u8]| {
+    let mut harness = TermHarness::new(24, 80);
+    harness.feed(data);
+    harness.screen_text();
+    harness.take_responses();
+});
@@ -172,6 +172,14 @@ pub trait TerminalConfiguration: Downcast + std::fmt::Debug + Send + Sync {
         "".to_string()
     }
 
+    /// Return true if replies to the application should start out using
+    /// 8-bit C1 controls, as though S8C1T had been received.
+    /// Applications can switch between 7-bit and 8-bit controls
+    /// with S7C1T, S8C1T and DECSCL.
+    fn send_8bit_c1_controls(&self) -> bool {
+        false
+    }
+
     fn enable_kitty_graphics(&self) -> bool {
         false
     }
@@ -222,9 +230,80 @@ pub trait TerminalConfiguration: Downcast + std::fmt::Debug + Send + Sync {
     fn log_unknown_escape_sequences(&self) -> bool {
         false
     }
+
+    /// When true, the contents of the alternate screen are kept in
+    /// the scrollback when an application switches back to the
+    /// primary screen
+    fn alternate_screen_capture(&self) -> bool {
+        false
+    }
+
+    /// When true, zero-width bidi controls and invisible characters
+    /// are kept in the model as part of the grapheme that follows
+    /// them, rather than being discarded, so that they can be found
+    /// and highlighted
+    fn keep_hidden_format_characters(&self) -> bool {
+        false
+    }
+
+    /// When true, the blank cells that horizontal tabs move across are
+    /// marked with `TabSpan`, and lines that a carriage return without
+    /// a line feed returned to the start of are marked, so that the
+    /// whitespace of the output can be shown
+    fn record_whitespace(&self) -> bool {
+        false
+    }
+
+    /// The longest OSC sequence, in bytes, that is accepted.
+    /// Longer sequences, such as oversized iTerm2 file downloads,
+    /// are discarded as they are received rather than held in memory
+    /// until they are complete
+    fn max_osc_len(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Returns which of the escape sequences that reach outside of the
+    /// terminal are permitted, given the host reported by the program
+    /// with OSC 7, if any
+    fn escape_sequence_permissions(&self, _host: Option<&str>) -> EscapeSequencePermissions {
+        EscapeSequencePermissions::default()
+    }
 }
 impl_downcast!(TerminalConfiguration);
 
+/// The escape sequences that let a program affect things outside of
+/// the terminal, such as the clipboard or files on the local system.
+/// Sequences that are not permitted are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscapeSequencePermissions {
+    /// OSC 52 set and clear the clipboard
+    pub clipboard_write: bool,
+    /// OSC 0, 1 and 2 set the window, tab and icon titles
+    pub set_title: bool,
+    /// OSC 9 and OSC 777 show notifications
+    pub notifications: bool,
+    /// The iTerm2 `File=` protocol saves files that aren't displayed
+    /// inline, and ZMODEM transfers are handed to helpers
+    pub file_transfer: bool,
+    /// OSC 1337 SetUserVar
+    pub user_vars: bool,
+    /// OSC 8 hyperlinks
+    pub hyperlinks: bool,
+}
+
+impl Default for EscapeSequencePermissions {
+    fn default() -> Self {
+        Self {
+            clipboard_write: true,
+            set_title: true,
+            notifications: true,
+            file_transfer: true,
+            user_vars: true,
+            hyperlinks: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BidiMode {
     pub enabled: bool,
@@ -0,0 +1,419 @@
+//! A harness for exercising the terminal model without a pty or a gui.
+//!
+//! [TermHarness] feeds byte streams to a [Terminal] and provides
+//! assertions about the resulting screen, cursor and answerback
+//! responses.  [Case] and [run_corpus] describe and run conformance
+//! cases that are stored in text files, so that changes to the
+//! emulator can be validated against a corpus of expected behavior.
+//!
+//! A case file is a sequence of directives, one per line.  Blank
+//! lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # Set a scroll region and move to its top
+//! size 5x10
+//! input \e[2;4r\e[3H
+//! input hello
+//! expect-cursor 5,2
+//! expect-screen
+//! |
+//! |
+//! |hello
+//! input \e[c
+//! expect-response \e[?65;4;6;18;22c
+//! ```
+//!
+//! * `size ROWSxCOLS` - the size of the terminal; must precede any input.
+//!   The default is 24x80.
+//! * `input TEXT` - feeds `TEXT` to the terminal.  `\e`, `\r`, `\n`,
+//!   `\t`, `\a`, `\\` and `\xHH` escapes are recognized.
+//!   Newlines are only sent when written as `\n`.
+//! * `expect-screen` - followed by lines starting with `|`, one per
+//!   row from the top of the screen.  Trailing whitespace is ignored,
+//!   and rows that are not listed are expected to be blank.
+//! * `expect-cursor X,Y` - the zero-based column and row of the cursor.
+//! * `expect-response TEXT` - the bytes that the terminal sent back
+//!   since the last `expect-response`, using the same escapes as `input`.
+//!
+//! Expectations are checked at the point where they appear, so a case
+//! can check the state at several points along the way.
+use crate::color::ColorPalette;
+use crate::{Terminal, TerminalConfiguration, TerminalSize, VisibleRowIndex};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use termwiz::cell::CellAttributes;
+use termwiz::surface::{Change, Position, Surface};
+
+#[derive(Debug)]
+struct HarnessConfig {
+    scrollback: usize,
+}
+
+impl TerminalConfiguration for HarnessConfig {
+    fn scrollback_size(&self) -> usize {
+        self.scrollback
+    }
+
+    fn color_palette(&self) -> ColorPalette {
+        ColorPalette::default()
+    }
+}
+
+/// Collects the bytes that the terminal writes back to the application
+struct ResponseWriter {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl std::io::Write for ResponseWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub struct TermHarness {
+    term: Terminal,
+    responses: Arc<Mutex<Vec<u8>>>,
+}
+
+impl TermHarness {
+    /// Creates a terminal with the specified size and no scrollback
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self::with_scrollback(rows, cols, 0)
+    }
+
+    pub fn with_scrollback(rows: usize, cols: usize, scrollback: usize) -> Self {
+        let responses = Arc::new(Mutex::new(vec![]));
+        let term = Terminal::new(
+            TerminalSize {
+                rows,
+                cols,
+                pixel_width: cols * 8,
+                pixel_height: rows * 16,
+                dpi: 0,
+            },
+            Arc::new(HarnessConfig { scrollback }),
+            "WezTerm",
+            "harness",
+            Box::new(ResponseWriter {
+                buffer: Arc::clone(&responses),
+            }),
+        );
+        Self { term, responses }
+    }
+
+    /// Creates a terminal whose state is restored from a snapshot,
+    /// such as one produced by `wezterm cli dump-pane-state`
+    #[cfg(feature = "use_serde")]
+    pub fn from_snapshot(snapshot: &crate::TerminalSnapshot) -> anyhow::Result<Self> {
+        let mut harness = Self::with_scrollback(
+            snapshot.size.rows,
+            snapshot.size.cols,
+            snapshot.primary.lines.len(),
+        );
+        harness.term.restore_snapshot(snapshot)?;
+        Ok(harness)
+    }
+
+    pub fn terminal(&self) -> &Terminal {
+        &self.term
+    }
+
+    pub fn terminal_mut(&mut self) -> &mut Terminal {
+        &mut self.term
+    }
+
+    /// Feeds bytes to the terminal, as though they were output
+    /// by an application
+    pub fn feed<B: AsRef<[u8]>>(&mut self, bytes: B) {
+        self.term.advance_bytes(bytes);
+    }
+
+    /// Returns the bytes that the terminal has sent back to the
+    /// application since the last call, such as responses to queries
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        self.term.sync_writer();
+        std::mem::take(&mut *self.responses.lock().unwrap())
+    }
+
+    /// Returns the text of the visible rows, with trailing
+    /// whitespace removed
+    pub fn screen_text(&self) -> Vec<String> {
+        self.visible_lines()
+            .iter()
+            .map(|line| line.as_str().trim_end().to_string())
+            .collect()
+    }
+
+    /// Returns the zero-based column and row of the cursor
+    pub fn cursor(&self) -> (usize, VisibleRowIndex) {
+        let cursor = self.term.cursor_pos();
+        (cursor.x, cursor.y)
+    }
+
+    /// Returns copies of the lines of the visible rows
+    pub fn visible_lines(&self) -> Vec<crate::Line> {
+        let screen = self.term.screen();
+        let rows = screen.physical_rows as VisibleRowIndex;
+        screen.lines_in_phys_range(screen.phys_range(&(0..rows)))
+    }
+
+    /// Renders the visible rows and the cursor into a `Surface`
+    pub fn to_surface(&self) -> Surface {
+        let screen = self.term.screen();
+        let mut surface = Surface::new(screen.physical_cols, screen.physical_rows);
+        for (y, line) in self.visible_lines().iter().enumerate() {
+            surface.add_change(Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(y),
+            });
+            surface.add_changes(line.changes(&CellAttributes::default()));
+        }
+        let cursor = self.term.cursor_pos();
+        surface.add_change(Change::AllAttributes(CellAttributes::default()));
+        surface.add_change(Change::CursorPosition {
+            x: Position::Absolute(cursor.x),
+            y: Position::Absolute(cursor.y.max(0) as usize),
+        });
+        surface.add_change(Change::CursorShape(cursor.shape));
+        surface.add_change(Change::CursorVisibility(cursor.visibility));
+        surface
+    }
+
+    /// Panics if the visible rows don't match `expected`.
+    /// Trailing whitespace is ignored and rows beyond those
+    /// in `expected` must be blank.
+    pub fn assert_screen(&self, expected: &[&str]) {
+        if let Err(err) = self.check_screen(expected) {
+            panic!("{err}");
+        }
+    }
+
+    /// Panics if the cells, attributes or cursor position differ
+    /// from those of the `expected` surface
+    pub fn assert_surface(&self, expected: &Surface) {
+        let actual = self.to_surface();
+        let diff = actual.diff_screens(expected);
+        if !diff.is_empty() || actual.cursor_position() != expected.cursor_position() {
+            panic!(
+                "surface mismatch.\nexpected:\n{}\nactual:\n{}\ncursor: expected {:?}, actual {:?}\ndifferences: {:?}",
+                expected.screen_chars_to_string(),
+                actual.screen_chars_to_string(),
+                expected.cursor_position(),
+                actual.cursor_position(),
+                diff
+            );
+        }
+    }
+
+    fn check_screen<S: AsRef<str>>(&self, expected: &[S]) -> Result<(), String> {
+        let actual = self.screen_text();
+        let mismatch = actual.iter().enumerate().any(|(idx, line)| {
+            let expect = expected
+                .get(idx)
+                .map(|s| s.as_ref().trim_end())
+                .unwrap_or("");
+            line != expect
+        });
+        if mismatch || expected.len() > actual.len() {
+            let mut message = "screen mismatch\n".to_string();
+            for idx in 0..actual.len().max(expected.len()) {
+                let expect = expected
+                    .get(idx)
+                    .map(|s| s.as_ref().trim_end())
+                    .unwrap_or("");
+                let line = actual.get(idx).map(|s| s.as_str()).unwrap_or("");
+                let marker = if expect == line { ' ' } else { '!' };
+                message.push_str(&format!("{marker} {idx:>3} expected |{expect}\n"));
+                if expect != line {
+                    message.push_str(&format!("{marker} {idx:>3} actual   |{line}\n"));
+                }
+            }
+            return Err(message);
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the escapes used by the `input` and `expect-response`
+/// directives
+pub fn unescape(text: &str) -> anyhow::Result<Vec<u8>> {
+    let mut result = vec![];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('e') => result.push(0x1b),
+            Some('r') => result.push(b'\r'),
+            Some('n') => result.push(b'\n'),
+            Some('t') => result.push(b'\t'),
+            Some('a') => result.push(0x07),
+            Some('\\') => result.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .map_err(|_| anyhow::anyhow!("invalid escape \\x{hex}"))?;
+                result.push(byte);
+            }
+            Some(c) => anyhow::bail!("invalid escape \\{c}"),
+            None => anyhow::bail!("trailing backslash"),
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Input(Vec<u8>),
+    ExpectScreen(Vec<String>),
+    ExpectCursor(usize, VisibleRowIndex),
+    ExpectResponse(Vec<u8>),
+}
+
+/// A conformance case; see the module documentation for the format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub name: String,
+    pub rows: usize,
+    pub cols: usize,
+    steps: Vec<Step>,
+}
+
+impl Case {
+    pub fn parse(name: &str, text: &str) -> anyhow::Result<Self> {
+        let mut case = Case {
+            name: name.to_string(),
+            rows: 24,
+            cols: 80,
+            steps: vec![],
+        };
+
+        let mut lines = text.lines().enumerate().peekable();
+        while let Some((idx, line)) = lines.next() {
+            let line_number = idx + 1;
+            let line = line.trim_end_matches(|c| c == '\r' || c == '\n');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (directive, arg) = match line.split_once(' ') {
+                Some((directive, arg)) => (directive, arg),
+                None => (line, ""),
+            };
+            let error = |message: &str| anyhow::anyhow!("{name}:{line_number}: {message}");
+
+            match directive {
+                "size" => {
+                    if !case.steps.is_empty() {
+                        return Err(error("size must precede the other directives"));
+                    }
+                    let (rows, cols) = arg
+                        .split_once('x')
+                        .and_then(|(r, c)| Some((r.trim().parse().ok()?, c.trim().parse().ok()?)))
+                        .ok_or_else(|| error("expected size ROWSxCOLS"))?;
+                    case.rows = rows;
+                    case.cols = cols;
+                }
+                "input" => {
+                    let bytes = unescape(arg).map_err(|err| error(&format!("{err:#}")))?;
+                    case.steps.push(Step::Input(bytes));
+                }
+                "expect-response" => {
+                    let bytes = unescape(arg).map_err(|err| error(&format!("{err:#}")))?;
+                    case.steps.push(Step::ExpectResponse(bytes));
+                }
+                "expect-cursor" => {
+                    let (x, y) = arg
+                        .split_once(',')
+                        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                        .ok_or_else(|| error("expected expect-cursor X,Y"))?;
+                    case.steps.push(Step::ExpectCursor(x, y));
+                }
+                "expect-screen" => {
+                    let mut rows = vec![];
+                    while let Some((_, row)) = lines.peek() {
+                        match row.strip_prefix('|') {
+                            Some(row) => {
+                                rows.push(row.to_string());
+                                lines.next();
+                            }
+                            None => break,
+                        }
+                    }
+                    case.steps.push(Step::ExpectScreen(rows));
+                }
+                _ => return Err(error(&format!("unknown directive {directive}"))),
+            }
+        }
+
+        Ok(case)
+    }
+
+    /// Runs the case, returning a description of the first
+    /// expectation that was not met
+    pub fn run(&self) -> Result<(), String> {
+        let mut harness = TermHarness::new(self.rows, self.cols);
+        for (idx, step) in self.steps.iter().enumerate() {
+            let context = |message: String| format!("{} step {}: {}", self.name, idx + 1, message);
+            match step {
+                Step::Input(bytes) => harness.feed(bytes),
+                Step::ExpectScreen(rows) => harness.check_screen(rows).map_err(context)?,
+                Step::ExpectCursor(x, y) => {
+                    let actual = harness.cursor();
+                    if actual != (*x, *y) {
+                        return Err(context(format!(
+                            "expected cursor at {x},{y} but it is at {},{}",
+                            actual.0, actual.1
+                        )));
+                    }
+                }
+                Step::ExpectResponse(expected) => {
+                    let actual = harness.take_responses();
+                    if actual != *expected {
+                        return Err(context(format!(
+                            "expected response {:?} but got {:?}",
+                            String::from_utf8_lossy(expected),
+                            String::from_utf8_lossy(&actual)
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs each of the `.case` files in `dir`, in name order.
+/// Returns the name of each case along with its outcome.
+pub fn run_corpus(dir: &Path) -> anyhow::Result<Vec<(String, Result<(), String>)>> {
+    let mut paths = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map(|ext| ext == "case").unwrap_or(false) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut results = vec![];
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let text = std::fs::read_to_string(&path)?;
+        let result = match Case::parse(&name, &text) {
+            Ok(case) => case.run(),
+            Err(err) => Err(format!("{err:#}")),
+        };
+        results.push((name, result));
+    }
+    Ok(results)
+}
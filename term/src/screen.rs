@@ -749,6 +749,41 @@ impl Screen {
         }
     }
 
+    /// Replaces all of the lines, such as when restoring a snapshot.
+    /// `lines` must hold at least `physical_rows` lines; any beyond
+    /// the scrollback capacity are discarded from the top.
+    pub(crate) fn restore_lines(&mut self, lines: Vec<Line>, stable_row_index_offset: usize) {
+        self.lines = lines.into();
+        self.stable_row_index_offset = stable_row_index_offset;
+        while self.lines.len() > self.physical_rows + self.scrollback_size() {
+            self.lines.pop_front();
+            self.stable_row_index_offset += 1;
+        }
+    }
+
+    /// Inserts `lines` at the bottom of the scrollback, immediately
+    /// above the visible rows, discarding the oldest scrollback if
+    /// there isn't room for them.
+    pub(crate) fn insert_into_scrollback(&mut self, lines: Vec<Line>, seqno: SequenceNo) {
+        if lines.is_empty() || self.scrollback_size() == 0 {
+            return;
+        }
+        let insert_idx = self.lines.len() - self.physical_rows;
+        for (idx, mut line) in lines.into_iter().enumerate() {
+            line.compress_for_scrollback();
+            line.update_last_change_seqno(seqno);
+            self.lines.insert(insert_idx + idx, line);
+        }
+        while self.lines.len() > self.physical_rows + self.scrollback_size() {
+            self.lines.pop_front();
+            self.stable_row_index_offset += 1;
+        }
+        // The visible rows now have different stable indices
+        for idx in self.lines.len() - self.physical_rows..self.lines.len() {
+            self.line_mut(idx).update_last_change_seqno(seqno);
+        }
+    }
+
     pub fn erase_scrollback(&mut self) {
         let len = self.lines.len();
         let to_clear = len - self.physical_rows;
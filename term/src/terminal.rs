@@ -59,6 +59,15 @@ pub enum Alert {
     /// When something bumps the seqno in the terminal model and
     /// the terminal is not focused
     OutputSinceFocusLost,
+    /// The application sent text to the printer using the
+    /// MC (Media Copy) sequences
+    PrintRequested {
+        data: String,
+        /// The output of printer controller mode is passed on in
+        /// parts; this is true for all but the last part of the job,
+        /// which should be sent to the same printer session
+        more_follows: bool,
+    },
 }
 
 pub trait AlertHandler: Send + Sync {
@@ -154,6 +163,8 @@ impl Terminal {
         {
             let bytes = bytes.as_ref();
 
+            self.parser
+                .set_max_osc_len(self.state.get_config().max_osc_len());
             let mut performer = Performer::new(&mut self.state);
 
             self.parser.parse(bytes, |action| performer.perform(action));
@@ -0,0 +1,138 @@
+//! Tracks the commands that have been run in the terminal, as reported
+//! by the shell using the OSC 133 semantic prompt escapes.
+//! The text of each command is taken from the cells that the shell
+//! marked up as `Input`, and its exit status from the `D` marker.
+use super::*;
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+/// The most recent commands are retained, up to this limit
+const MAX_COMMAND_HISTORY: usize = 500;
+
+#[cfg_attr(feature = "use_serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// The text of the command line
+    pub command: String,
+    /// The row on which the output of the command begins
+    pub output_row: StableRowIndex,
+    /// The working directory reported via OSC 7 when the command
+    /// was started, if any
+    pub cwd: Option<String>,
+    pub started: SystemTime,
+    pub finished: Option<SystemTime>,
+    /// The exit status reported by the shell, if any
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CommandHistory {
+    commands: VecDeque<CommandRecord>,
+    /// The row on which the current prompt started
+    prompt_row: Option<StableRowIndex>,
+}
+
+impl CommandHistory {
+    pub fn start_prompt(&mut self, row: StableRowIndex) {
+        self.finish_command(None);
+        self.prompt_row.replace(row);
+    }
+
+    pub fn prompt_row(&self) -> Option<StableRowIndex> {
+        self.prompt_row
+    }
+
+    pub fn start_command(
+        &mut self,
+        command: String,
+        output_row: StableRowIndex,
+        cwd: Option<&Url>,
+    ) {
+        self.finish_command(None);
+        self.prompt_row.take();
+        if command.is_empty() {
+            return;
+        }
+        if self.commands.len() >= MAX_COMMAND_HISTORY {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(CommandRecord {
+            command,
+            output_row,
+            cwd: cwd.map(|url| url.to_string()),
+            started: SystemTime::now(),
+            finished: None,
+            exit_code: None,
+        });
+    }
+
+    /// Marks the most recent command as finished, unless it already was
+    pub fn finish_command(&mut self, exit_code: Option<i32>) {
+        if let Some(last) = self.commands.back_mut() {
+            if last.finished.is_none() {
+                last.finished.replace(SystemTime::now());
+                last.exit_code = exit_code;
+            }
+        }
+    }
+
+    pub fn commands(&self) -> Vec<CommandRecord> {
+        self.commands.iter().cloned().collect()
+    }
+}
+
+impl TerminalState {
+    /// Returns the commands that the shell has reported running in
+    /// this terminal, oldest first.
+    /// This requires shell integration that uses OSC 133 to mark
+    /// up the prompt, the command line and its output.
+    pub fn get_command_history(&self) -> Vec<CommandRecord> {
+        self.command_history.commands()
+    }
+
+    pub(crate) fn semantic_prompt_started(&mut self) {
+        let row = self.screen().visible_row_to_stable_row(self.cursor.y);
+        self.command_history.start_prompt(row);
+    }
+
+    pub(crate) fn semantic_output_started(&mut self) {
+        let cursor_row = self.screen().visible_row_to_stable_row(self.cursor.y);
+        let first_row = self
+            .command_history
+            .prompt_row()
+            .unwrap_or(cursor_row)
+            .min(cursor_row);
+        let command = self.command_text(first_row..cursor_row + 1);
+        let cwd = self.current_dir.clone();
+        self.command_history
+            .start_command(command, cursor_row, cwd.as_ref());
+    }
+
+    /// Collects the text of the cells marked as `Input` in the
+    /// specified range of rows
+    fn command_text(&self, rows: Range<StableRowIndex>) -> String {
+        let screen = self.screen();
+        let mut command = String::new();
+        let mut prior_wrapped = false;
+        for line in screen.lines_in_phys_range(screen.stable_range(&rows)) {
+            let mut text = String::new();
+            for cell in line.visible_cells() {
+                if cell.attrs().semantic_type() == SemanticType::Input {
+                    text.push_str(cell.str());
+                }
+            }
+            let wrapped = line.last_cell_was_wrapped();
+            let text = if wrapped { &text } else { text.trim_end() };
+            if !text.is_empty() {
+                if !command.is_empty() && !prior_wrapped {
+                    command.push('\n');
+                }
+                command.push_str(text);
+            }
+            prior_wrapped = wrapped;
+        }
+        command.trim().to_string()
+    }
+}
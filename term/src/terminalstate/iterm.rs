@@ -2,13 +2,20 @@ use crate::terminalstate::image::*;
 use crate::TerminalState;
 use ::image::imageops::FilterType;
 use ::image::ImageFormat;
-use log::error;
+use log::{debug, error};
 use termwiz::escape::osc::ITermFileData;
 use termwiz::image::ImageDataType;
 
 impl TerminalState {
     pub(crate) fn set_image(&mut self, image: ITermFileData) {
         if !image.inline {
+            if !self.escape_sequence_permissions().file_transfer {
+                debug!(
+                    "Ignoring file download request name={:?} as file_transfer is not permitted",
+                    image.name
+                );
+                return;
+            }
             if let Some(handler) = &self.download_handler {
                 handler.save_to_downloads(image.name, image.data);
             } else {
@@ -51,7 +51,15 @@ impl TerminalState {
         } else {
             log::trace!("{}: sending {:?}, {:?} {:?}", label, to_send, key, mods);
         }
-        self.writer.write_all(to_send.as_bytes())?;
+        // Only the CSI and SS3 introducers of key sequences are sent as
+        // 8-bit controls; ESC prefixes for the Alt modifier are not
+        let is_control_sequence =
+            to_send.len() > 2 && (to_send.starts_with("\x1b[") || to_send.starts_with("\x1bO"));
+        if is_control_sequence {
+            self.writer.write_all(to_send.as_bytes())?;
+        } else {
+            self.writer.write_raw(to_send.as_bytes())?;
+        }
         self.writer.flush()?;
 
         Ok(())
@@ -3,7 +3,7 @@
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::range_plus_one))]
 use super::*;
 use crate::color::{ColorPalette, RgbColor};
-use crate::config::{BidiMode, NewlineCanon};
+use crate::config::{BidiMode, EscapeSequencePermissions, NewlineCanon};
 use log::debug;
 use num_traits::ToPrimitive;
 use std::collections::HashMap;
@@ -24,15 +24,22 @@ use termwiz::surface::{CursorShape, CursorVisibility, SequenceNo};
 use url::Url;
 use wezterm_bidi::ParagraphDirectionHint;
 
+mod history;
 mod image;
 mod iterm;
 mod keyboard;
 mod kitty;
 mod mouse;
 pub(crate) mod performer;
+mod print;
 mod sixel;
+#[cfg(feature = "use_serde")]
+mod snapshot;
+pub use crate::terminalstate::history::*;
 use crate::terminalstate::image::*;
 use crate::terminalstate::kitty::*;
+#[cfg(feature = "use_serde")]
+pub use crate::terminalstate::snapshot::*;
 
 lazy_static::lazy_static! {
     static ref DB: Database = {
@@ -338,6 +345,8 @@ pub struct TerminalState {
     title: String,
     /// The icon title string (OSC 1)
     icon_title: Option<String>,
+    /// The host that was reported with OSC 7 when the title was last set
+    title_host: Option<String>,
 
     palette: Option<ColorPalette>,
 
@@ -351,16 +360,32 @@ pub struct TerminalState {
     download_handler: Option<Arc<dyn DownloadHandler>>,
 
     current_dir: Option<Url>,
+    command_history: CommandHistory,
 
     term_program: String,
     term_version: String,
 
-    writer: BufWriter<ThreadedWriter>,
+    writer: ReplyWriter,
+
+    /// DECPFF - send a form feed after printing the screen
+    print_form_feed: bool,
+    /// DECPEX - print the whole screen rather than the scrolling region
+    print_extent: bool,
+    /// Print each line as the cursor moves off it
+    auto_print: bool,
+    /// The print job that is accumulating the output of the
+    /// application while in printer controller mode
+    printer_controller: Option<print::PrinterController>,
+    /// The level selected by DECSCL, which is reported by DECRQSS
+    conformance_level: i64,
 
     image_cache: lru::LruCache<[u8; 32], Arc<ImageData>>,
     sixel_scrolls_right: bool,
 
     user_vars: HashMap<String, String>,
+    /// The host that was reported with OSC 7 when each user var was
+    /// last set
+    user_var_hosts: HashMap<String, Option<String>>,
 
     kitty_img: KittyImageState,
     seqno: SequenceNo,
@@ -368,8 +393,18 @@ pub struct TerminalState {
     /// The unicode version that is in effect
     unicode_version: UnicodeVersion,
     unicode_version_stack: Vec<UnicodeVersionStackEntry>,
+    /// Zero-width bidi controls and invisible characters that are
+    /// waiting to be attached to the next printed grapheme; see
+    /// TerminalConfiguration::keep_hidden_format_characters
+    pending_hidden: String,
+    /// The row of the carriage return that is yet to be followed by a
+    /// line feed, when TerminalConfiguration::record_whitespace is set
+    pending_carriage_return: Option<VisibleRowIndex>,
 
     enable_conpty_quirks: bool,
+
+    /// Overrides the enq_answerback of the configuration
+    enq_answerback: Option<String>,
     /// On Windows, the ConPTY layer emits an OSC sequence to
     /// set the title shortly after it starts up.
     /// We don't want that, so we use this flag to remember
@@ -394,6 +429,11 @@ pub struct TerminalState {
     /// applied to lines.
     /// If none, then the default value specified by the config is used.
     bidi_hint: Option<ParagraphDirectionHint>,
+    /// When true, the contents of the alternate screen are copied into
+    /// the scrollback of the primary screen when the application that
+    /// was using it switches back.
+    /// If none, then the default value specified by the config is used.
+    alt_screen_capture: Option<bool>,
 }
 
 #[derive(Debug)]
@@ -452,6 +492,8 @@ struct ThreadedWriter {
 enum WriterMessage {
     Data(Vec<u8>),
     Flush,
+    /// Acknowledged once all of the preceding messages were processed
+    Sync(Sender<()>),
 }
 
 impl ThreadedWriter {
@@ -471,12 +513,24 @@ impl ThreadedWriter {
                             break;
                         }
                     }
+                    WriterMessage::Sync(ack) => {
+                        ack.send(()).ok();
+                    }
                 }
             }
         });
 
         Self { sender }
     }
+
+    /// Blocks until the data written so far has been passed on
+    /// to the underlying writer
+    fn sync(&self) {
+        let (ack, done) = channel();
+        if self.sender.send(WriterMessage::Sync(ack)).is_ok() {
+            done.recv().ok();
+        }
+    }
 }
 
 impl std::io::Write for ThreadedWriter {
@@ -495,6 +549,91 @@ impl std::io::Write for ThreadedWriter {
     }
 }
 
+/// The VT500 level that is also reported by DA1
+const DEFAULT_CONFORMANCE_LEVEL: i64 = 65;
+
+/// Wraps the writer used to send input and replies to the application
+/// so that replies can use 8-bit C1 controls, as selected by S8C1T or
+/// DECSCL, rather than their 7-bit `ESC Fe` equivalents.
+/// The C1 controls are written as UTF-8 like any other character; a pane
+/// using a legacy character encoding transcodes them to their single
+/// byte form.
+struct ReplyWriter {
+    inner: BufWriter<ThreadedWriter>,
+    eight_bit_controls: bool,
+    /// An ESC at the end of the last write, whose translation depends
+    /// on the byte that follows it
+    pending_esc: bool,
+}
+
+impl ReplyWriter {
+    fn new(writer: Box<dyn std::io::Write + Send>, eight_bit_controls: bool) -> Self {
+        Self {
+            inner: BufWriter::new(ThreadedWriter::new(writer)),
+            eight_bit_controls,
+            pending_esc: false,
+        }
+    }
+
+    fn set_eight_bit_controls(&mut self, enable: bool) {
+        self.flush_pending_esc().ok();
+        self.eight_bit_controls = enable;
+    }
+
+    /// Writes `buf` without translating its control sequences, such as
+    /// for the content of a paste
+    fn write_raw(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.flush_pending_esc()?;
+        self.inner.write_all(buf)
+    }
+
+    fn flush_pending_esc(&mut self) -> std::io::Result<()> {
+        if self.pending_esc {
+            self.pending_esc = false;
+            self.inner.write_all(b"\x1b")?;
+        }
+        Ok(())
+    }
+
+    fn get_ref(&self) -> &ThreadedWriter {
+        self.inner.get_ref()
+    }
+}
+
+impl std::io::Write for ReplyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.eight_bit_controls {
+            return self.inner.write(buf);
+        }
+
+        let mut translated = Vec::with_capacity(buf.len());
+        for &b in buf {
+            if self.pending_esc {
+                self.pending_esc = false;
+                if (0x40..=0x5f).contains(&b) {
+                    // ESC Fe -> the UTF-8 encoding of the C1 control
+                    translated.push(0xc2);
+                    translated.push(b + 0x40);
+                    continue;
+                }
+                translated.push(0x1b);
+            }
+            if b == 0x1b {
+                self.pending_esc = true;
+            } else {
+                translated.push(b);
+            }
+        }
+        self.inner.write_all(&translated)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_pending_esc()?;
+        self.inner.flush()
+    }
+}
+
 impl TerminalState {
     /// Constructs the terminal state.
     /// You generally want the `Terminal` struct rather than this one;
@@ -506,7 +645,7 @@ impl TerminalState {
         term_version: &str,
         writer: Box<dyn std::io::Write + Send>,
     ) -> TerminalState {
-        let writer = BufWriter::new(ThreadedWriter::new(writer));
+        let writer = ReplyWriter::new(writer, config.send_8bit_c1_controls());
         let seqno = 1;
         let screen = ScreenOrAlt::new(size, &config, seqno, config.bidi_mode());
 
@@ -556,6 +695,7 @@ impl TerminalState {
             tabs: TabStop::new(size.cols, 8),
             title: "wezterm".to_string(),
             icon_title: None,
+            title_host: None,
             palette: None,
             pixel_height: size.pixel_height,
             pixel_width: size.pixel_width,
@@ -565,23 +705,34 @@ impl TerminalState {
             alert_handler: None,
             download_handler: None,
             current_dir: None,
+            command_history: CommandHistory::default(),
             term_program: term_program.to_string(),
             term_version: term_version.to_string(),
             writer,
+            conformance_level: DEFAULT_CONFORMANCE_LEVEL,
+            print_form_feed: false,
+            print_extent: false,
+            auto_print: false,
+            printer_controller: None,
             image_cache: lru::LruCache::new(16),
             user_vars: HashMap::new(),
+            user_var_hosts: HashMap::new(),
             kitty_img: Default::default(),
             seqno,
             unicode_version,
+            pending_hidden: String::new(),
+            pending_carriage_return: None,
             unicode_version_stack: vec![],
             suppress_initial_title_change: false,
             enable_conpty_quirks: false,
+            enq_answerback: None,
             accumulating_title: None,
             lost_focus_seqno: seqno,
             lost_focus_alerted_seqno: seqno,
             focused: true,
             bidi_enabled: None,
             bidi_hint: None,
+            alt_screen_capture: None,
         }
     }
 
@@ -590,6 +741,13 @@ impl TerminalState {
         self.suppress_initial_title_change = true;
     }
 
+    /// Sets the string that is sent in response to ENQ, overriding
+    /// the enq_answerback of the configuration.  This allows eg: each
+    /// domain to have its own answerback.
+    pub fn set_enq_answerback(&mut self, answerback: Option<String>) {
+        self.enq_answerback = answerback;
+    }
+
     pub fn current_seqno(&self) -> SequenceNo {
         self.seqno
     }
@@ -643,6 +801,13 @@ impl TerminalState {
         self.icon_title.as_ref().unwrap_or(&self.title)
     }
 
+    /// Returns the host that the application had reported with OSC 7
+    /// when it last set the title.  The host is claimed by the
+    /// application, so it isn't proof of where the title came from.
+    pub fn get_title_host(&self) -> Option<&str> {
+        self.title_host.as_deref()
+    }
+
     /// Returns the current working directory associated with the
     /// terminal session.  The working directory can be changed by
     /// the applicaiton using the OSC 7 escape sequence.
@@ -650,6 +815,22 @@ impl TerminalState {
         self.current_dir.as_ref()
     }
 
+    /// Returns which of the escape sequences that reach outside of
+    /// the terminal are permitted, given the host that was last
+    /// reported with OSC 7
+    pub(crate) fn escape_sequence_permissions(&self) -> EscapeSequencePermissions {
+        self.config
+            .escape_sequence_permissions(self.reported_host())
+    }
+
+    /// Returns the host that was last reported with OSC 7, if any
+    pub(crate) fn reported_host(&self) -> Option<&str> {
+        self.current_dir
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .filter(|host| !host.is_empty())
+    }
+
     /// Returns a copy of the palette.
     /// By default we don't keep a copy in the terminal state,
     /// preferring to take the config values from the users
@@ -689,6 +870,21 @@ impl TerminalState {
         }
     }
 
+    /// Blocks until any responses that were generated so far
+    /// have been delivered to the writer
+    pub(crate) fn sync_writer(&mut self) {
+        self.writer.flush().ok();
+        self.writer.get_ref().sync();
+    }
+
+    /// Sends the responses of the terminal to `writer` from now on,
+    /// such as when a new process has been started in the pane
+    pub fn set_writer(&mut self, writer: Box<dyn std::io::Write + Send>) {
+        self.writer.flush().ok();
+        let eight_bit_controls = self.writer.eight_bit_controls;
+        self.writer = ReplyWriter::new(writer, eight_bit_controls);
+    }
+
     /// Returns a reference to the active screen (either the primary or
     /// the alternate screen).
     pub fn screen(&self) -> &Screen {
@@ -753,6 +949,37 @@ impl TerminalState {
         self.screen.is_alt_screen_active()
     }
 
+    /// Overrides the `alternate_screen_capture` setting of the config
+    /// for this terminal, or restores it if `enabled` is None
+    pub fn set_alt_screen_capture(&mut self, enabled: Option<bool>) {
+        self.alt_screen_capture = enabled;
+    }
+
+    /// Copies the non-blank rows of the alternate screen into the
+    /// scrollback of the primary screen, so that the output of an
+    /// application that was using the alternate screen isn't lost
+    /// when it switches back to the primary screen.
+    fn capture_alt_screen(&mut self) {
+        let enabled = self
+            .alt_screen_capture
+            .unwrap_or_else(|| self.config.alternate_screen_capture());
+        if !enabled || !self.screen.is_alt_screen_active() {
+            return;
+        }
+        let alt_screen = &self.screen.alt_screen;
+        let rows = alt_screen.physical_rows as VisibleRowIndex;
+        let mut lines = alt_screen.lines_in_phys_range(alt_screen.phys_range(&(0..rows)));
+        while lines
+            .last()
+            .map(|line| line.is_whitespace())
+            .unwrap_or(false)
+        {
+            lines.pop();
+        }
+        let seqno = self.seqno;
+        self.screen.screen.insert_into_scrollback(lines, seqno);
+    }
+
     /// Returns true if the associated application has enabled
     /// bracketed paste mode, which can be helpful to the hosting
     /// GUI application to decide about fragmenting a large paste.
@@ -818,11 +1045,16 @@ impl TerminalState {
     /// De-fang the text by removing any embedded bracketed paste
     /// sequence that may be present.
     pub fn send_paste(&mut self, text: &str) -> Result<(), Error> {
-        let mut buf = String::new();
-        if self.bracketed_paste {
-            buf.push_str("\x1b[200~");
-        }
+        let data = self.paste_data(text);
+        self.writer.write_raw(data.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
 
+    /// Returns the data that `send_paste` would send for `text`,
+    /// for embedders that send it to the application themselves,
+    /// such as a piece at a time.
+    pub fn paste_data(&self, text: &str) -> String {
         let canon = if self.bracketed_paste {
             NewlineCanon::None
         } else {
@@ -831,15 +1063,19 @@ impl TerminalState {
 
         let canon = canon.canonicalize(text);
         let de_fanged = canon.replace("\x1b[200~", "").replace("\x1b[201~", "");
-        buf.push_str(&de_fanged);
 
         if self.bracketed_paste {
-            buf.push_str("\x1b[201~");
+            // The pasted text is sent as-is, even when 8-bit controls
+            // are enabled for the bracketing
+            let csi = if self.writer.eight_bit_controls {
+                "\u{9b}"
+            } else {
+                "\x1b["
+            };
+            format!("{csi}200~{de_fanged}{csi}201~")
+        } else {
+            de_fanged
         }
-
-        self.writer.write_all(buf.as_bytes())?;
-        self.writer.flush()?;
-        Ok(())
     }
 
     /// Informs the terminal that the viewport of the window has resized to the
@@ -960,6 +1196,22 @@ impl TerminalState {
         &self.user_vars
     }
 
+    /// Sets the user var `name` as the application would with
+    /// `SetUserVar`, but without a host, as it was set locally
+    pub fn set_user_var(&mut self, name: String, value: String) {
+        self.user_vars.insert(name.clone(), value.clone());
+        self.user_var_hosts.insert(name.clone(), None);
+        if let Some(handler) = self.alert_handler.as_mut() {
+            handler.alert(Alert::SetUserVar { name, value });
+        }
+    }
+
+    /// Returns the host that the application had reported with OSC 7
+    /// when it last set the user var `name`
+    pub fn user_var_host(&self, name: &str) -> Option<&str> {
+        self.user_var_hosts.get(name)?.as_deref()
+    }
+
     fn clear_semantic_attribute_due_to_movement(&mut self) {
         if self.clear_semantic_attribute_on_newline {
             self.clear_semantic_attribute_on_newline = false;
@@ -1156,10 +1408,44 @@ impl TerminalState {
             Some(x) => x,
             None => self.left_and_right_margins.end - 1,
         };
-        self.cursor.x = x.min(self.left_and_right_margins.end - 1);
+        let x = x.min(self.left_and_right_margins.end - 1);
+        if self.config.record_whitespace() {
+            self.mark_tab_span(self.cursor.x..x);
+        }
+        self.cursor.x = x;
         self.cursor.seqno = seqno;
     }
 
+    /// Marks the blank cells in `cols` of the cursor line as having been
+    /// moved across by a horizontal tab; see
+    /// TerminalConfiguration::record_whitespace
+    fn mark_tab_span(&mut self, cols: Range<usize>) {
+        let seqno = self.seqno;
+        let y = self.cursor.y;
+        let screen = self.screen_mut();
+        let line_idx = screen.phys_row(y);
+        let line = screen.line_mut(line_idx);
+
+        // The tab doesn't erase the text that it moves across
+        let mut blanks: Vec<(usize, CellAttributes)> = line
+            .visible_cells()
+            .filter(|cell| cols.contains(&cell.cell_index()) && cell.str() == " ")
+            .map(|cell| (cell.cell_index(), cell.attrs().clone()))
+            .collect();
+        for idx in line.len().max(cols.start)..cols.end {
+            blanks.push((idx, CellAttributes::default()));
+        }
+
+        for (n, (idx, mut attrs)) in blanks.into_iter().enumerate() {
+            attrs.set_tab_span(if n == 0 {
+                TabSpan::Start
+            } else {
+                TabSpan::Continuation
+            });
+            line.set_cell(idx, Cell::blank_with_attrs(attrs), seqno);
+        }
+    }
+
     /// Move the cursor up 1 line.  If the position is at the top scroll margin,
     /// scroll the region down.
     fn c1_reverse_index(&mut self) {
@@ -1279,6 +1565,21 @@ impl TerminalState {
                 self.g0_charset = CharSet::Ascii;
                 self.g1_charset = CharSet::Ascii;
             }
+            Device::SelectConformanceLevel {
+                level,
+                eight_bit_controls,
+            } => {
+                if !(61..=DEFAULT_CONFORMANCE_LEVEL).contains(&level) {
+                    if self.config.log_unknown_escape_sequences() {
+                        log::warn!("unsupported conformance level {}", level);
+                    }
+                    return;
+                }
+                // Selecting the level also performs a soft reset
+                self.perform_device(Device::SoftReset);
+                self.conformance_level = level;
+                self.writer.set_eight_bit_controls(eight_bit_controls);
+            }
             Device::RequestPrimaryDeviceAttributes => {
                 let mut ident = "\x1b[?65".to_string(); // Vt500
                 ident.push_str(";4"); // Sixel graphics
@@ -1330,6 +1631,7 @@ impl TerminalState {
                 self.writer.write(b"\x1b[0n").ok();
                 self.writer.flush().ok();
             }
+            Device::MediaCopy(mc) => self.perform_media_copy(mc),
             Device::XtSmGraphics(g) => {
                 let response = if matches!(g.item, XtSmGraphicsItem::Unspecified(_)) {
                     XtSmGraphics {
@@ -1442,6 +1744,26 @@ impl TerminalState {
                 // We leave key repeat to the GUI layer prefs
             }
 
+            Mode::SetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintFormFeed)) => {
+                self.print_form_feed = true;
+            }
+            Mode::ResetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintFormFeed)) => {
+                self.print_form_feed = false;
+            }
+            Mode::QueryDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintFormFeed)) => {
+                self.decqrm_response(mode, true, self.print_form_feed);
+            }
+
+            Mode::SetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintExtent)) => {
+                self.print_extent = true;
+            }
+            Mode::ResetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintExtent)) => {
+                self.print_extent = false;
+            }
+            Mode::QueryDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::PrintExtent)) => {
+                self.decqrm_response(mode, true, self.print_extent);
+            }
+
             Mode::SetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::Win32InputMode)) => {
                 self.keyboard_encoding = KeyboardEncoding::Win32;
             }
@@ -1679,6 +2001,7 @@ impl TerminalState {
                 DecPrivateModeCode::OptEnableAlternateScreen,
             )) => {
                 if self.screen.is_alt_screen_active() {
+                    self.capture_alt_screen();
                     self.pen = CellAttributes::default();
                     self.erase_in_display(EraseInDisplay::EraseDisplay);
                     self.screen.activate_primary_screen(self.seqno);
@@ -1689,6 +2012,7 @@ impl TerminalState {
                 DecPrivateModeCode::EnableAlternateScreen,
             )) => {
                 if self.screen.is_alt_screen_active() {
+                    self.capture_alt_screen();
                     self.screen.activate_primary_screen(self.seqno);
                     self.pen = CellAttributes::default();
                 }
@@ -1896,6 +2220,7 @@ impl TerminalState {
                 DecPrivateModeCode::ClearAndEnableAlternateScreen,
             )) => {
                 if self.screen.is_alt_screen_active() {
+                    self.capture_alt_screen();
                     self.screen.activate_primary_screen(self.seqno);
                     self.dec_restore_cursor();
                 }
@@ -1,6 +1,7 @@
 use crate::terminal::Alert;
 use crate::terminalstate::{
     default_color_map, CharSet, MouseEncoding, TabStop, UnicodeVersionStackEntry,
+    DEFAULT_CONFORMANCE_LEVEL,
 };
 use crate::{ClipboardSelection, Position, TerminalState, VisibleRowIndex, DCS, ST};
 use finl_unicode::grapheme_clusters::Graphemes;
@@ -12,7 +13,8 @@ use std::io::Write as _;
 use std::ops::{Deref, DerefMut};
 use termwiz::cell::{grapheme_column_width, Cell, CellAttributes, SemanticType};
 use termwiz::escape::csi::{
-    CharacterPath, EraseInDisplay, Keyboard, KittyKeyboardFlags, KittyKeyboardMode,
+    CharacterPath, Device, EraseInDisplay, Keyboard, KittyKeyboardFlags, KittyKeyboardMode,
+    MediaCopy,
 };
 use termwiz::escape::osc::{
     ChangeColorPair, ColorOrQuery, FinalTermSemanticPrompt, ITermProprietary,
@@ -22,10 +24,15 @@ use termwiz::escape::{
     Action, ControlCode, DeviceControlMode, Esc, EscCode, OperatingSystemCommand, CSI,
 };
 use termwiz::input::KeyboardEncoding;
+use termwiz::suspicious::{is_bidi_control, is_invisible};
 use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
 use url::Url;
 use wezterm_bidi::ParagraphDirectionHint;
 
+/// The most bytes of hidden format characters that are attached to
+/// a single grapheme
+const MAX_PENDING_HIDDEN: usize = 64;
+
 /// A helper struct for implementing `vtparse::VTActor` while compartmentalizing
 /// the terminal state and the embedding/host terminal interface
 pub(crate) struct Performer<'a> {
@@ -112,12 +119,25 @@ impl<'a> Performer<'a> {
         }
     }
 
+    fn mark_bare_carriage_return(&mut self, y: VisibleRowIndex) {
+        let seqno = self.seqno;
+        let screen = self.screen_mut();
+        let idx = screen.phys_row(y);
+        screen.line_mut(idx).set_bare_carriage_return(seqno);
+    }
+
     fn flush_print(&mut self) {
         if self.print.is_empty() {
             return;
         }
 
         let seqno = self.seqno;
+        if let Some(y) = self.pending_carriage_return.take() {
+            if y == self.cursor.y {
+                // Text is being written over the line that was returned to
+                self.mark_bare_carriage_return(y);
+            }
+        }
         let mut p = std::mem::take(&mut self.print);
         let normalized: String;
         let text = if self.config.normalize_output_to_unicode_nfc()
@@ -133,6 +153,17 @@ impl<'a> Performer<'a> {
             let g = self.remap_grapheme(g);
 
             let print_width = grapheme_column_width(g, Some(self.unicode_version));
+            if print_width == 0
+                && self.config.keep_hidden_format_characters()
+                && g.chars().all(|c| is_bidi_control(c) || is_invisible(c))
+            {
+                // Attach it to the next grapheme so that it can be
+                // found in the model, within reason
+                if self.pending_hidden.len() < MAX_PENDING_HIDDEN {
+                    self.pending_hidden.push_str(g);
+                }
+                continue;
+            }
             if print_width == 0 {
                 // We got a zero-width grapheme.
                 // We used to force them into a cell to guarantee that we
@@ -174,9 +205,18 @@ impl<'a> Performer<'a> {
                         screen.line_mut(y).set_last_cell_was_wrapped(true, seqno);
                     }
                 }
+                self.auto_print_cursor_line();
                 self.new_line(true);
             }
 
+            let with_hidden: String;
+            let g = if self.pending_hidden.is_empty() {
+                g
+            } else {
+                with_hidden = std::mem::take(&mut self.pending_hidden) + g;
+                with_hidden.as_str()
+            };
+
             let x = self.cursor.x;
             let y = self.cursor.y;
             let width = self.left_and_right_margins.end;
@@ -247,6 +287,19 @@ impl<'a> Performer<'a> {
                 _ => {}
             }
         }
+        if self.in_printer_controller_mode() {
+            // Everything up to the sequence that ends printer controller
+            // mode goes to the printer rather than the screen
+            let is_end = matches!(
+                &action,
+                Action::CSI(CSI::Device(dev))
+                    if **dev == Device::MediaCopy(MediaCopy::PrinterControllerOff)
+            );
+            if !is_end {
+                self.print_action(&action);
+                return;
+            }
+        }
         match action {
             Action::Print(c) => self.print(c),
             Action::PrintString(s) => {
@@ -286,7 +339,10 @@ impl<'a> Performer<'a> {
                         match s.data.as_slice() {
                             &[b'"', b'p'] => {
                                 // DECSCL - select conformance level
-                                write!(self.writer, "{}1$r65;1\"p{}", DCS, ST).ok();
+                                let level = self.conformance_level;
+                                let controls = if self.writer.eight_bit_controls { 0 } else { 1 };
+                                write!(self.writer, "{}1$r{};{}\"p{}", DCS, level, controls, ST)
+                                    .ok();
                                 self.writer.flush().ok();
                             }
                             &[b'r'] => {
@@ -361,6 +417,8 @@ impl<'a> Performer<'a> {
         self.flush_print();
         match control {
             ControlCode::LineFeed | ControlCode::VerticalTab | ControlCode::FormFeed => {
+                self.pending_carriage_return = None;
+                self.auto_print_cursor_line();
                 if self.left_and_right_margins.contains(&self.cursor.x) {
                     self.new_line(false);
                 } else {
@@ -383,6 +441,15 @@ impl<'a> Performer<'a> {
                 }
             }
             ControlCode::CarriageReturn => {
+                if self.config.record_whitespace() {
+                    let y = self.cursor.y;
+                    if self.pending_carriage_return == Some(y) {
+                        // Such as a CRLF line ending that the tty has
+                        // turned into CR CR LF
+                        self.mark_bare_carriage_return(y);
+                    }
+                    self.pending_carriage_return = Some(y);
+                }
                 if self.cursor.x >= self.left_and_right_margins.start {
                     self.cursor.x = self.left_and_right_margins.start;
                 } else {
@@ -454,9 +521,14 @@ impl<'a> Performer<'a> {
             }
 
             ControlCode::Enquiry => {
-                let response = self.config.enq_answerback();
+                let response = match &self.enq_answerback {
+                    Some(answerback) => answerback.clone(),
+                    None => self.config.enq_answerback(),
+                };
                 if response.len() > 0 {
-                    write!(self.writer, "{}", response).ok();
+                    // The answerback is sent as configured, rather than
+                    // being translated like the replies to queries
+                    self.writer.write_raw(response.as_bytes()).ok();
                     self.writer.flush().ok();
                 }
             }
@@ -655,6 +727,13 @@ impl<'a> Performer<'a> {
                 self.cursor = Default::default();
             }
 
+            Esc::Code(EscCode::SevenBitControls) => {
+                self.writer.set_eight_bit_controls(false);
+            }
+            Esc::Code(EscCode::EightBitControls) => {
+                self.writer.set_eight_bit_controls(true);
+            }
+
             // RIS resets a device to its initial state, i.e. the state it has after it is switched
             // on. This may imply, if applicable: remove tabulation stops, remove qualified areas,
             // reset graphic rendition, erase all positions, move active position to first
@@ -698,6 +777,12 @@ impl<'a> Performer<'a> {
                 self.unicode_version_stack.clear();
                 self.suppress_initial_title_change = false;
                 self.accumulating_title.take();
+                self.print_form_feed = false;
+                self.print_extent = false;
+                self.auto_print = false;
+                self.conformance_level = DEFAULT_CONFORMANCE_LEVEL;
+                let eight_bit_controls = self.config.send_8bit_c1_controls();
+                self.writer.set_eight_bit_controls(eight_bit_controls);
 
                 self.screen.full_reset();
                 self.screen.activate_alt_screen(seqno);
@@ -716,9 +801,47 @@ impl<'a> Performer<'a> {
         }
     }
 
+    /// Returns false if `osc` uses a feature that isn't permitted,
+    /// in which case it is ignored
+    fn osc_permitted(&self, osc: &OperatingSystemCommand) -> bool {
+        let permissions = || self.escape_sequence_permissions();
+        let (feature, permitted) = match osc {
+            OperatingSystemCommand::SetIconNameSun(_)
+            | OperatingSystemCommand::SetIconName(_)
+            | OperatingSystemCommand::SetIconNameAndWindowTitle(_)
+            | OperatingSystemCommand::SetWindowTitleSun(_)
+            | OperatingSystemCommand::SetWindowTitle(_) => ("set_title", permissions().set_title),
+            OperatingSystemCommand::SetHyperlink(Some(_)) => {
+                ("hyperlinks", permissions().hyperlinks)
+            }
+            OperatingSystemCommand::ClearSelection(_)
+            | OperatingSystemCommand::SetSelection(..) => {
+                ("clipboard_write", permissions().clipboard_write)
+            }
+            OperatingSystemCommand::SystemNotification(_)
+            | OperatingSystemCommand::RxvtExtension(_) => {
+                ("notifications", permissions().notifications)
+            }
+            OperatingSystemCommand::ITermProprietary(ITermProprietary::SetUserVar { .. }) => {
+                ("user_vars", permissions().user_vars)
+            }
+            _ => return true,
+        };
+        if !permitted {
+            debug!(
+                "ignoring OSC that requires {} permission: {:?}",
+                feature, osc
+            );
+        }
+        permitted
+    }
+
     fn osc_dispatch(&mut self, osc: OperatingSystemCommand) {
         self.pop_tmux_title_state();
         self.flush_print();
+        if !self.osc_permitted(&osc) {
+            return;
+        }
         match osc {
             OperatingSystemCommand::SetIconNameSun(title)
             | OperatingSystemCommand::SetIconName(title) => {
@@ -727,6 +850,7 @@ impl<'a> Performer<'a> {
                 } else {
                     self.icon_title = Some(title);
                 }
+                self.title_host = self.reported_host().map(|host| host.to_string());
                 let title = self.icon_title.clone();
                 if let Some(handler) = self.alert_handler.as_mut() {
                     handler.alert(Alert::IconTitleChanged(title));
@@ -735,6 +859,7 @@ impl<'a> Performer<'a> {
             OperatingSystemCommand::SetIconNameAndWindowTitle(title) => {
                 self.icon_title.take();
                 self.title = title.clone();
+                self.title_host = self.reported_host().map(|host| host.to_string());
                 if let Some(handler) = self.alert_handler.as_mut() {
                     handler.alert(Alert::WindowTitleChanged(title.clone()));
                     handler.alert(Alert::IconTitleChanged(Some(title)));
@@ -744,6 +869,7 @@ impl<'a> Performer<'a> {
             OperatingSystemCommand::SetWindowTitleSun(title)
             | OperatingSystemCommand::SetWindowTitle(title) => {
                 self.title = title.clone();
+                self.title_host = self.reported_host().map(|host| host.to_string());
                 if let Some(handler) = self.alert_handler.as_mut() {
                     handler.alert(Alert::WindowTitleChanged(title));
                 }
@@ -810,6 +936,8 @@ impl<'a> Performer<'a> {
                 ITermProprietary::File(image) => self.set_image(*image),
                 ITermProprietary::SetUserVar { name, value } => {
                     self.user_vars.insert(name.clone(), value.clone());
+                    let host = self.reported_host().map(|host| host.to_string());
+                    self.user_var_hosts.insert(name.clone(), host);
                     if let Some(handler) = self.alert_handler.as_mut() {
                         handler.alert(Alert::SetUserVar { name, value });
                     }
@@ -850,17 +978,20 @@ impl<'a> Performer<'a> {
             ) => {
                 self.fresh_line();
                 self.pen.set_semantic_type(SemanticType::Prompt);
+                self.semantic_prompt_started();
             }
             OperatingSystemCommand::FinalTermSemanticPrompt(
                 FinalTermSemanticPrompt::StartPrompt(_),
             ) => {
                 self.pen.set_semantic_type(SemanticType::Prompt);
+                self.semantic_prompt_started();
             }
             OperatingSystemCommand::FinalTermSemanticPrompt(
                 FinalTermSemanticPrompt::MarkEndOfCommandWithFreshLine { .. },
             ) => {
                 self.fresh_line();
                 self.pen.set_semantic_type(SemanticType::Prompt);
+                self.semantic_prompt_started();
             }
             OperatingSystemCommand::FinalTermSemanticPrompt(
                 FinalTermSemanticPrompt::MarkEndOfPromptAndStartOfInputUntilNextMarker { .. },
@@ -876,12 +1007,15 @@ impl<'a> Performer<'a> {
             OperatingSystemCommand::FinalTermSemanticPrompt(
                 FinalTermSemanticPrompt::MarkEndOfInputAndStartOfOutput { .. },
             ) => {
+                self.semantic_output_started();
                 self.pen.set_semantic_type(SemanticType::Output);
             }
 
             OperatingSystemCommand::FinalTermSemanticPrompt(
-                FinalTermSemanticPrompt::CommandStatus { .. },
-            ) => {}
+                FinalTermSemanticPrompt::CommandStatus { status, .. },
+            ) => {
+                self.command_history.finish_command(Some(status));
+            }
 
             OperatingSystemCommand::SystemNotification(message) => {
                 if let Some(handler) = self.alert_handler.as_mut() {
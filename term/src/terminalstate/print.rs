@@ -0,0 +1,130 @@
+//! MC - Media Copy, which sends the content of the screen or the
+//! output of the application to a printer.
+//! The terminal doesn't print anything itself; the print jobs are
+//! passed to the embedding application with `Alert::PrintRequested`.
+use crate::terminal::Alert;
+use crate::{Line, TerminalState, VisibleRowIndex};
+use std::ops::Range;
+use termwiz::escape::csi::MediaCopy;
+use termwiz::escape::Action;
+
+/// The output captured in printer controller mode is passed on in
+/// parts of about this size, so that an application that doesn't
+/// end printer controller mode can't use an unbounded amount of memory.
+/// The parts are marked so that the embedding application can send
+/// them to a single printer session.
+const MAX_PRINT_JOB: usize = 1024 * 1024;
+
+/// The state of printer controller mode
+#[derive(Debug, Default)]
+pub(crate) struct PrinterController {
+    /// Output that hasn't been passed on yet
+    job: String,
+    /// Whether part of the job has already been passed on, in which
+    /// case the end of the job is passed on even if it is empty
+    sent_part: bool,
+}
+
+/// Formats lines as they are sent to a printer
+fn lines_for_print(lines: &[Line]) -> String {
+    let mut job = String::new();
+    for line in lines {
+        job.push_str(line.as_str().trim_end());
+        job.push_str("\r\n");
+    }
+    job
+}
+
+impl TerminalState {
+    pub(crate) fn perform_media_copy(&mut self, mc: MediaCopy) {
+        match mc {
+            MediaCopy::PrintScreen | MediaCopy::PrintComposedDisplay => {
+                // DECPEX selects between the scrolling region and the
+                // whole screen
+                let rows = if self.print_extent {
+                    0..self.screen().physical_rows as VisibleRowIndex
+                } else {
+                    self.top_and_bottom_margins.clone()
+                };
+                let mut job = self.lines_for_print_in_range(rows);
+                if self.print_form_feed {
+                    job.push('\x0c');
+                }
+                self.send_print_job(job);
+            }
+            MediaCopy::PrintAllPages => {
+                let screen = self.screen();
+                let end = screen.phys_row(screen.physical_rows as VisibleRowIndex);
+                let job = lines_for_print(&screen.lines_in_phys_range(0..end));
+                self.send_print_job(job);
+            }
+            MediaCopy::PrintCursorLine => self.print_cursor_line(),
+            MediaCopy::AutoPrintOn => self.auto_print = true,
+            MediaCopy::AutoPrintOff => self.auto_print = false,
+            MediaCopy::PrinterControllerOn => {
+                if self.printer_controller.is_none() {
+                    self.printer_controller = Some(PrinterController::default());
+                }
+            }
+            MediaCopy::PrinterControllerOff => {
+                if let Some(controller) = self.printer_controller.take() {
+                    if controller.sent_part || !controller.job.is_empty() {
+                        self.send_print_part(controller.job, false);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns true if the output of the application is being
+    /// sent to the printer rather than the screen
+    pub(crate) fn in_printer_controller_mode(&self) -> bool {
+        self.printer_controller.is_some()
+    }
+
+    /// Adds output of the application to the print job of
+    /// printer controller mode
+    pub(crate) fn print_action(&mut self, action: &Action) {
+        let controller = match self.printer_controller.as_mut() {
+            Some(controller) => controller,
+            None => return,
+        };
+        controller.job.push_str(&action.to_string());
+        if controller.job.len() >= MAX_PRINT_JOB {
+            controller.sent_part = true;
+            let data = std::mem::take(&mut controller.job);
+            self.send_print_part(data, true);
+        }
+    }
+
+    /// Prints the line containing the cursor if auto print mode is
+    /// enabled; called as the cursor moves off that line
+    pub(crate) fn auto_print_cursor_line(&mut self) {
+        if self.auto_print {
+            self.print_cursor_line();
+        }
+    }
+
+    fn print_cursor_line(&mut self) {
+        let y = self.cursor.y;
+        let job = self.lines_for_print_in_range(y..y + 1);
+        self.send_print_job(job);
+    }
+
+    fn lines_for_print_in_range(&self, rows: Range<VisibleRowIndex>) -> String {
+        let screen = self.screen();
+        lines_for_print(&screen.lines_in_phys_range(screen.phys_range(&rows)))
+    }
+
+    fn send_print_job(&mut self, data: String) {
+        if !data.is_empty() {
+            self.send_print_part(data, false);
+        }
+    }
+
+    fn send_print_part(&mut self, data: String, more_follows: bool) {
+        if let Some(handler) = self.alert_handler.as_mut() {
+            handler.alert(Alert::PrintRequested { data, more_follows });
+        }
+    }
+}
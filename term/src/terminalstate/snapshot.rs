@@ -0,0 +1,239 @@
+//! Captures the state of the terminal model in a form that can be
+//! saved to a file and later restored into a fresh `Terminal`.
+//! This allows a rendering problem observed in a live session to
+//! be reproduced deterministically, without needing to recreate the
+//! sequence of events that led up to it.
+use super::*;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the structure of the snapshot changes in a way
+/// that would prevent older snapshots from being restored
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenSnapshot {
+    /// The scrollback followed by the visible lines, including
+    /// the attributes and any images attached to the cells
+    pub lines: Vec<Line>,
+    pub stable_row_index_offset: usize,
+}
+
+/// The modes that influence how subsequent output and input are handled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalModes {
+    pub wrap_next: bool,
+    pub insert: bool,
+    pub dec_auto_wrap: bool,
+    pub reverse_wraparound_mode: bool,
+    pub reverse_video_mode: bool,
+    pub dec_origin_mode: bool,
+    pub left_and_right_margin_mode: bool,
+    pub application_cursor_keys: bool,
+    pub modify_other_keys: Option<i64>,
+    pub dec_ansi_mode: bool,
+    pub sixel_display_mode: bool,
+    pub application_keypad: bool,
+    pub bracketed_paste: bool,
+    pub focus_tracking: bool,
+    pub mouse_tracking: bool,
+    pub button_event_mouse: bool,
+    pub any_event_mouse: bool,
+    /// One of `X10`, `Utf8`, `SGR` or `SgrPixels`
+    pub mouse_encoding: String,
+    pub newline_mode: bool,
+    /// The G0 and G1 character sets; one of `Ascii`, `Uk`
+    /// or `DecLineDrawing`
+    pub g0_charset: String,
+    pub g1_charset: String,
+    pub shift_out: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminalSnapshot {
+    pub version: u32,
+    pub size: TerminalSize,
+    pub title: String,
+    pub icon_title: Option<String>,
+    pub current_dir: Option<String>,
+    pub user_vars: HashMap<String, String>,
+    pub primary: ScreenSnapshot,
+    pub alternate: ScreenSnapshot,
+    pub alt_screen_is_active: bool,
+    pub cursor: CursorPosition,
+    pub cursor_visible: bool,
+    pub pen: CellAttributes,
+    pub modes: TerminalModes,
+    pub top_and_bottom_margins: Range<VisibleRowIndex>,
+    pub left_and_right_margins: Range<usize>,
+    /// The columns that have tab stops
+    pub tab_stops: Vec<usize>,
+    /// Only present if the palette was changed via escape sequences
+    pub palette: Option<ColorPalette>,
+}
+
+fn screen_snapshot(screen: &Screen) -> ScreenSnapshot {
+    let mut lines = vec![];
+    screen.for_each_phys_line(|_, line| lines.push(line.clone()));
+    ScreenSnapshot {
+        lines,
+        stable_row_index_offset: screen.phys_to_stable_row_index(0) as usize,
+    }
+}
+
+fn charset_name(charset: CharSet) -> String {
+    format!("{charset:?}")
+}
+
+fn parse_charset(name: &str) -> anyhow::Result<CharSet> {
+    match name {
+        "Ascii" => Ok(CharSet::Ascii),
+        "Uk" => Ok(CharSet::Uk),
+        "DecLineDrawing" => Ok(CharSet::DecLineDrawing),
+        _ => anyhow::bail!("invalid charset {name}"),
+    }
+}
+
+fn parse_mouse_encoding(name: &str) -> anyhow::Result<MouseEncoding> {
+    match name {
+        "X10" => Ok(MouseEncoding::X10),
+        "Utf8" => Ok(MouseEncoding::Utf8),
+        "SGR" => Ok(MouseEncoding::SGR),
+        "SgrPixels" => Ok(MouseEncoding::SgrPixels),
+        _ => anyhow::bail!("invalid mouse encoding {name}"),
+    }
+}
+
+impl TerminalState {
+    /// Captures the screen contents, cursor, modes and other state
+    /// that affect how the terminal renders and how it will interpret
+    /// subsequent output.
+    /// Keyboard encoding, saved cursors and in-progress image transfers
+    /// are not captured.
+    pub fn snapshot(&self) -> TerminalSnapshot {
+        TerminalSnapshot {
+            version: SNAPSHOT_VERSION,
+            size: self.get_size(),
+            title: self.title.clone(),
+            icon_title: self.icon_title.clone(),
+            current_dir: self.current_dir.as_ref().map(|url| url.to_string()),
+            user_vars: self.user_vars.clone(),
+            primary: screen_snapshot(&self.screen.screen),
+            alternate: screen_snapshot(&self.screen.alt_screen),
+            alt_screen_is_active: self.screen.alt_screen_is_active,
+            cursor: self.cursor,
+            cursor_visible: self.cursor_visible,
+            pen: self.pen.clone(),
+            modes: TerminalModes {
+                wrap_next: self.wrap_next,
+                insert: self.insert,
+                dec_auto_wrap: self.dec_auto_wrap,
+                reverse_wraparound_mode: self.reverse_wraparound_mode,
+                reverse_video_mode: self.reverse_video_mode,
+                dec_origin_mode: self.dec_origin_mode,
+                left_and_right_margin_mode: self.left_and_right_margin_mode,
+                application_cursor_keys: self.application_cursor_keys,
+                modify_other_keys: self.modify_other_keys,
+                dec_ansi_mode: self.dec_ansi_mode,
+                sixel_display_mode: self.sixel_display_mode,
+                application_keypad: self.application_keypad,
+                bracketed_paste: self.bracketed_paste,
+                focus_tracking: self.focus_tracking,
+                mouse_tracking: self.mouse_tracking,
+                button_event_mouse: self.button_event_mouse,
+                any_event_mouse: self.any_event_mouse,
+                mouse_encoding: format!("{:?}", self.mouse_encoding),
+                newline_mode: self.newline_mode,
+                g0_charset: charset_name(self.g0_charset),
+                g1_charset: charset_name(self.g1_charset),
+                shift_out: self.shift_out,
+            },
+            top_and_bottom_margins: self.top_and_bottom_margins.clone(),
+            left_and_right_margins: self.left_and_right_margins.clone(),
+            tab_stops: self
+                .tabs
+                .tabs
+                .iter()
+                .enumerate()
+                .filter_map(|(col, &set)| if set { Some(col) } else { None })
+                .collect(),
+            palette: self.palette.clone(),
+        }
+    }
+
+    /// Replaces the state of the terminal with that from a snapshot.
+    /// The terminal is resized to match the snapshot first.
+    pub fn restore_snapshot(&mut self, snapshot: &TerminalSnapshot) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            snapshot.version == SNAPSHOT_VERSION,
+            "snapshot version {} is not supported; expected version {}",
+            snapshot.version,
+            SNAPSHOT_VERSION
+        );
+        anyhow::ensure!(
+            snapshot.primary.lines.len() >= snapshot.size.rows
+                && snapshot.alternate.lines.len() >= snapshot.size.rows,
+            "snapshot has fewer lines than its size"
+        );
+        let mouse_encoding = parse_mouse_encoding(&snapshot.modes.mouse_encoding)?;
+        let g0_charset = parse_charset(&snapshot.modes.g0_charset)?;
+        let g1_charset = parse_charset(&snapshot.modes.g1_charset)?;
+        let current_dir = match &snapshot.current_dir {
+            Some(url) => Some(Url::parse(url)?),
+            None => None,
+        };
+
+        self.resize(snapshot.size);
+
+        self.screen.screen.restore_lines(
+            snapshot.primary.lines.clone(),
+            snapshot.primary.stable_row_index_offset,
+        );
+        self.screen.alt_screen.restore_lines(
+            snapshot.alternate.lines.clone(),
+            snapshot.alternate.stable_row_index_offset,
+        );
+        self.screen.alt_screen_is_active = snapshot.alt_screen_is_active;
+
+        self.title = snapshot.title.clone();
+        self.icon_title = snapshot.icon_title.clone();
+        self.current_dir = current_dir;
+        self.user_vars = snapshot.user_vars.clone();
+        self.cursor = snapshot.cursor;
+        self.cursor_visible = snapshot.cursor_visible;
+        self.pen = snapshot.pen.clone();
+
+        let modes = &snapshot.modes;
+        self.wrap_next = modes.wrap_next;
+        self.insert = modes.insert;
+        self.dec_auto_wrap = modes.dec_auto_wrap;
+        self.reverse_wraparound_mode = modes.reverse_wraparound_mode;
+        self.reverse_video_mode = modes.reverse_video_mode;
+        self.dec_origin_mode = modes.dec_origin_mode;
+        self.left_and_right_margin_mode = modes.left_and_right_margin_mode;
+        self.application_cursor_keys = modes.application_cursor_keys;
+        self.modify_other_keys = modes.modify_other_keys;
+        self.dec_ansi_mode = modes.dec_ansi_mode;
+        self.sixel_display_mode = modes.sixel_display_mode;
+        self.application_keypad = modes.application_keypad;
+        self.bracketed_paste = modes.bracketed_paste;
+        self.focus_tracking = modes.focus_tracking;
+        self.mouse_tracking = modes.mouse_tracking;
+        self.button_event_mouse = modes.button_event_mouse;
+        self.any_event_mouse = modes.any_event_mouse;
+        self.mouse_encoding = mouse_encoding;
+        self.newline_mode = modes.newline_mode;
+        self.g0_charset = g0_charset;
+        self.g1_charset = g1_charset;
+        self.shift_out = modes.shift_out;
+
+        self.top_and_bottom_margins = snapshot.top_and_bottom_margins.clone();
+        self.left_and_right_margins = snapshot.left_and_right_margins.clone();
+        for (col, tab) in self.tabs.tabs.iter_mut().enumerate() {
+            *tab = snapshot.tab_stops.contains(&col);
+        }
+        self.palette = snapshot.palette.clone();
+
+        self.increment_seqno();
+        Ok(())
+    }
+}
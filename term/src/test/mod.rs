@@ -479,6 +479,83 @@ fn test_semantic() {
     );
 }
 
+#[test]
+fn test_command_history() {
+    let mut term = TestTerm::new(5, 20, 10);
+    term.print("\x1b]7;file://host/tmp\x1b\\");
+    term.print("\x1b]133;A\x1b\\$ \x1b]133;B\x1b\\ls -l\r\n");
+    term.print("\x1b]133;C\x1b\\total 0\r\n\x1b]133;D;2\x1b\\");
+    term.print("\x1b]133;A\x1b\\$ \x1b]133;B\x1b\\\r\n\x1b]133;C\x1b\\");
+    term.print("\x1b]133;A\x1b\\$ \x1b]133;B\x1b\\make\r\n\x1b]133;C\x1b\\");
+
+    let history = term.get_command_history();
+    let summary: Vec<_> = history
+        .iter()
+        .map(|c| {
+            (
+                c.command.as_str(),
+                c.output_row,
+                c.cwd.as_deref(),
+                c.exit_code,
+                c.finished.is_some(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("ls -l", 1, Some("file://host/tmp"), Some(2), true),
+            // The empty command line isn't recorded
+            ("make", 4, Some("file://host/tmp"), None, false),
+        ]
+    );
+}
+
+#[test]
+fn test_set_user_var() {
+    struct Alerts(Arc<Mutex<Vec<Alert>>>);
+    impl AlertHandler for Alerts {
+        fn alert(&mut self, alert: Alert) {
+            self.0.lock().unwrap().push(alert);
+        }
+    }
+
+    let mut term = TestTerm::new(5, 20, 10);
+    let alerts = Arc::new(Mutex::new(vec![]));
+    term.set_notification_handler(Box::new(Alerts(Arc::clone(&alerts))));
+
+    term.print("\x1b]7;file://host/tmp\x1b\\");
+    // "bar", base64 encoded
+    term.print("\x1b]1337;SetUserVar=FOO=YmFy\x07");
+    assert_eq!(term.user_vars().get("FOO").map(String::as_str), Some("bar"));
+    assert_eq!(term.user_var_host("FOO"), Some("host"));
+
+    // A value that is set locally has no host
+    term.set_user_var("FOO".to_string(), "captured".to_string());
+    assert_eq!(
+        term.user_vars().get("FOO").map(String::as_str),
+        Some("captured")
+    );
+    assert_eq!(term.user_var_host("FOO"), None);
+
+    let set: Vec<(String, String)> = alerts
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|alert| match alert {
+            Alert::SetUserVar { name, value } => Some((name.clone(), value.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        set,
+        vec![
+            ("FOO".to_string(), "bar".to_string()),
+            ("FOO".to_string(), "captured".to_string()),
+        ]
+    );
+}
+
 #[test]
 fn issue_1161() {
     let mut term = TestTerm::new(1, 5, 0);
@@ -1121,6 +1198,23 @@ fn test_ri() {
     assert_all_contents(&term, file!(), line!(), &["1", "", ""]);
 }
 
+#[test]
+fn test_alt_screen_capture() {
+    let mut term = TestTerm::new(3, 4, 10);
+    term.print("1\r\n");
+    term.set_mode("?1049", true);
+    term.print("ab\r\ncd");
+    term.set_mode("?1049", false);
+    assert_all_contents(&term, file!(), line!(), &["1", "", ""]);
+
+    term.set_alt_screen_capture(Some(true));
+    term.set_mode("?1049", true);
+    term.print("ab\r\ncd");
+    term.set_mode("?1049", false);
+    assert_all_contents(&term, file!(), line!(), &["ab", "cd", "1", "", ""]);
+    assert_eq!(term.screen().visible_row_to_stable_row(0), 2);
+}
+
 #[test]
 fn test_scroll_margins() {
     let mut term = TestTerm::new(3, 1, 10);
@@ -1265,3 +1359,22 @@ fn test_hyperlinks() {
         Compare::TEXT | Compare::ATTRS,
     );
 }
+
+#[cfg(feature = "use_serde")]
+#[test]
+fn snapshot_round_trip() {
+    let mut term = TestTerm::new(5, 10, 10);
+    term.print("\x1b[1mhello\x1b[0m\r\nworld");
+    term.set_mode("?2004", true);
+    term.set_scroll_region(1, 3);
+    term.cup(2, 1);
+    let snapshot = term.snapshot();
+
+    let mut restored = TestTerm::new(3, 4, 10);
+    restored.restore_snapshot(&snapshot).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+    assert!(restored.bracketed_paste_enabled());
+
+    restored.print("!");
+    assert_visible_contents(&restored, file!(), line!(), &["hello", "wo!ld", "", "", ""]);
+}
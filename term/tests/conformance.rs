@@ -0,0 +1,275 @@
+use std::path::Path;
+use wezterm_term::harness::{run_corpus, TermHarness};
+
+#[test]
+fn conformance_corpus() {
+    let _ = env_logger::Builder::new().is_test(true).try_init();
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/conformance");
+    let results = run_corpus(&dir).unwrap();
+    assert!(!results.is_empty(), "no cases found in {}", dir.display());
+
+    let failures: Vec<String> = results
+        .into_iter()
+        .filter_map(|(name, result)| result.err().map(|err| format!("{name}: {err}")))
+        .collect();
+    if !failures.is_empty() {
+        panic!(
+            "{} conformance case(s) failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+#[test]
+fn harness_surface() {
+    let mut harness = TermHarness::new(2, 4);
+    harness.feed("ab\x1b[1mc");
+
+    let mut expected = termwiz::surface::Surface::new(4, 2);
+    expected.add_change("ab");
+    expected.add_change(termwiz::cell::AttributeChange::Intensity(
+        termwiz::cell::Intensity::Bold,
+    ));
+    expected.add_change("c");
+
+    harness.assert_screen(&["abc"]);
+    harness.assert_surface(&expected);
+}
+
+#[test]
+fn eight_bit_key_sequences() {
+    use wezterm_term::{KeyCode, KeyModifiers};
+
+    let mut harness = TermHarness::new(2, 4);
+    harness.feed("\x1b G\x1b[?2004h");
+    let term = harness.terminal_mut();
+    term.key_down(KeyCode::UpArrow, KeyModifiers::NONE).unwrap();
+    term.key_down(KeyCode::Char('['), KeyModifiers::ALT)
+        .unwrap();
+    term.send_paste("\x1b[A").unwrap();
+    assert_eq!(
+        harness.take_responses(),
+        b"\xc2\x9bA\x1b[\xc2\x9b200~\x1b[A\xc2\x9b201~"
+    );
+
+    harness.feed("\x1b F");
+    let term = harness.terminal_mut();
+    term.key_down(KeyCode::UpArrow, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.take_responses(), b"\x1b[A");
+}
+
+#[test]
+fn paste_data() {
+    let mut harness = TermHarness::new(2, 4);
+    harness.feed("\x1b[?2004h");
+    let term = harness.terminal_mut();
+    assert_eq!(term.paste_data("a\x1b[201~b"), "\x1b[200~ab\x1b[201~");
+    assert_eq!(harness.take_responses(), b"");
+}
+
+#[test]
+fn media_copy() {
+    use std::sync::{Arc, Mutex};
+    use wezterm_term::{Alert, AlertHandler};
+
+    struct PrintJobs(Arc<Mutex<Vec<(String, bool)>>>);
+    impl AlertHandler for PrintJobs {
+        fn alert(&mut self, alert: Alert) {
+            if let Alert::PrintRequested { data, more_follows } = alert {
+                self.0.lock().unwrap().push((data, more_follows));
+            }
+        }
+    }
+
+    let jobs = Arc::new(Mutex::new(vec![]));
+    let mut harness = TermHarness::new(3, 4);
+    harness
+        .terminal_mut()
+        .set_notification_handler(Box::new(PrintJobs(Arc::clone(&jobs))));
+
+    // Printer controller mode passes the output through unchanged
+    harness.feed("\x1b[5ihi\r\n\x1b[1mx\x1b[4i");
+    // Print screen, with a form feed
+    harness.feed("ab\r\ncd\x1b[?18h\x1b[i");
+    // Print cursor line
+    harness.feed("\x1b[?1i");
+    // Auto print prints each line as the cursor leaves it
+    harness.feed("\x1b[?5i\r\nef\r\n\x1b[?4i\r\n");
+
+    assert_eq!(
+        std::mem::take(&mut *jobs.lock().unwrap()),
+        vec![
+            ("hi\r\n\x1b[1mx".to_string(), false),
+            ("ab\r\ncd\r\n\r\n\x0c".to_string(), false),
+            ("cd\r\n".to_string(), false),
+            ("cd\r\n".to_string(), false),
+            ("ef\r\n".to_string(), false),
+        ]
+    );
+
+    // Long printer controller output is passed on in parts, and the
+    // end of the job is passed on even if there is no more output
+    let long = "x".repeat(1024 * 1024);
+    harness.feed(&format!("\x1b[5i{}\x1b[4i", long));
+    assert_eq!(
+        std::mem::take(&mut *jobs.lock().unwrap()),
+        vec![(long, true), (String::new(), false)]
+    );
+}
+
+#[test]
+fn enq_answerback() {
+    let mut harness = TermHarness::new(2, 4);
+    harness
+        .terminal_mut()
+        .set_enq_answerback(Some("\x1b[ok\r".to_string()));
+    harness.feed("\x05");
+    assert_eq!(harness.take_responses(), b"\x1b[ok\r");
+
+    // The answerback isn't translated to 8-bit controls
+    harness.feed("\x1b G\x05");
+    assert_eq!(harness.take_responses(), b"\x1b[ok\r");
+
+    harness.terminal_mut().set_enq_answerback(None);
+    harness.feed("\x05");
+    assert_eq!(harness.take_responses(), b"");
+}
+
+#[test]
+fn escape_sequence_permissions() {
+    use std::sync::Arc;
+    use wezterm_term::color::ColorPalette;
+    use wezterm_term::config::EscapeSequencePermissions;
+    use wezterm_term::TerminalConfiguration;
+
+    #[derive(Debug)]
+    struct UntrustedHost;
+    impl TerminalConfiguration for UntrustedHost {
+        fn color_palette(&self) -> ColorPalette {
+            ColorPalette::default()
+        }
+
+        fn escape_sequence_permissions(&self, host: Option<&str>) -> EscapeSequencePermissions {
+            let trusted = host != Some("untrusted");
+            EscapeSequencePermissions {
+                set_title: trusted,
+                user_vars: trusted,
+                ..Default::default()
+            }
+        }
+    }
+
+    let mut harness = TermHarness::new(2, 4);
+    harness.terminal_mut().set_config(Arc::new(UntrustedHost));
+    harness.feed("\x1b]2;local\x07\x1b]1337;SetUserVar=a=MQ==\x07");
+    harness.feed("\x1b]7;file://untrusted/tmp\x07");
+    harness.feed("\x1b]2;remote\x07\x1b]1337;SetUserVar=b=Mg==\x07");
+
+    let term = harness.terminal();
+    assert_eq!(term.get_title(), "local");
+    assert_eq!(term.user_vars().get("a").map(String::as_str), Some("1"));
+    assert_eq!(term.user_vars().get("b"), None);
+}
+
+#[test]
+fn title_and_user_var_hosts() {
+    let mut harness = TermHarness::new(2, 4);
+    harness.feed("\x1b]2;local\x07\x1b]1337;SetUserVar=a=MQ==\x07");
+    assert_eq!(harness.terminal().get_title_host(), None);
+
+    harness.feed("\x1b]7;file://remote/tmp\x07");
+    harness.feed("\x1b]1;remote\x07\x1b]1337;SetUserVar=b=Mg==\x07");
+
+    let term = harness.terminal();
+    assert_eq!(term.get_title(), "remote");
+    assert_eq!(term.get_title_host(), Some("remote"));
+    assert_eq!(term.user_var_host("a"), None);
+    assert_eq!(term.user_var_host("b"), Some("remote"));
+}
+
+#[test]
+fn keep_hidden_format_characters() {
+    use std::sync::Arc;
+    use wezterm_term::color::ColorPalette;
+    use wezterm_term::TerminalConfiguration;
+
+    #[derive(Debug)]
+    struct KeepHidden;
+    impl TerminalConfiguration for KeepHidden {
+        fn color_palette(&self) -> ColorPalette {
+            ColorPalette::default()
+        }
+
+        fn keep_hidden_format_characters(&self) -> bool {
+            true
+        }
+    }
+
+    let mut harness = TermHarness::new(2, 8);
+    harness.feed("a\u{202e}b\u{200b}c");
+    harness.assert_screen(&["abc"]);
+
+    harness.terminal_mut().set_config(Arc::new(KeepHidden));
+    harness.feed("\r\na\u{202e}b\u{200b}c");
+    let line = &harness.visible_lines()[1];
+    let cells: Vec<String> = line
+        .visible_cells()
+        .take(3)
+        .map(|cell| cell.str().to_string())
+        .collect();
+    assert_eq!(cells, vec!["a", "\u{202e}b", "\u{200b}c"]);
+}
+
+#[test]
+fn record_whitespace() {
+    use std::sync::Arc;
+    use wezterm_term::color::ColorPalette;
+    use wezterm_term::{TabSpan, TerminalConfiguration};
+
+    #[derive(Debug)]
+    struct RecordWhitespace;
+    impl TerminalConfiguration for RecordWhitespace {
+        fn color_palette(&self) -> ColorPalette {
+            ColorPalette::default()
+        }
+
+        fn record_whitespace(&self) -> bool {
+            true
+        }
+    }
+
+    let mut harness = TermHarness::new(5, 16);
+    harness.feed("a\tb\r\n");
+    assert_eq!(
+        harness.visible_lines()[0]
+            .get_cell(1)
+            .unwrap()
+            .attrs()
+            .tab_span(),
+        TabSpan::None
+    );
+
+    harness
+        .terminal_mut()
+        .set_config(Arc::new(RecordWhitespace));
+    harness.feed("a\tb\r\nabc\rd\r\ne\r\r\n");
+    harness.assert_screen(&["a       b", "a       b", "dbc", "e"]);
+
+    let lines = harness.visible_lines();
+    let spans: Vec<TabSpan> = lines[1]
+        .visible_cells()
+        .take(9)
+        .map(|cell| cell.attrs().tab_span())
+        .collect();
+    let mut expected = vec![TabSpan::Continuation; 9];
+    expected[0] = TabSpan::None;
+    expected[1] = TabSpan::Start;
+    expected[8] = TabSpan::None;
+    assert_eq!(spans, expected);
+
+    assert!(!lines[1].has_bare_carriage_return());
+    assert!(lines[2].has_bare_carriage_return());
+    assert!(lines[3].has_bare_carriage_return());
+}
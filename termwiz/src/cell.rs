@@ -68,6 +68,7 @@ impl std::fmt::Debug for CellAttributes {
             .field("wrapped", &self.wrapped())
             .field("overline", &self.overline())
             .field("semantic_type", &self.semantic_type())
+            .field("tab_span", &self.tab_span())
             .field("foreground", &self.foreground)
             .field("background", &self.background)
             .field("fat", &self.fat)
@@ -258,6 +259,26 @@ pub enum VerticalAlign {
     SubScript = 2,
 }
 
+/// Marks the blank cells that a horizontal tab moved the cursor across,
+/// so that tabs can be told apart from spaces.  Terminals only record
+/// this when they have been asked to.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TabSpan {
+    None = 0,
+    /// The first cell that the tab moved across
+    Start = 1,
+    /// One of the cells that follow the first
+    Continuation = 2,
+}
+
+impl Default for TabSpan {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
 impl Default for CellAttributes {
     fn default() -> Self {
         Self::blank()
@@ -276,6 +297,7 @@ impl CellAttributes {
     bitfield!(overline, set_overline, 12);
     bitfield!(semantic_type, set_semantic_type, SemanticType, 0b11, 13);
     bitfield!(vertical_align, set_vertical_align, VerticalAlign, 0b11, 15);
+    bitfield!(tab_span, set_tab_span, TabSpan, 0b11, 17);
 
     pub const fn blank() -> Self {
         Self {
@@ -508,6 +530,8 @@ impl CellAttributes {
         res.set_underline(Underline::None);
         res.set_overline(false);
         res.set_strikethrough(false);
+        // A blank made from a tab is not itself a tab
+        res.set_tab_span(TabSpan::None);
         res
     }
 
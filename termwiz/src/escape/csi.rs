@@ -351,6 +351,13 @@ pub enum Device {
     DeviceAttributes(DeviceAttributes),
     /// DECSTR - https://vt100.net/docs/vt510-rm/DECSTR.html
     SoftReset,
+    /// DECSCL - https://vt100.net/docs/vt510-rm/DECSCL.html
+    SelectConformanceLevel {
+        /// 61 for VT100 level, 62 and up for VT200 and later levels
+        level: i64,
+        /// Whether replies use 8-bit rather than 7-bit C1 controls
+        eight_bit_controls: bool,
+    },
     RequestPrimaryDeviceAttributes,
     RequestSecondaryDeviceAttributes,
     RequestTertiaryDeviceAttributes,
@@ -360,6 +367,48 @@ pub enum Device {
     RequestTerminalNameAndVersion,
     RequestTerminalParameters(i64),
     XtSmGraphics(XtSmGraphics),
+    /// MC - https://vt100.net/docs/vt510-rm/MC.html
+    MediaCopy(MediaCopy),
+}
+
+/// The functions of the MC (Media Copy) sequence, which sends the
+/// content of the display or the output of the application to a printer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaCopy {
+    /// `CSI i` or `CSI 0 i`: print the screen, or the scrolling
+    /// region if DECPEX is reset
+    PrintScreen,
+    /// `CSI 4 i`: end printer controller mode
+    PrinterControllerOff,
+    /// `CSI 5 i`: start printer controller mode, in which the output
+    /// of the application is sent to the printer instead of the screen
+    PrinterControllerOn,
+    /// `CSI ? 1 i`: print the line containing the cursor
+    PrintCursorLine,
+    /// `CSI ? 4 i`: end auto print mode
+    AutoPrintOff,
+    /// `CSI ? 5 i`: start auto print mode, in which each line is
+    /// printed as the cursor moves off it
+    AutoPrintOn,
+    /// `CSI ? 10 i`: print the composed display
+    PrintComposedDisplay,
+    /// `CSI ? 11 i`: print all pages, including the scrollback
+    PrintAllPages,
+}
+
+impl Display for MediaCopy {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match self {
+            Self::PrintScreen => write!(f, "i"),
+            Self::PrinterControllerOff => write!(f, "4i"),
+            Self::PrinterControllerOn => write!(f, "5i"),
+            Self::PrintCursorLine => write!(f, "?1i"),
+            Self::AutoPrintOff => write!(f, "?4i"),
+            Self::AutoPrintOn => write!(f, "?5i"),
+            Self::PrintComposedDisplay => write!(f, "?10i"),
+            Self::PrintAllPages => write!(f, "?11i"),
+        }
+    }
 }
 
 impl Display for Device {
@@ -374,12 +423,22 @@ impl Display for Device {
             Device::DeviceAttributes(DeviceAttributes::Vt320(attr)) => attr.emit(f, "?63")?,
             Device::DeviceAttributes(DeviceAttributes::Vt420(attr)) => attr.emit(f, "?64")?,
             Device::SoftReset => write!(f, "!p")?,
+            Device::SelectConformanceLevel {
+                level,
+                eight_bit_controls,
+            } => write!(
+                f,
+                "{};{}\"p",
+                level,
+                if *eight_bit_controls { 0 } else { 1 }
+            )?,
             Device::RequestPrimaryDeviceAttributes => write!(f, "c")?,
             Device::RequestSecondaryDeviceAttributes => write!(f, ">c")?,
             Device::RequestTertiaryDeviceAttributes => write!(f, "=c")?,
             Device::RequestTerminalNameAndVersion => write!(f, ">q")?,
             Device::RequestTerminalParameters(n) => write!(f, "{};1;1;128;128;1;0x", n + 2)?,
             Device::StatusReport => write!(f, "5n")?,
+            Device::MediaCopy(mc) => mc.fmt(f)?,
             Device::XtSmGraphics(g) => {
                 write!(f, "?{};{}", g.item, g.action_or_status)?;
                 for v in &g.value {
@@ -801,6 +860,13 @@ pub enum DecPrivateModeCode {
     AutoWrap = 7,
     /// https://vt100.net/docs/vt510-rm/DECARM.html
     AutoRepeat = 8,
+    /// https://vt100.net/docs/vt510-rm/DECPFF.html
+    /// When enabled, a form feed is sent after printing the screen
+    PrintFormFeed = 18,
+    /// https://vt100.net/docs/vt510-rm/DECPEX.html
+    /// When enabled, printing the screen prints all of it rather
+    /// than only the scrolling region
+    PrintExtent = 19,
     StartBlinkingCursor = 12,
     ShowCursor = 25,
 
@@ -1769,6 +1835,21 @@ impl<'a> CSIParser<'a> {
             ('m', [CsiParam::P(b'>'), ..]) => self.xterm_key_modifier(params),
 
             ('p', [CsiParam::P(b'!')]) => Ok(CSI::Device(Box::new(Device::SoftReset))),
+            // The VT100 level has no 8-bit controls; the higher levels
+            // default to using them unless 7-bit controls are requested
+            ('p', [CsiParam::Integer(level), CsiParam::P(b'"')]) => {
+                Ok(CSI::Device(Box::new(Device::SelectConformanceLevel {
+                    level: *level,
+                    eight_bit_controls: *level > 61,
+                })))
+            }
+            (
+                'p',
+                [CsiParam::Integer(level), CsiParam::P(b';'), CsiParam::Integer(controls), CsiParam::P(b'"')],
+            ) => Ok(CSI::Device(Box::new(Device::SelectConformanceLevel {
+                level: *level,
+                eight_bit_controls: *level > 61 && *controls != 1,
+            }))),
             ('u', [CsiParam::P(b'='), CsiParam::Integer(flags)]) => {
                 Ok(CSI::Keyboard(Keyboard::SetKittyState {
                     flags: KittyKeyboardFlags::from_bits_truncate(
@@ -1862,6 +1943,9 @@ impl<'a> CSIParser<'a> {
                 'h' => self
                     .terminal_mode(params)
                     .map(|mode| CSI::Mode(Mode::SetMode(mode))),
+                'i' => self
+                    .media_copy(params)
+                    .map(|dev| CSI::Device(Box::new(dev))),
                 'j' => parse!(Cursor, CharacterPositionBackward, params),
                 'k' => parse!(Cursor, LinePositionBackward, params),
                 'l' => self
@@ -2177,6 +2261,21 @@ impl<'a> CSIParser<'a> {
         }
     }
 
+    fn media_copy(&mut self, params: &'a [CsiParam]) -> Result<Device, ()> {
+        let mc = match params {
+            [] | [CsiParam::Integer(0)] => MediaCopy::PrintScreen,
+            [CsiParam::Integer(4)] => MediaCopy::PrinterControllerOff,
+            [CsiParam::Integer(5)] => MediaCopy::PrinterControllerOn,
+            [CsiParam::P(b'?'), CsiParam::Integer(1)] => MediaCopy::PrintCursorLine,
+            [CsiParam::P(b'?'), CsiParam::Integer(4)] => MediaCopy::AutoPrintOff,
+            [CsiParam::P(b'?'), CsiParam::Integer(5)] => MediaCopy::AutoPrintOn,
+            [CsiParam::P(b'?'), CsiParam::Integer(10)] => MediaCopy::PrintComposedDisplay,
+            [CsiParam::P(b'?'), CsiParam::Integer(11)] => MediaCopy::PrintAllPages,
+            _ => return Err(()),
+        };
+        Ok(Device::MediaCopy(mc))
+    }
+
     fn req_terminal_parameters(&mut self, params: &'a [CsiParam]) -> Result<Device, ()> {
         match params {
             [] | [CsiParam::Integer(0)] => Ok(Device::RequestTerminalParameters(0)),
@@ -3086,6 +3185,99 @@ mod test {
         assert_eq!(res, vec![CSI::Device(Box::new(Device::SoftReset))],);
     }
 
+    #[test]
+    fn media_copy() {
+        for (params, expected, encoded) in [
+            (vec![], MediaCopy::PrintScreen, "\x1b[i"),
+            (vec![CsiParam::Integer(0)], MediaCopy::PrintScreen, "\x1b[i"),
+            (
+                vec![CsiParam::Integer(5)],
+                MediaCopy::PrinterControllerOn,
+                "\x1b[5i",
+            ),
+            (
+                vec![CsiParam::Integer(4)],
+                MediaCopy::PrinterControllerOff,
+                "\x1b[4i",
+            ),
+            (
+                vec![CsiParam::P(b'?'), CsiParam::Integer(1)],
+                MediaCopy::PrintCursorLine,
+                "\x1b[?1i",
+            ),
+            (
+                vec![CsiParam::P(b'?'), CsiParam::Integer(5)],
+                MediaCopy::AutoPrintOn,
+                "\x1b[?5i",
+            ),
+            (
+                vec![CsiParam::P(b'?'), CsiParam::Integer(11)],
+                MediaCopy::PrintAllPages,
+                "\x1b[?11i",
+            ),
+        ] {
+            let res: Vec<_> = CSI::parse(&params, false, 'i').collect();
+            assert_eq!(encode(&res), encoded);
+            assert_eq!(
+                res,
+                vec![CSI::Device(Box::new(Device::MediaCopy(expected)))]
+            );
+        }
+    }
+
+    #[test]
+    fn select_conformance_level() {
+        let res: Vec<_> = CSI::parse(
+            &[
+                CsiParam::Integer(62),
+                CsiParam::P(b';'),
+                CsiParam::Integer(1),
+                CsiParam::P(b'"'),
+            ],
+            false,
+            'p',
+        )
+        .collect();
+        assert_eq!(encode(&res), "\x1b[62;1\"p");
+        assert_eq!(
+            res,
+            vec![CSI::Device(Box::new(Device::SelectConformanceLevel {
+                level: 62,
+                eight_bit_controls: false,
+            }))]
+        );
+
+        let res: Vec<_> =
+            CSI::parse(&[CsiParam::Integer(63), CsiParam::P(b'"')], false, 'p').collect();
+        assert_eq!(encode(&res), "\x1b[63;0\"p");
+        assert_eq!(
+            res,
+            vec![CSI::Device(Box::new(Device::SelectConformanceLevel {
+                level: 63,
+                eight_bit_controls: true,
+            }))]
+        );
+
+        let res: Vec<_> = CSI::parse(
+            &[
+                CsiParam::Integer(61),
+                CsiParam::P(b';'),
+                CsiParam::Integer(0),
+                CsiParam::P(b'"'),
+            ],
+            false,
+            'p',
+        )
+        .collect();
+        assert_eq!(
+            res,
+            vec![CSI::Device(Box::new(Device::SelectConformanceLevel {
+                level: 61,
+                eight_bit_controls: false,
+            }))]
+        );
+    }
+
     #[test]
     fn device_attr() {
         let res: Vec<_> = CSI::parse(
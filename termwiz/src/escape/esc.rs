@@ -84,6 +84,11 @@ pub enum EscCode {
     /// Designate G1 Character Set – US ASCII
     AsciiCharacterSetG1 = esc!(')', 'B'),
 
+    /// S7C1T - Send 7-bit C1 control sequences in replies
+    SevenBitControls = esc!(' ', 'F'),
+    /// S8C1T - Send 8-bit C1 control characters in replies
+    EightBitControls = esc!(' ', 'G'),
+
     /// https://vt100.net/docs/vt510-rm/DECALN.html
     DecScreenAlignmentDisplay = esc!('#', '8'),
 
@@ -201,5 +206,7 @@ mod test {
         );
         assert_eq!(parse("#5"), Esc::Code(EscCode::DecSingleWidthLine));
         assert_eq!(parse("#6"), Esc::Code(EscCode::DecDoubleWidthLine));
+        assert_eq!(parse(" F"), Esc::Code(EscCode::SevenBitControls));
+        assert_eq!(parse(" G"), Esc::Code(EscCode::EightBitControls));
     }
 }
@@ -75,6 +75,13 @@ impl Parser {
         }
     }
 
+    /// Limits the length of OSC sequences; longer sequences are
+    /// discarded as they are received rather than accumulated.
+    /// See `VTParser::set_max_osc_len`.
+    pub fn set_max_osc_len(&mut self, max_len: usize) {
+        self.state_machine.set_max_osc_len(max_len);
+    }
+
     /// advance with tmux parser, bypass VTParse
     fn advance_tmux_bytes(&mut self, bytes: &[u8]) -> anyhow::Result<Vec<Event>> {
         let parser_state = self.state.borrow();
@@ -63,6 +63,7 @@ mod nerdfonts_data;
 mod readbuf;
 pub mod render;
 pub mod surface;
+pub mod suspicious;
 pub mod terminal;
 pub mod tmux_cc;
 #[cfg(feature = "widgets")]
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 
 lazy_static::lazy_static! {
     pub static ref NERD_FONTS: HashMap<&'static str, char> = build_map();
@@ -12,3 +13,59 @@ fn build_map() -> HashMap<&'static str, char> {
         .map(|tuple| *tuple)
         .collect()
 }
+
+lazy_static::lazy_static! {
+    static ref NERD_FONT_CODEPOINTS: HashSet<char> = crate::nerdfonts_data::NERD_FONT_GLYPHS
+        .iter()
+        .map(|(_name, c)| *c)
+        .collect();
+}
+
+/// The Material Design Icons were at these codepoints in Nerd Fonts v2.
+/// Nerd Fonts v3 moved them, in the same order, to the range that starts
+/// at `MDI_V3_FIRST`, and reused the start of the old range for octicons.
+const MDI_V2: RangeInclusive<u32> = 0xf500..=0xfd46;
+const MDI_V3_FIRST: u32 = 0xf0001;
+
+/// Returns the Nerd Fonts v3 equivalent of `c`, if `c` is one of the
+/// codepoints that were removed from Nerd Fonts v3.
+pub fn nerd_font_v2_to_v3(c: char) -> Option<char> {
+    let code = c as u32;
+    if !MDI_V2.contains(&code) || NERD_FONT_CODEPOINTS.contains(&c) {
+        return None;
+    }
+    char::from_u32(code - MDI_V2.start() + MDI_V3_FIRST)
+        .filter(|mapped| NERD_FONT_CODEPOINTS.contains(mapped))
+}
+
+/// Returns the Nerd Fonts v2 equivalent of `c`, if `c` is one of the
+/// codepoints that were introduced by Nerd Fonts v3 for a glyph that
+/// was also present in Nerd Fonts v2.
+pub fn nerd_font_v3_to_v2(c: char) -> Option<char> {
+    let code = c as u32;
+    let first = MDI_V3_FIRST;
+    let last = MDI_V3_FIRST + (MDI_V2.end() - MDI_V2.start());
+    if !(first..=last).contains(&code) || !NERD_FONT_CODEPOINTS.contains(&c) {
+        return None;
+    }
+    char::from_u32(code - MDI_V3_FIRST + MDI_V2.start())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mdi_mapping() {
+        // nf-mdi-language_python -> nf-md-language_python
+        assert_eq!(nerd_font_v2_to_v3('\u{f81f}'), Some('\u{f0320}'));
+        assert_eq!(nerd_font_v3_to_v2('\u{f0320}'), Some('\u{f81f}'));
+        // nf-mdi-folder -> nf-md-folder
+        assert_eq!(nerd_font_v2_to_v3('\u{f74a}'), Some('\u{f024b}'));
+        // nf-oct-sparkle_fill reuses a codepoint from the v2 range
+        assert_eq!(nerd_font_v2_to_v3('\u{f51b}'), None);
+        // Not part of the moved range
+        assert_eq!(nerd_font_v2_to_v3('\u{e0b0}'), None);
+        assert_eq!(nerd_font_v3_to_v2('\u{f1af0}'), None);
+    }
+}
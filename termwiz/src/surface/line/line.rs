@@ -294,6 +294,22 @@ impl Line {
         self.update_last_change_seqno(seqno);
     }
 
+    /// Returns true if a carriage return that wasn't followed by a
+    /// line feed returned to the start of the line.  Terminals only
+    /// record this when they have been asked to.
+    #[inline]
+    pub fn has_bare_carriage_return(&self) -> bool {
+        self.bits.contains(LineBits::BARE_CARRIAGE_RETURN)
+    }
+
+    /// Records that a carriage return that wasn't followed by a line
+    /// feed returned to the start of the line
+    #[inline]
+    pub fn set_bare_carriage_return(&mut self, seqno: SequenceNo) {
+        self.bits.insert(LineBits::BARE_CARRIAGE_RETURN);
+        self.update_last_change_seqno(seqno);
+    }
+
     /// Check whether the line is double-width and not double-height.
     #[inline]
     pub fn is_double_width(&self) -> bool {
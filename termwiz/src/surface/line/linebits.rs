@@ -49,5 +49,9 @@ bitflags! {
         /// Otherwise, the auto-detect direction is used, falling back
         /// to the direction specified by the RTL bit.
         const AUTO_DETECT_DIRECTION = 1<<8;
+
+        /// true if a carriage return that wasn't followed by a line
+        /// feed returned to the start of this line
+        const BARE_CARRIAGE_RETURN = 1<<9;
     }
 }
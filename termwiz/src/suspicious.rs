@@ -0,0 +1,234 @@
+//! Finds characters that can disguise what a line of text says, as
+//! is done with commands that are meant to be pasted into a shell:
+//! bidirectional controls that reorder the text that follows them,
+//! characters that are never displayed, and letters that look like
+//! the letters of another script among which they are used.
+use crate::surface::Line;
+
+/// The scripts whose letters are commonly mistaken for one another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Cherokee,
+}
+
+/// Why a character was found to be suspicious
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// A bidirectional control, which changes the order in which the
+    /// text that follows it is displayed
+    Bidi,
+    /// A character that takes up no space and is never displayed
+    Invisible,
+    /// A letter that is displayed as a blank, like a space
+    Blank,
+    /// A letter of `letter` script in a word whose other letters are
+    /// mostly of `word` script
+    MixedScript { letter: Script, word: Script },
+}
+
+/// A suspicious character of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    /// The index of the cell that holds the character
+    pub cell_index: usize,
+    pub c: char,
+    pub reason: Reason,
+}
+
+/// Returns true if `c` is a bidirectional control: an embedding,
+/// override or isolate, or one of the marks that act like them
+pub fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Returns true if `c` is a format character that takes up no space
+/// and is never displayed.  Some of them are legitimately used in
+/// emoji and in some scripts; `scan_line` considers the grapheme
+/// in which they are used.
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{034f}'
+            | '\u{180e}'
+            | '\u{200b}'..='\u{200d}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{feff}'
+            | '\u{e0000}'..='\u{e007f}'
+    )
+}
+
+/// Returns the reason that `c` is suspicious when used in `grapheme`,
+/// leaving aside the script of the letters around it
+fn reason_for(c: char, grapheme: &str) -> Option<Reason> {
+    if is_bidi_control(c) {
+        return Some(Reason::Bidi);
+    }
+    match c {
+        // Joiners shape emoji sequences and some scripts; among
+        // ASCII they only serve to disguise text
+        '\u{200c}' | '\u{200d}' => {
+            if grapheme.chars().all(|g| g.is_ascii() || is_invisible(g)) {
+                Some(Reason::Invisible)
+            } else {
+                None
+            }
+        }
+        // Tags spell out the flags of subdivisions, such as that of
+        // Scotland, but can also smuggle ASCII text
+        '\u{e0020}'..='\u{e007f}' if grapheme.starts_with('\u{1f3f4}') => None,
+        c if is_invisible(c) => Some(Reason::Invisible),
+        '\u{3164}' | '\u{ffa0}' => Some(Reason::Blank),
+        _ => None,
+    }
+}
+
+fn script(c: char) -> Option<Script> {
+    match c {
+        'a'..='z' | 'A'..='Z' | '\u{00c0}'..='\u{024f}' | '\u{1e00}'..='\u{1eff}' => {
+            Some(Script::Latin)
+        }
+        '\u{0370}'..='\u{03ff}' | '\u{1f00}'..='\u{1fff}' => Some(Script::Greek),
+        '\u{0400}'..='\u{052f}'
+        | '\u{1c80}'..='\u{1c8f}'
+        | '\u{2de0}'..='\u{2dff}'
+        | '\u{a640}'..='\u{a69f}' => Some(Script::Cyrillic),
+        '\u{0530}'..='\u{058f}' => Some(Script::Armenian),
+        '\u{13a0}'..='\u{13ff}' | '\u{ab70}'..='\u{abbf}' => Some(Script::Cherokee),
+        _ => None,
+    }
+}
+
+/// Flags the letters of `word` that aren't of the script of most of
+/// its letters, preferring Latin when there is no majority
+fn flag_mixed_scripts(word: &mut Vec<(usize, char, Script)>, findings: &mut Vec<Finding>) {
+    let count = |script: Script| word.iter().filter(|(_, _, s)| *s == script).count();
+    let mut dominant = Script::Latin;
+    for &(_, _, script) in word.iter() {
+        if count(script) > count(dominant) {
+            dominant = script;
+        }
+    }
+    for &(cell_index, c, script) in word.iter() {
+        if script != dominant {
+            findings.push(Finding {
+                cell_index,
+                c,
+                reason: Reason::MixedScript {
+                    letter: script,
+                    word: dominant,
+                },
+            });
+        }
+    }
+    word.clear();
+}
+
+/// Returns the suspicious characters of `line`, ordered by cell
+pub fn scan_line(line: &Line) -> Vec<Finding> {
+    let mut findings = vec![];
+    let mut word = vec![];
+    for cell in line.visible_cells() {
+        let text = cell.str();
+        let cell_index = cell.cell_index();
+        for c in text.chars() {
+            if let Some(reason) = reason_for(c, text) {
+                findings.push(Finding {
+                    cell_index,
+                    c,
+                    reason,
+                });
+            }
+        }
+        if text.chars().any(char::is_alphanumeric) {
+            for c in text.chars().filter(|c| c.is_alphabetic()) {
+                if let Some(script) = script(c) {
+                    word.push((cell_index, c, script));
+                }
+            }
+        } else {
+            flag_mixed_scripts(&mut word, &mut findings);
+        }
+    }
+    flag_mixed_scripts(&mut word, &mut findings);
+    findings.sort_by_key(|finding| finding.cell_index);
+    findings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cell::CellAttributes;
+    use crate::surface::SEQ_ZERO;
+
+    fn scan(text: &str) -> Vec<(usize, char, Reason)> {
+        let line = Line::from_text(text, &CellAttributes::default(), SEQ_ZERO, None);
+        scan_line(&line)
+            .into_iter()
+            .map(|f| (f.cell_index, f.c, f.reason))
+            .collect()
+    }
+
+    #[test]
+    fn mixed_scripts() {
+        assert!(scan("ls -la /tmp && echo paypal").is_empty());
+        assert!(scan("Привет, мир").is_empty());
+        assert!(scan("5µs").is_empty());
+        assert_eq!(
+            scan("curl p\u{430}ypal.com"),
+            vec![(
+                6,
+                '\u{430}',
+                Reason::MixedScript {
+                    letter: Script::Cyrillic,
+                    word: Script::Latin
+                }
+            )]
+        );
+        // The Latin letter stands out in a mostly Cyrillic word
+        assert_eq!(
+            scan("\u{43f}\u{440}u\u{432}\u{435}\u{442}"),
+            vec![(
+                2,
+                'u',
+                Reason::MixedScript {
+                    letter: Script::Latin,
+                    word: Script::Cyrillic
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn hidden() {
+        assert_eq!(scan("a\u{200d}b"), vec![(0, '\u{200d}', Reason::Invisible)]);
+        // The joiners of emoji sequences are fine
+        assert!(scan("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}").is_empty());
+        // As are the tags of the flag of Scotland
+        assert!(scan("\u{1f3f4}\u{e0067}\u{e0062}\u{e0073}\u{e0063}\u{e0074}\u{e007f}").is_empty());
+        assert_eq!(
+            scan("ok\u{e0072}\u{e006d}"),
+            vec![
+                (1, '\u{e0072}', Reason::Invisible),
+                (1, '\u{e006d}', Reason::Invisible)
+            ]
+        );
+        assert_eq!(scan("a\u{3164}b"), vec![(1, '\u{3164}', Reason::Blank)]);
+    }
+
+    #[test]
+    fn bidi() {
+        assert!(is_bidi_control('\u{202e}'));
+        assert!(is_bidi_control('\u{2067}'));
+        assert!(!is_bidi_control('\u{202f}'));
+        assert!(is_invisible('\u{200b}'));
+        assert!(!is_invisible(' '));
+    }
+}
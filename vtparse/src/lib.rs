@@ -302,10 +302,18 @@ struct OscState {
     param_indices: [usize; MAX_OSC],
     num_params: usize,
     full: bool,
+    /// The longest string that is accumulated
+    max_len: usize,
+    /// Set once the string grew beyond `max_len`; the rest of it
+    /// is ignored and the sequence is not dispatched
+    overflowed: bool,
 }
 
 impl OscState {
     fn put(&mut self, param: char) {
+        if self.overflowed {
+            return;
+        }
         if param == ';' {
             match self.num_params {
                 MAX_OSC => {
@@ -322,8 +330,13 @@ impl OscState {
             }
 
             let mut buf = [0u8; 8];
-            self.buffer
-                .extend_from_slice(param.encode_utf8(&mut buf).as_bytes());
+            let bytes = param.encode_utf8(&mut buf).as_bytes();
+            if self.buffer.len() + bytes.len() > self.max_len {
+                self.overflowed = true;
+                self.buffer = Vec::new();
+                return;
+            }
+            self.buffer.extend_from_slice(bytes);
         }
     }
 }
@@ -420,6 +433,8 @@ impl VTParser {
                 param_indices,
                 num_params: 0,
                 full: false,
+                max_len: usize::MAX,
+                overflowed: false,
             },
 
             params: Default::default(),
@@ -432,6 +447,14 @@ impl VTParser {
         }
     }
 
+    /// Limits the string of an OSC sequence to `max_len` bytes.
+    /// Sequences that are longer than that are discarded as they
+    /// are received, rather than accumulated in memory.
+    /// There is no limit by default.
+    pub fn set_max_osc_len(&mut self, max_len: usize) {
+        self.osc.max_len = max_len;
+    }
+
     /// Returns if the state machine is in the ground state,
     /// i.e. there is no pending state held by the state machine.
     pub fn is_ground(&self) -> bool {
@@ -567,11 +590,14 @@ impl VTParser {
                 self.osc.buffer.clear();
                 self.osc.num_params = 0;
                 self.osc.full = false;
+                self.osc.overflowed = false;
             }
             Action::OscPut => self.osc.put(param as char),
 
             Action::OscEnd => {
-                if self.osc.num_params == 0 {
+                if self.osc.overflowed {
+                    // Discarded
+                } else if self.osc.num_params == 0 {
                     actor.osc_dispatch(&[]);
                 } else {
                     let mut params: [&[u8]; MAX_OSC] = [b""; MAX_OSC];
@@ -802,6 +828,21 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_osc_too_long() {
+        let mut parser = VTParser::new();
+        parser.set_max_osc_len(8);
+        let mut actor = CollectingVTActor::default();
+        parser.parse(b"\x1b]0;too long\x07\x1b]0;short\x07", &mut actor);
+        assert_eq!(
+            actor.into_vec(),
+            vec![VTAction::OscDispatch(vec![
+                b"0".to_vec(),
+                b"short".to_vec()
+            ])]
+        );
+    }
+
     #[test]
     fn test_osc_with_no_params() {
         assert_eq!(
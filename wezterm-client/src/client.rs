@@ -1355,4 +1355,18 @@ impl Client {
         GetPaneDirectionResponse
     );
     rpc!(adjust_pane_size, AdjustPaneSize, UnitResponse);
+    rpc!(set_log_level, SetLogLevel, SetLogLevelResponse);
+    rpc!(get_pane_snapshot, GetPaneSnapshot, GetPaneSnapshotResponse);
+    rpc!(capture_pane, CapturePane, CapturePaneResponse);
+    rpc!(get_command_history, GetCommandHistory, GetCommandHistoryResponse);
+    rpc!(get_window_config, GetWindowConfig, WindowConfigResponse);
+    rpc!(set_window_config, SetWindowConfig, WindowConfigResponse);
+    rpc!(
+        set_window_config_profile,
+        SetWindowConfigProfile,
+        WindowConfigResponse
+    );
+    rpc!(send_text_to_panes, SendTextToPanes, SendTextToPanesResponse);
+    rpc!(set_pane_tags, PaneTagsChanged, UnitResponse);
+    rpc!(set_user_var, SetUserVar, UnitResponse);
 }
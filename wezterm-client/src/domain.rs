@@ -4,7 +4,7 @@ use anyhow::{anyhow, bail};
 use async_trait::async_trait;
 use codec::{ListPanesResponse, SpawnV2, SplitPane};
 use config::keyassignment::SpawnTabDomain;
-use config::{SshDomain, TlsDomainClient, UnixDomain};
+use config::{ExitBehavior, SshDomain, TlsDomainClient, UnixDomain};
 use mux::connui::{ConnectionUI, ConnectionUIParams};
 use mux::domain::{alloc_domain_id, Domain, DomainId, DomainState, SplitSource};
 use mux::pane::{Pane, PaneId};
@@ -354,6 +354,30 @@ fn mux_notify_client_domain(local_domain_id: DomainId, notif: MuxNotification) -
                 }
             }
         }
+        MuxNotification::PaneTagsChanged { pane_id, tags } => {
+            let remote_pane_id = mux
+                .get_pane(pane_id)
+                .filter(|pane| pane.domain_id() == local_domain_id)
+                .and_then(|pane| {
+                    pane.downcast_ref::<ClientPane>()
+                        .filter(|pane| !pane.is_applying_server_tags())
+                        .map(|pane| pane.remote_pane_id())
+                });
+            if let Some(remote_pane_id) = remote_pane_id {
+                if let Some(inner) = client_domain.inner() {
+                    promise::spawn::spawn(async move {
+                        inner
+                            .client
+                            .set_pane_tags(codec::PaneTagsChanged {
+                                pane_id: remote_pane_id,
+                                tags,
+                            })
+                            .await
+                    })
+                    .detach();
+                }
+            }
+        }
         MuxNotification::WindowTitleChanged {
             window_id,
             title: _,
@@ -416,6 +440,15 @@ impl ClientDomain {
         self.config.connect_automatically()
     }
 
+    /// Returns the ssh domain configuration if this domain
+    /// connects via ssh
+    pub fn ssh_domain(&self) -> Option<&SshDomain> {
+        match &self.config {
+            ClientDomainConfig::Ssh(ssh) => Some(ssh),
+            _ => None,
+        }
+    }
+
     pub fn perform_detach(&self) {
         log::info!("detached domain {}", self.local_domain_id);
         self.inner.lock().unwrap().take();
@@ -580,7 +613,7 @@ impl ClientDomain {
                 tab.sync_with_pane_tree(root_size, tabroot, |entry| {
                     workspace.replace(entry.workspace.clone());
                     remote_panes_to_forget.remove(&entry.pane_id);
-                    if let Some(pane_id) = inner.remote_to_local_pane_id(entry.pane_id) {
+                    let pane = if let Some(pane_id) = inner.remote_to_local_pane_id(entry.pane_id) {
                         match mux.get_pane(pane_id) {
                             Some(pane) => pane,
                             None => {
@@ -615,7 +648,11 @@ impl ClientDomain {
                         );
                         mux.add_pane(&pane).expect("failed to add pane to mux");
                         pane
+                    };
+                    if let Some(client_pane) = pane.downcast_ref::<ClientPane>() {
+                        client_pane.apply_server_tags(entry.tags.iter().cloned().collect());
                     }
+                    pane
                 });
 
                 if let Some(local_window_id) = inner.remote_to_local_window(remote_window_id) {
@@ -756,6 +793,7 @@ impl Domain for ClientDomain {
         _size: TerminalSize,
         _command: Option<CommandBuilder>,
         _command_dir: Option<String>,
+        _exit_behavior: Option<ExitBehavior>,
     ) -> anyhow::Result<Arc<dyn Pane>> {
         anyhow::bail!("spawn_pane not implemented for ClientDomain")
     }
@@ -819,6 +857,8 @@ impl Domain for ClientDomain {
         size: TerminalSize,
         command: Option<CommandBuilder>,
         command_dir: Option<String>,
+        // The remote mux doesn't honor this override
+        _exit_behavior: Option<ExitBehavior>,
         window: WindowId,
     ) -> anyhow::Result<Arc<Tab>> {
         let inner = self
@@ -887,6 +927,7 @@ impl Domain for ClientDomain {
             SplitSource::Spawn {
                 command,
                 command_dir,
+                exit_behavior: _,
             } => (command, command_dir, None),
             SplitSource::MovePane(move_pane_id) => (None, None, Some(move_pane_id)),
         };
@@ -18,7 +18,7 @@ use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
 use rangeset::RangeSet;
 use ratelim::RateLimiter;
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::Range;
 use std::sync::Arc;
 use termwiz::input::KeyEvent;
@@ -27,7 +27,7 @@ use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Alert, Clipboard, KeyCode, KeyModifiers, Line, MouseEvent, StableRowIndex,
+    Alert, Clipboard, CommandRecord, KeyCode, KeyModifiers, Line, MouseEvent, StableRowIndex,
     TerminalConfiguration, TerminalSize,
 };
 
@@ -48,6 +48,9 @@ pub struct ClientPane {
     user_vars: Mutex<HashMap<String, String>>,
     config: Mutex<Option<Arc<dyn TerminalConfiguration>>>,
     unseen_output: Mutex<bool>,
+    /// Set while the tags reported by the server are being applied,
+    /// so that they aren't sent back to it
+    applying_server_tags: Mutex<bool>,
 }
 
 impl ClientPane {
@@ -127,6 +130,7 @@ impl ClientPane {
             mouse_grabbed: Mutex::new(false),
             ignore_next_kill: Mutex::new(false),
             unseen_output: Mutex::new(false),
+            applying_server_tags: Mutex::new(false),
             user_vars: Mutex::new(HashMap::new()),
             config: Mutex::new(None),
         }
@@ -197,6 +201,9 @@ impl ClientPane {
                     alert,
                 });
             }
+            Pdu::PaneTagsChanged(PaneTagsChanged { tags, .. }) => {
+                self.apply_server_tags(tags.into_iter().collect());
+            }
             Pdu::PaneRemoved(PaneRemoved { pane_id }) => {
                 log::trace!("remote pane {} has been removed", pane_id);
                 self.renderable.lock().inner.borrow_mut().dead = true;
@@ -231,6 +238,20 @@ impl ClientPane {
         self.remote_pane_id
     }
 
+    /// Replaces the tags of the pane with those that the server
+    /// reported for it.  The change is announced to the subscribers
+    /// of the mux, but isn't sent back to the server; doing so would
+    /// race with any later local change, which the echo could revert.
+    pub fn apply_server_tags(&self, tags: BTreeSet<String>) {
+        *self.applying_server_tags.lock() = true;
+        Mux::get().set_pane_tags(self.local_pane_id, tags);
+        *self.applying_server_tags.lock() = false;
+    }
+
+    pub(crate) fn is_applying_server_tags(&self) -> bool {
+        *self.applying_server_tags.lock()
+    }
+
     /// Arrange to suppress the next Pane::kill call.
     /// This is a bit of a hack that we use when closing a window;
     /// our Domain::local_window_is_closing impl calls this for each
@@ -241,6 +262,31 @@ impl ClientPane {
     pub fn ignore_next_kill(&self) {
         *self.ignore_next_kill.lock() = true;
     }
+
+    fn paste(&self, text: &str, toggle_slow_paste: bool) -> anyhow::Result<()> {
+        let client = Arc::clone(&self.client);
+        let remote_pane_id = self.remote_pane_id;
+        self.renderable
+            .lock()
+            .inner
+            .borrow_mut()
+            .predict_from_paste(text);
+
+        let data = text.to_owned();
+        promise::spawn::spawn(async move {
+            client
+                .client
+                .send_paste(SendPaste {
+                    pane_id: remote_pane_id,
+                    data,
+                    toggle_slow_paste,
+                })
+                .await
+        })
+        .detach();
+        self.renderable.lock().inner.borrow_mut().update_last_send();
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
@@ -262,6 +308,15 @@ impl Pane for ClientPane {
             Value::String("since_last_response_ms".to_string()),
             Value::U64(inner.last_recv_time.elapsed().as_millis() as u64),
         );
+        map.insert(
+            Value::String("password_input".to_string()),
+            Value::Bool(
+                inner
+                    .text_before_cursor()
+                    .map(|text| mux::pane::looks_like_password_prompt(&text))
+                    .unwrap_or(false),
+            ),
+        );
 
         Value::Object(map.into())
     }
@@ -317,27 +372,11 @@ impl Pane for ClientPane {
     }
 
     fn send_paste(&self, text: &str) -> anyhow::Result<()> {
-        let client = Arc::clone(&self.client);
-        let remote_pane_id = self.remote_pane_id;
-        self.renderable
-            .lock()
-            .inner
-            .borrow_mut()
-            .predict_from_paste(text);
+        self.paste(text, false)
+    }
 
-        let data = text.to_owned();
-        promise::spawn::spawn(async move {
-            client
-                .client
-                .send_paste(SendPaste {
-                    pane_id: remote_pane_id,
-                    data,
-                })
-                .await
-        })
-        .detach();
-        self.renderable.lock().inner.borrow_mut().update_last_send();
-        Ok(())
+    fn send_paste_toggling_slow_paste(&self, text: &str) -> anyhow::Result<()> {
+        self.paste(text, true)
     }
 
     fn reader(&self) -> anyhow::Result<Option<Box<dyn std::io::Read + Send>>> {
@@ -434,6 +473,17 @@ impl Pane for ClientPane {
         }
     }
 
+    async fn get_command_history(&self) -> anyhow::Result<Vec<CommandRecord>> {
+        let GetCommandHistoryResponse { commands } = self
+            .client
+            .client
+            .get_command_history(GetCommandHistory {
+                pane_id: self.remote_pane_id,
+            })
+            .await?;
+        Ok(commands)
+    }
+
     fn key_down(&self, key: KeyCode, mods: KeyModifiers) -> anyhow::Result<()> {
         let input_serial;
         {
@@ -597,6 +647,33 @@ impl Pane for ClientPane {
         self.user_vars.lock().clone()
     }
 
+    fn set_user_var(&self, name: &str, value: &str) -> anyhow::Result<()> {
+        // Take the value now, rather than when the server reports it
+        // back, so that it can be read straight away
+        self.user_vars
+            .lock()
+            .insert(name.to_string(), value.to_string());
+        let client = Arc::clone(&self.client);
+        let remote_pane_id = self.remote_pane_id;
+        let name = name.to_string();
+        let value = value.to_string();
+        promise::spawn::spawn(async move {
+            if let Err(err) = client
+                .client
+                .set_user_var(SetUserVar {
+                    pane_id: remote_pane_id,
+                    name,
+                    value,
+                })
+                .await
+            {
+                log::error!("failed to set user var of pane {remote_pane_id}: {err:#}");
+            }
+        })
+        .detach();
+        Ok(())
+    }
+
     fn set_config(&self, config: Arc<dyn TerminalConfiguration>) {
         let palette = config.color_palette();
         // If the application running in the pane hasn't changed the
@@ -130,6 +130,18 @@ impl RenderableInner {
         }
     }
 
+    /// Returns the text of the cursor line that precedes the cursor,
+    /// if we have a copy of that line
+    pub fn text_before_cursor(&self) -> Option<String> {
+        let cursor = self.cursor_position;
+        match self.lines.peek(&cursor.y)? {
+            LineEntry::Line(line)
+            | LineEntry::LineAndFetching(line, _)
+            | LineEntry::Stale(line) => Some(line.columns_as_str(0..cursor.x)),
+            LineEntry::Fetching(_) => None,
+        }
+    }
+
     /// Predictive echo can be noisy when the link is working well,
     /// so we only employ it when it looks like the latency is high.
     fn should_predict(&self) -> bool {
@@ -465,6 +465,7 @@ struct FontConfigInner {
     pane_select_font: RefCell<Option<Rc<LoadedFont>>>,
     char_select_font: RefCell<Option<Rc<LoadedFont>>>,
     command_palette_font: RefCell<Option<Rc<LoadedFont>>>,
+    key_display_font: RefCell<Option<Rc<LoadedFont>>>,
     fallback_channel: RefCell<Option<Sender<FallbackResolveInfo>>>,
 }
 
@@ -486,6 +487,7 @@ impl FontConfigInner {
             pane_select_font: RefCell::new(None),
             char_select_font: RefCell::new(None),
             command_palette_font: RefCell::new(None),
+            key_display_font: RefCell::new(None),
             font_scale: RefCell::new(1.0),
             dpi: RefCell::new(dpi),
             config: RefCell::new(config.clone()),
@@ -504,6 +506,7 @@ impl FontConfigInner {
         self.pane_select_font.borrow_mut().take();
         self.char_select_font.borrow_mut().take();
         self.command_palette_font.borrow_mut().take();
+        self.key_display_font.borrow_mut().take();
         self.metrics.borrow_mut().take();
         *self.font_dirs.borrow_mut() = Arc::new(FontDatabase::with_font_dirs(config)?);
         Ok(())
@@ -695,6 +698,22 @@ impl FontConfigInner {
         Ok(loaded)
     }
 
+    fn key_display_font(&self, myself: &Rc<Self>) -> anyhow::Result<Rc<LoadedFont>> {
+        let config = self.config.borrow();
+
+        let mut key_display_font = self.key_display_font.borrow_mut();
+
+        if let Some(entry) = key_display_font.as_ref() {
+            return Ok(Rc::clone(entry));
+        }
+
+        let loaded = self.make_title_font_impl(myself, Some(config.key_display_font_size), true)?;
+
+        key_display_font.replace(Rc::clone(&loaded));
+
+        Ok(loaded)
+    }
+
     fn resolve_font_helper_impl(
         &self,
         attributes: &[FontAttributes],
@@ -1065,6 +1084,10 @@ impl FontConfiguration {
         self.inner.char_select_font(&self.inner)
     }
 
+    pub fn key_display_font(&self) -> anyhow::Result<Rc<LoadedFont>> {
+        self.inner.key_display_font(&self.inner)
+    }
+
     /// Given a text style, load (with caching) the font that best
     /// matches according to the fontconfig pattern.
     pub fn resolve_font(&self, style: &TextStyle) -> anyhow::Result<Rc<LoadedFont>> {
@@ -1,6 +1,7 @@
 use clap::builder::ValueParser;
-use clap::{Parser, ValueHint};
-use config::{GuiPosition, SshParameters};
+use clap::{Parser, ValueEnum, ValueHint};
+use config::{CharacterEncoding, ExitBehavior, GuiPosition, SshParameters};
+use std::convert::TryFrom;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
@@ -24,6 +25,11 @@ pub fn name_equals_value(arg: &str) -> Result<(String, String), String> {
     }
 }
 
+/// Helper for parsing character encoding names
+pub fn character_encoding(arg: &str) -> Result<CharacterEncoding, String> {
+    CharacterEncoding::try_from(arg.to_string()).map_err(|err| err.to_string())
+}
+
 #[derive(Debug, Parser, Default, Clone)]
 #[command(trailing_var_arg = true)]
 pub struct StartCommand {
@@ -44,6 +50,12 @@ pub struct StartCommand {
     #[arg(long, conflicts_with = "always_new_process")]
     pub new_tab: bool,
 
+    /// When spawning into an existing GUI instance, spawn a new
+    /// window even if `prefer_to_spawn_tabs` is enabled in the
+    /// configuration.
+    #[arg(long, conflicts_with_all = ["always_new_process", "new_tab"])]
+    pub new_window: bool,
+
     /// Specify the current working directory for the initially
     /// spawned program
     #[arg(long = "cwd", value_parser, value_hint=ValueHint::DirPath)]
@@ -71,6 +83,14 @@ pub struct StartCommand {
     #[arg(long = "class")]
     pub class: Option<String>,
 
+    /// Set the role of the windows spawned by this instance of
+    /// wezterm, which window managers can use to tell them apart
+    /// from other windows with the same class.
+    /// This is only supported under X11, where it sets the
+    /// WM_WINDOW_ROLE property.
+    #[arg(long = "role")]
+    pub role: Option<String>,
+
     /// Override the default workspace with the provided name.
     /// The default is "default".
     #[arg(long = "workspace")]
@@ -101,6 +121,12 @@ pub struct StartCommand {
     #[arg(long, requires = "domain")]
     pub attach: bool,
 
+    /// Override the exit_behavior config for the spawned program,
+    /// so that its output remains visible after it exits.
+    /// Implies --always-new-process.
+    #[arg(long, value_enum)]
+    pub exit_behavior: Option<StartExitBehavior>,
+
     /// Instead of executing your shell, run PROG.
     /// For example: `wezterm start -- bash -l` will spawn bash
     /// as if it were a login shell. [aliases: -e]
@@ -174,6 +200,18 @@ pub struct SerialCommand {
     #[arg(long = "baud")]
     pub baud: Option<usize>,
 
+    /// The character encoding used by the device, such as
+    /// `ISO-8859-1` or `Shift_JIS`.  The default is taken from
+    /// the `default_pane_encoding` configuration option.
+    #[arg(long = "encoding", value_parser=ValueParser::new(character_encoding))]
+    pub encoding: Option<CharacterEncoding>,
+
+    /// The string sent to the device in response to ENQ.
+    /// The default is taken from the `enq_answerback`
+    /// configuration option.
+    #[arg(long = "answerback")]
+    pub answerback: Option<String>,
+
     /// Override the default windowing system class.
     /// The default is "org.wezfurlong.wezterm".
     /// Under X11 and Windows this changes the window class.
@@ -270,4 +308,64 @@ pub struct ShowKeysCommand {
     /// In lua mode, show only the named key table
     #[arg(long)]
     pub key_table: Option<String>,
+    /// Instead of showing the keys, report assignments that are
+    /// shadowed, that reference undefined key tables, or that
+    /// are reserved by the operating system
+    #[arg(long, conflicts_with_all = ["lua", "key_table"])]
+    pub conflicts: bool,
+    /// In conflicts mode, check for the chords reserved by this
+    /// platform rather than the current one
+    #[arg(long, value_enum, requires = "conflicts")]
+    pub platform: Option<KeyPlatform>,
+    /// In conflicts mode, how to format the report
+    #[arg(long, value_enum, default_value_t, requires = "conflicts")]
+    pub format: ShowKeysFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StartExitBehavior {
+    /// Close the pane as soon as the program exits
+    Close,
+    /// Keep the pane open after the program exits
+    Hold,
+    /// Keep the pane open only if the program didn't exit cleanly
+    HoldOnError,
+}
+
+impl From<StartExitBehavior> for ExitBehavior {
+    fn from(behavior: StartExitBehavior) -> ExitBehavior {
+        match behavior {
+            StartExitBehavior::Close => ExitBehavior::Close,
+            StartExitBehavior::Hold => ExitBehavior::Hold,
+            StartExitBehavior::HoldOnError => ExitBehavior::CloseOnCleanExit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeyPlatform {
+    Macos,
+    Windows,
+    Linux,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ShowKeysFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct DoctorCommand {
+    /// How to format the report
+    #[arg(long, value_enum, default_value_t)]
+    pub format: DoctorFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum DoctorFormat {
+    #[default]
+    Text,
+    Json,
 }
@@ -1,6 +1,6 @@
 use crate::inputmap::InputMap;
 use config::keyassignment::*;
-use config::window::WindowLevel;
+use config::window::{WindowLevel, WindowSnapPosition};
 use config::{ConfigHandle, DeferredKeyCode};
 use mux::domain::DomainState;
 use mux::Mux;
@@ -223,6 +223,22 @@ impl CommandDef {
             });
         }
 
+        let mut profiles: Vec<&String> = config.profiles.keys().collect();
+        profiles.sort();
+        for name in profiles {
+            let action = KeyAssignment::ActivateConfigProfile(name.clone());
+            if let Some(cmd) = derive_command_from_key_assignment(&action) {
+                result.push(ExpandedCommand {
+                    brief: cmd.brief,
+                    doc: cmd.doc,
+                    keys: vec![],
+                    action,
+                    menubar: cmd.menubar,
+                    icon: cmd.icon.map(Into::into),
+                });
+            }
+        }
+
         // Generate some stuff based on the mux state
         if let Some(mux) = Mux::try_get() {
             let mut domains = mux.iter_domains();
@@ -632,7 +648,11 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             text: _,
             destination: ClipboardCopyDestination::PrimarySelection,
         }
-        | CopyTo(ClipboardCopyDestination::PrimarySelection) => CommandDef {
+        | CopyTo(CopyToArguments {
+            destination: ClipboardCopyDestination::PrimarySelection,
+            append: false,
+            ..
+        }) => CommandDef {
             brief: "Copy to primary selection".into(),
             doc: "Copies text to the primary selection".into(),
             keys: vec![(Modifiers::CTRL, "Insert".into())],
@@ -644,7 +664,11 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             text: _,
             destination: ClipboardCopyDestination::Clipboard,
         }
-        | CopyTo(ClipboardCopyDestination::Clipboard) => CommandDef {
+        | CopyTo(CopyToArguments {
+            destination: ClipboardCopyDestination::Clipboard,
+            append: false,
+            ..
+        }) => CommandDef {
             brief: "Copy to clipboard".into(),
             doc: "Copies text to the clipboard".into(),
             keys: vec![
@@ -659,7 +683,11 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             text: _,
             destination: ClipboardCopyDestination::ClipboardAndPrimarySelection,
         }
-        | CopyTo(ClipboardCopyDestination::ClipboardAndPrimarySelection) => CommandDef {
+        | CopyTo(CopyToArguments {
+            destination: ClipboardCopyDestination::ClipboardAndPrimarySelection,
+            append: false,
+            ..
+        }) => CommandDef {
             brief: "Copy to clipboard and primary selection".into(),
             doc: "Copies text to the clipboard and the primary selection".into(),
             keys: vec![(Modifiers::CTRL, "Insert".into())],
@@ -667,6 +695,34 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Edit"],
             icon: Some("md_content_copy"),
         },
+        CopyTo(CopyToArguments { append: true, .. }) => CommandDef {
+            brief: "Append selection to clipboard".into(),
+            doc: "Appends the selection to the text that the clipboard already holds".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: Some("md_content_copy"),
+        },
+        CaptureSelectionToUserVar(name) => CommandDef {
+            brief: format!("Capture selection to user var {name}").into(),
+            doc: format!("Sets the user var {name} of the pane to the selected text").into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &[],
+            icon: Some("md_variable"),
+        },
+        CaptureLastCommandOutputToUserVar(name) => CommandDef {
+            brief: format!("Capture last command output to user var {name}").into(),
+            doc: format!(
+                "Sets the user var {name} of the pane to the output of the \
+                 last command, which requires shell integration"
+            )
+            .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &[],
+            icon: Some("md_variable"),
+        },
         PasteFrom(ClipboardPasteSource::Clipboard) => CommandDef {
             brief: "Paste from clipboard".into(),
             doc: "Pastes text from the clipboard".into(),
@@ -686,6 +742,71 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["View"],
             icon: Some("md_fullscreen"),
         },
+        TogglePresentationMode => CommandDef {
+            brief: "Toggle presentation mode".into(),
+            doc: "Increases the font size, hides the tab bar and pane borders \
+                  and shows the keys that are pressed.  Toggling it again \
+                  restores the prior state"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_presentation"),
+        },
+        ActivateConfigProfile(name) => CommandDef {
+            brief: format!("Activate config profile `{name}`").into(),
+            doc: format!(
+                "Applies the overrides from the `{name}` entry of \
+                 `config.profiles` to the window, in place of any \
+                 other profile"
+            )
+            .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View", "Config Profile"],
+            icon: Some("md_tune"),
+        },
+        DeactivateConfigProfile => CommandDef {
+            brief: "Deactivate config profile".into(),
+            doc: "Removes the overrides of the active config profile \
+                  from the window"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View", "Config Profile"],
+            icon: Some("md_tune"),
+        },
+        ToggleKeyDisplay => CommandDef {
+            brief: "Toggle key display".into(),
+            doc: "Shows or hides an overlay that displays the keys \
+                  that are pressed and the actions that they trigger"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_keyboard"),
+        },
+        ToggleShowInvisibleCharacters => CommandDef {
+            brief: "Toggle showing invisible characters".into(),
+            doc: "Shows or hides dim pictures of the non-breaking spaces, \
+                  soft hyphens and zero-width characters in the panes \
+                  of the window"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_format_pilcrow"),
+        },
+        ToggleShowWhitespace => CommandDef {
+            brief: "Toggle showing whitespace".into(),
+            doc: "Shows or hides markers for the trailing spaces, tabs \
+                  and bare carriage returns in the panes of the window"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_keyboard_tab"),
+        },
         ToggleAlwaysOnTop => CommandDef {
             brief: "Toggle always on Top".into(),
             doc: "Toggles the window between floating and non-floating states to stay on top of other windows.".into(),
@@ -695,6 +816,14 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             icon: None,
 
         },
+        ToggleStickyWindow => CommandDef {
+            brief: "Toggle sticky window".into(),
+            doc: "Toggles whether the window is shown on all workspaces/virtual desktops".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["Window"],
+            icon: Some("md_pin"),
+        },
         ToggleAlwaysOnBottom => CommandDef {
             brief: "Toggle always on Bottom".into(),
             doc: "Toggles the window to remain behind all other windows.".into(),
@@ -727,6 +856,54 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Window", "Level"],
             icon: None,
         },
+        MoveWindow { dx, dy } => CommandDef {
+            brief: format!("Move window by {dx}, {dy}").into(),
+            doc: format!("Moves the window {dx} pixels horizontally and {dy} pixels vertically")
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &[],
+            icon: Some("md_arrow_all"),
+        },
+        ResizeWindow { dx, dy } => CommandDef {
+            brief: format!("Resize window by {dx}, {dy}").into(),
+            doc: format!("Changes the width of the window by {dx} pixels and its height by {dy} pixels")
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &[],
+            icon: Some("md_resize"),
+        },
+        SnapWindow(position) => {
+            let (brief, doc) = match position {
+                WindowSnapPosition::LeftHalf => ("Left Half", "the left half of the screen"),
+                WindowSnapPosition::RightHalf => ("Right Half", "the right half of the screen"),
+                WindowSnapPosition::TopHalf => ("Top Half", "the top half of the screen"),
+                WindowSnapPosition::BottomHalf => ("Bottom Half", "the bottom half of the screen"),
+                WindowSnapPosition::TopLeftQuarter => {
+                    ("Top Left Quarter", "the top left quarter of the screen")
+                }
+                WindowSnapPosition::TopRightQuarter => {
+                    ("Top Right Quarter", "the top right quarter of the screen")
+                }
+                WindowSnapPosition::BottomLeftQuarter => {
+                    ("Bottom Left Quarter", "the bottom left quarter of the screen")
+                }
+                WindowSnapPosition::BottomRightQuarter => (
+                    "Bottom Right Quarter",
+                    "the bottom right quarter of the screen",
+                ),
+                WindowSnapPosition::Center => ("Center", "the center of the screen"),
+            };
+            CommandDef {
+                brief: brief.into(),
+                doc: format!("Moves the window to {doc}").into(),
+                keys: vec![],
+                args: &[ArgType::ActiveWindow],
+                menubar: &["Window", "Snap"],
+                icon: None,
+            }
+        }
         Hide => CommandDef {
             brief: "Hide/Minimize Window".into(),
             doc: "Hides/Mimimizes the current window".into(),
@@ -761,6 +938,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Shell"],
             icon: Some("cod_empty_window"),
         },
+        MirrorPane => CommandDef {
+            brief: "Mirror pane in a new window".into(),
+            doc: "Opens a new window showing a read-only view of the \
+                  current pane that follows its output"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window"],
+            icon: Some("cod_mirror"),
+        },
         ClearScrollback(ScrollbackEraseMode::ScrollbackOnly) => CommandDef {
             brief: "Clear scrollback".into(),
             doc: "Clears any text that has scrolled out of the \
@@ -795,6 +982,85 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &[],
             icon: Some("oct_search"),
         },
+        SearchAllPanes(_) | SearchAllPanesArgs(_) => CommandDef {
+            brief: "Search all panes".into(),
+            doc: "Searches the output of every pane in the current workspace".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["Edit"],
+            icon: Some("md_text_search"),
+        },
+        ShowCommandJournal => CommandDef {
+            brief: "Show command journal".into(),
+            doc: "Lists the commands recently run in the panes of the \
+                  current workspace, as reported by shell integration"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_history"),
+        },
+        ShowDownloads => CommandDef {
+            brief: "Show downloads".into(),
+            doc: "Lists the files that programs running in panes have \
+                  downloaded during this session"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_download"),
+        },
+        RevealSuspiciousCharacters => CommandDef {
+            brief: "Reveal suspicious characters".into(),
+            doc: "Lists the characters in the viewport of the active pane \
+                  that can disguise what its text says, such as bidi \
+                  overrides and zero-width spaces"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("md_eye_outline"),
+        },
+        ShowColorSchemeEditor => CommandDef {
+            brief: "Edit the color scheme".into(),
+            doc: "Adjusts the colors of the window with a live preview, \
+                  and saves them as a color scheme"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("md_palette"),
+        },
+        AdjustOpacity(delta) => {
+            let delta = delta.into_inner();
+            if delta < 0.0 {
+                CommandDef {
+                    brief: "Decrease window opacity".into(),
+                    doc: format!(
+                        "Makes the background of the window more transparent by {}",
+                        -delta
+                    )
+                    .into(),
+                    keys: vec![],
+                    args: &[ArgType::ActiveWindow],
+                    menubar: &["View", "Opacity"],
+                    icon: Some("md_opacity"),
+                }
+            } else {
+                CommandDef {
+                    brief: "Increase window opacity".into(),
+                    doc: format!(
+                        "Makes the background of the window more opaque by {}",
+                        delta
+                    )
+                    .into(),
+                    keys: vec![],
+                    args: &[ArgType::ActiveWindow],
+                    menubar: &["View", "Opacity"],
+                    icon: Some("md_opacity"),
+                }
+            }
+        }
         ShowDebugOverlay => CommandDef {
             brief: "Show debug overlay".into(),
             doc: "Activates the debug overlay and Lua REPL".into(),
@@ -803,6 +1069,64 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Help"],
             icon: Some("cod_debug"),
         },
+        ShowVtInspector => CommandDef {
+            brief: "Show VT inspector".into(),
+            doc: "Shows the escape sequences that are parsed from the \
+                  output of the active pane in a split alongside it"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Help"],
+            icon: Some("cod_debug_console"),
+        },
+        ShowReleaseNotes => CommandDef {
+            brief: "Show release notes".into(),
+            doc: "Shows the notes of the most recent release in the \
+                  configured update channel"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["Help"],
+            icon: Some("md_newspaper_variant_outline"),
+        },
+        StartCapture(_) => CommandDef {
+            brief: "Start recording".into(),
+            doc: "Records the active pane to an animated GIF until \
+                  recording is stopped"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("cod_record"),
+        },
+        StopCapture => CommandDef {
+            brief: "Stop recording".into(),
+            doc: "Stops recording and saves the animated GIF".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: Some("cod_debug_stop"),
+        },
+        ExportToPdf(_) => CommandDef {
+            brief: "Export scrollback to PDF".into(),
+            doc: "Saves the scrollback of the active pane, or the \
+                  selected rows, as a paginated PDF"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("cod_file_pdf"),
+        },
+        ShowMagnifier(_) => CommandDef {
+            brief: "Magnify the text under the mouse".into(),
+            doc: "Shows an enlarged view of the text around the mouse \
+                  pointer while the key or button is held down"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &[],
+            icon: Some("md_magnify"),
+        },
         InputSelector(_) => CommandDef {
             brief: "Prompt the user to choose from a list".into(),
             doc: "Activates the selector overlay and wait for input".into(),
@@ -835,6 +1159,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &[],
             icon: None,
         },
+        ShowLinkHints => CommandDef {
+            brief: "Show link hints".into(),
+            doc: "Labels the links in the current pane so that they \
+                  can be opened or copied by typing their label"
+                .into(),
+            keys: vec![(Modifiers::CTRL.union(Modifiers::SHIFT), "e".into())],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: Some("md_open_in_new"),
+        },
         CharSelect(_) => CommandDef {
             brief: "Enter Emoji / Character selection mode".into(),
             doc: "Activates the character selection UI for the current pane".into(),
@@ -931,6 +1265,30 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["View", "Font Size"],
             icon: Some("md_format_size"),
         },
+        DecreasePaneFontSize => CommandDef {
+            brief: "Decrease pane font size".into(),
+            doc: "Scales the font size of the active pane smaller by 10%".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View", "Font Size"],
+            icon: Some("md_format_size"),
+        },
+        IncreasePaneFontSize => CommandDef {
+            brief: "Increase pane font size".into(),
+            doc: "Scales the font size of the active pane larger by 10%".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View", "Font Size"],
+            icon: Some("md_format_size"),
+        },
+        ResetPaneFontSize => CommandDef {
+            brief: "Reset pane font size".into(),
+            doc: "Restores the font size of the active pane to match the window".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View", "Font Size"],
+            icon: Some("md_format_size"),
+        },
         ResetFontAndWindowSize => CommandDef {
             brief: "Reset the window and font size".into(),
             doc: "Restores the original window and font size".into(),
@@ -1427,6 +1785,26 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["View"],
             icon: Some("md_format_align_bottom"),
         },
+        ToggleScrollbackSplit => CommandDef {
+            brief: "Toggle split scrollback view".into(),
+            doc: "When scrolled back, splits the pane view so that the \
+                  live output remains visible below the scrollback"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("md_view_split_horizontal"),
+        },
+        PauseOutputScrolling => CommandDef {
+            brief: "Pause/resume output scrolling".into(),
+            doc: "Freezes the view of the pane while output continues \
+                  to arrive in the background, or resumes following it"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("md_pause"),
+        },
         ScrollToTop => CommandDef {
             brief: "Scroll to the top".into(),
             doc: "Scrolls to the top of the viewport".into(),
@@ -1595,6 +1973,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Window"],
             icon: Some("md_fullscreen"),
         },
+        RespawnPane => CommandDef {
+            brief: "Respawn the current pane".into(),
+            doc: "Runs the command of the current pane again, \
+                  once it has exited, keeping its scrollback"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Shell"],
+            icon: Some("md_reload"),
+        },
         ActivateLastTab => CommandDef {
             brief: "Activate the last active tab".into(),
             doc: "If there was no prior active tab, has no effect.".into(),
@@ -1774,6 +2162,19 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &[],
             icon: None,
         },
+        ExtendSelectionToSemanticUnit => CommandDef {
+            brief: "Extends the selection to the enclosing path, URL, \
+                    quoted string or bracketed region"
+                .into(),
+            doc: "Extends the selection, or the text at the mouse cursor, \
+                  to the smallest path, URL or other quick select match, \
+                  quoted string or bracketed region that encloses it"
+                .into(),
+            keys: vec![],
+            args: &[],
+            menubar: &[],
+            icon: None,
+        },
         ClearSelection => CommandDef {
             brief: "Clears the selection in the current pane".into(),
             doc: "Clears the selection in the current pane".into(),
@@ -2000,6 +2401,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Shell"],
             icon: None,
         },
+        CancelZmodemTransfer => CommandDef {
+            brief: "Cancel the ZMODEM transfer in the current pane".into(),
+            doc: "Stops the zmodem_receive_command or zmodem_send_command \
+                  that is handling a transfer in the current pane"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Shell"],
+            icon: None,
+        },
         ActivateCommandPalette => CommandDef {
             brief: "Activate Command Palette".into(),
             doc: "Shows the command palette modal".into(),
@@ -2037,16 +2448,18 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         CloseCurrentPane { confirm: true },
         DetachDomain(SpawnTabDomain::CurrentPaneDomain),
         ResetTerminal,
+        CancelZmodemTransfer,
         // ----------------- Edit
         #[cfg(not(target_os = "macos"))]
         PasteFrom(ClipboardPasteSource::PrimarySelection),
         #[cfg(not(target_os = "macos"))]
-        CopyTo(ClipboardCopyDestination::PrimarySelection),
-        CopyTo(ClipboardCopyDestination::Clipboard),
+        CopyTo(ClipboardCopyDestination::PrimarySelection.into()),
+        CopyTo(ClipboardCopyDestination::Clipboard.into()),
         PasteFrom(ClipboardPasteSource::Clipboard),
         ClearScrollback(ScrollbackEraseMode::ScrollbackOnly),
         ClearScrollback(ScrollbackEraseMode::ScrollbackAndViewport),
         QuickSelect,
+        ShowLinkHints,
         CharSelect(CharSelectArguments::default()),
         ActivateCopyMode,
         ClearKeyTableStack,
@@ -2056,19 +2469,50 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         IncreaseFontSize,
         ResetFontSize,
         ResetFontAndWindowSize,
+        DecreasePaneFontSize,
+        IncreasePaneFontSize,
+        ResetPaneFontSize,
+        AdjustOpacity(NotNan::new(-0.05).unwrap()),
+        AdjustOpacity(NotNan::new(0.05).unwrap()),
         ScrollByPage(NotNan::new(-1.0).unwrap()),
         ScrollByPage(NotNan::new(1.0).unwrap()),
         ScrollToTop,
         ScrollToBottom,
+        ToggleScrollbackSplit,
+        PauseOutputScrolling,
+        StartCapture(CaptureArguments::default()),
+        StopCapture,
+        ExportToPdf(ExportToPdfArguments::default()),
         // ----------------- Window
         ToggleFullScreen,
+        TogglePresentationMode,
+        DeactivateConfigProfile,
+        ToggleKeyDisplay,
+        ToggleShowInvisibleCharacters,
+        ToggleShowWhitespace,
+        MirrorPane,
         ToggleAlwaysOnTop,
         ToggleAlwaysOnBottom,
+        ToggleStickyWindow,
         SetWindowLevel(WindowLevel::AlwaysOnBottom),
         SetWindowLevel(WindowLevel::Normal),
         SetWindowLevel(WindowLevel::AlwaysOnTop),
+        SnapWindow(WindowSnapPosition::LeftHalf),
+        SnapWindow(WindowSnapPosition::RightHalf),
+        SnapWindow(WindowSnapPosition::TopHalf),
+        SnapWindow(WindowSnapPosition::BottomHalf),
+        SnapWindow(WindowSnapPosition::TopLeftQuarter),
+        SnapWindow(WindowSnapPosition::TopRightQuarter),
+        SnapWindow(WindowSnapPosition::BottomLeftQuarter),
+        SnapWindow(WindowSnapPosition::BottomRightQuarter),
+        SnapWindow(WindowSnapPosition::Center),
         Hide,
         Search(Pattern::CurrentSelectionOrEmptyString),
+        SearchAllPanes(Pattern::CurrentSelectionOrEmptyString),
+        ShowCommandJournal,
+        ShowDownloads,
+        RevealSuspiciousCharacters,
+        ShowColorSchemeEditor,
         PaneSelect(PaneSelectArguments {
             alphabet: String::new(),
             mode: PaneSelectMode::Activate,
@@ -2130,6 +2574,7 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         ActivatePaneDirection(PaneDirection::Up),
         ActivatePaneDirection(PaneDirection::Down),
         TogglePaneZoomState,
+        RespawnPane,
         ActivateLastTab,
         ShowLauncher,
         ShowTabNavigator,
@@ -2138,6 +2583,8 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         OpenUri("https://github.com/wez/wezterm/discussions/".to_string()),
         OpenUri("https://github.com/wez/wezterm/issues/".to_string()),
         ShowDebugOverlay,
+        ShowVtInspector,
+        ShowReleaseNotes,
         // ----------------- Misc
         OpenLinkAtMouseCursor,
     ];
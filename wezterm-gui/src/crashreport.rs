@@ -0,0 +1,197 @@
+//! Opt-in crash reporting.
+//! When `enable_crash_reports` is set, panics and GPU failures write a
+//! report to the `crash-reports` directory under the wezterm data
+//! directory, and a notification offers to open it.
+//! The report is never sent anywhere; it is up to the user to decide
+//! whether to share it.
+use config::{ConfigHandle, HOME_DIR};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use wezterm_dynamic::{ToDynamic, Value};
+
+/// Mirrors the `enable_crash_reports` config, so that we don't need
+/// to consult the config from inside the panic hook
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// A summary of the config, computed ahead of time for the same reason
+static CONFIG_DIGEST: Mutex<String> = Mutex::new(String::new());
+/// Descriptions of the renderers that have been initialized
+static RENDERERS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// The report that was written by this process, if any.
+/// A failure often cascades into several more; only the first
+/// is recorded.
+static REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub fn update_config(config: &ConfigHandle) {
+    ENABLED.store(config.enable_crash_reports, Ordering::Relaxed);
+    if config.enable_crash_reports {
+        *CONFIG_DIGEST.lock().unwrap() = config_digest(config);
+    }
+}
+
+/// Records the renderer of a newly created window
+pub fn note_renderer(info: &str) {
+    let mut renderers = RENDERERS.lock().unwrap();
+    if !renderers.iter().any(|r| r == info) {
+        renderers.push(info.to_string());
+    }
+}
+
+/// Describes a config value without revealing its content.
+/// Strings in particular may hold paths, host names or credentials.
+fn scrub_value(value: &Value) -> String {
+    match value {
+        Value::Null => "nil".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::U64(n) => n.to_string(),
+        Value::I64(n) => n.to_string(),
+        Value::F64(f) => f.to_string(),
+        Value::String(s) => format!("<string of {} chars>", s.chars().count()),
+        Value::Array(a) => format!("<{} items>", a.len()),
+        Value::Object(o) => format!("<{} fields>", o.len()),
+    }
+}
+
+/// Produces a hash of the whole config, so that reports from the same
+/// config can be recognized, followed by the scrubbed values of the
+/// options that differ from the defaults.
+fn config_digest(config: &ConfigHandle) -> String {
+    let current = config.to_dynamic();
+    let defaults = config::Config::default_config().to_dynamic();
+
+    let mut hasher = DefaultHasher::new();
+    current.hash(&mut hasher);
+    let mut digest = format!("digest: {:016x}\n", hasher.finish());
+
+    if let (Value::Object(current), Value::Object(defaults)) = (&current, &defaults) {
+        for (key, value) in current.iter() {
+            if defaults.get(key) != Some(value) {
+                if let Value::String(key) = key {
+                    writeln!(digest, "{key} = {}", scrub_value(value)).ok();
+                }
+            }
+        }
+    }
+
+    digest
+}
+
+fn write_report(kind: &str, details: &str) -> Option<PathBuf> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    // Avoid blocking if the failure happened while one of these
+    // locks was held
+    let mut report_path = REPORT.try_lock().ok()?;
+    if let Some(path) = report_path.as_ref() {
+        return Some(path.clone());
+    }
+
+    let now = chrono::Local::now();
+    let mut report = String::new();
+    writeln!(report, "wezterm crash report").ok();
+    writeln!(report).ok();
+    writeln!(report, "Kind:    {kind}").ok();
+    writeln!(report, "Time:    {}", now.to_rfc3339()).ok();
+    writeln!(report, "Version: {}", config::wezterm_version()).ok();
+    writeln!(report, "Target:  {}", config::wezterm_target_triple()).ok();
+    writeln!(
+        report,
+        "OS:      {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+    .ok();
+
+    writeln!(report, "\n## Details\n\n{details}").ok();
+
+    writeln!(report, "\n## Renderers\n").ok();
+    if let Ok(renderers) = RENDERERS.try_lock() {
+        for renderer in renderers.iter() {
+            writeln!(report, "{renderer}").ok();
+        }
+    }
+
+    writeln!(report, "\n## Configuration\n").ok();
+    if let Ok(digest) = CONFIG_DIGEST.try_lock() {
+        writeln!(report, "{}", *digest).ok();
+    }
+
+    writeln!(report, "\n## Recent log entries\n").ok();
+    for entry in env_bootstrap::ringlog::get_entries() {
+        writeln!(
+            report,
+            "{} {:6} {} > {}",
+            entry.then.format("%H:%M:%S%.3f"),
+            entry.level,
+            entry.target,
+            entry.msg
+        )
+        .ok();
+    }
+
+    let home = HOME_DIR.to_string_lossy();
+    if home.len() > 1 {
+        report = report.replace(&*home, "~");
+    }
+
+    let dir = config::DATA_DIR.join("crash-reports");
+    config::create_user_owned_dirs(&dir).ok()?;
+    let path = dir.join(format!(
+        "wezterm-crash-{}-{}.txt",
+        now.format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    ));
+    if let Err(err) = std::fs::write(&path, report) {
+        log::error!("Failed to write crash report {}: {err:#}", path.display());
+        return None;
+    }
+    log::error!("Wrote crash report to {}", path.display());
+
+    report_path.replace(path.clone());
+    Some(path)
+}
+
+/// Writes a report for a panic.
+/// Returns the path to the report, if one was written.
+pub fn report_panic(message: &str, location: Option<String>) -> Option<PathBuf> {
+    let thread = std::thread::current();
+    let details = format!(
+        "panic in thread {} at {}: {message}\n\n{}",
+        thread.name().unwrap_or("<unnamed>"),
+        location.as_deref().unwrap_or("<unknown location>"),
+        std::backtrace::Backtrace::force_capture()
+    );
+    write_report("panic", &details)
+}
+
+/// Writes a report for a GPU error, such as the device or context being lost.
+/// Returns the path to the report, if one was written.
+pub fn report_gpu_failure(message: &str) -> Option<PathBuf> {
+    let kind = if message.to_lowercase().contains("lost") {
+        "GPU device lost"
+    } else {
+        "GPU error"
+    };
+    write_report(kind, message)
+}
+
+/// Shows a notification that, when clicked, opens the report
+pub fn show_report_notification(title: &str, path: &Path) {
+    let url = match url::Url::from_file_path(path) {
+        Ok(url) => url.to_string(),
+        Err(_) => return,
+    };
+    wezterm_toast_notification::persistent_toast_notification_with_click_to_open_url(
+        title,
+        &format!(
+            "A crash report was saved to {}. Click to open it.",
+            path.display()
+        ),
+        &url,
+    );
+}
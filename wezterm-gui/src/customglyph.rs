@@ -195,25 +195,33 @@ pub enum PolyCommand {
 }
 
 impl PolyCommand {
-    fn to_skia(&self, width: usize, height: usize, underline_height: f32, pb: &mut PathBuilder) {
+    /// Adds the command to `pb`.
+    /// When `snap` is set, the points of the path are moved to the
+    /// nearest pixel boundary plus that offset, which is `0.5` for
+    /// strokes that are an odd number of pixels wide so that they
+    /// cover whole pixels.
+    fn to_skia(
+        &self,
+        width: usize,
+        height: usize,
+        underline_height: f32,
+        snap: Option<f32>,
+        pb: &mut PathBuilder,
+    ) {
+        let px = |coord: &BlockCoord, max: usize| {
+            let v = coord.to_pixel(max, underline_height);
+            match snap {
+                Some(offset) => (v - offset).round() + offset,
+                None => v,
+            }
+        };
         match self {
-            Self::MoveTo(x, y) => pb.move_to(
-                x.to_pixel(width, underline_height),
-                y.to_pixel(height, underline_height),
-            ),
-            Self::LineTo(x, y) => pb.line_to(
-                x.to_pixel(width, underline_height),
-                y.to_pixel(height, underline_height),
-            ),
+            Self::MoveTo(x, y) => pb.move_to(px(x, width), px(y, height)),
+            Self::LineTo(x, y) => pb.line_to(px(x, width), px(y, height)),
             Self::QuadTo {
                 control: (x1, y1),
                 to: (x, y),
-            } => pb.quad_to(
-                x1.to_pixel(width, underline_height),
-                y1.to_pixel(height, underline_height),
-                x.to_pixel(width, underline_height),
-                y.to_pixel(height, underline_height),
-            ),
+            } => pb.quad_to(px(x1, width), px(y1, height), px(x, width), px(y, height)),
             Self::Oval {
                 center: (x, y),
                 radiuses: (w, h),
@@ -284,6 +292,18 @@ impl BlockKey {
     }
 
     pub fn from_char(c: char) -> Option<Self> {
+        let config = config::configuration();
+        if config
+            .custom_block_glyph_exclusions
+            .iter()
+            .any(|range| range.contains(c))
+        {
+            return None;
+        }
+        Self::from_char_unfiltered(c)
+    }
+
+    fn from_char_unfiltered(c: char) -> Option<Self> {
         let c = c as u32;
         Some(match c {
             // [─] BOX DRAWINGS LIGHT HORIZONTAL
@@ -3485,140 +3505,153 @@ impl BlockKey {
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [🭼] Left and lower one eighth block
-            0x1fb7c => Self::Edges(&[Edge::Left(1), Edge::Lower(1)]),
-            // [🭽] Left and upper one eighth block
-            0x1fb7d => Self::Edges(&[Edge::Left(1), Edge::Upper(1)]),
-            // [🭾] Right and upper one eighth block
-            0x1fb7e => Self::Edges(&[Edge::Right(1), Edge::Upper(1)]),
-            // [🭿] Right and lower one eighth block
-            0x1fb7f => Self::Edges(&[Edge::Right(1), Edge::Lower(1)]),
-            // [🮂] Upper One Quarter Block
-            0x1fb82 => Self::Edges(&[Edge::Upper(2)]),
-            // [🮃] Upper three eighths block
-            0x1fb83 => Self::Edges(&[Edge::Upper(3)]),
-            // [🮄] Upper five eighths block
-            0x1fb84 => Self::Edges(&[Edge::Upper(5)]),
-            // [🮅] Upper three quarters block
-            0x1fb85 => Self::Edges(&[Edge::Upper(6)]),
-            // [🮆] Upper seven eighths block
-            0x1fb86 => Self::Edges(&[Edge::Upper(7)]),
-            // [🮇] Right One Quarter Block
-            0x1fb87 => Self::Edges(&[Edge::Right(2)]),
-            // [🮈] Right three eighths block
-            0x1fb88 => Self::Edges(&[Edge::Right(3)]),
-            // [🮉] Right five eighths block
-            0x1fb89 => Self::Edges(&[Edge::Right(5)]),
-            // [🮊] Right three quarters block
-            0x1fb8a => Self::Edges(&[Edge::Right(6)]),
-            // [🮋] Right seven eighths block
-            0x1fb8b => Self::Edges(&[Edge::Right(7)]),
-            // [] Powerline filled right arrow
-            0xe0b0 => Self::Poly(&[Poly {
+            // [🭁] LOWER RIGHT BLOCK DIAGONAL UPPER MIDDLE LEFT TO UPPER CENTRE
+            0x1fb41 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
                     PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
-                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline right arrow
-            0xe0b1 => Self::Poly(&[Poly {
+            // [🭂] LOWER RIGHT BLOCK DIAGONAL UPPER MIDDLE LEFT TO UPPER RIGHT
+            0x1fb42 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
-                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
                     PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            // [] Powerline filled left arrow
-            0xe0b2 => Self::Poly(&[Poly {
+            // [🭃] LOWER RIGHT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER CENTRE
+            0x1fb43 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
-                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline left arrow
-            0xe0b3 => Self::Poly(&[Poly {
+            // [🭄] LOWER RIGHT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER RIGHT
+            0x1fb44 => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
-                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-
-            // [] Powerline filled left semicircle
-            0xe0b4 => Self::Poly(&[Poly {
+            // [🭅] LOWER RIGHT BLOCK DIAGONAL LOWER LEFT TO UPPER CENTRE
+            0x1fb45 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::One, BlockCoord::Zero),
-                        to: (BlockCoord::One, BlockCoord::Frac(1, 2)),
-                    },
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::One, BlockCoord::One),
-                        to: (BlockCoord::Zero, BlockCoord::One),
-                    },
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline left semicircle
-            0xe0b5 => Self::Poly(&[Poly {
+            // [🭆] LOWER RIGHT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER MIDDLE RIGHT
+            0x1fb46 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Frac(-1, 4), BlockCoord::Frac(-1, 3)),
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::Frac(7, 4), BlockCoord::Frac(1, 2)),
-                        to: (BlockCoord::Frac(-1, 4), BlockCoord::Frac(4, 3)),
-                    },
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            // [] Powerline filled right semicircle
-            0xe0b6 => Self::Poly(&[Poly {
+            // [🭇] LOWER RIGHT BLOCK DIAGONAL LOWER CENTRE TO LOWER MIDDLE RIGHT
+            0x1fb47 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭈] LOWER RIGHT BLOCK DIAGONAL LOWER LEFT TO LOWER MIDDLE RIGHT
+            0x1fb48 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭉] LOWER RIGHT BLOCK DIAGONAL LOWER CENTRE TO UPPER MIDDLE RIGHT
+            0x1fb49 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭊] LOWER RIGHT BLOCK DIAGONAL LOWER LEFT TO UPPER MIDDLE RIGHT
+            0x1fb4a => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭋] LOWER RIGHT BLOCK DIAGONAL LOWER CENTRE TO UPPER RIGHT
+            0x1fb4b => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::Zero, BlockCoord::Zero),
-                        to: (BlockCoord::Zero, BlockCoord::Frac(1, 2)),
-                    },
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::Zero, BlockCoord::One),
-                        to: (BlockCoord::One, BlockCoord::One),
-                    },
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline right semicircle
-            0xe0b7 => Self::Poly(&[Poly {
+            // [🭌] LOWER LEFT BLOCK DIAGONAL UPPER CENTRE TO UPPER MIDDLE RIGHT
+            0x1fb4c => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Frac(5, 4), BlockCoord::Frac(-1, 3)),
-                    PolyCommand::QuadTo {
-                        control: (BlockCoord::Frac(-3, 4), BlockCoord::Frac(1, 2)),
-                        to: (BlockCoord::Frac(5, 4), BlockCoord::Frac(4, 3)),
-                    },
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-
-            // [] Powerline filled bottom left half triangle
-            0xe0b8 => Self::Poly(&[Poly {
+            // [🭍] LOWER LEFT BLOCK DIAGONAL UPPER LEFT TO UPPER MIDDLE RIGHT
+            0x1fb4d => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
                     PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
                     PolyCommand::Close,
@@ -3626,111 +3659,946 @@ impl BlockKey {
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline bottom left half triangle
-            0xe0b9 => Self::Poly(&[Poly {
+            // [🭎] LOWER LEFT BLOCK DIAGONAL UPPER CENTRE TO LOWER MIDDLE RIGHT
+            0x1fb4e => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            // [] Powerline filled bottom right half triangle
-            0xe0ba => Self::Poly(&[Poly {
+            // [🭏] LOWER LEFT BLOCK DIAGONAL UPPER LEFT TO LOWER MIDDLE RIGHT
+            0x1fb4f => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
-                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline bottom right half triangle
-            0xe0bb => Self::Poly(&[Poly {
+            // [🭐] LOWER LEFT BLOCK DIAGONAL UPPER CENTRE TO LOWER RIGHT
+            0x1fb50 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭑] LOWER LEFT BLOCK DIAGONAL UPPER MIDDLE LEFT TO LOWER MIDDLE RIGHT
+            0x1fb51 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭒] UPPER RIGHT BLOCK DIAGONAL LOWER MIDDLE LEFT TO LOWER CENTRE
+            0x1fb52 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            // [] Powerline filled top left half triangle
-            0xe0bc => Self::Poly(&[Poly {
+            // [🭓] UPPER RIGHT BLOCK DIAGONAL LOWER MIDDLE LEFT TO LOWER RIGHT
+            0x1fb53 => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
-                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline top left half triangle
-            0xe0bd => Self::Poly(&[Poly {
+            // [🭔] UPPER RIGHT BLOCK DIAGONAL UPPER MIDDLE LEFT TO LOWER CENTRE
+            0x1fb54 => Self::Poly(&[Poly {
                 path: &[
-                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            // [] Powerline filled top right half triangle
-            0xe0be => Self::Poly(&[Poly {
+            // [🭕] UPPER RIGHT BLOCK DIAGONAL UPPER MIDDLE LEFT TO LOWER RIGHT
+            0x1fb55 => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
                     PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
                 style: PolyStyle::Fill,
             }]),
-            // [] Powerline outline top right half triangle
-            0xe0bf => Self::Poly(&[Poly {
+            // [🭖] UPPER RIGHT BLOCK DIAGONAL UPPER LEFT TO LOWER CENTRE
+            0x1fb56 => Self::Poly(&[Poly {
                 path: &[
                     PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
                     PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::Close,
                 ],
                 intensity: BlockAlpha::Full,
-                style: PolyStyle::Outline,
+                style: PolyStyle::Fill,
             }]),
-            _ => return None,
-        })
-    }
-
-    pub fn from_cell_iter(cell: termwiz::surface::line::CellRef) -> Option<Self> {
-        let mut chars = cell.str().chars();
-        let first_char = chars.next()?;
-        if chars.next().is_some() {
-            None
-        } else {
-            Self::from_char(first_char)
-        }
-    }
-
-    pub fn from_cell(cell: &termwiz::cell::Cell) -> Option<Self> {
-        let mut chars = cell.str().chars();
-        let first_char = chars.next()?;
-        if chars.next().is_some() {
-            None
-        } else {
-            Self::from_char(first_char)
-        }
-    }
-}
-
-impl GlyphCache {
-    fn draw_polys(
-        &mut self,
-        metrics: &RenderMetrics,
-        polys: &[Poly],
-        buffer: &mut Image,
-        aa: PolyAA,
-    ) {
-        let (width, height) = buffer.image_dimensions();
-        let mut pixmap =
-            PixmapMut::from_bytes(buffer.pixel_data_slice_mut(), width as u32, height as u32)
+            // [🭗] UPPER LEFT BLOCK DIAGONAL UPPER MIDDLE LEFT TO UPPER CENTRE
+            0x1fb57 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭘] UPPER LEFT BLOCK DIAGONAL UPPER MIDDLE LEFT TO UPPER RIGHT
+            0x1fb58 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭙] UPPER LEFT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER CENTRE
+            0x1fb59 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭚] UPPER LEFT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER RIGHT
+            0x1fb5a => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭛] UPPER LEFT BLOCK DIAGONAL LOWER LEFT TO UPPER CENTRE
+            0x1fb5b => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭜] UPPER LEFT BLOCK DIAGONAL LOWER MIDDLE LEFT TO UPPER MIDDLE RIGHT
+            0x1fb5c => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭝] UPPER LEFT BLOCK DIAGONAL LOWER CENTRE TO LOWER MIDDLE RIGHT
+            0x1fb5d => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭞] UPPER LEFT BLOCK DIAGONAL LOWER LEFT TO LOWER MIDDLE RIGHT
+            0x1fb5e => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭟] UPPER LEFT BLOCK DIAGONAL LOWER CENTRE TO UPPER MIDDLE RIGHT
+            0x1fb5f => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭠] UPPER LEFT BLOCK DIAGONAL LOWER LEFT TO UPPER MIDDLE RIGHT
+            0x1fb60 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭡] UPPER LEFT BLOCK DIAGONAL LOWER CENTRE TO UPPER RIGHT
+            0x1fb61 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭢] UPPER RIGHT BLOCK DIAGONAL UPPER CENTRE TO UPPER MIDDLE RIGHT
+            0x1fb62 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭣] UPPER RIGHT BLOCK DIAGONAL UPPER LEFT TO UPPER MIDDLE RIGHT
+            0x1fb63 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭤] UPPER RIGHT BLOCK DIAGONAL UPPER CENTRE TO LOWER MIDDLE RIGHT
+            0x1fb64 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭥] UPPER RIGHT BLOCK DIAGONAL UPPER LEFT TO LOWER MIDDLE RIGHT
+            0x1fb65 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭦] UPPER RIGHT BLOCK DIAGONAL UPPER CENTRE TO LOWER RIGHT
+            0x1fb66 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭧] UPPER RIGHT BLOCK DIAGONAL UPPER MIDDLE LEFT TO LOWER MIDDLE RIGHT
+            0x1fb67 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(2, 3)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 3)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭨] UPPER AND RIGHT AND LOWER TRIANGULAR THREE QUARTERS BLOCK
+            0x1fb68 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭩] LEFT AND LOWER AND RIGHT TRIANGULAR THREE QUARTERS BLOCK
+            0x1fb69 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭪] UPPER AND LEFT AND LOWER TRIANGULAR THREE QUARTERS BLOCK
+            0x1fb6a => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭫] LEFT AND UPPER AND RIGHT TRIANGULAR THREE QUARTERS BLOCK
+            0x1fb6b => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭬] LEFT TRIANGULAR ONE QUARTER BLOCK
+            0x1fb6c => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭭] UPPER TRIANGULAR ONE QUARTER BLOCK
+            0x1fb6d => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭮] RIGHT TRIANGULAR ONE QUARTER BLOCK
+            0x1fb6e => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭯] LOWER TRIANGULAR ONE QUARTER BLOCK
+            0x1fb6f => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭰] VERTICAL ONE EIGHTH BLOCK-2
+            0x1fb70 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 4), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 4), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 8), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭱] VERTICAL ONE EIGHTH BLOCK-3
+            0x1fb71 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 4), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 8), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 4), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭲] VERTICAL ONE EIGHTH BLOCK-4
+            0x1fb72 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(3, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 8), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭳] VERTICAL ONE EIGHTH BLOCK-5
+            0x1fb73 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(5, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(5, 8), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭴] VERTICAL ONE EIGHTH BLOCK-6
+            0x1fb74 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(5, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 4), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 4), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(5, 8), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭵] VERTICAL ONE EIGHTH BLOCK-7
+            0x1fb75 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(3, 4), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(7, 8), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(7, 8), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(3, 4), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭶] HORIZONTAL ONE EIGHTH BLOCK-2
+            0x1fb76 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 4)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 4)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭷] HORIZONTAL ONE EIGHTH BLOCK-3
+            0x1fb77 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 4)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 4)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(3, 8)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(3, 8)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭸] HORIZONTAL ONE EIGHTH BLOCK-4
+            0x1fb78 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(3, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(3, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭹] HORIZONTAL ONE EIGHTH BLOCK-5
+            0x1fb79 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(5, 8)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(5, 8)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭺] HORIZONTAL ONE EIGHTH BLOCK-6
+            0x1fb7a => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(5, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(5, 8)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(3, 4)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(3, 4)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭻] HORIZONTAL ONE EIGHTH BLOCK-7
+            0x1fb7b => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(3, 4)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(3, 4)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(7, 8)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(7, 8)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [🭼] Left and lower one eighth block
+            0x1fb7c => Self::Edges(&[Edge::Left(1), Edge::Lower(1)]),
+            // [🭽] Left and upper one eighth block
+            0x1fb7d => Self::Edges(&[Edge::Left(1), Edge::Upper(1)]),
+            // [🭾] Right and upper one eighth block
+            0x1fb7e => Self::Edges(&[Edge::Right(1), Edge::Upper(1)]),
+            // [🭿] Right and lower one eighth block
+            0x1fb7f => Self::Edges(&[Edge::Right(1), Edge::Lower(1)]),
+            // [🮂] Upper One Quarter Block
+            0x1fb82 => Self::Edges(&[Edge::Upper(2)]),
+            // [🮃] Upper three eighths block
+            0x1fb83 => Self::Edges(&[Edge::Upper(3)]),
+            // [🮄] Upper five eighths block
+            0x1fb84 => Self::Edges(&[Edge::Upper(5)]),
+            // [🮅] Upper three quarters block
+            0x1fb85 => Self::Edges(&[Edge::Upper(6)]),
+            // [🮆] Upper seven eighths block
+            0x1fb86 => Self::Edges(&[Edge::Upper(7)]),
+            // [🮇] Right One Quarter Block
+            0x1fb87 => Self::Edges(&[Edge::Right(2)]),
+            // [🮈] Right three eighths block
+            0x1fb88 => Self::Edges(&[Edge::Right(3)]),
+            // [🮉] Right five eighths block
+            0x1fb89 => Self::Edges(&[Edge::Right(5)]),
+            // [🮊] Right three quarters block
+            0x1fb8a => Self::Edges(&[Edge::Right(6)]),
+            // [🮋] Right seven eighths block
+            0x1fb8b => Self::Edges(&[Edge::Right(7)]),
+            // [🮌] LEFT HALF MEDIUM SHADE
+            0x1fb8c => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Medium,
+                style: PolyStyle::Fill,
+            }]),
+            // [🮍] RIGHT HALF MEDIUM SHADE
+            0x1fb8d => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Medium,
+                style: PolyStyle::Fill,
+            }]),
+            // [🮎] UPPER HALF MEDIUM SHADE
+            0x1fb8e => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Medium,
+                style: PolyStyle::Fill,
+            }]),
+            // [🮏] LOWER HALF MEDIUM SHADE
+            0x1fb8f => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Medium,
+                style: PolyStyle::Fill,
+            }]),
+            // [🮐] INVERSE MEDIUM SHADE
+            0x1fb90 => Self::Full(BlockAlpha::Medium),
+            // [🮑] UPPER HALF BLOCK AND LOWER HALF INVERSE MEDIUM SHADE
+            0x1fb91 => Self::Poly(&[
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Full,
+                    style: PolyStyle::Fill,
+                },
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                        PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Medium,
+                    style: PolyStyle::Fill,
+                },
+            ]),
+            // [🮒] UPPER HALF INVERSE MEDIUM SHADE AND LOWER HALF BLOCK
+            0x1fb92 => Self::Poly(&[
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Medium,
+                    style: PolyStyle::Fill,
+                },
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                        PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Full,
+                    style: PolyStyle::Fill,
+                },
+            ]),
+            // [🮔] LEFT HALF INVERSE MEDIUM SHADE AND RIGHT HALF BLOCK
+            0x1fb94 => Self::Poly(&[
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                        PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Medium,
+                    style: PolyStyle::Fill,
+                },
+                Poly {
+                    path: &[
+                        PolyCommand::MoveTo(BlockCoord::Frac(1, 2), BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                        PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                        PolyCommand::LineTo(BlockCoord::Frac(1, 2), BlockCoord::One),
+                        PolyCommand::Close,
+                    ],
+                    intensity: BlockAlpha::Full,
+                    style: PolyStyle::Fill,
+                },
+            ]),
+            // [] Powerline filled right arrow
+            0xe0b0 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline right arrow
+            0xe0b1 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            // [] Powerline filled left arrow
+            0xe0b2 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline left arrow
+            0xe0b3 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+
+            // [] Powerline filled left semicircle
+            0xe0b4 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::One, BlockCoord::Zero),
+                        to: (BlockCoord::One, BlockCoord::Frac(1, 2)),
+                    },
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::One, BlockCoord::One),
+                        to: (BlockCoord::Zero, BlockCoord::One),
+                    },
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline left semicircle
+            0xe0b5 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(-1, 4), BlockCoord::Frac(-1, 3)),
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::Frac(7, 4), BlockCoord::Frac(1, 2)),
+                        to: (BlockCoord::Frac(-1, 4), BlockCoord::Frac(4, 3)),
+                    },
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            // [] Powerline filled right semicircle
+            0xe0b6 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::Zero, BlockCoord::Zero),
+                        to: (BlockCoord::Zero, BlockCoord::Frac(1, 2)),
+                    },
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::Zero, BlockCoord::One),
+                        to: (BlockCoord::One, BlockCoord::One),
+                    },
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline right semicircle
+            0xe0b7 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Frac(5, 4), BlockCoord::Frac(-1, 3)),
+                    PolyCommand::QuadTo {
+                        control: (BlockCoord::Frac(-3, 4), BlockCoord::Frac(1, 2)),
+                        to: (BlockCoord::Frac(5, 4), BlockCoord::Frac(4, 3)),
+                    },
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+
+            // [] Powerline filled bottom left half triangle
+            0xe0b8 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline bottom left half triangle
+            0xe0b9 => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            // [] Powerline filled bottom right half triangle
+            0xe0ba => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline bottom right half triangle
+            0xe0bb => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            // [] Powerline filled top left half triangle
+            0xe0bc => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline top left half triangle
+            0xe0bd => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::One),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            // [] Powerline filled top right half triangle
+            0xe0be => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                    PolyCommand::Close,
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Fill,
+            }]),
+            // [] Powerline outline top right half triangle
+            0xe0bf => Self::Poly(&[Poly {
+                path: &[
+                    PolyCommand::MoveTo(BlockCoord::Zero, BlockCoord::Zero),
+                    PolyCommand::LineTo(BlockCoord::One, BlockCoord::One),
+                ],
+                intensity: BlockAlpha::Full,
+                style: PolyStyle::Outline,
+            }]),
+            _ => return None,
+        })
+    }
+
+    pub fn from_cell_iter(cell: termwiz::surface::line::CellRef) -> Option<Self> {
+        let mut chars = cell.str().chars();
+        let first_char = chars.next()?;
+        if chars.next().is_some() {
+            None
+        } else {
+            Self::from_char(first_char)
+        }
+    }
+
+    pub fn from_cell(cell: &termwiz::cell::Cell) -> Option<Self> {
+        let mut chars = cell.str().chars();
+        let first_char = chars.next()?;
+        if chars.next().is_some() {
+            None
+        } else {
+            Self::from_char(first_char)
+        }
+    }
+}
+
+impl GlyphCache {
+    fn draw_polys(
+        &mut self,
+        metrics: &RenderMetrics,
+        polys: &[Poly],
+        buffer: &mut Image,
+        aa: PolyAA,
+    ) {
+        self.draw_polys_with_weight(
+            metrics,
+            polys,
+            buffer,
+            aa,
+            metrics.underline_height as f32,
+            false,
+        )
+    }
+
+    /// Like `draw_polys`, but strokes are `line_width` pixels wide
+    /// rather than the underline thickness, and the points of the
+    /// paths are optionally snapped to the pixel grid
+    fn draw_polys_with_weight(
+        &mut self,
+        metrics: &RenderMetrics,
+        polys: &[Poly],
+        buffer: &mut Image,
+        aa: PolyAA,
+        line_width: f32,
+        snap: bool,
+    ) {
+        let (width, height) = buffer.image_dimensions();
+        let mut pixmap =
+            PixmapMut::from_bytes(buffer.pixel_data_slice_mut(), width as u32, height as u32)
                 .expect("make pixmap from existing bitmap");
 
         for Poly {
@@ -3749,12 +4617,19 @@ impl GlyphCache {
                 PolyAA::MoarPixels => false,
             };
             paint.force_hq_pipeline = true;
+            let snap = if !snap {
+                None
+            } else if *style == PolyStyle::Outline && line_width as usize % 2 == 1 {
+                Some(0.5)
+            } else {
+                Some(0.)
+            };
             let mut pb = PathBuilder::new();
             for item in path.iter() {
-                item.to_skia(width, height, metrics.underline_height as f32, &mut pb);
+                item.to_skia(width, height, line_width, snap, &mut pb);
             }
             let path = pb.finish().expect("poly path to be valid");
-            style.apply(metrics.underline_height as f32, &paint, &path, &mut pixmap);
+            style.apply(line_width, &paint, &path, &mut pixmap);
         }
     }
 
@@ -4028,15 +4903,23 @@ impl GlyphCache {
                 }
             }
             BlockKey::Poly(polys) | BlockKey::PolyWithCustomMetrics { polys, .. } => {
-                self.draw_polys(
+                let config = config::configuration();
+                let mut line_width =
+                    metrics.underline_height as f32 * config.custom_block_glyph_line_weight as f32;
+                if config.custom_block_glyph_pixel_snapping {
+                    line_width = line_width.round().max(1.);
+                }
+                self.draw_polys_with_weight(
                     &metrics,
                     polys,
                     &mut buffer,
-                    if config::configuration().anti_alias_custom_block_glyphs {
+                    if config.anti_alias_custom_block_glyphs {
                         PolyAA::AntiAlias
                     } else {
                         PolyAA::MoarPixels
                     },
+                    line_width,
+                    config.custom_block_glyph_pixel_snapping,
                 );
             }
         }
@@ -0,0 +1,498 @@
+//! Implements `wezterm doctor`, which examines the environment for
+//! common problems and produces a report that can be shared when
+//! asking for help.
+use config::{ConfigHandle, FrontEndSelection, GpuInfo, HOME_DIR};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::Mutex;
+use wezterm_gui_subcommands::{DoctorCommand, DoctorFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Info => "INFO",
+            Self::Warning => "WARNING",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub area: &'static str,
+    pub status: Status,
+    pub summary: String,
+    pub details: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub version: String,
+    pub target: String,
+    pub os: String,
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Records the outcome of a check.  The home directory is replaced
+    /// with `~` so that the report can be shared without revealing
+    /// the user name.
+    fn push(&mut self, area: &'static str, status: Status, summary: String, details: Vec<String>) {
+        self.checks.push(Check {
+            area,
+            status,
+            summary: scrub(&summary),
+            details: details.iter().map(|d| scrub(d)).collect(),
+        });
+    }
+
+    fn count(&self, status: Status) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    fn print_text(&self) {
+        println!("wezterm doctor");
+        println!();
+        println!("Version: {}", self.version);
+        println!("Target:  {}", self.target);
+        println!("OS:      {}", self.os);
+        println!();
+
+        let area_width = self.checks.iter().map(|c| c.area.len()).max().unwrap_or(0);
+        for check in &self.checks {
+            println!(
+                "{:7}   {:area_width$}   {}",
+                check.status.label(),
+                check.area,
+                check.summary
+            );
+            for detail in &check.details {
+                println!("{:7}   {:area_width$}     {}", "", "", detail);
+            }
+        }
+
+        println!();
+        println!(
+            "{} error(s), {} warning(s)",
+            self.count(Status::Error),
+            self.count(Status::Warning)
+        );
+    }
+}
+
+fn scrub(s: &str) -> String {
+    let home = HOME_DIR.to_string_lossy();
+    if home.len() > 1 {
+        s.replace(&*home, "~")
+    } else {
+        s.to_string()
+    }
+}
+
+fn describe_os() -> String {
+    let mut os = format!("{} {}", std::env::consts::OS, std::env::consts::ARCH);
+    if let Ok(session) = std::env::var("XDG_SESSION_TYPE") {
+        os.push_str(&format!(" ({session})"));
+    }
+    os
+}
+
+/// Extracts the file names and line numbers from a lua error message
+fn error_locations(message: &str) -> Vec<(String, usize)> {
+    lazy_static::lazy_static! {
+        static ref CHUNK: Regex = Regex::new(r#"\[string "([^"]+)"\]:(\d+):"#).unwrap();
+        static ref FILE: Regex = Regex::new(r#"(?m)(?:^|\s)([^\s"\[\]]+\.lua):(\d+):"#).unwrap();
+    }
+
+    let mut locations = vec![];
+    for re in [&*CHUNK, &*FILE] {
+        for caps in re.captures_iter(message) {
+            if let Ok(line) = caps[2].parse::<usize>() {
+                let location = (caps[1].to_string(), line);
+                if !locations.contains(&location) {
+                    locations.push(location);
+                }
+            }
+        }
+    }
+    locations
+}
+
+/// Returns the lines surrounding `line` in the file, with the
+/// line itself marked
+fn source_excerpt(path: &str, line: usize) -> Vec<String> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(_) => return vec![],
+    };
+
+    let first = line.saturating_sub(2).max(1);
+    data.lines()
+        .enumerate()
+        .map(|(idx, text)| (idx + 1, text))
+        .skip(first - 1)
+        .take(line + 3 - first)
+        .map(|(n, text)| {
+            let marker = if n == line { ">" } else { " " };
+            format!("{marker} {n:4} | {text}")
+        })
+        .collect()
+}
+
+fn check_config(report: &mut Report) {
+    let loaded = config::Config::load();
+    let file_name = loaded
+        .file_name
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "the default configuration".to_string());
+
+    match &loaded.config {
+        Ok(_) if loaded.file_name.is_none() => {
+            report.push(
+                "config",
+                Status::Info,
+                "No config file was found; using the default configuration".to_string(),
+                vec![],
+            );
+        }
+        Ok(_) => {
+            report.push("config", Status::Ok, format!("Loaded {file_name}"), vec![]);
+        }
+        Err(err) => {
+            let message = format!("{err:#}");
+            let mut details: Vec<String> = message.lines().map(|l| l.to_string()).collect();
+            for (path, line) in error_locations(&message) {
+                let excerpt = source_excerpt(&path, line);
+                if !excerpt.is_empty() {
+                    details.push(String::new());
+                    details.push(format!("{path}:{line}"));
+                    details.extend(excerpt);
+                }
+            }
+            report.push(
+                "config",
+                Status::Error,
+                format!("Failed to load {file_name}"),
+                details,
+            );
+        }
+    }
+
+    for warning in &loaded.warnings {
+        let mut lines = warning.lines().map(|l| l.to_string());
+        let summary = lines.next().unwrap_or_default();
+        report.push("config", Status::Warning, summary, lines.collect());
+    }
+}
+
+fn check_fonts(config: &ConfigHandle, report: &mut Report) {
+    static ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    // Font problems are reported via the error callback rather than
+    // being returned from the resolution functions
+    config::assign_error_callback(|err| ERRORS.lock().unwrap().push(err.to_string()));
+
+    let fonts = match wezterm_font::FontConfiguration::new(
+        Some(config.clone()),
+        config.dpi.unwrap_or_else(|| ::window::default_dpi()) as usize,
+    ) {
+        Ok(fonts) => fonts,
+        Err(err) => {
+            report.push(
+                "fonts",
+                Status::Error,
+                "Unable to initialize the font system".to_string(),
+                vec![format!("{err:#}")],
+            );
+            return;
+        }
+    };
+
+    let mut primary = vec![];
+    let mut errors = vec![];
+
+    match fonts.resolve_font(&config.font) {
+        Ok(font) => {
+            primary = font.clone_handles().iter().map(|p| p.lua_name()).collect();
+        }
+        Err(err) => errors.push(format!("{err:#}")),
+    }
+    for rule in &config.font_rules {
+        if let Err(err) = fonts.resolve_font(&rule.font) {
+            errors.push(format!("{err:#}"));
+        }
+    }
+
+    errors.append(&mut ERRORS.lock().unwrap());
+    errors.dedup();
+    config::assign_error_callback(|err| eprintln!("{}", err));
+
+    if errors.is_empty() {
+        report.push(
+            "fonts",
+            Status::Ok,
+            format!(
+                "Resolved the configured fonts ({} font_rules)",
+                config.font_rules.len()
+            ),
+            primary,
+        );
+    } else {
+        report.push(
+            "fonts",
+            Status::Error,
+            format!("{} problem(s) loading the configured fonts", errors.len()),
+            errors,
+        );
+    }
+}
+
+fn check_gpu(config: &ConfigHandle, report: &mut Report) {
+    let backends = wgpu::Backends::all();
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    let gpus: Vec<GpuInfo> = instance
+        .enumerate_adapters(backends)
+        .map(|adapter| crate::termwindow::webgpu::adapter_info_to_gpu_info(adapter.get_info()))
+        .collect();
+    let details: Vec<String> = gpus.iter().map(|gpu| gpu.to_string()).collect();
+
+    let summary = format!(
+        "front_end = {:?}; found {} GPU adapter(s)",
+        config.front_end,
+        gpus.len()
+    );
+    let status = match config.front_end {
+        FrontEndSelection::WebGpu if gpus.is_empty() => Status::Error,
+        _ if gpus.is_empty() => Status::Warning,
+        _ => Status::Ok,
+    };
+    report.push("gpu", status, summary, details);
+
+    if let Some(preferred) = &config.webgpu_preferred_adapter {
+        let found = gpus
+            .iter()
+            .any(|gpu| gpu.name == preferred.name && gpu.backend == preferred.backend);
+        if !found {
+            report.push(
+                "gpu",
+                Status::Warning,
+                "webgpu_preferred_adapter does not match any of the GPU adapters".to_string(),
+                vec![preferred.to_string()],
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+fn check_terminfo(config: &ConfigHandle, report: &mut Report) {
+    use std::path::PathBuf;
+
+    let term = &config.term;
+    let first = match term.chars().next() {
+        Some(c) => c,
+        None => {
+            report.push(
+                "terminfo",
+                Status::Error,
+                "The term config is empty".to_string(),
+                vec![],
+            );
+            return;
+        }
+    };
+
+    let mut dirs = vec![];
+    if let Some(dir) = std::env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(HOME_DIR.join(".terminfo"));
+    if let Some(list) = std::env::var_os("TERMINFO_DIRS") {
+        dirs.extend(std::env::split_paths(&list).filter(|p| !p.as_os_str().is_empty()));
+    }
+    for dir in [
+        "/etc/terminfo",
+        "/lib/terminfo",
+        "/usr/share/terminfo",
+        "/usr/lib/terminfo",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+
+    let found = dirs.iter().find_map(|dir| {
+        [first.to_string(), format!("{:x}", first as u32)]
+            .iter()
+            .map(|sub| dir.join(sub).join(term))
+            .find(|p| p.exists())
+    });
+
+    match found {
+        Some(path) => report.push(
+            "terminfo",
+            Status::Ok,
+            format!("Found terminfo for TERM={term}"),
+            vec![path.display().to_string()],
+        ),
+        None => report.push(
+            "terminfo",
+            if term == "xterm-256color" {
+                Status::Warning
+            } else {
+                Status::Error
+            },
+            format!("No terminfo entry was found for TERM={term}"),
+            vec![
+                "Programs running in the terminal may misbehave.".to_string(),
+                "See https://wezfurlong.org/wezterm/config/lua/config/term.html".to_string(),
+            ],
+        ),
+    }
+}
+
+fn check_ssh(report: &mut Report) {
+    let mut ssh_config = wezterm_ssh::Config::new();
+    ssh_config.add_default_config_files();
+    let files: Vec<String> = ssh_config
+        .loaded_config_files()
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+
+    if files.is_empty() {
+        report.push(
+            "ssh",
+            Status::Info,
+            "No ssh config files were found".to_string(),
+            vec![],
+        );
+        return;
+    }
+
+    let problems = ssh_config.parse_problems();
+    if problems.is_empty() {
+        report.push(
+            "ssh",
+            Status::Ok,
+            format!("Parsed {} ssh config file(s)", files.len()),
+            files,
+        );
+    } else {
+        report.push(
+            "ssh",
+            Status::Warning,
+            format!("{} problem(s) in the ssh config", problems.len()),
+            problems,
+        );
+    }
+}
+
+fn check_mux(config: &ConfigHandle, report: &mut Report) {
+    for domain in &config.unix_domains {
+        let name = &domain.name;
+        if domain.proxy_command.is_some() {
+            report.push(
+                "mux",
+                Status::Info,
+                format!("unix domain `{name}` uses a proxy_command and was not checked"),
+                vec![],
+            );
+            continue;
+        }
+
+        let path = domain.socket_path();
+        let details = vec![path.display().to_string()];
+        if !path.exists() {
+            report.push(
+                "mux",
+                Status::Info,
+                format!("unix domain `{name}`: no mux server is running"),
+                details,
+            );
+        } else if wezterm_client::discovery::is_sock_dead(&path) {
+            report.push(
+                "mux",
+                Status::Warning,
+                format!("unix domain `{name}`: the socket exists but is not accepting connections"),
+                vec![
+                    path.display().to_string(),
+                    "The mux server may have crashed; if it is not running, \
+                    the socket can be removed"
+                        .to_string(),
+                ],
+            );
+        } else {
+            report.push(
+                "mux",
+                Status::Ok,
+                format!("unix domain `{name}`: the mux server is accepting connections"),
+                details,
+            );
+        }
+    }
+
+    let guis = wezterm_client::discovery::discover_gui_socks();
+    report.push(
+        "mux",
+        Status::Info,
+        format!("{} running wezterm-gui instance(s)", guis.len()),
+        vec![],
+    );
+}
+
+pub fn run_doctor(config: ConfigHandle, cmd: &DoctorCommand) -> anyhow::Result<()> {
+    let mut report = Report {
+        version: config::wezterm_version().to_string(),
+        target: config::wezterm_target_triple().to_string(),
+        os: describe_os(),
+        checks: vec![],
+    };
+
+    check_config(&mut report);
+    check_fonts(&config, &mut report);
+    check_gpu(&config, &mut report);
+    #[cfg(unix)]
+    check_terminfo(&config, &mut report);
+    check_ssh(&mut report);
+    check_mux(&config, &mut report);
+
+    match cmd.format {
+        DoctorFormat::Text => report.print_text(),
+        DoctorFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lua_error_locations() {
+        assert_eq!(
+            error_locations(
+                "runtime error: [string \"/home/me/.wezterm.lua\"]:12: attempt to call a nil value\n\
+                 stack traceback:\n\
+                 \t/home/me/.config/wezterm/keys.lua:3: in main chunk"
+            ),
+            vec![
+                ("/home/me/.wezterm.lua".to_string(), 12),
+                ("/home/me/.config/wezterm/keys.lua".to_string(), 3),
+            ]
+        );
+        assert_eq!(error_locations("no location here"), vec![]);
+    }
+}
@@ -1,7 +1,107 @@
+use crate::scripting::guiwin::GuiWin;
 use anyhow::Context;
+use mux::pane::PaneId;
+use mux::Mux;
+use mux_lua::MuxPane;
+use std::collections::VecDeque;
+use std::ffi::OsString;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// How many downloads are remembered for the downloads overlay
+const MAX_RECENT_DOWNLOADS: usize = 100;
+
+lazy_static::lazy_static! {
+    static ref DOWNLOADS: Mutex<Downloads> = Mutex::new(Downloads::default());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadStatus {
+    Saving,
+    Saved(PathBuf),
+    /// The download-requested event chose not to save it
+    Cancelled,
+    /// Downloads are disabled, or it is too large
+    Rejected(String),
+    Failed(String),
+}
+
+/// A file that a program running in a pane asked to be downloaded
+#[derive(Debug, Clone)]
+pub struct Download {
+    id: usize,
+    pub pane_id: PaneId,
+    /// The name suggested by the remote system
+    pub name: Option<String>,
+    pub size: usize,
+    pub requested: SystemTime,
+    pub status: DownloadStatus,
+}
+
+/// The downloads of this session, oldest first
+#[derive(Default)]
+struct Downloads {
+    next_id: usize,
+    entries: VecDeque<Download>,
+}
+
+impl Downloads {
+    fn add(
+        &mut self,
+        pane_id: PaneId,
+        name: Option<String>,
+        size: usize,
+        status: DownloadStatus,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push_back(Download {
+            id,
+            pane_id,
+            name,
+            size,
+            requested: SystemTime::now(),
+            status,
+        });
+        while self.entries.len() > MAX_RECENT_DOWNLOADS {
+            self.entries.pop_front();
+        }
+        id
+    }
+
+    fn set_status(&mut self, id: usize, status: DownloadStatus) {
+        if let Some(download) = self.entries.iter_mut().find(|d| d.id == id) {
+            download.status = status;
+        }
+    }
+}
+
+/// Returns the downloads of this session, most recent first
+pub fn recent_downloads() -> Vec<Download> {
+    DOWNLOADS
+        .lock()
+        .unwrap()
+        .entries
+        .iter()
+        .rev()
+        .cloned()
+        .collect()
+}
+
+/// Where a download is saved, as decided by the
+/// `download-requested` event
+pub enum Destination {
+    /// The download folder, using the name suggested by the
+    /// remote system
+    Default,
+    /// A file name in the download folder, or an absolute path
+    Chosen(String),
+    Cancel,
+}
 
 /// Simple heuristics to try to avoid obvious trickery with
 /// the name provided by the remote system
@@ -11,7 +111,7 @@ fn neuter_name(name: &str) -> Option<&str> {
         None => name,
     };
 
-    if name == "." || name == ".." {
+    if name.is_empty() || name == "." || name == ".." {
         return None;
     }
 
@@ -21,23 +121,38 @@ fn neuter_name(name: &str) -> Option<&str> {
 
     Some(name)
 }
-/// Given a suggested name, make a few attempts to derive a local name
-/// in the user's download folder that doesn't conflict with any other
-/// files in that folder.
-/// Returns the selected name and the opened File on success.
-fn resolve_file_name(name: Option<&str>) -> anyhow::Result<(PathBuf, File)> {
-    let name = name
-        .and_then(neuter_name)
-        .unwrap_or("downloaded-via-wezterm");
 
-    let download_dir = dirs_next::download_dir()
-        .ok_or_else(|| anyhow::anyhow!("unable to locate download directory"))?;
+fn download_dir() -> anyhow::Result<PathBuf> {
+    dirs_next::download_dir().ok_or_else(|| anyhow::anyhow!("unable to locate download directory"))
+}
+
+/// Returns the path that is preferred for the download, before
+/// avoiding conflicts with existing files
+fn preferred_path(name: Option<&str>, destination: &Destination) -> anyhow::Result<PathBuf> {
+    match destination {
+        // The configuration is trusted to pick any location
+        Destination::Chosen(chosen) if Path::new(chosen).is_absolute() => Ok(chosen.into()),
+        Destination::Chosen(chosen) => Ok(download_dir()?.join(chosen)),
+        Destination::Default | Destination::Cancel => {
+            let name = name
+                .and_then(neuter_name)
+                .unwrap_or("downloaded-via-wezterm");
+            Ok(download_dir()?.join(name))
+        }
+    }
+}
 
+/// Given a preferred path, make a few attempts to derive a local name
+/// that doesn't conflict with any other files in the same folder.
+/// Returns the selected name and the opened File on success.
+fn resolve_file_name(preferred: &Path) -> anyhow::Result<(PathBuf, File)> {
     for n in 0..20 {
         let candidate = if n == 0 {
-            download_dir.join(name)
+            preferred.to_path_buf()
         } else {
-            download_dir.join(&format!("{}.{}", name, n))
+            let mut candidate = OsString::from(preferred.as_os_str());
+            candidate.push(format!(".{}", n));
+            PathBuf::from(candidate)
         };
 
         if let Ok(file) = OpenOptions::new()
@@ -50,25 +165,190 @@ fn resolve_file_name(name: Option<&str>) -> anyhow::Result<(PathBuf, File)> {
     }
 
     anyhow::bail!(
-        "Unable to find non-conflicting download name for {} in {}",
-        name,
-        download_dir.display()
+        "Unable to find non-conflicting download name for {}",
+        preferred.display()
     );
 }
 
-pub fn save_to_downloads(orig_name: Option<String>, data: &[u8]) -> anyhow::Result<()> {
-    let (name, mut file) = resolve_file_name(orig_name.as_deref())?;
+fn write_download(
+    name: Option<&str>,
+    destination: &Destination,
+    data: &[u8],
+) -> anyhow::Result<PathBuf> {
+    let (name, mut file) = resolve_file_name(&preferred_path(name, destination)?)?;
     file.write_all(data)
         .with_context(|| format!("writing {} of data to {}", data.len(), name.display()))?;
+    Ok(name)
+}
+
+/// Records a download that isn't saved because it isn't permitted
+pub fn reject_download(pane_id: PaneId, name: Option<String>, size: usize, reason: String) {
+    log::error!(
+        "Ignoring download request for {:?} from pane {}: {}",
+        name,
+        pane_id,
+        reason
+    );
+    DOWNLOADS
+        .lock()
+        .unwrap()
+        .add(pane_id, name, size, DownloadStatus::Rejected(reason));
+}
+
+/// Gives the `download-requested` event the chance to cancel or
+/// rename a file download requested by `pane_id` before it is saved.
+/// This is driven by the mux notification rather than by a window,
+/// so that downloads from panes in workspaces that aren't shown in
+/// a window aren't lost; the event has no window in that case.
+pub fn request_download(pane_id: PaneId, name: Option<String>, data: Arc<Vec<u8>>) {
+    let config = config::configuration();
+    if !config.allow_download_protocols {
+        reject_download(
+            pane_id,
+            name,
+            data.len(),
+            "allow_download_protocols=false".to_string(),
+        );
+        return;
+    }
+    if data.len() > config.max_download_size {
+        reject_download(
+            pane_id,
+            name,
+            data.len(),
+            format!("larger than max_download_size={}", config.max_download_size),
+        );
+        return;
+    }
+
+    let window = Mux::get()
+        .resolve_pane_id(pane_id)
+        .and_then(|(_domain, window_id, _tab_id)| {
+            crate::frontend::front_end().gui_window_for_mux_window(window_id)
+        });
+    let pane = MuxPane(pane_id);
+
+    async fn do_event(
+        lua: Option<Rc<mlua::Lua>>,
+        window: Option<GuiWin>,
+        pane: MuxPane,
+        name: Option<String>,
+        data: Arc<Vec<u8>>,
+    ) -> anyhow::Result<()> {
+        let destination = match lua {
+            Some(lua) => {
+                let args = lua.pack_multi((window, pane, name.clone(), data.len()))?;
+                match config::lua::emit_async_callback(
+                    &lua,
+                    ("download-requested".to_string(), args),
+                )
+                .await
+                {
+                    Ok(mlua::Value::Nil) | Ok(mlua::Value::Boolean(true)) => Destination::Default,
+                    Ok(mlua::Value::Boolean(false)) => Destination::Cancel,
+                    Ok(mlua::Value::String(s)) => Destination::Chosen(s.to_str()?.to_string()),
+                    Ok(value) => {
+                        log::error!(
+                            "download-requested: expected a string, boolean or nil, got {}",
+                            value.type_name()
+                        );
+                        Destination::Cancel
+                    }
+                    Err(err) => {
+                        log::error!("while processing download-requested event: {:#}", err);
+                        Destination::Cancel
+                    }
+                }
+            }
+            None => Destination::Default,
+        };
+        save_to_downloads(pane.0, name, data, destination);
+        Ok(())
+    }
+
+    promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
+        do_event(lua, window, pane, name, data)
+    }))
+    .detach();
+}
 
-    let url = format!("file://{}", name.display());
-    wezterm_toast_notification::persistent_toast_notification_with_click_to_open_url(
-        "Download completed",
-        &format!("Downloaded {}", name.display()),
-        &url,
+/// Saves a download requested by `pane_id` to `destination`.
+/// Writing the file may block, so this happens on a separate thread.
+pub fn save_to_downloads(
+    pane_id: PaneId,
+    orig_name: Option<String>,
+    data: Arc<Vec<u8>>,
+    destination: Destination,
+) {
+    if let Destination::Cancel = destination {
+        log::info!("download of {:?} was cancelled", orig_name);
+        DOWNLOADS
+            .lock()
+            .unwrap()
+            .add(pane_id, orig_name, data.len(), DownloadStatus::Cancelled);
+        return;
+    }
+
+    let id = DOWNLOADS.lock().unwrap().add(
+        pane_id,
+        orig_name.clone(),
+        data.len(),
+        DownloadStatus::Saving,
     );
 
-    log::info!("Downloaded {}", name.display());
+    std::thread::spawn(move || {
+        let status = match write_download(orig_name.as_deref(), &destination, &data) {
+            Ok(name) => {
+                let message = format!("Downloaded {}", name.display());
+                match url::Url::from_file_path(&name) {
+                    Ok(url) => {
+                        wezterm_toast_notification::persistent_toast_notification_with_click_to_open_url(
+                            "Download completed",
+                            &message,
+                            url.as_str(),
+                        )
+                    }
+                    Err(()) => wezterm_toast_notification::persistent_toast_notification(
+                        "Download completed",
+                        &message,
+                    ),
+                }
+                log::info!("Downloaded {}", name.display());
+                DownloadStatus::Saved(name)
+            }
+            Err(err) => {
+                log::error!("save_to_downloads: {:#}", err);
+                DownloadStatus::Failed(format!("{:#}", err))
+            }
+        };
+        DOWNLOADS.lock().unwrap().set_status(id, status);
+    });
+}
 
-    Ok(())
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn neuter() {
+        assert_eq!(neuter_name("report.pdf"), Some("report.pdf"));
+        assert_eq!(neuter_name("../../.bashrc"), Some(".bashrc"));
+        assert_eq!(neuter_name("C:\\evil.exe"), Some("evil.exe"));
+        assert_eq!(neuter_name("C:evil.exe"), None);
+        assert_eq!(neuter_name("dir/.."), None);
+        assert_eq!(neuter_name("dir/"), None);
+    }
+
+    #[test]
+    fn recent_downloads_are_bounded() {
+        let mut downloads = Downloads::default();
+        for _ in 0..MAX_RECENT_DOWNLOADS + 2 {
+            downloads.add(1, None, 0, DownloadStatus::Saving);
+        }
+        assert_eq!(downloads.entries.len(), MAX_RECENT_DOWNLOADS);
+        assert_eq!(downloads.entries[0].id, 2);
+
+        downloads.set_status(2, DownloadStatus::Cancelled);
+        assert_eq!(downloads.entries[0].status, DownloadStatus::Cancelled);
+    }
 }
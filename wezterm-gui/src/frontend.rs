@@ -87,10 +87,14 @@ impl GuiFrontEnd {
                     .detach();
                 }
                 MuxNotification::TabTitleChanged { .. } => {}
+                MuxNotification::PaneTagsChanged { .. } => {}
                 MuxNotification::WindowTitleChanged { .. } => {}
                 MuxNotification::TabResized(_) => {}
                 MuxNotification::TabAddedToWindow { .. } => {}
-                MuxNotification::PaneRemoved(_) => {}
+                MuxNotification::PaneRemoved(pane_id) => {
+                    // Complete a print job that the pane left in printer controller mode
+                    crate::print::end_print_job(pane_id);
+                }
                 MuxNotification::WindowInvalidated(_) => {}
                 MuxNotification::PaneOutput(_) => {}
                 MuxNotification::PaneAdded(_) => {}
@@ -121,7 +125,11 @@ impl GuiFrontEnd {
                                 }
                             };
 
-                            if show {
+                            if show && crate::screenlock::is_screen_locked() {
+                                crate::screenlock::note_suppressed_notification(
+                                    title.as_ref().unwrap_or(&body),
+                                );
+                            } else if show {
                                 let message = if title.is_none() { "" } else { &body };
                                 let title = title.as_ref().unwrap_or(&body);
                                 // FIXME: if notification.focus is true, we should do
@@ -147,7 +155,8 @@ impl GuiFrontEnd {
                         | Alert::WindowTitleChanged(_)
                         | Alert::TabTitleChanged(_)
                         | Alert::IconTitleChanged(_)
-                        | Alert::SetUserVar { .. },
+                        | Alert::SetUserVar { .. }
+                        | Alert::PrintRequested { .. },
                 } => {}
                 MuxNotification::Empty => {
                     if config::configuration().quit_when_all_windows_are_closed {
@@ -160,16 +169,15 @@ impl GuiFrontEnd {
                         .detach();
                     }
                 }
-                MuxNotification::SaveToDownloads { name, data } => {
-                    if !config::configuration().allow_download_protocols {
-                        log::error!(
-                            "Ignoring download request for {:?}, \
-                                 as allow_download_protocols=false",
-                            name
-                        );
-                    } else if let Err(err) = crate::download::save_to_downloads(name, &*data) {
-                        log::error!("save_to_downloads: {:#}", err);
-                    }
+                MuxNotification::SaveToDownloads {
+                    pane_id,
+                    name,
+                    data,
+                } => {
+                    promise::spawn::spawn_into_main_thread(async move {
+                        crate::download::request_download(pane_id, name, data);
+                    })
+                    .detach();
                 }
                 MuxNotification::AssignClipboard {
                     pane_id,
@@ -203,6 +211,10 @@ impl GuiFrontEnd {
             }
             true
         });
+        crate::screenlock::start_monitor();
+        crate::power::start_monitor();
+        crate::termwindow::query_system_reduced_motion();
+
         // Re-evaluate the config so that folks that are using
         // `wezterm.gui.get_appearance()` can have that take effect
         // before any windows are created
@@ -252,6 +264,7 @@ impl GuiFrontEnd {
                             SpawnTabDomain::DomainName("local".to_string()),
                             cmd,
                             cwd,
+                            None, // optional exit_behavior override
                             TerminalSize::default(),
                             pane_id,
                             workspace,
@@ -531,10 +544,25 @@ pub fn try_new() -> Result<Rc<GuiFrontEnd>, Error> {
     let front_end = GuiFrontEnd::try_new()?;
     FRONT_END.with(|f| *f.borrow_mut() = Some(Rc::clone(&front_end)));
 
+    crate::crashreport::update_config(&config::configuration());
+    mux::capture::set_capture_handler(|pane_id| {
+        Box::pin(crate::termwindow::capture::capture_pane_png(pane_id))
+    });
+    mux::windowconfig::set_window_config_handler(|window_id, request| {
+        Box::pin(crate::termwindow::windowconfig::window_config(
+            window_id, request,
+        ))
+    });
+
     let config_subscription = config::subscribe_to_config_reload({
         move || {
             promise::spawn::spawn_into_main_thread(async {
-                crate::commands::CommandDef::recreate_menubar(&config::configuration());
+                let config = config::configuration();
+                crate::crashreport::update_config(&config);
+                crate::commands::CommandDef::recreate_menubar(&config);
+                if let Some(mux) = Mux::try_get() {
+                    mux.update_services(&config.services);
+                }
             })
             .detach();
             true
@@ -26,6 +26,9 @@ impl InputMap {
         let mut mouse = config.mouse_bindings();
 
         let mut keys = config.key_bindings();
+        if let Some(preset) = config.key_preset {
+            crate::keypresets::apply_key_preset(preset, &mut keys);
+        }
 
         let leader = config.leader.as_ref().map(|leader| {
             (
@@ -148,6 +151,30 @@ impl InputMap {
                     },
                     SelectTextAtMouseCursor(SelectionMode::Cell)
                 ],
+                [
+                    MouseEventTriggerMods {
+                        mods: Modifiers::CTRL,
+                        mouse_reporting: false,
+                        alt_screen: MouseEventAltScreen::Any,
+                    },
+                    MouseEventTrigger::Down {
+                        streak: 2,
+                        button: MouseButton::Left
+                    },
+                    ExtendSelectionToSemanticUnit
+                ],
+                [
+                    MouseEventTriggerMods {
+                        mods: Modifiers::CTRL,
+                        mouse_reporting: false,
+                        alt_screen: MouseEventAltScreen::Any,
+                    },
+                    MouseEventTrigger::Up {
+                        streak: 2,
+                        button: MouseButton::Left
+                    },
+                    CompleteSelection(ClipboardCopyDestination::ClipboardAndPrimarySelection)
+                ],
                 [
                     MouseEventTriggerMods {
                         mods: Modifiers::ALT,
@@ -389,6 +416,17 @@ impl InputMap {
         }
     }
 
+    /// Construct an input map from an explicit set of key tables,
+    /// with no mouse assignments
+    #[cfg(test)]
+    pub fn with_keys(keys: KeyTables, leader: Option<(KeyCode, Modifiers)>) -> Self {
+        Self {
+            keys,
+            leader: leader.map(|(key, mods)| (key, mods, Duration::from_millis(1000))),
+            mouse: HashMap::new(),
+        }
+    }
+
     /// Given an action, return the corresponding set of application-wide key assignments that are
     /// mapped to it.
     /// If any key_tables reference a given combination, then that combination
@@ -434,6 +472,10 @@ impl InputMap {
         None
     }
 
+    pub fn has_leader(&self) -> bool {
+        self.leader.is_some()
+    }
+
     pub fn has_table(&self, name: &str) -> bool {
         self.keys.by_name.contains_key(name)
     }
@@ -0,0 +1,132 @@
+//! Renders the characters that are normally displayed as blanks, or not
+//! at all, as dim pictures, for `show_invisible_characters`.
+use termwiz::cell::{Cell, Intensity, Underline};
+use termwiz::suspicious::{is_bidi_control, is_invisible};
+use wezterm_term::Line;
+
+fn is_hidden(c: char) -> bool {
+    is_bidi_control(c) || is_invisible(c)
+}
+
+/// Returns the picture that stands for `c`, if it is normally
+/// displayed as a blank even though it isn't an ordinary space
+fn picture(c: char) -> Option<char> {
+    match c {
+        '\u{00a0}' | '\u{2007}' | '\u{202f}' => Some('⍽'),
+        '\u{00ad}' => Some('-'),
+        '\u{2000}'..='\u{200a}' | '\u{205f}' | '\u{3000}' => Some('·'),
+        '\u{3164}' | '\u{ffa0}' => Some('␣'),
+        _ => None,
+    }
+}
+
+/// Returns the text that the cell holding `text` is rendered with,
+/// along with whether it held zero-width characters that aren't
+/// otherwise shown, or None if it is rendered as-is
+fn reveal(text: &str) -> Option<(String, bool)> {
+    let mut revealed = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut had_hidden = false;
+    for c in text.chars() {
+        // Some of the pictured characters, such as the soft hyphen,
+        // are also hidden, but showing them as a picture says more
+        if let Some(p) = picture(c) {
+            revealed.push(p);
+            changed = true;
+        } else if is_hidden(c) {
+            // Joiners shape emoji sequences and some scripts, and tags
+            // spell out the flags of subdivisions
+            let legitimate = match c {
+                '\u{200c}' | '\u{200d}' => !text.chars().all(|g| g.is_ascii() || is_hidden(g)),
+                '\u{e0020}'..='\u{e007f}' => text.starts_with('\u{1f3f4}'),
+                _ => false,
+            };
+            if legitimate {
+                revealed.push(c);
+                continue;
+            }
+            had_hidden = true;
+            changed = true;
+        } else {
+            revealed.push(c);
+        }
+    }
+    if !changed {
+        return None;
+    }
+    if had_hidden && revealed.trim().is_empty() {
+        // There is nothing to underline, so show the hidden
+        // characters in place of the blank
+        return Some(("◌".to_string(), false));
+    }
+    Some((revealed, had_hidden))
+}
+
+/// Returns a copy of `line` in which non-breaking spaces, soft hyphens
+/// and other characters that are normally displayed as blanks are
+/// replaced by dim pictures, and cells that hold zero-width characters
+/// are underlined with a dotted line, or None if there are none
+pub fn show_invisible_characters(line: &Line) -> Option<Line> {
+    let mut revealed = None;
+    for cell in line.visible_cells() {
+        let (text, had_hidden) = match reveal(cell.str()) {
+            Some(reveal) => reveal,
+            None => continue,
+        };
+        let line = revealed.get_or_insert_with(|| {
+            let line = line.clone();
+            // The copy would otherwise share the cached shaping of `line`
+            line.clear_appdata();
+            line
+        });
+
+        let mut attrs = cell.attrs().clone();
+        attrs.set_intensity(Intensity::Half);
+        if had_hidden {
+            attrs.set_underline(Underline::Dotted);
+        }
+        let seqno = line.current_seqno();
+        line.set_cell(
+            cell.cell_index(),
+            Cell::new_grapheme_with_width(&text, cell.width(), attrs),
+            seqno,
+        );
+    }
+    revealed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::cell::CellAttributes;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn reveal_cells() {
+        assert_eq!(reveal("a"), None);
+        assert_eq!(reveal("\u{1f468}\u{200d}\u{1f469}"), None);
+        assert_eq!(reveal("\u{a0}"), Some(("⍽".to_string(), false)));
+        assert_eq!(reveal("\u{ad}"), Some(("-".to_string(), false)));
+        assert_eq!(reveal("a\u{ad}"), Some(("a-".to_string(), false)));
+        assert_eq!(reveal("\u{200b}a"), Some(("a".to_string(), true)));
+        assert_eq!(reveal("\u{200b} "), Some(("◌".to_string(), false)));
+    }
+
+    #[test]
+    fn show_line() {
+        let mut line = Line::from_text("a\u{a0}b c", &CellAttributes::default(), SEQ_ZERO, None);
+        let shown = show_invisible_characters(&line).unwrap();
+        assert_eq!(shown.as_str(), "a⍽b c");
+        assert_eq!(
+            shown.get_cell(1).unwrap().attrs().intensity(),
+            Intensity::Half
+        );
+        assert_eq!(
+            shown.get_cell(2).unwrap().attrs().intensity(),
+            Intensity::Normal
+        );
+
+        line.set_cell_grapheme(1, " ", 1, CellAttributes::default(), SEQ_ZERO);
+        assert!(show_invisible_characters(&line).is_none());
+    }
+}
@@ -0,0 +1,474 @@
+//! Analyzes the effective key assignments for problems that are otherwise
+//! hard to spot: bindings that are shadowed by other bindings, bindings
+//! that reference key tables that don't exist, and bindings that will
+//! never be seen by wezterm because the OS reserves the chord for itself.
+//! This powers `wezterm show-keys --conflicts`.
+use crate::inputmap::{human_key, InputMap};
+use config::keyassignment::{KeyAssignment, KeyTable};
+use config::{ConfigHandle, Key};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use wezterm_gui_subcommands::KeyPlatform;
+use window::{KeyCode, Modifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The same chord is assigned more than once in the same table;
+    /// only the last assignment takes effect
+    Duplicate,
+    /// A key table assigns a chord that is also present in the
+    /// default table, hiding it while the key table is active
+    ShadowedByKeyTable,
+    /// The chord is the same as the leader key, which is processed
+    /// before any key assignments are considered
+    ShadowedByLeader,
+    /// The assignment uses the LEADER modifier, but no leader is configured
+    NoLeader,
+    /// The assignment activates a key table that is not defined
+    UndefinedKeyTable,
+    /// The key table is defined but nothing activates it
+    UnusedKeyTable,
+    /// The chord is reserved by the operating system or desktop
+    /// environment and is typically never delivered to wezterm
+    OsReserved,
+}
+
+impl ConflictKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Duplicate => "duplicate",
+            Self::ShadowedByKeyTable => "shadowed by key table",
+            Self::ShadowedByLeader => "shadowed by leader",
+            Self::NoLeader => "no leader",
+            Self::UndefinedKeyTable => "undefined key table",
+            Self::UnusedKeyTable => "unused key table",
+            Self::OsReserved => "os reserved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    /// The key table containing the assignment; None for the default table
+    pub table: Option<String>,
+    pub key: Option<String>,
+    pub action: Option<String>,
+    pub detail: String,
+}
+
+fn key_label(key: &KeyCode, mods: Modifiers) -> String {
+    let key = human_key(key);
+    if mods == Modifiers::NONE {
+        key
+    } else {
+        let mods = format!("{mods:?}").replace(" ", "");
+        format!("{mods}+{key}")
+    }
+}
+
+fn table_label(table: Option<&str>) -> String {
+    match table {
+        Some(name) => format!("key table `{name}`"),
+        None => "the default key table".to_string(),
+    }
+}
+
+/// Returns true if two chords refer to the same key, either because
+/// they are identical or because they map to the same physical key.
+fn same_chord(a: &(KeyCode, Modifiers), b: &(KeyCode, Modifiers)) -> bool {
+    let (a_key, a_mods) = a.0.normalize_shift(a.1.remove_positional_mods());
+    let (b_key, b_mods) = b.0.normalize_shift(b.1.remove_positional_mods());
+    if a_mods != b_mods {
+        return false;
+    }
+    if a_key == b_key {
+        return true;
+    }
+    match (a_key.to_phys(), b_key.to_phys()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns the platform that wezterm was built for
+pub fn current_platform() -> KeyPlatform {
+    if cfg!(target_os = "macos") {
+        KeyPlatform::Macos
+    } else if cfg!(windows) {
+        KeyPlatform::Windows
+    } else {
+        KeyPlatform::Linux
+    }
+}
+
+/// The chords that are reserved by the system on each platform.
+/// For Linux these are the defaults of the popular desktop environments.
+fn os_reserved_chords(platform: KeyPlatform) -> Vec<(KeyCode, Modifiers, &'static str)> {
+    use KeyCode::*;
+    let alt = Modifiers::ALT;
+    let ctrl = Modifiers::CTRL;
+    let shift = Modifiers::SHIFT;
+    let sup = Modifiers::SUPER;
+
+    match platform {
+        KeyPlatform::Macos => vec![
+            (Char('\t'), sup, "Switches between applications"),
+            (Char('\t'), sup | shift, "Switches between applications"),
+            (Char(' '), sup, "Opens Spotlight"),
+            (Char(' '), ctrl, "Selects the previous input source"),
+            (Char('3'), sup | shift, "Takes a screenshot"),
+            (Char('4'), sup | shift, "Takes a screenshot of a selection"),
+            (Char('5'), sup | shift, "Opens the screenshot toolbar"),
+            (Char('\x1b'), sup | alt, "Opens the Force Quit dialog"),
+            (Char('q'), sup | ctrl, "Locks the screen"),
+            (Char('h'), sup | alt, "Hides other applications"),
+            (UpArrow, ctrl, "Opens Mission Control"),
+            (DownArrow, ctrl, "Shows application windows"),
+            (LeftArrow, ctrl, "Moves to the space on the left"),
+            (RightArrow, ctrl, "Moves to the space on the right"),
+        ],
+        KeyPlatform::Windows => vec![
+            (Char('\t'), alt, "Switches between windows"),
+            (Char('\t'), alt | shift, "Switches between windows"),
+            (Function(4), alt, "Closes the window"),
+            (
+                Char('\x7f'),
+                ctrl | alt,
+                "Opens the security options screen",
+            ),
+            (Char('\x1b'), ctrl | shift, "Opens Task Manager"),
+            (Char('\x1b'), ctrl, "Opens the Start menu"),
+            (Char('\t'), sup, "Opens Task View"),
+            (Char('d'), sup, "Shows the desktop"),
+            (Char('e'), sup, "Opens File Explorer"),
+            (Char('l'), sup, "Locks the screen"),
+            (Char('r'), sup, "Opens the Run dialog"),
+            (Char('s'), sup | shift, "Takes a screenshot of a selection"),
+            (Char('v'), sup, "Opens the clipboard history"),
+        ],
+        KeyPlatform::Linux => vec![
+            (Char('\t'), alt, "Switches between windows"),
+            (Char('\t'), alt | shift, "Switches between windows"),
+            (Char('\t'), sup, "Switches between applications"),
+            (Function(2), alt, "Opens the run command dialog"),
+            (Function(4), alt, "Closes the window"),
+            (Char('\x7f'), ctrl | alt, "Logs out"),
+            (Char('l'), sup, "Locks the screen"),
+            (Char('t'), ctrl | alt, "Opens a terminal"),
+            (LeftArrow, ctrl | alt, "Moves to the workspace on the left"),
+            (
+                RightArrow,
+                ctrl | alt,
+                "Moves to the workspace on the right",
+            ),
+            (UpArrow, ctrl | alt, "Moves to the workspace above"),
+            (DownArrow, ctrl | alt, "Moves to the workspace below"),
+        ],
+    }
+}
+
+/// Collects the names of key tables that are activated by an action,
+/// including those nested in `Multiple`.
+fn activated_tables<'a>(action: &'a KeyAssignment, names: &mut Vec<&'a str>) {
+    match action {
+        KeyAssignment::ActivateKeyTable { name, .. } => names.push(name),
+        KeyAssignment::Multiple(actions) => {
+            for action in actions {
+                activated_tables(action, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reports chords that are assigned more than once in the list of
+/// keys from the config; later entries replace earlier ones.
+fn check_duplicates(
+    config: &ConfigHandle,
+    table: Option<&str>,
+    keys: &[Key],
+    conflicts: &mut Vec<Conflict>,
+) {
+    let mut seen: HashMap<(KeyCode, Modifiers), &KeyAssignment> = HashMap::new();
+    for k in keys {
+        let chord = k
+            .key
+            .key
+            .resolve(config.key_map_preference)
+            .normalize_shift(k.key.mods);
+        if let Some(prior) = seen.insert(chord.clone(), &k.action) {
+            if *prior != k.action {
+                conflicts.push(Conflict {
+                    kind: ConflictKind::Duplicate,
+                    table: table.map(|s| s.to_string()),
+                    key: Some(key_label(&chord.0, chord.1)),
+                    action: Some(format!("{prior:?}")),
+                    detail: format!(
+                        "Assigned again later in {}; only {:?} takes effect",
+                        table_label(table),
+                        k.action
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Checks the resolved key tables; this is separate from the config
+/// so that it can be exercised without loading a configuration.
+fn check_tables(map: &InputMap, platform: Option<KeyPlatform>, conflicts: &mut Vec<Conflict>) {
+    let mut tables: Vec<(Option<&str>, &KeyTable)> = vec![(None, &map.keys.default)];
+    let mut names: Vec<&String> = map.keys.by_name.keys().collect();
+    names.sort();
+    for name in names {
+        tables.push((Some(name.as_str()), &map.keys.by_name[name]));
+    }
+
+    let reserved = platform.map(os_reserved_chords).unwrap_or_default();
+    let mut referenced = BTreeSet::new();
+
+    for (table_name, table) in &tables {
+        for ((key, mods), entry) in table.iter() {
+            let label = key_label(key, *mods);
+            let action = format!("{:?}", entry.action);
+            let mut push = |kind, detail: String| {
+                conflicts.push(Conflict {
+                    kind,
+                    table: table_name.map(|s| s.to_string()),
+                    key: Some(label.clone()),
+                    action: Some(action.clone()),
+                    detail,
+                })
+            };
+
+            if let Some(name) = table_name {
+                if let Some(default) = map.keys.default.get(&(key.clone(), *mods)) {
+                    if default.action != entry.action {
+                        push(
+                            ConflictKind::ShadowedByKeyTable,
+                            format!(
+                                "Hides {:?} from the default key table while `{name}` is active",
+                                default.action
+                            ),
+                        );
+                    }
+                }
+            }
+
+            if mods.contains(Modifiers::LEADER) {
+                if !map.has_leader() {
+                    push(
+                        ConflictKind::NoLeader,
+                        "Uses the LEADER modifier but no leader is configured".to_string(),
+                    );
+                }
+            } else if map.is_leader(key, *mods).is_some() {
+                push(
+                    ConflictKind::ShadowedByLeader,
+                    "This is the leader key, so this assignment never fires".to_string(),
+                );
+            }
+
+            let mut activated = vec![];
+            activated_tables(&entry.action, &mut activated);
+            for name in activated {
+                referenced.insert(name.to_string());
+                if !map.has_table(name) {
+                    push(
+                        ConflictKind::UndefinedKeyTable,
+                        format!("Activates key table `{name}`, which is not defined"),
+                    );
+                }
+            }
+
+            if let Some(platform) = platform {
+                for (r_key, r_mods, reason) in &reserved {
+                    if same_chord(&(key.clone(), *mods), &(r_key.clone(), *r_mods)) {
+                        push(
+                            ConflictKind::OsReserved,
+                            format!("Reserved on {platform:?}: {reason}"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    for name in map.keys.by_name.keys() {
+        // These are activated by ActivateCopyMode and Search
+        if name == "copy_mode" || name == "search_mode" {
+            continue;
+        }
+        if !referenced.contains(name) {
+            conflicts.push(Conflict {
+                kind: ConflictKind::UnusedKeyTable,
+                table: Some(name.to_string()),
+                key: None,
+                action: None,
+                detail: "No assignment activates this key table".to_string(),
+            });
+        }
+    }
+}
+
+/// Analyzes the key assignments from the config, returning the
+/// list of problems that were found, ordered by kind and table.
+pub fn find_conflicts(
+    config: &ConfigHandle,
+    map: &InputMap,
+    platform: KeyPlatform,
+) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+
+    check_duplicates(config, None, &config.keys, &mut conflicts);
+    let mut names: Vec<&String> = config.key_tables.keys().collect();
+    names.sort();
+    for name in names {
+        check_duplicates(config, Some(name), &config.key_tables[name], &mut conflicts);
+    }
+
+    check_tables(map, Some(platform), &mut conflicts);
+
+    conflicts.sort();
+    conflicts
+}
+
+pub fn show_conflicts(conflicts: &[Conflict]) {
+    if conflicts.is_empty() {
+        println!("No conflicts found");
+        return;
+    }
+
+    let rows: Vec<[String; 4]> = conflicts
+        .iter()
+        .map(|c| {
+            [
+                c.kind.label().to_string(),
+                c.table.clone().unwrap_or_else(|| "default".to_string()),
+                c.key.clone().unwrap_or_default(),
+                c.detail.clone(),
+            ]
+        })
+        .collect();
+
+    let headers = ["PROBLEM", "TABLE", "KEY", "DETAIL"];
+    let mut widths = headers.map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let [kind_width, table_width, key_width, _] = widths;
+    println!(
+        "{:kind_width$}   {:table_width$}   {:key_width$}   {}",
+        headers[0], headers[1], headers[2], headers[3]
+    );
+    for [kind, table, key, detail] in rows {
+        println!("{kind:kind_width$}   {table:table_width$}   {key:key_width$}   {detail}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::keyassignment::{KeyTableEntry, KeyTables};
+
+    fn entry(action: KeyAssignment) -> KeyTableEntry {
+        KeyTableEntry { action }
+    }
+
+    fn activate(name: &str) -> KeyAssignment {
+        KeyAssignment::ActivateKeyTable {
+            name: name.to_string(),
+            timeout_milliseconds: None,
+            replace_current: false,
+            one_shot: true,
+            until_unknown: false,
+            prevent_fallback: false,
+        }
+    }
+
+    fn kinds(map: &InputMap, platform: Option<KeyPlatform>) -> Vec<ConflictKind> {
+        let mut conflicts = vec![];
+        check_tables(map, platform, &mut conflicts);
+        conflicts.sort();
+        conflicts.into_iter().map(|c| c.kind).collect()
+    }
+
+    #[test]
+    fn undefined_and_unused_tables() {
+        let mut keys = KeyTables::default();
+        keys.default.insert(
+            (KeyCode::Char('r'), Modifiers::CTRL | Modifiers::SHIFT),
+            entry(KeyAssignment::Multiple(vec![
+                KeyAssignment::Nop,
+                activate("resize"),
+            ])),
+        );
+        keys.by_name
+            .insert("unused".to_string(), KeyTable::default());
+        let map = InputMap::with_keys(keys, None);
+
+        assert_eq!(
+            kinds(&map, None),
+            vec![
+                ConflictKind::UndefinedKeyTable,
+                ConflictKind::UnusedKeyTable
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_and_leader() {
+        let mut keys = KeyTables::default();
+        keys.default.insert(
+            (KeyCode::Char('a'), Modifiers::CTRL),
+            entry(KeyAssignment::Nop),
+        );
+        keys.default.insert(
+            (KeyCode::Char('b'), Modifiers::LEADER),
+            entry(activate("table")),
+        );
+        let mut table = KeyTable::default();
+        table.insert(
+            (KeyCode::Char('a'), Modifiers::CTRL),
+            entry(KeyAssignment::PopKeyTable),
+        );
+        keys.by_name.insert("table".to_string(), table);
+
+        let map = InputMap::with_keys(keys.clone(), None);
+        assert_eq!(
+            kinds(&map, None),
+            vec![ConflictKind::ShadowedByKeyTable, ConflictKind::NoLeader]
+        );
+
+        let map = InputMap::with_keys(keys, Some((KeyCode::Char('a'), Modifiers::CTRL)));
+        assert_eq!(
+            kinds(&map, None),
+            vec![
+                ConflictKind::ShadowedByKeyTable,
+                ConflictKind::ShadowedByLeader,
+                ConflictKind::ShadowedByLeader
+            ]
+        );
+    }
+
+    #[test]
+    fn os_reserved() {
+        let mut keys = KeyTables::default();
+        keys.default.insert(
+            (KeyCode::Char('\t'), Modifiers::ALT),
+            entry(KeyAssignment::Nop),
+        );
+        let map = InputMap::with_keys(keys, None);
+
+        assert_eq!(
+            kinds(&map, Some(KeyPlatform::Windows)),
+            vec![ConflictKind::OsReserved]
+        );
+        assert_eq!(kinds(&map, Some(KeyPlatform::Macos)), vec![]);
+    }
+}
@@ -0,0 +1,426 @@
+//! The key presets, selected by `key_preset`, emulate the key bindings
+//! of other programs so that people migrating from them can keep using
+//! the keys that they already know.
+//! The tmux and screen presets bind their prefix key to activate a key
+//! table named after the preset, while the iTerm2 preset binds keys in
+//! the default table.  Either way, the preset is layered between the
+//! configured `keys` and `key_tables`, which take precedence over it,
+//! and the default key bindings.
+use config::keyassignment::{
+    ClipboardPasteSource, KeyAssignment, KeyTable, KeyTableEntry, KeyTables, LauncherActionArgs,
+    LauncherFlags, PaneDirection, PaneSelectArguments, RotationDirection, ScrollbackEraseMode,
+    SpawnCommand, SpawnTabDomain,
+};
+use config::{DeferredKeyCode, KeyNoAction, KeyPreset};
+use window::{KeyCode, Modifiers};
+
+/// Adds the bindings of `preset` to `keys` without replacing any
+/// that are already present
+pub fn apply_key_preset(preset: KeyPreset, keys: &mut KeyTables) {
+    let (default, table) = match preset {
+        KeyPreset::Tmux => (prefix_key(preset, 'b'), Some(tmux_key_table())),
+        KeyPreset::Screen => (prefix_key(preset, 'a'), Some(screen_key_table())),
+        KeyPreset::ITerm2 => (iterm2_keys(), None),
+    };
+
+    layer(&mut keys.default, default);
+    if let Some(table) = table {
+        layer(
+            keys.by_name.entry(preset.name().to_string()).or_default(),
+            table,
+        );
+    }
+}
+
+fn layer(table: &mut KeyTable, entries: Vec<(KeyCode, Modifiers, KeyAssignment)>) {
+    for (key, mods, action) in entries {
+        table
+            .entry(key.normalize_shift(mods))
+            .or_insert(KeyTableEntry { action });
+    }
+}
+
+/// CTRL-`key` activates the key table of the preset for the next key
+fn prefix_key(preset: KeyPreset, key: char) -> Vec<(KeyCode, Modifiers, KeyAssignment)> {
+    vec![(
+        KeyCode::Char(key),
+        Modifiers::CTRL,
+        KeyAssignment::ActivateKeyTable {
+            name: preset.name().to_string(),
+            timeout_milliseconds: None,
+            replace_current: false,
+            one_shot: true,
+            until_unknown: false,
+            prevent_fallback: true,
+        },
+    )]
+}
+
+fn send_ctrl(key: char) -> KeyAssignment {
+    KeyAssignment::SendKey(KeyNoAction {
+        key: DeferredKeyCode::KeyCode(KeyCode::Char(key)),
+        mods: Modifiers::CTRL,
+    })
+}
+
+fn split_vertical() -> KeyAssignment {
+    KeyAssignment::SplitVertical(SpawnCommand {
+        domain: SpawnTabDomain::CurrentPaneDomain,
+        ..Default::default()
+    })
+}
+
+fn split_horizontal() -> KeyAssignment {
+    KeyAssignment::SplitHorizontal(SpawnCommand {
+        domain: SpawnTabDomain::CurrentPaneDomain,
+        ..Default::default()
+    })
+}
+
+fn activate_tab_by_number() -> Vec<(KeyCode, Modifiers, KeyAssignment)> {
+    (0..=9)
+        .map(|i| {
+            (
+                KeyCode::Char(char::from_digit(i, 10).unwrap()),
+                Modifiers::NONE,
+                KeyAssignment::ActivateTab(i as isize),
+            )
+        })
+        .collect()
+}
+
+fn tmux_key_table() -> Vec<(KeyCode, Modifiers, KeyAssignment)> {
+    use KeyAssignment::*;
+    let mut keys = vec![
+        (KeyCode::Char('b'), Modifiers::CTRL, send_ctrl('b')),
+        // Panes
+        (KeyCode::Char('"'), Modifiers::SHIFT, split_vertical()),
+        (KeyCode::Char('%'), Modifiers::SHIFT, split_horizontal()),
+        (
+            KeyCode::LeftArrow,
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Left),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Right),
+        ),
+        (
+            KeyCode::UpArrow,
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Up),
+        ),
+        (
+            KeyCode::DownArrow,
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Down),
+        ),
+        (
+            KeyCode::Char('o'),
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Next),
+        ),
+        (
+            KeyCode::Char(';'),
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Prev),
+        ),
+        (
+            KeyCode::LeftArrow,
+            Modifiers::CTRL,
+            AdjustPaneSize(PaneDirection::Left, 1),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::CTRL,
+            AdjustPaneSize(PaneDirection::Right, 1),
+        ),
+        (
+            KeyCode::UpArrow,
+            Modifiers::CTRL,
+            AdjustPaneSize(PaneDirection::Up, 1),
+        ),
+        (
+            KeyCode::DownArrow,
+            Modifiers::CTRL,
+            AdjustPaneSize(PaneDirection::Down, 1),
+        ),
+        (
+            KeyCode::LeftArrow,
+            Modifiers::ALT,
+            AdjustPaneSize(PaneDirection::Left, 5),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::ALT,
+            AdjustPaneSize(PaneDirection::Right, 5),
+        ),
+        (
+            KeyCode::UpArrow,
+            Modifiers::ALT,
+            AdjustPaneSize(PaneDirection::Up, 5),
+        ),
+        (
+            KeyCode::DownArrow,
+            Modifiers::ALT,
+            AdjustPaneSize(PaneDirection::Down, 5),
+        ),
+        (
+            KeyCode::Char('{'),
+            Modifiers::SHIFT,
+            RotatePanes(RotationDirection::CounterClockwise),
+        ),
+        (
+            KeyCode::Char('}'),
+            Modifiers::SHIFT,
+            RotatePanes(RotationDirection::Clockwise),
+        ),
+        (KeyCode::Char('z'), Modifiers::NONE, TogglePaneZoomState),
+        (
+            KeyCode::Char('q'),
+            Modifiers::NONE,
+            PaneSelect(PaneSelectArguments::default()),
+        ),
+        (
+            KeyCode::Char('x'),
+            Modifiers::NONE,
+            CloseCurrentPane { confirm: true },
+        ),
+        // Windows, which are tabs in wezterm
+        (
+            KeyCode::Char('c'),
+            Modifiers::NONE,
+            SpawnTab(SpawnTabDomain::CurrentPaneDomain),
+        ),
+        (KeyCode::Char('n'), Modifiers::NONE, ActivateTabRelative(1)),
+        (KeyCode::Char('p'), Modifiers::NONE, ActivateTabRelative(-1)),
+        (KeyCode::Char('l'), Modifiers::NONE, ActivateLastTab),
+        (KeyCode::Char('w'), Modifiers::NONE, ShowTabNavigator),
+        (
+            KeyCode::Char('&'),
+            Modifiers::SHIFT,
+            CloseCurrentTab { confirm: true },
+        ),
+        // Sessions, which are workspaces in wezterm
+        (
+            KeyCode::Char('s'),
+            Modifiers::NONE,
+            ShowLauncherArgs(LauncherActionArgs {
+                flags: LauncherFlags::FUZZY | LauncherFlags::WORKSPACES,
+                title: None,
+            }),
+        ),
+        (
+            KeyCode::Char('d'),
+            Modifiers::NONE,
+            DetachDomain(SpawnTabDomain::CurrentPaneDomain),
+        ),
+        // Copy mode
+        (KeyCode::Char('['), Modifiers::NONE, ActivateCopyMode),
+        (
+            KeyCode::Char(']'),
+            Modifiers::NONE,
+            PasteFrom(ClipboardPasteSource::Clipboard),
+        ),
+        (KeyCode::Char(':'), Modifiers::SHIFT, ActivateCommandPalette),
+    ];
+    keys.append(&mut activate_tab_by_number());
+    keys
+}
+
+fn screen_key_table() -> Vec<(KeyCode, Modifiers, KeyAssignment)> {
+    use KeyAssignment::*;
+    let mut keys = vec![
+        (KeyCode::Char('a'), Modifiers::NONE, send_ctrl('a')),
+        (KeyCode::Char('a'), Modifiers::CTRL, ActivateLastTab),
+        // Windows, which are tabs in wezterm
+        (
+            KeyCode::Char('c'),
+            Modifiers::NONE,
+            SpawnTab(SpawnTabDomain::CurrentPaneDomain),
+        ),
+        (
+            KeyCode::Char('c'),
+            Modifiers::CTRL,
+            SpawnTab(SpawnTabDomain::CurrentPaneDomain),
+        ),
+        (KeyCode::Char('n'), Modifiers::NONE, ActivateTabRelative(1)),
+        (KeyCode::Char('n'), Modifiers::CTRL, ActivateTabRelative(1)),
+        (KeyCode::Char(' '), Modifiers::NONE, ActivateTabRelative(1)),
+        (KeyCode::Char('p'), Modifiers::NONE, ActivateTabRelative(-1)),
+        (KeyCode::Char('p'), Modifiers::CTRL, ActivateTabRelative(-1)),
+        (
+            KeyCode::Char('\u{8}'),
+            Modifiers::NONE,
+            ActivateTabRelative(-1),
+        ),
+        (KeyCode::Char('"'), Modifiers::SHIFT, ShowTabNavigator),
+        (KeyCode::Char('w'), Modifiers::NONE, ShowTabNavigator),
+        (
+            KeyCode::Char('k'),
+            Modifiers::NONE,
+            CloseCurrentTab { confirm: true },
+        ),
+        // Regions, which are panes in wezterm
+        (KeyCode::Char('S'), Modifiers::SHIFT, split_vertical()),
+        (KeyCode::Char('|'), Modifiers::SHIFT, split_horizontal()),
+        (
+            KeyCode::Char('\t'),
+            Modifiers::NONE,
+            ActivatePaneDirection(PaneDirection::Next),
+        ),
+        (
+            KeyCode::Char('X'),
+            Modifiers::SHIFT,
+            CloseCurrentPane { confirm: true },
+        ),
+        (
+            KeyCode::Char('d'),
+            Modifiers::NONE,
+            DetachDomain(SpawnTabDomain::CurrentPaneDomain),
+        ),
+        // Copy mode
+        (KeyCode::Char('['), Modifiers::NONE, ActivateCopyMode),
+        (KeyCode::Char('\x1b'), Modifiers::NONE, ActivateCopyMode),
+        (
+            KeyCode::Char(']'),
+            Modifiers::NONE,
+            PasteFrom(ClipboardPasteSource::Clipboard),
+        ),
+        (KeyCode::Char(':'), Modifiers::SHIFT, ActivateCommandPalette),
+    ];
+    keys.append(&mut activate_tab_by_number());
+    keys
+}
+
+fn iterm2_keys() -> Vec<(KeyCode, Modifiers, KeyAssignment)> {
+    use KeyAssignment::*;
+    vec![
+        (KeyCode::Char('d'), Modifiers::SUPER, split_horizontal()),
+        (
+            KeyCode::Char('D'),
+            Modifiers::SUPER | Modifiers::SHIFT,
+            split_vertical(),
+        ),
+        (
+            KeyCode::LeftArrow,
+            Modifiers::SUPER | Modifiers::ALT,
+            ActivatePaneDirection(PaneDirection::Left),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::SUPER | Modifiers::ALT,
+            ActivatePaneDirection(PaneDirection::Right),
+        ),
+        (
+            KeyCode::UpArrow,
+            Modifiers::SUPER | Modifiers::ALT,
+            ActivatePaneDirection(PaneDirection::Up),
+        ),
+        (
+            KeyCode::DownArrow,
+            Modifiers::SUPER | Modifiers::ALT,
+            ActivatePaneDirection(PaneDirection::Down),
+        ),
+        (
+            KeyCode::Char('['),
+            Modifiers::SUPER,
+            ActivatePaneDirection(PaneDirection::Prev),
+        ),
+        (
+            KeyCode::Char(']'),
+            Modifiers::SUPER,
+            ActivatePaneDirection(PaneDirection::Next),
+        ),
+        (
+            KeyCode::Char('\r'),
+            Modifiers::SUPER | Modifiers::SHIFT,
+            TogglePaneZoomState,
+        ),
+        (
+            KeyCode::Char('w'),
+            Modifiers::SUPER,
+            CloseCurrentPane { confirm: true },
+        ),
+        (KeyCode::Char('\r'), Modifiers::SUPER, ToggleFullScreen),
+        (
+            KeyCode::Char('k'),
+            Modifiers::SUPER,
+            ClearScrollback(ScrollbackEraseMode::ScrollbackAndViewport),
+        ),
+        // The "Natural Text Editing" key mappings
+        (
+            KeyCode::LeftArrow,
+            Modifiers::ALT,
+            SendString("\x1bb".to_string()),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::ALT,
+            SendString("\x1bf".to_string()),
+        ),
+        (
+            KeyCode::LeftArrow,
+            Modifiers::SUPER,
+            SendString("\x01".to_string()),
+        ),
+        (
+            KeyCode::RightArrow,
+            Modifiers::SUPER,
+            SendString("\x05".to_string()),
+        ),
+        (
+            KeyCode::Char('\u{8}'),
+            Modifiers::ALT,
+            SendString("\x17".to_string()),
+        ),
+        (
+            KeyCode::Char('\u{8}'),
+            Modifiers::SUPER,
+            SendString("\x15".to_string()),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn configured_keys_take_precedence() {
+        let mut keys = KeyTables::default();
+        keys.by_name.insert(
+            "tmux".to_string(),
+            [(
+                (KeyCode::Char('c'), Modifiers::NONE),
+                KeyTableEntry {
+                    action: KeyAssignment::SpawnWindow,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        apply_key_preset(KeyPreset::Tmux, &mut keys);
+
+        assert!(matches!(
+            keys.default
+                .get(&(KeyCode::Char('b'), Modifiers::CTRL))
+                .map(|entry| &entry.action),
+            Some(KeyAssignment::ActivateKeyTable { name, .. }) if name == "tmux"
+        ));
+        let table = &keys.by_name["tmux"];
+        assert_eq!(
+            table[&(KeyCode::Char('c'), Modifiers::NONE)].action,
+            KeyAssignment::SpawnWindow
+        );
+        assert_eq!(
+            table[&KeyCode::Char('%').normalize_shift(Modifiers::SHIFT)].action,
+            split_horizontal()
+        );
+        assert_eq!(
+            table[&(KeyCode::Char('7'), Modifiers::NONE)].action,
+            KeyAssignment::ActivateTab(7)
+        );
+    }
+}
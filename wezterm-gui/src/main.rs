@@ -9,7 +9,7 @@ use anyhow::{anyhow, Context};
 use clap::builder::ValueParser;
 use clap::{Parser, ValueHint};
 use config::keyassignment::{SpawnCommand, SpawnTabDomain};
-use config::{ConfigHandle, SerialDomain, SshDomain, SshMultiplexing};
+use config::{ConfigHandle, ExitBehavior, SerialDomain, SshDomain, SshMultiplexing};
 use mux::activity::Activity;
 use mux::domain::{Domain, LocalDomain};
 use mux::Mux;
@@ -36,27 +36,38 @@ use wezterm_toast_notification::*;
 
 mod colorease;
 mod commands;
+mod crashreport;
 mod customglyph;
+mod doctor;
 mod download;
 mod frontend;
 mod glyphcache;
 mod inputmap;
+mod invisible;
+mod keyconflicts;
+mod keypresets;
 mod overlay;
+mod pdfexport;
+mod power;
+mod print;
 mod quad;
 mod renderstate;
 mod resize_increment_calculator;
+mod screenlock;
 mod scripting;
 mod scrollbar;
 mod selection;
 mod shapecache;
 mod spawn;
 mod stats;
+mod suspicious;
 mod tabbar;
 mod termwindow;
 mod unicode_names;
 mod uniforms;
 mod update;
 mod utilsprites;
+mod whitespace;
 
 #[cfg(feature = "dhat-heap")]
 #[global_allocator]
@@ -131,6 +142,12 @@ enum SubCommand {
 
     #[command(name = "show-keys", about = "Show key assignments")]
     ShowKeys(ShowKeysCommand),
+
+    #[command(
+        name = "doctor",
+        about = "Check the configuration and environment for problems"
+    )]
+    Doctor(DoctorCommand),
 }
 
 async fn async_run_ssh(opts: SshCommand) -> anyhow::Result<()> {
@@ -206,6 +223,9 @@ async fn async_run_serial(opts: SerialCommand) -> anyhow::Result<()> {
         name: format!("Serial Port {}", opts.port),
         port: Some(opts.port.clone()),
         baud: opts.baud,
+        encoding: opts.encoding,
+        answerback: opts.answerback,
+        slow_paste: None,
     };
 
     let start_command = StartCommand {
@@ -286,6 +306,7 @@ async fn spawn_tab_in_domain_if_mux_is_empty(
     is_connecting: bool,
     domain: Option<Arc<dyn Domain>>,
     workspace: Option<String>,
+    exit_behavior: Option<ExitBehavior>,
 ) -> anyhow::Result<()> {
     let mux = Mux::get();
 
@@ -310,7 +331,7 @@ async fn spawn_tab_in_domain_if_mux_is_empty(
         *builder
     };
 
-    let config = config::configuration();
+    let config = window_rule_config();
     config.update_ulimit()?;
 
     domain.attach(Some(window_id)).await?;
@@ -336,6 +357,7 @@ async fn spawn_tab_in_domain_if_mux_is_empty(
             config.initial_size(dpi as u32, Some(cell_pixel_dims(&config, dpi)?)),
             cmd,
             None,
+            exit_behavior,
             window_id,
         )
         .await?;
@@ -420,6 +442,10 @@ async fn async_run_terminal_gui(
     if let Err(err) = spawn_mux_server(unix_socket_path, should_publish) {
         log::warn!("{:#}", err);
     }
+    if let Err(err) = wezterm_mux_server_impl::jsonrpc::spawn_rpc_listener(&config::configuration())
+    {
+        log::warn!("{:#}", err);
+    }
 
     if !opts.no_auto_connect {
         connect_to_auto_connect_domains().await?;
@@ -458,6 +484,7 @@ async fn async_run_terminal_gui(
     }
 
     let is_connecting = opts.attach;
+    let exit_behavior = opts.exit_behavior.map(Into::into);
 
     if let Some(domain) = &domain {
         if !opts.attach {
@@ -471,13 +498,14 @@ async fn async_run_terminal_gui(
             };
 
             domain.attach(Some(window_id)).await?;
-            let config = config::configuration();
+            let config = window_rule_config();
             let dpi = config.dpi.unwrap_or_else(|| ::window::default_dpi());
             let tab = domain
                 .spawn(
                     config.initial_size(dpi as u32, Some(cell_pixel_dims(&config, dpi)?)),
                     cmd.clone(),
                     None,
+                    exit_behavior,
                     window_id,
                 )
                 .await?;
@@ -490,7 +518,8 @@ async fn async_run_terminal_gui(
             trigger_and_log_gui_attached(MuxDomain(domain.domain_id())).await;
         }
     }
-    spawn_tab_in_domain_if_mux_is_empty(cmd, is_connecting, domain, opts.workspace).await
+    spawn_tab_in_domain_if_mux_is_empty(cmd, is_connecting, domain, opts.workspace, exit_behavior)
+        .await
 }
 
 #[derive(Debug)]
@@ -502,12 +531,16 @@ enum Publish {
 
 impl Publish {
     pub fn resolve(mux: &Arc<Mux>, config: &ConfigHandle, always_new_process: bool) -> Self {
-        if mux.default_domain().domain_name() != config.default_domain.as_deref().unwrap_or("local")
-        {
+        let default_domain_name = config
+            .gui_shared_mux_domain
+            .as_deref()
+            .or(config.default_domain.as_deref())
+            .unwrap_or("local");
+        if mux.default_domain().domain_name() != default_domain_name {
             return Self::NoConnectNoPublish;
         }
 
-        if always_new_process {
+        if always_new_process || !config.gui_single_instance {
             return Self::NoConnectNoPublish;
         }
 
@@ -570,7 +603,7 @@ impl Publish {
                             );
                         }
 
-                        let window_id = if new_tab || config.prefer_to_spawn_tabs {
+                        let window_id = if new_tab {
                             if let Ok(pane_id) = client.resolve_pane_id(None).await {
                                 let panes = client.list_panes().await?;
 
@@ -694,6 +727,7 @@ fn setup_mux(
     mux.set_active_workspace(&default_workspace_name);
     crate::update::load_last_release_info_and_set_banner();
     update_mux_domains(config)?;
+    mux.update_services(&config.services);
 
     let default_name =
         default_domain_name.unwrap_or(config.default_domain.as_deref().unwrap_or("local"));
@@ -718,15 +752,77 @@ fn build_initial_mux(
     setup_mux(domain, config, default_domain_name, default_workspace_name)
 }
 
-fn run_terminal_gui(opts: StartCommand, default_domain_name: Option<String>) -> anyhow::Result<()> {
+/// When gui_shared_mux_domain is configured, and no domain was
+/// explicitly requested, start the GUI as though `wezterm connect`
+/// had been used to connect to the shared domain.
+/// If a program was specified, it is spawned into a new window
+/// in that domain, otherwise we simply attach to it.
+fn apply_gui_shared_mux_domain(
+    config: &ConfigHandle,
+    opts: StartCommand,
+) -> (StartCommand, Option<String>) {
+    match &config.gui_shared_mux_domain {
+        Some(name) if opts.domain.is_none() => {
+            let attach = opts.prog.is_empty() && opts.cwd.is_none();
+            (
+                StartCommand {
+                    domain: Some(name.clone()),
+                    attach,
+                    ..opts
+                },
+                Some(name.clone()),
+            )
+        }
+        _ => (opts, None),
+    }
+}
+
+/// Returns the configuration with the config_overrides of the
+/// window rule that matches our window class applied, so that
+/// eg: the initial size and the default program respect the rule
+fn window_rule_config() -> ConfigHandle {
+    let config = config::configuration();
+    match config.window_rule_for_class(&crate::termwindow::get_window_class()) {
+        Some(rule) => match config::overridden_config(&rule.config_overrides) {
+            Ok(overridden) => overridden,
+            Err(err) => {
+                log::error!(
+                    "Failed to apply window rule for class {}: {:#}",
+                    rule.class,
+                    err
+                );
+                config
+            }
+        },
+        None => config,
+    }
+}
+
+fn run_terminal_gui(
+    mut opts: StartCommand,
+    default_domain_name: Option<String>,
+) -> anyhow::Result<()> {
     if let Some(cls) = opts.class.as_ref() {
         crate::set_window_class(cls);
     }
+    if let Some(role) = opts.role.as_ref() {
+        ::window::set_window_role(role);
+    }
+
+    let config = window_rule_config();
+    if let Some(rule) = config.window_rule_for_class(&crate::termwindow::get_window_class()) {
+        if opts.workspace.is_none() {
+            opts.workspace = rule.workspace.clone();
+        }
+        if opts.position.is_none() {
+            if let Some(pos) = rule.position.as_ref() {
+                set_window_position(pos.clone());
+            }
+        }
+    }
     if let Some(pos) = opts.position.as_ref() {
         set_window_position(pos.clone());
     }
-
-    let config = config::configuration();
     let need_builder = !opts.prog.is_empty() || opts.cwd.is_some();
 
     let cmd = if need_builder {
@@ -760,7 +856,10 @@ fn run_terminal_gui(opts: StartCommand, default_domain_name: Option<String>) ->
     let mut publish = Publish::resolve(
         &mux,
         &config,
-        opts.always_new_process || opts.position.is_some(),
+        opts.always_new_process
+            || opts.position.is_some()
+            || opts.role.is_some()
+            || opts.exit_behavior.is_some(),
     );
     log::trace!("{:?}", publish);
     if publish.try_spawn(
@@ -771,7 +870,7 @@ fn run_terminal_gui(opts: StartCommand, default_domain_name: Option<String>) ->
             Some(name) => SpawnTabDomain::DomainName(name.to_string()),
             None => SpawnTabDomain::DefaultDomain,
         },
-        opts.new_tab,
+        opts.new_tab || (config.prefer_to_spawn_tabs && !opts.new_window),
     )? {
         return Ok(());
     }
@@ -802,7 +901,22 @@ fn fatal_toast_notification(title: &str, message: &str) {
 fn notify_on_panic() {
     let default_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
-        if let Some(s) = info.payload().downcast_ref::<&str>() {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()));
+        let location = info
+            .location()
+            .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column()));
+
+        if let Some(report) = crashreport::report_panic(message.unwrap_or("!?"), location) {
+            crashreport::show_report_notification("Wezterm crashed", &report);
+            // We need a short delay otherwise the notification
+            // will not show
+            #[cfg(windows)]
+            std::thread::sleep(std::time::Duration::new(2, 0));
+        } else if let Some(s) = payload.downcast_ref::<&str>() {
             fatal_toast_notification("Wezterm panic", s);
         }
         default_hook(info);
@@ -851,7 +965,18 @@ fn maybe_show_configuration_error_window() {
 
 fn run_show_keys(config: config::ConfigHandle, cmd: &ShowKeysCommand) -> anyhow::Result<()> {
     let map = crate::inputmap::InputMap::new(&config);
-    if cmd.lua {
+    if cmd.conflicts {
+        let platform = cmd
+            .platform
+            .unwrap_or_else(crate::keyconflicts::current_platform);
+        let conflicts = crate::keyconflicts::find_conflicts(&config, &map, platform);
+        match cmd.format {
+            ShowKeysFormat::Table => crate::keyconflicts::show_conflicts(&conflicts),
+            ShowKeysFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&conflicts)?);
+            }
+        }
+    } else if cmd.lua {
         map.dump_config(cmd.key_table.as_deref());
     } else {
         map.show_keys();
@@ -1250,7 +1375,8 @@ fn run() -> anyhow::Result<()> {
     match sub {
         SubCommand::Start(start) => {
             log::trace!("Using configuration: {:#?}\nopts: {:#?}", config, opts);
-            let res = run_terminal_gui(start, None);
+            let (start, default_domain_name) = apply_gui_shared_mux_domain(&config, start);
+            let res = run_terminal_gui(start, default_domain_name);
             wezterm_blob_leases::clear_storage();
             res
         }
@@ -1261,19 +1387,23 @@ fn run() -> anyhow::Result<()> {
             StartCommand {
                 domain: Some(connect.domain_name.clone()),
                 class: connect.class,
+                role: None,
                 workspace: connect.workspace,
                 position: connect.position,
                 prog: connect.prog,
                 new_tab: connect.new_tab,
+                new_window: false,
                 always_new_process: true,
                 attach: true,
                 _cmd: false,
                 no_auto_connect: false,
                 cwd: None,
+                exit_behavior: None,
             },
             Some(connect.domain_name),
         ),
         SubCommand::LsFonts(cmd) => run_ls_fonts(config, &cmd),
         SubCommand::ShowKeys(cmd) => run_show_keys(config, &cmd),
+        SubCommand::Doctor(cmd) => crate::doctor::run_doctor(config, &cmd),
     }
 }
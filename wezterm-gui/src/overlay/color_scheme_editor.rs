@@ -0,0 +1,511 @@
+//! The color scheme editor overlay allows adjusting the colors of the
+//! active palette while previewing them in the window.
+//! The edited palette can be exported to the `colors` directory in the
+//! config directory, either as a TOML color scheme file or as a Lua
+//! snippet that can be assigned to `config.colors`.
+//! The preview is discarded when the editor is closed.
+use crate::termwindow::{TermWindow, TermWindowNotif};
+use ::window::Window;
+use config::{ColorSchemeFile, ColorSchemeMetaData, Palette, RgbaColor, SrgbaTuple};
+use mux::termwiztermtab::TermWizTerminal;
+use std::path::PathBuf;
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+use wezterm_term::color::ColorPalette;
+
+const NAMED: [&str; 7] = [
+    "foreground",
+    "background",
+    "cursor_fg",
+    "cursor_bg",
+    "cursor_border",
+    "selection_fg",
+    "selection_bg",
+];
+const ANSI: [&str; 8] = [
+    "black", "maroon", "green", "olive", "navy", "purple", "teal", "silver",
+];
+const BRIGHTS: [&str; 8] = [
+    "grey", "red", "lime", "yellow", "blue", "fuchsia", "aqua", "white",
+];
+const NUM_ENTRIES: usize = NAMED.len() + ANSI.len() + BRIGHTS.len();
+const CHANNELS: [&str; 3] = ["red", "green", "blue"];
+
+/// The status line, the sample line and the input line
+const ROW_OVERHEAD: usize = 3;
+
+/// Returns `palette` with any of the colors that it leaves unspecified
+/// filled in from the defaults, so that every entry can be edited
+pub fn complete_palette(palette: &Palette) -> Palette {
+    let mut complete = Palette::from(ColorPalette::default()).overlay_with(palette);
+    // Don't export the whole of the default 256 color palette
+    complete.indexed = palette.indexed.clone();
+    complete
+}
+
+fn entry_name(idx: usize) -> String {
+    if idx < NAMED.len() {
+        NAMED[idx].to_string()
+    } else if idx < NAMED.len() + ANSI.len() {
+        let ansi = idx - NAMED.len();
+        format!("ansi[{}] {}", ansi + 1, ANSI[ansi])
+    } else {
+        let bright = idx - NAMED.len() - ANSI.len();
+        format!("brights[{}] {}", bright + 1, BRIGHTS[bright])
+    }
+}
+
+fn get_color(palette: &Palette, idx: usize) -> SrgbaTuple {
+    let color = match idx {
+        0 => palette.foreground,
+        1 => palette.background,
+        2 => palette.cursor_fg,
+        3 => palette.cursor_bg,
+        4 => palette.cursor_border,
+        5 => palette.selection_fg,
+        6 => palette.selection_bg,
+        idx if idx < NAMED.len() + ANSI.len() => palette.ansi.map(|a| a[idx - NAMED.len()]),
+        idx => palette.brights.map(|b| b[idx - NAMED.len() - ANSI.len()]),
+    };
+    color.map(|c| *c).unwrap_or_default()
+}
+
+fn set_color(palette: &mut Palette, idx: usize, color: SrgbaTuple) {
+    let color = Some(RgbaColor::from(color));
+    match idx {
+        0 => palette.foreground = color,
+        1 => palette.background = color,
+        2 => palette.cursor_fg = color,
+        3 => palette.cursor_bg = color,
+        4 => palette.cursor_border = color,
+        5 => palette.selection_fg = color,
+        6 => palette.selection_bg = color,
+        idx if idx < NAMED.len() + ANSI.len() => {
+            if let (Some(ansi), Some(color)) = (palette.ansi.as_mut(), color) {
+                ansi[idx - NAMED.len()] = color;
+            }
+        }
+        idx => {
+            if let (Some(brights), Some(color)) = (palette.brights.as_mut(), color) {
+                brights[idx - NAMED.len() - ANSI.len()] = color;
+            }
+        }
+    }
+}
+
+/// Adds `delta` to the specified channel of `color`
+fn adjust_channel(color: SrgbaTuple, channel: usize, delta: i16) -> SrgbaTuple {
+    let (r, g, b, _) = color.to_srgb_u8();
+    let mut rgb = [r, g, b];
+    rgb[channel] = (rgb[channel] as i16 + delta).clamp(0, 255) as u8;
+    let adjusted: SrgbaTuple = (rgb[0], rgb[1], rgb[2]).into();
+    SrgbaTuple(adjusted.0, adjusted.1, adjusted.2, color.3)
+}
+
+/// Returns the name for an exported scheme, along with the
+/// stem of the file name to use for it
+fn export_name() -> (String, String) {
+    let now = chrono::Local::now();
+    (
+        format!("Custom {}", now.format("%Y-%m-%d %H:%M:%S")),
+        format!("custom-{}", now.format("%Y%m%d-%H%M%S")),
+    )
+}
+
+fn colors_dir() -> anyhow::Result<PathBuf> {
+    let dir = config::CONFIG_DIRS
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no config directory"))?
+        .join("colors");
+    config::create_user_owned_dirs(&dir)?;
+    Ok(dir)
+}
+
+/// Saves the palette as a color scheme file that can be selected
+/// using `config.color_scheme`
+pub fn export_toml(palette: &Palette) -> anyhow::Result<PathBuf> {
+    let (name, stem) = export_name();
+    let path = colors_dir()?.join(format!("{stem}.toml"));
+    let scheme = ColorSchemeFile {
+        colors: palette.clone(),
+        metadata: ColorSchemeMetaData {
+            name: Some(name),
+            wezterm_version: Some(config::wezterm_version().to_string()),
+            ..Default::default()
+        },
+    };
+    scheme.save_to_file(&path)?;
+    Ok(path)
+}
+
+/// Produces a Lua snippet that evaluates to a table of the edited
+/// colors, suitable for assigning to `config.colors`
+pub fn palette_to_lua(palette: &Palette) -> String {
+    let quoted = |idx: usize| format!("'{}'", get_color(palette, idx).to_rgb_string());
+    let mut lua = String::from("-- Generated by the wezterm color scheme editor.\n");
+    lua.push_str("-- Use it in your config with:\n");
+    lua.push_str("-- config.colors = dofile(wezterm.config_dir .. '/colors/FILE.lua')\n");
+    lua.push_str("return {\n");
+    for (idx, name) in NAMED.iter().enumerate() {
+        lua.push_str(&format!("  {} = {},\n", name, quoted(idx)));
+    }
+    for (key, first, len) in [
+        ("ansi", NAMED.len(), ANSI.len()),
+        ("brights", NAMED.len() + ANSI.len(), BRIGHTS.len()),
+    ] {
+        let colors: Vec<String> = (first..first + len).map(quoted).collect();
+        lua.push_str(&format!("  {} = {{ {} }},\n", key, colors.join(", ")));
+    }
+    lua.push_str("}\n");
+    lua
+}
+
+pub fn export_lua(palette: &Palette) -> anyhow::Result<PathBuf> {
+    let (_name, stem) = export_name();
+    let path = colors_dir()?.join(format!("{stem}.lua"));
+    std::fs::write(&path, palette_to_lua(palette))?;
+    Ok(path)
+}
+
+struct EditorState {
+    palette: Palette,
+    window: Window,
+    active_idx: usize,
+    channel: usize,
+    top_row: usize,
+    max_rows: usize,
+    /// The hex text being typed, if any
+    input: Option<String>,
+    status: String,
+}
+
+impl EditorState {
+    fn preview(&self) {
+        let palette = self.palette.clone();
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                term_window.set_color_scheme_preview(Some(palette));
+            })));
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let idx = (self.active_idx as isize + delta).max(0) as usize;
+        self.active_idx = idx.min(NUM_ENTRIES - 1);
+        if self.active_idx < self.top_row {
+            self.top_row = self.active_idx;
+        } else if self.active_idx >= self.top_row + self.max_rows {
+            self.top_row = self.active_idx + 1 - self.max_rows;
+        }
+    }
+
+    fn adjust(&mut self, delta: i16) {
+        let color = get_color(&self.palette, self.active_idx);
+        let color = adjust_channel(color, self.channel, delta);
+        set_color(&mut self.palette, self.active_idx, color);
+        self.preview();
+    }
+
+    fn apply_input(&mut self) {
+        let text = match self.input.take() {
+            Some(text) => text,
+            None => return,
+        };
+        let text = text.trim();
+        let text = if text.starts_with('#') {
+            text.to_string()
+        } else {
+            format!("#{text}")
+        };
+        match text.parse::<SrgbaTuple>() {
+            Ok(color) => {
+                set_color(&mut self.palette, self.active_idx, color);
+                self.preview();
+            }
+            Err(()) => {
+                self.status = format!("{text} is not a valid color");
+            }
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+        self.max_rows = size.rows.saturating_sub(ROW_OVERHEAD).max(1);
+        self.move_by(0);
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            AttributeChange::Italic(true).into(),
+            Change::Text(truncate_right(&self.status, max_width)),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+        ];
+
+        // The sample uses the palette of the window, so that it shows
+        // the live preview
+        for idx in 0..16u8 {
+            changes.push(AttributeChange::Foreground(ColorAttribute::PaletteIndex(idx)).into());
+            changes.push(Change::Text(format!(" {idx:X}")));
+        }
+        changes.push(AttributeChange::Foreground(ColorAttribute::Default).into());
+        changes.push(AttributeChange::Reverse(true).into());
+        changes.push(Change::Text(" selected ".to_string()));
+        changes.push(Change::AllAttributes(CellAttributes::default()));
+        changes.push(Change::Text("\r\n".to_string()));
+
+        for idx in (0..NUM_ENTRIES).skip(self.top_row).take(self.max_rows) {
+            let color = get_color(&self.palette, idx);
+            let active = idx == self.active_idx;
+            changes.push(
+                AttributeChange::Background(ColorAttribute::TrueColorWithDefaultFallback(color))
+                    .into(),
+            );
+            changes.push(Change::Text("    ".to_string()));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            if active {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+            let (r, g, b, _) = color.to_srgb_u8();
+            let mut label = format!(" {:<20} {} ", entry_name(idx), color.to_rgb_string());
+            if active {
+                let values = [r, g, b];
+                for (channel, name) in CHANNELS.iter().enumerate() {
+                    if channel == self.channel {
+                        label.push_str(&format!(" [{name} {:3}]", values[channel]));
+                    } else {
+                        label.push_str(&format!("  {name} {:3} ", values[channel]));
+                    }
+                }
+            }
+            changes.push(Change::Text(truncate_right(
+                &label,
+                max_width.saturating_sub(4),
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(size.rows.saturating_sub(1)),
+        });
+        let prompt = match &self.input {
+            Some(text) => format!("Color: {text}"),
+            None => "Enter: type a color, +/-: adjust, CTRL-t: save TOML, \
+                     CTRL-l: save Lua, Escape: close"
+                .to_string(),
+        };
+        changes.push(AttributeChange::Foreground(AnsiColor::Grey.into()).into());
+        changes.push(Change::Text(truncate_right(&prompt, max_width)));
+        changes.push(Change::AllAttributes(CellAttributes::default()));
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<()> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            if let Some(input) = self.input.as_mut() {
+                match event {
+                    InputEvent::Key(KeyEvent {
+                        key: KeyCode::Escape,
+                        ..
+                    }) => {
+                        self.input.take();
+                    }
+                    InputEvent::Key(KeyEvent {
+                        key: KeyCode::Enter,
+                        ..
+                    }) => {
+                        self.apply_input();
+                    }
+                    InputEvent::Key(KeyEvent {
+                        key: KeyCode::Backspace,
+                        ..
+                    }) => {
+                        input.pop();
+                    }
+                    InputEvent::Key(KeyEvent {
+                        key: KeyCode::Char(c),
+                        modifiers: Modifiers::NONE | Modifiers::SHIFT,
+                    }) => {
+                        input.push(c);
+                    }
+                    _ => {}
+                }
+                self.render(term)?;
+                continue;
+            }
+
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) => {
+                    return Ok(());
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    self.input.replace(String::new());
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('T' | 't'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.status = match export_toml(&self.palette) {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(err) => format!("Failed to save: {err:#}"),
+                    };
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('L' | 'l'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.status = match export_lua(&self.palette) {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(err) => format!("Failed to save: {err:#}"),
+                    };
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('k'),
+                    modifiers: Modifiers::NONE,
+                }) => {
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('j'),
+                    modifiers: Modifiers::NONE,
+                }) => {
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::LeftArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('h'),
+                    modifiers: Modifiers::NONE,
+                }) => {
+                    self.channel = (self.channel + CHANNELS.len() - 1) % CHANNELS.len();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::RightArrow | KeyCode::Tab,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('l'),
+                    modifiers: Modifiers::NONE,
+                }) => {
+                    self.channel = (self.channel + 1) % CHANNELS.len();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('+' | '='),
+                    ..
+                }) => {
+                    self.adjust(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('-'),
+                    ..
+                }) => {
+                    self.adjust(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => {
+                    self.adjust(16);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => {
+                    self.adjust(-16);
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn color_scheme_editor(
+    mut term: TermWizTerminal,
+    window: Window,
+    palette: Palette,
+) -> anyhow::Result<()> {
+    let mut state = EditorState {
+        palette,
+        window,
+        active_idx: 0,
+        channel: 0,
+        top_row: 0,
+        max_rows: 0,
+        input: None,
+        status: "Up/Down: choose a color, Left/Right: choose a channel, \
+                 PageUp/PageDown: adjust by 16"
+            .to_string(),
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Color Scheme Editor".to_string())])?;
+    state.preview();
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
+
+impl TermWindow {
+    /// Sets the palette that the color scheme editor is previewing,
+    /// which takes precedence over the configured colors
+    pub fn set_color_scheme_preview(&mut self, palette: Option<Palette>) {
+        if self.color_scheme_preview != palette {
+            self.color_scheme_preview = palette;
+            self.config_was_reloaded();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edit_entries() {
+        let mut palette = complete_palette(&Palette::default());
+        let red: SrgbaTuple = (0xff, 0, 0).into();
+        set_color(&mut palette, NAMED.len() + 1, red);
+        assert_eq!(palette.ansi.unwrap()[1], RgbaColor::from(red));
+        assert_eq!(get_color(&palette, NAMED.len() + 1), red);
+
+        let adjusted = adjust_channel(red, 1, 16);
+        assert_eq!(adjusted.to_srgb_u8(), (0xff, 0x10, 0, 0xff));
+        let adjusted = adjust_channel(red, 0, 16);
+        assert_eq!(adjusted.to_srgb_u8(), (0xff, 0, 0, 0xff));
+
+        assert_eq!(entry_name(0), "foreground");
+        assert_eq!(entry_name(NUM_ENTRIES - 1), "brights[8] white");
+    }
+}
@@ -0,0 +1,437 @@
+//! The command journal overlay lists the commands that have recently
+//! been run in any pane of the active workspace, as reported by shell
+//! integration, along with their exit status.
+//! A command can be re-run in the pane where it originally ran, or
+//! copied to the clipboard.
+use mux::pane::{Pane, PaneId};
+use mux::termwiztermtab::TermWizTerminal;
+use mux::Mux;
+use std::io::Write;
+use std::sync::Arc;
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+use url::Url;
+use wezterm_term::CommandRecord;
+
+/// The filter line and the status line
+const ROW_OVERHEAD: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub pane_id: PaneId,
+    pub host: String,
+    pub record: CommandRecord,
+}
+
+impl JournalEntry {
+    fn status(&self) -> (String, AnsiColor) {
+        match (self.record.finished, self.record.exit_code) {
+            (None, _) => ("running".to_string(), AnsiColor::Yellow),
+            (Some(_), Some(0)) => ("ok".to_string(), AnsiColor::Green),
+            (Some(_), Some(code)) => (format!("exit {code}"), AnsiColor::Red),
+            (Some(_), None) => ("done".to_string(), AnsiColor::Grey),
+        }
+    }
+
+    fn matches(&self, filter: &Filter) -> bool {
+        if let Some(pane_id) = filter.pane_id {
+            if self.pane_id != pane_id {
+                return false;
+            }
+        }
+        if let Some(host) = &filter.host {
+            if !self.host.to_lowercase().contains(host) {
+                return false;
+            }
+        }
+        let command = self.record.command.to_lowercase();
+        filter.words.iter().all(|word| command.contains(word))
+    }
+}
+
+/// What to do with the chosen command once the overlay has closed
+pub enum JournalAction {
+    Rerun(JournalEntry),
+    Copy(JournalEntry),
+}
+
+/// The parsed form of the filter text.
+/// `pane:ID` and `host:NAME` restrict the listing to a pane or
+/// host, and any remaining words must appear in the command.
+#[derive(Default)]
+struct Filter {
+    pane_id: Option<PaneId>,
+    host: Option<String>,
+    words: Vec<String>,
+}
+
+impl Filter {
+    fn parse(text: &str) -> Self {
+        let mut filter = Self::default();
+        for word in text.split_whitespace() {
+            let word = word.to_lowercase();
+            if let Some(id) = word.strip_prefix("pane:") {
+                if let Ok(id) = id.parse() {
+                    filter.pane_id.replace(id);
+                    continue;
+                }
+            }
+            if let Some(host) = word.strip_prefix("host:") {
+                filter.host.replace(host.to_string());
+                continue;
+            }
+            filter.words.push(word);
+        }
+        filter
+    }
+}
+
+/// Collects the command history of each pane in `workspace`,
+/// most recent first.
+/// Must be called on the main thread.
+pub async fn collect_journal(workspace: String) -> Vec<JournalEntry> {
+    let mux = Mux::get();
+    let mut panes: Vec<Arc<dyn Pane>> = vec![];
+    for window_id in mux.iter_windows_in_workspace(&workspace) {
+        if let Some(window) = mux.get_window(window_id) {
+            for tab in window.iter() {
+                for pos in tab.iter_panes_ignoring_zoom() {
+                    panes.push(pos.pane);
+                }
+            }
+        }
+    }
+
+    let mut entries = vec![];
+    for pane in panes {
+        let commands = match pane.get_command_history().await {
+            Ok(commands) => commands,
+            Err(err) => {
+                log::error!(
+                    "fetching command history of pane {}: {:#}",
+                    pane.pane_id(),
+                    err
+                );
+                continue;
+            }
+        };
+        let domain = mux
+            .get_domain(pane.domain_id())
+            .map(|domain| domain.domain_name().to_string())
+            .unwrap_or_default();
+
+        for record in commands {
+            // Prefer the host reported by the shell via OSC 7, as
+            // that reflects eg: ssh sessions started from the pane
+            let host = record
+                .cwd
+                .as_deref()
+                .and_then(|cwd| Url::parse(cwd).ok())
+                .and_then(|url| url.host_str().map(|h| h.to_string()))
+                .filter(|host| !host.is_empty())
+                .unwrap_or_else(|| domain.clone());
+            entries.push(JournalEntry {
+                pane_id: pane.pane_id(),
+                host,
+                record,
+            });
+        }
+    }
+    entries.sort_by(|a, b| b.record.started.cmp(&a.record.started));
+    entries
+}
+
+struct JournalState {
+    entries: Vec<JournalEntry>,
+    filter_text: String,
+    filtered: Vec<usize>,
+    active_idx: usize,
+    top_row: usize,
+    max_rows: usize,
+}
+
+impl JournalState {
+    fn update_filter(&mut self) {
+        let filter = Filter::parse(&self.filter_text);
+        self.filtered = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.matches(&filter))
+            .map(|(idx, _)| idx)
+            .collect();
+        self.active_idx = 0;
+        self.top_row = 0;
+    }
+
+    fn active_entry(&self) -> Option<JournalEntry> {
+        self.filtered
+            .get(self.active_idx)
+            .map(|&idx| self.entries[idx].clone())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.filtered.is_empty() {
+            return;
+        }
+        let idx = (self.active_idx as isize + delta).max(0) as usize;
+        self.active_idx = idx.min(self.filtered.len() - 1);
+        if self.active_idx < self.top_row {
+            self.top_row = self.active_idx;
+        } else if self.active_idx >= self.top_row + self.max_rows {
+            self.top_row = self.active_idx + 1 - self.max_rows;
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+        self.max_rows = size.rows.saturating_sub(ROW_OVERHEAD).max(1);
+
+        let status = if self.entries.is_empty() {
+            "No commands have been reported; this requires shell integration".to_string()
+        } else {
+            format!(
+                "{} of {} commands.  Enter to re-run, CTRL-y to copy, \
+                 filter with pane:ID and host:NAME",
+                self.filtered.len(),
+                self.entries.len()
+            )
+        };
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            AttributeChange::Italic(true).into(),
+            Change::Text(truncate_right(&status, max_width)),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+        ];
+
+        for (row_idx, &entry_idx) in self
+            .filtered
+            .iter()
+            .enumerate()
+            .skip(self.top_row)
+            .take(self.max_rows)
+        {
+            let entry = &self.entries[entry_idx];
+            let active = row_idx == self.active_idx;
+            if active {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+
+            let time = chrono::DateTime::<chrono::Local>::from(entry.record.started)
+                .format("%H:%M:%S")
+                .to_string();
+            let (status, color) = entry.status();
+            changes.push(Change::Text(format!("{time} ")));
+            changes.push(AttributeChange::Foreground(color.into()).into());
+            changes.push(Change::Text(format!("{status:>8}")));
+            changes.push(AttributeChange::Foreground(ColorAttribute::Default).into());
+
+            let command = entry.record.command.replace('\n', " ⏎ ");
+            let label = format!(" {} pane {:<3} {}", entry.host, entry.pane_id, command,);
+            changes.push(Change::Text(truncate_right(
+                &label,
+                max_width.saturating_sub(time.len() + 9),
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(size.rows.saturating_sub(1)),
+        });
+        changes.push(Change::Text(truncate_right(
+            &format!("Filter: {}", self.filter_text),
+            max_width,
+        )));
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<Option<JournalAction>> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) => {
+                    return Ok(None);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    if let Some(entry) = self.active_entry() {
+                        return Ok(Some(JournalAction::Rerun(entry)));
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('Y' | 'y'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    if let Some(entry) = self.active_entry() {
+                        return Ok(Some(JournalAction::Copy(entry)));
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('P' | 'K'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('N' | 'J'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => {
+                    self.move_by(-(self.max_rows as isize));
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => {
+                    self.move_by(self.max_rows as isize);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Backspace,
+                    ..
+                }) => {
+                    self.filter_text.pop();
+                    self.update_filter();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('U'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.filter_text.clear();
+                    self.update_filter();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char(c),
+                    modifiers: Modifiers::NONE | Modifiers::SHIFT,
+                }) => {
+                    self.filter_text.push(c);
+                    self.update_filter();
+                }
+                InputEvent::Mouse(MouseEvent {
+                    y, mouse_buttons, ..
+                }) => {
+                    if mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                        if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                            self.move_by(-1);
+                        } else {
+                            self.move_by(1);
+                        }
+                    } else if mouse_buttons == MouseButtons::LEFT && y > 0 {
+                        let idx = self.top_row + y as usize - 1;
+                        if idx < self.filtered.len() && idx < self.top_row + self.max_rows {
+                            self.active_idx = idx;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(None)
+    }
+}
+
+pub fn command_journal(
+    mut term: TermWizTerminal,
+    workspace: String,
+) -> anyhow::Result<Option<JournalAction>> {
+    // Pane::get_command_history isn't Send, so it has to be spawned
+    // locally once we're on the main thread
+    let entries = smol::block_on(promise::spawn::spawn_into_main_thread(async move {
+        promise::spawn::spawn(collect_journal(workspace)).await
+    }));
+
+    let mut state = JournalState {
+        entries,
+        filter_text: String::new(),
+        filtered: vec![],
+        active_idx: 0,
+        top_row: 0,
+        max_rows: 0,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Command Journal".to_string())])?;
+    state.update_filter();
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
+
+/// Re-runs the command in the pane where it originally ran,
+/// activating that pane.  Must be called on the main thread.
+pub fn rerun_command(entry: &JournalEntry) -> anyhow::Result<()> {
+    let mux = Mux::get();
+    let pane = mux
+        .get_pane(entry.pane_id)
+        .ok_or_else(|| anyhow::anyhow!("pane {} no longer exists", entry.pane_id))?;
+    mux.focus_pane_and_containing_tab(entry.pane_id)?;
+    pane.send_paste(&entry.record.command)?;
+    pane.writer().write_all(b"\r")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn entry(pane_id: PaneId, host: &str, command: &str) -> JournalEntry {
+        JournalEntry {
+            pane_id,
+            host: host.to_string(),
+            record: CommandRecord {
+                command: command.to_string(),
+                output_row: 0,
+                cwd: None,
+                started: SystemTime::now(),
+                finished: None,
+                exit_code: None,
+            },
+        }
+    }
+
+    #[test]
+    fn filter() {
+        let filter = Filter::parse("pane:2 host:Build make");
+        assert!(entry(2, "buildbox", "make -j8").matches(&filter));
+        assert!(!entry(3, "buildbox", "make -j8").matches(&filter));
+        assert!(!entry(2, "laptop", "make -j8").matches(&filter));
+        assert!(!entry(2, "buildbox", "cargo build").matches(&filter));
+        assert!(entry(3, "laptop", "ls").matches(&Filter::parse("")));
+    }
+}
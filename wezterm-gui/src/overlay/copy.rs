@@ -1846,7 +1846,9 @@ pub fn copy_key_table() -> KeyTable {
             WKeyCode::Char('y'),
             Modifiers::NONE,
             KeyAssignment::Multiple(vec![
-                KeyAssignment::CopyTo(ClipboardCopyDestination::ClipboardAndPrimarySelection),
+                KeyAssignment::CopyTo(
+                    ClipboardCopyDestination::ClipboardAndPrimarySelection.into(),
+                ),
                 KeyAssignment::CopyMode(CopyModeAssignment::Close),
             ]),
         ),
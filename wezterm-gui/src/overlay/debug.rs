@@ -1,10 +1,12 @@
 use crate::scripting::guiwin::GuiWin;
 use chrono::prelude::*;
-use futures::FutureExt;
 use log::Level;
 use luahelper::ValuePrinter;
-use mlua::Value;
+use mlua::{Table, Value};
+use mux::pane::PaneId;
 use mux::termwiztermtab::TermWizTerminal;
+use mux::Mux;
+use mux_lua::MuxPane;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -19,6 +21,9 @@ lazy_static::lazy_static! {
     static ref LATEST_LOG_ENTRY: Mutex<Option<DateTime<Local>>> = Mutex::new(None);
 }
 
+/// The number of lines of REPL history that are retained between sessions
+const MAX_HISTORY: usize = 1000;
+
 struct LuaReplHost {
     history: BasicHistory,
     lua: mlua::Lua,
@@ -32,7 +37,16 @@ impl LuaReplHost {
     fn new(lua: mlua::Lua) -> Self {
         let mut history = BasicHistory::default();
         if let Ok(data) = std::fs::read_to_string(history_file_name()) {
-            for line in data.lines() {
+            let mut lines: Vec<&str> = data.lines().filter(|line| !line.is_empty()).collect();
+            lines.dedup();
+            if lines.len() > MAX_HISTORY {
+                lines.drain(0..lines.len() - MAX_HISTORY);
+                // Keep the file from growing without bound
+                let mut trimmed = lines.join("\n");
+                trimmed.push('\n');
+                std::fs::write(history_file_name(), trimmed).ok();
+            }
+            for line in lines {
                 history.add(line);
             }
         }
@@ -117,6 +131,29 @@ impl LineEditorHost for LuaReplHost {
         }
     }
 
+    fn complete(&self, line: &str, cursor_position: usize) -> Vec<CompletionCandidate> {
+        let (path, range) = match completion_context(line, cursor_position) {
+            Some(context) => context,
+            None => return vec![],
+        };
+        let partial = &line[range.clone()];
+
+        let mut names: Vec<String> = completion_names(&self.lua, &path)
+            .into_iter()
+            .filter(|name| name.starts_with(partial))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|text| CompletionCandidate {
+                range: range.clone(),
+                text,
+            })
+            .collect()
+    }
+
     fn render_preview(&self, line: &str) -> Vec<OutputElement> {
         let mut preview = vec![];
 
@@ -128,9 +165,166 @@ impl LineEditorHost for LuaReplHost {
     }
 }
 
+/// Figures out what is being completed at the cursor position.
+/// Returns the chain of names that lead up to the word at the cursor,
+/// such as `["wezterm", "mux"]` for `wezterm.mux.get_`, along with the
+/// range of the partially typed word.
+fn completion_context(
+    line: &str,
+    cursor_position: usize,
+) -> Option<(Vec<String>, std::ops::Range<usize>)> {
+    let before = line.get(..cursor_position)?;
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let expr_start = before
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c) || c == '.' || c == ':')
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(cursor_position);
+    let word_start = before
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(cursor_position);
+
+    let mut path = vec![];
+    if word_start > expr_start {
+        // Drop the trailing separator; method calls with `:` look
+        // up their names in the same place as fields
+        let prefix = &before[expr_start..word_start - 1];
+        for name in prefix.split(|c| c == '.' || c == ':') {
+            if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            path.push(name.to_string());
+        }
+    }
+
+    Some((path, word_start..cursor_position))
+}
+
+/// Returns the string keys of a table
+fn table_keys(table: &Table) -> Vec<String> {
+    table
+        .clone()
+        .pairs::<Value, Value>()
+        .filter_map(|pair| match pair {
+            Ok((Value::String(key), _)) => key.to_str().ok().map(|s| s.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the names that are available on a lua value.
+/// Userdata methods can only be listed when they are exposed
+/// via a table, which is the case for the wezterm types
+/// that don't also have fields.
+fn completion_names(lua: &mlua::Lua, path: &[String]) -> Vec<String> {
+    let mut value = Value::Table(lua.globals());
+    for name in path {
+        value = match value {
+            Value::Table(table) => match table.get(name.as_str()) {
+                Ok(value) => value,
+                Err(_) => return vec![],
+            },
+            _ => return vec![],
+        };
+    }
+
+    match value {
+        Value::Table(table) => table_keys(&table),
+        Value::UserData(ud) => match ud.get_metatable().and_then(|mt| mt.get::<Value>("__index")) {
+            Ok(Value::Table(methods)) => table_keys(&methods),
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+/// The methods that are called to describe a wezterm object when
+/// it is the result of an expression.  These are all free of side
+/// effects.
+fn inspect_methods(type_name: &str) -> &'static [&'static str] {
+    match type_name {
+        "MuxPane" => &[
+            "get_title",
+            "get_domain_name",
+            "get_current_working_dir",
+            "get_foreground_process_name",
+            "get_tty_name",
+            "get_dimensions",
+            "get_cursor_position",
+            "is_alt_screen_active",
+            "has_unseen_output",
+            "get_user_vars",
+            "tab",
+            "window",
+        ],
+        "MuxTab" => &["get_title", "get_size", "active_pane", "window"],
+        "MuxWindow" => &["get_title", "get_workspace", "active_tab", "active_pane"],
+        "GuiWin" => &[
+            "get_dimensions",
+            "is_focused",
+            "get_appearance",
+            "active_workspace",
+            "active_key_table",
+            "leader_is_active",
+            "active_tab",
+            "active_pane",
+        ],
+        _ => &[],
+    }
+}
+
+/// Produces a multi-line description of a wezterm object, showing
+/// the values returned by its getter methods.
+/// Returns None if the value isn't one of the types that we know
+/// how to inspect.
+async fn inspect_userdata(lua: &mlua::Lua, value: &Value<'_>) -> Option<String> {
+    let ud = match value {
+        Value::UserData(ud) => ud,
+        _ => return None,
+    };
+    let label = value.to_string().ok()?;
+    let type_name = label.split('(').next().unwrap_or("");
+    let methods = inspect_methods(type_name);
+    if methods.is_empty() {
+        return None;
+    }
+
+    // Index via lua so that this works regardless of how the
+    // metatable is structured
+    let index: mlua::Function = lua
+        .load("return function(obj, name) return obj[name] end")
+        .eval()
+        .ok()?;
+
+    let mut result = format!("{label} {{\n");
+    for name in methods {
+        let text = match index.call::<_, mlua::Function>((ud.clone(), *name)) {
+            Ok(method) => match method.call_async::<_, Value>(ud.clone()).await {
+                Ok(value) => format!("{:#?}", ValuePrinter(value)),
+                Err(err) => format!("error: {err:#}"),
+            },
+            Err(_) => continue,
+        };
+        result.push_str(&format!(
+            "    {name}() = {},\n",
+            text.replace("\n", "\n    ")
+        ));
+    }
+    result.push('}');
+    Some(result)
+}
+
 pub fn show_debug_overlay(
     mut term: TermWizTerminal,
     gui_win: GuiWin,
+    pane_id: Option<PaneId>,
     opengl_info: String,
     connection_info: String,
 ) -> anyhow::Result<()> {
@@ -149,6 +343,23 @@ pub fn show_debug_overlay(
 
     lua.load("wezterm = require 'wezterm'").exec()?;
     lua.globals().set("window", gui_win)?;
+    if let Some(pane_id) = pane_id {
+        lua.globals().set("pane", MuxPane(pane_id))?;
+    }
+    lua.globals().set(
+        "target_pane",
+        lua.create_function(|lua, pane_id: PaneId| {
+            let mux = Mux::try_get().ok_or_else(|| mlua::Error::external("no mux"))?;
+            if mux.get_pane(pane_id).is_none() {
+                return Err(mlua::Error::external(format!(
+                    "pane id {pane_id} not found"
+                )));
+            }
+            let pane = MuxPane(pane_id);
+            lua.globals().set("pane", pane)?;
+            Ok(pane)
+        })?,
+    )?;
     let lua_version: String = lua.globals().get("_VERSION")?;
 
     let mut host = Some(LuaReplHost::new(lua));
@@ -195,6 +406,7 @@ pub fn show_debug_overlay(
 
     let version = config::wezterm_version();
     let triple = config::wezterm_target_triple();
+    let log_level = env_bootstrap::ringlog::get_log_level();
 
     term.render(&[Change::Text(format!(
         "Debug Overlay\r\n\
@@ -202,7 +414,10 @@ pub fn show_debug_overlay(
          Window Environment: {connection_info}\r\n\
          Lua Version: {lua_version}\r\n\
          {opengl_info}\r\n\
+         Log Level: {log_level} (change it with wezterm.set_log_level 'mux=debug')\r\n\
          Enter lua statements or expressions and hit Enter.\r\n\
+         `window` and `pane` refer to this window and its active pane;\r\n\
+         use target_pane(pane_id) to change `pane`.  TAB completes names.\r\n\
          Press ESC or CTRL-D to exit\r\n",
     ))])?;
 
@@ -263,20 +478,40 @@ async fn evaluate(host: LuaReplHost, expr: String) -> (LuaReplHost, String) {
         };
         let chunk = host.lua.load(&code).set_name("repl");
 
-        let result = chunk
-            .eval_async::<Value>()
-            .map(|result| match result {
-                Ok(result) => {
-                    let value = ValuePrinter(result);
-                    format!("{:#?}", value)
-                }
-                Err(err) => format_lua_err(err),
-            })
-            .await;
+        let result = chunk.eval_async::<Value>().await;
 
-        result
+        match result {
+            Ok(result) => {
+                let inspected = inspect_userdata(&host.lua, &result).await;
+                inspected.unwrap_or_else(|| format!("{:#?}", ValuePrinter(result)))
+            }
+            Err(err) => format_lua_err(err),
+        }
     }
 
     let result = do_it(&host, &expr).await;
     (host, result)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn completion_contexts() {
+        assert_eq!(completion_context("wez", 3), Some((vec![], 0..3)));
+        assert_eq!(
+            completion_context("wezterm.mux.get_", 16),
+            Some((vec!["wezterm".to_string(), "mux".to_string()], 12..16))
+        );
+        assert_eq!(
+            completion_context("print(pane:get_t", 16),
+            Some((vec!["pane".to_string()], 11..16))
+        );
+        assert_eq!(
+            completion_context("window:", 7),
+            Some((vec!["window".to_string()], 7..7))
+        );
+        assert_eq!(completion_context("x = 1.5", 7), None);
+    }
+}
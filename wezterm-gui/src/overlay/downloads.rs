@@ -0,0 +1,281 @@
+//! The downloads overlay lists the files that programs running in
+//! panes have asked to be downloaded during this session, such as
+//! with the iTerm2 `File=` protocol, along with what became of them.
+//! A saved file can be opened, or its path copied to the clipboard.
+use crate::download::{recent_downloads, Download, DownloadStatus};
+use mux::termwiztermtab::TermWizTerminal;
+use std::path::PathBuf;
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+
+/// The status line
+const ROW_OVERHEAD: usize = 1;
+
+/// What to do with the chosen download once the overlay has closed
+pub enum DownloadAction {
+    Open(PathBuf),
+    Copy(PathBuf),
+}
+
+fn status(download: &Download) -> (&'static str, AnsiColor) {
+    match download.status {
+        DownloadStatus::Saving => ("saving", AnsiColor::Yellow),
+        DownloadStatus::Saved(_) => ("saved", AnsiColor::Green),
+        DownloadStatus::Cancelled => ("cancelled", AnsiColor::Grey),
+        DownloadStatus::Rejected(_) => ("rejected", AnsiColor::Red),
+        DownloadStatus::Failed(_) => ("failed", AnsiColor::Red),
+    }
+}
+
+fn describe(download: &Download) -> String {
+    let name = download.name.as_deref().unwrap_or("(unnamed)");
+    match &download.status {
+        DownloadStatus::Saved(path) => path.display().to_string(),
+        DownloadStatus::Rejected(reason) | DownloadStatus::Failed(reason) => {
+            format!("{name}: {reason}")
+        }
+        DownloadStatus::Saving | DownloadStatus::Cancelled => name.to_string(),
+    }
+}
+
+fn format_size(size: usize) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit + 1 < UNITS.len() {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+struct DownloadsState {
+    downloads: Vec<Download>,
+    active_idx: usize,
+    top_row: usize,
+    max_rows: usize,
+}
+
+impl DownloadsState {
+    fn active_path(&self) -> Option<PathBuf> {
+        match &self.downloads.get(self.active_idx)?.status {
+            DownloadStatus::Saved(path) => Some(path.clone()),
+            _ => None,
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.downloads.is_empty() {
+            return;
+        }
+        let idx = (self.active_idx as isize + delta).max(0) as usize;
+        self.active_idx = idx.min(self.downloads.len() - 1);
+        if self.active_idx < self.top_row {
+            self.top_row = self.active_idx;
+        } else if self.active_idx >= self.top_row + self.max_rows {
+            self.top_row = self.active_idx + 1 - self.max_rows;
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+        self.max_rows = size.rows.saturating_sub(ROW_OVERHEAD).max(1);
+
+        let status = if self.downloads.is_empty() {
+            "Nothing has been downloaded yet".to_string()
+        } else {
+            format!(
+                "{} downloads.  Enter to open, CTRL-y to copy the path, R to refresh",
+                self.downloads.len()
+            )
+        };
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            AttributeChange::Italic(true).into(),
+            Change::Text(truncate_right(&status, max_width)),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+        ];
+
+        for (row_idx, download) in self
+            .downloads
+            .iter()
+            .enumerate()
+            .skip(self.top_row)
+            .take(self.max_rows)
+        {
+            if row_idx == self.active_idx {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+
+            let time = chrono::DateTime::<chrono::Local>::from(download.requested)
+                .format("%H:%M:%S")
+                .to_string();
+            let (status, color) = status(download);
+            changes.push(Change::Text(format!("{time} ")));
+            changes.push(AttributeChange::Foreground(color.into()).into());
+            changes.push(Change::Text(format!("{status:>9}")));
+            changes.push(AttributeChange::Foreground(ColorAttribute::Default).into());
+
+            let label = format!(
+                " {:>10} pane {:<3} {}",
+                format_size(download.size),
+                download.pane_id,
+                describe(download)
+            );
+            changes.push(Change::Text(truncate_right(
+                &label,
+                max_width.saturating_sub(time.len() + 10),
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<Option<DownloadAction>> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('q'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) => {
+                    return Ok(None);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    if let Some(path) = self.active_path() {
+                        return Ok(Some(DownloadAction::Open(path)));
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('Y' | 'y'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    if let Some(path) = self.active_path() {
+                        return Ok(Some(DownloadAction::Copy(path)));
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('r' | 'R'),
+                    modifiers: Modifiers::NONE | Modifiers::SHIFT,
+                }) => {
+                    self.downloads = recent_downloads();
+                    self.move_by(0);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('k'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('P' | 'K'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('j'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('N' | 'J'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => {
+                    self.move_by(-(self.max_rows as isize));
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => {
+                    self.move_by(self.max_rows as isize);
+                }
+                InputEvent::Mouse(MouseEvent {
+                    y, mouse_buttons, ..
+                }) => {
+                    if mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                        if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                            self.move_by(-1);
+                        } else {
+                            self.move_by(1);
+                        }
+                    } else if mouse_buttons == MouseButtons::LEFT && y > 0 {
+                        let idx = self.top_row + y as usize - 1;
+                        if idx < self.downloads.len() && idx < self.top_row + self.max_rows {
+                            self.active_idx = idx;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(None)
+    }
+}
+
+pub fn downloads(mut term: TermWizTerminal) -> anyhow::Result<Option<DownloadAction>> {
+    let mut state = DownloadsState {
+        downloads: recent_downloads(),
+        active_idx: 0,
+        top_row: 0,
+        max_rows: 0,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Downloads".to_string())])?;
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(100 * 1024 * 1024), "100.0 MiB");
+    }
+}
@@ -0,0 +1,484 @@
+//! The global search overlay searches the scrollback of every pane
+//! in the active workspace, or of those that have a given tag,
+//! including panes in remote mux domains, and lists the matching lines
+//! grouped by pane.
+//! Choosing a match activates the pane that contains it and scrolls
+//! the match into view.
+use crate::selection::{SelectionCoordinate, SelectionRange};
+use crate::termwindow::TermWindowNotif;
+use mux::pane::{Pane, PaneId, Pattern, SearchResult};
+use mux::termwiztermtab::TermWizTerminal;
+use mux::Mux;
+use std::sync::Arc;
+use termwiz::cell::{AttributeChange, CellAttributes, Intensity};
+use termwiz::color::ColorAttribute;
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+use wezterm_term::StableRowIndex;
+
+/// Don't let a single very chatty pane swamp the results
+const MAX_MATCHES_PER_PANE: u32 = 200;
+/// The query line and the status line
+const ROW_OVERHEAD: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct GlobalMatch {
+    pub pane_id: PaneId,
+    pub result: SearchResult,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaneMatches {
+    pub pane_id: PaneId,
+    pub title: String,
+    pub domain: String,
+    pub matches: Vec<GlobalMatch>,
+}
+
+/// Searches the scrollback of each pane in `workspace`, or only of
+/// those that have `tag` if it is set.
+/// Must be called on the main thread.
+pub async fn search_workspace(
+    workspace: String,
+    pattern: Pattern,
+    tag: Option<String>,
+) -> Vec<PaneMatches> {
+    let mux = Mux::get();
+    let mut panes: Vec<Arc<dyn Pane>> = vec![];
+    for window_id in mux.iter_windows_in_workspace(&workspace) {
+        if let Some(window) = mux.get_window(window_id) {
+            for tab in window.iter() {
+                for pos in tab.iter_panes_ignoring_zoom() {
+                    let tagged = match &tag {
+                        Some(tag) => mux.pane_has_tag(pos.pane.pane_id(), tag),
+                        None => true,
+                    };
+                    if tagged {
+                        panes.push(pos.pane);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut results = vec![];
+    for pane in panes {
+        let dims = pane.get_dimensions();
+        let range = dims.scrollback_top..dims.physical_top + dims.viewport_rows as StableRowIndex;
+        let found = match pane
+            .search(pattern.clone(), range, Some(MAX_MATCHES_PER_PANE))
+            .await
+        {
+            Ok(found) => found,
+            Err(err) => {
+                log::error!("searching pane {}: {:#}", pane.pane_id(), err);
+                continue;
+            }
+        };
+        if found.is_empty() {
+            continue;
+        }
+
+        let mut matches: Vec<GlobalMatch> = found
+            .into_iter()
+            .map(|result| {
+                let (_, lines) = pane.get_lines(result.start_y..result.start_y + 1);
+                let text = lines
+                    .first()
+                    .map(|line| line.as_str().trim().to_string())
+                    .unwrap_or_default();
+                GlobalMatch {
+                    pane_id: pane.pane_id(),
+                    result,
+                    text,
+                }
+            })
+            .collect();
+        matches.sort_by_key(|m| (m.result.start_y, m.result.start_x));
+
+        let domain = mux
+            .get_domain(pane.domain_id())
+            .map(|domain| domain.domain_name().to_string())
+            .unwrap_or_default();
+
+        results.push(PaneMatches {
+            pane_id: pane.pane_id(),
+            title: pane.get_title(),
+            domain,
+            matches,
+        });
+    }
+    results
+}
+
+/// Activates the pane containing the match, scrolls it into view
+/// and selects it.  Must be called on the main thread.
+pub fn jump_to_match(found: GlobalMatch) -> anyhow::Result<()> {
+    let mux = Mux::get();
+    let pane_id = found.pane_id;
+    let pane = mux
+        .get_pane(pane_id)
+        .ok_or_else(|| anyhow::anyhow!("pane {pane_id} no longer exists"))?;
+    let (_domain, window_id, _tab_id) = mux
+        .resolve_pane_id(pane_id)
+        .ok_or_else(|| anyhow::anyhow!("can't find pane {pane_id} in the mux"))?;
+    mux.focus_pane_and_containing_tab(pane_id)?;
+
+    let gui_win = crate::frontend::front_end()
+        .gui_window_for_mux_window(window_id)
+        .ok_or_else(|| anyhow::anyhow!("no gui window for mux window {window_id}"))?;
+
+    let result = found.result;
+    gui_win
+        .window
+        .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+            {
+                let mut selection = term_window.selection(pane_id);
+                let start = SelectionCoordinate::x_y(result.start_x, result.start_y);
+                selection.origin = Some(start);
+                selection.range = Some(SelectionRange {
+                    start,
+                    // inclusive range for selection, but the result
+                    // range is exclusive
+                    end: SelectionCoordinate::x_y(result.end_x.saturating_sub(1), result.end_y),
+                });
+                selection.seqno = pane.get_current_seqno();
+            }
+
+            // Place the match in the middle of the viewport
+            let dims = pane.get_dimensions();
+            let top = result.start_y - (dims.viewport_rows / 2) as StableRowIndex;
+            term_window.set_viewport(pane_id, Some(top), dims);
+        })));
+    Ok(())
+}
+
+enum Row {
+    Header(usize),
+    Match(usize),
+}
+
+struct GlobalSearchState {
+    workspace: String,
+    pattern: Pattern,
+    /// Only the panes with this tag are searched
+    tag: Option<String>,
+    editing: bool,
+    searched: bool,
+    results: Vec<PaneMatches>,
+    /// All of the matches, in the order that they are displayed
+    matches: Vec<GlobalMatch>,
+    /// The rows to display; headers and indices into `matches`
+    rows: Vec<Row>,
+    active_idx: usize,
+    top_row: usize,
+    max_rows: usize,
+}
+
+impl GlobalSearchState {
+    fn pattern_kind(&self) -> &'static str {
+        match &self.pattern {
+            Pattern::CaseSensitiveString(_) => "case-sensitive",
+            Pattern::CaseInSensitiveString(_) => "ignore-case",
+            Pattern::Regex(_) => "regex",
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match &self.tag {
+            Some(tag) => format!("Search panes tagged {} ({}): ", tag, self.pattern_kind()),
+            None => format!("Search all panes ({}): ", self.pattern_kind()),
+        }
+    }
+
+    fn cycle_pattern_kind(&mut self) {
+        self.pattern = match &self.pattern {
+            Pattern::CaseSensitiveString(s) => Pattern::CaseInSensitiveString(s.clone()),
+            Pattern::CaseInSensitiveString(s) => Pattern::Regex(s.clone()),
+            Pattern::Regex(s) => Pattern::CaseSensitiveString(s.clone()),
+        };
+    }
+
+    fn run_search(&mut self) {
+        let workspace = self.workspace.clone();
+        let pattern = self.pattern.clone();
+        let tag = self.tag.clone();
+        // Pane::search isn't Send, so it has to be spawned locally
+        // once we're on the main thread
+        self.results = smol::block_on(promise::spawn::spawn_into_main_thread(async move {
+            promise::spawn::spawn(search_workspace(workspace, pattern, tag)).await
+        }));
+        self.searched = true;
+
+        self.matches.clear();
+        self.rows.clear();
+        for (pane_idx, pane) in self.results.iter().enumerate() {
+            self.rows.push(Row::Header(pane_idx));
+            for m in &pane.matches {
+                self.rows.push(Row::Match(self.matches.len()));
+                self.matches.push(m.clone());
+            }
+        }
+        self.active_idx = 0;
+        self.top_row = 0;
+        self.editing = self.matches.is_empty();
+    }
+
+    /// Returns the display row of the active match
+    fn active_row(&self) -> usize {
+        self.rows
+            .iter()
+            .position(|row| matches!(row, Row::Match(idx) if *idx == self.active_idx))
+            .unwrap_or(0)
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let idx = (self.active_idx as isize + delta).max(0) as usize;
+        self.active_idx = idx.min(self.matches.len() - 1);
+
+        // Keep the pane header visible above its first match
+        let row = self.active_row();
+        let first_visible = row.saturating_sub(1);
+        if first_visible < self.top_row {
+            self.top_row = first_visible;
+        } else if row >= self.top_row + self.max_rows {
+            self.top_row = row + 1 - self.max_rows;
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+        self.max_rows = size.rows.saturating_sub(ROW_OVERHEAD).max(1);
+
+        let status = if !self.searched {
+            "Enter to search all panes in this workspace, \
+             CTRL-r to change the match type, Esc to cancel"
+                .to_string()
+        } else if self.matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            format!(
+                "{} matches in {} panes.  Enter to jump to a match, / to edit the search",
+                self.matches.len(),
+                self.results.len()
+            )
+        };
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            Change::Text(truncate_right(
+                &format!("{}{}", self.prompt(), *self.pattern),
+                max_width,
+            )),
+            Change::Text("\r\n".to_string()),
+            AttributeChange::Italic(true).into(),
+            Change::Text(truncate_right(&status, max_width)),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+        ];
+
+        for row in self.rows.iter().skip(self.top_row).take(self.max_rows) {
+            match row {
+                Row::Header(pane_idx) => {
+                    let pane = &self.results[*pane_idx];
+                    let mut label = format!("{} (pane {}", pane.title, pane.pane_id);
+                    if !pane.domain.is_empty() && pane.domain != "local" {
+                        label.push_str(&format!(", {}", pane.domain));
+                    }
+                    label.push_str(&format!("): {} matches", pane.matches.len()));
+                    changes.push(AttributeChange::Intensity(Intensity::Bold).into());
+                    changes.push(Change::Text(truncate_right(&label, max_width)));
+                }
+                Row::Match(idx) => {
+                    let m = &self.matches[*idx];
+                    let active = *idx == self.active_idx && !self.editing;
+                    if active {
+                        changes.push(AttributeChange::Reverse(true).into());
+                    }
+                    changes.push(Change::Text(truncate_right(
+                        &format!("  {:>6}: {}", m.result.start_y, m.text),
+                        max_width,
+                    )));
+                }
+            }
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        if self.editing {
+            let x = self.prompt().len() + self.pattern.len();
+            changes.push(Change::CursorPosition {
+                x: Position::Absolute(x.min(max_width)),
+                y: Position::Absolute(0),
+            });
+        }
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<Option<GlobalMatch>> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) => {
+                    return Ok(None);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('R' | 'r'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.cycle_pattern_kind();
+                    self.editing = true;
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    if self.editing {
+                        if !self.pattern.is_empty() {
+                            self.run_search();
+                        }
+                    } else if let Some(found) = self.matches.get(self.active_idx) {
+                        return Ok(Some(found.clone()));
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Backspace,
+                    ..
+                }) if self.editing => {
+                    self.pattern.pop();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('U'),
+                    modifiers: Modifiers::CTRL,
+                }) if self.editing => {
+                    self.pattern.clear();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char(c),
+                    modifiers: Modifiers::NONE | Modifiers::SHIFT,
+                }) if self.editing => {
+                    self.pattern.push(c);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('/'),
+                    ..
+                }) => {
+                    self.editing = true;
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('P' | 'K'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.editing = self.matches.is_empty();
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('k'),
+                    ..
+                }) => {
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('N' | 'J'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.editing = self.matches.is_empty();
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('j'),
+                    ..
+                }) => {
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => {
+                    self.move_by(-(self.max_rows as isize));
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => {
+                    self.move_by(self.max_rows as isize);
+                }
+                InputEvent::Mouse(MouseEvent {
+                    y, mouse_buttons, ..
+                }) => {
+                    if mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                        if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                            self.move_by(-1);
+                        } else {
+                            self.move_by(1);
+                        }
+                    } else if mouse_buttons == MouseButtons::LEFT && y as usize >= ROW_OVERHEAD {
+                        let row = self.top_row + y as usize - ROW_OVERHEAD;
+                        if let Some(Row::Match(idx)) = self.rows.get(row) {
+                            return Ok(self.matches.get(*idx).cloned());
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(None)
+    }
+}
+
+pub fn global_search(
+    mut term: TermWizTerminal,
+    workspace: String,
+    pattern: Pattern,
+    tag: Option<String>,
+) -> anyhow::Result<Option<GlobalMatch>> {
+    let mut state = GlobalSearchState {
+        workspace,
+        pattern,
+        tag,
+        editing: true,
+        searched: false,
+        results: vec![],
+        matches: vec![],
+        rows: vec![],
+        active_idx: 0,
+        top_row: 0,
+        max_rows: 0,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Search all panes".to_string())])?;
+    if !state.pattern.is_empty() {
+        state.run_search();
+    }
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
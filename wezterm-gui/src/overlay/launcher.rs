@@ -18,7 +18,7 @@ use mux::tab::TabId;
 use mux::termwiztermtab::TermWizTerminal;
 use mux::window::WindowId;
 use mux::Mux;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use termwiz::cell::{AttributeChange, CellAttributes};
 use termwiz::color::ColorAttribute;
 use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
@@ -40,6 +40,8 @@ pub struct LauncherTabEntry {
     pub tab_id: TabId,
     pub tab_idx: usize,
     pub pane_count: Option<usize>,
+    /// The tags of the panes of the tab, so that it can be found by them
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -100,11 +102,17 @@ impl LauncherArgs {
                     } else {
                         tab_title
                     };
+                    let tags: BTreeSet<String> = tab
+                        .iter_panes_ignoring_zoom()
+                        .into_iter()
+                        .flat_map(|pos| mux.get_pane_tags(pos.pane.pane_id()))
+                        .collect();
                     LauncherTabEntry {
                         title,
                         tab_id: tab.tab_id(),
                         tab_idx,
                         pane_count: tab.count_panes(),
+                        tags: tags.into_iter().collect(),
                     }
                 })
                 .collect()
@@ -283,11 +291,15 @@ impl LauncherState {
         }
 
         for tab in &args.tabs {
+            let mut label = match tab.pane_count {
+                Some(pane_count) => format!("{}. {pane_count} panes", tab.title),
+                None => format!("{}.", tab.title),
+            };
+            for tag in &tab.tags {
+                label.push_str(&format!(" #{tag}"));
+            }
             self.entries.push(Entry {
-                label: match tab.pane_count {
-                    Some(pane_count) => format!("{}. {pane_count} panes", tab.title),
-                    None => format!("{}.", tab.title),
-                },
+                label,
                 action: KeyAssignment::ActivateTab(tab.tab_idx as isize),
             });
         }
@@ -6,13 +6,21 @@ use std::pin::Pin;
 use std::sync::Arc;
 use wezterm_term::{TerminalConfiguration, TerminalSize};
 
+pub mod color_scheme_editor;
+pub mod command_journal;
 pub mod confirm_close_pane;
 pub mod copy;
 pub mod debug;
+pub mod downloads;
+pub mod global_search;
 pub mod launcher;
 pub mod prompt;
 pub mod quickselect;
+pub mod release_notes;
 pub mod selector;
+pub mod setup_wizard;
+pub mod suspicious;
+pub mod vtinspect;
 
 pub use confirm_close_pane::{
     confirm_close_pane, confirm_close_tab, confirm_close_window, confirm_quit_program,
@@ -1,6 +1,9 @@
 use crate::selection::{SelectionCoordinate, SelectionRange};
 use crate::termwindow::{TermWindow, TermWindowNotif};
-use config::keyassignment::{ClipboardCopyDestination, QuickSelectArguments, ScrollbackEraseMode};
+use config::keyassignment::{
+    ClipboardCopyDestination, KeyAssignment, QuickSelectArguments, QuickSelectCommand,
+    ScrollbackEraseMode, SpawnCommand, SplitPane,
+};
 use config::ConfigHandle;
 use mux::domain::DomainId;
 use mux::pane::{
@@ -10,6 +13,7 @@ use mux::pane::{
 use mux::renderable::*;
 use parking_lot::{MappedMutexGuard, Mutex};
 use rangeset::RangeSet;
+use regex::Regex;
 use std::collections::HashMap;
 use std::ops::Range;
 use std::sync::Arc;
@@ -54,6 +58,21 @@ const PATTERNS: [&str; 14] = [
     r"[0-9]{4,}",
 ];
 
+/// Returns the patterns that quick select matches by default, which
+/// are also used to classify text by ExtendSelectionToSemanticUnit
+pub fn quick_select_patterns(config: &ConfigHandle) -> Vec<&str> {
+    // User-provided patterns take precedence over built-ins
+    let mut patterns: Vec<&str> = config
+        .quick_select_patterns
+        .iter()
+        .map(String::as_str)
+        .collect();
+    if !config.disable_default_quick_select_patterns {
+        patterns.extend_from_slice(&PATTERNS);
+    }
+    patterns
+}
+
 /// This function computes a set of labels for a given alphabet.
 /// It is derived from https://github.com/fcsonline/tmux-thumbs/blob/master/src/alphabets.rs
 /// which is Copyright (c) 2019 Ferran Basora and provided under the MIT license
@@ -193,6 +212,188 @@ mod alphabet_test {
     }
 }
 
+/// Finds the runs of cells in `lines`, the first of which is at `top`,
+/// that are linked to a uri.  Returns a result for each run along
+/// with the unique uris, which are indexed by the match_id of the results.
+fn find_links(top: StableRowIndex, lines: &[Line]) -> (Vec<SearchResult>, Vec<String>) {
+    let mut results = vec![];
+    let mut links: Vec<String> = vec![];
+
+    for (idx, line) in lines.iter().enumerate() {
+        let y = top + idx as StableRowIndex;
+        let mut push_run = |(start_x, end_x, uri): (usize, usize, String)| {
+            let match_id = match links.iter().position(|link| *link == uri) {
+                Some(id) => id,
+                None => {
+                    links.push(uri);
+                    links.len() - 1
+                }
+            };
+            results.push(SearchResult {
+                start_y: y,
+                start_x,
+                end_y: y,
+                end_x,
+                match_id,
+            });
+        };
+
+        let mut run: Option<(usize, usize, String)> = None;
+        for cell in line.visible_cells() {
+            let x = cell.cell_index();
+            let uri = cell.attrs().hyperlink().map(|link| link.uri().to_string());
+            if let Some((_, end_x, current)) = run.as_mut() {
+                if *end_x == x && uri.as_deref() == Some(current.as_str()) {
+                    *end_x = x + cell.width();
+                    continue;
+                }
+            }
+            if let Some(run) = run.take() {
+                push_run(run);
+            }
+            run = uri.map(|uri| (x, x + cell.width(), uri));
+        }
+        if let Some(run) = run.take() {
+            push_run(run);
+        }
+    }
+
+    (results, links)
+}
+
+#[cfg(test)]
+mod link_test {
+    use super::*;
+    use termwiz::hyperlink::Hyperlink;
+
+    #[test]
+    fn find_links_in_lines() {
+        let mut line = Line::from_text(
+            "see a.com and b.com, a.com",
+            &CellAttributes::default(),
+            SEQ_ZERO,
+            None,
+        );
+        let link = |uri: &str| {
+            CellAttributes::default()
+                .set_hyperlink(Some(Arc::new(Hyperlink::new(uri))))
+                .clone()
+        };
+        for (range, uri) in [
+            (4..9, "https://a.com"),
+            (14..19, "https://b.com"),
+            (21..26, "https://a.com"),
+        ] {
+            for x in range {
+                let c = line.get_cell(x).unwrap().str().chars().next().unwrap();
+                line.set_cell(x, Cell::new(c, link(uri)), SEQ_ZERO);
+            }
+        }
+        let (results, links) = find_links(
+            10,
+            &[
+                Line::from_text("nothing", &CellAttributes::default(), SEQ_ZERO, None),
+                line,
+            ],
+        );
+        assert_eq!(links, vec!["https://a.com", "https://b.com"]);
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.start_y, r.start_x, r.end_x, r.match_id))
+                .collect::<Vec<_>>(),
+            vec![(11, 4, 9, 0), (11, 14, 19, 1), (11, 21, 26, 0)]
+        );
+    }
+}
+
+/// Returns a split that runs the first of `commands` whose pattern
+/// matches `text`, with the captures of the pattern expanded into its args
+fn split_for_quick_select_command(
+    commands: &[QuickSelectCommand],
+    text: &str,
+) -> Option<SplitPane> {
+    commands.iter().find_map(|command| {
+        let re = match Regex::new(&command.pattern) {
+            Ok(re) => re,
+            Err(err) => {
+                log::error!(
+                    "quick_select_commands pattern {:?}: {:#}",
+                    command.pattern,
+                    err
+                );
+                return None;
+            }
+        };
+        let captures = re.captures(text)?;
+        let args = command
+            .args
+            .iter()
+            .map(|arg| {
+                let mut expanded = String::new();
+                captures.expand(arg, &mut expanded);
+                expanded
+            })
+            .collect();
+        Some(SplitPane {
+            direction: command.direction,
+            size: command.size.clone(),
+            command: SpawnCommand {
+                args: Some(args),
+                ..Default::default()
+            },
+            top_level: false,
+        })
+    })
+}
+
+#[cfg(test)]
+mod command_test {
+    use super::*;
+    use config::keyassignment::{PaneDirection, SplitSize};
+
+    #[test]
+    fn expand_command_args() {
+        let commands = vec![
+            QuickSelectCommand {
+                pattern: r"^(?P<file>[^:]+):(\d+)$".to_string(),
+                args: vec!["vim".to_string(), "+$2".to_string(), "$file".to_string()],
+                direction: PaneDirection::Right,
+                size: SplitSize::Percent(30),
+            },
+            QuickSelectCommand {
+                pattern: "[0-9a-f]{7,40}".to_string(),
+                args: vec!["git".to_string(), "show".to_string(), "$0".to_string()],
+                direction: PaneDirection::Down,
+                size: SplitSize::default(),
+            },
+        ];
+
+        let split = split_for_quick_select_command(&commands, "src/main.rs:42").unwrap();
+        assert_eq!(
+            split.command.args,
+            Some(vec![
+                "vim".to_string(),
+                "+42".to_string(),
+                "src/main.rs".to_string()
+            ])
+        );
+        assert_eq!(split.direction, PaneDirection::Right);
+
+        let split = split_for_quick_select_command(&commands, "3fbeebe").unwrap();
+        assert_eq!(
+            split.command.args,
+            Some(vec![
+                "git".to_string(),
+                "show".to_string(),
+                "3fbeebe".to_string()
+            ])
+        );
+
+        assert!(split_for_quick_select_command(&commands, "nothing").is_none());
+    }
+}
+
 pub struct QuickSelectOverlay {
     renderer: Mutex<QuickSelectRenderable>,
     delegate: Arc<dyn Pane>,
@@ -227,6 +428,9 @@ struct QuickSelectRenderable {
 
     config: ConfigHandle,
     args: QuickSelectArguments,
+    /// When showing link hints, the uris of the links, indexed
+    /// by the match_id of the results
+    links: Option<Vec<String>>,
 }
 
 impl QuickSelectOverlay {
@@ -235,9 +439,6 @@ impl QuickSelectOverlay {
         pane: &Arc<dyn Pane>,
         args: &QuickSelectArguments,
     ) -> Arc<dyn Pane> {
-        let viewport = term_window.get_viewport(pane.pane_id());
-        let dims = pane.get_dimensions();
-
         let config = term_window.config.clone();
 
         let mut pattern = "(?m)(".to_string();
@@ -250,28 +451,51 @@ impl QuickSelectOverlay {
                 pattern.push_str(p);
                 have_patterns = true;
             }
-        } else {
-            // User-provided patterns take precedence over built-ins
-            for p in &config.quick_select_patterns {
+        } else if args.run_command {
+            for command in &config.quick_select_commands {
                 if have_patterns {
                     pattern.push('|');
                 }
-                pattern.push_str(p);
+                pattern.push_str(&command.pattern);
                 have_patterns = true;
             }
-            if !config.disable_default_quick_select_patterns {
-                for p in &PATTERNS {
-                    if have_patterns {
-                        pattern.push('|');
-                    }
-                    pattern.push_str(p);
-                    have_patterns = true;
+        } else {
+            for p in quick_select_patterns(&config) {
+                if have_patterns {
+                    pattern.push('|');
                 }
+                pattern.push_str(p);
+                have_patterns = true;
             }
         }
         pattern.push(')');
 
-        let pattern = Pattern::Regex(pattern);
+        Self::new(term_window, pane, args, Pattern::Regex(pattern), None)
+    }
+
+    /// Labels each of the hyperlinks in the viewport of the pane,
+    /// including those matched by the `hyperlink_rules`, so that
+    /// they can be opened or copied by typing their label
+    pub fn with_links(term_window: &TermWindow, pane: &Arc<dyn Pane>) -> Arc<dyn Pane> {
+        Self::new(
+            term_window,
+            pane,
+            &QuickSelectArguments::default(),
+            Pattern::default(),
+            Some(vec![]),
+        )
+    }
+
+    fn new(
+        term_window: &TermWindow,
+        pane: &Arc<dyn Pane>,
+        args: &QuickSelectArguments,
+        pattern: Pattern,
+        links: Option<Vec<String>>,
+    ) -> Arc<dyn Pane> {
+        let viewport = term_window.get_viewport(pane.pane_id());
+        let dims = pane.get_dimensions();
+        let config = term_window.config.clone();
 
         let window = term_window.window.clone().unwrap();
         let mut renderer = QuickSelectRenderable {
@@ -290,6 +514,7 @@ impl QuickSelectOverlay {
             height: dims.viewport_rows,
             config,
             args: args.clone(),
+            links,
         };
 
         let search_row = renderer.compute_search_row();
@@ -418,7 +643,11 @@ impl Pane for QuickSelectOverlay {
                 let lowered = r.selection.to_lowercase();
                 let paste = lowered != r.selection;
                 if let Some(result_index) = r.by_label.get(&lowered).cloned() {
-                    r.select_and_copy_match_number(result_index, paste);
+                    if r.links.is_some() {
+                        r.open_or_copy_link(result_index, paste);
+                    } else {
+                        r.select_and_copy_match_number(result_index, paste);
+                    }
                     r.close();
                 }
             }
@@ -561,15 +790,7 @@ impl Pane for QuickSelectOverlay {
                         line.fill_range(0..self.dims.cols, &Cell::new(' ', rev.clone()), SEQ_ZERO);
                         line.overlay_text_with_attribute(
                             0,
-                            &format!(
-                                "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
-                                self.renderer.selection,
-                                if self.renderer.args.label.is_empty() {
-                                    "copy"
-                                } else {
-                                    &self.renderer.args.label
-                                },
-                            ),
+                            &self.renderer.status_text(),
                             rev,
                             SEQ_ZERO,
                         );
@@ -645,20 +866,7 @@ impl Pane for QuickSelectOverlay {
                 // Replace with search UI
                 let rev = CellAttributes::default().set_reverse(true).clone();
                 line.fill_range(0..dims.cols, &Cell::new(' ', rev.clone()), SEQ_ZERO);
-                line.overlay_text_with_attribute(
-                    0,
-                    &format!(
-                        "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
-                        renderer.selection,
-                        if renderer.args.label.is_empty() {
-                            "copy"
-                        } else {
-                            &renderer.args.label
-                        },
-                    ),
-                    rev,
-                    SEQ_ZERO,
-                );
+                line.overlay_text_with_attribute(0, &renderer.status_text(), rev, SEQ_ZERO);
                 renderer.last_bar_pos = Some(search_row);
             } else if let Some(matches) = renderer.by_line.get(&stable_idx) {
                 for m in matches {
@@ -718,6 +926,26 @@ impl QuickSelectRenderable {
         bottom
     }
 
+    fn status_text(&self) -> String {
+        if self.links.is_some() {
+            return format!(
+                "Open link: {}  (type highlighted prefix to open, uppercase copies, ESC to cancel)",
+                self.selection
+            );
+        }
+        format!(
+            "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
+            self.selection,
+            if !self.args.label.is_empty() {
+                &self.args.label
+            } else if self.args.run_command {
+                "run"
+            } else {
+                "copy"
+            },
+        )
+    }
+
     fn close(&self) {
         TermWindow::schedule_cancel_overlay_for_pane(self.window.clone(), self.delegate.pane_id());
     }
@@ -838,7 +1066,20 @@ impl QuickSelectRenderable {
         let bar_pos = self.compute_search_row();
         self.dirty_results.add(bar_pos);
 
-        if !self.pattern.is_empty() {
+        if self.links.is_some() {
+            // Links are only hinted in the viewport, which is always
+            // available to us, so there's no need to search asynchronously
+            let dims = self.delegate.get_dimensions();
+            let top = self.viewport.unwrap_or(dims.physical_top);
+            let range = top..bar_pos;
+            self.delegate
+                .apply_hyperlinks(range.clone(), &self.config.hyperlink_rules);
+            let (top, lines) = self.delegate.get_lines(range);
+            let (results, links) = find_links(top, &lines);
+            self.results = results;
+            self.links.replace(links);
+            self.recompute_results();
+        } else if !self.pattern.is_empty() {
             let pane: Arc<dyn Pane> = self.delegate.clone();
             let window = self.window.clone();
             let pattern = self.pattern.clone();
@@ -914,6 +1155,7 @@ impl QuickSelectRenderable {
 
         let pane_id = self.delegate.pane_id();
         let action = self.args.action.clone();
+        let run_command = self.args.run_command;
         self.window
             .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
                 let mux = mux::Mux::get();
@@ -941,7 +1183,20 @@ impl QuickSelectRenderable {
                         if paste {
                             let _ = pane.send_paste(&text);
                         }
-                        if let Some(action) = action {
+                        if run_command {
+                            let commands = &term_window.config.quick_select_commands;
+                            match split_for_quick_select_command(commands, &text) {
+                                Some(split) => {
+                                    let _ = term_window.perform_key_assignment(
+                                        &pane,
+                                        &KeyAssignment::SplitPane(split),
+                                    );
+                                }
+                                None => {
+                                    log::warn!("no quick_select_commands entry matches {text:?}")
+                                }
+                            }
+                        } else if let Some(action) = action {
                             let _ = term_window.perform_key_assignment(&pane, &action);
                         } else {
                             term_window.copy_to_clipboard(
@@ -954,6 +1209,30 @@ impl QuickSelectRenderable {
             })));
     }
 
+    fn open_or_copy_link(&mut self, n: usize, copy: bool) {
+        let link = match self
+            .links
+            .as_ref()
+            .and_then(|links| links.get(self.results[n].match_id))
+        {
+            Some(link) => link.clone(),
+            None => return,
+        };
+
+        let pane_id = self.delegate.pane_id();
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                if copy {
+                    term_window.copy_to_clipboard(
+                        ClipboardCopyDestination::ClipboardAndPrimarySelection,
+                        link,
+                    );
+                } else if let Some(pane) = mux::Mux::get().get_pane(pane_id) {
+                    term_window.open_link(&pane, link);
+                }
+            })));
+    }
+
     fn activate_match_number(&mut self, n: usize) {
         self.result_pos.replace(n);
         let result = self.results[n].clone();
@@ -0,0 +1,425 @@
+//! Shows the notes of the most recent release in the configured update
+//! channel, rendering the subset of markdown that is used in release
+//! notes: headings, bullet lists, code, emphasis and links.
+use crate::update::{cached_release_info, get_release_info, is_newer_release, release_notes_url};
+use config::{wezterm_version, UpdateChannel};
+use mux::termwiztermtab::TermWizTerminal;
+use std::sync::Arc;
+use termwiz::cell::{
+    unicode_column_width, AttributeChange, CellAttributes, Hyperlink, Intensity, Underline,
+};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Strong,
+    Heading,
+    Code,
+    Link(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    text: String,
+    style: Style,
+}
+
+impl Span {
+    fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+type Line = Vec<Span>;
+
+/// Splits the inline markup of `text` into styled spans.
+/// Text outside of any markup has the `plain` style.
+fn parse_inline(text: &str, plain: Style) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut current = String::new();
+    let mut strong = false;
+    let mut rest = text;
+
+    fn flush(spans: &mut Vec<Span>, current: &mut String, style: &Style) {
+        if !current.is_empty() {
+            spans.push(Span::new(std::mem::take(current), style.clone()));
+        }
+    }
+
+    while let Some(c) = rest.chars().next() {
+        let style = if strong { Style::Strong } else { plain.clone() };
+        if let Some(after) = rest.strip_prefix("**") {
+            flush(&mut spans, &mut current, &style);
+            strong = !strong;
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = after.find('`') {
+                flush(&mut spans, &mut current, &style);
+                spans.push(Span::new(&after[..end], Style::Code));
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            if let Some(close) = after.find("](") {
+                let target = &after[close + 2..];
+                if let Some(end) = target.find(')') {
+                    flush(&mut spans, &mut current, &style);
+                    spans.push(Span::new(
+                        &after[..close],
+                        Style::Link(target[..end].to_string()),
+                    ));
+                    rest = &target[end + 1..];
+                    continue;
+                }
+            }
+        }
+        current.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    let style = if strong { Style::Strong } else { plain };
+    flush(&mut spans, &mut current, &style);
+    spans
+}
+
+/// Word wraps `spans` to `width` columns, appending the resulting lines
+/// to `lines`.  The first line starts with `lead` and the rest with `hang`.
+fn wrap(spans: Vec<Span>, width: usize, lead: &str, hang: &str, lines: &mut Vec<Line>) {
+    let mut line = vec![Span::new(lead, Style::Plain)];
+    let mut col = unicode_column_width(lead, None);
+    let mut empty = true;
+
+    for span in spans {
+        for word in span.text.split_inclusive(' ') {
+            let word_width = unicode_column_width(word.trim_end(), None);
+            if !empty && col + word_width > width {
+                if let Some(last) = line.last_mut() {
+                    last.text.truncate(last.text.trim_end().len());
+                }
+                lines.push(std::mem::replace(
+                    &mut line,
+                    vec![Span::new(hang, Style::Plain)],
+                ));
+                col = unicode_column_width(hang, None);
+                empty = true;
+            }
+            let word = if empty { word.trim_start() } else { word };
+            if word.is_empty() {
+                continue;
+            }
+            col += unicode_column_width(word, None);
+            empty = false;
+            match line.last_mut() {
+                Some(last) if last.style == span.style => last.text.push_str(word),
+                _ => line.push(Span::new(word, span.style.clone())),
+            }
+        }
+    }
+    lines.push(line);
+}
+
+/// Renders markdown `body` to lines of at most `width` columns
+fn render_markdown(body: &str, width: usize) -> Vec<Line> {
+    let width = width.max(20);
+    let mut lines: Vec<Line> = vec![];
+    let mut in_code_block = false;
+
+    for raw in body.lines() {
+        let raw = raw.trim_end();
+        let content = raw.trim_start();
+        let indent = raw.len() - content.len();
+
+        if content.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(vec![Span::new(format!("    {raw}"), Style::Code)]);
+            continue;
+        }
+        if content.is_empty() {
+            lines.push(vec![]);
+            continue;
+        }
+
+        if content.starts_with('#') {
+            // Separate headings from whatever precedes them
+            if matches!(lines.last(), Some(line) if !line.is_empty()) {
+                lines.push(vec![]);
+            }
+            let text = content.trim_start_matches('#').trim();
+            wrap(
+                parse_inline(text, Style::Heading),
+                width,
+                "",
+                "",
+                &mut lines,
+            );
+            continue;
+        }
+
+        if let Some(item) = ["* ", "- ", "+ "]
+            .iter()
+            .find_map(|bullet| content.strip_prefix(bullet))
+        {
+            let lead = format!("{}\u{2022} ", " ".repeat(indent));
+            let hang = " ".repeat(indent + 2);
+            wrap(
+                parse_inline(item.trim(), Style::Plain),
+                width,
+                &lead,
+                &hang,
+                &mut lines,
+            );
+            continue;
+        }
+
+        let indent = " ".repeat(indent);
+        wrap(
+            parse_inline(content, Style::Plain),
+            width,
+            &indent,
+            &indent,
+            &mut lines,
+        );
+    }
+
+    lines
+}
+
+fn style_changes(style: &Style) -> Vec<Change> {
+    match style {
+        Style::Plain => vec![],
+        Style::Strong => vec![AttributeChange::Intensity(Intensity::Bold).into()],
+        Style::Heading => vec![
+            AttributeChange::Intensity(Intensity::Bold).into(),
+            AttributeChange::Underline(Underline::Single).into(),
+        ],
+        Style::Code => vec![AttributeChange::Foreground(AnsiColor::Teal.into()).into()],
+        Style::Link(url) => vec![
+            AttributeChange::Underline(Underline::Single).into(),
+            AttributeChange::Foreground(AnsiColor::Blue.into()).into(),
+            AttributeChange::Hyperlink(Some(Arc::new(Hyperlink::new(url)))).into(),
+        ],
+    }
+}
+
+struct Notes {
+    title: String,
+    status: String,
+    url: Option<String>,
+    body: String,
+}
+
+fn load_notes(channel: UpdateChannel) -> Notes {
+    let release = match cached_release_info(channel) {
+        Some(release) => Ok(release),
+        None => get_release_info(channel),
+    };
+    let current = wezterm_version();
+    match release {
+        Ok(release) => {
+            let status = if is_newer_release(channel, &release, current) {
+                format!("An update is available; this is version {current}")
+            } else {
+                format!("This version, {current}, is up to date")
+            };
+            Notes {
+                title: format!("{:?} release {}", channel, release.tag_name),
+                status,
+                url: Some(release_notes_url(channel, &release)),
+                body: release.body,
+            }
+        }
+        Err(err) => Notes {
+            title: "Release notes".to_string(),
+            status: format!("This is version {current}"),
+            url: Some("https://wezfurlong.org/wezterm/changelog.html".to_string()),
+            body: format!(
+                "The release notes could not be retrieved: {err:#}\n\n\
+                 See [the changelog](https://wezfurlong.org/wezterm/changelog.html) \
+                 for the changes in each release."
+            ),
+        },
+    }
+}
+
+fn render(
+    term: &mut TermWizTerminal,
+    notes: &Notes,
+    lines: &[Line],
+    top_row: usize,
+) -> termwiz::Result<()> {
+    let size = term.get_screen_size()?;
+    let width = size.cols.saturating_sub(1);
+
+    let mut changes = vec![
+        Change::ClearScreen(ColorAttribute::Default),
+        Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(0),
+        },
+        AttributeChange::Intensity(Intensity::Bold).into(),
+        Change::Text(truncate_right(&notes.title, width)),
+        Change::AllAttributes(CellAttributes::default()),
+        Change::Text("\r\n".to_string()),
+        AttributeChange::Italic(true).into(),
+        Change::Text(truncate_right(&notes.status, width)),
+        Change::AllAttributes(CellAttributes::default()),
+        Change::Text("\r\n\r\n".to_string()),
+    ];
+
+    let body_rows = size.rows.saturating_sub(4);
+    for line in lines.iter().skip(top_row).take(body_rows) {
+        let mut col = 0;
+        for span in line {
+            let text = truncate_right(&span.text, width.saturating_sub(col));
+            col += unicode_column_width(&text, None);
+            changes.extend(style_changes(&span.style));
+            changes.push(Change::Text(text));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+        }
+        changes.push(Change::Text("\r\n".to_string()));
+    }
+
+    changes.push(Change::CursorPosition {
+        x: Position::Absolute(0),
+        y: Position::Absolute(size.rows.saturating_sub(1)),
+    });
+    changes.push(AttributeChange::Italic(true).into());
+    changes.push(Change::Text(truncate_right(
+        "Up/Down/PageUp/PageDown: scroll, o: open in browser, q/Escape: close",
+        width,
+    )));
+    changes.push(Change::AllAttributes(CellAttributes::default()));
+
+    term.render(&changes)
+}
+
+pub fn release_notes(mut term: TermWizTerminal, channel: UpdateChannel) -> anyhow::Result<()> {
+    term.set_raw_mode()?;
+    term.render(&[
+        Change::Title("Release Notes".to_string()),
+        Change::Text("Retrieving release notes...".to_string()),
+    ])?;
+
+    let notes = load_notes(channel);
+    let mut top_row = 0;
+
+    loop {
+        let size = term.get_screen_size()?;
+        let lines = render_markdown(&notes.body, size.cols.saturating_sub(1));
+        let page = size.rows.saturating_sub(4).max(1);
+        let max_top = lines.len().saturating_sub(page);
+        top_row = top_row.min(max_top);
+
+        render(&mut term, &notes, &lines, top_row)?;
+
+        match term.poll_input(None)? {
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Escape | KeyCode::Char('q'),
+                ..
+            }))
+            | Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('G' | 'C'),
+                modifiers: Modifiers::CTRL,
+            })) => break,
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::UpArrow | KeyCode::Char('k'),
+                ..
+            })) => top_row = top_row.saturating_sub(1),
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::DownArrow | KeyCode::Char('j'),
+                ..
+            })) => top_row += 1,
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::PageUp | KeyCode::Char('b'),
+                ..
+            })) => top_row = top_row.saturating_sub(page),
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::PageDown | KeyCode::Char(' '),
+                ..
+            })) => top_row += page,
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Home | KeyCode::Char('g'),
+                ..
+            })) => top_row = 0,
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::End | KeyCode::Char('G'),
+                ..
+            })) => top_row = max_top,
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('o'),
+                ..
+            })) => {
+                if let Some(url) = &notes.url {
+                    wezterm_open_url::open_url(url);
+                }
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn inline() {
+        assert_eq!(
+            parse_inline("a **b** `c` [d](https://e)", Style::Plain),
+            vec![
+                Span::new("a ", Style::Plain),
+                Span::new("b", Style::Strong),
+                Span::new(" ", Style::Plain),
+                Span::new("c", Style::Code),
+                Span::new(" ", Style::Plain),
+                Span::new("d", Style::Link("https://e".to_string())),
+            ]
+        );
+        // Unbalanced markup is left alone
+        assert_eq!(
+            parse_inline("a `b [c]", Style::Plain),
+            vec![Span::new("a `b [c]", Style::Plain)]
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let lines = render_markdown(
+            "intro\n## Changes\n* one two three four five six seven\n  * nested\n```\nlet x;\n```",
+            20,
+        );
+        let lines: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "intro",
+                "",
+                "Changes",
+                "\u{2022} one two three four",
+                "  five six seven",
+                "  \u{2022} nested",
+                "    let x;",
+            ]
+        );
+    }
+}
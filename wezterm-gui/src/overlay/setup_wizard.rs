@@ -0,0 +1,618 @@
+//! The setup wizard is shown in the first window the first time that
+//! wezterm is launched without a config file.  It lets the user pick a
+//! font, a color scheme, a keybinding flavor and whether to enable shell
+//! integration, previewing the font and colors in the window as they are
+//! highlighted, and then writes a commented wezterm.lua that reflects
+//! those choices.
+use crate::termwindow::TermWindowNotif;
+use ::window::Window;
+use config::{FontAttributes, TextStyle};
+use mux::termwiztermtab::TermWizTerminal;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+use wezterm_dynamic::{ToDynamic, Value};
+
+/// Well known programming fonts, which are offered if installed
+const FONTS: &[&str] = &[
+    "Cascadia Code",
+    "Consolas",
+    "DejaVu Sans Mono",
+    "Fira Code",
+    "Hack",
+    "IBM Plex Mono",
+    "Inconsolata",
+    "Iosevka",
+    "Menlo",
+    "Monaco",
+    "Noto Sans Mono",
+    "Roboto Mono",
+    "SF Mono",
+    "Source Code Pro",
+    "Ubuntu Mono",
+    "Victor Mono",
+];
+
+/// A selection of popular built in color schemes
+const COLOR_SCHEMES: &[&str] = &[
+    "Catppuccin Mocha",
+    "Catppuccin Latte",
+    "Tokyo Night",
+    "Dracula (Official)",
+    "GruvboxDark",
+    "GruvboxLight",
+    "nord",
+    "OneDark (base16)",
+    "GitHub Dark",
+    "Github",
+    "Kanagawa (Gogh)",
+    "Builtin Solarized Dark",
+    "Builtin Solarized Light",
+    "Tomorrow Night",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFlavor {
+    Default,
+    Tmux,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupChoices {
+    /// None to use the built in default font
+    pub font: Option<String>,
+    /// None to use the default colors
+    pub color_scheme: Option<String>,
+    pub keys: KeyFlavor,
+    pub shell_integration: bool,
+}
+
+impl Default for SetupChoices {
+    fn default() -> Self {
+        Self {
+            font: None,
+            color_scheme: None,
+            keys: KeyFlavor::Default,
+            shell_integration: false,
+        }
+    }
+}
+
+/// Returns true if the wizard should be shown in the window that is
+/// being created.  That is only the case for the first window of the
+/// first launch that has no config file, and that doesn't specify
+/// config overrides on the command line.
+/// Records that the wizard has been shown, so that dismissing it,
+/// or quitting, doesn't cause it to be shown again.
+pub fn claim_first_run() -> bool {
+    static CLAIMED: AtomicBool = AtomicBool::new(false);
+    if CLAIMED.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    if config::loaded_config_file().is_some() || config::is_config_overridden() {
+        return false;
+    }
+    let marker = config::DATA_DIR.join("setup-wizard-shown");
+    if marker.exists() {
+        return false;
+    }
+    if let Err(err) = config::create_user_owned_dirs(&config::DATA_DIR)
+        .and_then(|_| Ok(std::fs::write(&marker, b"")?))
+    {
+        // Rather than risk showing it on every launch, don't show it
+        log::error!("recording that the setup wizard was shown: {:#}", err);
+        return false;
+    }
+    true
+}
+
+/// Returns the FONTS that are present in `families`
+pub fn installed_fonts(families: &[String]) -> Vec<String> {
+    FONTS
+        .iter()
+        .filter(|font| {
+            families
+                .iter()
+                .any(|family| family.eq_ignore_ascii_case(font))
+        })
+        .map(|font| font.to_string())
+        .collect()
+}
+
+/// Quotes `s` as a lua string literal
+fn lua_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Produces the text of a wezterm.lua that reflects `choices`.
+/// `shell_integration` is the script that was installed, and the
+/// shell startup file from which it is sourced.
+pub fn generate_config(
+    choices: &SetupChoices,
+    shell_integration: Option<(&Path, Option<&Path>)>,
+) -> String {
+    let mut lua = String::new();
+    lua.push_str(
+        "-- This file was written by the wezterm setup wizard.  It is a lua\n\
+         -- script that returns the configuration; edit it to customize\n\
+         -- wezterm further, and your changes will be applied as soon as\n\
+         -- you save it.  See https://wezfurlong.org/wezterm/config/files.html\n\
+         \n\
+         local wezterm = require 'wezterm'\n\
+         local act = wezterm.action\n\
+         \n\
+         local config = wezterm.config_builder()\n\
+         \n",
+    );
+
+    lua.push_str(
+        "-- The font used to render text.  JetBrains Mono is built in to\n\
+         -- wezterm and is used when no font is specified.\n\
+         -- `wezterm ls-fonts --list-system` shows the fonts you can use.\n",
+    );
+    match &choices.font {
+        Some(font) => lua.push_str(&format!("config.font = wezterm.font {}\n", lua_quote(font))),
+        None => lua.push_str("-- config.font = wezterm.font 'JetBrains Mono'\n"),
+    }
+    lua.push_str("-- config.font_size = 12.0\n\n");
+
+    lua.push_str(
+        "-- The color scheme.  There are hundreds of built in schemes; see\n\
+         -- https://wezfurlong.org/wezterm/colorschemes/index.html\n",
+    );
+    match &choices.color_scheme {
+        Some(scheme) => lua.push_str(&format!("config.color_scheme = {}\n\n", lua_quote(scheme))),
+        None => lua.push_str("-- config.color_scheme = 'Catppuccin Mocha'\n\n"),
+    }
+
+    match choices.keys {
+        KeyFlavor::Default => lua.push_str(
+            "-- The default key bindings are in use; see\n\
+             -- https://wezfurlong.org/wezterm/config/default-keys.html\n\
+             -- Add to config.keys to define your own, eg:\n\
+             -- config.keys = {\n\
+             --   { key = 'Enter', mods = 'ALT', action = act.ToggleFullScreen },\n\
+             -- }\n\n",
+        ),
+        KeyFlavor::Tmux => lua.push_str(
+            "-- tmux-like key bindings: press CTRL-b and then a tmux key, such as\n\
+             -- % or \" to split, c to create a tab or [ for copy mode.  The\n\
+             -- default key bindings remain available too.  See\n\
+             -- https://wezfurlong.org/wezterm/config/lua/config/key_preset.html\n\
+             config.key_preset = 'tmux'\n\n",
+        ),
+    }
+
+    lua.push_str(
+        "-- Shell integration tells wezterm about the current directory and\n\
+         -- the commands that you run, so that new tabs and splits start in\n\
+         -- the same directory and you can jump between prompts.  See\n\
+         -- https://wezfurlong.org/wezterm/shell-integration.html\n",
+    );
+    match shell_integration {
+        Some((script, Some(rc))) => lua.push_str(&format!(
+            "-- It was enabled by sourcing {} from {}\n\n",
+            script.display(),
+            rc.display()
+        )),
+        Some((script, None)) => lua.push_str(&format!(
+            "-- To enable it, source {} from the startup file of your shell\n\n",
+            script.display()
+        )),
+        None => lua.push_str("-- It has not been enabled.\n\n"),
+    }
+
+    lua.push_str("return config\n");
+    lua
+}
+
+/// Returns the startup file of the user's shell, if it is one that
+/// the shell integration script supports
+fn shell_startup_file() -> Option<PathBuf> {
+    let shell = std::env::var("SHELL").ok()?;
+    match Path::new(&shell).file_name()?.to_str()? {
+        "bash" => Some(config::HOME_DIR.join(".bashrc")),
+        "zsh" => Some(
+            std::env::var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| config::HOME_DIR.clone())
+                .join(".zshrc"),
+        ),
+        _ => None,
+    }
+}
+
+/// Installs the shell integration script into the data dir, and
+/// sources it from the startup file of the user's shell if that is
+/// bash or zsh.  Returns the path to the script and the startup file.
+fn install_shell_integration() -> anyhow::Result<(PathBuf, Option<PathBuf>)> {
+    let script = config::DATA_DIR
+        .join("shell-integration")
+        .join("wezterm.sh");
+    config::create_user_owned_dirs(script.parent().unwrap())?;
+    std::fs::write(
+        &script,
+        include_str!("../../../assets/shell-integration/wezterm.sh"),
+    )?;
+
+    let rc = match shell_startup_file() {
+        Some(rc) => rc,
+        None => return Ok((script, None)),
+    };
+    let existing = std::fs::read_to_string(&rc).unwrap_or_default();
+    if !existing.contains(&*script.to_string_lossy()) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&rc)?;
+        write!(
+            file,
+            "\n# Added by the wezterm setup wizard\n[ -f \"{0}\" ] && . \"{0}\"\n",
+            script.display()
+        )?;
+    }
+    Ok((script, Some(rc)))
+}
+
+/// Writes the config file reflecting `choices` to `path`, and
+/// installs shell integration if requested, then loads the config
+fn apply_choices(choices: &SetupChoices, path: &Path) -> anyhow::Result<()> {
+    if path.exists() {
+        anyhow::bail!("{} already exists", path.display());
+    }
+    let shell_integration = if choices.shell_integration {
+        Some(install_shell_integration()?)
+    } else {
+        None
+    };
+    let lua = generate_config(
+        choices,
+        shell_integration
+            .as_ref()
+            .map(|(script, rc)| (script.as_path(), rc.as_deref())),
+    );
+    if let Some(parent) = path.parent() {
+        config::create_user_owned_dirs(parent)?;
+    }
+    std::fs::write(path, lua)?;
+    config::reload();
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Font,
+    ColorScheme,
+    Keys,
+    ShellIntegration,
+    Confirm,
+}
+
+impl Step {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Font => "Which font would you like to use?",
+            Self::ColorScheme => "Which color scheme would you like to use?",
+            Self::Keys => "Which key bindings would you like to use?",
+            Self::ShellIntegration => "Would you like to enable shell integration?",
+            Self::Confirm => "Ready to write your config file",
+        }
+    }
+}
+
+struct WizardState {
+    window: Window,
+    fonts: Vec<String>,
+    config_path: PathBuf,
+    steps: Vec<Step>,
+    step_idx: usize,
+    active_idx: usize,
+    choices: SetupChoices,
+    error: Option<String>,
+}
+
+impl WizardState {
+    fn step(&self) -> Step {
+        self.steps[self.step_idx]
+    }
+
+    fn options(&self) -> Vec<String> {
+        match self.step() {
+            Step::Font => std::iter::once("JetBrains Mono (built in)".to_string())
+                .chain(self.fonts.iter().cloned())
+                .collect(),
+            Step::ColorScheme => std::iter::once("The default colors".to_string())
+                .chain(COLOR_SCHEMES.iter().map(|s| s.to_string()))
+                .collect(),
+            Step::Keys => vec![
+                "The default wezterm key bindings".to_string(),
+                "tmux-like: CTRL-b followed by a key for splits, tabs and copy mode".to_string(),
+            ],
+            Step::ShellIntegration => vec![
+                "Yes, source it from the startup file of my shell".to_string(),
+                "No".to_string(),
+            ],
+            Step::Confirm => vec![
+                format!("Write {}", self.config_path.display()),
+                "Exit without writing a config file".to_string(),
+            ],
+        }
+    }
+
+    /// Applies the highlighted option of the current step to `choices`
+    fn choose(&self, choices: &mut SetupChoices) {
+        let idx = self.active_idx;
+        match self.step() {
+            Step::Font => choices.font = idx.checked_sub(1).map(|i| self.fonts[i].clone()),
+            Step::ColorScheme => {
+                choices.color_scheme = idx.checked_sub(1).map(|i| COLOR_SCHEMES[i].to_string())
+            }
+            Step::Keys => {
+                choices.keys = if idx == 0 {
+                    KeyFlavor::Default
+                } else {
+                    KeyFlavor::Tmux
+                }
+            }
+            Step::ShellIntegration => choices.shell_integration = idx == 0,
+            Step::Confirm => {}
+        }
+    }
+
+    /// Shows the font and colors that would result from choosing
+    /// the highlighted option in the window
+    fn preview(&self) {
+        let mut choices = self.choices.clone();
+        self.choose(&mut choices);
+
+        let mut overrides = wezterm_dynamic::Object::default();
+        if let Some(font) = &choices.font {
+            let style = TextStyle {
+                font: vec![FontAttributes::new(font)],
+                foreground: None,
+            };
+            overrides.insert(Value::String("font".to_string()), style.to_dynamic());
+        }
+        if let Some(scheme) = &choices.color_scheme {
+            overrides.insert(
+                Value::String("color_scheme".to_string()),
+                scheme.to_dynamic(),
+            );
+        }
+        self.window
+            .notify(TermWindowNotif::SetConfigOverrides(Value::Object(
+                overrides,
+            )));
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let max = self.options().len().saturating_sub(1) as isize;
+        self.active_idx = (self.active_idx as isize + delta).clamp(0, max) as usize;
+        self.preview();
+    }
+
+    fn go_to_step(&mut self, step_idx: usize) {
+        self.step_idx = step_idx;
+        self.active_idx = 0;
+        self.error.take();
+        self.preview();
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            AttributeChange::Intensity(termwiz::cell::Intensity::Bold).into(),
+            Change::Text(truncate_right(
+                &format!(
+                    "Welcome to wezterm!  Step {} of {}: {}",
+                    self.step_idx + 1,
+                    self.steps.len(),
+                    self.step().title()
+                ),
+                max_width,
+            )),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n\r\n".to_string()),
+        ];
+
+        // Leave room for the header and the help at the bottom
+        let max_rows = size.rows.saturating_sub(5).max(1);
+        let top_row = self.active_idx.saturating_sub(max_rows - 1);
+        for (idx, label) in self
+            .options()
+            .iter()
+            .enumerate()
+            .skip(top_row)
+            .take(max_rows)
+        {
+            if idx == self.active_idx {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+            changes.push(Change::Text(truncate_right(
+                &format!(" {label} "),
+                max_width,
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(size.rows.saturating_sub(1)),
+        });
+        match &self.error {
+            Some(error) => {
+                changes.push(AttributeChange::Foreground(AnsiColor::Red.into()).into());
+                changes.push(Change::Text(truncate_right(error, max_width)));
+            }
+            None => {
+                changes.push(AttributeChange::Italic(true).into());
+                changes.push(Change::Text(truncate_right(
+                    "Up/Down: choose, Enter: select, Backspace: previous step, \
+                     Escape: skip setup",
+                    max_width,
+                )));
+            }
+        }
+        changes.push(Change::AllAttributes(CellAttributes::default()));
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<()> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    return Ok(());
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow | KeyCode::Char('k'),
+                    ..
+                }) => self.move_by(-1),
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow | KeyCode::Char('j'),
+                    ..
+                }) => self.move_by(1),
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => self.move_by(-10),
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => self.move_by(10),
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Backspace | KeyCode::LeftArrow,
+                    ..
+                }) => {
+                    if self.step_idx > 0 {
+                        self.go_to_step(self.step_idx - 1);
+                    }
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    if self.step() == Step::Confirm {
+                        if self.active_idx != 0 {
+                            return Ok(());
+                        }
+                        match apply_choices(&self.choices, &self.config_path) {
+                            Ok(()) => return Ok(()),
+                            Err(err) => {
+                                self.error.replace(format!("{err:#}"));
+                            }
+                        }
+                    } else {
+                        let mut choices = self.choices.clone();
+                        self.choose(&mut choices);
+                        self.choices = choices;
+                        self.go_to_step(self.step_idx + 1);
+                    }
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the setup wizard.  `fonts` lists the FONTS that are installed.
+pub fn setup_wizard(
+    mut term: TermWizTerminal,
+    window: Window,
+    fonts: Vec<String>,
+) -> anyhow::Result<()> {
+    let mut steps = vec![Step::Font, Step::ColorScheme, Step::Keys];
+    if cfg!(unix) {
+        steps.push(Step::ShellIntegration);
+    }
+    steps.push(Step::Confirm);
+
+    let mut state = WizardState {
+        window,
+        fonts,
+        config_path: config::config_file_path(),
+        steps,
+        step_idx: 0,
+        active_idx: 0,
+        choices: SetupChoices::default(),
+        error: None,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Welcome to wezterm".to_string())])?;
+    state.render(&mut term)?;
+    let result = state.run_loop(&mut term);
+
+    // Remove the preview; the config file, if written, takes over
+    state
+        .window
+        .notify(TermWindowNotif::SetConfigOverrides(Value::default()));
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn installed() {
+        let families = vec!["fira code".to_string(), "Comic Sans MS".to_string()];
+        assert_eq!(installed_fonts(&families), vec!["Fira Code".to_string()]);
+    }
+
+    #[test]
+    fn generate() {
+        let lua = generate_config(&SetupChoices::default(), None);
+        assert!(lua.contains("-- config.font = wezterm.font 'JetBrains Mono'\n"));
+        assert!(lua.contains("-- config.color_scheme = 'Catppuccin Mocha'\n"));
+        assert!(!lua.contains("config.key_preset"));
+        assert!(lua.ends_with("return config\n"));
+
+        let choices = SetupChoices {
+            font: Some("Fira Code".to_string()),
+            color_scheme: Some("Dracula (Official)".to_string()),
+            keys: KeyFlavor::Tmux,
+            shell_integration: true,
+        };
+        let lua = generate_config(
+            &choices,
+            Some((
+                Path::new("/data/wezterm.sh"),
+                Some(Path::new("/home/.bashrc")),
+            )),
+        );
+        assert!(lua.contains("\nconfig.font = wezterm.font 'Fira Code'\n"));
+        assert!(lua.contains("\nconfig.color_scheme = 'Dracula (Official)'\n"));
+        assert!(lua.contains("\nconfig.key_preset = 'tmux'\n"));
+        assert!(lua.contains("sourcing /data/wezterm.sh from /home/.bashrc"));
+    }
+
+    #[test]
+    fn quote() {
+        assert_eq!(lua_quote("it's"), "'it\\'s'");
+        assert_eq!(lua_quote("a\\b"), "'a\\\\b'");
+    }
+}
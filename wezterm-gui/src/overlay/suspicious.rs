@@ -0,0 +1,189 @@
+//! The suspicious characters overlay lists the characters in the
+//! viewport of a pane that can disguise what its text says, along with
+//! the lines that hold them with their hidden characters spelled out.
+use crate::suspicious::SuspiciousCharacter;
+use mux::termwiztermtab::TermWizTerminal;
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+
+/// The status line
+const ROW_OVERHEAD: usize = 1;
+
+struct SuspiciousState {
+    found: Vec<SuspiciousCharacter>,
+    active_idx: usize,
+    top_row: usize,
+    max_rows: usize,
+}
+
+impl SuspiciousState {
+    fn move_by(&mut self, delta: isize) {
+        if self.found.is_empty() {
+            return;
+        }
+        let idx = (self.active_idx as isize + delta).max(0) as usize;
+        self.active_idx = idx.min(self.found.len() - 1);
+        if self.active_idx < self.top_row {
+            self.top_row = self.active_idx;
+        } else if self.active_idx >= self.top_row + self.max_rows {
+            self.top_row = self.active_idx + 1 - self.max_rows;
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(1);
+        // Each character takes a row, followed by a row for its line
+        self.max_rows = (size.rows.saturating_sub(ROW_OVERHEAD) / 2).max(1);
+
+        let status = if self.found.is_empty() {
+            "No suspicious characters were found in the viewport".to_string()
+        } else {
+            format!(
+                "{} suspicious characters in the viewport.  Escape to close",
+                self.found.len()
+            )
+        };
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            AttributeChange::Italic(true).into(),
+            Change::Text(truncate_right(&status, max_width)),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+        ];
+
+        for (row_idx, found) in self
+            .found
+            .iter()
+            .enumerate()
+            .skip(self.top_row)
+            .take(self.max_rows)
+        {
+            if row_idx == self.active_idx {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+            let position = format!("{:>4}:{:<4} ", found.row + 1, found.column + 1);
+            changes.push(Change::Text(position.clone()));
+            changes.push(AttributeChange::Foreground(AnsiColor::Red.into()).into());
+            let code = format!("U+{:04X} ", found.c as u32);
+            changes.push(Change::Text(code.clone()));
+            changes.push(AttributeChange::Foreground(ColorAttribute::Default).into());
+            changes.push(Change::Text(truncate_right(
+                &found.description,
+                max_width.saturating_sub(position.len() + code.len()),
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+
+            changes.push(AttributeChange::Foreground(AnsiColor::Grey.into()).into());
+            changes.push(Change::Text(truncate_right(
+                &format!("  {}", found.text),
+                max_width,
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text("\r\n".to_string()));
+        }
+
+        term.render(&changes)
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<()> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G' | 'C'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('q'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape | KeyCode::Enter,
+                    ..
+                }) => {
+                    return Ok(());
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('k'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('P' | 'K'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(-1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('j'),
+                    modifiers: Modifiers::NONE,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('N' | 'J'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.move_by(1);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageUp,
+                    ..
+                }) => {
+                    self.move_by(-(self.max_rows as isize));
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::PageDown,
+                    ..
+                }) => {
+                    self.move_by(self.max_rows as isize);
+                }
+                InputEvent::Mouse(MouseEvent { mouse_buttons, .. }) => {
+                    if mouse_buttons.contains(MouseButtons::VERT_WHEEL) {
+                        if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                            self.move_by(-1);
+                        } else {
+                            self.move_by(1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn suspicious_characters(
+    mut term: TermWizTerminal,
+    found: Vec<SuspiciousCharacter>,
+) -> anyhow::Result<()> {
+    let mut state = SuspiciousState {
+        found,
+        active_idx: 0,
+        top_row: 0,
+        max_rows: 0,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title("Suspicious Characters".to_string())])?;
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
@@ -0,0 +1,235 @@
+use mux::pane::PaneId;
+use mux::termwiztermtab::TermWizTerminal;
+use mux::vtinspect::{self, InspectedAction};
+use mux::Mux;
+use std::collections::VecDeque;
+use std::time::Duration;
+use termwiz::cell::{AttributeChange, CellAttributes};
+use termwiz::color::AnsiColor;
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers};
+use termwiz::surface::{Change, CursorVisibility, Position};
+use termwiz::terminal::Terminal;
+
+/// How many entries are kept for display
+const MAX_ENTRIES: usize = 10_000;
+
+struct InspectorState {
+    pane_id: PaneId,
+    entries: VecDeque<InspectedAction>,
+    next_seqno: usize,
+    filter: String,
+    paused: bool,
+}
+
+impl InspectorState {
+    /// Fetches any newly recorded entries.
+    /// Returns true if there were any.
+    fn update(&mut self) -> bool {
+        if self.paused {
+            return false;
+        }
+        let mut changed = false;
+        for entry in vtinspect::get_entries(self.pane_id, self.next_seqno) {
+            // The most recent entry may have grown as more text
+            // was printed, so replace it rather than adding it again
+            if let Some(last) = self.entries.back() {
+                if last.seqno == entry.seqno {
+                    if *last == entry {
+                        continue;
+                    }
+                    self.entries.pop_back();
+                }
+            }
+            self.next_seqno = entry.seqno;
+            self.entries.push_back(entry);
+            changed = true;
+        }
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        changed
+    }
+
+    fn clear(&mut self) {
+        if let Some(last) = self.entries.back() {
+            self.next_seqno = last.seqno + 1;
+        }
+        self.entries.clear();
+    }
+
+    fn render(&self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let cols = size.cols.max(1);
+        let rows = size.rows.saturating_sub(2);
+
+        let mut changes = vec![
+            Change::ClearScreen(Default::default()),
+            Change::CursorVisibility(CursorVisibility::Hidden),
+            AttributeChange::Reverse(true).into(),
+            Change::Text(truncate(
+                &format!(
+                    "VT Inspector: pane {}{}  filter: {}",
+                    self.pane_id,
+                    if self.paused { " (paused)" } else { "" },
+                    self.filter
+                ),
+                cols,
+            )),
+            Change::AllAttributes(CellAttributes::default()),
+            Change::Text("\r\n".to_string()),
+            AttributeChange::Foreground(AnsiColor::Grey.into()).into(),
+            Change::Text(truncate(
+                "Type to filter, CTRL-P to pause, CTRL-L to clear, ESC to exit",
+                cols,
+            )),
+            Change::AllAttributes(CellAttributes::default()),
+        ];
+
+        let matching: Vec<&InspectedAction> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.matches(&self.filter))
+            .take(rows)
+            .collect();
+
+        for entry in matching.into_iter().rev() {
+            let seqno = format!("{:>6} ", entry.seqno);
+            let name = format!("{} ", entry.name);
+            let params = if entry.params.is_empty() {
+                entry.raw.clone()
+            } else {
+                format!("{}  {}", entry.params, entry.raw)
+            };
+
+            changes.push(Change::Text("\r\n".to_string()));
+            changes.push(AttributeChange::Foreground(AnsiColor::Grey.into()).into());
+            changes.push(Change::Text(truncate(&seqno, cols)));
+            changes.push(AttributeChange::Foreground(name_color(&entry.name).into()).into());
+            changes.push(Change::Text(truncate(
+                &name,
+                cols.saturating_sub(seqno.len()),
+            )));
+            changes.push(Change::AllAttributes(CellAttributes::default()));
+            changes.push(Change::Text(truncate(
+                &params.replace(|c: char| c.is_control(), " "),
+                cols.saturating_sub(seqno.len() + name.chars().count()),
+            )));
+        }
+
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(0),
+        });
+
+        term.render(&changes)
+    }
+}
+
+fn name_color(name: &str) -> AnsiColor {
+    match name.split("::").next().unwrap_or("") {
+        "Print" => AnsiColor::White,
+        "Control" => AnsiColor::Yellow,
+        "CSI" => AnsiColor::Aqua,
+        "Esc" => AnsiColor::Fuchsia,
+        "OperatingSystemCommand" => AnsiColor::Lime,
+        _ => AnsiColor::Red,
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Shows the escape sequences that are parsed from the output of
+/// the pane with the specified id as they arrive.
+/// Runs until the user presses ESC or the pane is closed.
+pub fn show_vt_inspector(mut term: TermWizTerminal, pane_id: PaneId) -> anyhow::Result<()> {
+    term.no_grab_mouse_in_raw_mode();
+    term.render(&[Change::Title(format!("VT Inspector: pane {pane_id}"))])?;
+
+    // Recording may also have been turned on via lua, in which case
+    // it should stay on after we're done
+    let was_enabled = vtinspect::is_enabled(pane_id);
+    vtinspect::set_enabled(pane_id, true);
+
+    let result = run_inspector(&mut term, pane_id);
+
+    if !was_enabled {
+        vtinspect::set_enabled(pane_id, false);
+    }
+    result
+}
+
+fn run_inspector(term: &mut TermWizTerminal, pane_id: PaneId) -> anyhow::Result<()> {
+    let mut state = InspectorState {
+        pane_id,
+        entries: VecDeque::new(),
+        next_seqno: 0,
+        filter: String::new(),
+        paused: false,
+    };
+    state.update();
+    state.render(term)?;
+
+    loop {
+        if Mux::get().get_pane(pane_id).is_none() {
+            return Ok(());
+        }
+
+        let mut dirty = state.update();
+
+        match term.poll_input(Some(Duration::from_millis(100)))? {
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Escape,
+                ..
+            })) => {
+                if state.filter.is_empty() {
+                    return Ok(());
+                }
+                state.filter.clear();
+                dirty = true;
+            }
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('c'),
+                modifiers: Modifiers::CTRL,
+            })) => return Ok(()),
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('p'),
+                modifiers: Modifiers::CTRL,
+            })) => {
+                state.paused = !state.paused;
+                dirty = true;
+            }
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char('l'),
+                modifiers: Modifiers::CTRL,
+            })) => {
+                state.clear();
+                dirty = true;
+            }
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Backspace,
+                ..
+            })) => {
+                state.filter.pop();
+                dirty = true;
+            }
+            Some(InputEvent::Key(KeyEvent {
+                key: KeyCode::Char(c),
+                modifiers: Modifiers::NONE | Modifiers::SHIFT,
+            })) => {
+                state.filter.push(c);
+                dirty = true;
+            }
+            Some(InputEvent::Resized { .. }) => {
+                dirty = true;
+            }
+            _ => {}
+        }
+
+        if dirty {
+            state.render(term)?;
+        }
+    }
+}
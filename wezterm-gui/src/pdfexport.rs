@@ -0,0 +1,507 @@
+//! Exports the scrollback of a pane, or the rows of its selection,
+//! to a paginated PDF.
+//! The lines are rasterized using the configured fonts and the colors
+//! of the pane, so that the document looks like the terminal did, and
+//! each page is embedded in the document as an image.
+use crate::termwindow::TermWindow;
+use crate::utilsprites::RenderMetrics;
+use anyhow::Context;
+use config::keyassignment::ExportToPdfArguments;
+use config::ConfigHandle;
+use image::{Rgb, RgbImage, RgbaImage};
+use mux::pane::Pane;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use termwiz::cell::{CellAttributes, Underline};
+use termwiz::surface::Line;
+use wezterm_font::{FontConfiguration, RasterizedGlyph};
+use wezterm_term::color::ColorPalette;
+
+/// The width of the margin around the text, in cells
+const MARGIN_CELLS: usize = 2;
+/// The pages are A4, in portrait orientation
+const PAGE_WIDTH_POINTS: f64 = 595.0;
+const PAGE_HEIGHT_POINTS: f64 = 842.0;
+
+/// An image of a page, ready to be embedded in the document
+pub struct PdfPage {
+    pub width: u32,
+    pub height: u32,
+    /// The RGB pixels of the page, compressed with zlib
+    pub data: Vec<u8>,
+}
+
+impl PdfPage {
+    pub fn new(image: &RgbImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            data: miniz_oxide::deflate::compress_to_vec_zlib(image.as_raw(), 6),
+        }
+    }
+}
+
+/// Produces a PDF document with one page per image.
+/// Each image is scaled to fill the width of an A4 page.
+pub fn write_pdf(pages: &[PdfPage]) -> Vec<u8> {
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = vec![];
+
+    fn begin_object(pdf: &mut Vec<u8>, offsets: &mut Vec<usize>) {
+        offsets.push(pdf.len());
+        writeln!(pdf, "{} 0 obj", offsets.len()).ok();
+    }
+    fn end_object(pdf: &mut Vec<u8>) {
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    // Objects 1 and 2 are the catalog and the page tree.
+    // Each page then has three objects: the page, its content
+    // stream and its image.
+    let page_object = |idx: usize| 3 + idx * 3;
+
+    begin_object(&mut pdf, &mut offsets);
+    pdf.extend_from_slice(b"<< /Type /Catalog /Pages 2 0 R >>");
+    end_object(&mut pdf);
+
+    begin_object(&mut pdf, &mut offsets);
+    let kids: Vec<String> = (0..pages.len())
+        .map(|idx| format!("{} 0 R", page_object(idx)))
+        .collect();
+    write!(
+        pdf,
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    )
+    .ok();
+    end_object(&mut pdf);
+
+    for (idx, page) in pages.iter().enumerate() {
+        let object = page_object(idx);
+        let width = PAGE_WIDTH_POINTS;
+        let height = (PAGE_WIDTH_POINTS * page.height as f64 / page.width.max(1) as f64)
+            .min(PAGE_HEIGHT_POINTS);
+
+        begin_object(&mut pdf, &mut offsets);
+        write!(
+            pdf,
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width:.2} {height:.2}] \
+             /Resources << /XObject << /Im0 {} 0 R >> >> /Contents {} 0 R >>",
+            object + 2,
+            object + 1
+        )
+        .ok();
+        end_object(&mut pdf);
+
+        let content = format!("q {width:.2} 0 0 {height:.2} 0 0 cm /Im0 Do Q");
+        begin_object(&mut pdf, &mut offsets);
+        writeln!(pdf, "<< /Length {} >>\nstream", content.len()).ok();
+        pdf.extend_from_slice(content.as_bytes());
+        pdf.extend_from_slice(b"\nendstream");
+        end_object(&mut pdf);
+
+        begin_object(&mut pdf, &mut offsets);
+        writeln!(
+            pdf,
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} \
+             /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode \
+             /Length {} >>\nstream",
+            page.width,
+            page.height,
+            page.data.len()
+        )
+        .ok();
+        pdf.extend_from_slice(&page.data);
+        pdf.extend_from_slice(b"\nendstream");
+        end_object(&mut pdf);
+    }
+
+    let xref = pdf.len();
+    writeln!(pdf, "xref\n0 {}\n0000000000 65535 f ", offsets.len() + 1).ok();
+    for offset in &offsets {
+        writeln!(pdf, "{offset:010} 00000 n ").ok();
+    }
+    writeln!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF",
+        offsets.len() + 1
+    )
+    .ok();
+
+    pdf
+}
+
+/// Rasterizes terminal lines into page images
+struct PageRenderer<'a> {
+    fonts: &'a FontConfiguration,
+    config: &'a ConfigHandle,
+    palette: ColorPalette,
+    metrics: &'a RenderMetrics,
+    cols: usize,
+    lines_per_page: usize,
+}
+
+impl<'a> PageRenderer<'a> {
+    fn cell_width(&self) -> usize {
+        self.metrics.cell_size.width as usize
+    }
+
+    fn cell_height(&self) -> usize {
+        self.metrics.cell_size.height as usize
+    }
+
+    fn margin(&self) -> usize {
+        MARGIN_CELLS * self.cell_width()
+    }
+
+    fn page_width(&self) -> u32 {
+        (self.cols * self.cell_width() + 2 * self.margin()) as u32
+    }
+
+    fn page_height(&self) -> u32 {
+        (self.lines_per_page * self.cell_height() + 2 * self.margin()) as u32
+    }
+
+    fn render_page(&self, lines: &[Line]) -> anyhow::Result<RgbImage> {
+        let (r, g, b, _) = self.palette.background.to_srgb_u8();
+        let mut image = RgbImage::from_pixel(self.page_width(), self.page_height(), Rgb([r, g, b]));
+        for (idx, line) in lines.iter().enumerate() {
+            let top = self.margin() + idx * self.cell_height();
+            self.render_line(&mut image, line, top)?;
+        }
+        Ok(image)
+    }
+
+    fn colors(&self, attrs: &CellAttributes) -> (Rgb<u8>, Rgb<u8>) {
+        let mut fg = self.palette.resolve_fg(attrs.foreground());
+        let mut bg = self.palette.resolve_bg(attrs.background());
+        if attrs.reverse() {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        let (fr, fg_, fb, _) = fg.to_srgb_u8();
+        let (br, bg_, bb, _) = bg.to_srgb_u8();
+        (Rgb([fr, fg_, fb]), Rgb([br, bg_, bb]))
+    }
+
+    fn render_line(&self, image: &mut RgbImage, line: &Line, top: usize) -> anyhow::Result<()> {
+        let cell_width = self.cell_width();
+        let cell_height = self.cell_height();
+        let baseline = top as f64 + cell_height as f64 + self.metrics.descender.get();
+        let default_bg = *image.get_pixel(0, 0);
+
+        for cluster in line.cluster(None) {
+            let left = self.margin() + cluster.first_cell_idx * cell_width;
+            let width = cluster
+                .width
+                .min(self.cols.saturating_sub(cluster.first_cell_idx));
+            if width == 0 {
+                continue;
+            }
+            let (fg, bg) = self.colors(&cluster.attrs);
+            if bg != default_bg {
+                fill_rect(image, left, top, width * cell_width, cell_height, bg);
+            }
+            if cluster.attrs.invisible() {
+                continue;
+            }
+
+            let style = self.fonts.match_style(self.config, &cluster.attrs);
+            let font = self.fonts.resolve_font(style)?;
+            let glyphs = font.blocking_shape(
+                &cluster.text,
+                Some(cluster.presentation),
+                cluster.direction,
+                None,
+                None,
+            )?;
+            for info in glyphs {
+                if info.is_space {
+                    continue;
+                }
+                let glyph = font.rasterize_glyph(info.glyph_pos, info.font_idx)?;
+                if glyph.width == 0 || glyph.height == 0 {
+                    continue;
+                }
+                let cell_idx = cluster.byte_to_cell_idx(info.cluster as usize);
+                let x = (self.margin() + cell_idx * cell_width) as f64 + info.x_offset.get();
+                let y = baseline - info.y_offset.get();
+                draw_glyph(image, &glyph, x, y, cell_height as f64, fg);
+            }
+
+            if cluster.attrs.underline() != Underline::None {
+                let row = top + self.metrics.descender_row.max(0) as usize;
+                let height = self.metrics.underline_height.max(1) as usize;
+                fill_rect(image, left, row, width * cell_width, height, fg);
+            }
+            if cluster.attrs.strikethrough() {
+                let row = top + self.metrics.strike_row.max(0) as usize;
+                let height = self.metrics.underline_height.max(1) as usize;
+                fill_rect(image, left, row, width * cell_width, height, fg);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fill_rect(
+    image: &mut RgbImage,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    color: Rgb<u8>,
+) {
+    for py in y..(y + height).min(image.height() as usize) {
+        for px in x..(x + width).min(image.width() as usize) {
+            image.put_pixel(px as u32, py as u32, color);
+        }
+    }
+}
+
+/// Composites a glyph onto the image with its origin at `x` and its
+/// baseline at `y`.  Glyphs from bitmap fonts, such as color emoji,
+/// can be much larger than the cell; those are scaled down to fit.
+fn draw_glyph(
+    image: &mut RgbImage,
+    glyph: &RasterizedGlyph,
+    x: f64,
+    y: f64,
+    cell_height: f64,
+    fg: Rgb<u8>,
+) {
+    let mut bitmap =
+        match RgbaImage::from_raw(glyph.width as u32, glyph.height as u32, glyph.data.clone()) {
+            Some(bitmap) => bitmap,
+            None => return,
+        };
+    let mut scale = 1.0;
+    if !glyph.is_scaled && glyph.height as f64 > cell_height {
+        scale = cell_height / glyph.height as f64;
+        bitmap = image::imageops::resize(
+            &bitmap,
+            ((glyph.width as f64 * scale).ceil() as u32).max(1),
+            ((glyph.height as f64 * scale).ceil() as u32).max(1),
+            image::imageops::FilterType::Triangle,
+        );
+    }
+
+    let left = (x + glyph.bearing_x.get() * scale).round() as i64;
+    let top = (y - glyph.bearing_y.get() * scale).round() as i64;
+
+    for (gx, gy, pixel) in bitmap.enumerate_pixels() {
+        let (px, py) = (left + gx as i64, top + gy as i64);
+        if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+            continue;
+        }
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        let dest = image.get_pixel_mut(px as u32, py as u32);
+        for c in 0..3 {
+            let src = if glyph.has_color {
+                // Color glyphs have premultiplied alpha
+                pixel[c] as u32
+            } else {
+                fg[c] as u32 * alpha / 255
+            };
+            dest[c] = (src + dest[c] as u32 * (255 - alpha) / 255).min(255) as u8;
+        }
+    }
+}
+
+fn default_export_path() -> PathBuf {
+    let dir = dirs_next::download_dir().unwrap_or_else(|| config::HOME_DIR.clone());
+    dir.join(format!(
+        "wezterm-scrollback-{}.pdf",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+fn lines_per_page(cols: usize, metrics: &RenderMetrics) -> usize {
+    // Size the page so that its aspect ratio matches A4
+    let cell_width = metrics.cell_size.width as f64;
+    let cell_height = metrics.cell_size.height as f64;
+    let margin = (MARGIN_CELLS as f64) * cell_width;
+    let width = cols as f64 * cell_width + 2. * margin;
+    let height = width * PAGE_HEIGHT_POINTS / PAGE_WIDTH_POINTS;
+    (((height - 2. * margin) / cell_height).floor() as usize).max(1)
+}
+
+impl TermWindow {
+    /// Exports the selected rows of the pane, or all of its scrollback
+    /// and viewport if there is no selection, to a PDF
+    pub fn export_pdf(
+        &mut self,
+        pane: &Arc<dyn Pane>,
+        args: &ExportToPdfArguments,
+    ) -> anyhow::Result<()> {
+        let dims = pane.get_dimensions();
+        let rows = match self
+            .selection(pane.pane_id())
+            .range
+            .as_ref()
+            .map(|r| r.normalize())
+        {
+            Some(sel) => sel.rows(),
+            None => dims.scrollback_top..dims.physical_top + dims.viewport_rows as isize,
+        };
+        let (_first_row, mut lines) = pane.get_lines(rows);
+        // Don't produce trailing pages of blank lines
+        while lines
+            .last()
+            .map(|line| line.is_whitespace())
+            .unwrap_or(false)
+        {
+            lines.pop();
+        }
+
+        let path = match &args.path {
+            Some(path) => PathBuf::from(path),
+            None => default_export_path(),
+        };
+        let config = self.config.clone();
+        let dpi = self.dimensions.dpi;
+        let font_scale = self.fonts.get_font_scale();
+        let metrics = self.render_metrics;
+        let palette = pane.palette();
+        let cols = dims.cols;
+
+        // Rasterizing a long scrollback takes a while, so do it away from
+        // the gui thread, using fonts of its own
+        std::thread::Builder::new()
+            .name("pdf-export".to_string())
+            .spawn(move || {
+                let result = render_pdf(&config, dpi, font_scale, &metrics, palette, cols, &lines)
+                    .and_then(|(pdf, num_pages)| {
+                        std::fs::write(&path, pdf)
+                            .with_context(|| format!("writing {}", path.display()))?;
+                        Ok(num_pages)
+                    });
+                promise::spawn::spawn_into_main_thread(async move {
+                    report_export_result(&path, result);
+                })
+                .detach();
+            })?;
+        Ok(())
+    }
+}
+
+/// Rasterizes the lines into pages and produces the document.
+/// Returns the document along with its number of pages.
+fn render_pdf(
+    config: &ConfigHandle,
+    dpi: usize,
+    font_scale: f64,
+    metrics: &RenderMetrics,
+    palette: ColorPalette,
+    cols: usize,
+    lines: &[Line],
+) -> anyhow::Result<(Vec<u8>, usize)> {
+    let fonts = FontConfiguration::new(Some(config.clone()), dpi)?;
+    fonts.change_scaling(font_scale, dpi);
+    let renderer = PageRenderer {
+        fonts: &fonts,
+        config,
+        palette,
+        metrics,
+        cols,
+        lines_per_page: lines_per_page(cols, metrics),
+    };
+
+    let mut pages = vec![];
+    for chunk in lines.chunks(renderer.lines_per_page) {
+        let image = renderer.render_page(chunk)?;
+        pages.push(PdfPage::new(&image));
+    }
+    if pages.is_empty() {
+        pages.push(PdfPage::new(&renderer.render_page(&[])?));
+    }
+    Ok((write_pdf(&pages), pages.len()))
+}
+
+fn report_export_result(path: &Path, result: anyhow::Result<usize>) {
+    match result {
+        Ok(num_pages) => {
+            log::info!("Exported {num_pages} pages to {}", path.display());
+            let message = format!("Exported {num_pages} pages to {}", path.display());
+            // A relative path was written relative to our cwd; the url
+            // needs it to be absolute
+            match std::fs::canonicalize(path)
+                .ok()
+                .and_then(|path| url::Url::from_file_path(path).ok())
+            {
+                Some(url) => {
+                    wezterm_toast_notification::persistent_toast_notification_with_click_to_open_url(
+                        "Export completed",
+                        &message,
+                        url.as_str(),
+                    )
+                }
+                None => wezterm_toast_notification::persistent_toast_notification(
+                    "Export completed",
+                    &message,
+                ),
+            }
+        }
+        Err(err) => {
+            log::error!("Failed to export to {}: {err:#}", path.display());
+            wezterm_toast_notification::persistent_toast_notification(
+                "Export failed",
+                &format!("{err:#}"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pdf_structure() {
+        let image = RgbImage::from_pixel(4, 6, Rgb([255, 0, 0]));
+        let pdf = write_pdf(&[PdfPage::new(&image), PdfPage::new(&image)]);
+        // The image streams are binary; the structure around them is not
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/Kids [3 0 R 6 0 R]"));
+
+        // The cross reference table must point at each object
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|s| s.lines().next())
+            .and_then(|s| s.parse().ok())
+            .unwrap();
+        let xref = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        assert!(xref.starts_with("xref\n0 9\n"));
+        for (idx, entry) in xref.lines().skip(3).take(8).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                pdf[offset..].starts_with(format!("{} 0 obj\n", idx + 1).as_bytes()),
+                "entry {idx} {entry}"
+            );
+        }
+    }
+
+    #[test]
+    fn page_aspect() {
+        let metrics = RenderMetrics {
+            descender: wezterm_font::units::PixelLength::new(-3.0),
+            descender_row: 13,
+            descender_plus_two: 15,
+            underline_height: 1,
+            strike_row: 8,
+            cell_size: ::window::Size::new(8, 16),
+        };
+        // 80 columns plus margins are 672 pixels wide; an A4 page
+        // is then about 950 pixels tall, leaving room for 57 rows
+        assert_eq!(lines_per_page(80, &metrics), 57);
+    }
+}
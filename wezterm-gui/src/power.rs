@@ -0,0 +1,84 @@
+//! Tracks the state of the power supply so that windows can switch
+//! to the `power_saving` rendering profile when it applies.
+use crate::termwindow::TermWindowNotif;
+use config::{configuration, PowerSavingMode};
+use std::cell::Cell;
+use std::time::Duration;
+use wezterm_dynamic::ToDynamic;
+use window::{Connection, ConnectionOps, PowerState};
+
+/// How often we ask the system about the power supply
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+thread_local! {
+    static STATE: Cell<PowerState> = Cell::new(PowerState::default());
+}
+
+/// The power state, as reported to lua
+#[derive(ToDynamic)]
+pub struct PowerStateInfo {
+    pub on_battery: bool,
+    pub low_power_mode: bool,
+    /// Whether the power_saving profile is active
+    pub power_saving: bool,
+}
+
+/// Returns the most recently observed power state.
+/// Must be called on the main thread.
+pub fn power_state() -> PowerState {
+    STATE.with(|state| state.get())
+}
+
+/// Returns true if the power_saving profile should be applied
+pub fn is_power_saving(config: &config::Config) -> bool {
+    let state = power_state();
+    config
+        .power_saving
+        .is_enabled(state.on_battery, state.low_power_mode)
+}
+
+pub fn power_state_info() -> PowerStateInfo {
+    let state = power_state();
+    PowerStateInfo {
+        on_battery: state.on_battery,
+        low_power_mode: state.low_power_mode,
+        power_saving: is_power_saving(&configuration()),
+    }
+}
+
+/// Starts polling the power state.  Must be called on the main
+/// thread, after the gui connection has been established.
+/// Nothing is polled while `power_saving` is set to `Never`.
+pub fn start_monitor() {
+    promise::spawn::spawn(async move {
+        loop {
+            if configuration().power_saving == PowerSavingMode::Never {
+                smol::Timer::after(POLL_INTERVAL).await;
+                continue;
+            }
+            let new_state = match Connection::get() {
+                Some(conn) => conn.power_state(),
+                None => return,
+            };
+            let new_state = match new_state.await {
+                Ok(new_state) => new_state,
+                Err(err) => {
+                    log::debug!("power_state: {err:#}");
+                    PowerState::default()
+                }
+            };
+            if STATE.with(|state| state.replace(new_state)) != new_state {
+                log::debug!("power state changed: {new_state:?}");
+                for window in crate::frontend::front_end().gui_windows() {
+                    window
+                        .window
+                        .notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                            term_window.power_state_changed();
+                        })));
+                }
+            }
+            smol::Timer::after(POLL_INTERVAL).await;
+        }
+    })
+    .detach();
+}
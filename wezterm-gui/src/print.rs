@@ -0,0 +1,163 @@
+use anyhow::Context;
+use config::PrintDestination;
+use mux::pane::PaneId;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    /// The print jobs that are waiting for more parts of the
+    /// output of printer controller mode, keyed by pane
+    static ref PRINT_JOBS: Mutex<HashMap<PaneId, Sender<String>>> = Mutex::new(HashMap::new());
+}
+
+/// A print_destination that a print job is being written to
+enum PrintSink {
+    Disabled {
+        len: usize,
+    },
+    File {
+        file: File,
+        path: String,
+    },
+    Command {
+        child: Child,
+        stdin: ChildStdin,
+        argv: Vec<String>,
+    },
+}
+
+impl PrintSink {
+    fn open(destination: &PrintDestination) -> anyhow::Result<Self> {
+        match destination {
+            PrintDestination::Disabled => Ok(Self::Disabled { len: 0 }),
+            PrintDestination::File(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("opening {} for printing", path))?;
+                Ok(Self::File {
+                    file,
+                    path: path.clone(),
+                })
+            }
+            PrintDestination::Command(argv) => {
+                let (prog, args) = argv
+                    .split_first()
+                    .ok_or_else(|| anyhow::anyhow!("print_destination command is empty"))?;
+                let mut child = Command::new(prog)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("spawning print command {:?}", argv))?;
+                let stdin = child
+                    .stdin
+                    .take()
+                    .ok_or_else(|| anyhow::anyhow!("print command {:?} has no stdin", argv))?;
+                Ok(Self::Command {
+                    child,
+                    stdin,
+                    argv: argv.clone(),
+                })
+            }
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> anyhow::Result<()> {
+        match self {
+            Self::Disabled { len } => *len += data.len(),
+            Self::File { file, path } => file
+                .write_all(data)
+                .with_context(|| format!("writing print job to {}", path))?,
+            Self::Command { stdin, argv, .. } => stdin
+                .write_all(data)
+                .with_context(|| format!("writing print job to {:?}", argv))?,
+        }
+        Ok(())
+    }
+
+    /// Completes the print job; for a command, this closes its
+    /// stdin and waits for it to finish
+    fn finish(self) -> anyhow::Result<()> {
+        match self {
+            Self::Disabled { len } => {
+                log::info!(
+                    "Ignoring print job of {} bytes because print_destination is Disabled",
+                    len
+                );
+            }
+            Self::File { .. } => {}
+            Self::Command {
+                mut child,
+                stdin,
+                argv,
+            } => {
+                drop(stdin);
+                let status = child.wait()?;
+                if !status.success() {
+                    anyhow::bail!("print command {:?} failed: {}", argv, status);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the parts of a print job, as they arrive, to a single
+/// session of the print_destination
+fn print_parts(
+    destination: &PrintDestination,
+    parts: impl Iterator<Item = String>,
+) -> anyhow::Result<()> {
+    let mut sink = PrintSink::open(destination)?;
+    for data in parts {
+        if let Err(err) = sink.write(data.as_bytes()) {
+            // Reap the command, if any; the write error is the
+            // more interesting one to report
+            sink.finish().ok();
+            return Err(err);
+        }
+    }
+    sink.finish()
+}
+
+/// Sends a print job from `pane_id` to the configured print_destination.
+/// The output of printer controller mode arrives in several parts;
+/// while `more_follows` is true, the parts are streamed into the same
+/// file or command, which is completed by the final part.
+/// Writing to a file or a command may block, so this happens
+/// on a separate thread.
+pub fn print_job(pane_id: PaneId, data: String, more_follows: bool) {
+    let mut jobs = PRINT_JOBS.lock().unwrap();
+    let tx = match jobs.get(&pane_id) {
+        Some(tx) => tx.clone(),
+        None => {
+            let (tx, rx) = channel();
+            let destination = config::configuration().print_destination.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = print_parts(&destination, rx.into_iter()) {
+                    log::error!("while printing: {:#}", err);
+                }
+            });
+            tx
+        }
+    };
+    // If the worker failed, it has already logged why
+    tx.send(data).ok();
+    if more_follows {
+        jobs.insert(pane_id, tx);
+    } else {
+        // Dropping the last sender completes the job
+        jobs.remove(&pane_id);
+    }
+}
+
+/// Completes the print job from `pane_id`, if any, when the
+/// final part of it isn't going to be sent to the print_destination
+pub fn end_print_job(pane_id: PaneId) {
+    PRINT_JOBS.lock().unwrap().remove(&pane_id);
+}
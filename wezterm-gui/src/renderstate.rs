@@ -27,7 +27,11 @@ pub enum RenderContext {
 
 pub enum RenderFrame<'a> {
     Glium(&'a mut glium::Frame),
+    /// An off-screen framebuffer, used to capture the window
+    GliumTexture(glium::framebuffer::SimpleFrameBuffer<'a>),
     WebGpu,
+    /// An off-screen texture, used to capture the window
+    WebGpuTexture(&'a wgpu::Texture),
 }
 
 impl RenderContext {
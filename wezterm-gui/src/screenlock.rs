@@ -0,0 +1,121 @@
+//! Tracks whether the screen is locked, so that we don't make noise
+//! or check for updates while nobody is there to see it.
+//! Bells and notifications that arrive while the screen is locked are
+//! counted and summarized in a single notification once it is unlocked.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use wezterm_toast_notification::persistent_toast_notification;
+use window::{Connection, ConnectionOps};
+
+/// How often we ask the system whether the screen is locked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Default)]
+struct Suppressed {
+    bells: usize,
+    notifications: Vec<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref SUPPRESSED: Mutex<Suppressed> = Mutex::new(Suppressed::default());
+}
+
+/// Returns true if the screen was locked when we last checked.
+/// This is safe to call from any thread.
+pub fn is_screen_locked() -> bool {
+    LOCKED.load(Ordering::Relaxed)
+}
+
+/// Records that the audible bell was not sounded because the
+/// screen is locked
+pub fn note_suppressed_bell() {
+    SUPPRESSED.lock().unwrap().bells += 1;
+}
+
+/// Records that a notification with the specified title was not
+/// shown because the screen is locked
+pub fn note_suppressed_notification(title: &str) {
+    SUPPRESSED
+        .lock()
+        .unwrap()
+        .notifications
+        .push(title.to_string());
+}
+
+/// Starts polling the lock state of the screen.  Must be called on
+/// the main thread, after the gui connection has been established.
+pub fn start_monitor() {
+    promise::spawn::spawn(async move {
+        loop {
+            let locked = match Connection::get() {
+                Some(conn) => conn.is_screen_locked(),
+                None => return,
+            };
+            let locked = locked.await.unwrap_or(false);
+            if LOCKED.swap(locked, Ordering::Relaxed) != locked {
+                log::debug!("screen locked: {locked}");
+                if !locked {
+                    show_summary();
+                }
+            }
+            smol::Timer::after(POLL_INTERVAL).await;
+        }
+    })
+    .detach();
+}
+
+fn show_summary() {
+    let suppressed = std::mem::take(&mut *SUPPRESSED.lock().unwrap());
+    if let Some(message) = summarize(&suppressed) {
+        persistent_toast_notification("While the screen was locked", &message);
+    }
+}
+
+fn summarize(suppressed: &Suppressed) -> Option<String> {
+    let mut lines = vec![];
+    match suppressed.bells {
+        0 => {}
+        1 => lines.push("The bell rang once".to_string()),
+        n => lines.push(format!("The bell rang {n} times")),
+    }
+    match suppressed.notifications.as_slice() {
+        [] => {}
+        [title] => lines.push(title.to_string()),
+        [.., last] => lines.push(format!(
+            "{} notifications, the last of which was: {last}",
+            suppressed.notifications.len()
+        )),
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        assert_eq!(summarize(&Suppressed::default()), None);
+        assert_eq!(
+            summarize(&Suppressed {
+                bells: 2,
+                notifications: vec!["build finished".to_string()],
+            }),
+            Some("The bell rang 2 times\nbuild finished".to_string())
+        );
+        assert_eq!(
+            summarize(&Suppressed {
+                bells: 0,
+                notifications: vec!["one".to_string(), "two".to_string()],
+            }),
+            Some("2 notifications, the last of which was: two".to_string())
+        );
+    }
+}
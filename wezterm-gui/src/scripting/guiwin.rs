@@ -198,6 +198,64 @@ impl UserData for GuiWin {
                 .notify(TermWindowNotif::SetConfigOverrides(value));
             Ok(())
         });
+        methods.add_async_method(
+            "set_config_profile",
+            |_, this, name: Option<String>| async move {
+                let (tx, rx) = smol::channel::bounded(1);
+                this.window
+                    .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        tx.try_send(term_window.set_config_profile(name)).ok();
+                    })));
+                rx.recv()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{:#}", e))
+                    .map_err(luaerr)?
+                    .map_err(luaerr)
+            },
+        );
+        methods.add_async_method("get_config_profile", |_, this, _: ()| async move {
+            let (tx, rx) = smol::channel::bounded(1);
+            this.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    tx.try_send(term_window.config_profile()).ok();
+                })));
+            let result = rx
+                .recv()
+                .await
+                .map_err(|e| anyhow::anyhow!("{:#}", e))
+                .map_err(luaerr)?;
+
+            Ok(result)
+        });
+        methods.add_method("set_opacity", |_, this, opacity: Option<f32>| {
+            this.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    term_window.set_window_opacity(opacity);
+                })));
+            Ok(())
+        });
+        methods.add_method("set_background_blur", |_, this, blur: Option<i64>| {
+            this.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    term_window.set_window_background_blur(blur);
+                })));
+            Ok(())
+        });
+        methods.add_method(
+            "set_pane_visual_bell",
+            |_, this, (pane, visual_bell): (UserDataRef<MuxPane>, mlua::Value)| {
+                let pane_id = pane.0;
+                let visual_bell: Option<config::VisualBell> = match visual_bell {
+                    mlua::Value::Nil => None,
+                    value => Some(from_lua_value_dynamic(value)?),
+                };
+                this.window
+                    .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        term_window.set_pane_visual_bell(pane_id, visual_bell);
+                    })));
+                Ok(())
+            },
+        );
         methods.add_async_method("is_focused", |_, this, _: ()| async move {
             let (tx, rx) = smol::channel::bounded(1);
             this.window
@@ -335,5 +393,16 @@ impl UserData for GuiWin {
                 Ok(result)
             },
         );
+        methods.add_async_method(
+            "capture",
+            |lua, this, pane: Option<UserDataRef<MuxPane>>| async move {
+                let pane_id = pane.map(|pane| pane.0);
+                let image = crate::termwindow::capture::capture_window(&this.window, pane_id)
+                    .await
+                    .map_err(luaerr)?;
+                let png = crate::termwindow::capture::encode_png(&image).map_err(luaerr)?;
+                lua.create_string(&png)
+            },
+        );
     }
 }
@@ -8,6 +8,7 @@ use luahelper::dynamic_to_lua_value;
 use mux::window::WindowId as MuxWindowId;
 use std::collections::HashMap;
 use wezterm_dynamic::ToDynamic;
+use window::ConnectionOps;
 
 pub mod guiwin;
 
@@ -78,6 +79,29 @@ pub fn register(lua: &Lua) -> anyhow::Result<()> {
         })?,
     )?;
 
+    window_mod.set(
+        "screen_is_locked",
+        lua.create_function(|_, _: ()| Ok(crate::screenlock::is_screen_locked()))?,
+    )?;
+
+    window_mod.set(
+        "idle_time",
+        lua.create_async_function(|_, _: ()| async move {
+            let idle_time = ::window::Connection::get()
+                .ok_or_else(|| mlua::Error::external("not called on gui thread"))?
+                .idle_time();
+            let idle_time = idle_time.await.ok().flatten();
+            Ok(idle_time.map(|idle| idle.as_secs_f64()))
+        })?,
+    )?;
+
+    window_mod.set(
+        "power_state",
+        lua.create_function(|lua, _: ()| {
+            dynamic_to_lua_value(lua, crate::power::power_state_info().to_dynamic())
+        })?,
+    )?;
+
     window_mod.set(
         "enumerate_gpus",
         lua.create_function(|_, _: ()| {
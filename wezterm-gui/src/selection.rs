@@ -1,8 +1,10 @@
 // The range_plus_one lint can't see when the LHS is not compatible with
 // and inclusive range
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::range_plus_one))]
-use mux::pane::Pane;
+use mux::pane::{CachePolicy, Pane};
+use regex::Regex;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::ops::Range;
 use termwiz::surface::line::DoubleClickRange;
 use termwiz::surface::SequenceNo;
@@ -164,9 +166,131 @@ pub struct SelectionRange {
     pub end: SelectionCoordinate,
 }
 
-fn is_double_click_word(s: &str) -> bool {
+/// The quotes that delimit a string for `enclosing_unit`
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+/// The brackets that delimit a region for `enclosing_unit`
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// Returns the position of the last `open` in `text` that isn't
+/// closed by a `close` later in `text`
+fn unclosed_bracket(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in text.char_indices().rev() {
+        if c == close {
+            depth += 1;
+        } else if c == open {
+            if depth == 0 {
+                return Some(idx);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Returns the position of the first `close` in `text` that isn't
+/// opened by an `open` earlier in `text`
+fn unopened_bracket(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (idx, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(idx);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Returns the smallest range of `text` that encloses `sel` and is
+/// larger than it, taken from the matches of `patterns` and their
+/// first capture groups, and from the quoted strings and bracketed
+/// regions around `sel`, both with and without their delimiters
+fn enclosing_unit(text: &str, sel: Range<usize>, patterns: &[Regex]) -> Option<Range<usize>> {
+    let mut candidates = vec![];
+    for re in patterns {
+        for caps in re.captures_iter(text) {
+            candidates.extend(caps.get(0).map(|m| m.range()));
+            candidates.extend(caps.get(1).map(|m| m.range()));
+        }
+    }
+    let (before, after) = (&text[..sel.start], &text[sel.end..]);
+    for quote in QUOTES {
+        if let (Some(open), Some(close)) = (before.rfind(quote), after.find(quote)) {
+            let close = sel.end + close;
+            candidates.push(open + 1..close);
+            candidates.push(open..close + 1);
+        }
+    }
+    for (open, close) in BRACKETS {
+        if let (Some(open), Some(close)) = (
+            unclosed_bracket(before, open, close),
+            unopened_bracket(after, open, close),
+        ) {
+            let close = sel.end + close;
+            candidates.push(open + 1..close);
+            candidates.push(open..close + 1);
+        }
+    }
+    candidates
+        .into_iter()
+        .filter(|r| r.start <= sel.start && r.end >= sel.end && r.len() > sel.len())
+        .min_by_key(|r| r.len())
+}
+
+/// The user var with which the application in a pane can set the
+/// word boundary for its output
+const WORD_BOUNDARY_USER_VAR: &str = "WEZTERM_SELECTION_WORD_BOUNDARY";
+
+/// Returns the characters that end a word in `pane`: those set by its
+/// user var, or else those configured for its foreground process, or
+/// else `selection_word_boundary`
+fn word_boundary(pane: &dyn Pane) -> String {
+    let config = config::configuration();
+    choose_word_boundary(
+        pane.copy_user_vars().remove(WORD_BOUNDARY_USER_VAR),
+        || {
+            if config.selection_word_boundary_by_process.is_empty() {
+                None
+            } else {
+                pane.get_foreground_process_name(CachePolicy::AllowStale)
+            }
+        },
+        &config.selection_word_boundary_by_process,
+        &config.selection_word_boundary,
+    )
+}
+
+/// Picks the word boundary from the value of the user var, or else
+/// from `by_process` for the foreground process, whose path is only
+/// looked up if it is needed, or else `default`
+fn choose_word_boundary(
+    user_var: Option<String>,
+    process_path: impl FnOnce() -> Option<String>,
+    by_process: &HashMap<String, String>,
+    default: &str,
+) -> String {
+    if let Some(boundary) = user_var {
+        return boundary;
+    }
+    if let Some(path) = process_path() {
+        let name = std::path::Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        if let Some(boundary) = by_process.get(&name).or_else(|| by_process.get(&path)) {
+            return boundary.clone();
+        }
+    }
+    default.to_string()
+}
+
+fn is_double_click_word(s: &str, boundary: &str) -> bool {
     match s.chars().count() {
-        1 => !config::configuration().selection_word_boundary.contains(s),
+        1 => !boundary.contains(s),
         0 => false,
         _ => true,
     }
@@ -238,6 +362,7 @@ impl SelectionRange {
 
     /// Computes the selection range for the word around the specified coords
     pub fn word_around(start: SelectionCoordinate, pane: &dyn Pane) -> Self {
+        let boundary = word_boundary(pane);
         for logical in pane.get_logical_lines(start.y..start.y + 1) {
             if !logical.contains_y(start.y) {
                 continue;
@@ -247,7 +372,7 @@ impl SelectionRange {
                 let start_idx = logical.xy_to_logical_x(start_x, start.y);
                 return match logical
                     .logical
-                    .compute_double_click_range(start_idx, is_double_click_word)
+                    .compute_double_click_range(start_idx, |s| is_double_click_word(s, &boundary))
                 {
                     DoubleClickRange::RangeWithWrap(click_range)
                     | DoubleClickRange::Range(click_range) => {
@@ -268,6 +393,70 @@ impl SelectionRange {
         Self { start, end: start }
     }
 
+    /// Computes the smallest path, URL or other text matched by
+    /// `patterns`, quoted string or bracketed region that encloses this
+    /// range and is larger than it, or None if there is none within
+    /// the logical line that holds it
+    pub fn semantic_unit_around(&self, pane: &dyn Pane, patterns: &[Regex]) -> Option<Self> {
+        let norm = self.normalize();
+        let (start, end) = if norm.start.y == norm.end.y && norm.start.x > norm.end.x {
+            (norm.end, norm.start)
+        } else {
+            (norm.start, norm.end)
+        };
+        let logical = pane
+            .get_logical_lines(start.y..end.y + 1)
+            .into_iter()
+            .find(|logical| logical.contains_y(start.y))?;
+        if !logical.contains_y(end.y) {
+            return None;
+        }
+
+        // The byte range of the text of each cell
+        let mut text = String::new();
+        let mut cells = vec![];
+        for cell in logical.logical.visible_cells() {
+            let bytes = text.len()..text.len() + cell.str().len();
+            cells.push((bytes, cell.cell_index(), cell.width()));
+            text.push_str(cell.str());
+        }
+
+        let len = logical.logical.len();
+        let first_x = match start.x {
+            SelectionX::Cell(x) => logical.xy_to_logical_x(x.min(len), start.y),
+            SelectionX::BeforeZero => logical.xy_to_logical_x(0, start.y),
+        };
+        let last_x = match end.x {
+            SelectionX::Cell(x) => logical.xy_to_logical_x(x.min(len), end.y),
+            SelectionX::BeforeZero => logical.xy_to_logical_x(0, end.y),
+        };
+        let sel_start = cells
+            .iter()
+            .find(|(_, idx, width)| idx + width > first_x)
+            .map(|(bytes, _, _)| bytes.start)?;
+        let sel_end = cells
+            .iter()
+            .rev()
+            .find(|(_, idx, _)| *idx <= last_x)
+            .map(|(bytes, _, _)| bytes.end)?;
+
+        let unit = enclosing_unit(&text, sel_start..sel_end.max(sel_start), patterns)?;
+        let (_, first_idx, _) = cells
+            .iter()
+            .rev()
+            .find(|(bytes, _, _)| bytes.start <= unit.start)?;
+        let (_, last_idx, last_width) = cells
+            .iter()
+            .rev()
+            .find(|(bytes, _, _)| bytes.start < unit.end)?;
+        let (start_y, start_x) = logical.logical_x_to_physical_coord(*first_idx);
+        let (end_y, end_x) = logical.logical_x_to_physical_coord(last_idx + last_width - 1);
+        Some(Self {
+            start: SelectionCoordinate::x_y(start_x, start_y),
+            end: SelectionCoordinate::x_y(end_x, end_y),
+        })
+    }
+
     /// Extends the current selection by unioning it with another selection range
     pub fn extend_with(&self, other: Self) -> Self {
         let norm = self.normalize();
@@ -355,3 +544,55 @@ impl SelectionRange {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn enclosing_units() {
+        let patterns = [Regex::new(r"(?:[.\w\-@~]+)?(?:/[.\w\-@]+)+").unwrap()];
+        let text = r#"open("/tmp/a b.txt", [x, (y)])"#;
+        let unit = |sel: Range<usize>| enclosing_unit(text, sel, &patterns).map(|r| &text[r]);
+
+        // The path pattern stops at the space, and the quotes don't
+        assert_eq!(unit(7..8), Some("/tmp/a"));
+        assert_eq!(unit(6..12), Some("/tmp/a b.txt"));
+        assert_eq!(unit(6..18), Some(r#""/tmp/a b.txt""#));
+        assert_eq!(unit(5..19), Some(r#""/tmp/a b.txt", [x, (y)]"#));
+        assert_eq!(unit(26..27), Some("(y)"));
+        assert_eq!(unit(25..28), Some("x, (y)"));
+        assert_eq!(unit(0..text.len()), None);
+    }
+
+    #[test]
+    fn word_boundary_precedence() {
+        let by_process: HashMap<String, String> = [
+            ("vim".to_string(), " ".to_string()),
+            ("/opt/bin/less".to_string(), " /".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let choose = |user_var: Option<&str>, path: Option<&str>| {
+            choose_word_boundary(
+                user_var.map(|s| s.to_string()),
+                || path.map(|s| s.to_string()),
+                &by_process,
+                " \t\n",
+            )
+        };
+
+        // The user var wins over the process
+        assert_eq!(choose(Some("|"), Some("/usr/bin/vim")), "|");
+        assert_eq!(choose(Some("|"), None), "|");
+
+        // The process is matched by name before its full path
+        assert_eq!(choose(None, Some("/usr/bin/vim")), " ");
+        assert_eq!(choose(None, Some("/opt/bin/less")), " /");
+        assert_eq!(choose(None, Some("/usr/bin/less")), " \t\n");
+
+        // Otherwise the configured boundary applies
+        assert_eq!(choose(None, Some("/bin/zsh")), " \t\n");
+        assert_eq!(choose(None, None), " \t\n");
+    }
+}
@@ -39,6 +39,8 @@ struct ShaderUniform {
   foreground_text_hsb: vec3<f32>,
   milliseconds: u32,
   projection: mat4x4<f32>,
+  window_hsb: vec3<f32>,
+  _padding: u32,
 };
 @group(0) @binding(0) var<uniform> uniforms: ShaderUniform;
 
@@ -119,6 +121,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
   }
 
   color = apply_hsv(color, hsv);
+  color = apply_hsv(color, uniforms.window_hsb);
 
   return color;
 }
@@ -3,6 +3,7 @@ use config::keyassignment::SpawnCommand;
 use config::TermConfig;
 use mux::activity::Activity;
 use mux::domain::SplitSource;
+use mux::pane::Pane;
 use mux::tab::SplitRequest;
 use mux::window::WindowId as MuxWindowId;
 use mux::Mux;
@@ -17,6 +18,17 @@ pub enum SpawnWhere {
     SplitPane(SplitRequest),
 }
 
+/// Gives `pane` a copy of `term_config` that keeps the domain of the
+/// pane, so that the escape_sequence_policy of the domain still applies
+pub fn set_pane_term_config(pane: &Arc<dyn Pane>, term_config: &TermConfig) {
+    let domain_name = Mux::try_get()
+        .and_then(|mux| mux.get_domain(pane.domain_id()))
+        .map(|domain| domain.domain_name().to_string());
+    pane.set_config(Arc::new(
+        term_config.with_domain_name(domain_name.as_deref()),
+    ));
+}
+
 pub fn spawn_command_impl(
     spawn: &SpawnCommand,
     spawn_where: SpawnWhere,
@@ -77,6 +89,14 @@ pub async fn spawn_command_internal(
             builder.cwd(cwd);
         }
         Some(builder)
+    } else if !spawn.set_environment_variables.is_empty() {
+        // Run the default program of the domain, with the
+        // additional environment
+        let mut builder = CommandBuilder::new_default_prog();
+        for (k, v) in spawn.set_environment_variables.iter() {
+            builder.env(k, v);
+        }
+        Some(builder)
     } else {
         None
     };
@@ -103,12 +123,13 @@ pub async fn spawn_command_internal(
                         SplitSource::Spawn {
                             command: cmd_builder,
                             command_dir: cwd,
+                            exit_behavior: spawn.exit_behavior,
                         },
                         spawn.domain,
                     )
                     .await
                     .context("split_pane")?;
-                pane.set_config(term_config);
+                set_pane_term_config(&pane, &term_config);
             } else {
                 bail!("there is no active tab while splitting pane!?");
             }
@@ -123,6 +144,7 @@ pub async fn spawn_command_internal(
                     spawn.domain,
                     cmd_builder,
                     cwd,
+                    spawn.exit_behavior,
                     size,
                     current_pane_id,
                     workspace,
@@ -135,7 +157,7 @@ pub async fn spawn_command_internal(
             // Otherwise, we'll pick them up when we later respond to
             // the new window being created.
             if Some(window_id) == src_window_id {
-                pane.set_config(term_config);
+                set_pane_term_config(&pane, &term_config);
             }
         }
     };
@@ -0,0 +1,147 @@
+//! Highlights the characters of a pane that can disguise what its
+//! text says, as found by `termwiz::suspicious`, and describes them
+//! for the RevealSuspiciousCharacters overlay.
+use config::SuspiciousCharacterHighlight;
+use termwiz::cell::{Cell, Underline};
+use termwiz::color::AnsiColor;
+use termwiz::suspicious::{is_bidi_control, is_invisible, scan_line, Finding, Reason};
+use wezterm_term::{Line, StableRowIndex};
+
+/// A suspicious character in the viewport of a pane
+pub struct SuspiciousCharacter {
+    pub row: StableRowIndex,
+    pub column: usize,
+    pub c: char,
+    pub description: String,
+    /// The line that holds the character, with the hidden characters
+    /// spelled out
+    pub text: String,
+}
+
+fn is_hidden(c: char) -> bool {
+    is_bidi_control(c) || is_invisible(c)
+}
+
+/// Returns a copy of `line` in which the cells that hold suspicious
+/// characters are highlighted, or None if there is nothing to highlight.
+/// Hidden characters are removed from the copy, as they would
+/// otherwise upset the shaping of the cells that hold them.
+pub fn highlight_line(line: &Line, highlight: SuspiciousCharacterHighlight) -> Option<Line> {
+    if highlight == SuspiciousCharacterHighlight::None {
+        return None;
+    }
+    let findings = scan_line(line);
+    if findings.is_empty() {
+        return None;
+    }
+
+    let mut highlighted = line.clone();
+    // The copy would otherwise share the cached shaping of `line`
+    highlighted.clear_appdata();
+    let seqno = highlighted.current_seqno();
+
+    for (idx, finding) in findings.iter().enumerate() {
+        // Findings are ordered by cell, so only the first one of each
+        // cell needs to rewrite it
+        if idx > 0 && findings[idx - 1].cell_index == finding.cell_index {
+            continue;
+        }
+        let hidden: Vec<char> = findings
+            .iter()
+            .filter(|f| f.cell_index == finding.cell_index)
+            .filter(|f| matches!(f.reason, Reason::Bidi | Reason::Invisible))
+            .map(|f| f.c)
+            .collect();
+
+        let cell = match line.get_cell(finding.cell_index) {
+            Some(cell) => cell,
+            None => continue,
+        };
+        let mut text: String = cell.str().chars().filter(|c| !hidden.contains(c)).collect();
+        if text.is_empty() {
+            text.push(' ');
+        }
+        let mut attrs = cell.attrs().clone();
+        attrs
+            .set_underline(Underline::Curly)
+            .set_underline_color(AnsiColor::Red);
+        if highlight == SuspiciousCharacterHighlight::Reverse {
+            attrs.set_reverse(!attrs.reverse());
+        }
+        let cell = Cell::new_grapheme_with_width(&text, cell.width(), attrs);
+        highlighted.set_cell(finding.cell_index, cell, seqno);
+    }
+
+    Some(highlighted)
+}
+
+/// Returns the text of `line` with its hidden characters spelled out
+/// as `<U+XXXX>`
+pub fn reveal_text(line: &Line) -> String {
+    let mut text = String::new();
+    for c in line.as_str().chars() {
+        if is_hidden(c) {
+            text.push_str(&format!("<U+{:04X}>", c as u32));
+        } else {
+            text.push(c);
+        }
+    }
+    text.trim_end().to_string()
+}
+
+fn describe(finding: &Finding) -> String {
+    match finding.reason {
+        Reason::Bidi => "bidi control, reorders the text that follows".to_string(),
+        Reason::Invisible => "invisible character".to_string(),
+        Reason::Blank => "letter that is displayed as a blank".to_string(),
+        Reason::MixedScript { letter, word } => format!("{letter:?} letter in a {word:?} word"),
+    }
+}
+
+/// Finds the suspicious characters in `lines`, the first of which
+/// is at `top`
+pub fn find_suspicious_characters(top: StableRowIndex, lines: &[Line]) -> Vec<SuspiciousCharacter> {
+    let mut found = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        let findings = scan_line(line);
+        if findings.is_empty() {
+            continue;
+        }
+        let text = reveal_text(line);
+        for finding in findings {
+            found.push(SuspiciousCharacter {
+                row: top + idx as StableRowIndex,
+                column: finding.cell_index,
+                c: finding.c,
+                description: describe(&finding),
+                text: text.clone(),
+            });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::cell::CellAttributes;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn highlight() {
+        let mut line = Line::from_text("abc", &CellAttributes::default(), SEQ_ZERO, None);
+        assert!(highlight_line(&line, SuspiciousCharacterHighlight::Underline).is_none());
+
+        line.set_cell_grapheme(1, "\u{202e}b", 1, CellAttributes::default(), SEQ_ZERO);
+        assert_eq!(reveal_text(&line), "a<U+202E>bc");
+        assert!(highlight_line(&line, SuspiciousCharacterHighlight::None).is_none());
+
+        let highlighted = highlight_line(&line, SuspiciousCharacterHighlight::Reverse).unwrap();
+        let cell = highlighted.get_cell(1).unwrap();
+        assert_eq!(cell.str(), "b");
+        assert_eq!(cell.attrs().underline(), Underline::Curly);
+        assert!(cell.attrs().reverse());
+        assert_eq!(highlighted.get_cell(2).unwrap().str(), "c");
+        assert_eq!(highlighted.as_str(), "abc");
+    }
+}
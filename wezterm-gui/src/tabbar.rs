@@ -122,6 +122,9 @@ fn compute_tab_title(
                 } else {
                     tab.tab_title.clone()
                 };
+                if tab.has_bell {
+                    title = format!("{}{}", config.tab_bell_badge, title);
+                }
                 let classic_spacing = if config.use_fancy_tab_bar { "" } else { " " };
                 if config.show_tab_index_in_tab_bar {
                     title = format!(
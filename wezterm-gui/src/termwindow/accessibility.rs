@@ -0,0 +1,315 @@
+//! Builds the accessibility tree for a window, so that screen readers
+//! can read the content of the active pane and are told about new output.
+//!
+//! The tree has this shape:
+//!
+//! * The window
+//!   * A terminal node, holding the text of the active pane
+//!     * One text box per visible line
+//!   * A live region containing the most recently output lines
+use crate::TermWindow;
+use mux::pane::{Pane, PaneId};
+use mux::renderable::StableCursorPosition;
+use std::sync::Arc;
+use termwiz::surface::SequenceNo;
+use wezterm_term::StableRowIndex;
+use window::accessibility::accesskit::{
+    Live, NodeBuilder, NodeClassSet, NodeId, Role, TextPosition, TextSelection, Tree, TreeUpdate,
+};
+use window::accessibility::AccessibilityAdapter;
+use window::Window;
+
+const WINDOW_NODE: u64 = 1;
+const TERMINAL_NODE: u64 = 2;
+const LIVE_REGION_NODE: u64 = 3;
+/// Line nodes are allocated sequentially from this value,
+/// offset by their row in the viewport
+const FIRST_LINE_NODE: u64 = 100;
+
+fn node_id(id: u64) -> NodeId {
+    NodeId(id)
+}
+
+fn tree() -> Tree {
+    Tree {
+        app_name: Some("wezterm".to_string()),
+        toolkit_name: Some("wezterm".to_string()),
+        toolkit_version: Some(config::wezterm_version().to_string()),
+        ..Tree::new(node_id(WINDOW_NODE))
+    }
+}
+
+/// Identifies what was last published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PublishedKey {
+    pane_id: PaneId,
+    seqno: SequenceNo,
+    cursor: StableCursorPosition,
+    viewport: Option<StableRowIndex>,
+}
+
+pub struct AccessibilityState {
+    adapter: AccessibilityAdapter,
+    classes: NodeClassSet,
+    /// Identifies what we last published, so that we can avoid
+    /// rebuilding the tree when nothing has changed
+    last_published: Option<PublishedKey>,
+    /// The pane that was last published, along with the last row
+    /// of its output that was published, used to figure out what
+    /// should be announced via the live region
+    output_end: Option<(PaneId, StableRowIndex)>,
+}
+
+impl AccessibilityState {
+    pub fn new(window: &Window) -> Option<Self> {
+        let adapter = AccessibilityAdapter::new(window, || {
+            let mut classes = NodeClassSet::new();
+            let mut window = NodeBuilder::new(Role::Window);
+            window.set_name("wezterm");
+            TreeUpdate {
+                nodes: vec![(node_id(WINDOW_NODE), window.build(&mut classes))],
+                tree: Some(tree()),
+                focus: node_id(WINDOW_NODE),
+            }
+        })?;
+        Some(Self {
+            adapter,
+            classes: NodeClassSet::new(),
+            last_published: None,
+            output_end: None,
+        })
+    }
+
+    pub fn update_window_focus_state(&self, focused: bool) {
+        self.adapter.update_window_focus_state(focused);
+    }
+
+    fn update(&mut self, pane: &Arc<dyn Pane>, viewport: Option<StableRowIndex>) {
+        let key = PublishedKey {
+            pane_id: pane.pane_id(),
+            seqno: pane.get_current_seqno(),
+            cursor: pane.get_cursor_position(),
+            viewport,
+        };
+        if self.last_published == Some(key) {
+            return;
+        }
+
+        let classes = &mut self.classes;
+        let output_end = match self.output_end {
+            Some((pane_id, end)) if pane_id == key.pane_id => Some(end),
+            _ => None,
+        };
+        let mut new_output_end = output_end;
+        let published = self.adapter.update_if_active(|| {
+            let (update, end) = build_tree(classes, output_end, pane, key);
+            new_output_end = end;
+            update
+        });
+        // Until an assistive technology connects, nothing is published,
+        // and it must be given the current tree once it does
+        if published {
+            self.last_published.replace(key);
+            self.output_end = new_output_end.map(|end| (key.pane_id, end));
+        }
+    }
+}
+
+/// Returns the indices of the `lines`, starting at `first_row`, that
+/// hold new output to announce, along with the last row of output
+/// that has been published once they are.
+///
+/// Only the rows below `output_end`, the end of the output that was
+/// previously published, are announced, so that scrolling back doesn't
+/// announce the history as if it were new.  The line with the cursor
+/// is likely still being edited, so it isn't announced, nor does it
+/// count as published output, until the cursor moves on.  Nothing is
+/// announced when there is no previously published output to compare
+/// with, as the whole content is published in the tree.
+fn rows_to_announce(
+    first_row: StableRowIndex,
+    lines: &[String],
+    cursor_row: StableRowIndex,
+    output_end: Option<StableRowIndex>,
+) -> (Vec<usize>, Option<StableRowIndex>) {
+    let mut announce = vec![];
+    let mut end = output_end;
+    for (idx, text) in lines.iter().enumerate() {
+        let row = first_row + idx as StableRowIndex;
+        if row == cursor_row || text.is_empty() {
+            continue;
+        }
+        if let Some(output_end) = output_end {
+            if row > output_end {
+                announce.push(idx);
+            }
+        }
+        end = Some(end.map_or(row, |end| end.max(row)));
+    }
+    (announce, end)
+}
+
+fn build_tree(
+    classes: &mut NodeClassSet,
+    output_end: Option<StableRowIndex>,
+    pane: &Arc<dyn Pane>,
+    key: PublishedKey,
+) -> (TreeUpdate, Option<StableRowIndex>) {
+    let cursor = key.cursor;
+    let dims = pane.get_dimensions();
+    let top = key.viewport.unwrap_or(dims.physical_top);
+    let (first_row, lines) = pane.get_lines(top..top + dims.viewport_rows as StableRowIndex);
+    let texts: Vec<String> = lines
+        .iter()
+        .map(|line| line.as_str().trim_end().to_string())
+        .collect();
+    let (announce, output_end) = rows_to_announce(first_row, &texts, cursor.y, output_end);
+
+    let mut nodes = vec![];
+    let mut line_ids = vec![];
+    let mut selection = None;
+
+    for (idx, text) in texts.iter().enumerate() {
+        let row = first_row + idx as StableRowIndex;
+        let id = node_id(FIRST_LINE_NODE + idx as u64);
+
+        if row == cursor.y {
+            let character_index = text.chars().count().min(cursor.x);
+            let position = TextPosition {
+                node: id,
+                character_index,
+            };
+            selection.replace(TextSelection {
+                anchor: position,
+                focus: position,
+            });
+        }
+
+        let mut node = NodeBuilder::new(Role::InlineTextBox);
+        node.set_character_lengths(
+            text.chars()
+                .map(|c| c.len_utf8() as u8)
+                .collect::<Vec<u8>>(),
+        );
+        node.set_word_lengths(word_lengths(text));
+        node.set_value(text.clone());
+        nodes.push((id, node.build(classes)));
+        line_ids.push(id);
+    }
+    let announce: Vec<&str> = announce
+        .into_iter()
+        .map(|idx| texts[idx].as_str())
+        .collect();
+
+    let title = pane.get_title();
+
+    let mut terminal = NodeBuilder::new(Role::Terminal);
+    terminal.set_name(title.clone());
+    terminal.set_children(line_ids);
+    if let Some(selection) = selection {
+        terminal.set_text_selection(selection);
+    }
+    nodes.push((node_id(TERMINAL_NODE), terminal.build(classes)));
+
+    let mut live = NodeBuilder::new(Role::Log);
+    live.set_live(Live::Polite);
+    live.set_name(announce.join("\n"));
+    nodes.push((node_id(LIVE_REGION_NODE), live.build(classes)));
+
+    let mut window = NodeBuilder::new(Role::Window);
+    window.set_name(title);
+    window.set_children(vec![node_id(TERMINAL_NODE), node_id(LIVE_REGION_NODE)]);
+    nodes.push((node_id(WINDOW_NODE), window.build(classes)));
+
+    let update = TreeUpdate {
+        nodes,
+        tree: Some(tree()),
+        focus: node_id(TERMINAL_NODE),
+    };
+    (update, output_end)
+}
+
+/// Compute the length, in characters, of each word in `text`.
+/// Words include their trailing whitespace, and the lengths
+/// must fit in a u8, so very long words are split up.
+fn word_lengths(text: &str) -> Vec<u8> {
+    let mut result = vec![];
+    let mut len: u8 = 0;
+    let mut in_space = false;
+    for c in text.chars() {
+        let is_space = c.is_whitespace();
+        if (in_space && !is_space) || len == u8::MAX {
+            result.push(len);
+            len = 0;
+        }
+        in_space = is_space;
+        len += 1;
+    }
+    if len > 0 {
+        result.push(len);
+    }
+    result
+}
+
+impl TermWindow {
+    /// Publish the content of the active pane to any
+    /// assistive technology that may be connected
+    pub fn update_accessibility(&mut self) {
+        if self.accessibility.is_none() {
+            return;
+        }
+        let pane = match self.get_active_pane_or_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+        let viewport = self.get_viewport(pane.pane_id());
+        if let Some(state) = self.accessibility.as_mut() {
+            state.update(&pane, viewport);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn word_lengths_include_trailing_space() {
+        assert_eq!(word_lengths(""), Vec::<u8>::new());
+        assert_eq!(word_lengths("ls"), vec![2]);
+        assert_eq!(word_lengths("ls -l  foo"), vec![3, 4, 3]);
+        assert_eq!(word_lengths("  foo"), vec![2, 3]);
+        assert_eq!(word_lengths(&"x".repeat(300)), vec![255, 45]);
+    }
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn announce_only_new_output() {
+        let screen = lines(&["$ ls", "a b", "$", ""]);
+        // Nothing to compare with, so nothing is announced
+        assert_eq!(rows_to_announce(10, &screen, 12, None), (vec![], Some(11)));
+        // The output below what was published before is announced
+        assert_eq!(
+            rows_to_announce(10, &screen, 12, Some(10)),
+            (vec![1], Some(11))
+        );
+        // The cursor line is left until the cursor moves on
+        let screen = lines(&["$ ls", "a b", "$ cat", "x"]);
+        assert_eq!(
+            rows_to_announce(10, &screen, 13, Some(11)),
+            (vec![2], Some(12))
+        );
+    }
+
+    #[test]
+    fn scrolling_back_announces_nothing() {
+        let history = lines(&["old", "older", "", "x"]);
+        assert_eq!(
+            rows_to_announce(0, &history, 50, Some(40)),
+            (vec![], Some(40))
+        );
+    }
+}
@@ -0,0 +1,568 @@
+//! Captures the rendered contents of a window, or of a single pane
+//! within it, as an image or as an animated GIF recording.
+//! Captures are rendered by the same renderer as the window, so they
+//! include everything that it draws: images, fonts, colors and the
+//! window background.  They are rendered into a texture of their own
+//! rather than read back from the window, so they work even while
+//! the window is minimized or hidden.
+use crate::termwindow::webgpu::WebGpuState;
+use crate::termwindow::{RenderFrame, TermWindow, TermWindowNotif};
+use ::window::glium;
+use ::window::WindowOps;
+use anyhow::{anyhow, Context};
+use config::keyassignment::CaptureArguments;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use mux::pane::{Pane, PaneId};
+use mux::Mux;
+use smol::channel::Sender;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A rectangle within the window, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TermWindow {
+    /// Renders the window, cropped to the area of `pane_id` if it is
+    /// set, and sends the image to `tx`
+    pub fn capture(&mut self, pane_id: Option<PaneId>, tx: Sender<anyhow::Result<RgbaImage>>) {
+        let result = self.render_offscreen().and_then(|frame| match pane_id {
+            Some(pane_id) => self.pane_pixel_rect(pane_id).map(|rect| crop(&frame, rect)),
+            None => Ok(frame),
+        });
+        tx.try_send(result).ok();
+    }
+
+    /// Renders the window into a texture of its own, and reads it back
+    pub fn render_offscreen(&mut self) -> anyhow::Result<RgbaImage> {
+        let width = self.dimensions.pixel_width as u32;
+        let height = self.dimensions.pixel_height as u32;
+        anyhow::ensure!(width > 0 && height > 0, "the window has no size");
+
+        if let Some(gl) = self.gl.clone() {
+            let texture = glium::texture::SrgbTexture2d::empty_with_format(
+                &gl,
+                glium::texture::SrgbFormat::U8U8U8U8,
+                glium::texture::MipmapsOption::NoMipmap,
+                width,
+                height,
+            )
+            .context("creating the capture texture")?;
+            let target = glium::framebuffer::SimpleFrameBuffer::new(&gl, &texture)
+                .context("creating the capture framebuffer")?;
+            self.paint_impl(&mut RenderFrame::GliumTexture(target));
+            return read_glium_texture(&texture);
+        }
+
+        let webgpu = self
+            .webgpu
+            .clone()
+            .ok_or_else(|| anyhow!("the window has not been rendered yet"))?;
+        let texture = webgpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: webgpu.config.borrow().format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        self.paint_impl(&mut RenderFrame::WebGpuTexture(&texture));
+        read_webgpu_texture(&webgpu, &texture)
+    }
+
+    /// Returns the area occupied by the cells of the specified pane,
+    /// which must be in the active tab of this window
+    pub fn pane_pixel_rect(&self, pane_id: PaneId) -> anyhow::Result<PixelRect> {
+        let pos = self
+            .get_panes_to_render()
+            .into_iter()
+            .find(|pos| pos.pane.pane_id() == pane_id)
+            .ok_or_else(|| {
+                anyhow!("pane {pane_id} is not visible in the active tab of its window")
+            })?;
+
+        let (padding_left, padding_top) = self.padding_left_top();
+        let top_bar_height = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height()?
+        } else {
+            0.
+        };
+        let border = self.get_os_border();
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+
+        Ok(PixelRect {
+            x: (padding_left + border.left.get() as f32 + pos.left as f32 * cell_width) as u32,
+            y: (top_bar_height
+                + padding_top
+                + border.top.get() as f32
+                + pos.top as f32 * cell_height) as u32,
+            width: (pos.width as f32 * cell_width) as u32,
+            height: (pos.height as f32 * cell_height) as u32,
+        })
+    }
+
+    /// Returns true if a frame of the recording is due.
+    /// If the next frame is not yet due, this arranges for the window
+    /// to repaint when it is, so that the final state of the pane is
+    /// not missed.
+    fn recording_frame_is_due(&self) -> bool {
+        match &self.recording {
+            Some(recording) => {
+                let now = Instant::now();
+                if recording.frame_is_due(now) {
+                    return true;
+                }
+                if !recording.repaint_scheduled.replace(true) {
+                    if let Some(window) = self.window.clone() {
+                        let due = recording.next_frame_due(now);
+                        promise::spawn::spawn(async move {
+                            smol::Timer::at(due).await;
+                            window.notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                                if let Some(recording) = &term_window.recording {
+                                    recording.repaint_scheduled.set(false);
+                                }
+                                if let Some(window) = term_window.window.as_ref() {
+                                    window.invalidate();
+                                }
+                            })));
+                        })
+                        .detach();
+                    }
+                }
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Called after the window has been painted.  If a frame of the
+    /// recording is due, renders it and adds it to the recording.
+    pub fn record_frame_if_due(&mut self) {
+        if !self.recording_frame_is_due() {
+            return;
+        }
+        match self.render_offscreen() {
+            Ok(frame) => self.record_frame(&frame),
+            Err(err) => {
+                if self.recording.take().is_some() {
+                    log::error!("Stopping recording because a frame could not be read: {err:#}");
+                }
+            }
+        }
+    }
+
+    fn record_frame(&mut self, frame: &RgbaImage) {
+        let now = Instant::now();
+        let pane_id = match &self.recording {
+            Some(recording) if recording.frame_is_due(now) => recording.pane_id,
+            _ => return,
+        };
+        let image = match pane_id {
+            Some(pane_id) => match self.pane_pixel_rect(pane_id) {
+                Ok(rect) => crop(frame, rect),
+                // The pane isn't visible right now, perhaps because
+                // another tab is active; keep showing the last frame
+                Err(_) => return,
+            },
+            None => frame.clone(),
+        };
+        if let Some(recording) = self.recording.as_mut() {
+            if recording.add_frame(image, now).is_err() {
+                // The encoder has failed and reported the error
+                self.recording.take();
+            }
+        }
+    }
+
+    /// Starts recording the specified pane, or the whole window,
+    /// to an animated GIF
+    pub fn start_recording(
+        &mut self,
+        pane: &Arc<dyn Pane>,
+        args: &CaptureArguments,
+    ) -> anyhow::Result<()> {
+        if self.recording.is_some() {
+            log::warn!("StartCapture: a recording is already in progress");
+            return Ok(());
+        }
+
+        let path = match &args.path {
+            Some(path) => PathBuf::from(path),
+            None => default_recording_path(),
+        };
+        let pane_id = if args.whole_window {
+            None
+        } else {
+            Some(pane.pane_id())
+        };
+        self.recording
+            .replace(Recording::new(path, pane_id, args.fps)?);
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Stops the recording, if any.  It is saved once the remaining
+    /// frames have been encoded.
+    pub fn stop_recording(&mut self) {
+        if self.recording.take().is_none() {
+            log::warn!("StopCapture: there is no recording in progress");
+        }
+    }
+}
+
+fn default_recording_path() -> PathBuf {
+    let dir = dirs_next::download_dir().unwrap_or_else(|| config::HOME_DIR.clone());
+    dir.join(format!(
+        "wezterm-recording-{}.gif",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+enum RecorderMessage {
+    Frame(RgbaImage, Instant),
+    Finish(Instant),
+}
+
+/// A recording in progress.
+/// Frames are encoded on a separate thread, as quantizing them for
+/// GIF is too slow to do while painting.
+/// The recording is finished and saved when this is dropped.
+pub struct Recording {
+    pane_id: Option<PaneId>,
+    frame_interval: Duration,
+    last_frame: Option<Instant>,
+    repaint_scheduled: Cell<bool>,
+    size: Option<(u32, u32)>,
+    tx: std::sync::mpsc::Sender<RecorderMessage>,
+}
+
+impl Recording {
+    fn new(path: PathBuf, pane_id: Option<PaneId>, fps: u32) -> anyhow::Result<Self> {
+        let file = File::create(&path)
+            .with_context(|| format!("creating recording file {}", path.display()))?;
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("gif-recorder".to_string())
+            .spawn(move || {
+                let result = encode_recording(file, rx);
+                promise::spawn::spawn_into_main_thread(async move {
+                    report_recording_result(&path, result);
+                })
+                .detach();
+            })?;
+
+        Ok(Self {
+            pane_id,
+            frame_interval: Duration::from_secs(1) / fps.max(1),
+            last_frame: None,
+            repaint_scheduled: Cell::new(false),
+            size: None,
+            tx,
+        })
+    }
+
+    fn next_frame_due(&self, now: Instant) -> Instant {
+        match self.last_frame {
+            Some(last) => last + self.frame_interval,
+            None => now,
+        }
+    }
+
+    fn frame_is_due(&self, now: Instant) -> bool {
+        self.next_frame_due(now) <= now
+    }
+
+    fn add_frame(&mut self, image: RgbaImage, now: Instant) -> anyhow::Result<()> {
+        // The frames of the animation all have the size of the first
+        // one, even if the pane is resized while recording
+        let (width, height) = *self.size.get_or_insert(image.dimensions());
+        let image = if image.dimensions() == (width, height) {
+            image
+        } else {
+            let mut canvas = RgbaImage::new(width, height);
+            image::imageops::replace(&mut canvas, &image, 0, 0);
+            canvas
+        };
+
+        self.last_frame.replace(now);
+        self.tx
+            .send(RecorderMessage::Frame(image, now))
+            .map_err(|_| anyhow!("recorder has stopped"))
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        self.tx.send(RecorderMessage::Finish(Instant::now())).ok();
+    }
+}
+
+fn report_recording_result(path: &std::path::Path, result: anyhow::Result<usize>) {
+    match result {
+        Ok(frames) => {
+            log::info!("Saved recording of {frames} frames to {}", path.display());
+            let message = format!("Saved {}", path.display());
+            // A relative path was created relative to our cwd; the url
+            // needs it to be absolute
+            match std::fs::canonicalize(path)
+                .ok()
+                .and_then(|path| url::Url::from_file_path(path).ok())
+            {
+                Some(url) => {
+                    wezterm_toast_notification::persistent_toast_notification_with_click_to_open_url(
+                        "Recording saved",
+                        &message,
+                        url.as_str(),
+                    )
+                }
+                None => wezterm_toast_notification::persistent_toast_notification(
+                    "Recording saved",
+                    &message,
+                ),
+            }
+        }
+        Err(err) => {
+            log::error!("Failed to save recording to {}: {err:#}", path.display());
+            wezterm_toast_notification::persistent_toast_notification(
+                "Recording failed",
+                &format!("{err:#}"),
+            );
+        }
+    }
+}
+
+/// Encodes frames as they arrive, until the recording is finished.
+/// Each frame is shown until the time at which the next one arrived.
+/// Returns the number of frames that were encoded.
+fn encode_recording(
+    file: File,
+    rx: std::sync::mpsc::Receiver<RecorderMessage>,
+) -> anyhow::Result<usize> {
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let mut frames = 0;
+    let mut emit = |mut image: RgbaImage, duration: Duration| -> anyhow::Result<()> {
+        // GIF has only on/off transparency; show translucent
+        // window backgrounds as opaque
+        for pixel in image.pixels_mut() {
+            pixel[3] = 255;
+        }
+        encoder.encode_frame(Frame::from_parts(
+            image,
+            0,
+            0,
+            Delay::from_saturating_duration(duration),
+        ))?;
+        frames += 1;
+        Ok(())
+    };
+
+    let mut pending: Option<(RgbaImage, Instant)> = None;
+    let mut finished = None;
+    for message in rx.iter() {
+        match message {
+            RecorderMessage::Frame(image, when) => {
+                if let Some((prior, prior_when)) = pending.replace((image, when)) {
+                    emit(prior, when.saturating_duration_since(prior_when))?;
+                }
+            }
+            RecorderMessage::Finish(when) => {
+                finished.replace(when);
+                break;
+            }
+        }
+    }
+
+    // Hold the final frame until the recording was stopped
+    if let Some((last, last_when)) = pending {
+        let duration = finished
+            .map(|when| when.saturating_duration_since(last_when))
+            .unwrap_or(Duration::from_secs(1));
+        emit(last, duration.max(Duration::from_millis(100)))?;
+    }
+    drop(emit);
+
+    anyhow::ensure!(frames > 0, "no frames were recorded");
+    Ok(frames)
+}
+
+/// Returns the portion of `image` that is within `rect`,
+/// clipping `rect` to the bounds of the image
+pub fn crop(image: &RgbaImage, rect: PixelRect) -> RgbaImage {
+    let x = rect.x.min(image.width());
+    let y = rect.y.min(image.height());
+    let width = rect.width.min(image.width() - x);
+    let height = rect.height.min(image.height() - y);
+    image::imageops::crop_imm(image, x, y, width, height).to_image()
+}
+
+pub fn encode_png(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = vec![];
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .context("encoding PNG")?;
+    Ok(png)
+}
+
+/// Copies the content of an OpenGL texture into an image
+fn read_glium_texture(texture: &glium::texture::SrgbTexture2d) -> anyhow::Result<RgbaImage> {
+    let raw: glium::texture::RawImage2d<u8> = texture.read();
+    let image = RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned())
+        .ok_or_else(|| anyhow!("capture texture has an unexpected size"))?;
+    // OpenGL rows go from the bottom up
+    Ok(image::imageops::flip_vertical(&image))
+}
+
+/// Copies the content of a WebGpu texture into an image
+fn read_webgpu_texture(webgpu: &WebGpuState, texture: &wgpu::Texture) -> anyhow::Result<RgbaImage> {
+    let swap_red_blue = match texture.format().remove_srgb_suffix() {
+        wgpu::TextureFormat::Rgba8Unorm => false,
+        wgpu::TextureFormat::Bgra8Unorm => true,
+        format => anyhow::bail!("cannot capture frames with texture format {format:?}"),
+    };
+
+    let width = texture.width();
+    let height = texture.height();
+    let unpadded_bytes_per_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let buffer = webgpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = webgpu
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    webgpu.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        tx.send(result).ok();
+    });
+    webgpu.device.poll(wgpu::Maintain::Wait);
+    rx.recv()?.context("mapping the capture buffer")?;
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow!("capture buffer has an unexpected size"))
+}
+
+/// Captures the gui window that contains `pane_id`, or the whole
+/// window if `pane_id` is None
+pub async fn capture_window(
+    window: &::window::Window,
+    pane_id: Option<PaneId>,
+) -> anyhow::Result<RgbaImage> {
+    let (tx, rx) = smol::channel::bounded(1);
+    window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+        term_window.capture(pane_id, tx);
+    })));
+
+    match rx.recv().await {
+        Ok(result) => result,
+        Err(err) => Err(anyhow!("{err:#}")),
+    }
+}
+
+/// Renders the specified pane and returns it encoded as PNG.
+/// The pane must be in the active tab of a gui window.
+pub async fn capture_pane_png(pane_id: PaneId) -> anyhow::Result<Vec<u8>> {
+    let (_domain_id, window_id, _tab_id) = Mux::get()
+        .resolve_pane_id(pane_id)
+        .ok_or_else(|| anyhow!("pane {pane_id} not found"))?;
+    let gui_win = crate::frontend::front_end()
+        .gui_window_for_mux_window(window_id)
+        .ok_or_else(|| anyhow!("pane {pane_id} is not in a gui window"))?;
+    let image = capture_window(&gui_win.window, Some(pane_id)).await?;
+    encode_png(&image)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crop_is_clipped_to_image() {
+        let image = RgbaImage::from_fn(4, 3, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+
+        let cropped = crop(
+            &image,
+            PixelRect {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 1,
+            },
+        );
+        assert_eq!(cropped.dimensions(), (2, 1));
+        assert_eq!(cropped.get_pixel(0, 0), &image::Rgba([1, 1, 0, 255]));
+
+        let clipped = crop(
+            &image,
+            PixelRect {
+                x: 3,
+                y: 2,
+                width: 10,
+                height: 10,
+            },
+        );
+        assert_eq!(clipped.dimensions(), (1, 1));
+    }
+}
@@ -1,28 +1,137 @@
 use crate::termwindow::TermWindowNotif;
 use crate::TermWindow;
-use config::keyassignment::{ClipboardCopyDestination, ClipboardPasteSource};
+use config::keyassignment::{ClipboardCopyDestination, ClipboardPasteSource, CopyToArguments};
 use mux::pane::Pane;
 use mux::Mux;
+use std::collections::VecDeque;
+use std::rc::Rc;
 use std::sync::Arc;
-use window::{Clipboard, WindowOps};
+use window::{Clipboard, Modifiers, Window, WindowOps};
+
+fn clipboards(destination: ClipboardCopyDestination) -> [Option<Clipboard>; 2] {
+    match destination {
+        ClipboardCopyDestination::Clipboard => [Some(Clipboard::Clipboard), None],
+        ClipboardCopyDestination::PrimarySelection => [Some(Clipboard::PrimarySelection), None],
+        ClipboardCopyDestination::ClipboardAndPrimarySelection => [
+            Some(Clipboard::Clipboard),
+            Some(Clipboard::PrimarySelection),
+        ],
+    }
+}
+
+fn set_clipboards(window: &Window, destination: ClipboardCopyDestination, text: String) {
+    for &c in &clipboards(destination) {
+        if let Some(c) = c {
+            window.set_clipboard(c, text.clone());
+        }
+    }
+}
+
+/// Joins the text that the clipboard holds with the text that is
+/// appended to it.  An empty clipboard is replaced by the text.
+fn append_text(existing: String, separator: &str, text: &str) -> String {
+    if existing.is_empty() {
+        text.to_string()
+    } else {
+        format!("{existing}{separator}{text}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ClipboardAppend {
+    destination: ClipboardCopyDestination,
+    separator: String,
+    text: String,
+}
+
+/// Appending reads the clipboard before writing it, so the appends of
+/// a window are applied one at a time; otherwise an append could read
+/// the clipboard before an earlier one had written it, and lose it.
+#[derive(Default)]
+pub struct ClipboardAppends {
+    queue: VecDeque<ClipboardAppend>,
+}
+
+impl ClipboardAppends {
+    /// Queues an append.  Returns true if no append was in progress,
+    /// in which case the caller applies the queued appends.
+    fn push(&mut self, append: ClipboardAppend) -> bool {
+        self.queue.push_back(append);
+        self.queue.len() == 1
+    }
+
+    /// Returns the append to apply next; it remains queued, so that
+    /// later appends wait for it, until `complete` is called
+    fn current(&self) -> Option<ClipboardAppend> {
+        self.queue.front().cloned()
+    }
+
+    fn complete(&mut self) {
+        self.queue.pop_front();
+    }
+}
 
 impl TermWindow {
     pub fn copy_to_clipboard(&self, clipboard: ClipboardCopyDestination, text: String) {
-        let clipboard = match clipboard {
-            ClipboardCopyDestination::Clipboard => [Some(Clipboard::Clipboard), None],
-            ClipboardCopyDestination::PrimarySelection => [Some(Clipboard::PrimarySelection), None],
-            ClipboardCopyDestination::ClipboardAndPrimarySelection => [
-                Some(Clipboard::Clipboard),
-                Some(Clipboard::PrimarySelection),
-            ],
-        };
-        for &c in &clipboard {
-            if let Some(c) = c {
-                self.window.as_ref().unwrap().set_clipboard(c, text.clone());
-            }
+        set_clipboards(self.window.as_ref().unwrap(), clipboard, text);
+    }
+
+    /// Copies `text` to the destination of `args`, or appends it to
+    /// what the destination already holds if `args.append` is set
+    pub fn copy_to(&self, args: &CopyToArguments, text: String) {
+        if args.append {
+            self.append_to_clipboard(args.destination, args.separator.clone(), text);
+        } else {
+            self.copy_to_clipboard(args.destination, text);
         }
     }
 
+    /// Appends `text` to what `clipboard` already holds, after
+    /// `separator`, so that several selections can be gathered into one
+    /// payload.  An empty clipboard is replaced by `text`.
+    pub fn append_to_clipboard(
+        &self,
+        clipboard: ClipboardCopyDestination,
+        separator: String,
+        text: String,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+        let start = self.clipboard_appends.borrow_mut().push(ClipboardAppend {
+            destination: clipboard,
+            separator,
+            text,
+        });
+        if !start {
+            // It is applied once those that were queued before it are
+            return;
+        }
+
+        let window = self.window.as_ref().unwrap().clone();
+        let appends = Rc::clone(&self.clipboard_appends);
+        promise::spawn::spawn(async move {
+            loop {
+                let append = match appends.borrow().current() {
+                    Some(append) => append,
+                    None => break,
+                };
+                let source = match append.destination {
+                    ClipboardCopyDestination::PrimarySelection => Clipboard::PrimarySelection,
+                    ClipboardCopyDestination::Clipboard
+                    | ClipboardCopyDestination::ClipboardAndPrimarySelection => {
+                        Clipboard::Clipboard
+                    }
+                };
+                let existing = window.get_clipboard(source).await.unwrap_or_default();
+                let text = append_text(existing, &append.separator, &append.text);
+                set_clipboards(&window, append.destination, text);
+                appends.borrow_mut().complete();
+            }
+        })
+        .detach();
+    }
+
     pub fn paste_from_clipboard(&mut self, pane: &Arc<dyn Pane>, clipboard: ClipboardPasteSource) {
         let pane_id = pane.pane_id();
         log::trace!(
@@ -36,6 +145,7 @@ impl TermWindow {
             ClipboardPasteSource::PrimarySelection => Clipboard::PrimarySelection,
         };
         let future = window.get_clipboard(clipboard);
+        let toggle_slow_paste = self.slow_paste_toggled();
         promise::spawn::spawn(async move {
             if let Ok(clip) = future.await {
                 window.notify(TermWindowNotif::Apply(Box::new(move |myself| {
@@ -49,7 +159,11 @@ impl TermWindow {
                             mux.get_pane(pane_id)
                         })
                     {
-                        pane.send_paste(&clip).ok();
+                        if toggle_slow_paste {
+                            pane.send_paste_toggling_slow_paste(&clip).ok();
+                        } else {
+                            pane.send_paste(&clip).ok();
+                        }
                     }
                 })));
             }
@@ -57,4 +171,46 @@ impl TermWindow {
         .detach();
         self.maybe_scroll_to_bottom_for_input(&pane);
     }
+
+    /// Returns true if slow_paste_toggle_mods are held down, so that
+    /// the paste about to be made uses the opposite slow_paste setting
+    fn slow_paste_toggled(&self) -> bool {
+        let mods = self.config.slow_paste_toggle_mods;
+        mods != Modifiers::NONE
+            && self
+                .current_modifier_and_leds
+                .0
+                .remove_positional_mods()
+                .contains(mods)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn append() {
+        assert_eq!(append_text(String::new(), ", ", "a"), "a");
+        assert_eq!(append_text("a".to_string(), ", ", "b"), "a, b");
+    }
+
+    #[test]
+    fn appends_are_applied_in_turn() {
+        let append = |text: &str| ClipboardAppend {
+            destination: ClipboardCopyDestination::Clipboard,
+            separator: "\n".to_string(),
+            text: text.to_string(),
+        };
+        let mut appends = ClipboardAppends::default();
+        assert!(appends.push(append("a")));
+        // The first append is still in progress
+        assert!(!appends.push(append("b")));
+        assert_eq!(appends.current(), Some(append("a")));
+        appends.complete();
+        assert_eq!(appends.current(), Some(append("b")));
+        appends.complete();
+        assert_eq!(appends.current(), None);
+        assert!(appends.push(append("c")));
+    }
 }
@@ -0,0 +1,143 @@
+//! The optional `idle_lock` obscures the panes of a window, and ignores
+//! input to it, once the window has gone without input for a while.
+//! Pressing a key starts authentication, either via the operating
+//! system or via the configured `unlock_command`, and the window is
+//! unlocked only if that succeeds.
+use crate::termwindow::{TermWindow, TermWindowNotif};
+use ::window::{Connection, ConnectionOps, WindowOps};
+use mux::Mux;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleLockState {
+    Unlocked,
+    Locked,
+    /// Waiting for the user to authenticate
+    Authenticating,
+}
+
+impl TermWindow {
+    pub fn is_idle_locked(&self) -> bool {
+        self.idle_lock_state != IdleLockState::Unlocked
+    }
+
+    /// Records that the user interacted with the window.
+    /// Returns true if the input must be discarded because the
+    /// window is locked; a key press starts unlocking it.
+    pub fn idle_lock_input(&mut self, is_key_press: bool) -> bool {
+        match self.idle_lock_state {
+            IdleLockState::Unlocked => {
+                self.last_input = Instant::now();
+                false
+            }
+            IdleLockState::Locked => {
+                if is_key_press {
+                    self.begin_unlock();
+                }
+                true
+            }
+            IdleLockState::Authenticating => true,
+        }
+    }
+
+    /// Locks the window if it has gone without input for longer
+    /// than the idle_lock timeout.  This is called periodically
+    /// as part of the status update.
+    pub fn check_idle_lock(&mut self) {
+        if self.is_idle_locked() {
+            return;
+        }
+        let idle_lock = match &self.config.idle_lock {
+            Some(idle_lock) => idle_lock,
+            None => return,
+        };
+        let timeout = Duration::from_secs(idle_lock.timeout_minutes.saturating_mul(60));
+        if timeout.is_zero() || self.last_input.elapsed() < timeout {
+            return;
+        }
+        let workspace = match Mux::get().get_window(self.mux_window_id) {
+            Some(window) => window.get_workspace().to_string(),
+            None => return,
+        };
+        if !idle_lock.applies_to_workspace(&workspace) {
+            return;
+        }
+        if idle_lock.unlock_command.is_none()
+            && !Connection::get()
+                .map(|conn| conn.can_authenticate_user())
+                .unwrap_or(false)
+        {
+            // Locking the window would leave no way to unlock it
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                log::error!(
+                    "idle_lock: this system has no built in authentication, \
+                     so idle_lock.unlock_command must be set"
+                );
+            });
+            return;
+        }
+
+        log::debug!("idle_lock: locking window {}", self.mux_window_id);
+        self.idle_lock_state = IdleLockState::Locked;
+        self.update_title();
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    fn begin_unlock(&mut self) {
+        let window = match self.window.clone() {
+            Some(window) => window,
+            None => return,
+        };
+        self.idle_lock_state = IdleLockState::Authenticating;
+        let unlock_command = self
+            .config
+            .idle_lock
+            .as_ref()
+            .and_then(|idle_lock| idle_lock.unlock_command.clone());
+
+        promise::spawn::spawn(async move {
+            let result = match unlock_command {
+                Some(argv) => run_unlock_command(argv).await,
+                None => match Connection::get() {
+                    Some(conn) => conn.authenticate_user("unlock wezterm").await,
+                    None => Ok(false),
+                },
+            };
+            let unlocked = result.unwrap_or_else(|err| {
+                log::error!("idle_lock: {:#}", err);
+                false
+            });
+            window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                term_window.finish_unlock(unlocked);
+            })));
+        })
+        .detach();
+    }
+
+    fn finish_unlock(&mut self, unlocked: bool) {
+        if unlocked {
+            self.last_input = Instant::now();
+            self.idle_lock_state = IdleLockState::Unlocked;
+        } else {
+            self.idle_lock_state = IdleLockState::Locked;
+        }
+        self.update_title();
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+}
+
+/// Runs the unlock_command and returns true if it succeeded
+async fn run_unlock_command(argv: Vec<String>) -> anyhow::Result<bool> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("unlock_command is empty"))?;
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    let status = smol::unblock(move || command.status()).await?;
+    Ok(status.success())
+}
@@ -0,0 +1,259 @@
+//! Shows the most recently pressed keys, along with the names of the
+//! actions that they triggered, in an overlay in the bottom right corner
+//! of the window.  This allows an audience to follow along with what is
+//! being typed, and helps to figure out which binding a key press matched.
+use crate::termwindow::box_model::*;
+use crate::termwindow::render::corners::{
+    BOTTOM_LEFT_ROUNDED_CORNER, BOTTOM_RIGHT_ROUNDED_CORNER, TOP_LEFT_ROUNDED_CORNER,
+    TOP_RIGHT_ROUNDED_CORNER,
+};
+use crate::termwindow::DimensionContext;
+use crate::utilsprites::RenderMetrics;
+use crate::TermWindow;
+use ::window::{KeyCode, Modifiers, WindowOps};
+use config::keyassignment::KeyAssignment;
+use config::Dimension;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How many key presses to show at once
+const MAX_ENTRIES: usize = 6;
+
+struct Entry {
+    label: String,
+    /// The name of the action that was triggered, if any
+    action: Option<String>,
+    /// true if this entry is plain text that was typed, rather
+    /// than a chord involving modifiers or a special key
+    is_text: bool,
+    expires: Instant,
+}
+
+#[derive(Default)]
+pub struct KeyDisplay {
+    entries: VecDeque<Entry>,
+}
+
+impl KeyDisplay {
+    fn push(&mut self, label: String, action: Option<String>, is_text: bool, duration: Duration) {
+        let expires = Instant::now() + duration;
+
+        // Coalesce runs of typed text into a single entry
+        if is_text {
+            if let Some(last) = self.entries.back_mut() {
+                if last.is_text && last.expires > Instant::now() {
+                    last.label.push_str(&label);
+                    last.expires = expires;
+                    return;
+                }
+            }
+        }
+
+        self.entries.push_back(Entry {
+            label,
+            action,
+            is_text,
+            expires,
+        });
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    fn expire(&mut self) {
+        let now = Instant::now();
+        self.entries.retain(|entry| entry.expires > now);
+    }
+}
+
+impl TermWindow {
+    pub fn set_key_display(&mut self, enabled: bool) {
+        if enabled {
+            if self.key_display.is_none() {
+                self.key_display.replace(KeyDisplay::default());
+            }
+        } else {
+            self.key_display.take();
+        }
+        self.key_display_for_presentation = false;
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    /// Record a key press so that it will be shown by the key display.
+    /// `action` is the action that the key press triggered, if any.
+    pub fn record_key_for_display(
+        &mut self,
+        key: &KeyCode,
+        mods: Modifiers,
+        action: Option<&KeyAssignment>,
+    ) {
+        if self.key_display.is_none() {
+            return;
+        }
+        if key.is_modifier() {
+            return;
+        }
+
+        let mods = mods.remove_positional_mods();
+        let (label, is_text) = match key {
+            KeyCode::Char(c)
+                if action.is_none() && (mods - Modifiers::SHIFT).is_empty() && !c.is_control() =>
+            {
+                let label = if *c == ' ' {
+                    "\u{2423}".to_string()
+                } else {
+                    c.to_string()
+                };
+                (label, true)
+            }
+            _ => {
+                let ui_key_cap_rendering = self.config.ui_key_cap_rendering;
+                let separator = if ui_key_cap_rendering == ::window::UIKeyCapRendering::AppleSymbols
+                {
+                    " "
+                } else {
+                    "-"
+                };
+                let mut label = mods.to_string_with_separator(::window::ModifierToStringArgs {
+                    separator,
+                    want_none: false,
+                    ui_key_cap_rendering: Some(ui_key_cap_rendering),
+                });
+                if !label.is_empty() {
+                    label.push_str(separator);
+                }
+                label.push_str(&crate::inputmap::ui_key(key, ui_key_cap_rendering));
+                (label, false)
+            }
+        };
+
+        let action = action.map(|action| {
+            crate::commands::derive_command_from_key_assignment(action)
+                .map(|cmd| cmd.brief.to_string())
+                .unwrap_or_else(|| format!("{action:?}"))
+        });
+
+        let duration = Duration::from_millis(self.config.key_display_duration_ms);
+        if let Some(display) = self.key_display.as_mut() {
+            display.push(label, action, is_text, duration);
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    pub fn paint_key_display(&mut self) -> anyhow::Result<()> {
+        let (labels, next_expiry) = match self.key_display.as_mut() {
+            Some(display) => {
+                display.expire();
+                let labels: Vec<String> = display
+                    .entries
+                    .iter()
+                    .map(|entry| match &entry.action {
+                        Some(action) => format!("{}  {}", entry.label, action),
+                        None => entry.label.clone(),
+                    })
+                    .collect();
+                (labels, display.entries.front().map(|entry| entry.expires))
+            }
+            None => return Ok(()),
+        };
+        if labels.is_empty() {
+            return Ok(());
+        }
+        // Schedule a repaint for when the oldest entry expires
+        self.update_next_frame_time(next_expiry);
+
+        let font = self.fonts.key_display_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let fg = self.config.key_display_fg_color.to_linear();
+        let bg = self.config.key_display_bg_color.to_linear();
+
+        // One row per key press, with the most recent at the bottom
+        let rows = labels
+            .into_iter()
+            .map(|label| {
+                Element::new(&font, ElementContent::Text(label)).display(DisplayType::Block)
+            })
+            .collect();
+
+        let element = Element::new(&font, ElementContent::Children(rows))
+            .colors(ElementColors {
+                border: BorderColor::new(bg.into()),
+                bg: bg.into(),
+                text: fg.into(),
+            })
+            .padding(BoxDimension {
+                left: Dimension::Cells(0.5),
+                right: Dimension::Cells(0.5),
+                top: Dimension::Cells(0.25),
+                bottom: Dimension::Cells(0.25),
+            })
+            .border(BoxDimension::new(Dimension::Pixels(1.)))
+            .border_corners(Some(Corners {
+                top_left: SizedPoly {
+                    width: Dimension::Cells(0.25),
+                    height: Dimension::Cells(0.25),
+                    poly: TOP_LEFT_ROUNDED_CORNER,
+                },
+                top_right: SizedPoly {
+                    width: Dimension::Cells(0.25),
+                    height: Dimension::Cells(0.25),
+                    poly: TOP_RIGHT_ROUNDED_CORNER,
+                },
+                bottom_left: SizedPoly {
+                    width: Dimension::Cells(0.25),
+                    height: Dimension::Cells(0.25),
+                    poly: BOTTOM_LEFT_ROUNDED_CORNER,
+                },
+                bottom_right: SizedPoly {
+                    width: Dimension::Cells(0.25),
+                    height: Dimension::Cells(0.25),
+                    poly: BOTTOM_RIGHT_ROUNDED_CORNER,
+                },
+            }));
+
+        let dimensions = self.dimensions;
+        let gl_state = self.render_state.as_ref().unwrap();
+        let mut computed = self.compute_element(
+            &LayoutContext {
+                height: DimensionContext {
+                    dpi: dimensions.dpi as f32,
+                    pixel_max: dimensions.pixel_height as f32,
+                    pixel_cell: metrics.cell_size.height as f32,
+                },
+                width: DimensionContext {
+                    dpi: dimensions.dpi as f32,
+                    pixel_max: dimensions.pixel_width as f32,
+                    pixel_cell: metrics.cell_size.width as f32,
+                },
+                bounds: euclid::rect(
+                    0.,
+                    0.,
+                    dimensions.pixel_width as f32,
+                    dimensions.pixel_height as f32,
+                ),
+                metrics: &metrics,
+                gl_state,
+                zindex: 110,
+            },
+            &element,
+        )?;
+
+        // Move it to the bottom right corner, leaving a gap of
+        // one cell between it and the edges of the window
+        let x = (dimensions.pixel_width as f32
+            - computed.bounds.width()
+            - metrics.cell_size.width as f32)
+            .max(0.);
+        let y = (dimensions.pixel_height as f32
+            - computed.bounds.height()
+            - metrics.cell_size.height as f32)
+            .max(0.);
+        computed.translate(euclid::vec2(x, y));
+
+        self.render_element(&computed, gl_state, None)
+    }
+}
@@ -312,6 +312,7 @@ impl super::TermWindow {
                 };
 
                 if handled {
+                    self.record_key_for_display(keycode, raw_modifiers, Some(&entry.action));
                     context.invalidate();
 
                     if leader_active {
@@ -455,8 +456,18 @@ impl super::TermWindow {
         let modifier_and_leds = (key.modifiers, key.leds);
         if self.current_modifier_and_leds != modifier_and_leds {
             self.current_modifier_and_leds = modifier_and_leds;
+            self.output_pause_modifiers_changed(key.modifiers);
             self.schedule_next_status_update();
         }
+        if key.key_is_down && !key.key.is_modifier() {
+            self.output_pause_key_pressed();
+        }
+
+        if !key.key_is_down {
+            // The magnifier is only shown while the keys that
+            // triggered it are held down
+            self.hide_magnifier();
+        }
 
         let pane = match self.get_active_pane_or_overlay() {
             Some(pane) => pane,
@@ -647,6 +658,7 @@ impl super::TermWindow {
         // entries from the stack.
         if window_key.key_is_down {
             self.key_table_state.pop_until_unknown();
+            self.record_key_for_display(&window_key.key, modifiers, None);
         }
 
         let key = self.win_key_code_to_termwiz_key_code(&window_key.key);
@@ -0,0 +1,232 @@
+//! A momentary magnifier that shows an enlarged rendering of the cells
+//! around the mouse pointer, to help with reading small text.
+//! The cells are shaped and rasterized again using fonts loaded at the
+//! magnified size, rather than scaling up the bitmap of the window,
+//! so that the result is as crisp as text rendered at that size.
+//! The magnifier is shown by the `ShowMagnifier` action and is hidden
+//! when the key or mouse button that triggered it is released.
+use crate::termwindow::render::RenderScreenLineParams;
+use crate::termwindow::resize::PaneFonts;
+use crate::TermWindow;
+use ::window::WindowOps;
+use anyhow::Context;
+use config::keyassignment::MagnifierArguments;
+use mux::renderable::StableCursorPosition;
+use std::rc::Rc;
+use wezterm_term::StableRowIndex;
+
+pub struct Magnifier {
+    args: MagnifierArguments,
+    fonts: Rc<PaneFonts>,
+}
+
+impl TermWindow {
+    pub fn show_magnifier(&mut self, args: &MagnifierArguments) {
+        if matches!(&self.magnifier, Some(magnifier) if magnifier.args == *args) {
+            // Auto-repeat of the key that triggered it
+            return;
+        }
+        let scale = args.scale.max(1.0);
+        let fonts = match &self.magnifier_fonts {
+            Some(fonts) if fonts.scale == scale => Rc::clone(fonts),
+            _ => match self.load_pane_fonts(scale) {
+                Ok(fonts) => {
+                    let fonts = Rc::new(fonts);
+                    self.magnifier_fonts.replace(Rc::clone(&fonts));
+                    fonts
+                }
+                Err(err) => {
+                    log::error!("{:#} while loading fonts for the magnifier", err);
+                    return;
+                }
+            },
+        };
+        self.magnifier.replace(Magnifier {
+            args: args.clone(),
+            fonts,
+        });
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    pub fn hide_magnifier(&mut self) {
+        if self.magnifier.take().is_some() {
+            if let Some(window) = self.window.as_ref() {
+                window.invalidate();
+            }
+        }
+    }
+
+    pub fn magnifier_is_active(&self) -> bool {
+        self.magnifier.is_some()
+    }
+
+    pub fn paint_magnifier(&mut self) -> anyhow::Result<()> {
+        let (args, fonts) = match self.magnifier.as_ref() {
+            Some(magnifier) => (magnifier.args.clone(), Rc::clone(&magnifier.fonts)),
+            None => return Ok(()),
+        };
+        let global_cursor_fg = self.palette().cursor_fg;
+        let global_cursor_bg = self.palette().cursor_bg;
+        let coords = match self.current_mouse_event.as_ref() {
+            Some(event) => event.coords,
+            None => return Ok(()),
+        };
+
+        // Figure out which cell of which pane is under the pointer
+        let border = self.get_os_border();
+        let top_bar_height = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height().unwrap_or(0.)
+        } else {
+            0.
+        };
+        let (padding_left, padding_top) = self.padding_left_top();
+        let x = coords.x as f32 - (padding_left + border.left.get() as f32);
+        let y = coords.y as f32 - (top_bar_height + padding_top + border.top.get() as f32);
+        if x < 0. || y < 0. {
+            return Ok(());
+        }
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+        let window_column = (x / cell_width) as usize;
+        let window_row = (y / cell_height) as usize;
+
+        let pos = match self.get_panes_to_render().into_iter().find(|pos| {
+            window_row >= pos.top
+                && window_row < pos.top + pos.height
+                && window_column >= pos.left
+                && window_column < pos.left + pos.width
+        }) {
+            Some(pos) => pos,
+            None => return Ok(()),
+        };
+        let pane_id = pos.pane.pane_id();
+        let pane_metrics = self
+            .pane_fonts(pane_id)
+            .map(|f| f.render_metrics)
+            .unwrap_or(self.render_metrics);
+        let column =
+            ((x - pos.left as f32 * cell_width) / pane_metrics.cell_size.width as f32) as usize;
+        let row =
+            ((y - pos.top as f32 * cell_height) / pane_metrics.cell_size.height as f32) as i64;
+
+        let dims = pos.pane.get_dimensions();
+        if dims.cols == 0 || column >= dims.cols || row >= dims.viewport_rows as i64 {
+            return Ok(());
+        }
+        let stable_row = self.pane_row_to_stable_row(pane_id, &dims, row);
+
+        // The region of cells to magnify, centered on the pointer
+        let num_cols = args.cols.clamp(1, dims.cols);
+        let num_rows = args.rows.max(1);
+        let first_col = column
+            .saturating_sub(num_cols / 2)
+            .min(dims.cols - num_cols);
+        let first_row = stable_row - (num_rows / 2) as StableRowIndex;
+        let (first_row, lines) = pos
+            .pane
+            .get_lines(first_row..first_row + num_rows as StableRowIndex);
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let metrics = fonts.render_metrics;
+        let width = num_cols as f32 * metrics.cell_size.width as f32;
+        let height = lines.len() as f32 * metrics.cell_size.height as f32;
+        let left = (coords.x as f32 - width / 2.)
+            .min(self.dimensions.pixel_width as f32 - width)
+            .max(0.);
+        let top = (coords.y as f32 - height / 2.)
+            .min(self.dimensions.pixel_height as f32 - height)
+            .max(0.);
+
+        let palette = pos.pane.palette();
+        let gl_state = self.render_state.as_ref().unwrap();
+        let layer = gl_state
+            .layer_for_zindex(120)
+            .context("layer_for_zindex(120)")?;
+        let mut layers = layer.quad_allocator();
+
+        let border_width = (self.dimensions.dpi as f32 / 96.).max(1.).round();
+        self.filled_rectangle(
+            &mut layers,
+            0,
+            euclid::rect(
+                left - border_width,
+                top - border_width,
+                width + 2. * border_width,
+                height + 2. * border_width,
+            ),
+            palette.split.to_linear(),
+        )
+        .context("filled_rectangle")?;
+        let default_bg = palette.background.to_linear();
+        self.filled_rectangle(
+            &mut layers,
+            0,
+            euclid::rect(left, top, width, height),
+            default_bg,
+        )
+        .context("filled_rectangle")?;
+
+        let cursor = pos.pane.get_cursor_position();
+        let cursor = if cursor.x >= first_col && cursor.x < first_col + num_cols {
+            StableCursorPosition {
+                x: cursor.x - first_col,
+                ..cursor
+            }
+        } else {
+            // Not within the magnified region
+            StableCursorPosition { y: -1, ..cursor }
+        };
+        let cursor_is_default_color =
+            palette.cursor_fg == global_cursor_fg && palette.cursor_bg == global_cursor_bg;
+        let sub_dims = mux::renderable::RenderableDimensions {
+            cols: num_cols,
+            ..dims
+        };
+
+        for (idx, line) in lines.iter().enumerate() {
+            let line = line.columns_as_line(first_col..first_col + num_cols);
+            self.render_screen_line(
+                RenderScreenLineParams {
+                    top_pixel_y: top + idx as f32 * metrics.cell_size.height as f32,
+                    left_pixel_x: left,
+                    pixel_width: width,
+                    stable_line_idx: Some(first_row + idx as StableRowIndex),
+                    line: &line,
+                    selection: 0..0,
+                    cursor: &cursor,
+                    palette: &palette,
+                    dims: &sub_dims,
+                    config: &self.config,
+                    pane: Some(&pos.pane),
+                    white_space: gl_state.util_sprites.white_space.texture_coords(),
+                    filled_box: gl_state.util_sprites.filled_box.texture_coords(),
+                    cursor_border_color: palette.cursor_border.to_linear(),
+                    foreground: palette.foreground.to_linear(),
+                    is_active: true,
+                    selection_fg: palette.selection_fg.to_linear(),
+                    selection_bg: palette.selection_bg.to_linear(),
+                    cursor_fg: palette.cursor_fg.to_linear(),
+                    cursor_bg: palette.cursor_bg.to_linear(),
+                    cursor_is_default_color,
+                    window_is_transparent: false,
+                    default_bg,
+                    font: None,
+                    fonts: Some(Rc::clone(&fonts.fonts)),
+                    style: None,
+                    use_pixel_positioning: self.config.experimental_pixel_positioning,
+                    render_metrics: metrics,
+                    shape_key: None,
+                    password_input: false,
+                },
+                &mut layers,
+            )
+            .context("render_screen_line")?;
+        }
+
+        Ok(())
+    }
+}
@@ -27,16 +27,17 @@ use crate::termwindow::render::{
 };
 use crate::termwindow::webgpu::WebGpuState;
 use ::wezterm_term::input::{ClickPosition, MouseButton as TMB};
+use ::window::screen::WindowGeometryAdjustment;
 use ::window::*;
 use anyhow::{anyhow, ensure, Context};
 use config::keyassignment::{
-    KeyAssignment, PaneDirection, Pattern, PromptInputLine, QuickSelectArguments,
-    RotationDirection, SpawnCommand, SplitSize,
+    ClipboardCopyDestination, KeyAssignment, PaneDirection, Pattern, PromptInputLine,
+    QuickSelectArguments, RotationDirection, SpawnCommand, SplitSize,
 };
 use config::window::WindowLevel;
 use config::{
     configuration, AudibleBell, ConfigHandle, Dimension, DimensionContext, FrontEndSelection,
-    GeometryOrigin, GuiPosition, TermConfig, WindowCloseConfirmation,
+    GeometryOrigin, GuiPosition, TermConfig, VisualBell, WindowCloseConfirmation,
 };
 use lfucache::*;
 use mlua::{FromLua, UserData, UserDataFields};
@@ -53,8 +54,8 @@ use mux::{Mux, MuxNotification};
 use mux_lua::MuxPane;
 use smol::channel::Sender;
 use smol::Timer;
-use std::cell::{RefCell, RefMut};
-use std::collections::{HashMap, LinkedList};
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::{HashMap, HashSet, LinkedList};
 use std::ops::Add;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -62,27 +63,36 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::SequenceNo;
-use wezterm_dynamic::Value;
+use wezterm_dynamic::{ToDynamic, Value};
 use wezterm_font::FontConfiguration;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::input::LastMouseClick;
 use wezterm_term::{Alert, StableRowIndex, TerminalConfiguration, TerminalSize};
 
+pub mod accessibility;
 pub mod background;
 pub mod box_model;
+pub mod capture;
 pub mod charselect;
 pub mod clipboard;
+pub mod idlelock;
+pub mod keydisplay;
 pub mod keyevent;
+pub mod magnifier;
 pub mod modal;
 mod mouseevent;
+pub mod opacity;
+pub mod outputpause;
 pub mod palette;
 pub mod paneselect;
 mod prevcursor;
 pub mod render;
 pub mod resize;
+pub mod scrollbacksplit;
 mod selection;
 pub mod spawn;
 pub mod webgpu;
+pub mod windowconfig;
 use crate::spawn::SpawnWhere;
 use prevcursor::PrevCursorPos;
 
@@ -93,12 +103,41 @@ lazy_static::lazy_static! {
     static ref POSITION: Mutex<Option<GuiPosition>> = Mutex::new(None);
 }
 
+thread_local! {
+    /// Whether the system prefers reduced motion, as of the last query
+    static SYSTEM_REDUCED_MOTION: Cell<bool> = Cell::new(false);
+}
+
 pub const ICON_DATA: &'static [u8] = include_bytes!("../../../assets/icon/terminal.png");
 
 pub fn set_window_position(pos: GuiPosition) {
     POSITION.lock().unwrap().replace(pos);
 }
 
+/// Asks the system whether it prefers reduced motion, and re-evaluates
+/// `reduce_motion` in all windows when the answer changes.
+/// The answer may take a while to arrive, so the windows use the
+/// previous answer in the meantime.  Must be called on the main thread.
+pub fn query_system_reduced_motion() {
+    let query = match Connection::get() {
+        Some(conn) => conn.prefers_reduced_motion(),
+        None => return,
+    };
+    promise::spawn::spawn(async move {
+        let reduced_motion = query.await.unwrap_or(false);
+        if SYSTEM_REDUCED_MOTION.with(|state| state.replace(reduced_motion)) != reduced_motion {
+            for window in crate::frontend::front_end().gui_windows() {
+                window
+                    .window
+                    .notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                        term_window.check_reduced_motion();
+                    })));
+            }
+        }
+    })
+    .detach();
+}
+
 pub fn set_window_class(cls: &str) {
     *WINDOW_CLASS.lock().unwrap() = cls.to_owned();
 }
@@ -107,6 +146,20 @@ pub fn get_window_class() -> String {
     WINDOW_CLASS.lock().unwrap().clone()
 }
 
+/// Combines layers of config overrides, with the keys of later
+/// layers replacing those of earlier layers
+fn merge_override_layers(layers: Vec<Value>) -> wezterm_dynamic::Object {
+    let mut merged = wezterm_dynamic::Object::default();
+    for layer in layers {
+        if let Value::Object(layer) = layer {
+            for (key, value) in layer.iter() {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    merged
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MouseCapture {
     UI,
@@ -159,6 +212,7 @@ pub enum UIItemType {
     ScrollThumb,
     BelowScrollThumb,
     Split(PositionedSplit),
+    JumpToBottom(PaneId),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -203,7 +257,21 @@ pub struct PaneState {
     pub overlay: Option<OverlayState>,
 
     bell_start: Option<Instant>,
+    /// If is_some(), overrides the visual_bell config for this pane
+    visual_bell: Option<VisualBell>,
     pub mouse_terminal_coords: Option<(ClickPosition, StableRowIndex)>,
+    /// If is_some(), the font size of this pane has been adjusted
+    /// independently of the rest of the window
+    pub fonts: Option<Rc<resize::PaneFonts>>,
+    /// If true, and the pane is scrolled back, the view is split
+    /// between the frozen scrollback and the live output
+    scrollback_split: bool,
+    /// The physical top of the pane at the time that it was
+    /// scrolled back, used to count the lines that arrive since
+    scrolled_back_from: Option<StableRowIndex>,
+    /// If is_some(), the view of the pane has been frozen by
+    /// PauseOutputScrolling or by holding down pause_output_scrolling_mods
+    output_pause: Option<outputpause::OutputPause>,
 }
 
 /// Data used when synchronously formatting pane and window titles
@@ -215,6 +283,11 @@ pub struct TabInformation {
     pub active_pane: Option<PaneInformation>,
     pub window_id: MuxWindowId,
     pub tab_title: String,
+    /// true if the bell rang in this tab since it was last seen
+    pub has_bell: bool,
+    /// The number of lines of output that have arrived in each of
+    /// the scrolled back panes of this tab since they were scrolled back
+    pub unseen_output_lines: HashMap<PaneId, usize>,
 }
 
 impl UserData for TabInformation {
@@ -236,13 +309,17 @@ impl UserData for TabInformation {
                 panes = tab
                     .iter_panes()
                     .iter()
-                    .map(TermWindow::pos_pane_to_pane_info)
+                    .map(|pos| {
+                        let unseen = this.unseen_output_lines.get(&pos.pane.pane_id());
+                        TermWindow::pos_pane_to_pane_info(pos, unseen.copied().unwrap_or(0))
+                    })
                     .collect();
             }
             Ok(panes)
         });
         fields.add_field_method_get("window_id", |_, this| Ok(this.window_id));
         fields.add_field_method_get("tab_title", |_, this| Ok(this.tab_title.clone()));
+        fields.add_field_method_get("has_bell", |_, this| Ok(this.has_bell));
         fields.add_field_method_get("window_title", |_, this| {
             let mux = Mux::get();
             let window = mux.get_window(this.window_id).ok_or_else(|| {
@@ -261,6 +338,9 @@ pub struct PaneInformation {
     pub is_active: bool,
     pub is_zoomed: bool,
     pub has_unseen_output: bool,
+    /// The number of lines of output that have arrived since the
+    /// pane was scrolled back, or 0 if it isn't scrolled back
+    pub unseen_output_lines: usize,
     pub left: usize,
     pub top: usize,
     pub width: usize,
@@ -278,6 +358,10 @@ impl UserData for PaneInformation {
         fields.add_field_method_get("is_active", |_, this| Ok(this.is_active));
         fields.add_field_method_get("is_zoomed", |_, this| Ok(this.is_zoomed));
         fields.add_field_method_get("has_unseen_output", |_, this| Ok(this.has_unseen_output));
+        fields.add_field_method_get(
+            "unseen_output_lines",
+            |_, this| Ok(this.unseen_output_lines),
+        );
         fields.add_field_method_get("left", |_, this| Ok(this.left));
         fields.add_field_method_get("top", |_, this| Ok(this.top));
         fields.add_field_method_get("width", |_, this| Ok(this.width));
@@ -362,6 +446,47 @@ pub struct TermWindow {
     pub window: Option<Window>,
     pub config: ConfigHandle,
     pub config_overrides: wezterm_dynamic::Value,
+    /// The overrides from the `ssh_host_profiles` entry that matches
+    /// the host of the active pane, if any
+    ssh_host_overrides: Option<wezterm_dynamic::Value>,
+    /// The name of the entry in `config.profiles` that is
+    /// active in this window, if any
+    config_profile: Option<String>,
+    /// Whether `TogglePresentationMode` is active
+    presentation_mode: bool,
+    /// Overrides `show_invisible_characters` once it has been
+    /// toggled with `ToggleShowInvisibleCharacters`
+    show_invisible_override: Option<bool>,
+    /// Overrides `show_whitespace` once it has been toggled with
+    /// `ToggleShowWhitespace`
+    show_whitespace_override: Option<bool>,
+    /// If is_some(), recently pressed keys are shown in an overlay
+    key_display: Option<keydisplay::KeyDisplay>,
+    /// true if the key display was turned on by presentation mode,
+    /// and should be turned off again when it ends
+    key_display_for_presentation: bool,
+    /// If is_some(), the cells under the mouse pointer are shown enlarged
+    magnifier: Option<magnifier::Magnifier>,
+    /// The fonts most recently loaded for the magnifier, kept so that
+    /// showing it again at the same scale doesn't reload them
+    magnifier_fonts: Option<Rc<resize::PaneFonts>>,
+    /// The palette being edited in the color scheme editor, if any
+    color_scheme_preview: Option<config::Palette>,
+    /// The background opacity and blur that have been set for this
+    /// window at runtime, if any
+    opacity_override: Option<f32>,
+    background_blur_override: Option<i64>,
+    opacity_animation: Option<opacity::OpacityAnimation>,
+    /// The tabs in which the bell has rung since they were last seen
+    tabs_with_unseen_bell: HashSet<TabId>,
+    /// When the user last interacted with this window, for idle_lock
+    last_input: Instant,
+    idle_lock_state: idlelock::IdleLockState,
+    accessibility: Option<accessibility::AccessibilityState>,
+    /// Whether the `high_contrast` mode is active
+    pub high_contrast: bool,
+    /// Whether the `reduce_motion` mode is active
+    reduced_motion: bool,
     os_parameters: Option<parameters::Parameters>,
     /// When we most recently received keyboard focus
     pub focused: Option<Instant>,
@@ -442,6 +567,8 @@ pub struct TermWindow {
     event_states: HashMap<String, EventState>,
     pub current_event: Option<Value>,
     has_animation: RefCell<Option<Instant>>,
+    recording: Option<capture::Recording>,
+    clipboard_appends: Rc<RefCell<clipboard::ClipboardAppends>>,
     /// We use this to attempt to do something reasonable
     /// if we run out of texture space
     allow_images: AllowImage,
@@ -520,9 +647,16 @@ impl TermWindow {
 
     fn focus_changed(&mut self, focused: bool, window: &Window) {
         log::trace!("Setting focus to {:?}", focused);
+        let opacity = self.window_background_opacity();
         self.focused = if focused { Some(Instant::now()) } else { None };
+        self.animate_opacity_from(opacity);
         self.quad_generation += 1;
         self.load_os_parameters();
+        if focused {
+            // The system preference is usually changed in another
+            // application, so pick it up when we regain the focus
+            query_system_reduced_motion();
+        }
 
         if self.focused.is_none() {
             self.last_mouse_click = None;
@@ -545,6 +679,10 @@ impl TermWindow {
             pane.focus_changed(focused);
         }
 
+        if let Some(state) = &self.accessibility {
+            state.update_window_focus_state(focused);
+        }
+
         self.update_title();
         self.emit_window_event("window-focus-changed", None);
     }
@@ -554,6 +692,7 @@ impl TermWindow {
 
         let render_info = ctx.renderer_info();
         self.opengl_info.replace(render_info.clone());
+        crate::crashreport::note_renderer(&render_info);
 
         match RenderState::new(ctx, &self.fonts, &self.render_metrics, ATLAS_SIZE) {
             Ok(render_state) => {
@@ -580,6 +719,17 @@ impl TermWindow {
 impl TermWindow {
     pub async fn new_window(mux_window_id: MuxWindowId) -> anyhow::Result<()> {
         let config = configuration();
+        let layers: Vec<Value> = Self::power_saving_overrides(&config)
+            .into_iter()
+            .chain(Self::reduced_motion_overrides(&config))
+            .chain(Self::window_rule_overrides(&config))
+            .collect();
+        let config = if layers.is_empty() {
+            config
+        } else {
+            config::overridden_config(&Value::Object(merge_override_layers(layers)))
+                .unwrap_or(config)
+        };
         let dpi = config.dpi.unwrap_or_else(|| ::window::default_dpi()) as usize;
         let fontconfig = Rc::new(FontConfiguration::new(Some(config.clone()), dpi)?);
 
@@ -688,6 +838,29 @@ impl TermWindow {
             window_background,
             config: config.clone(),
             config_overrides: wezterm_dynamic::Value::default(),
+            ssh_host_overrides: None,
+            config_profile: None,
+            presentation_mode: false,
+            show_invisible_override: None,
+            show_whitespace_override: None,
+            key_display: if config.show_key_display {
+                Some(keydisplay::KeyDisplay::default())
+            } else {
+                None
+            },
+            key_display_for_presentation: false,
+            magnifier: None,
+            magnifier_fonts: None,
+            color_scheme_preview: None,
+            opacity_override: None,
+            background_blur_override: None,
+            opacity_animation: None,
+            tabs_with_unseen_bell: HashSet::new(),
+            last_input: Instant::now(),
+            idle_lock_state: idlelock::IdleLockState::Unlocked,
+            accessibility: None,
+            high_contrast: Self::is_high_contrast(&config),
+            reduced_motion: Self::is_reduced_motion(&configuration()),
             palette: None,
             focused: None,
             mux_window_id,
@@ -774,6 +947,8 @@ impl TermWindow {
             event_states: HashMap::new(),
             current_event: None,
             has_animation: RefCell::new(None),
+            recording: None,
+            clipboard_appends: Rc::default(),
             scheduled_animation: RefCell::new(None),
             allow_images: AllowImage::Yes,
             semantic_zones: HashMap::new(),
@@ -827,6 +1002,7 @@ impl TermWindow {
         )
         .await?;
         tw.borrow_mut().window.replace(window.clone());
+        tw.borrow_mut().accessibility = accessibility::AccessibilityState::new(&window);
 
         Self::apply_icon(&window)?;
 
@@ -886,7 +1062,13 @@ impl TermWindow {
         }
 
         crate::update::start_update_checker();
-        front_end().record_known_window(window, mux_window_id);
+        front_end().record_known_window(window.clone(), mux_window_id);
+
+        if crate::overlay::setup_wizard::claim_first_run() {
+            window.notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                term_window.show_setup_wizard();
+            })));
+        }
 
         Ok(())
     }
@@ -928,6 +1110,9 @@ impl TermWindow {
                 Ok(true)
             }
             WindowEvent::PerformKeyAssignment(action) => {
+                if self.idle_lock_input(false) {
+                    return Ok(true);
+                }
                 if let Some(pane) = self.get_active_pane_or_overlay() {
                     self.perform_key_assignment(&pane, &action)?;
                     window.invalidate();
@@ -939,6 +1124,9 @@ impl TermWindow {
                 Ok(true)
             }
             WindowEvent::MouseEvent(event) => {
+                if self.idle_lock_input(false) {
+                    return Ok(true);
+                }
                 self.mouse_event_impl(event, window);
                 Ok(true)
             }
@@ -969,15 +1157,22 @@ impl TermWindow {
             }
             WindowEvent::AdviseModifiersLedStatus(modifiers, leds) => {
                 self.current_modifier_and_leds = (modifiers, leds);
+                self.output_pause_modifiers_changed(modifiers);
                 self.update_title();
                 window.invalidate();
                 Ok(true)
             }
             WindowEvent::RawKeyEvent(event) => {
+                if self.idle_lock_input(event.key_is_down) {
+                    return Ok(true);
+                }
                 self.raw_key_event_impl(event, window);
                 Ok(true)
             }
             WindowEvent::KeyEvent(event) => {
+                if self.idle_lock_input(event.key_is_down) {
+                    return Ok(true);
+                }
                 self.key_event_impl(event, window);
                 Ok(true)
             }
@@ -1012,6 +1207,9 @@ impl TermWindow {
                 Ok(true)
             }
             WindowEvent::DroppedFile(paths) => {
+                if self.idle_lock_input(false) {
+                    return Ok(true);
+                }
                 let pane = match self.get_active_pane_or_overlay() {
                     Some(pane) => pane,
                     None => return Ok(true),
@@ -1040,6 +1238,10 @@ impl TermWindow {
 
         if gl.is_context_lost() {
             log::error!("opengl context was lost; should reinit");
+            if let Some(report) = crate::crashreport::report_gpu_failure("opengl context was lost")
+            {
+                crate::crashreport::show_report_notification("Wezterm GPU error", &report);
+            }
             window.close();
             front_end().forget_known_window(window);
             return false;
@@ -1053,7 +1255,9 @@ impl TermWindow {
             ),
         );
         self.paint_impl(&mut RenderFrame::Glium(&mut frame));
-        window.finish_frame(frame).is_ok()
+        let finished = window.finish_frame(frame).is_ok();
+        self.record_frame_if_due();
+        finished
     }
 
     fn do_paint_webgpu(&mut self) -> anyhow::Result<bool> {
@@ -1075,6 +1279,7 @@ impl TermWindow {
 
     fn do_paint_webgpu_impl(&mut self) -> anyhow::Result<bool> {
         self.paint_impl(&mut RenderFrame::WebGpu);
+        self.record_frame_if_due();
         Ok(true)
     }
 
@@ -1174,6 +1379,12 @@ impl TermWindow {
                 } => {
                     self.emit_user_var_event(pane_id, name, value);
                 }
+                MuxNotification::Alert {
+                    alert: Alert::PrintRequested { data, more_follows },
+                    pane_id,
+                } => {
+                    self.emit_print_event(pane_id, data, more_follows);
+                }
                 MuxNotification::WindowTitleChanged { .. }
                 | MuxNotification::Alert {
                     alert:
@@ -1200,16 +1411,26 @@ impl TermWindow {
                     alert: Alert::Bell,
                     pane_id,
                 } => {
-                    match self.config.audible_bell {
-                        AudibleBell::SystemBeep => {
-                            Connection::get().expect("on main thread").beep();
-                        }
-                        AudibleBell::Disabled => {}
-                    }
+                    let mux = Mux::get();
+                    self.ring_audible_bell(pane_id);
 
                     log::trace!("Ding! (this is the bell) in pane {}", pane_id);
                     self.emit_window_event("bell", Some(pane_id));
 
+                    if let Some((_domain, window_id, tab_id)) = mux.resolve_pane_id(pane_id) {
+                        let tab_is_seen = self.focused.is_some()
+                            && mux
+                                .get_active_tab_for_window(window_id)
+                                .map(|tab| tab.tab_id())
+                                == Some(tab_id);
+                        if window_id == self.mux_window_id
+                            && !tab_is_seen
+                            && self.tabs_with_unseen_bell.insert(tab_id)
+                        {
+                            self.update_title();
+                        }
+                    }
+
                     let mut per_pane = self.pane_state(pane_id);
                     per_pane.bell_start.replace(Instant::now());
                     window.invalidate();
@@ -1271,12 +1492,14 @@ impl TermWindow {
                 }
                 MuxNotification::TabResized(_) => {
                     // Also handled by wezterm-client
+                    self.apply_pane_font_sizes();
                     self.update_title_post_status();
                 }
                 MuxNotification::TabTitleChanged { .. } => {
                     self.update_title_post_status();
                 }
                 MuxNotification::PaneAdded(_)
+                | MuxNotification::PaneTagsChanged { .. }
                 | MuxNotification::WorkspaceRenamed { .. }
                 | MuxNotification::PaneRemoved(_)
                 | MuxNotification::WindowWorkspaceChanged(_)
@@ -1285,6 +1508,7 @@ impl TermWindow {
                 | MuxNotification::WindowCreated(_) => {}
             },
             TermWindowNotif::EmitStatusUpdate => {
+                self.check_idle_lock();
                 self.emit_status_event();
             }
             TermWindowNotif::GetSelectionForPane { pane_id, tx } => {
@@ -1429,6 +1653,7 @@ impl TermWindow {
                     | Alert::TabTitleChanged(_)
                     | Alert::IconTitleChanged(_)
                     | Alert::SetUserVar { .. }
+                    | Alert::PrintRequested { .. }
                     | Alert::Bell,
             }
             | MuxNotification::PaneFocused(pane_id)
@@ -1484,6 +1709,7 @@ impl TermWindow {
             | MuxNotification::WindowCreated(_)
             | MuxNotification::ActiveWorkspaceChanged(_)
             | MuxNotification::WorkspaceRenamed { .. }
+            | MuxNotification::PaneTagsChanged { .. }
             | MuxNotification::Empty
             | MuxNotification::WindowWorkspaceChanged(_) => return true,
             MuxNotification::Alert {
@@ -1536,15 +1762,27 @@ impl TermWindow {
         };
         let pane = MuxPane(pane.pane_id());
         let name = name.to_string();
+        let command = if name == "bell" {
+            Self::last_command_for_pane(&pane)
+        } else {
+            None
+        };
 
         async fn do_event(
             lua: Option<Rc<mlua::Lua>>,
             name: String,
             window: GuiWin,
             pane: MuxPane,
+            command: Option<String>,
         ) -> anyhow::Result<()> {
             let again = if let Some(lua) = lua {
-                let args = lua.pack_multi((window.clone(), pane))?;
+                let args = if name == "bell" {
+                    let info = lua.create_table()?;
+                    info.set("command", command)?;
+                    lua.pack_multi((window.clone(), pane, info))?
+                } else {
+                    lua.pack_multi((window.clone(), pane))?
+                };
 
                 if let Err(err) = config::lua::emit_event(&lua, (name.clone(), args)).await {
                     log::error!("while processing {} event: {:#}", name, err);
@@ -1562,11 +1800,97 @@ impl TermWindow {
         }
 
         promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
-            do_event(lua, name, window, pane)
+            do_event(lua, name, window, pane, command)
         }))
         .detach();
     }
 
+    /// Sounds the audible_bell for a bell that rang in the specified pane,
+    /// taking into account the audible_bell_muted_domains and
+    /// audible_bell_sounds that apply to it.
+    /// No sound is made while the screen is locked.
+    fn ring_audible_bell(&self, pane_id: PaneId) {
+        if !matches!(self.config.audible_bell, AudibleBell::SystemBeep) {
+            return;
+        }
+        let mux = Mux::get();
+        let domain_name = mux
+            .get_pane(pane_id)
+            .and_then(|pane| mux.get_domain(pane.domain_id()))
+            .map(|domain| domain.domain_name().to_string())
+            .unwrap_or_default();
+        if self
+            .config
+            .audible_bell_muted_domains
+            .iter()
+            .any(|name| *name == domain_name)
+        {
+            return;
+        }
+
+        let conn = Connection::get().expect("on main thread");
+        let window_id = mux
+            .resolve_pane_id(pane_id)
+            .map(|(_domain, window_id, _tab_id)| window_id);
+
+        if crate::screenlock::is_screen_locked() {
+            // Every window is notified about the bell; count it once
+            if window_id == Some(self.mux_window_id) {
+                crate::screenlock::note_suppressed_bell();
+            }
+            return;
+        }
+        let workspace = window_id
+            .and_then(|window_id| mux.get_window(window_id))
+            .map(|window| window.get_workspace().to_string())
+            .unwrap_or_else(|| mux.active_workspace());
+
+        match self.config.bell_sound_for(&domain_name, &workspace) {
+            Some(sound) => {
+                // Every window is notified about the bell; only the
+                // window that contains the pane plays the sound, so
+                // that it isn't heard multiple times
+                if window_id != Some(self.mux_window_id) {
+                    return;
+                }
+                if let Err(err) = conn.play_sound(&sound.path, sound.volume) {
+                    log::error!("{:#}", err);
+                    conn.beep();
+                }
+            }
+            None => conn.beep(),
+        }
+    }
+
+    /// Returns the text of the most recent input zone in the pane,
+    /// which requires that shell integration is enabled
+    fn last_command_for_pane(pane: &MuxPane) -> Option<String> {
+        let zones = Mux::get().get_pane(pane.0)?.get_semantic_zones().ok()?;
+        let zone = zones
+            .into_iter()
+            .rev()
+            .find(|zone| zone.semantic_type == wezterm_term::SemanticType::Input)?;
+        let command = pane.get_text_from_semantic_zone(zone).ok()?;
+        let command = command.trim();
+        if command.is_empty() {
+            None
+        } else {
+            Some(command.to_string())
+        }
+    }
+
+    /// Returns the text of the most recent output zone in the pane,
+    /// which requires that shell integration is enabled
+    fn last_command_output_for_pane(pane: &MuxPane) -> Option<String> {
+        let zones = Mux::get().get_pane(pane.0)?.get_semantic_zones().ok()?;
+        let zone = zones
+            .into_iter()
+            .rev()
+            .find(|zone| zone.semantic_type == wezterm_term::SemanticType::Output)?;
+        let output = pane.get_text_from_semantic_zone(zone).ok()?;
+        Some(output.trim_end().to_string())
+    }
+
     /// Called as part of finishing up a callout to lua.
     /// If again==false it means that there isn't a lua config
     /// to execute against, so we should just mark as done.
@@ -1687,19 +2011,25 @@ impl TermWindow {
         );
         self.key_table_state.clear_stack();
         self.connection_name = Connection::get().unwrap().name();
-        let config = match config::overridden_config(&self.config_overrides) {
+        let overrides = self.effective_config_overrides();
+        let config = match config::overridden_config(&overrides) {
             Ok(config) => config,
             Err(err) => {
                 log::error!(
                     "Failed to apply config overrides to window: {:#}: {:?}",
                     err,
-                    self.config_overrides
+                    overrides
                 );
                 configuration()
             }
         };
+        if config.show_key_display != self.config.show_key_display {
+            self.set_key_display(config.show_key_display);
+        }
         self.config = config.clone();
         self.palette.take();
+        self.high_contrast = Self::is_high_contrast(&config);
+        self.reduced_motion = Self::is_reduced_motion(&configuration());
 
         let mux = Mux::get();
         let window = match mux.get_window(self.mux_window_id) {
@@ -1758,13 +2088,13 @@ impl TermWindow {
         }
 
         if let Some(window) = mux.get_window(self.mux_window_id) {
-            let term_config: Arc<dyn TerminalConfiguration> =
-                Arc::new(TermConfig::with_config(config.clone()));
+            let pane_config = TermConfig::with_config(config.clone());
             for tab in window.iter() {
                 for pane in tab.iter_panes_ignoring_zoom() {
-                    pane.pane.set_config(Arc::clone(&term_config));
+                    crate::spawn::set_pane_term_config(&pane.pane, &pane_config);
                 }
             }
+            let term_config: Arc<dyn TerminalConfiguration> = Arc::new(pane_config);
             for state in self.pane_state.borrow().values() {
                 if let Some(overlay) = &state.overlay {
                     overlay.pane.set_config(Arc::clone(&term_config));
@@ -1906,13 +2236,321 @@ impl TermWindow {
         .detach();
     }
 
+    /// Gives the `print-requested` event the chance to handle a print
+    /// job from `pane_id` before falling back to the print_destination
+    fn emit_print_event(&mut self, pane_id: PaneId, data: String, more_follows: bool) {
+        let mux = Mux::get();
+
+        let (_domain, window_id, _tab_id) = match mux.resolve_pane_id(pane_id) {
+            Some(tuple) => tuple,
+            None => return,
+        };
+
+        // Only the window which contains this pane prints
+        if window_id != self.mux_window_id {
+            return;
+        }
+
+        let window = GuiWin::new(self);
+        let pane = MuxPane(pane_id);
+
+        async fn do_event(
+            lua: Option<Rc<mlua::Lua>>,
+            window: GuiWin,
+            pane: MuxPane,
+            data: String,
+            more_follows: bool,
+        ) -> anyhow::Result<()> {
+            let pane_id = pane.0;
+            let default_print = match lua {
+                Some(lua) => {
+                    let args = lua.pack_multi((window, pane, data.clone(), more_follows))?;
+                    config::lua::emit_event(&lua, ("print-requested".to_string(), args))
+                        .await
+                        .map_err(|e| {
+                            log::error!("while processing print-requested event: {:#}", e);
+                            e
+                        })?
+                }
+                None => true,
+            };
+            if default_print {
+                crate::print::print_job(pane_id, data, more_follows);
+            } else if !more_follows {
+                // Parts of the job that were already sent
+                // to print_destination need to be completed
+                crate::print::end_print_job(pane_id);
+            }
+            Ok(())
+        }
+
+        promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
+            do_event(lua, window, pane, data, more_follows)
+        }))
+        .detach();
+    }
+
     /// Called by window:set_right_status after the status has
     /// been updated; let's update the bar
     pub fn update_title_post_status(&mut self) {
         self.update_title_impl();
     }
 
+    /// Returns the overrides needed to implement `reduce_motion`,
+    /// if it is active
+    fn reduced_motion_overrides(config: &ConfigHandle) -> Option<Value> {
+        if Self::is_reduced_motion(config) {
+            Some(config::reduced_motion_overrides(config))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the overrides needed to implement `power_saving`,
+    /// if it is active
+    fn power_saving_overrides(config: &ConfigHandle) -> Option<Value> {
+        if crate::power::is_power_saving(config) {
+            Some(config::power_saving_overrides(config))
+        } else {
+            None
+        }
+    }
+
+    /// Called when the state of the power supply has changed
+    pub fn power_state_changed(&mut self) {
+        self.config_was_reloaded();
+        self.emit_window_event("power-state-changed", None);
+    }
+
+    /// Returns true if the `reduce_motion` mode is active
+    fn is_reduced_motion(config: &ConfigHandle) -> bool {
+        let system = SYSTEM_REDUCED_MOTION.with(Cell::get);
+        config.reduce_motion.is_enabled(system)
+    }
+
+    /// Re-evaluates `reduce_motion`, as the system preference may have
+    /// changed since the config was last loaded
+    fn check_reduced_motion(&mut self) {
+        if Self::is_reduced_motion(&configuration()) != self.reduced_motion {
+            self.config_was_reloaded();
+        }
+    }
+
+    /// Returns true if the `high_contrast` mode is active
+    fn is_high_contrast(config: &ConfigHandle) -> bool {
+        let system = Connection::get()
+            .map(|conn| conn.get_appearance().is_high_contrast())
+            .unwrap_or(false);
+        config.high_contrast.is_enabled(system)
+    }
+
+    /// Returns the config_overrides of the window rule that matches
+    /// the window class of this process
+    fn window_rule_overrides(config: &ConfigHandle) -> Option<Value> {
+        config
+            .window_rule_for_class(&get_window_class())
+            .map(|rule| rule.config_overrides.clone())
+    }
+
+    /// Returns the overrides that apply to this window. In increasing
+    /// order of precedence, these are the overrides that implement
+    /// `power_saving` and `reduce_motion`, those from the matching window rule, those
+    /// from the matching ssh host profile, those of the active config profile,
+    /// those set via `window:set_config_overrides`, the colors being
+    /// previewed by the color scheme editor and finally those that
+    /// implement presentation mode
+    fn effective_config_overrides(&self) -> Value {
+        let mut layers = vec![];
+        let config = configuration();
+        if let Some(overrides) = Self::power_saving_overrides(&config) {
+            layers.push(overrides);
+        }
+        if let Some(overrides) = Self::reduced_motion_overrides(&config) {
+            layers.push(overrides);
+        }
+        if let Some(overrides) = Self::window_rule_overrides(&config) {
+            layers.push(overrides);
+        }
+        if let Some(profile) = &self.ssh_host_overrides {
+            layers.push(profile.clone());
+        }
+        if let Some(profile) = self
+            .config_profile
+            .as_ref()
+            .and_then(|name| config.profiles.get(name))
+        {
+            layers.push(profile.clone());
+        }
+        if layers.is_empty()
+            && !self.presentation_mode
+            && self.show_invisible_override.is_none()
+            && self.show_whitespace_override.is_none()
+            && self.color_scheme_preview.is_none()
+            && self.opacity_override.is_none()
+            && self.background_blur_override.is_none()
+        {
+            return self.config_overrides.clone();
+        }
+        layers.push(self.config_overrides.clone());
+        if let Some(palette) = &self.color_scheme_preview {
+            let mut preview = wezterm_dynamic::Object::default();
+            preview.insert(Value::String("colors".to_string()), palette.to_dynamic());
+            layers.push(Value::Object(preview));
+        }
+        if self.opacity_override.is_some() || self.background_blur_override.is_some() {
+            let mut opacity = wezterm_dynamic::Object::default();
+            if let Some(value) = self.opacity_override {
+                opacity.insert(
+                    Value::String("window_background_opacity".to_string()),
+                    value.to_dynamic(),
+                );
+            }
+            if let Some(value) = self.background_blur_override {
+                opacity.insert(
+                    Value::String("macos_window_background_blur".to_string()),
+                    value.to_dynamic(),
+                );
+            }
+            layers.push(Value::Object(opacity));
+        }
+
+        let mut merged = merge_override_layers(layers);
+
+        if let Some(show) = self.show_invisible_override {
+            merged.insert(
+                Value::String("show_invisible_characters".to_string()),
+                Value::Bool(show),
+            );
+        }
+
+        if let Some(show) = self.show_whitespace_override {
+            merged.insert(
+                Value::String("show_whitespace".to_string()),
+                Value::Bool(show),
+            );
+        }
+
+        if self.presentation_mode {
+            // Scale relative to the font size that would otherwise apply
+            let config = config::overridden_config(&Value::Object(merged.clone()))
+                .unwrap_or_else(|_| configuration());
+            merged.insert(
+                Value::String("font_size".to_string()),
+                (config.font_size * config.presentation_mode_font_scale).to_dynamic(),
+            );
+            merged.insert(
+                Value::String("enable_tab_bar".to_string()),
+                Value::Bool(false),
+            );
+        }
+
+        Value::Object(merged)
+    }
+
+    /// Enlarge the font, hide the tab bar and pane borders and show
+    /// the keys that are pressed, or restore the prior state
+    pub fn toggle_presentation_mode(&mut self) {
+        self.presentation_mode = !self.presentation_mode;
+        if self.presentation_mode {
+            if self.key_display.is_none() {
+                self.set_key_display(true);
+                self.key_display_for_presentation = true;
+            }
+        } else if self.key_display_for_presentation {
+            self.set_key_display(false);
+        }
+        self.config_was_reloaded();
+    }
+
+    /// Shows or hides the invisible characters in the panes of the
+    /// window, regardless of `show_invisible_characters`
+    pub fn toggle_show_invisible_characters(&mut self) {
+        self.show_invisible_override = Some(!self.config.show_invisible_characters);
+        self.config_was_reloaded();
+    }
+
+    /// Shows or hides the whitespace markers in the panes of the
+    /// window, regardless of `show_whitespace`
+    pub fn toggle_show_whitespace(&mut self) {
+        self.show_whitespace_override = Some(!self.config.show_whitespace);
+        self.config_was_reloaded();
+    }
+
+    /// Activates the entry named `name` in `config.profiles` for
+    /// this window, replacing any other profile, or deactivates the
+    /// active profile if `name` is None
+    pub fn set_config_profile(&mut self, name: Option<String>) -> anyhow::Result<()> {
+        if let Some(name) = &name {
+            let config = configuration();
+            if !config.profiles.contains_key(name) {
+                let mut known: Vec<&String> = config.profiles.keys().collect();
+                known.sort();
+                anyhow::bail!(
+                    "there is no config profile named `{name}`. The profiles are: {known:?}"
+                );
+            }
+        }
+        if name == self.config_profile {
+            return Ok(());
+        }
+        let prior = std::mem::replace(&mut self.config_profile, name);
+
+        // config_was_reloaded can only log bad overrides, so check
+        // them here in order to report them to the caller
+        if let Err(err) = config::overridden_config(&self.effective_config_overrides()) {
+            self.config_profile = prior;
+            return Err(err).context("applying config profile");
+        }
+        self.config_was_reloaded();
+        Ok(())
+    }
+
+    pub fn config_profile(&self) -> Option<String> {
+        self.config_profile.clone()
+    }
+
+    /// Returns the host that the pane is connected to, if it
+    /// belongs to an ssh domain
+    fn ssh_host_for_pane(pane: &Arc<dyn Pane>) -> Option<String> {
+        if pane.is_dead() {
+            return None;
+        }
+        let domain = Mux::get().get_domain(pane.domain_id())?;
+        if let Some(ssh) = domain.downcast_ref::<mux::ssh::RemoteSshDomain>() {
+            return Some(ssh.ssh_domain().remote_host_name().to_string());
+        }
+        if let Some(client) = domain.downcast_ref::<wezterm_client::domain::ClientDomain>() {
+            return client
+                .ssh_domain()
+                .map(|dom| dom.remote_host_name().to_string());
+        }
+        None
+    }
+
+    /// Applies the `ssh_host_profiles` entry that matches the active
+    /// pane, reverting to the regular configuration when there is none
+    fn update_ssh_host_profile(&mut self) {
+        let config = configuration();
+        let overrides = if config.ssh_host_profiles.is_empty() {
+            None
+        } else {
+            self.get_active_pane_no_overlay()
+                .and_then(|pane| Self::ssh_host_for_pane(&pane))
+                .and_then(|host| {
+                    let profile = config.ssh_host_profile(&host)?;
+                    log::trace!("ssh host {host} matches profile {:?}", profile.name);
+                    Some(profile.overrides.clone())
+                })
+        };
+
+        if overrides != self.ssh_host_overrides {
+            self.ssh_host_overrides = overrides;
+            self.config_was_reloaded();
+        }
+    }
+
     fn update_title_impl(&mut self) {
+        self.update_ssh_host_profile();
         let mux = Mux::get();
         let window = match mux.get_window(self.mux_window_id) {
             Some(window) => window,
@@ -2025,6 +2663,13 @@ impl TermWindow {
             }
         };
 
+        // Pane titles may reveal what is in the panes
+        let title = if self.is_idle_locked() {
+            "wezterm (locked)".to_string()
+        } else {
+            title
+        };
+
         if let Some(window) = self.window.as_ref() {
             window.set_title(&title);
 
@@ -2276,6 +2921,219 @@ impl TermWindow {
         promise::spawn::spawn(future).detach();
     }
 
+    fn show_global_search(&mut self, pattern: &Pattern, tag: Option<&str>) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let pane = match self.get_active_pane_or_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+
+        let pattern = self.resolve_search_pattern(pattern.clone(), &pane);
+        let workspace = mux.active_workspace();
+        let tag = tag.map(|tag| tag.to_string());
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::global_search::global_search(term, workspace, pattern, tag)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(async move {
+            if let Some(found) = future.await? {
+                crate::overlay::global_search::jump_to_match(found)?;
+            }
+            anyhow::Result::<()>::Ok(())
+        })
+        .detach();
+    }
+
+    fn show_setup_wizard(&mut self) {
+        let window = self.window.clone().unwrap();
+        let tab = match Mux::get().get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => {
+                // The initial tab is spawned after the window is created
+                promise::spawn::spawn(async move {
+                    Timer::after(Duration::from_millis(100)).await;
+                    window.notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                        term_window.show_setup_wizard();
+                    })));
+                })
+                .detach();
+                return;
+            }
+        };
+
+        let mut families: Vec<String> = self
+            .fonts
+            .list_fonts_in_font_dirs()
+            .into_iter()
+            .map(|font| font.names().family.clone())
+            .collect();
+        let locator = self.config.font_locator;
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            // Enumerating the system fonts can take a while, so it is
+            // done here rather than on the gui thread
+            let system_fonts = wezterm_font::locator::new_locator(locator)
+                .enumerate_all_fonts()
+                .unwrap_or_default();
+            families.extend(
+                system_fonts
+                    .into_iter()
+                    .map(|font| font.names().family.clone()),
+            );
+            families.sort();
+            families.dedup();
+            let fonts = crate::overlay::setup_wizard::installed_fonts(&families);
+            crate::overlay::setup_wizard::setup_wizard(term, window, fonts)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(async move {
+            if let Err(err) = future.await {
+                log::error!("setup wizard: {:#}", err);
+            }
+        })
+        .detach();
+    }
+
+    fn show_release_notes(&mut self) {
+        let tab = match Mux::get().get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let channel = self.config.update_channel;
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::release_notes::release_notes(term, channel)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(future).detach();
+    }
+
+    fn show_command_journal(&mut self) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let workspace = mux.active_workspace();
+        let window = self.window.clone().unwrap();
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::command_journal::command_journal(term, workspace)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(async move {
+            use crate::overlay::command_journal::{rerun_command, JournalAction};
+            match future.await? {
+                Some(JournalAction::Rerun(entry)) => rerun_command(&entry)?,
+                Some(JournalAction::Copy(entry)) => {
+                    window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        term_window.copy_to_clipboard(
+                            ClipboardCopyDestination::ClipboardAndPrimarySelection,
+                            entry.record.command,
+                        );
+                    })));
+                }
+                None => {}
+            }
+            anyhow::Result::<()>::Ok(())
+        })
+        .detach();
+    }
+
+    fn show_downloads(&mut self) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let window = self.window.clone().unwrap();
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::downloads::downloads(term)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(async move {
+            use crate::overlay::downloads::DownloadAction;
+            match future.await? {
+                Some(DownloadAction::Open(path)) => match url::Url::from_file_path(&path) {
+                    Ok(url) => wezterm_open_url::open_url(url.as_str()),
+                    Err(()) => log::error!("cannot open {}", path.display()),
+                },
+                Some(DownloadAction::Copy(path)) => {
+                    window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        term_window.copy_to_clipboard(
+                            ClipboardCopyDestination::ClipboardAndPrimarySelection,
+                            path.display().to_string(),
+                        );
+                    })));
+                }
+                None => {}
+            }
+            anyhow::Result::<()>::Ok(())
+        })
+        .detach();
+    }
+
+    fn reveal_suspicious_characters(&mut self) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+        let pane = match self.get_active_pane_no_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+
+        let dims = pane.get_dimensions();
+        let viewport = self
+            .get_viewport(pane.pane_id())
+            .unwrap_or(dims.physical_top);
+        let (top, lines) =
+            pane.get_lines(viewport..viewport + dims.viewport_rows as StableRowIndex);
+        let found = crate::suspicious::find_suspicious_characters(top - viewport, &lines);
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::suspicious::suspicious_characters(term, found)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(future).detach();
+    }
+
+    fn show_color_scheme_editor(&mut self) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let palette =
+            crate::overlay::color_scheme_editor::complete_palette(&self.config.resolved_palette);
+        let window = self.window.clone().unwrap();
+        let editor_window = window.clone();
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::color_scheme_editor::color_scheme_editor(term, editor_window, palette)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(async move {
+            let result = future.await;
+            window.notify(TermWindowNotif::Apply(Box::new(|term_window| {
+                term_window.set_color_scheme_preview(None);
+            })));
+            result
+        })
+        .detach();
+    }
+
     fn show_debug_overlay(&mut self) {
         let mux = Mux::get();
         let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
@@ -2284,17 +3142,70 @@ impl TermWindow {
         };
 
         let gui_win = GuiWin::new(self);
+        let pane_id = tab.get_active_pane().map(|pane| pane.pane_id());
 
         let opengl_info = self.opengl_info.as_deref().unwrap_or("Unknown").to_string();
         let connection_info = self.connection_name.clone();
 
         let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
-            crate::overlay::show_debug_overlay(term, gui_win, opengl_info, connection_info)
+            crate::overlay::show_debug_overlay(term, gui_win, pane_id, opengl_info, connection_info)
         });
         self.assign_overlay(tab.tab_id(), overlay);
         promise::spawn::spawn(future).detach();
     }
 
+    fn show_vt_inspector(&mut self) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+        let pane = match tab.get_active_pane() {
+            Some(pane) => pane,
+            None => return,
+        };
+        let pane_id = pane.pane_id();
+        let pane_index = match tab
+            .iter_panes_ignoring_zoom()
+            .iter()
+            .find(|p| p.pane.pane_id() == pane_id)
+        {
+            Some(p) => p.index,
+            None => return,
+        };
+
+        let request = SplitRequest {
+            direction: SplitDirection::Horizontal,
+            target_is_second: true,
+            top_level: false,
+            size: MuxSplitSize::Percent(40),
+        };
+        let size = match tab.compute_split_size(pane_index, request) {
+            Some(size) => size.second,
+            None => return,
+        };
+
+        let term_config: Arc<dyn TerminalConfiguration + Send + Sync> =
+            Arc::new(config::TermConfig::with_config(self.config.clone()));
+        let (term, inspector) = mux::termwiztermtab::allocate(size, term_config);
+        let inspector_id = inspector.pane_id();
+        if let Err(err) = tab.split_and_insert(pane_index, request, inspector) {
+            log::error!("Failed to show VT inspector: {err:#}");
+            mux.remove_pane(inspector_id);
+            return;
+        }
+
+        promise::spawn::spawn_into_new_thread(move || {
+            let result = crate::overlay::vtinspect::show_vt_inspector(term, pane_id);
+            promise::spawn::spawn_into_main_thread(async move {
+                Mux::get().remove_pane(inspector_id);
+            })
+            .detach();
+            result
+        })
+        .detach();
+    }
+
     fn show_tab_navigator(&mut self) {
         self.show_launcher_impl("Tab Navigator", LauncherFlags::TABS);
     }
@@ -2571,6 +3482,12 @@ impl TermWindow {
             ToggleFullScreen => {
                 self.window.as_ref().unwrap().toggle_fullscreen();
             }
+            TogglePresentationMode => self.toggle_presentation_mode(),
+            ActivateConfigProfile(name) => self.set_config_profile(Some(name.clone()))?,
+            DeactivateConfigProfile => self.set_config_profile(None)?,
+            ToggleKeyDisplay => self.set_key_display(self.key_display.is_none()),
+            ToggleShowInvisibleCharacters => self.toggle_show_invisible_characters(),
+            ToggleShowWhitespace => self.toggle_show_whitespace(),
             ToggleAlwaysOnTop => {
                 let window = self.window.clone().unwrap();
                 let current_level = self.window_state.as_window_level();
@@ -2597,17 +3514,61 @@ impl TermWindow {
                     }
                 }
             }
+            ToggleStickyWindow => {
+                let window = self.window.clone().unwrap();
+                window.set_sticky(!self.window_state.contains(WindowState::STICKY));
+            }
             SetWindowLevel(level) => {
                 let window = self.window.clone().unwrap();
                 window.set_window_level(level.clone());
             }
-            CopyTo(dest) => {
+            MoveWindow { dx, dy } => {
+                let window = self.window.clone().unwrap();
+                window
+                    .adjust_window_geometry(WindowGeometryAdjustment::MoveBy { dx: *dx, dy: *dy });
+            }
+            ResizeWindow { dx, dy } => {
+                let window = self.window.clone().unwrap();
+                window.adjust_window_geometry(WindowGeometryAdjustment::ResizeBy {
+                    dx: *dx,
+                    dy: *dy,
+                });
+            }
+            SnapWindow(position) => {
+                let window = self.window.clone().unwrap();
+                window.adjust_window_geometry(WindowGeometryAdjustment::Snap(*position));
+            }
+            CopyTo(args) => {
                 let text = self.selection_text(pane);
-                self.copy_to_clipboard(*dest, text);
+                self.copy_to(args, text);
             }
             CopyTextTo { text, destination } => {
                 self.copy_to_clipboard(*destination, text.clone());
             }
+            CaptureSelectionToUserVar(name) => {
+                let text = self.selection_text(pane);
+                if let Some(pane) = self.get_active_pane_no_overlay() {
+                    if let Err(err) = pane.set_user_var(name, &text) {
+                        log::error!("CaptureSelectionToUserVar: {:#}", err);
+                    }
+                }
+            }
+            CaptureLastCommandOutputToUserVar(name) => {
+                if let Some(pane) = self.get_active_pane_no_overlay() {
+                    match Self::last_command_output_for_pane(&MuxPane(pane.pane_id())) {
+                        Some(output) => {
+                            if let Err(err) = pane.set_user_var(name, &output) {
+                                log::error!("CaptureLastCommandOutputToUserVar: {:#}", err);
+                            }
+                        }
+                        None => log::error!(
+                            "CaptureLastCommandOutputToUserVar: pane {} has no command \
+                             output; this requires shell integration",
+                            pane.pane_id()
+                        ),
+                    }
+                }
+            }
             PasteFrom(source) => {
                 self.paste_from_clipboard(pane, *source);
             }
@@ -2621,6 +3582,9 @@ impl TermWindow {
             DecreaseFontSize => self.decrease_font_size(),
             IncreaseFontSize => self.increase_font_size(),
             ResetFontSize => self.reset_font_size(),
+            DecreasePaneFontSize => self.decrease_pane_font_size(),
+            IncreasePaneFontSize => self.increase_pane_font_size(),
+            ResetPaneFontSize => self.reset_pane_font_size(),
             ResetFontAndWindowSize => {
                 if let Some(w) = window.as_ref() {
                     self.reset_font_and_window_size(&w)?
@@ -2670,8 +3634,28 @@ impl TermWindow {
             ScrollToPrompt(n) => self.scroll_to_prompt(*n, pane)?,
             ScrollToTop => self.scroll_to_top(pane),
             ScrollToBottom => self.scroll_to_bottom(pane),
+            ToggleScrollbackSplit => self.toggle_scrollback_split(pane),
+            PauseOutputScrolling => self.toggle_output_pause(pane),
             ShowTabNavigator => self.show_tab_navigator(),
             ShowDebugOverlay => self.show_debug_overlay(),
+            ShowCommandJournal => self.show_command_journal(),
+            ShowDownloads => self.show_downloads(),
+            RevealSuspiciousCharacters => self.reveal_suspicious_characters(),
+            ShowColorSchemeEditor => self.show_color_scheme_editor(),
+            AdjustOpacity(delta) => self.adjust_opacity(**delta),
+            ShowVtInspector => self.show_vt_inspector(),
+            ShowReleaseNotes => self.show_release_notes(),
+            StartCapture(args) => self.start_recording(pane, args)?,
+            StopCapture => self.stop_recording(),
+            ExportToPdf(args) => self.export_pdf(pane, args)?,
+            ShowMagnifier(args) => self.show_magnifier(args),
+            MirrorPane => {
+                let mux = Mux::get();
+                let workspace = mux.active_workspace();
+                if let Err(err) = mux.mirror_pane_in_new_window(pane.pane_id(), Some(workspace)) {
+                    log::error!("failed to mirror pane {}: {err:#}", pane.pane_id());
+                }
+            }
             ShowLauncher => self.show_launcher(),
             ShowLauncherArgs(args) => {
                 self.show_launcher_impl(args.title.as_deref().unwrap_or("Launcher"), args.flags)
@@ -2709,6 +3693,7 @@ impl TermWindow {
             ExtendSelectionToMouseCursor(mode) => {
                 self.extend_selection_at_mouse_cursor(*mode, pane)
             }
+            ExtendSelectionToSemanticUnit => self.extend_selection_to_semantic_unit(pane),
             ClearSelection => {
                 self.clear_selection(pane);
             }
@@ -2744,6 +3729,8 @@ impl TermWindow {
                 let window = self.window.as_ref().unwrap();
                 window.invalidate();
             }
+            SearchAllPanes(pattern) => self.show_global_search(pattern, None),
+            SearchAllPanesArgs(args) => self.show_global_search(&args.pattern, args.tag.as_deref()),
             Search(pattern) => {
                 if let Some(pane) = self.get_active_pane_or_overlay() {
                     let mut replace_current = false;
@@ -2797,6 +3784,12 @@ impl TermWindow {
                     self.assign_overlay_for_pane(pane.pane_id(), qa);
                 }
             }
+            ShowLinkHints => {
+                if let Some(pane) = self.get_active_pane_no_overlay() {
+                    let hints = QuickSelectOverlay::with_links(self, &pane);
+                    self.assign_overlay_for_pane(pane.pane_id(), hints);
+                }
+            }
             ActivateCopyMode => {
                 if let Some(pane) = self.get_active_pane_or_overlay() {
                     let mut replace_current = false;
@@ -2881,6 +3874,24 @@ impl TermWindow {
                 };
                 tab.toggle_zoom();
             }
+            RespawnPane => {
+                let pane_id = pane.pane_id();
+                let domain_id = pane.domain_id();
+                promise::spawn::spawn(async move {
+                    let mux = Mux::get();
+                    let domain = mux
+                        .get_domain(domain_id)
+                        .ok_or_else(|| anyhow!("domain {domain_id} not found"))?;
+                    domain
+                        .respawn_pane(pane_id, None, None)
+                        .await
+                        .map_err(|err| {
+                            log::error!("Unable to respawn pane {pane_id}: {err:#}");
+                            err
+                        })
+                })
+                .detach();
+            }
             SetPaneZoomState(zoomed) => {
                 let mux = Mux::get();
                 let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
@@ -2972,6 +3983,7 @@ impl TermWindow {
                                 ),
                                 None,
                                 None,
+                                None,
                                 window,
                             )
                             .await?;
@@ -3039,6 +4051,12 @@ impl TermWindow {
                     termwiz::escape::Esc::Code(termwiz::escape::EscCode::FullReset),
                 )]);
             }
+            CancelZmodemTransfer => {
+                let mux = Mux::get();
+                if !mux.cancel_zmodem_transfer(pane.pane_id()) {
+                    log::info!("pane {} has no ZMODEM transfer to cancel", pane.pane_id());
+                }
+            }
             OpenUri(link) => {
                 wezterm_open_url::open_url(link);
             }
@@ -3061,40 +4079,47 @@ impl TermWindow {
         // perform below; here we allow the user to define an `open-uri` event
         // handler that can bypass the normal `open_url` functionality.
         if let Some(link) = self.current_highlight.as_ref().cloned() {
-            let window = GuiWin::new(self);
-            let pane = MuxPane(pane.pane_id());
-
-            async fn open_uri(
-                lua: Option<Rc<mlua::Lua>>,
-                window: GuiWin,
-                pane: MuxPane,
-                link: String,
-            ) -> anyhow::Result<()> {
-                let default_click = match lua {
-                    Some(lua) => {
-                        let args = lua.pack_multi((window, pane, link.clone()))?;
-                        config::lua::emit_event(&lua, ("open-uri".to_string(), args))
-                            .await
-                            .map_err(|e| {
-                                log::error!("while processing open-uri event: {:#}", e);
-                                e
-                            })?
-                    }
-                    None => true,
-                };
-                if default_click {
-                    log::info!("clicking {}", link);
-                    wezterm_open_url::open_url(&link);
+            self.open_link(pane, link.uri().to_string());
+        }
+    }
+
+    /// Opens `link`, giving the `open-uri` event the chance to handle
+    /// it before falling back to the system handler for the uri
+    pub fn open_link(&self, pane: &Arc<dyn Pane>, link: String) {
+        let window = GuiWin::new(self);
+        let pane = MuxPane(pane.pane_id());
+
+        async fn open_uri(
+            lua: Option<Rc<mlua::Lua>>,
+            window: GuiWin,
+            pane: MuxPane,
+            link: String,
+        ) -> anyhow::Result<()> {
+            let default_click = match lua {
+                Some(lua) => {
+                    let args = lua.pack_multi((window, pane, link.clone()))?;
+                    config::lua::emit_event(&lua, ("open-uri".to_string(), args))
+                        .await
+                        .map_err(|e| {
+                            log::error!("while processing open-uri event: {:#}", e);
+                            e
+                        })?
                 }
-                Ok(())
+                None => true,
+            };
+            if default_click {
+                log::info!("clicking {}", link);
+                wezterm_open_url::open_url(&link);
             }
-
-            promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
-                open_uri(lua, window, pane, link.uri().to_string())
-            }))
-            .detach();
+            Ok(())
         }
+
+        promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
+            open_uri(lua, window, pane, link)
+        }))
+        .detach();
     }
+
     fn close_current_pane(&mut self, confirm: bool) {
         let mux_window_id = self.mux_window_id;
         let mux = Mux::get();
@@ -3177,6 +4202,12 @@ impl TermWindow {
         })
     }
 
+    /// Overrides the visual bell style for the specified pane, or
+    /// restores the configured style if `visual_bell` is None
+    pub fn set_pane_visual_bell(&mut self, pane_id: PaneId, visual_bell: Option<VisualBell>) {
+        self.pane_state(pane_id).visual_bell = visual_bell;
+    }
+
     pub fn tab_state(&self, tab_id: TabId) -> RefMut<TabState> {
         RefMut::map(self.tab_state.borrow_mut(), |state| {
             state.entry(tab_id).or_insert_with(TabState::default)
@@ -3236,6 +4267,9 @@ impl TermWindow {
 
         let mut state = self.pane_state(pane_id);
         if pos != state.viewport {
+            if state.viewport.is_none() {
+                state.scrolled_back_from = Some(dims.physical_top);
+            }
             state.viewport = pos;
 
             // This is a bit gross.  If we add other overlays that need this information,
@@ -3321,13 +4355,14 @@ impl TermWindow {
         }
     }
 
-    fn pos_pane_to_pane_info(pos: &PositionedPane) -> PaneInformation {
+    fn pos_pane_to_pane_info(pos: &PositionedPane, unseen_output_lines: usize) -> PaneInformation {
         PaneInformation {
             pane_id: pos.pane.pane_id(),
             pane_index: pos.index,
             is_active: pos.is_active,
             is_zoomed: pos.is_zoomed,
             has_unseen_output: pos.pane.has_unseen_output(),
+            unseen_output_lines,
             left: pos.left,
             top: pos.top,
             width: pos.width,
@@ -3347,11 +4382,27 @@ impl TermWindow {
         };
         let tab_index = window.get_active_idx();
 
+        // The bell badge is cleared once the tab has been seen
+        if self.focused.is_some() {
+            if let Some(tab) = window.get_by_idx(tab_index) {
+                self.tabs_with_unseen_bell.remove(&tab.tab_id());
+            }
+        }
+
         window
             .iter()
             .enumerate()
             .map(|(idx, tab)| {
                 let panes = self.get_pos_panes_for_tab(tab);
+                let unseen_output_lines: HashMap<PaneId, usize> = panes
+                    .iter()
+                    .filter_map(|pos| {
+                        let pane_id = pos.pane.pane_id();
+                        let dims = pos.pane.get_dimensions();
+                        self.unseen_output_lines(pane_id, &dims)
+                            .map(|unseen| (pane_id, unseen))
+                    })
+                    .collect();
 
                 TabInformation {
                     tab_index: idx,
@@ -3359,10 +4410,12 @@ impl TermWindow {
                     is_active: tab_index == idx,
                     window_id: self.mux_window_id,
                     tab_title: tab.get_title(),
-                    active_pane: panes
-                        .iter()
-                        .find(|p| p.is_active)
-                        .map(Self::pos_pane_to_pane_info),
+                    has_bell: self.tabs_with_unseen_bell.contains(&tab.tab_id()),
+                    active_pane: panes.iter().find(|p| p.is_active).map(|pos| {
+                        let unseen = unseen_output_lines.get(&pos.pane.pane_id());
+                        Self::pos_pane_to_pane_info(pos, unseen.copied().unwrap_or(0))
+                    }),
+                    unseen_output_lines,
                 }
             })
             .collect()
@@ -3371,7 +4424,11 @@ impl TermWindow {
     fn get_pane_information(&self) -> Vec<PaneInformation> {
         self.get_panes_to_render()
             .iter()
-            .map(Self::pos_pane_to_pane_info)
+            .map(|pos| {
+                let dims = pos.pane.get_dimensions();
+                let unseen = self.unseen_output_lines(pos.pane.pane_id(), &dims);
+                Self::pos_pane_to_pane_info(pos, unseen.unwrap_or(0))
+            })
             .collect()
     }
 
@@ -3,12 +3,12 @@ use crate::termwindow::{
     GuiWin, MouseCapture, PositionedSplit, ScrollHit, TermWindowNotif, UIItem, UIItemType, TMB,
 };
 use ::window::{
-    MouseButtons as WMB, MouseCursor, MouseEvent, MouseEventKind as WMEK, MousePress,
+    Modifiers, MouseButtons as WMB, MouseCursor, MouseEvent, MouseEventKind as WMEK, MousePress,
     WindowDecorations, WindowOps, WindowState,
 };
 use config::keyassignment::{KeyAssignment, MouseEventTrigger, SpawnTabDomain};
 use config::MouseEventAltScreen;
-use mux::pane::{Pane, WithPaneLines};
+use mux::pane::{CachePolicy, Pane, WithPaneLines};
 use mux::tab::SplitDirection;
 use mux::Mux;
 use mux_lua::MuxPane;
@@ -43,7 +43,8 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::JumpToBottom(_) => {}
         }
     }
 
@@ -54,7 +55,8 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::JumpToBottom(_) => {}
         }
     }
 
@@ -67,6 +69,15 @@ impl super::TermWindow {
 
         self.current_mouse_event.replace(event.clone());
 
+        if self.magnifier_is_active() {
+            // The magnifier follows the pointer until the button
+            // that triggered it is released
+            match &event.kind {
+                WMEK::Release(_) => self.hide_magnifier(),
+                _ => context.invalidate(),
+            }
+        }
+
         let border = self.get_os_border();
 
         let first_line_offset = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
@@ -382,6 +393,9 @@ impl super::TermWindow {
             UIItemType::CloseTab(idx) => {
                 self.mouse_event_close_tab(idx, event, context);
             }
+            UIItemType::JumpToBottom(pane_id) => {
+                self.mouse_event_jump_to_bottom(pane_id, event, context);
+            }
         }
     }
 
@@ -724,6 +738,19 @@ impl super::TermWindow {
             }
         }
 
+        if let Some(fonts) = self.pane_fonts(pane.pane_id()) {
+            // The position was computed in terms of the window cell size,
+            // but this pane has its own font size, so translate it into
+            // the cells of the pane
+            let cell_size = fonts.render_metrics.cell_size;
+            let x = (column as isize * self.render_metrics.cell_size.width + x_pixel_offset).max(0);
+            let y = (row as isize * self.render_metrics.cell_size.height + y_pixel_offset).max(0);
+            column = (x / cell_size.width) as usize;
+            row = (y / cell_size.height) as i64;
+            x_pixel_offset = x % cell_size.width;
+            y_pixel_offset = y % cell_size.height;
+        }
+
         if capture_mouse {
             self.current_mouse_capture = Some(MouseCapture::TerminalPane(pane.pane_id()));
         }
@@ -768,10 +795,7 @@ impl super::TermWindow {
         );
 
         let dims = pane.get_dimensions();
-        let stable_row = self
-            .get_viewport(pane.pane_id())
-            .unwrap_or(dims.physical_top)
-            + row as StableRowIndex;
+        let stable_row = self.pane_row_to_stable_row(pane.pane_id(), &dims, row);
 
         self.pane_state(pane.pane_id())
             .mouse_terminal_coords
@@ -833,11 +857,14 @@ impl super::TermWindow {
             || event.coords.y < 0
             || event.coords.y as usize > self.dimensions.pixel_height;
 
+        let mouse_grabbed = pane.is_mouse_grabbed();
+        let mouse_reporting = self.is_mouse_reporting(&pane, event.modifiers);
+
         context.set_cursor(Some(if self.current_highlight.is_some() {
             // When hovering over a hyperlink, show an appropriate
             // mouse cursor to give the cue that it is clickable
             MouseCursor::Hand
-        } else if pane.is_mouse_grabbed() || outside_window {
+        } else if mouse_reporting || outside_window {
             MouseCursor::Arrow
         } else {
             MouseCursor::Text
@@ -921,12 +948,8 @@ impl super::TermWindow {
 
                 // Since we use shift to force assessing the mouse bindings, pretend
                 // that shift is not one of the mods when the mouse is grabbed.
-                let mut mouse_reporting = pane.is_mouse_grabbed();
-                if mouse_reporting {
-                    if modifiers.contains(self.config.bypass_mouse_reporting_modifiers) {
-                        modifiers.remove(self.config.bypass_mouse_reporting_modifiers);
-                        mouse_reporting = false;
-                    }
+                if mouse_grabbed && self.mouse_reporting_bypassed(modifiers) {
+                    modifiers.remove(self.config.bypass_mouse_reporting_modifiers);
                 }
 
                 if mouse_reporting {
@@ -1029,8 +1052,12 @@ impl super::TermWindow {
             modifiers: event.modifiers,
         };
 
+        // When reporting is bypassed, or disabled for the process,
+        // the application mustn't see the event, even if it didn't
+        // match any of the mouse assignments
         if allow_action
             && !(self.config.swallow_mouse_click_on_pane_focus && is_click_to_focus_pane)
+            && (mouse_reporting || !mouse_grabbed)
         {
             pane.mouse_event(mouse_event).ok();
         }
@@ -1042,6 +1069,37 @@ impl super::TermWindow {
             }
         }
     }
+
+    /// Returns true if the bypass_mouse_reporting_modifiers are held
+    fn mouse_reporting_bypassed(&self, modifiers: Modifiers) -> bool {
+        let bypass = self.config.bypass_mouse_reporting_modifiers;
+        bypass != Modifiers::NONE && modifiers.contains(bypass)
+    }
+
+    /// Returns true if mouse events are to be reported to the application
+    /// running in the pane, rather than being handled by the mouse
+    /// assignments.  That is the case when the application has enabled
+    /// one of the mouse reporting modes, unless the bypass modifiers are
+    /// held or reporting is disabled for its process.
+    fn is_mouse_reporting(&self, pane: &Arc<dyn Pane>, modifiers: Modifiers) -> bool {
+        if !pane.is_mouse_grabbed() || self.mouse_reporting_bypassed(modifiers) {
+            return false;
+        }
+        let disabled = &self.config.disable_mouse_reporting_for_processes_named;
+        if disabled.is_empty() {
+            return true;
+        }
+        match pane.get_foreground_process_name(CachePolicy::AllowStale) {
+            Some(path) => {
+                let name = std::path::Path::new(&path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                !disabled.iter().any(|n| *n == name || *n == path)
+            }
+            None => true,
+        }
+    }
 }
 
 fn mouse_press_to_tmb(press: &MousePress) -> TMB {
@@ -0,0 +1,133 @@
+//! The background opacity and blur of a window can be overridden at
+//! runtime, independently of the other windows, by the `AdjustOpacity`
+//! action or from lua.
+//! The overrides are layered over the config overrides of the window,
+//! so that the platform window picks them up in the same way as it
+//! does for changes to the configuration, and changes to the opacity
+//! are animated rather than taking effect immediately.
+//! The `inactive_window_opacity` option is applied on top of that
+//! while the window doesn't have focus.
+use crate::TermWindow;
+use ::window::WindowOps;
+use config::EasingFunction;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct OpacityAnimation {
+    from: f32,
+    to: f32,
+    start: Instant,
+    duration: Duration,
+    function: EasingFunction,
+}
+
+impl OpacityAnimation {
+    /// Returns the opacity at `now`, or None if the animation has completed
+    fn opacity_at(&self, now: Instant) -> Option<f32> {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= self.duration {
+            return None;
+        }
+        let position = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        let progress = self.function.evaluate_at_position(position).clamp(0., 1.);
+        Some(self.from + (self.to - self.from) * progress)
+    }
+}
+
+impl TermWindow {
+    /// The opacity with which to render the background of the window,
+    /// taking into account an animation that is in progress
+    pub fn window_background_opacity(&self) -> f32 {
+        if let Some(animation) = &self.opacity_animation {
+            let now = Instant::now();
+            if let Some(opacity) = animation.opacity_at(now) {
+                let fps = self.config.animation_fps.max(1) as u64;
+                self.update_next_frame_time(Some(now + Duration::from_millis(1000 / fps)));
+                return opacity;
+            }
+        }
+        self.target_window_background_opacity()
+    }
+
+    /// The opacity that the background settles on once any animation
+    /// has completed
+    fn target_window_background_opacity(&self) -> f32 {
+        let opacity = self.config.window_background_opacity;
+        if self.focused.is_some() {
+            opacity
+        } else {
+            opacity * self.config.inactive_window_opacity.clamp(0., 1.)
+        }
+    }
+
+    /// Overrides the background opacity of this window, or restores
+    /// the configured opacity if `opacity` is None
+    pub fn set_window_opacity(&mut self, opacity: Option<f32>) {
+        let opacity = opacity.map(|opacity| opacity.clamp(0., 1.));
+        if self.opacity_override == opacity {
+            return;
+        }
+        let from = self.window_background_opacity();
+        self.opacity_override = opacity;
+        self.config_was_reloaded();
+        self.animate_opacity_from(from);
+    }
+
+    /// Starts animating the background from `from` to its
+    /// current target opacity
+    pub fn animate_opacity_from(&mut self, from: f32) {
+        let to = self.target_window_background_opacity();
+        let duration = Duration::from_millis(self.config.opacity_animation_duration_ms);
+        self.opacity_animation = if from != to && !duration.is_zero() {
+            Some(OpacityAnimation {
+                from,
+                to,
+                start: Instant::now(),
+                duration,
+                function: self.config.opacity_animation_function,
+            })
+        } else {
+            None
+        };
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    /// Overrides the background blur radius of this window, or restores
+    /// the configured radius if `blur` is None
+    pub fn set_window_background_blur(&mut self, blur: Option<i64>) {
+        if self.background_blur_override != blur {
+            self.background_blur_override = blur;
+            self.config_was_reloaded();
+        }
+    }
+
+    pub fn adjust_opacity(&mut self, delta: f64) {
+        let opacity = self.config.window_background_opacity + delta as f32;
+        self.set_window_opacity(Some(opacity));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn animation() {
+        let start = Instant::now();
+        let animation = OpacityAnimation {
+            from: 1.0,
+            to: 0.5,
+            start,
+            duration: Duration::from_secs(1),
+            function: EasingFunction::Linear,
+        };
+        assert_eq!(animation.opacity_at(start), Some(1.0));
+        assert_eq!(
+            animation.opacity_at(start + Duration::from_millis(500)),
+            Some(0.75)
+        );
+        assert_eq!(animation.opacity_at(start + Duration::from_secs(1)), None);
+    }
+}
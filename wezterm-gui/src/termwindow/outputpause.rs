@@ -0,0 +1,304 @@
+//! While a pane is scrolled back, the output that continues to arrive
+//! is counted, and the count is shown in a pill in the bottom right
+//! corner of the pane that can be clicked to jump back to the bottom.
+//! The view of a pane can also be frozen at the bottom so that fast moving
+//! output can be read without entering copy mode, either by the
+//! `PauseOutputScrolling` action, which toggles it, or for as long as
+//! `pause_output_scrolling_mods` are held.
+use crate::termwindow::box_model::*;
+use crate::termwindow::render::corners::{
+    BOTTOM_LEFT_ROUNDED_CORNER, BOTTOM_RIGHT_ROUNDED_CORNER, TOP_LEFT_ROUNDED_CORNER,
+    TOP_RIGHT_ROUNDED_CORNER,
+};
+use crate::termwindow::{DimensionContext, UIItemType};
+use crate::utilsprites::RenderMetrics;
+use crate::TermWindow;
+use ::window::{Modifiers, MouseCursor, MouseEvent, MouseEventKind, MousePress, WindowOps};
+use config::Dimension;
+use mux::pane::{Pane, PaneId};
+use mux::renderable::RenderableDimensions;
+use std::sync::Arc;
+use wezterm_term::StableRowIndex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputPause {
+    /// true if the pause lasts only while the modifiers are held
+    held: bool,
+}
+
+/// The number of lines that have scrolled into the pane since
+/// it was scrolled back
+fn unseen_lines(scrolled_back_from: StableRowIndex, physical_top: StableRowIndex) -> usize {
+    (physical_top - scrolled_back_from).max(0) as usize
+}
+
+fn indicator_label(unseen: usize, paused: bool) -> String {
+    let lines = match unseen {
+        1 => "1 new line".to_string(),
+        n => format!("{n} new lines"),
+    };
+    match (paused, unseen) {
+        (true, 0) => "Output paused".to_string(),
+        (true, _) => format!("Output paused, {lines} \u{2193}"),
+        (false, _) => format!("{lines} \u{2193}"),
+    }
+}
+
+impl TermWindow {
+    /// Returns the number of lines of output that have arrived since
+    /// the pane was scrolled back, or None if it isn't scrolled back
+    pub fn unseen_output_lines(
+        &self,
+        pane_id: PaneId,
+        dims: &RenderableDimensions,
+    ) -> Option<usize> {
+        let mut state = self.pane_state(pane_id);
+        if state.viewport.is_none() {
+            // Something, such as typing or scrolling to the bottom,
+            // has returned the pane to following its output
+            state.output_pause.take();
+            return None;
+        }
+        let from = state.scrolled_back_from.unwrap_or(dims.physical_top);
+        Some(unseen_lines(from, dims.physical_top))
+    }
+
+    fn is_output_paused(&self, pane_id: PaneId) -> bool {
+        let state = self.pane_state(pane_id);
+        state.viewport.is_some() && state.output_pause.is_some()
+    }
+
+    fn pause_output(&mut self, pane: &Arc<dyn Pane>, held: bool) {
+        let pane_id = pane.pane_id();
+        let dims = pane.get_dimensions();
+        {
+            let mut state = self.pane_state(pane_id);
+            if state.output_pause.is_some() {
+                return;
+            }
+            // set_viewport treats a viewport at the physical top as
+            // following the output, so freeze it directly
+            if state.viewport.is_none() {
+                state.viewport = Some(dims.physical_top);
+                state.scrolled_back_from = Some(dims.physical_top);
+            }
+            state.output_pause.replace(OutputPause { held });
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    fn resume_output(&mut self, pane_id: PaneId) {
+        if self.pane_state(pane_id).output_pause.is_some() {
+            self.jump_to_new_output(pane_id);
+        }
+    }
+
+    /// Returns the pane to following its output
+    fn jump_to_new_output(&mut self, pane_id: PaneId) {
+        {
+            let mut state = self.pane_state(pane_id);
+            state.output_pause.take();
+            state.viewport = None;
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
+    pub fn toggle_output_pause(&mut self, pane: &Arc<dyn Pane>) {
+        if self.is_output_paused(pane.pane_id()) {
+            self.resume_output(pane.pane_id());
+        } else {
+            self.pause_output(pane, false);
+        }
+    }
+
+    pub fn mouse_event_jump_to_bottom(
+        &mut self,
+        pane_id: PaneId,
+        event: MouseEvent,
+        context: &dyn WindowOps,
+    ) {
+        if let MouseEventKind::Press(MousePress::Left) = event.kind {
+            self.jump_to_new_output(pane_id);
+        }
+        context.set_cursor(Some(MouseCursor::Hand));
+    }
+
+    /// Pauses the active pane while exactly the configured modifiers
+    /// are held down, and resumes it once they are released, unless
+    /// it was paused by PauseOutputScrolling
+    pub fn output_pause_modifiers_changed(&mut self, modifiers: Modifiers) {
+        let mods = self.config.pause_output_scrolling_mods;
+        if mods == Modifiers::NONE {
+            return;
+        }
+        let pane = match self.get_active_pane_no_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+        if modifiers.remove_positional_mods() == mods {
+            self.pause_output(&pane, true);
+        } else if matches!(
+            self.pane_state(pane.pane_id()).output_pause,
+            Some(OutputPause { held: true, .. })
+        ) {
+            self.resume_output(pane.pane_id());
+        }
+    }
+
+    /// Resumes the active pane if it was paused by holding the
+    /// configured modifiers, as pressing another key means that they
+    /// are being used for a key combination instead
+    pub fn output_pause_key_pressed(&mut self) {
+        let pane = match self.get_active_pane_no_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+        if matches!(
+            self.pane_state(pane.pane_id()).output_pause,
+            Some(OutputPause { held: true, .. })
+        ) {
+            self.resume_output(pane.pane_id());
+        }
+    }
+
+    pub fn paint_output_pause_indicators(&mut self) -> anyhow::Result<()> {
+        let indicators: Vec<_> = self
+            .get_panes_to_render()
+            .into_iter()
+            .filter_map(|pos| {
+                let pane_id = pos.pane.pane_id();
+                let dims = pos.pane.get_dimensions();
+                let unseen = self.unseen_output_lines(pane_id, &dims)?;
+                let paused = self.is_output_paused(pane_id);
+                if unseen == 0 && !paused {
+                    return None;
+                }
+                Some((pos, indicator_label(unseen, paused)))
+            })
+            .collect();
+        if indicators.is_empty() {
+            return Ok(());
+        }
+
+        let font = self.fonts.title_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+
+        let (padding_left, padding_top) = self.padding_left_top();
+        let tab_bar_height = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height()?
+        } else {
+            0.
+        };
+        let border = self.get_os_border();
+        let left_pixel_x = padding_left + border.left.get() as f32;
+        let top_pixel_y = tab_bar_height + padding_top + border.top.get() as f32;
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+        let dimensions = self.dimensions;
+
+        for (pos, label) in indicators {
+            let palette = pos.pane.palette();
+            let fg = palette.background.to_linear();
+            let bg = palette.foreground.to_linear();
+
+            let element = Element::new(&font, ElementContent::Text(label))
+                .item_type(UIItemType::JumpToBottom(pos.pane.pane_id()))
+                .colors(ElementColors {
+                    border: BorderColor::new(bg.into()),
+                    bg: bg.into(),
+                    text: fg.into(),
+                })
+                .padding(BoxDimension {
+                    left: Dimension::Cells(0.5),
+                    right: Dimension::Cells(0.5),
+                    top: Dimension::Cells(0.25),
+                    bottom: Dimension::Cells(0.25),
+                })
+                .border(BoxDimension::new(Dimension::Pixels(1.)))
+                .border_corners(Some(Corners {
+                    top_left: SizedPoly {
+                        width: Dimension::Cells(0.25),
+                        height: Dimension::Cells(0.25),
+                        poly: TOP_LEFT_ROUNDED_CORNER,
+                    },
+                    top_right: SizedPoly {
+                        width: Dimension::Cells(0.25),
+                        height: Dimension::Cells(0.25),
+                        poly: TOP_RIGHT_ROUNDED_CORNER,
+                    },
+                    bottom_left: SizedPoly {
+                        width: Dimension::Cells(0.25),
+                        height: Dimension::Cells(0.25),
+                        poly: BOTTOM_LEFT_ROUNDED_CORNER,
+                    },
+                    bottom_right: SizedPoly {
+                        width: Dimension::Cells(0.25),
+                        height: Dimension::Cells(0.25),
+                        poly: BOTTOM_RIGHT_ROUNDED_CORNER,
+                    },
+                }));
+
+            let gl_state = self.render_state.as_ref().unwrap();
+            let mut computed = self.compute_element(
+                &LayoutContext {
+                    height: DimensionContext {
+                        dpi: dimensions.dpi as f32,
+                        pixel_max: dimensions.pixel_height as f32,
+                        pixel_cell: metrics.cell_size.height as f32,
+                    },
+                    width: DimensionContext {
+                        dpi: dimensions.dpi as f32,
+                        pixel_max: dimensions.pixel_width as f32,
+                        pixel_cell: metrics.cell_size.width as f32,
+                    },
+                    bounds: euclid::rect(
+                        0.,
+                        0.,
+                        dimensions.pixel_width as f32,
+                        dimensions.pixel_height as f32,
+                    ),
+                    metrics: &metrics,
+                    gl_state,
+                    zindex: 100,
+                },
+                &element,
+            )?;
+
+            // Move it to the bottom right corner of the pane, leaving
+            // a gap of one cell between it and the edges of the pane
+            let pane_right = left_pixel_x + pos.left as f32 * cell_width + pos.pixel_width as f32;
+            let pane_bottom = top_pixel_y + pos.top as f32 * cell_height + pos.pixel_height as f32;
+            let x = (pane_right - computed.bounds.width() - cell_width).max(0.);
+            let y = (pane_bottom - computed.bounds.height() - cell_height).max(0.);
+            computed.translate(euclid::vec2(x, y));
+
+            self.render_element(&computed, gl_state, None)?;
+            self.ui_items.append(&mut computed.ui_items());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unseen() {
+        assert_eq!(unseen_lines(100, 100), 0);
+        assert_eq!(unseen_lines(100, 142), 42);
+        // The screen can be cleared, moving the physical top back
+        assert_eq!(unseen_lines(100, 50), 0);
+        assert_eq!(indicator_label(0, true), "Output paused");
+        assert_eq!(
+            indicator_label(1, true),
+            "Output paused, 1 new line \u{2193}"
+        );
+        assert_eq!(indicator_label(42, false), "42 new lines \u{2193}");
+    }
+}
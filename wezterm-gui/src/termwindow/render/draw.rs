@@ -7,26 +7,45 @@ use ::window::glium::uniforms::{
     MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerWrapFunction,
 };
 use ::window::glium::{BlendingFunction, LinearBlendingFactor, Surface};
-use config::FreeTypeLoadTarget;
+use config::{FreeTypeLoadTarget, HsbTransform};
 
 impl crate::TermWindow {
     pub fn call_draw(&mut self, frame: &mut RenderFrame) -> anyhow::Result<()> {
         match frame {
-            RenderFrame::Glium(ref mut frame) => self.call_draw_glium(frame),
-            RenderFrame::WebGpu => self.call_draw_webgpu(),
+            RenderFrame::Glium(ref mut frame) => self.call_draw_glium(&mut **frame),
+            RenderFrame::GliumTexture(ref mut target) => self.call_draw_glium(target),
+            RenderFrame::WebGpu => self.call_draw_webgpu(None),
+            RenderFrame::WebGpuTexture(texture) => self.call_draw_webgpu(Some(texture)),
         }
     }
 
-    fn call_draw_webgpu(&mut self) -> anyhow::Result<()> {
+    /// The transform applied to the whole window, which dims it
+    /// according to inactive_window_hsb while it doesn't have focus
+    fn window_hsb(&self) -> HsbTransform {
+        if self.focused.is_some() {
+            HsbTransform::default()
+        } else {
+            self.config.inactive_window_hsb
+        }
+    }
+
+    /// Draws to the surface of the window, or to `target` if specified
+    fn call_draw_webgpu(&mut self, target: Option<&wgpu::Texture>) -> anyhow::Result<()> {
         use crate::termwindow::webgpu::WebGpuTexture;
 
         let webgpu = self.webgpu.as_mut().unwrap();
         let render_state = self.render_state.as_ref().unwrap();
 
-        let output = webgpu.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let output = match target {
+            Some(_) => None,
+            None => Some(webgpu.surface.get_current_texture()?),
+        };
+        let view = match (target, &output) {
+            (Some(texture), _) => texture,
+            (None, Some(output)) => &output.texture,
+            (None, None) => unreachable!(),
+        }
+        .create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = webgpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -75,6 +94,8 @@ impl crate::TermWindow {
             foreground_text_hsb.saturation,
             foreground_text_hsb.brightness,
         ];
+        let window_hsb = self.window_hsb();
+        let window_hsb = [window_hsb.hue, window_hsb.saturation, window_hsb.brightness];
 
         let milliseconds = self.created.elapsed().as_millis() as u32;
         let projection = euclid::Transform3D::<f32, f32, f32>::ortho(
@@ -124,6 +145,8 @@ impl crate::TermWindow {
                         foreground_text_hsb,
                         milliseconds,
                         projection,
+                        window_hsb,
+                        _padding: 0,
                     });
 
                     render_pass.set_pipeline(&webgpu.render_pipeline);
@@ -144,12 +167,15 @@ impl crate::TermWindow {
 
         // submit will accept anything that implements IntoIter
         webgpu.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+
+        if let Some(output) = output {
+            output.present();
+        }
 
         Ok(())
     }
 
-    fn call_draw_glium(&mut self, frame: &mut glium::Frame) -> anyhow::Result<()> {
+    fn call_draw_glium<S: Surface>(&mut self, frame: &mut S) -> anyhow::Result<()> {
         use window::glium::texture::SrgbTexture2d;
 
         let gl_state = self.render_state.as_ref().unwrap();
@@ -227,6 +253,8 @@ impl crate::TermWindow {
             foreground_text_hsb.saturation,
             foreground_text_hsb.brightness,
         );
+        let window_hsb = self.window_hsb();
+        let window_hsb = (window_hsb.hue, window_hsb.saturation, window_hsb.brightness);
 
         let milliseconds = self.created.elapsed().as_millis() as u32;
 
@@ -248,6 +276,7 @@ impl crate::TermWindow {
                     uniforms.add("atlas_nearest_sampler", &atlas_nearest_sampler);
                     uniforms.add("atlas_linear_sampler", &atlas_linear_sampler);
                     uniforms.add("foreground_text_hsb", &foreground_text_hsb);
+                    uniforms.add("window_hsb", &window_hsb);
                     uniforms.add("subpixel_aa", &subpixel_aa);
                     uniforms.add("milliseconds", &milliseconds);
                     uniforms.add_struct("cursor_blink", &cursor_blink);
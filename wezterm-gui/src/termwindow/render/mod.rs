@@ -1,6 +1,6 @@
 use crate::colorease::ColorEase;
 use crate::customglyph::{BlockKey, *};
-use crate::glyphcache::{CachedGlyph, GlyphCache};
+use crate::glyphcache::{CachedGlyph, CellMetricKey, GlyphCache};
 use crate::quad::{
     HeapQuadAllocator, QuadAllocator, QuadImpl, QuadTrait, TripleLayerQuadAllocator,
     TripleLayerQuadAllocatorTrait,
@@ -12,7 +12,10 @@ use crate::utilsprites::RenderMetrics;
 use ::window::bitmaps::{TextureCoord, TextureRect, TextureSize};
 use ::window::{DeadKeyStatus, PointF, RectF, SizeF, WindowOps};
 use anyhow::{anyhow, Context};
-use config::{BoldBrightening, ConfigHandle, DimensionContext, TextStyle, VisualBellTarget};
+use config::{
+    BoldBrightening, ConfigHandle, DimensionContext, SuspiciousCharacterHighlight, TextStyle,
+    VisualBellTarget,
+};
 use euclid::num::Zero;
 use mux::pane::{Pane, PaneId};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
@@ -26,7 +29,7 @@ use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::{CursorShape, CursorVisibility, SequenceNo};
 use wezterm_font::shaper::PresentationWidth;
 use wezterm_font::units::{IntPixelLength, PixelLength};
-use wezterm_font::{ClearShapeCache, GlyphInfo, LoadedFont};
+use wezterm_font::{ClearShapeCache, FontConfiguration, GlyphInfo, LoadedFont};
 use wezterm_term::color::{ColorAttribute, ColorPalette};
 use wezterm_term::{CellAttributes, Line, StableRowIndex};
 use window::color::LinearRgba;
@@ -48,6 +51,42 @@ pub struct CachedLineState {
     pub id: u64,
     pub seqno: SequenceNo,
     pub shape_hash: [u8; 16],
+    /// The transform that was applied to the line
+    pub transform: LineTransform,
+    /// The copy of the line with the transform applied, if it changed
+    /// anything.  Keeping it here lets it hold on to its own cached
+    /// state, rather than being made and shaped again for each frame.
+    pub transformed: Option<Arc<Line>>,
+}
+
+/// The changes that are made to a copy of a line that is rendered
+/// in its place
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineTransform {
+    pub highlight: SuspiciousCharacterHighlight,
+    pub show_invisible_characters: bool,
+    /// The width of the pane, if `show_whitespace` is enabled
+    pub show_whitespace: Option<usize>,
+}
+
+impl LineTransform {
+    /// Returns the transformed copy of `line`, or None if the
+    /// transform leaves it unchanged
+    fn apply(&self, line: &Line) -> Option<Line> {
+        let highlighted = crate::suspicious::highlight_line(line, self.highlight);
+        let line = highlighted.as_ref().unwrap_or(line);
+        let revealed = if self.show_invisible_characters {
+            crate::invisible::show_invisible_characters(line)
+        } else {
+            None
+        };
+        let line = revealed.as_ref().unwrap_or(line);
+        let whitespace = match self.show_whitespace {
+            Some(cols) => crate::whitespace::show_whitespace(line, cols),
+            None => None,
+        };
+        whitespace.or(revealed).or(highlighted)
+    }
 }
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
@@ -91,6 +130,8 @@ pub struct LineToElementParams<'a> {
     pub cursor: &'a StableCursorPosition,
     pub reverse_video: bool,
     pub shape_key: &'a Option<LineToEleShapeCacheKey>,
+    pub fonts: &'a FontConfiguration,
+    pub render_metrics: &'a RenderMetrics,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
@@ -98,6 +139,7 @@ pub struct LineToEleShapeCacheKey {
     pub shape_hash: [u8; 16],
     pub composing: Option<(usize, String)>,
     pub shape_generation: usize,
+    pub metric: CellMetricKey,
 }
 
 pub struct LineToElementShapeItem {
@@ -162,6 +204,9 @@ pub struct RenderScreenLineParams<'a> {
     /// Override font resolution; useful together with
     /// the resolved title font
     pub font: Option<Rc<LoadedFont>>,
+    /// Override the font configuration; used for panes
+    /// that have their own font size
+    pub fonts: Option<Rc<FontConfiguration>>,
     pub style: Option<&'a TextStyle>,
 
     /// If true, use the shaper-determined pixel positions,
@@ -240,12 +285,16 @@ impl crate::TermWindow {
     ) -> Option<f32> {
         let mut per_pane = self.pane_state(pane.pane_id());
         if let Some(ringing) = per_pane.bell_start {
-            if config.visual_bell.target == target {
+            let visual_bell = per_pane
+                .visual_bell
+                .clone()
+                .unwrap_or_else(|| config.visual_bell.clone());
+            if visual_bell.target == target {
                 let mut color_ease = ColorEase::new(
-                    config.visual_bell.fade_in_duration_ms,
-                    config.visual_bell.fade_in_function,
-                    config.visual_bell.fade_out_duration_ms,
-                    config.visual_bell.fade_out_function,
+                    visual_bell.fade_in_duration_ms,
+                    visual_bell.fade_in_function,
+                    visual_bell.fade_out_duration_ms,
+                    visual_bell.fade_out_function,
                     Some(ringing),
                 );
 
@@ -368,13 +417,15 @@ impl crate::TermWindow {
         style: &TextStyle,
         attrs: &CellAttributes,
         font: Option<&Rc<LoadedFont>>,
+        fonts: &FontConfiguration,
         gl_state: &RenderState,
         metrics: &RenderMetrics,
     ) -> anyhow::Result<Rc<CachedGlyph>> {
         let fa_lock = "\u{f023}";
         let line = Line::from_text(fa_lock, attrs, 0, None);
         let cluster = line.cluster(None);
-        let shape_info = self.cached_cluster_shape(style, &cluster[0], gl_state, font, metrics)?;
+        let shape_info =
+            self.cached_cluster_shape(style, &cluster[0], gl_state, font, fonts, metrics)?;
         Ok(Rc::clone(&shape_info[0].glyph))
     }
 
@@ -489,6 +540,22 @@ impl crate::TermWindow {
         Ok(())
     }
 
+    /// Returns the minimum contrast ratio that text should have
+    /// with its background, if we should enforce one
+    pub fn minimum_contrast_ratio(&self) -> Option<f32> {
+        let ratio = if self.high_contrast {
+            // The WCAG "AAA" level for normal text
+            self.config.minimum_contrast_ratio.max(7.0)
+        } else {
+            self.config.minimum_contrast_ratio
+        };
+        if ratio > 1.0 {
+            Some(ratio)
+        } else {
+            None
+        }
+    }
+
     pub fn compute_cell_fg_bg(&self, params: ComputeCellFgBgParams) -> ComputeCellFgBgResult {
         if params.cursor.is_some() {
             if let Some(bg_color_mix) = self.get_intensity_if_bell_target_ringing(
@@ -733,12 +800,14 @@ impl crate::TermWindow {
         cluster: &CellCluster,
         gl_state: &RenderState,
         font: Option<&Rc<LoadedFont>>,
+        fonts: &FontConfiguration,
         metrics: &RenderMetrics,
     ) -> anyhow::Result<Rc<Vec<ShapedInfo>>> {
         let shape_resolve_start = Instant::now();
         let key = BorrowedShapeCacheKey {
             style,
             text: &cluster.text,
+            metric: metrics.into(),
         };
         let glyph_info = match self.lookup_cached_shape(&key) {
             Some(Ok(info)) => info,
@@ -746,7 +815,7 @@ impl crate::TermWindow {
             None => {
                 let font = match font {
                     Some(f) => Rc::clone(f),
-                    None => self.fonts.resolve_font(style)?,
+                    None => fonts.resolve_font(style)?,
                 };
                 let window = self.window.as_ref().unwrap().clone();
 
@@ -819,14 +888,33 @@ impl crate::TermWindow {
     }
 
     fn shape_hash_for_line(&mut self, line: &Line) -> [u8; 16] {
+        self.line_state(line, None).shape_hash
+    }
+
+    /// Returns a copy of `line` with `transform` applied, or None if
+    /// that leaves it unchanged
+    fn transformed_line(&mut self, line: &Line, transform: LineTransform) -> Option<Arc<Line>> {
+        self.line_state(line, Some(transform)).transformed.clone()
+    }
+
+    /// Returns the cached state of `line`, computing it again if the
+    /// line changed since, or if `transform` is given and differs from
+    /// the one that was applied to it
+    fn line_state(
+        &mut self,
+        line: &Line,
+        transform: Option<LineTransform>,
+    ) -> Arc<CachedLineState> {
         let seqno = line.current_seqno();
         let mut id = None;
         if let Some(cached_arc) = line.get_appdata() {
-            if let Some(line_state) = cached_arc.downcast_ref::<CachedLineState>() {
-                if line_state.seqno == seqno {
+            if let Ok(line_state) = cached_arc.downcast::<CachedLineState>() {
+                if line_state.seqno == seqno
+                    && transform.map_or(true, |t| t == line_state.transform)
+                {
                     // Touch the LRU
                     self.line_state_cache.borrow_mut().get(&line_state.id);
-                    return line_state.shape_hash;
+                    return line_state;
                 }
                 id.replace(line_state.id);
             }
@@ -839,17 +927,23 @@ impl crate::TermWindow {
         });
 
         let shape_hash = line.compute_shape_hash();
+        let transform = transform.unwrap_or_default();
+        let transformed = transform.apply(line).map(Arc::new);
 
         let state = Arc::new(CachedLineState {
             id,
             seqno,
             shape_hash,
+            transform,
+            transformed,
         });
 
         line.set_appdata(Arc::clone(&state));
 
-        self.line_state_cache.borrow_mut().put(id, state);
-        shape_hash
+        self.line_state_cache
+            .borrow_mut()
+            .put(id, Arc::clone(&state));
+        state
     }
 }
 
@@ -909,3 +1003,27 @@ fn same_hyperlink(a: Option<&Arc<Hyperlink>>, b: Option<&Arc<Hyperlink>>) -> boo
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn line_transform_combines_features() {
+        let line = Line::from_text("a\u{a0}b  ", &CellAttributes::default(), SEQ_ZERO, None);
+        assert!(LineTransform::default().apply(&line).is_none());
+
+        let transform = LineTransform {
+            show_invisible_characters: true,
+            ..LineTransform::default()
+        };
+        assert_eq!(transform.apply(&line).unwrap().as_str(), "a⍽b  ");
+
+        let transform = LineTransform {
+            show_whitespace: Some(10),
+            ..transform
+        };
+        assert_eq!(transform.apply(&line).unwrap().as_str(), "a⍽b··");
+    }
+}
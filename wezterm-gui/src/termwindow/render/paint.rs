@@ -115,6 +115,8 @@ impl crate::TermWindow {
         metrics::histogram!("gui.paint.impl", self.last_frame_duration);
         metrics::histogram!("gui.paint.impl.rate", 1.);
 
+        self.update_accessibility();
+
         // If self.has_animation is some, then the last render detected
         // image attachments with multiple frames, so we also need to
         // invalidate the viewport when the next frame is due
@@ -172,7 +174,7 @@ impl crate::TermWindow {
         let panes = self.get_panes_to_render();
         let focused = self.focused.is_some();
         let window_is_transparent =
-            !self.window_background.is_empty() || self.config.window_background_opacity != 1.0;
+            !self.window_background.is_empty() || self.window_background_opacity() != 1.0;
 
         let start = Instant::now();
         let gl_state = self.render_state.as_ref().unwrap();
@@ -230,7 +232,7 @@ impl crate::TermWindow {
                 self.palette().background
             }
             .to_linear()
-            .mul_alpha(self.config.window_background_opacity);
+            .mul_alpha(self.window_background_opacity());
 
             self.filled_rectangle(
                 &mut layers,
@@ -246,6 +248,14 @@ impl crate::TermWindow {
             .context("filled_rectangle for window background")?;
         }
 
+        if self.is_idle_locked() {
+            // Show nothing but the background until the user
+            // has authenticated again
+            self.paint_window_borders(&mut layers)
+                .context("paint_window_borders")?;
+            return Ok(());
+        }
+
         for pos in panes {
             if pos.is_active {
                 self.update_text_cursor(&pos);
@@ -257,7 +267,13 @@ impl crate::TermWindow {
             self.paint_pane(&pos, &mut layers).context("paint_pane")?;
         }
 
-        if let Some(pane) = self.get_active_pane_or_overlay() {
+        // Pane borders are hidden in presentation mode
+        let pane = if self.presentation_mode {
+            None
+        } else {
+            self.get_active_pane_or_overlay()
+        };
+        if let Some(pane) = pane {
             let splits = self.get_splits();
             for split in &splits {
                 self.paint_split(&mut layers, split, &pane)
@@ -272,7 +288,11 @@ impl crate::TermWindow {
         self.paint_window_borders(&mut layers)
             .context("paint_window_borders")?;
         drop(layers);
+        self.paint_output_pause_indicators()
+            .context("paint_output_pause_indicators")?;
         self.paint_modal().context("paint_modal")?;
+        self.paint_key_display().context("paint_key_display")?;
+        self.paint_magnifier().context("paint_magnifier")?;
 
         Ok(())
     }
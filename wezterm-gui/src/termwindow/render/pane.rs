@@ -3,9 +3,10 @@ use crate::selection::SelectionRange;
 use crate::termwindow::box_model::*;
 use crate::termwindow::render::{
     same_hyperlink, CursorProperties, LineQuadCacheKey, LineQuadCacheValue, LineToEleShapeCacheKey,
-    RenderScreenLineParams,
+    LineTransform, RenderScreenLineParams,
 };
 use crate::termwindow::{ScrollHit, UIItem, UIItemType};
+use crate::utilsprites::RenderMetrics;
 use ::window::bitmaps::TextureRect;
 use ::window::DeadKeyStatus;
 use anyhow::Context;
@@ -14,8 +15,10 @@ use mux::pane::{PaneId, WithPaneLines};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
 use mux::tab::PositionedPane;
 use ordered_float::NotNan;
+use std::rc::Rc;
 use std::time::Instant;
 use wezterm_dynamic::Value;
+use wezterm_font::FontConfiguration;
 use wezterm_term::color::{ColorAttribute, ColorPalette};
 use wezterm_term::{Line, StableRowIndex};
 use window::color::LinearRgba;
@@ -85,6 +88,11 @@ impl crate::TermWindow {
         let pane_id = pos.pane.pane_id();
         let current_viewport = self.get_viewport(pane_id);
         let dims = pos.pane.get_dimensions();
+        let pane_fonts = self.pane_fonts(pane_id);
+        let render_metrics = pane_fonts
+            .as_ref()
+            .map(|f| f.render_metrics)
+            .unwrap_or(self.render_metrics);
 
         let gl_state = self.render_state.as_ref().unwrap();
 
@@ -94,7 +102,7 @@ impl crate::TermWindow {
         let filled_box = gl_state.util_sprites.filled_box.texture_coords();
 
         let window_is_transparent =
-            !self.window_background.is_empty() || config.window_background_opacity != 1.0;
+            !self.window_background.is_empty() || self.window_background_opacity() != 1.0;
 
         let default_bg = palette
             .resolve_bg(ColorAttribute::Default)
@@ -162,7 +170,7 @@ impl crate::TermWindow {
                     palette
                         .background
                         .to_linear()
-                        .mul_alpha(config.window_background_opacity),
+                        .mul_alpha(self.window_background_opacity()),
                 )
                 .context("filled_rectangle")?;
             quad.set_hsv(if pos.is_active {
@@ -198,7 +206,7 @@ impl crate::TermWindow {
                     let (r1, g1, b1, a) = palette
                         .background
                         .to_linear()
-                        .mul_alpha(config.window_background_opacity)
+                        .mul_alpha(self.window_background_opacity())
                         .tuple();
                     LinearRgba::with_components(
                         r1 + (r - r1) * intensity,
@@ -302,10 +310,17 @@ impl crate::TermWindow {
         let cursor_is_default_color =
             palette.cursor_fg == global_cursor_fg && palette.cursor_bg == global_cursor_bg;
 
+        let scrollback_split = self.scrollback_split(pane_id, &dims);
+
         {
-            let stable_range = match current_viewport {
-                Some(top) => top..top + dims.viewport_rows as StableRowIndex,
-                None => dims.physical_top..dims.physical_top + dims.viewport_rows as StableRowIndex,
+            let stable_range = match (current_viewport, &scrollback_split) {
+                (_, Some(split)) => {
+                    split.frozen_top..split.frozen_top + split.frozen_rows as StableRowIndex
+                }
+                (Some(top), None) => top..top + dims.viewport_rows as StableRowIndex,
+                (None, None) => {
+                    dims.physical_top..dims.physical_top + dims.viewport_rows as StableRowIndex
+                }
             };
 
             pos.pane
@@ -316,6 +331,8 @@ impl crate::TermWindow {
                 selrange: Option<SelectionRange>,
                 rectangular: bool,
                 dims: RenderableDimensions,
+                fonts: Option<Rc<FontConfiguration>>,
+                render_metrics: RenderMetrics,
                 top_pixel_y: f32,
                 left_pixel_x: f32,
                 pos: &'a PositionedPane,
@@ -335,6 +352,8 @@ impl crate::TermWindow {
                 window_is_transparent: bool,
                 layers: &'a mut TripleLayerQuadAllocator<'b>,
                 error: Option<anyhow::Error>,
+                /// The row of the pane at which the lines are rendered
+                row_offset: usize,
             }
 
             let left_pixel_x = padding_left
@@ -346,6 +365,8 @@ impl crate::TermWindow {
                 selrange,
                 rectangular,
                 dims,
+                fonts: pane_fonts.map(|f| Rc::clone(&f.fonts)),
+                render_metrics,
                 top_pixel_y,
                 left_pixel_x,
                 pos,
@@ -365,16 +386,31 @@ impl crate::TermWindow {
                 window_is_transparent,
                 layers,
                 error: None,
+                row_offset: 0,
             };
 
             impl<'a, 'b> LineRender<'a, 'b> {
                 fn render_line(
                     &mut self,
-                    stable_top: StableRowIndex,
+                    stable_row: StableRowIndex,
                     line_idx: usize,
                     line: &&mut Line,
                 ) -> anyhow::Result<()> {
-                    let stable_row = stable_top + line_idx as StableRowIndex;
+                    let transform = LineTransform {
+                        highlight: self.term_window.config.suspicious_character_highlight,
+                        show_invisible_characters: self
+                            .term_window
+                            .config
+                            .show_invisible_characters,
+                        show_whitespace: if self.term_window.config.show_whitespace {
+                            Some(self.dims.cols)
+                        } else {
+                            None
+                        },
+                    };
+                    let transformed = self.term_window.transformed_line(line, transform);
+                    let line: &Line = transformed.as_deref().unwrap_or(&**line);
+
                     let selrange = self
                         .selrange
                         .map_or(0..0, |sel| sel.cols_for_row(stable_row, self.rectangular));
@@ -435,8 +471,9 @@ impl crate::TermWindow {
                         cursor,
                         shape_hash,
                         top_pixel_y: NotNan::new(self.top_pixel_y).unwrap()
-                            + (line_idx + self.pos.top) as f32
-                                * self.term_window.render_metrics.cell_size.height as f32,
+                            + self.pos.top as f32
+                                * self.term_window.render_metrics.cell_size.height as f32
+                            + line_idx as f32 * self.render_metrics.cell_size.height as f32,
                         left_pixel_x: NotNan::new(self.left_pixel_x).unwrap(),
                         phys_line_idx: line_idx,
                         reverse_video: self.dims.reverse_video,
@@ -473,6 +510,7 @@ impl crate::TermWindow {
                     let shape_key = LineToEleShapeCacheKey {
                         shape_hash,
                         shape_generation: quad_key.shape_generation,
+                        metric: (&self.render_metrics).into(),
                         composing: if self.cursor.y == stable_row && self.pos.is_active {
                             if let DeadKeyStatus::Composing(composing) =
                                 &self.term_window.dead_key_status
@@ -493,7 +531,7 @@ impl crate::TermWindow {
                                 top_pixel_y: *quad_key.top_pixel_y,
                                 left_pixel_x: self.left_pixel_x,
                                 pixel_width: self.dims.cols as f32
-                                    * self.term_window.render_metrics.cell_size.width as f32,
+                                    * self.render_metrics.cell_size.width as f32,
                                 stable_line_idx: Some(stable_row),
                                 line: &line,
                                 selection: selrange.clone(),
@@ -515,12 +553,13 @@ impl crate::TermWindow {
                                 window_is_transparent: self.window_is_transparent,
                                 default_bg: self.default_bg,
                                 font: None,
+                                fonts: self.fonts.clone(),
                                 style: None,
                                 use_pixel_positioning: self
                                     .term_window
                                     .config
                                     .experimental_pixel_positioning,
-                                render_metrics: self.term_window.render_metrics,
+                                render_metrics: self.render_metrics,
                                 shape_key: Some(shape_key),
                                 password_input,
                             },
@@ -558,7 +597,10 @@ impl crate::TermWindow {
             impl<'a, 'b> WithPaneLines for LineRender<'a, 'b> {
                 fn with_lines_mut(&mut self, stable_top: StableRowIndex, lines: &mut [&mut Line]) {
                     for (line_idx, line) in lines.iter().enumerate() {
-                        if let Err(err) = self.render_line(stable_top, line_idx, line) {
+                        let stable_row = stable_top + line_idx as StableRowIndex;
+                        if let Err(err) =
+                            self.render_line(stable_row, self.row_offset + line_idx, line)
+                        {
                             self.error.replace(err);
                             return;
                         }
@@ -570,6 +612,43 @@ impl crate::TermWindow {
             if let Some(error) = render.error.take() {
                 return Err(error).context("error while calling with_lines_mut");
             }
+
+            if let Some(split) = &scrollback_split {
+                // The live tail goes below the divider
+                let tail_range = split.tail_top..split.tail_top + split.tail_rows as StableRowIndex;
+                pos.pane.apply_hyperlinks(
+                    tail_range.clone(),
+                    &render.term_window.config.hyperlink_rules,
+                );
+                render.row_offset = split.divider_row() + 1;
+                pos.pane.with_lines_mut(tail_range, &mut render);
+                if let Some(error) = render.error.take() {
+                    return Err(error).context("error while calling with_lines_mut");
+                }
+            }
+        }
+
+        if let Some(split) = &scrollback_split {
+            let left_pixel_x = padding_left
+                + border.left.get() as f32
+                + (pos.left as f32 * self.render_metrics.cell_size.width as f32);
+            let divider_y = top_pixel_y
+                + (pos.top as f32 * cell_height)
+                + (split.divider_row() as f32 * render_metrics.cell_size.height as f32)
+                + (render_metrics.cell_size.height as f32 / 2.0);
+            let thickness = render_metrics.underline_height.max(1) as f32;
+            self.filled_rectangle(
+                layers,
+                2,
+                euclid::rect(
+                    left_pixel_x,
+                    divider_y - thickness / 2.0,
+                    dims.cols as f32 * render_metrics.cell_size.width as f32,
+                    thickness,
+                ),
+                palette.split.to_linear(),
+            )
+            .context("filled_rectangle")?;
         }
 
         /*
@@ -673,7 +752,7 @@ impl crate::TermWindow {
                     palette
                         .background
                         .to_linear()
-                        .mul_alpha(self.config.window_background_opacity)
+                        .mul_alpha(self.window_background_opacity())
                         .into()
                 } else {
                     InheritableColor::Inherited
@@ -7,13 +7,14 @@ use crate::termwindow::render::{
 use crate::termwindow::LineToElementShapeItem;
 use ::window::DeadKeyStatus;
 use anyhow::Context;
-use config::{HsbTransform, TextStyle};
+use config::{HsbTransform, NerdFontMapping, TextStyle};
 use std::ops::Range;
 use std::rc::Rc;
 use std::time::Instant;
-use termwiz::cell::{unicode_column_width, Blink};
+use termwiz::cell::{unicode_column_width, Blink, Cell};
 use termwiz::color::LinearRgba;
-use termwiz::surface::CursorShape;
+use termwiz::nerdfonts::{nerd_font_v2_to_v3, nerd_font_v3_to_v2};
+use termwiz::surface::{CursorShape, Line};
 use wezterm_bidi::Direction;
 use wezterm_term::color::ColorAttribute;
 use wezterm_term::CellAttributes;
@@ -38,6 +39,7 @@ impl crate::TermWindow {
         }
 
         let gl_state = self.render_state.as_ref().unwrap();
+        let fonts = params.fonts.as_ref().unwrap_or(&self.fonts);
 
         let num_cols = params.dims.cols;
 
@@ -150,6 +152,8 @@ impl crate::TermWindow {
                 window_is_transparent: params.window_is_transparent,
                 reverse_video: params.dims.reverse_video,
                 shape_key: &params.shape_key,
+                fonts,
+                render_metrics: &params.render_metrics,
             };
 
             let (shaped, invalidate_on_hover) = self.build_line_element_shape(params)?;
@@ -374,6 +378,7 @@ impl crate::TermWindow {
                             &TextStyle::default(),
                             &attrs,
                             params.font.as_ref(),
+                            fonts,
                             gl_state,
                             &params.render_metrics,
                         )
@@ -729,16 +734,18 @@ impl crate::TermWindow {
         } else {
             None
         };
+        let mapped_line = map_nerd_font_symbols(params.line, params.config.nerd_font_mapping);
+        let source_line = mapped_line.as_ref().unwrap_or(params.line);
         let cell_clusters = if let Some((cursor_x, composing)) =
             params.shape_key.as_ref().and_then(|k| k.composing.as_ref())
         {
             // Create an updated line with the composition overlaid
-            let mut line = params.line.clone();
+            let mut line = source_line.clone();
             let seqno = line.current_seqno();
             line.overlay_text_with_attribute(*cursor_x, &composing, CellAttributes::blank(), seqno);
             line.cluster(bidi_hint)
         } else {
-            params.line.cluster(bidi_hint)
+            source_line.cluster(bidi_hint)
         };
 
         let gl_state = self.render_state.as_ref().unwrap();
@@ -752,7 +759,7 @@ impl crate::TermWindow {
             if !matches!(last_style.as_ref(), Some(ClusterStyleCache{attrs,..}) if *attrs == &cluster.attrs)
             {
                 let attrs = &cluster.attrs;
-                let style = self.fonts.match_style(params.config, attrs);
+                let style = params.fonts.match_style(params.config, attrs);
                 let hyperlink = attrs.hyperlink();
                 let is_highlited_hyperlink =
                     same_hyperlink(hyperlink, self.current_highlight.as_ref());
@@ -768,7 +775,7 @@ impl crate::TermWindow {
                         attrs.strikethrough(),
                         attrs.underline(),
                         attrs.overline(),
-                        &self.render_metrics,
+                        params.render_metrics,
                     )?
                     .texture_coords();
                 let bg_is_default = attrs.background() == ColorAttribute::Default;
@@ -792,6 +799,10 @@ impl crate::TermWindow {
                         bg_default = false;
                     }
 
+                    if let Some(ratio) = self.minimum_contrast_ratio() {
+                        fg = fg.ensure_contrast(bg, ratio);
+                    }
+
                     // Check for blink, and if this is the "not-visible"
                     // part of blinking then set fg = bg.  This is a cheap
                     // means of getting it done without impacting other
@@ -862,7 +873,8 @@ impl crate::TermWindow {
                 &cluster,
                 &gl_state,
                 None,
-                &self.render_metrics,
+                params.fonts,
+                params.render_metrics,
             )?;
             let pixel_width = glyph_info
                 .iter()
@@ -906,3 +918,37 @@ impl crate::TermWindow {
         Ok((shaped, invalidate_on_hover_change))
     }
 }
+
+/// Returns a copy of `line` in which the Nerd Font symbols whose
+/// codepoints changed between Nerd Fonts v2 and v3 have been translated
+/// as specified by `mapping`, or None if there is nothing to translate
+fn map_nerd_font_symbols(line: &Line, mapping: NerdFontMapping) -> Option<Line> {
+    let map: fn(char) -> Option<char> = match mapping {
+        NerdFontMapping::None => return None,
+        NerdFontMapping::ToV3 => nerd_font_v2_to_v3,
+        NerdFontMapping::ToV2 => nerd_font_v3_to_v2,
+    };
+    let mut mapped: Option<Line> = None;
+    for cell in line.visible_cells() {
+        let mut chars = cell.str().chars();
+        let c = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => continue,
+        };
+        if let Some(replacement) = map(c) {
+            let mapped = mapped.get_or_insert_with(|| line.clone());
+            let seqno = mapped.current_seqno();
+            let mut text = [0u8; 4];
+            mapped.set_cell(
+                cell.cell_index(),
+                Cell::new_grapheme_with_width(
+                    replacement.encode_utf8(&mut text),
+                    cell.width(),
+                    cell.attrs().clone(),
+                ),
+                seqno,
+            );
+        }
+    }
+    mapped
+}
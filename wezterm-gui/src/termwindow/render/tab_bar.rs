@@ -38,7 +38,7 @@ impl crate::TermWindow {
         ));
 
         let window_is_transparent =
-            !self.window_background.is_empty() || self.config.window_background_opacity != 1.0;
+            !self.window_background.is_empty() || self.window_background_opacity() != 1.0;
         let gl_state = self.render_state.as_ref().unwrap();
         let white_space = gl_state.util_sprites.white_space.texture_coords();
         let filled_box = gl_state.util_sprites.filled_box.texture_coords();
@@ -89,6 +89,7 @@ impl crate::TermWindow {
                 default_bg,
                 style: None,
                 font: None,
+                fonts: None,
                 use_pixel_positioning: self.config.experimental_pixel_positioning,
                 render_metrics: self.render_metrics,
                 shape_key: None,
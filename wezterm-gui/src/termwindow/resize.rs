@@ -1,7 +1,9 @@
 use crate::resize_increment_calculator::ResizeIncrementCalculator;
 use crate::utilsprites::RenderMetrics;
-use ::window::{Dimensions, ResizeIncrement, Window, WindowOps, WindowState};
+use ::window::{Dimensions, ResizeIncrement, Size, Window, WindowOps, WindowState};
 use config::{ConfigHandle, DimensionContext};
+use mux::pane::PaneId;
+use mux::tab::PositionedPane;
 use mux::Mux;
 use std::rc::Rc;
 use wezterm_font::FontConfiguration;
@@ -13,12 +15,70 @@ pub struct RowsAndCols {
     pub cols: usize,
 }
 
+/// The fonts used by a pane whose font size has been adjusted
+/// independently of the rest of the window
+pub struct PaneFonts {
+    /// Scale relative to the font scale of the window
+    pub scale: f64,
+    pub fonts: Rc<FontConfiguration>,
+    pub render_metrics: RenderMetrics,
+}
+
 #[derive(Debug)]
 pub enum ScaleChange {
     Absolute(f64),
     Relative(f64),
 }
 
+impl ScaleChange {
+    /// Computes the pane font scale that results from applying this
+    /// change to the `current` pane font scale, given the `font_size`
+    /// that the window is using.  A scale that is close to 1.0 snaps
+    /// to exactly 1.0 so that the pane goes back to the window fonts.
+    /// Returns None if the resulting font would be unreasonably small.
+    fn pane_font_scale(&self, current: f64, font_size: f64, dpi: usize) -> Option<f64> {
+        let scale = match self {
+            Self::Relative(change) => current * change,
+            Self::Absolute(scale) => *scale,
+        };
+        if (scale - 1.0).abs() < 0.001 {
+            return Some(1.0);
+        }
+        if font_size * scale * dpi as f64 / 72.0 < 2.0 {
+            return None;
+        }
+        Some(scale)
+    }
+}
+
+/// Computes the terminal size for a pane that is `width` x `height`
+/// window cells in the layout, but which renders cells that are
+/// `pane_cell` pixels in size; as many of those as fit are used,
+/// with a minimum of one row and column.
+fn pane_terminal_size(
+    width: usize,
+    height: usize,
+    window_cell: Size,
+    pane_cell: Size,
+    dpi: usize,
+) -> TerminalSize {
+    let cell_width = window_cell.width as usize;
+    let cell_height = window_cell.height as usize;
+    let pane_cell_width = pane_cell.width as usize;
+    let pane_cell_height = pane_cell.height as usize;
+
+    let cols = ((width * cell_width) / pane_cell_width).max(1);
+    let rows = ((height * cell_height) / pane_cell_height).max(1);
+
+    TerminalSize {
+        rows,
+        cols,
+        pixel_width: cols * pane_cell_width,
+        pixel_height: rows * pane_cell_height,
+        dpi: dpi as u32,
+    }
+}
+
 impl super::TermWindow {
     pub fn resize(
         &mut self,
@@ -124,6 +184,7 @@ impl super::TermWindow {
         if let Err(err) = self.recreate_texture_atlas(None) {
             log::error!("recreate_texture_atlas: {:#}", err);
         }
+        self.reload_pane_fonts();
         self.invalidate_fancy_tab_bar();
         self.invalidate_modal();
     }
@@ -298,6 +359,7 @@ impl super::TermWindow {
                 tab.resize(size);
             }
         };
+        self.apply_pane_font_sizes();
         self.resize_overlays();
         self.invalidate_fancy_tab_bar();
         self.update_title();
@@ -472,6 +534,161 @@ impl super::TermWindow {
         self.apply_pending_scale_changes();
     }
 
+    pub fn load_pane_fonts(&self, scale: f64) -> anyhow::Result<PaneFonts> {
+        let dpi = self.dimensions.dpi;
+        let fonts = Rc::new(FontConfiguration::new(Some(self.config.clone()), dpi)?);
+        fonts.change_scaling(self.fonts.get_font_scale() * scale, dpi);
+        let render_metrics = RenderMetrics::new(&fonts)?;
+        Ok(PaneFonts {
+            scale,
+            fonts,
+            render_metrics,
+        })
+    }
+
+    /// Returns the fonts for the pane, if its font size has been
+    /// adjusted independently of the window
+    pub fn pane_fonts(&self, pane_id: PaneId) -> Option<Rc<PaneFonts>> {
+        self.pane_state
+            .borrow()
+            .get(&pane_id)
+            .and_then(|state| state.fonts.clone())
+    }
+
+    /// Re-load the per-pane fonts; this is needed when the window
+    /// scale, dpi or font configuration has changed
+    fn reload_pane_fonts(&mut self) {
+        // The magnifier fonts are loaded again when it is next shown
+        self.magnifier_fonts.take();
+        let scales: Vec<(PaneId, f64)> = self
+            .pane_state
+            .borrow()
+            .iter()
+            .filter_map(|(pane_id, state)| state.fonts.as_ref().map(|f| (*pane_id, f.scale)))
+            .collect();
+        for (pane_id, scale) in scales {
+            let fonts = match self.load_pane_fonts(scale) {
+                Ok(fonts) => Some(Rc::new(fonts)),
+                Err(err) => {
+                    log::error!("{:#} while loading fonts for pane {}", err, pane_id);
+                    None
+                }
+            };
+            self.pane_state(pane_id).fonts = fonts;
+        }
+    }
+
+    /// Computes the terminal size for a pane, taking into account
+    /// its font size.  The layout of the splits is expressed in terms
+    /// of the window cell size, so a pane with a different font size
+    /// fits as many of its own cells as it can into the space that
+    /// is allocated to it.
+    fn terminal_size_for_pane(&self, pos: &PositionedPane) -> TerminalSize {
+        let metrics = match self.pane_fonts(pos.pane.pane_id()) {
+            Some(fonts) => fonts.render_metrics,
+            None => self.render_metrics,
+        };
+        pane_terminal_size(
+            pos.width,
+            pos.height,
+            self.render_metrics.cell_size,
+            metrics.cell_size,
+            self.dimensions.dpi,
+        )
+    }
+
+    /// Resize the panes that have their own font size so that they
+    /// reflow to fit the space allocated to them in the layout.
+    /// This needs to be called after the tabs have been resized.
+    pub fn apply_pane_font_sizes(&self) {
+        if self
+            .pane_state
+            .borrow()
+            .values()
+            .all(|state| state.fonts.is_none())
+        {
+            return;
+        }
+        let mux = Mux::get();
+        if let Some(window) = mux.get_window(self.mux_window_id) {
+            for tab in window.iter() {
+                for pos in tab.iter_panes() {
+                    if self.pane_fonts(pos.pane.pane_id()).is_some() {
+                        let size = self.terminal_size_for_pane(&pos);
+                        if let Err(err) = pos.pane.resize(size) {
+                            log::error!("resizing pane {}: {:#}", pos.pane.pane_id(), err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn decrease_pane_font_size(&mut self) {
+        self.adjust_pane_font_scale(ScaleChange::Relative(1.0 / 1.1));
+    }
+
+    pub fn increase_pane_font_size(&mut self) {
+        self.adjust_pane_font_scale(ScaleChange::Relative(1.1));
+    }
+
+    pub fn reset_pane_font_size(&mut self) {
+        self.adjust_pane_font_scale(ScaleChange::Absolute(1.0));
+    }
+
+    /// Change the font scale of the active pane, relative to the
+    /// font scale of the window
+    fn adjust_pane_font_scale(&mut self, change: ScaleChange) {
+        let pane = match self.get_active_pane_no_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+        let pane_id = pane.pane_id();
+        let current = self.pane_fonts(pane_id).map(|f| f.scale).unwrap_or(1.0);
+        let font_size = self.config.font_size * self.fonts.get_font_scale();
+        let scale = match change.pane_font_scale(current, font_size, self.dimensions.dpi) {
+            Some(scale) => scale,
+            None => {
+                log::warn!(
+                    "refusing to go to an unreasonably small pane font scale \
+                     {current} with {change:?}"
+                );
+                return;
+            }
+        };
+
+        let fonts = if scale == 1.0 {
+            None
+        } else {
+            match self.load_pane_fonts(scale) {
+                Ok(fonts) => Some(Rc::new(fonts)),
+                Err(err) => {
+                    log::error!("{:#} while attempting to scale pane font to {}", err, scale);
+                    return;
+                }
+            }
+        };
+        self.pane_state(pane_id).fonts = fonts;
+        self.quad_generation += 1;
+
+        if let Some(tab) = Mux::get().get_active_tab_for_window(self.mux_window_id) {
+            if let Some(pos) = tab
+                .iter_panes()
+                .into_iter()
+                .find(|pos| pos.pane.pane_id() == pane_id)
+            {
+                let size = self.terminal_size_for_pane(&pos);
+                if let Err(err) = pane.resize(size) {
+                    log::error!("resizing pane {}: {:#}", pane_id, err);
+                }
+            }
+        }
+
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+
     pub fn set_window_size(&mut self, size: TerminalSize, window: &Window) -> anyhow::Result<()> {
         let config = &self.config;
         let fontconfig = Rc::new(FontConfiguration::new(
@@ -566,3 +783,90 @@ pub fn effective_right_padding(config: &ConfigHandle, context: DimensionContext)
         config.window_padding.right.evaluate_as_pixels(context) as usize
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pane_font_scale_changes() {
+        let change = ScaleChange::Relative(1.1);
+        let scale = change.pane_font_scale(1.0, 12.0, 96).unwrap();
+        assert!((scale - 1.1).abs() < 0.0001);
+
+        let change = ScaleChange::Relative(1.0 / 1.1);
+        let scale = change.pane_font_scale(1.1, 12.0, 96).unwrap();
+        assert_eq!(scale, 1.0, "snaps back to the window fonts");
+
+        let change = ScaleChange::Absolute(1.0);
+        assert_eq!(change.pane_font_scale(3.5, 12.0, 96), Some(1.0));
+
+        let change = ScaleChange::Absolute(2.0);
+        assert_eq!(change.pane_font_scale(0.5, 12.0, 96), Some(2.0));
+    }
+
+    #[test]
+    fn pane_font_scale_refuses_tiny_fonts() {
+        // 12pt at 96dpi is 16px; 0.1 of that is 1.6px
+        let change = ScaleChange::Absolute(0.1);
+        assert_eq!(change.pane_font_scale(1.0, 12.0, 96), None);
+
+        // 0.125 of 16px is exactly 2px, which is the smallest allowed
+        let change = ScaleChange::Absolute(0.125);
+        assert_eq!(change.pane_font_scale(1.0, 12.0, 96), Some(0.125));
+
+        // Shrinking repeatedly stops at the limit rather than
+        // going below it
+        let change = ScaleChange::Relative(1.0 / 1.1);
+        let mut scale = 1.0;
+        while let Some(next) = change.pane_font_scale(scale, 12.0, 96) {
+            scale = next;
+        }
+        assert!(12.0 * scale * 96.0 / 72.0 >= 2.0);
+
+        // Resetting is always possible, even when the window font
+        // is tiny
+        let change = ScaleChange::Absolute(1.0);
+        assert_eq!(change.pane_font_scale(4.0, 1.0, 72), Some(1.0));
+    }
+
+    #[test]
+    fn terminal_size_with_default_scale() {
+        let cell = Size::new(8, 16);
+        let size = pane_terminal_size(80, 24, cell, cell, 96);
+        assert_eq!(size.cols, 80);
+        assert_eq!(size.rows, 24);
+        assert_eq!(size.pixel_width, 640);
+        assert_eq!(size.pixel_height, 384);
+        assert_eq!(size.dpi, 96);
+    }
+
+    #[test]
+    fn terminal_size_with_larger_pane_font() {
+        // 80x24 window cells of 8x16 is 640x384 pixels, which fits
+        // 53x19 cells of 12x20, leaving the remainder unused
+        let size = pane_terminal_size(80, 24, Size::new(8, 16), Size::new(12, 20), 96);
+        assert_eq!(size.cols, 53);
+        assert_eq!(size.rows, 19);
+        assert_eq!(size.pixel_width, 636);
+        assert_eq!(size.pixel_height, 380);
+    }
+
+    #[test]
+    fn terminal_size_with_smaller_pane_font() {
+        let size = pane_terminal_size(80, 24, Size::new(8, 16), Size::new(4, 8), 96);
+        assert_eq!(size.cols, 160);
+        assert_eq!(size.rows, 48);
+        assert_eq!(size.pixel_width, 640);
+        assert_eq!(size.pixel_height, 384);
+    }
+
+    #[test]
+    fn terminal_size_is_at_least_one_cell() {
+        let size = pane_terminal_size(1, 1, Size::new(8, 16), Size::new(30, 60), 96);
+        assert_eq!(size.cols, 1);
+        assert_eq!(size.rows, 1);
+        assert_eq!(size.pixel_width, 30);
+        assert_eq!(size.pixel_height, 60);
+    }
+}
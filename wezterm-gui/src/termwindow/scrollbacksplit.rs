@@ -0,0 +1,144 @@
+//! A pane that has been scrolled back can have its view split so that
+//! the upper part shows the scrollback at the viewport position, frozen,
+//! while the lower part continues to follow the live output, much like
+//! tmux copy-mode with follow.
+//! The two parts are separated by a row that holds a divider.
+use crate::termwindow::TermWindow;
+use ::window::WindowOps;
+use mux::pane::{Pane, PaneId};
+use mux::renderable::RenderableDimensions;
+use std::sync::Arc;
+use wezterm_term::StableRowIndex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollbackSplit {
+    /// The first row of the frozen scrollback view
+    pub frozen_top: StableRowIndex,
+    pub frozen_rows: usize,
+    /// The first row of the live tail
+    pub tail_top: StableRowIndex,
+    pub tail_rows: usize,
+}
+
+impl ScrollbackSplit {
+    /// Computes the layout for a pane scrolled back to `viewport`.
+    /// The live tail takes up a third of the rows.
+    pub fn compute(viewport: StableRowIndex, dims: &RenderableDimensions) -> Option<Self> {
+        if dims.viewport_rows < 4 {
+            return None;
+        }
+        let tail_rows = (dims.viewport_rows / 3).max(1);
+        let frozen_rows = dims.viewport_rows - tail_rows - 1;
+        Some(Self {
+            frozen_top: viewport,
+            frozen_rows,
+            tail_top: dims.physical_top + (dims.viewport_rows - tail_rows) as StableRowIndex,
+            tail_rows,
+        })
+    }
+
+    /// The row of the pane, relative to its top, on which the divider
+    /// is drawn
+    pub fn divider_row(&self) -> usize {
+        self.frozen_rows
+    }
+
+    /// Maps a row of the pane, relative to its top, to the stable row
+    /// that is displayed there
+    pub fn stable_row(&self, row: i64) -> StableRowIndex {
+        if row < self.frozen_rows as i64 {
+            self.frozen_top + row as StableRowIndex
+        } else {
+            let tail_row = (row - self.frozen_rows as i64 - 1).max(0);
+            self.tail_top + tail_row as StableRowIndex
+        }
+    }
+}
+
+impl TermWindow {
+    /// Returns the layout of the split scrollback view for the pane,
+    /// if it is enabled and the pane is scrolled back
+    pub fn scrollback_split(
+        &self,
+        pane_id: PaneId,
+        dims: &RenderableDimensions,
+    ) -> Option<ScrollbackSplit> {
+        let state = self.pane_state(pane_id);
+        if !state.scrollback_split {
+            return None;
+        }
+        ScrollbackSplit::compute(state.viewport?, dims)
+    }
+
+    /// Maps a row of the pane, relative to its top, to the stable row
+    /// that is displayed there, taking the split scrollback view into
+    /// account
+    pub fn pane_row_to_stable_row(
+        &self,
+        pane_id: PaneId,
+        dims: &RenderableDimensions,
+        row: i64,
+    ) -> StableRowIndex {
+        match self.scrollback_split(pane_id, dims) {
+            Some(split) => split.stable_row(row),
+            None => self.get_viewport(pane_id).unwrap_or(dims.physical_top) + row as StableRowIndex,
+        }
+    }
+
+    pub fn toggle_scrollback_split(&mut self, pane: &Arc<dyn Pane>) {
+        let pane_id = pane.pane_id();
+        let enable = {
+            let mut state = self.pane_state(pane_id);
+            state.scrollback_split = !state.scrollback_split;
+            state.scrollback_split
+        };
+        let dims = pane.get_dimensions();
+        if enable && self.get_viewport(pane_id).is_none() {
+            // Start out showing the rows just above the screen, so that
+            // enabling the split has a visible effect
+            if let Some(split) = ScrollbackSplit::compute(dims.physical_top, &dims) {
+                let top = dims.physical_top - split.frozen_rows as StableRowIndex;
+                self.set_viewport(pane_id, Some(top), dims);
+            }
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let dims = RenderableDimensions {
+            cols: 80,
+            viewport_rows: 24,
+            scrollback_rows: 1000,
+            physical_top: 976,
+            scrollback_top: 0,
+            dpi: 96,
+            pixel_width: 800,
+            pixel_height: 600,
+            reverse_video: false,
+        };
+        let split = ScrollbackSplit::compute(100, &dims).unwrap();
+        assert_eq!(
+            split,
+            ScrollbackSplit {
+                frozen_top: 100,
+                frozen_rows: 15,
+                tail_top: 992,
+                tail_rows: 8,
+            }
+        );
+        assert_eq!(split.stable_row(0), 100);
+        assert_eq!(split.stable_row(14), 114);
+        // The divider maps to the top of the tail
+        assert_eq!(split.stable_row(15), 992);
+        assert_eq!(split.stable_row(16), 992);
+        assert_eq!(split.stable_row(23), 999);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::overlay::quickselect::quick_select_patterns;
 use crate::selection::{Selection, SelectionCoordinate, SelectionMode, SelectionRange, SelectionX};
 use ::window::WindowOps;
 use mux::pane::{Pane, PaneId};
+use regex::Regex;
 use std::cell::RefMut;
 use std::sync::Arc;
 use termwiz::surface::Line;
@@ -240,6 +242,42 @@ impl super::TermWindow {
         self.window.as_ref().unwrap().invalidate();
     }
 
+    /// Extends the selection, or the cell under the mouse cursor if
+    /// there is none, to the smallest enclosing unit that quick select
+    /// would match, quoted string or bracketed region
+    pub fn extend_selection_to_semantic_unit(&mut self, pane: &Arc<dyn Pane>) {
+        let range = match self.selection(pane.pane_id()).range {
+            Some(range) => range,
+            None => match self.pane_state(pane.pane_id()).mouse_terminal_coords {
+                Some((position, y)) => {
+                    SelectionRange::start(SelectionCoordinate::x_y(position.column, y))
+                }
+                None => return,
+            },
+        };
+
+        let patterns: Vec<Regex> = quick_select_patterns(&self.config)
+            .into_iter()
+            .filter_map(|p| match Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(err) => {
+                    log::error!("invalid quick select pattern {p:?}: {err:#}");
+                    None
+                }
+            })
+            .collect();
+        let selection_range = match range.semantic_unit_around(&**pane, &patterns) {
+            Some(selection_range) => selection_range,
+            None => return,
+        };
+
+        self.selection(pane.pane_id()).origin = Some(selection_range.start);
+        self.selection(pane.pane_id()).range = Some(selection_range);
+        self.selection(pane.pane_id()).rectangular = false;
+        self.selection(pane.pane_id()).seqno = pane.get_current_seqno();
+        self.window.as_ref().unwrap().invalidate();
+    }
+
     pub fn select_text_at_mouse_cursor(&mut self, mode: SelectionMode, pane: &Arc<dyn Pane>) {
         let (x, y) = match self.pane_state(pane.pane_id()).mouse_terminal_coords {
             Some(coords) => (coords.0.column, coords.1),
@@ -16,6 +16,8 @@ pub struct ShaderUniform {
     pub foreground_text_hsb: [f32; 3],
     pub milliseconds: u32,
     pub projection: [[f32; 4]; 4],
+    pub window_hsb: [f32; 3],
+    pub _padding: u32,
     // sampler2D atlas_nearest_sampler;
     // sampler2D atlas_linear_sampler;
 }
@@ -331,6 +333,13 @@ impl WebGpuState {
             )
             .await?;
 
+        // Errors are fatal, just as with the default handler, but we
+        // want to record a crash report with the specifics first
+        device.on_uncaptured_error(Box::new(|err| {
+            crate::crashreport::report_gpu_failure(&err.to_string());
+            panic!("wgpu error: {err}");
+        }));
+
         let queue = Arc::new(queue);
 
         // Explicitly request an SRGB format, if available
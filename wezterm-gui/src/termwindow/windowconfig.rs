@@ -0,0 +1,89 @@
+//! Implements `wezterm cli get-config` and `wezterm cli set-config`,
+//! which read the effective configuration of a window and override
+//! a subset of its options at runtime.  The overrides are merged into
+//! those set via `window:set_config_overrides`, so they can also be
+//! seen and adjusted from lua.
+//! `wezterm cli set-config-profile` is also handled here.
+use crate::termwindow::{TermWindow, TermWindowNotif};
+use ::window::WindowOps;
+use anyhow::{anyhow, Context};
+use config::meta::{check_runtime_option, config_option_value, parse_runtime_override};
+use mux::window::WindowId as MuxWindowId;
+use mux::windowconfig::WindowConfigRequest;
+use wezterm_dynamic::Value;
+
+impl TermWindow {
+    /// Overrides the option `key` with `value` for this window, or
+    /// removes the override if `value` is None
+    fn set_config_override(&mut self, key: &str, value: Option<Value>) -> anyhow::Result<()> {
+        let mut overrides = match &self.config_overrides {
+            Value::Object(overrides) => overrides.clone(),
+            _ => Default::default(),
+        };
+        let name = Value::String(key.to_string());
+        match value {
+            Some(value) => {
+                overrides.insert(name, value);
+            }
+            None => {
+                overrides.remove(&name);
+            }
+        }
+        let overrides = Value::Object(overrides);
+
+        // config_was_reloaded can only log a bad value, so check it
+        // here in order to report it to the caller
+        config::overridden_config(&overrides)?;
+
+        if overrides != self.config_overrides {
+            self.config_overrides = overrides;
+            self.config_was_reloaded();
+        }
+        Ok(())
+    }
+}
+
+/// Handles a request for the configuration of the gui window
+/// that holds `window_id`, returning the effective value of the
+/// requested option as JSON
+pub async fn window_config(
+    window_id: MuxWindowId,
+    request: WindowConfigRequest,
+) -> anyhow::Result<String> {
+    let gui_win = crate::frontend::front_end()
+        .gui_window_for_mux_window(window_id)
+        .ok_or_else(|| anyhow!("window {window_id} is not a gui window"))?;
+
+    let (tx, rx) = smol::channel::bounded(1);
+    gui_win
+        .window
+        .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+            let result = (|| -> anyhow::Result<String> {
+                let key = match request {
+                    WindowConfigRequest::SetProfile { profile } => {
+                        term_window.set_config_profile(profile)?;
+                        return Ok(serde_json::to_string(&term_window.config_profile())?);
+                    }
+                    WindowConfigRequest::Get { key } => key,
+                    WindowConfigRequest::Set { key, value } => {
+                        let value = match value {
+                            Some(value) => Some(parse_runtime_override(&key, &value)?),
+                            None => {
+                                check_runtime_option(&key)?;
+                                None
+                            }
+                        };
+                        term_window.set_config_override(&key, value)?;
+                        key
+                    }
+                };
+                let value = config_option_value(&term_window.config, &key)?;
+                Ok(serde_json::to_string(&value)?)
+            })();
+            tx.try_send(result).ok();
+        })));
+
+    rx.recv()
+        .await
+        .context("waiting for the window to respond")?
+}
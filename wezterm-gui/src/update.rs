@@ -1,11 +1,16 @@
+//! The update checker periodically asks GitHub for the most recent
+//! release in the configured `update_channel`, and when that is newer
+//! than the running build, shows a banner and a toast notification.
+//! The notes of that release can then be read in an overlay via the
+//! `ShowReleaseNotes` action.
+//! Builds made without the `update-checker` feature, such as those of
+//! distributions that manage updates themselves, never contact GitHub,
+//! regardless of the configuration.
 use crate::ICON_DATA;
-use anyhow::anyhow;
-use config::{configuration, wezterm_version};
-use http_req::request::{HttpVersion, Request};
-use http_req::uri::Uri;
+use config::{configuration, wezterm_version, UpdateChannel};
 use mux::connui::ConnectionUI;
 use serde::*;
-use std::convert::TryFrom;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::time::Duration;
@@ -31,9 +36,19 @@ pub struct Asset {
     pub size: usize,
     pub url: String,
     pub browser_download_url: String,
+    /// When the asset was last uploaded; the nightly release is
+    /// updated in place, so this is how we can tell its age
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
+#[cfg(feature = "update-checker")]
 fn get_github_release_info(uri: &str) -> anyhow::Result<Release> {
+    use anyhow::anyhow;
+    use http_req::request::{HttpVersion, Request};
+    use http_req::uri::Uri;
+    use std::convert::TryFrom;
+
     let uri = Uri::try_from(uri)?;
 
     let mut latest = Vec::new();
@@ -52,13 +67,76 @@ fn get_github_release_info(uri: &str) -> anyhow::Result<Release> {
     Ok(latest)
 }
 
-pub fn get_latest_release_info() -> anyhow::Result<Release> {
-    get_github_release_info("https://api.github.com/repos/wez/wezterm/releases/latest")
+#[cfg(not(feature = "update-checker"))]
+fn get_github_release_info(_uri: &str) -> anyhow::Result<Release> {
+    anyhow::bail!("this build of wezterm was made without the update checker")
+}
+
+/// Returns true if this build is able to check for updates
+pub fn update_checker_available() -> bool {
+    cfg!(feature = "update-checker")
+}
+
+pub fn get_release_info(channel: UpdateChannel) -> anyhow::Result<Release> {
+    get_github_release_info(match channel {
+        UpdateChannel::Stable => "https://api.github.com/repos/wez/wezterm/releases/latest",
+        UpdateChannel::Nightly => "https://api.github.com/repos/wez/wezterm/releases/tags/nightly",
+    })
+}
+
+/// The file in which the most recent release info of `channel` is
+/// recorded; its modification time is the time of the last check
+fn release_info_file_name(channel: UpdateChannel) -> PathBuf {
+    config::DATA_DIR.join(match channel {
+        UpdateChannel::Stable => "check_update",
+        UpdateChannel::Nightly => "check_update_nightly",
+    })
+}
+
+/// Returns the release info that was recorded by the last check
+pub fn cached_release_info(channel: UpdateChannel) -> Option<Release> {
+    let data = std::fs::read(release_info_file_name(channel)).ok()?;
+    serde_json::from_slice(&data).ok()
 }
 
-#[allow(unused)]
-pub fn get_nightly_release_info() -> anyhow::Result<Release> {
-    get_github_release_info("https://api.github.com/repos/wez/wezterm/releases/tags/nightly")
+/// Returns true if `latest` is newer than the `current` build
+pub fn is_newer_release(channel: UpdateChannel, latest: &Release, current: &str) -> bool {
+    match channel {
+        UpdateChannel::Stable => latest.tag_name.as_str() > current,
+        UpdateChannel::Nightly => {
+            // The version of a build is the time of its commit,
+            // which precedes the upload of the nightly build made
+            // from it; allow for that so that a build doesn't
+            // consider itself to be out of date
+            let built = match current
+                .get(..15)
+                .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y%m%d-%H%M%S").ok())
+            {
+                Some(built) => built,
+                None => return false,
+            };
+            latest
+                .assets
+                .iter()
+                .filter_map(|asset| asset.updated_at.as_deref())
+                .filter_map(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.naive_utc())
+                .max()
+                .map(|published| published - built > chrono::Duration::days(1))
+                .unwrap_or(false)
+        }
+    }
+}
+
+/// Returns the url of the human readable notes for `release`
+pub fn release_notes_url(channel: UpdateChannel, release: &Release) -> String {
+    match channel {
+        UpdateChannel::Stable => format!(
+            "https://wezfurlong.org/wezterm/changelog.html#{}",
+            release.tag_name
+        ),
+        UpdateChannel::Nightly => release.html_url.clone(),
+    }
 }
 
 lazy_static::lazy_static! {
@@ -66,33 +144,24 @@ lazy_static::lazy_static! {
 }
 
 pub fn load_last_release_info_and_set_banner() {
-    if !configuration().check_for_updates {
+    let config = configuration();
+    if !config.check_for_updates || !update_checker_available() {
         return;
     }
 
-    let update_file_name = config::DATA_DIR.join("check_update");
-    if let Ok(data) = std::fs::read(update_file_name) {
-        let latest: Release = match serde_json::from_slice(&data) {
-            Ok(d) => d,
-            Err(_) => return,
-        };
-
-        let current = wezterm_version();
+    if let Some(latest) = cached_release_info(config.update_channel) {
         let force_ui = std::env::var_os("WEZTERM_ALWAYS_SHOW_UPDATE_UI").is_some();
-        if latest.tag_name.as_str() <= current && !force_ui {
+        if !is_newer_release(config.update_channel, &latest, wezterm_version()) && !force_ui {
             return;
         }
 
-        set_banner_from_release_info(&latest);
+        set_banner_from_release_info(config.update_channel, &latest);
     }
 }
 
-fn set_banner_from_release_info(latest: &Release) {
+fn set_banner_from_release_info(channel: UpdateChannel, latest: &Release) {
     let mux = crate::Mux::get();
-    let url = format!(
-        "https://wezfurlong.org/wezterm/changelog.html#{}",
-        latest.tag_name
-    );
+    let url = release_notes_url(channel, latest);
 
     let icon = ITermFileData {
         name: None,
@@ -131,15 +200,14 @@ fn set_banner_from_release_info(latest: &Release) {
     )));
 }
 
-fn schedule_set_banner_from_release_info(latest: &Release) {
-    let current = wezterm_version();
-    if latest.tag_name.as_str() <= current {
+fn schedule_set_banner_from_release_info(channel: UpdateChannel, latest: &Release) {
+    if !is_newer_release(channel, latest, wezterm_version()) {
         return;
     }
     promise::spawn::spawn_into_main_thread({
         let latest = latest.clone();
         async move {
-            set_banner_from_release_info(&latest);
+            set_banner_from_release_info(channel, &latest);
         }
     })
     .detach();
@@ -156,7 +224,7 @@ fn update_checker() {
 
     let force_ui = std::env::var_os("WEZTERM_ALWAYS_SHOW_UPDATE_UI").is_some();
 
-    let update_file_name = config::DATA_DIR.join("check_update");
+    let update_file_name = release_info_file_name(configuration().update_channel);
     let delay = update_file_name
         .metadata()
         .and_then(|metadata| metadata.modified())
@@ -172,6 +240,11 @@ fn update_checker() {
     let my_sock = config::RUNTIME_DIR.join(format!("gui-sock-{}", unsafe { libc::getpid() }));
 
     loop {
+        // Defer the check until someone is around to see the result
+        while crate::screenlock::is_screen_locked() {
+            std::thread::sleep(Duration::from_secs(60));
+        }
+
         // Figure out which other wezterm-guis are running.
         // We have a little "consensus protocol" to decide which
         // of us will show the toast notification or show the update
@@ -180,21 +253,21 @@ fn update_checker() {
         // running, we don't spam the user with a lot of notifications.
         let socks = wezterm_client::discovery::discover_gui_socks();
 
-        if configuration().check_for_updates {
-            if let Ok(latest) = get_latest_release_info() {
-                schedule_set_banner_from_release_info(&latest);
+        let config = configuration();
+        if config.check_for_updates {
+            let channel = config.update_channel;
+            if let Ok(latest) = get_release_info(channel) {
+                schedule_set_banner_from_release_info(channel, &latest);
                 let current = wezterm_version();
-                if latest.tag_name.as_str() > current || force_ui {
+                if is_newer_release(channel, &latest, current) || force_ui {
                     log::info!(
-                        "latest release {} is newer than current build {}",
+                        "latest {:?} release {} is newer than current build {}",
+                        channel,
                         latest.tag_name,
                         current
                     );
 
-                    let url = format!(
-                        "https://wezfurlong.org/wezterm/changelog.html#{}",
-                        latest.tag_name
-                    );
+                    let url = release_notes_url(channel, &latest);
 
                     if force_ui || socks.is_empty() || socks[0] == my_sock {
                         persistent_toast_notification_with_click_to_open_url(
@@ -205,6 +278,7 @@ fn update_checker() {
                     }
                 }
 
+                let update_file_name = release_info_file_name(channel);
                 config::create_user_owned_dirs(update_file_name.parent().unwrap()).ok();
 
                 // Record the time of this check
@@ -226,6 +300,9 @@ fn update_checker() {
 }
 
 pub fn start_update_checker() {
+    if !update_checker_available() {
+        return;
+    }
     static CHECKER_STARTED: AtomicBool = AtomicBool::new(false);
     if let Ok(false) =
         CHECKER_STARTED.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
@@ -0,0 +1,104 @@
+//! Marks the whitespace of the output of a pane for `show_whitespace`:
+//! trailing spaces, the tabs that the terminal recorded with `TabSpan`
+//! and the carriage returns that weren't followed by a line feed.
+use termwiz::cell::{Cell, CellAttributes, Intensity, TabSpan};
+use termwiz::color::ColorAttribute;
+use wezterm_term::Line;
+
+/// Returns true if a cell holding `text` is a space that was written
+/// as part of the text, rather than one that paints a background
+fn is_plain_space(text: &str, attrs: &CellAttributes) -> bool {
+    text == " " && attrs.background() == ColorAttribute::Default && !attrs.reverse()
+}
+
+/// Returns a copy of `line` in which tabs are shown as dim arrows,
+/// trailing spaces as dim dots and a carriage return that wasn't
+/// followed by a line feed as a dim `␍` after the text, or None if
+/// there is nothing to show.  `cols` is the width of the pane; the `␍`
+/// is left out when the line is full.
+pub fn show_whitespace(line: &Line, cols: usize) -> Option<Line> {
+    let mut marks = vec![];
+    // The column that follows the last cell that holds text
+    let mut text_end = 0;
+    for cell in line.visible_cells() {
+        if cell.attrs().tab_span() == TabSpan::Start {
+            marks.push((cell.cell_index(), "→", cell.attrs().clone()));
+        }
+        if cell.str() != " " {
+            text_end = cell.cell_index() + cell.width();
+        }
+    }
+
+    // The trailing whitespace ends at the first cell that paints a
+    // background.  Lines without text are left alone, as programs
+    // often clear the screen by writing spaces.
+    let mut whitespace_end = text_end;
+    for cell in line
+        .visible_cells()
+        .skip_while(|cell| cell.cell_index() < text_end)
+    {
+        let attrs = cell.attrs();
+        if attrs.tab_span() == TabSpan::None {
+            if !is_plain_space(cell.str(), attrs) {
+                break;
+            }
+            if text_end > 0 {
+                marks.push((cell.cell_index(), "·", attrs.clone()));
+            }
+        }
+        whitespace_end = cell.cell_index() + 1;
+    }
+
+    if line.has_bare_carriage_return() && whitespace_end < cols {
+        let attrs = line
+            .get_cell(whitespace_end)
+            .map(|cell| cell.attrs().clone())
+            .unwrap_or_default();
+        marks.push((whitespace_end, "␍", attrs));
+    }
+
+    if marks.is_empty() {
+        return None;
+    }
+
+    let mut shown = line.clone();
+    // The copy would otherwise share the cached shaping of `line`
+    shown.clear_appdata();
+    let seqno = shown.current_seqno();
+    for (idx, text, mut attrs) in marks {
+        attrs.set_intensity(Intensity::Half);
+        shown.set_cell(idx, Cell::new_grapheme_with_width(text, 1, attrs), seqno);
+    }
+    Some(shown)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn show_line() {
+        let mut line = Line::from_text("a b", &CellAttributes::default(), SEQ_ZERO, None);
+        assert!(show_whitespace(&line, 10).is_none());
+
+        line.set_cell_grapheme(4, " ", 1, CellAttributes::default(), SEQ_ZERO);
+        let mut tab = CellAttributes::default();
+        tab.set_tab_span(TabSpan::Start);
+        line.set_cell(1, Cell::blank_with_attrs(tab), SEQ_ZERO);
+        let shown = show_whitespace(&line, 10).unwrap();
+        assert_eq!(shown.as_str(), "a→b··");
+        assert_eq!(
+            shown.get_cell(3).unwrap().attrs().intensity(),
+            Intensity::Half
+        );
+        assert_eq!(
+            shown.get_cell(2).unwrap().attrs().intensity(),
+            Intensity::Normal
+        );
+
+        line.set_bare_carriage_return(SEQ_ZERO);
+        assert_eq!(show_whitespace(&line, 10).unwrap().as_str(), "a→b··␍");
+        assert_eq!(show_whitespace(&line, 5).unwrap().as_str(), "a→b··");
+    }
+}
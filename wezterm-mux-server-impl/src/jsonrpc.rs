@@ -0,0 +1,642 @@
+//! A JSON-RPC 2.0 interface to the mux, for external tools such as
+//! window managers and automation utilities that want to control
+//! wezterm without speaking the binary protocol used by `wezterm cli`,
+//! or spawning a process for each command.
+//!
+//! Each line received on the socket is a request whose `method` is
+//! the name of a Pdu, such as `ListPanes` or `SpawnV2`, and whose
+//! `params` are the fields of that Pdu.  The `result` of the response
+//! holds the fields of the Pdu that was sent in reply.
+//! The `Subscribe` method causes mux events to be delivered to the
+//! client as JSON-RPC notifications.
+use crate::dispatch::AsRawDesc;
+use crate::local::safely_create_sock_path;
+use crate::sessionhandler::{PduSender, SessionHandler};
+use crate::UnixListener;
+use anyhow::{anyhow, Context};
+use codec::{DecodedPdu, Pdu};
+use config::{ConfigHandle, UnixDomain};
+use futures::FutureExt;
+use mux::pane::CachePolicy;
+use mux::{Mux, MuxNotification};
+use promise::spawn::spawn_into_main_thread;
+use serde_json::{json, Value};
+use smol::prelude::*;
+use smol::Async;
+use std::collections::HashMap;
+use std::path::Path;
+use wezterm_term::terminal::Alert;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const SERVER_ERROR: i64 = -32000;
+
+pub struct JsonRpcListener {
+    listener: UnixListener,
+}
+
+impl JsonRpcListener {
+    pub fn with_path(path: &Path) -> anyhow::Result<Self> {
+        let listener = safely_create_sock_path(&UnixDomain {
+            socket_path: Some(path.to_path_buf()),
+            ..Default::default()
+        })?;
+        Ok(Self { listener })
+    }
+
+    pub fn run(&mut self) {
+        for stream in self.listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    spawn_into_main_thread(async move {
+                        process(stream).await.map_err(|e| {
+                            log::error!("jsonrpc: {:#}", e);
+                            e
+                        })
+                    })
+                    .detach();
+                }
+                Err(err) => {
+                    log::error!("jsonrpc: accept failed: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Starts listening for JSON-RPC connections on the configured
+/// `rpc_socket_path`, if any
+pub fn spawn_rpc_listener(config: &ConfigHandle) -> anyhow::Result<()> {
+    if let Some(path) = &config.rpc_socket_path {
+        let mut listener = JsonRpcListener::with_path(path)
+            .with_context(|| format!("setting up rpc socket {}", path.display()))?;
+        std::env::set_var("WEZTERM_RPC_SOCKET", path);
+        std::thread::spawn(move || {
+            listener.run();
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+enum Item {
+    Notif(MuxNotification),
+    Response(DecodedPdu),
+    Readable,
+}
+
+/// Tracks the events that the client has subscribed to
+#[derive(Debug, PartialEq)]
+enum Subscription {
+    None,
+    All,
+    Events(Vec<String>),
+}
+
+impl Subscription {
+    fn wants(&self, event: &str) -> bool {
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::Events(events) => events.iter().any(|e| e == event),
+        }
+    }
+}
+
+/// What to do with a request received from the client
+#[derive(Debug)]
+enum Dispatch {
+    /// Send this message to the client
+    Reply(Value),
+    /// Pass the Pdu to the SessionHandler; its response is mapped
+    /// back to the request by `RpcSession::response`
+    Pdu(DecodedPdu),
+    /// Nothing is sent, such as for a notification that failed
+    Nothing,
+}
+
+/// The state of a connection that translates between JSON-RPC
+/// messages and Pdus, independent of the socket
+#[derive(Debug)]
+struct RpcSession {
+    /// JSON-RPC ids can be any JSON value, so we allocate our own
+    /// serial numbers for the Pdus and map the responses back
+    next_serial: u64,
+    ids: HashMap<u64, Option<Value>>,
+    subscription: Subscription,
+}
+
+impl Default for RpcSession {
+    fn default() -> Self {
+        Self {
+            next_serial: 1,
+            ids: HashMap::new(),
+            subscription: Subscription::None,
+        }
+    }
+}
+
+impl RpcSession {
+    fn is_subscribed(&self) -> bool {
+        self.subscription != Subscription::None
+    }
+
+    /// Processes a line received from the client
+    fn request(&mut self, line: &[u8]) -> Dispatch {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Dispatch::Nothing;
+        }
+        let request: Value = match serde_json::from_slice(line) {
+            Ok(request) => request,
+            Err(err) => {
+                return Dispatch::Reply(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("{err:#}"),
+                ))
+            }
+        };
+        let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => {
+                return Dispatch::Reply(error_response(
+                    id.unwrap_or(Value::Null),
+                    INVALID_REQUEST,
+                    "request has no method",
+                ))
+            }
+        };
+        let params = match request.get("params") {
+            Some(Value::Null) | None => json!({}),
+            Some(params) => params.clone(),
+        };
+
+        if method == "Subscribe" {
+            self.subscription = match params.get("events").and_then(Value::as_array) {
+                Some(events) => Subscription::Events(
+                    events
+                        .iter()
+                        .filter_map(|e| e.as_str().map(|s| s.to_string()))
+                        .collect(),
+                ),
+                None => Subscription::All,
+            };
+            return match id {
+                Some(id) => Dispatch::Reply(result_response(id, json!({}))),
+                None => Dispatch::Nothing,
+            };
+        }
+
+        match Pdu::deserialize_named(method, params) {
+            Ok(pdu) => {
+                let serial = self.next_serial;
+                self.next_serial += 1;
+                self.ids.insert(serial, id);
+                Dispatch::Pdu(DecodedPdu { pdu, serial })
+            }
+            Err(err) => match id {
+                Some(id) => {
+                    let code = if err.to_string().starts_with("unknown Pdu") {
+                        METHOD_NOT_FOUND
+                    } else {
+                        INVALID_REQUEST
+                    };
+                    Dispatch::Reply(error_response(id, code, &format!("{err:#}")))
+                }
+                None => Dispatch::Nothing,
+            },
+        }
+    }
+
+    /// Produces the message for the response to a Pdu that was
+    /// returned by `request`.
+    /// Responses to notification style requests, which have no id,
+    /// and unsolicited Pdus are not sent
+    fn response(&mut self, decoded: DecodedPdu) -> Option<Value> {
+        let id = self.ids.remove(&decoded.serial)??;
+        Some(match decoded.pdu {
+            Pdu::ErrorResponse(err) => error_response(id, SERVER_ERROR, &err.reason),
+            pdu => match pdu.serialize_payload(serde_json::value::Serializer) {
+                Ok(result) => result_response(id, result),
+                Err(err) => error_response(id, SERVER_ERROR, &format!("{err:#}")),
+            },
+        })
+    }
+
+    /// Produces the message for a mux notification, if the client
+    /// has subscribed to it
+    fn notification(&self, notif: MuxNotification) -> Option<Value> {
+        let (event, params) = notification_for(notif)?;
+        if !self.subscription.wants(event) {
+            return None;
+        }
+        Some(json!({
+            "jsonrpc": "2.0",
+            "method": event,
+            "params": params,
+        }))
+    }
+}
+
+pub async fn process<T>(stream: T) -> anyhow::Result<()>
+where
+    T: 'static,
+    T: std::io::Read,
+    T: std::io::Write,
+    T: AsRawDesc,
+    T: std::fmt::Debug,
+{
+    let mut stream = Async::new(stream)?;
+
+    let (item_tx, item_rx) = smol::channel::unbounded::<Item>();
+
+    let pdu_sender = PduSender::new({
+        let item_tx = item_tx.clone();
+        move |pdu| {
+            item_tx
+                .try_send(Item::Response(pdu))
+                .map_err(|e| anyhow!("{:?}", e))
+        }
+    });
+    let mut handler = SessionHandler::new(pdu_sender);
+    let mut session = RpcSession::default();
+    let mut buf = vec![];
+
+    loop {
+        let rx_msg = item_rx.recv();
+        let wait_for_read = stream.readable().map(|_| Ok(Item::Readable));
+
+        match smol::future::or(rx_msg, wait_for_read).await {
+            Ok(Item::Readable) => {
+                let mut chunk = [0u8; 4096];
+                let len = match stream.read(&mut chunk).await {
+                    Ok(0) => return Ok(()),
+                    Ok(len) => len,
+                    Err(err) => match err.kind() {
+                        std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::UnexpectedEof => {
+                            return Ok(())
+                        }
+                        _ => return Err(err).context("reading from rpc client"),
+                    },
+                };
+                buf.extend_from_slice(&chunk[..len]);
+
+                while let Some(idx) = buf.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=idx).collect();
+                    let was_subscribed = session.is_subscribed();
+                    match session.request(&line) {
+                        Dispatch::Reply(message) => write_message(&mut stream, &message).await?,
+                        Dispatch::Pdu(decoded) => handler.process_one(decoded),
+                        Dispatch::Nothing => {}
+                    }
+                    if !was_subscribed && session.is_subscribed() {
+                        let tx = item_tx.clone();
+                        Mux::get().subscribe(move |n| tx.try_send(Item::Notif(n)).is_ok());
+                    }
+                }
+            }
+            Ok(Item::Response(decoded)) => {
+                if let Some(message) = session.response(decoded) {
+                    write_message(&mut stream, &message).await?;
+                }
+            }
+            Ok(Item::Notif(notif)) => {
+                if let Some(message) = session.notification(notif) {
+                    write_message(&mut stream, &message).await?;
+                }
+            }
+            Err(err) => {
+                log::error!("jsonrpc: process Err {}", err);
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn result_response(id: Value, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+async fn write_message<T>(stream: &mut Async<T>, message: &Value) -> anyhow::Result<()>
+where
+    T: std::io::Write,
+{
+    let mut data = serde_json::to_vec(message)?;
+    data.push(b'\n');
+    stream
+        .write_all(&data)
+        .await
+        .context("writing to rpc client")?;
+    stream.flush().await.context("flushing rpc client")?;
+    Ok(())
+}
+
+/// Maps a mux notification to the name and parameters of the
+/// event that is sent to subscribed clients
+fn notification_for(notif: MuxNotification) -> Option<(&'static str, Value)> {
+    match notif {
+        MuxNotification::PaneAdded(pane_id) => Some(("pane-added", json!({ "pane_id": pane_id }))),
+        MuxNotification::PaneRemoved(pane_id) => {
+            Some(("pane-removed", json!({ "pane_id": pane_id })))
+        }
+        MuxNotification::PaneFocused(pane_id) => {
+            Some(("pane-focused", json!({ "pane_id": pane_id })))
+        }
+        MuxNotification::WindowCreated(window_id) => {
+            Some(("window-created", json!({ "window_id": window_id })))
+        }
+        MuxNotification::WindowRemoved(window_id) => {
+            Some(("window-removed", json!({ "window_id": window_id })))
+        }
+        MuxNotification::TabAddedToWindow { tab_id, window_id } => Some((
+            "tab-added",
+            json!({ "tab_id": tab_id, "window_id": window_id }),
+        )),
+        MuxNotification::TabTitleChanged { tab_id, title } => Some((
+            "tab-title-changed",
+            json!({ "tab_id": tab_id, "title": title }),
+        )),
+        MuxNotification::WindowTitleChanged { window_id, title } => Some((
+            "window-title-changed",
+            json!({ "window_id": window_id, "title": title }),
+        )),
+        MuxNotification::PaneTagsChanged { pane_id, tags } => Some((
+            "pane-tags-changed",
+            json!({ "pane_id": pane_id, "tags": tags }),
+        )),
+        MuxNotification::WorkspaceRenamed {
+            old_workspace,
+            new_workspace,
+        } => Some((
+            "workspace-renamed",
+            json!({ "old_workspace": old_workspace, "new_workspace": new_workspace }),
+        )),
+        MuxNotification::Alert { pane_id, alert } => match alert {
+            Alert::Bell => Some(("bell", json!({ "pane_id": pane_id }))),
+            Alert::WindowTitleChanged(_)
+            | Alert::IconTitleChanged(_)
+            | Alert::TabTitleChanged(_) => {
+                let title = Mux::get().get_pane(pane_id)?.get_title();
+                Some((
+                    "pane-title-changed",
+                    json!({ "pane_id": pane_id, "title": title }),
+                ))
+            }
+            Alert::CurrentWorkingDirectoryChanged => {
+                let cwd = Mux::get()
+                    .get_pane(pane_id)?
+                    .get_current_working_dir(CachePolicy::AllowStale)
+                    .map(|url| url.to_string());
+                Some(("cwd-changed", json!({ "pane_id": pane_id, "cwd": cwd })))
+            }
+            Alert::ToastNotification { title, body, .. } => Some((
+                "toast-notification",
+                json!({ "pane_id": pane_id, "title": title, "body": body }),
+            )),
+            Alert::SetUserVar { name, value } => Some((
+                "user-var-changed",
+                json!({ "pane_id": pane_id, "name": name, "value": value }),
+            )),
+            Alert::PrintRequested { data, more_follows } => Some((
+                "print-requested",
+                json!({ "pane_id": pane_id, "data": data, "more_follows": more_follows }),
+            )),
+            Alert::PaletteChanged | Alert::OutputSinceFocusLost => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::{ErrorResponse, UnitResponse};
+
+    fn pdu(dispatch: Dispatch) -> DecodedPdu {
+        match dispatch {
+            Dispatch::Pdu(decoded) => decoded,
+            other => panic!("expected a Pdu, got {other:?}"),
+        }
+    }
+
+    fn error_code(dispatch: Dispatch) -> (Value, i64) {
+        match dispatch {
+            Dispatch::Reply(message) => (
+                message["id"].clone(),
+                message["error"]["code"].as_i64().unwrap(),
+            ),
+            other => panic!("expected a reply, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn responses_are_mapped_to_request_ids() {
+        let mut session = RpcSession::default();
+        let first = pdu(session.request(br#"{"jsonrpc":"2.0","id":"a","method":"ListPanes"}"#));
+        assert!(matches!(first.pdu, Pdu::ListPanes(_)));
+        let second = pdu(session.request(
+            br#"{"jsonrpc":"2.0","id":7,"method":"SetPaneZoomed",
+                "params":{"containing_tab_id":1,"pane_id":2,"zoomed":true}}"#,
+        ));
+        assert!(matches!(
+            second.pdu,
+            Pdu::SetPaneZoomed(codec::SetPaneZoomed {
+                containing_tab_id: 1,
+                pane_id: 2,
+                zoomed: true,
+            })
+        ));
+        let notification = pdu(session.request(br#"{"jsonrpc":"2.0","method":"ListPanes"}"#));
+        assert_ne!(first.serial, second.serial);
+
+        // Responses can arrive in any order
+        assert_eq!(
+            session.response(DecodedPdu {
+                serial: second.serial,
+                pdu: Pdu::ErrorResponse(ErrorResponse {
+                    reason: "no such pane".to_string(),
+                }),
+            }),
+            Some(json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "error": {"code": SERVER_ERROR, "message": "no such pane"},
+            }))
+        );
+        assert_eq!(
+            session.response(DecodedPdu {
+                serial: first.serial,
+                pdu: Pdu::UnitResponse(UnitResponse {}),
+            }),
+            Some(json!({"jsonrpc": "2.0", "id": "a", "result": {}}))
+        );
+
+        // Requests without an id, repeated responses and unsolicited
+        // Pdus get no reply
+        for serial in [notification.serial, first.serial, 0] {
+            assert_eq!(
+                session.response(DecodedPdu {
+                    serial,
+                    pdu: Pdu::UnitResponse(UnitResponse {}),
+                }),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn request_errors() {
+        let mut session = RpcSession::default();
+        assert_eq!(
+            error_code(session.request(b"{not json}\n")),
+            (Value::Null, PARSE_ERROR)
+        );
+        assert_eq!(
+            error_code(session.request(br#"{"jsonrpc":"2.0","id":1}"#)),
+            (json!(1), INVALID_REQUEST)
+        );
+        assert_eq!(
+            error_code(session.request(br#"{"jsonrpc":"2.0","id":2,"method":"Frobnicate"}"#)),
+            (json!(2), METHOD_NOT_FOUND)
+        );
+        assert_eq!(
+            error_code(session.request(
+                br#"{"jsonrpc":"2.0","id":3,"method":"SetPaneZoomed","params":{"pane_id":"x"}}"#
+            )),
+            (json!(3), INVALID_REQUEST)
+        );
+
+        // Notifications that fail are ignored, as are empty lines
+        assert!(matches!(
+            session.request(br#"{"jsonrpc":"2.0","method":"Frobnicate"}"#),
+            Dispatch::Nothing
+        ));
+        assert!(matches!(session.request(b" \r\n"), Dispatch::Nothing));
+        assert!(session.ids.is_empty());
+    }
+
+    #[test]
+    fn subscription_filters_notifications() {
+        let bell = || MuxNotification::Alert {
+            pane_id: 3,
+            alert: Alert::Bell,
+        };
+
+        let mut session = RpcSession::default();
+        assert!(!session.is_subscribed());
+        assert_eq!(session.notification(bell()), None);
+
+        match session.request(
+            br#"{"jsonrpc":"2.0","id":1,"method":"Subscribe","params":{"events":["bell"]}}"#,
+        ) {
+            Dispatch::Reply(message) => {
+                assert_eq!(message, json!({"jsonrpc": "2.0", "id": 1, "result": {}}))
+            }
+            other => panic!("expected a reply, got {other:?}"),
+        }
+        assert!(session.is_subscribed());
+        assert_eq!(
+            session.notification(bell()),
+            Some(json!({"jsonrpc": "2.0", "method": "bell", "params": {"pane_id": 3}}))
+        );
+        assert_eq!(session.notification(MuxNotification::PaneAdded(3)), None);
+
+        assert!(matches!(
+            session.request(br#"{"jsonrpc":"2.0","method":"Subscribe"}"#),
+            Dispatch::Nothing
+        ));
+        assert_eq!(session.subscription, Subscription::All);
+        assert_eq!(
+            session.notification(MuxNotification::PaneAdded(3)),
+            Some(json!({"jsonrpc": "2.0", "method": "pane-added", "params": {"pane_id": 3}}))
+        );
+        // Notifications that have no event are never sent
+        assert_eq!(session.notification(MuxNotification::PaneOutput(3)), None);
+    }
+
+    #[test]
+    fn notifications() {
+        assert_eq!(
+            notification_for(MuxNotification::TabAddedToWindow {
+                tab_id: 1,
+                window_id: 2,
+            }),
+            Some(("tab-added", json!({"tab_id": 1, "window_id": 2})))
+        );
+        assert_eq!(
+            notification_for(MuxNotification::WorkspaceRenamed {
+                old_workspace: "default".to_string(),
+                new_workspace: "work".to_string(),
+            }),
+            Some((
+                "workspace-renamed",
+                json!({"old_workspace": "default", "new_workspace": "work"})
+            ))
+        );
+        assert_eq!(
+            notification_for(MuxNotification::PaneTagsChanged {
+                pane_id: 1,
+                tags: vec!["build".to_string()],
+            }),
+            Some((
+                "pane-tags-changed",
+                json!({"pane_id": 1, "tags": ["build"]})
+            ))
+        );
+        assert_eq!(
+            notification_for(MuxNotification::Alert {
+                pane_id: 1,
+                alert: Alert::SetUserVar {
+                    name: "foo".to_string(),
+                    value: "bar".to_string(),
+                },
+            }),
+            Some((
+                "user-var-changed",
+                json!({"pane_id": 1, "name": "foo", "value": "bar"})
+            ))
+        );
+        assert_eq!(
+            notification_for(MuxNotification::Alert {
+                pane_id: 1,
+                alert: Alert::PrintRequested {
+                    data: "hello".to_string(),
+                    more_follows: true,
+                },
+            }),
+            Some((
+                "print-requested",
+                json!({"pane_id": 1, "data": "hello", "more_follows": true})
+            ))
+        );
+        assert_eq!(
+            notification_for(MuxNotification::Alert {
+                pane_id: 1,
+                alert: Alert::PaletteChanged,
+            }),
+            None
+        );
+        assert_eq!(
+            notification_for(MuxNotification::WindowInvalidated(1)),
+            None
+        );
+    }
+}
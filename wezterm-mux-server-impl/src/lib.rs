@@ -11,6 +11,7 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use uds_windows::{UnixListener, UnixStream};
 
 pub mod dispatch;
+pub mod jsonrpc;
 pub mod local;
 pub mod pki;
 pub mod sessionhandler;
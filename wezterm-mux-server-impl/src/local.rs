@@ -42,7 +42,7 @@ impl LocalListener {
 /// we need to be sure that the directory that we create it in
 /// is owned by the user and has appropriate file permissions
 /// that prevent other users from manipulating its contents.
-fn safely_create_sock_path(unix_dom: &UnixDomain) -> anyhow::Result<UnixListener> {
+pub(crate) fn safely_create_sock_path(unix_dom: &UnixDomain) -> anyhow::Result<UnixListener> {
     let sock_path = &unix_dom.socket_path();
     log::trace!("setting up {}", sock_path.display());
 
@@ -7,6 +7,7 @@ use mux::domain::SplitSource;
 use mux::pane::{CachePolicy, Pane, PaneId};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
 use mux::tab::TabId;
+use mux::windowconfig::WindowConfigRequest;
 use mux::{Mux, MuxNotification};
 use promise::spawn::spawn_into_main_thread;
 use std::collections::HashMap;
@@ -424,6 +425,76 @@ impl SessionHandler {
                 })
                 .detach();
             }
+            Pdu::SendTextToPanes(SendTextToPanes {
+                match_domain,
+                match_tag,
+                pane_ids,
+                data,
+                paste,
+                dry_run,
+            }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let mux = Mux::get();
+                            let mut panes = vec![];
+                            for pane in mux.panes_matching(
+                                match_domain.as_deref(),
+                                match_tag.as_deref(),
+                                pane_ids.as_deref(),
+                            ) {
+                                let pane_id = pane.pane_id();
+                                if !dry_run {
+                                    let result = if paste {
+                                        pane.send_paste(&data)
+                                    } else {
+                                        pane.writer().write_all(data.as_bytes()).map_err(Into::into)
+                                    };
+                                    // Carry on with the other panes
+                                    if let Err(err) = result {
+                                        log::error!("sending text to pane {pane_id}: {err:#}");
+                                        continue;
+                                    }
+                                }
+                                let domain_name = mux
+                                    .get_domain(pane.domain_id())
+                                    .map(|domain| domain.domain_name().to_string())
+                                    .unwrap_or_default();
+                                panes.push(MatchedPane {
+                                    pane_id,
+                                    domain_name,
+                                    title: pane.get_title(),
+                                });
+                            }
+                            Ok(Pdu::SendTextToPanesResponse(SendTextToPanesResponse {
+                                panes,
+                            }))
+                        },
+                        send_response,
+                    );
+                })
+                .detach();
+            }
+            Pdu::SetUserVar(SetUserVar {
+                pane_id,
+                name,
+                value,
+            }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let mux = Mux::get();
+                            let pane = mux
+                                .get_pane(pane_id)
+                                .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
+                            pane.set_user_var(&name, &value)?;
+                            Ok(Pdu::UnitResponse(UnitResponse {}))
+                        },
+                        send_response,
+                    );
+                })
+                .detach();
+            }
             Pdu::EraseScrollbackRequest(EraseScrollbackRequest {
                 pane_id,
                 erase_mode,
@@ -463,7 +534,11 @@ impl SessionHandler {
                 })
                 .detach();
             }
-            Pdu::SendPaste(SendPaste { pane_id, data }) => {
+            Pdu::SendPaste(SendPaste {
+                pane_id,
+                data,
+                toggle_slow_paste,
+            }) => {
                 let sender = self.to_write_tx.clone();
                 let per_pane = self.per_pane(pane_id);
                 spawn_into_main_thread(async move {
@@ -473,7 +548,11 @@ impl SessionHandler {
                             let pane = mux
                                 .get_pane(pane_id)
                                 .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
-                            pane.send_paste(&data)?;
+                            if toggle_slow_paste {
+                                pane.send_paste_toggling_slow_paste(&data)?;
+                            } else {
+                                pane.send_paste(&data)?;
+                            }
                             maybe_push_pane_changes(&pane, sender, per_pane)?;
                             Ok(Pdu::UnitResponse(UnitResponse {}))
                         },
@@ -871,6 +950,23 @@ impl SessionHandler {
                 })
                 .detach();
             }
+            Pdu::PaneTagsChanged(PaneTagsChanged { pane_id, tags }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let mux = Mux::get();
+                            mux.get_pane(pane_id)
+                                .ok_or_else(|| anyhow!("no such pane {pane_id}"))?;
+
+                            mux.set_pane_tags(pane_id, tags.into_iter().collect());
+
+                            Ok(Pdu::UnitResponse(UnitResponse {}))
+                        },
+                        send_response,
+                    )
+                })
+                .detach();
+            }
             Pdu::TabTitleChanged(TabTitleChanged { tab_id, title }) => {
                 spawn_into_main_thread(async move {
                     catch(
@@ -929,6 +1025,121 @@ impl SessionHandler {
                 .detach();
             }
 
+            Pdu::SetLogLevel(SetLogLevel { spec }) => catch(
+                move || {
+                    let spec = env_bootstrap::ringlog::set_log_level(&spec)?;
+                    Ok(Pdu::SetLogLevelResponse(SetLogLevelResponse { spec }))
+                },
+                send_response,
+            ),
+
+            Pdu::GetPaneSnapshot(GetPaneSnapshot { pane_id }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let mux = Mux::get();
+                            let pane = mux
+                                .get_pane(pane_id)
+                                .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
+                            let snapshot = pane.get_terminal_snapshot()?;
+                            Ok(Pdu::GetPaneSnapshotResponse(GetPaneSnapshotResponse {
+                                snapshot,
+                            }))
+                        },
+                        send_response,
+                    )
+                })
+                .detach();
+            }
+
+            Pdu::CapturePane(CapturePane { pane_id }) => {
+                spawn_into_main_thread(async move {
+                    promise::spawn::spawn(async move {
+                        let result = mux::capture::capture_pane_png(pane_id).await;
+                        send_response(
+                            result.map(|png| Pdu::CapturePaneResponse(CapturePaneResponse { png })),
+                        );
+                    })
+                    .detach();
+                })
+                .detach();
+            }
+
+            Pdu::GetCommandHistory(GetCommandHistory { pane_id }) => {
+                spawn_into_main_thread(async move {
+                    promise::spawn::spawn(async move {
+                        let result = async move {
+                            let mux = Mux::get();
+                            let pane = mux
+                                .get_pane(pane_id)
+                                .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
+                            let commands = pane.get_command_history().await?;
+                            anyhow::Result::<Pdu>::Ok(Pdu::GetCommandHistoryResponse(
+                                GetCommandHistoryResponse { commands },
+                            ))
+                        };
+                        send_response(result.await);
+                    })
+                    .detach();
+                })
+                .detach();
+            }
+
+            Pdu::GetWindowConfig(GetWindowConfig { window_id, key }) => {
+                spawn_into_main_thread(async move {
+                    promise::spawn::spawn(async move {
+                        let result = mux::windowconfig::window_config(
+                            window_id,
+                            WindowConfigRequest::Get { key },
+                        )
+                        .await;
+                        send_response(result.map(|value| {
+                            Pdu::WindowConfigResponse(WindowConfigResponse { value })
+                        }));
+                    })
+                    .detach();
+                })
+                .detach();
+            }
+
+            Pdu::SetWindowConfig(SetWindowConfig {
+                window_id,
+                key,
+                value,
+            }) => {
+                spawn_into_main_thread(async move {
+                    promise::spawn::spawn(async move {
+                        let result = mux::windowconfig::window_config(
+                            window_id,
+                            WindowConfigRequest::Set { key, value },
+                        )
+                        .await;
+                        send_response(result.map(|value| {
+                            Pdu::WindowConfigResponse(WindowConfigResponse { value })
+                        }));
+                    })
+                    .detach();
+                })
+                .detach();
+            }
+
+            Pdu::SetWindowConfigProfile(SetWindowConfigProfile { window_id, profile }) => {
+                spawn_into_main_thread(async move {
+                    promise::spawn::spawn(async move {
+                        let result = mux::windowconfig::window_config(
+                            window_id,
+                            WindowConfigRequest::SetProfile { profile },
+                        )
+                        .await;
+                        send_response(result.map(|value| {
+                            Pdu::WindowConfigResponse(WindowConfigResponse { value })
+                        }));
+                    })
+                    .detach();
+                })
+                .detach();
+            }
+
             Pdu::AdjustPaneSize(AdjustPaneSize {
                 pane_id,
                 direction,
@@ -971,6 +1182,12 @@ impl SessionHandler {
             | Pdu::UnitResponse { .. }
             | Pdu::LivenessResponse { .. }
             | Pdu::GetPaneDirectionResponse { .. }
+            | Pdu::SetLogLevelResponse { .. }
+            | Pdu::SendTextToPanesResponse { .. }
+            | Pdu::GetPaneSnapshotResponse { .. }
+            | Pdu::CapturePaneResponse { .. }
+            | Pdu::GetCommandHistoryResponse { .. }
+            | Pdu::WindowConfigResponse { .. }
             | Pdu::SearchScrollbackResponse { .. }
             | Pdu::GetLinesResponse { .. }
             | Pdu::GetCodecVersionResponse { .. }
@@ -1028,6 +1245,7 @@ async fn split_pane(split: SplitPane, client_id: Option<Arc<ClientId>>) -> anyho
         SplitSource::Spawn {
             command: split.command,
             command_dir: split.command_dir,
+            exit_behavior: None,
         }
     };
 
@@ -1053,6 +1271,7 @@ async fn domain_spawn_v2(spawn: SpawnV2, client_id: Option<Arc<ClientId>>) -> an
             spawn.domain,
             spawn.command,
             spawn.command_dir,
+            None, // optional exit_behavior override
             spawn.size,
             None, // optional current pane_id
             spawn.workspace,
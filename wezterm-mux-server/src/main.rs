@@ -318,5 +318,7 @@ pub fn spawn_listener() -> anyhow::Result<()> {
         ossl::spawn_tls_listener(tls_server)?;
     }
 
+    wezterm_mux_server_impl::jsonrpc::spawn_rpc_listener(&config)?;
+
     Ok(())
 }
@@ -136,6 +136,16 @@ impl ChannelWrap {
         }
     }
 
+    pub fn request_subsystem(&mut self, name: &str) -> anyhow::Result<()> {
+        match self {
+            #[cfg(feature = "ssh2")]
+            Self::Ssh2(chan) => Ok(chan.subsystem(name)?),
+
+            #[cfg(feature = "libssh-rs")]
+            Self::LibSsh(chan) => Ok(chan.request_subsystem(name)?),
+        }
+    }
+
     pub fn request_shell(&mut self) -> anyhow::Result<()> {
         match self {
             #[cfg(feature = "ssh2")]
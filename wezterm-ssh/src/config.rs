@@ -15,7 +15,7 @@ struct Pattern {
 }
 
 /// Compile a glob style pattern string into a regex pattern string
-fn wildcard_to_pattern(s: &str) -> (String, bool) {
+pub(crate) fn wildcard_to_pattern(s: &str) -> (String, bool) {
     let mut pattern = String::new();
     let mut is_literal = true;
     pattern.push('^');
@@ -241,7 +241,8 @@ impl ParsedConfigFile {
             if let Some(sep) = line.find(|c: char| c == '=' || c.is_whitespace()) {
                 let (k, v) = line.split_at(sep);
                 let k = k.trim().to_lowercase();
-                let v = v[1..].trim();
+                // The separator may be a multibyte whitespace character
+                let v = v[v.chars().next().map_or(0, char::len_utf8)..].trim();
 
                 let v = if v.starts_with('"') && v.ends_with('"') {
                     &v[1..v.len() - 1]
@@ -738,6 +739,86 @@ impl Config {
 
         hosts
     }
+
+    /// Re-examines the loaded config files and returns a description of
+    /// each line that the parser could not fully understand.
+    /// Each entry is prefixed with the file name and line number.
+    pub fn parse_problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for file in self.loaded_config_files() {
+            match std::fs::read_to_string(&file) {
+                Ok(data) => {
+                    for (line_no, problem) in check_config_text(&data) {
+                        problems.push(format!("{}:{}: {}", file.display(), line_no, problem));
+                    }
+                }
+                Err(err) => {
+                    problems.push(format!("{}: unable to read: {:#}", file.display(), err));
+                }
+            }
+        }
+        problems
+    }
+}
+
+/// Returns the 1-based line number and a description of each line in `s`
+/// that is either malformed or uses a feature that we don't support.
+fn check_config_text(s: &str) -> Vec<(usize, String)> {
+    let mut problems = vec![];
+    for (idx, line) in s.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (k, v) = match line.find(|c: char| c == '=' || c.is_whitespace()) {
+            Some(sep) => {
+                let (k, v) = line.split_at(sep);
+                let sep_len = v.chars().next().map_or(0, char::len_utf8);
+                (k.trim().to_lowercase(), v[sep_len..].trim())
+            }
+            None => {
+                problems.push((line_no, format!("`{}` has no value and is ignored", line)));
+                continue;
+            }
+        };
+
+        if v.is_empty() {
+            problems.push((line_no, format!("`{}` has no value", k)));
+            continue;
+        }
+
+        if k == "match" {
+            let mut tokens = v.split_ascii_whitespace();
+            while let Some(cname) = tokens.next() {
+                match cname.to_lowercase().as_str() {
+                    "all" | "canonical" | "final" => {}
+                    "host" | "originalhost" | "user" | "localuser" => {
+                        tokens.next();
+                    }
+                    "exec" => {
+                        tokens.next();
+                        problems.push((
+                            line_no,
+                            "`Match exec` is not supported and always matches".to_string(),
+                        ));
+                    }
+                    other => {
+                        problems.push((
+                            line_no,
+                            format!(
+                                "unsupported `Match` criteria `{}`; the rest of the line is ignored",
+                                other
+                            ),
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    problems
 }
 
 #[cfg(test)]
@@ -745,6 +826,55 @@ mod test {
     use super::*;
     use k9::snapshot;
 
+    #[test]
+    fn config_text_problems() {
+        let problems = check_config_text(
+            r#"
+        Host foo
+            Port
+            Compression yes
+            BogusLine
+        Match exec "true" host bar
+        Match tagged work
+            "#,
+        );
+        snapshot!(
+            problems,
+            r#"
+[
+    (
+        3,
+        "`Port` has no value and is ignored",
+    ),
+    (
+        5,
+        "`BogusLine` has no value and is ignored",
+    ),
+    (
+        6,
+        "`Match exec` is not supported and always matches",
+    ),
+    (
+        7,
+        "unsupported `Match` criteria `tagged`; the rest of the line is ignored",
+    ),
+]
+"#
+        );
+    }
+
+    #[test]
+    fn multibyte_separator() {
+        // U+3000 IDEOGRAPHIC SPACE is whitespace that is 3 bytes long
+        let text = "Host\u{3000}foo\n    Port\u{3000}2222\n";
+        assert!(check_config_text(text).is_empty());
+
+        let mut config = Config::new();
+        config.add_config_string(text);
+        let opts = config.for_host("foo");
+        assert_eq!(opts.get("port").map(String::as_str), Some("2222"));
+    }
+
     #[test]
     fn parse_proxy_command_tokens() {
         let mut config = Config::new();
@@ -1,7 +1,24 @@
+use crate::sftp::error::SftpError;
 use crate::sftp::types::Metadata;
 use crate::sftp::{SftpChannelError, SftpChannelResult};
 use camino::Utf8PathBuf;
 
+/// The error that libssh2 reports once there are no more entries
+#[cfg(feature = "ssh2")]
+const LIBSSH2_ERROR_FILE: std::os::raw::c_int = -16;
+
+/// Returns true if `err` is how `DirWrap::read_dir` reports the
+/// end of the directory
+fn is_end_of_dir(err: &SftpChannelError) -> bool {
+    match err {
+        SftpChannelError::Sftp(SftpError::Eof) => true,
+        SftpChannelError::FileIo(err) => err.kind() == std::io::ErrorKind::UnexpectedEof,
+        #[cfg(feature = "ssh2")]
+        SftpChannelError::Ssh2(err) => err.code() == ssh2::ErrorCode::Session(LIBSSH2_ERROR_FILE),
+        _ => false,
+    }
+}
+
 pub(crate) enum DirWrap {
     #[cfg(feature = "ssh2")]
     Ssh2(ssh2::File),
@@ -11,6 +28,28 @@ pub(crate) enum DirWrap {
 }
 
 impl DirWrap {
+    /// Reads up to `max` entries, skipping `.` and `..`.
+    /// Fewer entries are returned only once the end of the directory
+    /// has been reached.
+    pub fn read_dir_batch(
+        &mut self,
+        max: usize,
+    ) -> SftpChannelResult<Vec<(Utf8PathBuf, Metadata)>> {
+        let mut entries = vec![];
+        while entries.len() < max {
+            match self.read_dir() {
+                Ok((path, metadata)) => {
+                    if path != "." && path != ".." {
+                        entries.push((path, metadata));
+                    }
+                }
+                Err(err) if is_end_of_dir(&err) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(entries)
+    }
+
     pub fn read_dir(&mut self) -> SftpChannelResult<(Utf8PathBuf, Metadata)> {
         match self {
             #[cfg(feature = "ssh2")]
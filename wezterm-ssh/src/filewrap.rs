@@ -1,5 +1,6 @@
-use crate::sftp::types::Metadata;
+use crate::sftp::types::{Limits, Metadata};
 use crate::sftp::{SftpChannelError, SftpChannelResult};
+use std::io::{Read, Write};
 
 pub(crate) enum FileWrap {
     #[cfg(feature = "ssh2")]
@@ -10,6 +11,34 @@ pub(crate) enum FileWrap {
 }
 
 impl FileWrap {
+    /// Returns true if a read or write that spans several sftp requests
+    /// sends them all before waiting for their replies.  libssh sends
+    /// them one at a time; its bindings don't expose the sftp_aio
+    /// functions that would allow otherwise.
+    pub fn pipelines_requests(&self) -> bool {
+        match self {
+            #[cfg(feature = "ssh2")]
+            Self::Ssh2(_) => true,
+
+            #[cfg(feature = "libssh-rs")]
+            Self::LibSsh(_) => false,
+        }
+    }
+
+    /// Returns the most data that a single read or write puts in each
+    /// sftp request, if the backend splits larger ones itself.
+    /// libssh2 uses requests of at most 30000 bytes, while libssh makes
+    /// a single request of whatever size it is given.
+    fn max_request_len(&self) -> Option<usize> {
+        match self {
+            #[cfg(feature = "ssh2")]
+            Self::Ssh2(_) => Some(30000),
+
+            #[cfg(feature = "libssh-rs")]
+            Self::LibSsh(_) => None,
+        }
+    }
+
     pub fn reader(&mut self) -> Box<dyn std::io::Read + '_> {
         match self {
             #[cfg(feature = "ssh2")]
@@ -64,11 +93,241 @@ impl FileWrap {
             #[cfg(feature = "ssh2")]
             Self::Ssh2(file) => file.fsync().map_err(SftpChannelError::from),
 
+            // The libssh bindings don't expose sftp_fsync, and
+            // flushing only guarantees that the server received
+            // the data, not that it is durable
             #[cfg(feature = "libssh-rs")]
-            Self::LibSsh(file) => {
-                use std::io::Write;
-                Ok(file.flush()?)
+            Self::LibSsh(_) => Err(SftpChannelError::NotImplemented),
+        }
+    }
+
+    pub fn seek(&mut self, offset: u64) -> SftpChannelResult<()> {
+        use std::io::{Seek, SeekFrom};
+        match self {
+            #[cfg(feature = "ssh2")]
+            Self::Ssh2(file) => file
+                .seek(SeekFrom::Start(offset))
+                .map(|_| ())
+                .map_err(SftpChannelError::from),
+
+            #[cfg(feature = "libssh-rs")]
+            Self::LibSsh(file) => file
+                .seek(SeekFrom::Start(offset))
+                .map(|_| ())
+                .map_err(SftpChannelError::from),
+        }
+    }
+
+    /// Moves the position of the file `len` bytes back
+    pub fn seek_back(&mut self, len: u64) -> SftpChannelResult<()> {
+        use std::io::{Seek, SeekFrom};
+        let delta = -(len as i64);
+        match self {
+            #[cfg(feature = "ssh2")]
+            Self::Ssh2(file) => file
+                .seek(SeekFrom::Current(delta))
+                .map(|_| ())
+                .map_err(SftpChannelError::from),
+
+            #[cfg(feature = "libssh-rs")]
+            Self::LibSsh(file) => file
+                .seek(SeekFrom::Current(delta))
+                .map(|_| ())
+                .map_err(SftpChannelError::from),
+        }
+    }
+}
+
+/// The number of bytes that is assumed to be carried by each
+/// outstanding read or write request, as `sftp(1)` does by default
+pub(crate) const PIPELINE_REQUEST_SIZE: usize = 32 * 1024;
+
+/// The number of requests that are kept outstanding for each file,
+/// unless `wezterm_sftp_pipeline_depth` says otherwise
+pub(crate) const DEFAULT_PIPELINE_DEPTH: usize = 64;
+
+/// The room left in a packet for the header of a read or write and the
+/// handle that it names, which OpenSSH also subtracts from the packet
+/// length to arrive at the read and write lengths that it reports
+const REQUEST_OVERHEAD: u64 = 1024;
+
+/// Returns the most data to read or write with a single request, given
+/// the limit on the length of the read or write and the limit on the
+/// length of a packet, either of which may be 0 when it isn't reported
+fn request_limit(max_len: u64, max_packet_len: u64) -> Option<usize> {
+    let packet_limit = match max_packet_len {
+        0 => None,
+        len => Some(len.saturating_sub(REQUEST_OVERHEAD).max(1)),
+    };
+    let len_limit = match max_len {
+        0 => None,
+        len => Some(len),
+    };
+    let limit = match (len_limit, packet_limit) {
+        (Some(a), Some(b)) => a.min(b),
+        (Some(limit), None) | (None, Some(limit)) => limit,
+        (None, None) => return None,
+    };
+    Some(limit.min(usize::MAX as u64) as usize)
+}
+
+/// A file along with the data that has been read ahead of, or is yet
+/// to be written after, the requests made for it.
+///
+/// Reads and writes of the backends are synchronous, but given a
+/// buffer that spans several requests, libssh2 sends them all before
+/// waiting for their replies.  Reading ahead into, and writing behind
+/// from, a buffer of `depth` requests keeps that many in flight, rather
+/// than waiting a round trip for each of the small reads and writes
+/// made through the `AsyncRead` and `AsyncWrite` traits of `File`.
+///
+/// As that gains nothing with libssh, which waits for the reply to
+/// each request before sending the next, files of that backend are
+/// read and written as requested, without buffering.
+///
+/// Reads and writes of the backend are split so that no request is
+/// larger than the limits that the server reported.
+pub(crate) struct PipelinedFile {
+    file: FileWrap,
+    window: usize,
+    /// The most data to read, and to write, with a single call of the
+    /// backend, so that the requests it makes are within the limits
+    max_read_len: usize,
+    max_write_len: usize,
+    read_buf: Vec<u8>,
+    /// The data at `read_buf[read_pos..read_len]` has been read from
+    /// the file but not yet returned
+    read_pos: usize,
+    read_len: usize,
+    write_buf: Vec<u8>,
+}
+
+impl PipelinedFile {
+    pub fn new(file: FileWrap, depth: usize, limits: Option<Limits>) -> Self {
+        let window = if file.pipelines_requests() {
+            depth.max(1) * PIPELINE_REQUEST_SIZE
+        } else {
+            0
+        };
+        let call_len = |limit: Option<usize>| match (limit, file.max_request_len()) {
+            (Some(limit), Some(request_len)) if request_len <= limit => usize::MAX,
+            (Some(limit), _) => limit,
+            (None, _) => usize::MAX,
+        };
+        let max_read_len =
+            call_len(limits.and_then(|l| request_limit(l.max_read_len, l.max_packet_len)));
+        let max_write_len =
+            call_len(limits.and_then(|l| request_limit(l.max_write_len, l.max_packet_len)));
+        Self {
+            file,
+            window,
+            max_read_len,
+            max_write_len,
+            read_buf: vec![],
+            read_pos: 0,
+            read_len: 0,
+            write_buf: vec![],
+        }
+    }
+
+    /// Reads up to `max_bytes`, reading ahead up to the window of the
+    /// file, or `window` if that is smaller
+    pub fn read(&mut self, max_bytes: usize, window: Option<usize>) -> SftpChannelResult<Vec<u8>> {
+        self.flush_writes()?;
+        if self.read_pos == self.read_len {
+            let size = self.window(window).max(max_bytes);
+            if self.read_buf.len() < size {
+                self.read_buf.resize(size, 0);
             }
+            self.read_pos = 0;
+            self.read_len = 0;
+            let size = size.min(self.max_read_len);
+            self.read_len = self.file.reader().read(&mut self.read_buf[..size])?;
+        }
+        let end = self.read_len.min(self.read_pos + max_bytes);
+        let data = self.read_buf[self.read_pos..end].to_vec();
+        self.read_pos = end;
+        Ok(data)
+    }
+
+    /// Buffers `data` until a window's worth of it is waiting, where
+    /// `window` may make the window of the file smaller, so the failure
+    /// of a write may only be reported by the next write, flush or
+    /// close of the file
+    pub fn write(&mut self, data: &[u8], window: Option<usize>) -> SftpChannelResult<()> {
+        self.discard_read_ahead()?;
+        self.write_buf.extend_from_slice(data);
+        if self.write_buf.len() >= self.window(window) {
+            self.flush_writes()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered data and waits for the server to acknowledge it
+    pub fn flush(&mut self) -> SftpChannelResult<()> {
+        self.flush_writes()?;
+        self.file.writer().flush()?;
+        Ok(())
+    }
+
+    pub fn seek(&mut self, offset: u64) -> SftpChannelResult<()> {
+        self.flush_writes()?;
+        self.read_pos = 0;
+        self.read_len = 0;
+        self.file.seek(offset)
+    }
+
+    /// Returns the file once the buffered data has been written, for
+    /// the requests that aren't reads or writes
+    pub fn file(&mut self) -> SftpChannelResult<&mut FileWrap> {
+        self.flush_writes()?;
+        Ok(&mut self.file)
+    }
+
+    fn window(&self, limit: Option<usize>) -> usize {
+        limit.map_or(self.window, |limit| self.window.min(limit))
+    }
+
+    fn flush_writes(&mut self) -> SftpChannelResult<()> {
+        if self.write_buf.is_empty() {
+            return Ok(());
+        }
+        let data = std::mem::take(&mut self.write_buf);
+        let result = data
+            .chunks(self.max_write_len)
+            .try_for_each(|chunk| self.file.writer().write_all(chunk));
+        self.write_buf = data;
+        self.write_buf.clear();
+        result?;
+        Ok(())
+    }
+
+    /// Moves the position of the file back to the data that has been
+    /// read ahead but not returned, so that a write follows the data
+    /// that was last returned
+    fn discard_read_ahead(&mut self) -> SftpChannelResult<()> {
+        let unread = self.read_len - self.read_pos;
+        self.read_pos = 0;
+        self.read_len = 0;
+        if unread > 0 {
+            self.file.seek_back(unread as u64)?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn request_limit_follows_limits() {
+        assert_eq!(request_limit(0, 0), None);
+        assert_eq!(request_limit(261120, 0), Some(261120));
+        assert_eq!(request_limit(0, 262144), Some(261120));
+        assert_eq!(request_limit(261120, 262144), Some(261120));
+        assert_eq!(request_limit(65536, 262144), Some(65536));
+        assert_eq!(request_limit(261120, 32768), Some(31744));
+        assert_eq!(request_limit(0, 100), Some(1));
     }
 }
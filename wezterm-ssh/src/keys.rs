@@ -0,0 +1,182 @@
+//! Helpers for inspecting the public keys that are available to
+//! authenticate an ssh session: identity files referenced by the
+//! ssh config and the keys held by a running ssh agent.
+use crate::config::ConfigMap;
+use anyhow::Context;
+use base64::Engine;
+use std::path::PathBuf;
+
+/// A public key in the OpenSSH wire format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    /// The key type, eg: `ssh-ed25519`
+    pub key_type: String,
+    /// The raw key blob
+    pub blob: Vec<u8>,
+    /// The comment associated with the key, if any
+    pub comment: Option<String>,
+}
+
+impl PublicKey {
+    /// Parse a key from its raw wire format blob.
+    /// The key type is extracted from the blob itself.
+    pub fn from_blob(blob: &[u8], comment: Option<&str>) -> anyhow::Result<Self> {
+        let len = blob
+            .get(0..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .context("public key blob is too short")?;
+        let key_type = blob
+            .get(4..4 + len)
+            .context("public key blob is truncated")?;
+        let key_type = std::str::from_utf8(key_type)
+            .context("public key type is not utf8")?
+            .to_string();
+        Ok(Self {
+            key_type,
+            blob: blob.to_vec(),
+            comment: comment.filter(|c| !c.is_empty()).map(|c| c.to_string()),
+        })
+    }
+
+    /// Parse the `TYPE BASE64 [COMMENT]` form used in `.pub` files,
+    /// `authorized_keys` and (after the host patterns) `known_hosts`.
+    pub fn parse_openssh(text: &str) -> anyhow::Result<Self> {
+        let mut fields = text.trim().splitn(3, char::is_whitespace);
+        let key_type = fields.next().filter(|s| !s.is_empty());
+        let data = fields.next().map(|s| s.trim());
+        let comment = fields.next().map(|s| s.trim()).filter(|s| !s.is_empty());
+
+        let (key_type, data) = match (key_type, data) {
+            (Some(key_type), Some(data)) => (key_type, data),
+            _ => anyhow::bail!("expected `TYPE BASE64` but got {text:?}"),
+        };
+
+        let blob = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .with_context(|| format!("decoding base64 key data for {key_type}"))?;
+
+        Ok(Self {
+            key_type: key_type.to_string(),
+            blob,
+            comment: comment.map(|s| s.to_string()),
+        })
+    }
+
+    /// Returns the fingerprint of the key in the same `SHA256:...`
+    /// form that is displayed by `ssh-keygen -l`
+    pub fn fingerprint(&self) -> String {
+        use sha2::Digest;
+        let digest = sha2::Sha256::digest(&self.blob);
+        let engine = base64::engine::general_purpose::GeneralPurpose::new(
+            &base64::alphabet::STANDARD,
+            base64::engine::general_purpose::NO_PAD,
+        );
+        format!("SHA256:{}", engine.encode(digest))
+    }
+
+    /// Returns the `TYPE BASE64` representation of the key
+    pub fn to_openssh(&self) -> String {
+        let data = base64::engine::general_purpose::STANDARD.encode(&self.blob);
+        format!("{} {}", self.key_type, data)
+    }
+}
+
+/// An `IdentityFile` referenced by the ssh config
+#[derive(Debug, Clone)]
+pub struct IdentityFile {
+    /// Path to the private key
+    pub path: PathBuf,
+    /// Whether the private key file is present
+    pub exists: bool,
+    /// The public key, loaded from the adjacent `.pub` file
+    pub public_key: Option<PublicKey>,
+}
+
+/// Returns the list of identity files that would be considered when
+/// authenticating with the provided resolved configuration.
+/// Files that don't exist are included so that the caller can report
+/// on them, but are flagged via `IdentityFile::exists`.
+pub fn configured_identities(config: &ConfigMap) -> Vec<IdentityFile> {
+    let mut result = vec![];
+    if let Some(files) = config.get("identityfile") {
+        for file in files.split_whitespace() {
+            let path = PathBuf::from(file);
+            let pubkey = PathBuf::from(format!("{}.pub", file));
+            let public_key =
+                std::fs::read_to_string(&pubkey).ok().and_then(
+                    |text| match PublicKey::parse_openssh(&text) {
+                        Ok(key) => Some(key),
+                        Err(err) => {
+                            log::warn!("{}: {:#}", pubkey.display(), err);
+                            None
+                        }
+                    },
+                );
+            result.push(IdentityFile {
+                exists: path.exists(),
+                path,
+                public_key,
+            });
+        }
+    }
+    result
+}
+
+/// Returns the list of keys held by the ssh agent
+/// that is referenced by `SSH_AUTH_SOCK`
+#[cfg(feature = "ssh2")]
+pub fn agent_identities() -> anyhow::Result<Vec<PublicKey>> {
+    let sess = ssh2::Session::new()?;
+    let mut agent = sess.agent()?;
+    agent.connect().context("connecting to ssh agent")?;
+    agent
+        .list_identities()
+        .context("listing ssh agent identities")?;
+    let mut result = vec![];
+    for identity in agent.identities()? {
+        result.push(PublicKey::from_blob(
+            identity.blob(),
+            Some(identity.comment()),
+        )?);
+    }
+    agent.disconnect().ok();
+    Ok(result)
+}
+
+#[cfg(not(feature = "ssh2"))]
+pub fn agent_identities() -> anyhow::Result<Vec<PublicKey>> {
+    anyhow::bail!("listing agent identities requires wezterm-ssh to be built with `ssh2`");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_pub_file() {
+        let key = PublicKey::parse_openssh(
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4f me@host\n",
+        )
+        .unwrap();
+        assert_eq!(key.key_type, "ssh-ed25519");
+        assert_eq!(key.comment.as_deref(), Some("me@host"));
+        assert_eq!(
+            key.to_openssh(),
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4f"
+        );
+
+        let from_blob = PublicKey::from_blob(&key.blob, Some("me@host")).unwrap();
+        assert_eq!(from_blob, key);
+        assert_eq!(
+            key.fingerprint(),
+            "SHA256:ZkAslGjFiUHdGf/WUL8rQvkib4PTvQatUV0OUQSncCA"
+        );
+    }
+
+    #[test]
+    fn parse_bad_key() {
+        assert!(PublicKey::parse_openssh("ssh-ed25519").is_err());
+        assert!(PublicKey::parse_openssh("ssh-ed25519 !!!").is_err());
+        assert!(PublicKey::from_blob(&[0, 0, 0, 9, b'a'], None).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+//! Parse and edit OpenSSH `known_hosts` files, as described by
+//! the SSH_KNOWN_HOSTS FILE FORMAT section of `man 8 sshd`.
+use crate::config::{wildcard_to_pattern, ConfigMap};
+use crate::keys::PublicKey;
+use anyhow::Context;
+use base64::Engine;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The optional marker that may prefix a known_hosts entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownHostMarker {
+    CertAuthority,
+    Revoked,
+}
+
+/// The host portion of a known_hosts entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownHostNames {
+    /// A comma separated list of patterns, possibly negated with `!`
+    Patterns(Vec<String>),
+    /// A `|1|salt|hash` entry as produced by `HashKnownHosts yes`
+    Hashed { salt: Vec<u8>, hash: Vec<u8> },
+}
+
+/// A single key entry from a known_hosts file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownHostEntry {
+    /// The 1-based line number of this entry within its file
+    pub line_number: usize,
+    pub marker: Option<KnownHostMarker>,
+    pub names: KnownHostNames,
+    pub key: PublicKey,
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    /// Comments, blank lines and lines that we failed to parse.
+    /// These are preserved verbatim when the file is saved.
+    Verbatim(String),
+    Entry {
+        text: String,
+        entry: KnownHostEntry,
+    },
+}
+
+/// The parsed contents of a known_hosts file
+#[derive(Debug, Clone)]
+pub struct KnownHostsFile {
+    pub path: PathBuf,
+    lines: Vec<Line>,
+}
+
+/// Produce the name that ssh uses to look up a host in known_hosts;
+/// non-standard ports are recorded in `[host]:port` form.
+fn lookup_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+fn hmac_sha1(key: &[u8], message: &[u8]) -> Vec<u8> {
+    use sha1::{Digest, Sha1};
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        let digest = Sha1::digest(key);
+        block[..digest.len()].copy_from_slice(&digest);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha1::new();
+    inner.update(block.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+    inner.update(message);
+    let inner = inner.finalize();
+
+    let mut outer = Sha1::new();
+    outer.update(block.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+    outer.update(inner);
+    outer.finalize().to_vec()
+}
+
+impl KnownHostNames {
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        if let Some(hashed) = text.strip_prefix("|1|") {
+            let (salt, hash) = hashed
+                .split_once('|')
+                .with_context(|| format!("malformed hashed host {text:?}"))?;
+            let engine = &base64::engine::general_purpose::STANDARD;
+            return Ok(Self::Hashed {
+                salt: engine.decode(salt).context("decoding hashed host salt")?,
+                hash: engine.decode(hash).context("decoding hashed host hash")?,
+            });
+        }
+        Ok(Self::Patterns(
+            text.split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect(),
+        ))
+    }
+
+    /// Returns true if these names apply to the specified host and port
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        let name = lookup_name(host, port);
+        match self {
+            Self::Hashed { salt, hash } => hmac_sha1(salt, name.as_bytes()) == *hash,
+            Self::Patterns(patterns) => {
+                let mut matched = false;
+                for pattern in patterns {
+                    let (negated, pattern) = match pattern.strip_prefix('!') {
+                        Some(p) => (true, p),
+                        None => (false, pattern.as_str()),
+                    };
+                    let (re, _is_literal) = wildcard_to_pattern(pattern);
+                    let is_match = Regex::new(&re)
+                        .map(|re| re.is_match(&name))
+                        .unwrap_or(false);
+                    if is_match {
+                        if negated {
+                            // A negated match overrides any other match
+                            return false;
+                        }
+                        matched = true;
+                    }
+                }
+                matched
+            }
+        }
+    }
+
+    /// Returns a host name that can be used to connect to the host
+    /// described by these names, along with its port.
+    /// Returns None for hashed entries and entries that consist
+    /// solely of wildcards or negations.
+    pub fn connectable_host(&self) -> Option<(String, u16)> {
+        match self {
+            Self::Hashed { .. } => None,
+            Self::Patterns(patterns) => {
+                for pattern in patterns {
+                    if pattern.starts_with('!') || pattern.contains(&['*', '?'][..]) {
+                        continue;
+                    }
+                    if let Some(bracketed) = pattern.strip_prefix('[') {
+                        if let Some((host, port)) = bracketed.split_once("]:") {
+                            if let Ok(port) = port.parse() {
+                                return Some((host.to_string(), port));
+                            }
+                        }
+                        continue;
+                    }
+                    return Some((pattern.to_string(), 22));
+                }
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for KnownHostNames {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Patterns(patterns) => write!(fmt, "{}", patterns.join(",")),
+            Self::Hashed { .. } => write!(fmt, "(hashed)"),
+        }
+    }
+}
+
+impl KnownHostEntry {
+    fn parse(line: &str, line_number: usize) -> anyhow::Result<Self> {
+        let line = line.trim();
+        let (marker, line) = if let Some(rest) = line.strip_prefix("@cert-authority") {
+            (Some(KnownHostMarker::CertAuthority), rest.trim_start())
+        } else if let Some(rest) = line.strip_prefix("@revoked") {
+            (Some(KnownHostMarker::Revoked), rest.trim_start())
+        } else {
+            (None, line)
+        };
+
+        let (names, key) = line
+            .split_once(char::is_whitespace)
+            .with_context(|| format!("line {line_number}: expected host names and a key"))?;
+
+        Ok(Self {
+            line_number,
+            marker,
+            names: KnownHostNames::parse(names).with_context(|| format!("line {line_number}"))?,
+            key: PublicKey::parse_openssh(key).with_context(|| format!("line {line_number}"))?,
+        })
+    }
+
+    /// Returns true if this entry applies to the specified host and port
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        self.names.matches(host, port)
+    }
+}
+
+impl KnownHostsFile {
+    /// Parse `text` as the contents of a known_hosts file.
+    /// Lines that cannot be parsed are logged and preserved as-is.
+    pub fn parse<P: AsRef<Path>>(text: &str, path: P) -> Self {
+        let path = path.as_ref();
+        let mut lines = vec![];
+        for (idx, text) in text.lines().enumerate() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                lines.push(Line::Verbatim(text.to_string()));
+                continue;
+            }
+            match KnownHostEntry::parse(trimmed, idx + 1) {
+                Ok(entry) => lines.push(Line::Entry {
+                    text: text.to_string(),
+                    entry,
+                }),
+                Err(err) => {
+                    log::warn!("{}: {:#}", path.display(), err);
+                    lines.push(Line::Verbatim(text.to_string()));
+                }
+            }
+        }
+        Self {
+            path: path.to_path_buf(),
+            lines,
+        }
+    }
+
+    /// Load a known_hosts file.
+    /// A file that doesn't exist is treated as being empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text, path)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::parse("", path)),
+            Err(err) => Err(err).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    /// Returns an iterator over the key entries in the file
+    pub fn entries(&self) -> impl Iterator<Item = &KnownHostEntry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry { entry, .. } => Some(entry),
+            Line::Verbatim(_) => None,
+        })
+    }
+
+    /// Returns the entries that apply to the specified host and port
+    pub fn find(&self, host: &str, port: u16) -> Vec<&KnownHostEntry> {
+        self.entries()
+            .filter(|entry| entry.matches(host, port))
+            .collect()
+    }
+
+    /// Remove the entry that was parsed from `line_number`.
+    /// Returns true if an entry was removed.
+    pub fn remove_line(&mut self, line_number: usize) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(|line| match line {
+            Line::Entry { entry, .. } => entry.line_number != line_number,
+            Line::Verbatim(_) => true,
+        });
+        before != self.lines.len()
+    }
+
+    /// Remove all entries that apply to the specified host and port,
+    /// in the same way as `ssh-keygen -R`.
+    /// Returns the number of entries that were removed.
+    pub fn remove_host(&mut self, host: &str, port: u16) -> usize {
+        let before = self.lines.len();
+        self.lines.retain(|line| match line {
+            Line::Entry { entry, .. } => !entry.matches(host, port),
+            Line::Verbatim(_) => true,
+        });
+        before - self.lines.len()
+    }
+
+    /// Returns the file contents as they would be written by `save`
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        for line in &self.lines {
+            match line {
+                Line::Verbatim(text) | Line::Entry { text, .. } => result.push_str(text),
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Write the file back to disk.
+    /// Following the lead of `ssh-keygen -R`, the prior contents are
+    /// preserved in a file with an additional `.old` extension.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut backup = self.path.clone().into_os_string();
+        backup.push(".old");
+        if self.path.exists() {
+            std::fs::copy(&self.path, &backup)
+                .with_context(|| format!("backing up {} to {:?}", self.path.display(), backup))?;
+        }
+        std::fs::write(&self.path, self.to_text())
+            .with_context(|| format!("writing {}", self.path.display()))
+    }
+}
+
+/// Returns the list of user known_hosts files that are referenced by the
+/// provided resolved configuration
+pub fn known_hosts_files(config: &ConfigMap) -> Vec<PathBuf> {
+    config
+        .get("userknownhostsfile")
+        .map(|files| files.split_whitespace().map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4f";
+
+    fn sample() -> KnownHostsFile {
+        KnownHostsFile::parse(
+            &format!(
+                "# a comment\n\
+                 github.com,140.82.112.3 {KEY}\n\
+                 [example.com]:2222 {KEY}\n\
+                 |1|AAECAwQFBgcICQoLDA0ODxAREhM=|nnUK16ANsXd3hL31YfAkGOluSjU= {KEY}\n\
+                 *.corp,!bastion.corp {KEY} corp hosts\n\
+                 @revoked revoked.example {KEY}\n\
+                 this line is garbage\n"
+            ),
+            "known_hosts",
+        )
+    }
+
+    #[test]
+    fn parse_entries() {
+        let file = sample();
+        let entries: Vec<_> = file.entries().collect();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].line_number, 2);
+        assert_eq!(entries[0].names.to_string(), "github.com,140.82.112.3");
+        assert_eq!(entries[2].names.to_string(), "(hashed)");
+        assert_eq!(entries[3].key.comment.as_deref(), Some("corp hosts"));
+        assert_eq!(entries[4].marker, Some(KnownHostMarker::Revoked));
+    }
+
+    #[test]
+    fn match_hosts() {
+        let file = sample();
+        assert_eq!(file.find("github.com", 22).len(), 1);
+        assert_eq!(file.find("github.com", 2222).len(), 0);
+        assert_eq!(file.find("example.com", 2222).len(), 1);
+        // Matched via the hashed entry
+        assert_eq!(file.find("example.com", 22).len(), 1);
+        assert_eq!(file.find("db.corp", 22).len(), 1);
+        assert_eq!(file.find("bastion.corp", 22).len(), 0);
+    }
+
+    #[test]
+    fn connectable() {
+        let file = sample();
+        let hosts: Vec<_> = file
+            .entries()
+            .map(|entry| entry.names.connectable_host())
+            .collect();
+        assert_eq!(
+            hosts,
+            vec![
+                Some(("github.com".to_string(), 22)),
+                Some(("example.com".to_string(), 2222)),
+                None,
+                None,
+                Some(("revoked.example".to_string(), 22)),
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_and_preserve() {
+        let mut file = sample();
+        assert_eq!(file.remove_host("example.com", 22), 1);
+        assert!(file.remove_line(2));
+        assert!(!file.remove_line(2));
+        assert_eq!(
+            file.to_text(),
+            format!(
+                "# a comment\n\
+                 [example.com]:2222 {KEY}\n\
+                 *.corp,!bastion.corp {KEY} corp hosts\n\
+                 @revoked revoked.example {KEY}\n\
+                 this line is garbage\n"
+            )
+        );
+    }
+}
@@ -7,6 +7,8 @@ mod config;
 mod dirwrap;
 mod filewrap;
 mod host;
+mod keys;
+mod known_hosts;
 mod pty;
 mod session;
 mod sessioninner;
@@ -17,6 +19,8 @@ mod sftpwrap;
 pub use auth::*;
 pub use config::*;
 pub use host::*;
+pub use keys::*;
+pub use known_hosts::*;
 pub use pty::*;
 pub use session::*;
 pub use sftp::error::*;
@@ -3,7 +3,7 @@ use crate::config::ConfigMap;
 use crate::host::*;
 use crate::pty::*;
 use crate::sessioninner::*;
-use crate::sftp::{Sftp, SftpRequest};
+use crate::sftp::{RateLimit, Sftp, SftpRequest};
 use filedescriptor::{socketpair, FileDescriptor};
 use portable_pty::PtySize;
 use smol::channel::{bounded, Receiver, Sender};
@@ -75,6 +75,8 @@ pub(crate) struct Exec {
 #[derive(Clone)]
 pub struct Session {
     tx: SessionSender,
+    /// From the `wezterm_sftp_rate_limit` option
+    sftp_rate_limit: Option<RateLimit>,
 }
 
 impl Drop for Session {
@@ -97,6 +99,21 @@ impl Session {
             pipe: Arc::new(Mutex::new(sender_write)),
         };
 
+        let sftp_rate_limit = match config.get("wezterm_sftp_rate_limit") {
+            Some(value) => match value.parse::<u64>() {
+                Ok(0) => None,
+                Ok(bytes_per_second) => Some(RateLimit::new(bytes_per_second)),
+                Err(_) => {
+                    log::warn!(
+                        "invalid wezterm_sftp_rate_limit value: {}, expected bytes per second",
+                        value
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
         let mut inner = SessionInner {
             config,
             tx_event,
@@ -109,9 +126,17 @@ impl Session {
             sender_read,
             session_was_dropped: false,
             shown_accept_env_error: false,
+            sftp_extensions: None,
+            sftp_extension_channel: None,
         };
         std::thread::spawn(move || inner.run());
-        Ok((Self { tx: session_sender }, rx_event))
+        Ok((
+            Self {
+                tx: session_sender,
+                sftp_rate_limit,
+            },
+            rx_event,
+        ))
     }
 
     pub async fn request_pty(
@@ -171,6 +196,7 @@ impl Session {
     pub fn sftp(&self) -> Sftp {
         Sftp {
             tx: self.tx.clone(),
+            rate_limits: self.sftp_rate_limit.iter().cloned().collect(),
         }
     }
 }
@@ -1,16 +1,21 @@
 use crate::channelwrap::ChannelWrap;
 use crate::config::ConfigMap;
 use crate::dirwrap::DirWrap;
-use crate::filewrap::FileWrap;
+use crate::filewrap::{PipelinedFile, DEFAULT_PIPELINE_DEPTH};
 use crate::pty::*;
 use crate::session::{Exec, ExecResult, SessionEvent, SessionRequest, SignalChannel};
 use crate::sessionwrap::SessionWrap;
 use crate::sftp::dir::{Dir, DirId, DirRequest};
+use crate::sftp::error::SftpError;
+use crate::sftp::extension::{self, Extensions};
 use crate::sftp::file::{File, FileId, FileRequest};
-use crate::sftp::{OpenWithMode, SftpChannelResult, SftpRequest};
+use crate::sftp::types::{FilesystemStats, Limits, OwnerNames};
+use crate::sftp::{
+    OpenWithMode, ResolveOwnerNames, SftpChannelError, SftpChannelResult, SftpRequest,
+};
 use crate::sftpwrap::SftpWrap;
 use anyhow::{anyhow, Context};
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use filedescriptor::{
     poll, pollfd, socketpair, AsRawSocketDescriptor, FileDescriptor, POLLIN, POLLOUT,
 };
@@ -43,13 +48,19 @@ pub(crate) struct SessionInner {
     pub tx_event: Sender<SessionEvent>,
     pub rx_req: Receiver<SessionRequest>,
     pub channels: HashMap<ChannelId, ChannelInfo>,
-    pub files: HashMap<FileId, FileWrap>,
+    pub files: HashMap<FileId, PipelinedFile>,
     pub dirs: HashMap<DirId, DirWrap>,
     pub next_channel_id: ChannelId,
     pub next_file_id: FileId,
     pub sender_read: FileDescriptor,
     pub session_was_dropped: bool,
     pub shown_accept_env_error: bool,
+    /// The sftp extensions advertised by the server, which are
+    /// negotiated when the sftp channel is opened
+    pub sftp_extensions: Option<Extensions>,
+    /// The channel on which `sftp_extensions` were negotiated, which is
+    /// kept open to make the requests of the extensions
+    pub sftp_extension_channel: Option<ChannelWrap>,
 }
 
 impl Drop for SessionInner {
@@ -631,8 +642,7 @@ impl SessionInner {
                                     .files
                                     .get_mut(&msg.file_id)
                                     .ok_or_else(|| anyhow!("invalid file_id"))?;
-                                file.writer().write_all(&msg.data)?;
-                                Ok(())
+                                file.write(&msg.data, msg.window)
                             },
                             "write_file",
                         )
@@ -645,12 +655,7 @@ impl SessionInner {
                                     .files
                                     .get_mut(&msg.file_id)
                                     .ok_or_else(|| anyhow!("invalid file_id"))?;
-
-                                // TODO: Move this somewhere to avoid re-allocating buffer
-                                let mut buf = vec![0u8; msg.max_bytes];
-                                let n = file.reader().read(&mut buf)?;
-                                buf.truncate(n);
-                                Ok(buf)
+                                file.read(msg.max_bytes, msg.window)
                             },
                             "read_file",
                         )
@@ -658,9 +663,9 @@ impl SessionInner {
                     SessionRequest::Sftp(SftpRequest::File(FileRequest::Close(file_id, reply))) => {
                         dispatch(
                             reply,
-                            || {
-                                self.files.remove(&file_id);
-                                Ok(())
+                            || match self.files.remove(&file_id) {
+                                Some(mut file) => file.flush(),
+                                None => Ok(()),
                             },
                             "close_file",
                         )
@@ -690,6 +695,21 @@ impl SessionInner {
                             "read_dir",
                         )
                     }
+                    SessionRequest::Sftp(SftpRequest::Dir(DirRequest::ReadDirBatch(
+                        dir_id,
+                        max,
+                        reply,
+                    ))) => dispatch(
+                        reply,
+                        || {
+                            let dir = self
+                                .dirs
+                                .get_mut(&dir_id)
+                                .ok_or_else(|| anyhow!("invalid dir_id"))?;
+                            dir.read_dir_batch(max)
+                        },
+                        "read_dir_batch",
+                    ),
                     SessionRequest::Sftp(SftpRequest::File(FileRequest::Flush(file_id, reply))) => {
                         dispatch(
                             reply,
@@ -698,8 +718,7 @@ impl SessionInner {
                                     .files
                                     .get_mut(&file_id)
                                     .ok_or_else(|| anyhow!("invalid file_id"))?;
-                                file.writer().flush()?;
-                                Ok(())
+                                file.flush()
                             },
                             "flush_file",
                         )
@@ -714,7 +733,7 @@ impl SessionInner {
                                 .files
                                 .get_mut(&msg.file_id)
                                 .ok_or_else(|| anyhow!("invalid file_id"))?;
-                            file.set_metadata(msg.metadata)
+                            file.file()?.set_metadata(msg.metadata)
                         },
                         "set_metadata_file",
                     ),
@@ -728,7 +747,7 @@ impl SessionInner {
                                 .files
                                 .get_mut(&file_id)
                                 .ok_or_else(|| anyhow!("invalid file_id"))?;
-                            file.metadata()
+                            file.file()?.metadata()
                         },
                         "metadata_file",
                     ),
@@ -736,15 +755,37 @@ impl SessionInner {
                         dispatch(
                             reply,
                             || {
+                                let supported = self
+                                    .sftp_extensions
+                                    .as_ref()
+                                    .map_or(false, |ext| ext.supports(extension::FSYNC));
+                                if !supported {
+                                    return Err(SftpError::OpUnsupported.into());
+                                }
                                 let file = self
                                     .files
                                     .get_mut(&file_id)
                                     .ok_or_else(|| anyhow!("invalid file_id"))?;
-                                file.fsync()
+                                file.file()?.fsync()
                             },
                             "fsync",
                         )
                     }
+                    SessionRequest::Sftp(SftpRequest::File(FileRequest::Seek(
+                        file_id,
+                        offset,
+                        reply,
+                    ))) => dispatch(
+                        reply,
+                        || {
+                            let file = self
+                                .files
+                                .get_mut(&file_id)
+                                .ok_or_else(|| anyhow!("invalid file_id"))?;
+                            file.seek(offset)
+                        },
+                        "seek_file",
+                    ),
 
                     SessionRequest::Sftp(SftpRequest::ReadDir(path, reply)) => {
                         dispatch(reply, || self.init_sftp(sess)?.read_dir(&path), "read_dir")
@@ -790,6 +831,9 @@ impl SessionInner {
                         || self.init_sftp(sess)?.canonicalize(&path),
                         "canonicalize",
                     ),
+                    SessionRequest::Sftp(SftpRequest::ExpandPath(path, reply)) => {
+                        dispatch(reply, || self.expand_path(sess, &path), "expand_path")
+                    }
                     SessionRequest::Sftp(SftpRequest::Rename(msg, reply)) => dispatch(
                         reply,
                         || self.init_sftp(sess)?.rename(&msg.src, &msg.dst, msg.opts),
@@ -798,6 +842,40 @@ impl SessionInner {
                     SessionRequest::Sftp(SftpRequest::RemoveFile(path, reply)) => {
                         dispatch(reply, || self.init_sftp(sess)?.unlink(&path), "remove_file")
                     }
+                    SessionRequest::Sftp(SftpRequest::Limits(reply)) => {
+                        dispatch(reply, || self.limits(sess), "limits")
+                    }
+                    SessionRequest::Sftp(SftpRequest::SupportsExtension(name, reply)) => dispatch(
+                        reply,
+                        || self.supports_extension(sess, &name),
+                        "supports_extension",
+                    ),
+                    SessionRequest::Sftp(SftpRequest::Statvfs(path, reply)) => {
+                        dispatch(reply, || self.statvfs(sess, &path), "statvfs")
+                    }
+                    SessionRequest::Sftp(SftpRequest::Hardlink(msg, reply)) => dispatch(
+                        reply,
+                        || self.hardlink(sess, &msg.src, &msg.dst),
+                        "hardlink",
+                    ),
+                    SessionRequest::Sftp(SftpRequest::PosixRename(msg, reply)) => dispatch(
+                        reply,
+                        || self.posix_rename(sess, &msg.src, &msg.dst),
+                        "posix_rename",
+                    ),
+                    SessionRequest::Sftp(SftpRequest::CheckFile(msg, reply)) => dispatch(
+                        reply,
+                        || self.check_file(sess, &msg.path, msg.algorithm.name()),
+                        "check_file",
+                    ),
+                    SessionRequest::Sftp(SftpRequest::CopyData(msg, reply)) => dispatch(
+                        reply,
+                        || self.copy_data(sess, &msg.src, &msg.dst),
+                        "copy_data",
+                    ),
+                    SessionRequest::Sftp(SftpRequest::ResolveOwnerNames(msg, reply)) => {
+                        dispatch(reply, || self.owner_names(sess, &msg), "owner_names")
+                    }
                 };
                 sess.set_blocking(false);
                 res
@@ -903,10 +981,28 @@ impl SessionInner {
 
         let file = File::new(file_id);
 
-        self.files.insert(file_id, ssh_file);
+        let depth = self.sftp_pipeline_depth();
+        let limits = self.sftp_extensions.as_ref().and_then(|ext| ext.limits);
+        self.files
+            .insert(file_id, PipelinedFile::new(ssh_file, depth, limits));
         Ok(file)
     }
 
+    /// The number of read or write requests to keep outstanding for
+    /// each file, from the `wezterm_sftp_pipeline_depth` option
+    fn sftp_pipeline_depth(&self) -> usize {
+        match self.config.get("wezterm_sftp_pipeline_depth") {
+            Some(depth) => depth.parse().unwrap_or_else(|_| {
+                log::warn!(
+                    "invalid wezterm_sftp_pipeline_depth value: {}, expected a number",
+                    depth
+                );
+                DEFAULT_PIPELINE_DEPTH
+            }),
+            None => DEFAULT_PIPELINE_DEPTH,
+        }
+    }
+
     /// Helper to open a directory for reading its contents.
     pub fn open_dir(
         &mut self,
@@ -924,8 +1020,172 @@ impl SessionInner {
         Ok(dir)
     }
 
+    /// Returns the limits that the server reported with the sftp limits
+    /// extension when the sftp session was started
+    fn limits(&mut self, sess: &mut SessionWrap) -> SftpChannelResult<Limits> {
+        self.init_sftp(sess)?;
+        self.sftp_extensions
+            .as_ref()
+            .and_then(|ext| ext.limits)
+            .ok_or_else(|| SftpError::OpUnsupported.into())
+    }
+
+    /// Returns true if the server advertised the sftp extension `name`
+    /// when the sftp session was started
+    fn supports_extension(
+        &mut self,
+        sess: &mut SessionWrap,
+        name: &str,
+    ) -> SftpChannelResult<bool> {
+        self.init_sftp(sess)?;
+        Ok(self
+            .sftp_extensions
+            .as_ref()
+            .map_or(false, |ext| ext.supports(name)))
+    }
+
+    /// Queries the filesystem containing `path` with the sftp statvfs
+    /// extension
+    fn statvfs(
+        &mut self,
+        sess: &mut SessionWrap,
+        path: &Utf8Path,
+    ) -> SftpChannelResult<FilesystemStats> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::statvfs(channel, extensions, path.as_str())
+        })
+    }
+
+    /// Resolves `path`, expanding a leading `~`, with the sftp
+    /// expand-path extension
+    fn expand_path(
+        &mut self,
+        sess: &mut SessionWrap,
+        path: &Utf8Path,
+    ) -> SftpChannelResult<Utf8PathBuf> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::expand_path(channel, extensions, path.as_str()).map(Utf8PathBuf::from)
+        })
+    }
+
+    /// Creates `dst` as a hard link to `src` with the sftp hardlink
+    /// extension
+    fn hardlink(
+        &mut self,
+        sess: &mut SessionWrap,
+        src: &Utf8Path,
+        dst: &Utf8Path,
+    ) -> SftpChannelResult<()> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::hardlink(channel, extensions, src.as_str(), dst.as_str())
+        })
+    }
+
+    /// Renames `src` to `dst`, replacing `dst`, with the sftp
+    /// posix-rename extension
+    fn posix_rename(
+        &mut self,
+        sess: &mut SessionWrap,
+        src: &Utf8Path,
+        dst: &Utf8Path,
+    ) -> SftpChannelResult<()> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::posix_rename(channel, extensions, src.as_str(), dst.as_str())
+        })
+    }
+
+    /// Has the server hash `path` with the sftp check-file extensions
+    fn check_file(
+        &mut self,
+        sess: &mut SessionWrap,
+        path: &Utf8Path,
+        algorithm: &str,
+    ) -> SftpChannelResult<Vec<u8>> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::check_file(channel, extensions, path.as_str(), algorithm)
+        })
+    }
+
+    /// Has the server copy the data of `src` to `dst` with the sftp
+    /// copy-data extension
+    fn copy_data(
+        &mut self,
+        sess: &mut SessionWrap,
+        src: &Utf8Path,
+        dst: &Utf8Path,
+    ) -> SftpChannelResult<()> {
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::copy_data(channel, extensions, src.as_str(), dst.as_str())
+        })
+    }
+
+    /// Looks up the names of the owners and groups of `msg` with the
+    /// sftp users-groups-by-id extension, or from the long names of the
+    /// entries of its directories
+    fn owner_names(
+        &mut self,
+        sess: &mut SessionWrap,
+        msg: &ResolveOwnerNames,
+    ) -> SftpChannelResult<OwnerNames> {
+        let dirs: Vec<&str> = msg.dirs.iter().map(|dir| dir.as_str()).collect();
+        self.with_sftp_extensions(sess, |channel, extensions| {
+            extension::owner_names(channel, extensions, &msg.uids, &msg.gids, &dirs)
+        })
+    }
+
+    /// Runs `f` on the channel that is kept open for the sftp extensions,
+    /// along with the extensions that the server advertised on it,
+    /// opening the channel again if an earlier request broke it
+    fn with_sftp_extensions<T, F>(&mut self, sess: &mut SessionWrap, f: F) -> SftpChannelResult<T>
+    where
+        F: FnOnce(&mut ChannelWrap, &Extensions) -> SftpChannelResult<T>,
+    {
+        self.init_sftp(sess)?;
+        if self.sftp_extension_channel.is_none() {
+            let (channel, extensions) = open_sftp_extension_channel(sess)?;
+            self.sftp_extension_channel.replace(channel);
+            self.sftp_extensions.replace(extensions);
+        }
+        let channel = self
+            .sftp_extension_channel
+            .as_mut()
+            .expect("sftp extension channel should have been opened above");
+        let extensions = self
+            .sftp_extensions
+            .as_ref()
+            .expect("sftp extensions are negotiated along with the channel");
+
+        let result = f(channel, extensions);
+        match &result {
+            // The server refused the request, but is still in step
+            // with the channel
+            Err(SftpChannelError::Sftp(err)) if *err != SftpError::BadMessage => {}
+            Err(_) => {
+                if let Some(mut channel) = self.sftp_extension_channel.take() {
+                    channel.close();
+                }
+            }
+            Ok(_) => {}
+        }
+        result
+    }
+
     /// Initialize the sftp channel if not already created, returning a mutable reference to it
     fn init_sftp<'a>(&mut self, sess: &'a mut SessionWrap) -> SftpChannelResult<&'a mut SftpWrap> {
+        if self.sftp_extensions.is_none() {
+            let extensions = match open_sftp_extension_channel(sess) {
+                Ok((channel, extensions)) => {
+                    self.sftp_extension_channel.replace(channel);
+                    extensions
+                }
+                Err(err) => {
+                    log::warn!("unable to negotiate sftp extensions: {:#}", err);
+                    Extensions::default()
+                }
+            };
+            self.sftp_extensions.replace(extensions);
+        }
+
         match sess {
             #[cfg(feature = "ssh2")]
             SessionWrap::Ssh2(sess) => {
@@ -946,6 +1206,26 @@ impl SessionInner {
     }
 }
 
+/// Opens a channel of its own running the sftp subsystem, for the
+/// requests that the ssh libraries don't expose, returning it along
+/// with the extensions that the server advertised on it
+fn open_sftp_extension_channel(
+    sess: &mut SessionWrap,
+) -> SftpChannelResult<(ChannelWrap, Extensions)> {
+    let mut channel = sess.open_session()?;
+    match channel
+        .request_subsystem("sftp")
+        .map_err(SftpChannelError::from)
+        .and_then(|()| extension::startup(&mut channel))
+    {
+        Ok(extensions) => Ok((channel, extensions)),
+        Err(err) => {
+            channel.close();
+            Err(err)
+        }
+    }
+}
+
 fn write_from_buf<W: Write>(w: &mut W, buf: &mut VecDeque<u8>) -> std::io::Result<()> {
     match w.write(buf.make_contiguous()) {
         Ok(len) => {
@@ -0,0 +1,145 @@
+use super::error::SftpError;
+use super::{
+    into_invalid_data, CheckFile, SessionRequest, Sftp, SftpChannelError, SftpChannelResult,
+    SftpRequest,
+};
+use camino::Utf8PathBuf;
+use sha2::digest::DynDigest;
+use smol::channel::bounded;
+use smol::io::{AsyncRead, AsyncReadExt};
+use std::convert::TryInto;
+use std::io;
+
+/// The size of the reads of the data that is hashed locally
+const DIGEST_BUF_SIZE: usize = 64 * 1024;
+
+/// The hash algorithms of [`Sftp::checksum`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Returns the name of the algorithm in the sftp check-file extensions
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    fn hasher(self) -> Box<dyn DynDigest + Send> {
+        match self {
+            Self::Sha1 => Box::new(sha1::Sha1::default()),
+            Self::Sha256 => Box::new(sha2::Sha256::default()),
+            Self::Sha384 => Box::new(sha2::Sha384::default()),
+            Self::Sha512 => Box::new(sha2::Sha512::default()),
+        }
+    }
+
+    /// Computes the digest of the data of `reader`, such as a local
+    /// file that is compared with the checksum of a remote one
+    pub async fn digest<R>(self, reader: &mut R) -> io::Result<Vec<u8>>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut hasher = self.hasher();
+        let mut buf = vec![0u8; DIGEST_BUF_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hasher.finalize().into_vec())
+    }
+}
+
+impl Sftp {
+    /// Computes the digest of the remote file `path` with `algorithm`,
+    /// to verify that a transfer produced the same data.
+    ///
+    /// The server hashes the file if it supports the `check-file-name`
+    /// or `check-file-handle` extension along with `algorithm`.
+    /// Otherwise, as with OpenSSH, the data of the file is read and
+    /// hashed locally, which transfers all of it.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// use wezterm_ssh::HashAlgorithm;
+    ///
+    /// let remote = sftp.checksum("/srv/backup.tar", HashAlgorithm::Sha256).await?;
+    /// let mut local = smol::fs::File::open("backup.tar").await?;
+    /// assert_eq!(remote, HashAlgorithm::Sha256.digest(&mut local).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn checksum<T, E>(
+        &self,
+        path: T,
+        algorithm: HashAlgorithm,
+    ) -> SftpChannelResult<Vec<u8>>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let path: Utf8PathBuf = path.try_into().map_err(into_invalid_data)?;
+
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::CheckFile(
+                CheckFile {
+                    path: path.clone(),
+                    algorithm,
+                },
+                reply,
+            )))
+            .await?;
+        match rx.recv().await? {
+            Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => {
+                log::debug!(
+                    "server can't hash {} with {}, hashing it locally",
+                    path,
+                    algorithm.name()
+                );
+            }
+            result => return result,
+        }
+
+        let mut file = self.open(path).await?;
+        Ok(algorithm.digest(&mut file).await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn local_digest() {
+        smol::block_on(async {
+            let digest = HashAlgorithm::Sha1.digest(&mut &b"abc"[..]).await.unwrap();
+            assert_eq!(hex(&digest), "a9993e364706816aba3e25717850c26c9cd0d89d");
+            let digest = HashAlgorithm::Sha256.digest(&mut &b""[..]).await.unwrap();
+            assert_eq!(
+                hex(&digest),
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            );
+            let digest = HashAlgorithm::Sha512
+                .digest(&mut &b"abc"[..])
+                .await
+                .unwrap();
+            assert_eq!(digest.len(), 64);
+        });
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
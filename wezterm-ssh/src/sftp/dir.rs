@@ -21,6 +21,11 @@ impl fmt::Debug for Dir {
 pub(crate) enum DirRequest {
     Close(DirId, Sender<SftpChannelResult<()>>),
     ReadDir(DirId, Sender<SftpChannelResult<(Utf8PathBuf, Metadata)>>),
+    ReadDirBatch(
+        DirId,
+        usize,
+        Sender<SftpChannelResult<Vec<(Utf8PathBuf, Metadata)>>>,
+    ),
 }
 
 impl Drop for Dir {
@@ -69,4 +74,26 @@ impl Dir {
         let result = rx.recv().await??;
         Ok(result)
     }
+
+    /// Reads up to `max` entries in a single request, skipping `.` and
+    /// `..`.  Fewer entries are returned only once the end of the
+    /// directory has been reached, rather than failing like `read_dir`.
+    ///
+    /// As with `read_dir`, the returned paths are the filenames of the
+    /// files in this directory.
+    pub(crate) async fn read_dir_batch(
+        &self,
+        max: usize,
+    ) -> SftpChannelResult<Vec<(Utf8PathBuf, Metadata)>> {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .as_ref()
+            .unwrap()
+            .send(SessionRequest::Sftp(SftpRequest::Dir(
+                DirRequest::ReadDirBatch(self.dir_id, max, reply),
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
 }
@@ -0,0 +1,975 @@
+//! A minimal client for the sftp protocol version negotiation and the
+//! `limits@openssh.com`, `statvfs@openssh.com`, `hardlink@openssh.com`,
+//! `posix-rename@openssh.com`, `expand-path@openssh.com`,
+//! `users-groups-by-id@openssh.com`, `check-file-name`,
+//! `check-file-handle` and `copy-data` extensions, along with the
+//! directory listings that stand in for the latter's lookups.
+//!
+//! Neither libssh2 nor libssh expose the extensions advertised by the
+//! server, nor these extensions, through their rust bindings, so the
+//! requests are made on a dedicated channel running the sftp subsystem.
+//! The channel is negotiated once, when the sftp session is started, and
+//! is kept open for the requests that follow, each of which is answered
+//! before the next is made.
+use super::error::SftpError;
+use super::types::{FilesystemStats, Limits, OwnerNames};
+use super::{into_invalid_data, SftpChannelError, SftpChannelResult};
+use crate::channelwrap::ChannelWrap;
+use std::io::{Read, Write};
+
+pub(crate) const STATVFS: &str = "statvfs@openssh.com";
+pub(crate) const FSYNC: &str = "fsync@openssh.com";
+pub(crate) const HARDLINK: &str = "hardlink@openssh.com";
+pub(crate) const POSIX_RENAME: &str = "posix-rename@openssh.com";
+pub(crate) const LIMITS: &str = "limits@openssh.com";
+pub(crate) const EXPAND_PATH: &str = "expand-path@openssh.com";
+pub(crate) const USERS_GROUPS_BY_ID: &str = "users-groups-by-id@openssh.com";
+pub(crate) const CHECK_FILE_NAME: &str = "check-file-name";
+pub(crate) const CHECK_FILE_HANDLE: &str = "check-file-handle";
+pub(crate) const COPY_DATA: &str = "copy-data";
+/// The name of the replies to both check-file extensions
+const CHECK_FILE: &str = "check-file";
+const SFTP_VERSION: u32 = 3;
+/// Replies are small; anything larger than this is garbage
+const MAX_PACKET_LEN: usize = 256 * 1024;
+const REQUEST_ID: u32 = 1;
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_OPEN: u8 = 3;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_EXTENDED: u8 = 200;
+const SSH_FXP_EXTENDED_REPLY: u8 = 201;
+
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+
+const SSH_FILEXFER_ATTR_SIZE: u32 = 0x1;
+const SSH_FILEXFER_ATTR_UIDGID: u32 = 0x2;
+const SSH_FILEXFER_ATTR_PERMISSIONS: u32 = 0x4;
+const SSH_FILEXFER_ATTR_ACMODTIME: u32 = 0x8;
+const SSH_FILEXFER_ATTR_EXTENDED: u32 = 0x8000_0000;
+
+const SSH_FXF_READ: u32 = 0x1;
+const SSH_FXF_WRITE: u32 = 0x2;
+const SSH_FXF_CREAT: u32 = 0x8;
+const SSH_FXF_TRUNC: u32 = 0x10;
+
+/// The extensions advertised by the server
+#[derive(Debug, Default)]
+pub(crate) struct Extensions {
+    names: Vec<String>,
+    /// The limits of the server, if it reports them
+    pub limits: Option<Limits>,
+}
+
+impl Extensions {
+    pub fn supports(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n == name)
+    }
+}
+
+impl OwnerNames {
+    /// Records the owner and group named in `long_name`, the line that
+    /// the server lists for a directory entry in the style of `ls -l`,
+    /// for the `uid` and `gid` in the attributes of the entry
+    fn add_long_name(&mut self, long_name: &str, uid: u32, gid: u32) {
+        let mut fields = long_name.split_whitespace().skip(2);
+        let (owner, group) = match (fields.next(), fields.next()) {
+            (Some(owner), Some(group)) => (owner, group),
+            _ => return,
+        };
+        // Servers list the ids of unknown users and groups instead
+        if owner != uid.to_string() {
+            self.users.entry(uid).or_insert_with(|| owner.to_string());
+        }
+        if group != gid.to_string() {
+            self.groups.entry(gid).or_insert_with(|| group.to_string());
+        }
+    }
+}
+
+/// Negotiates the protocol version on a channel that has just started
+/// the sftp subsystem, returning the extensions advertised by the server
+fn negotiate(chan: &mut ChannelWrap) -> SftpChannelResult<Extensions> {
+    chan.writer().write_all(&init_packet())?;
+    let version = read_packet(&mut chan.reader(0))?;
+    parse_version(&version)
+}
+
+/// Negotiates the protocol version as `negotiate` does, and queries
+/// the limits of the server if it advertises them, making `chan` ready
+/// for the requests below
+pub(crate) fn startup(chan: &mut ChannelWrap) -> SftpChannelResult<Extensions> {
+    let mut extensions = negotiate(chan)?;
+    if extensions.supports(LIMITS) {
+        chan.writer()
+            .write_all(&extended_packet(REQUEST_ID, LIMITS, &[]))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        match parse_limits_reply(&reply, REQUEST_ID) {
+            Ok(limits) => extensions.limits = Some(limits),
+            Err(err) => log::warn!("unable to query sftp limits: {:#}", err),
+        }
+    }
+    Ok(extensions)
+}
+
+/// Queries the filesystem containing `path` on `chan`, which has
+/// negotiated `extensions`
+pub(crate) fn statvfs(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    path: &str,
+) -> SftpChannelResult<FilesystemStats> {
+    if !extensions.supports(STATVFS) {
+        return Err(SftpError::OpUnsupported.into());
+    }
+
+    chan.writer()
+        .write_all(&extended_packet(REQUEST_ID, STATVFS, &[path]))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    parse_statvfs_reply(&reply, REQUEST_ID)
+}
+
+/// Creates `dst` as a hard link to the existing file `src` on `chan`,
+/// which has negotiated `extensions`
+pub(crate) fn hardlink(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    src: &str,
+    dst: &str,
+) -> SftpChannelResult<()> {
+    status_request(chan, extensions, HARDLINK, &[src, dst])
+}
+
+/// Renames `src` to `dst`, atomically replacing `dst` if it exists,
+/// on `chan`, which has negotiated `extensions`
+pub(crate) fn posix_rename(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    src: &str,
+    dst: &str,
+) -> SftpChannelResult<()> {
+    status_request(chan, extensions, POSIX_RENAME, &[src, dst])
+}
+
+/// Has the server expand a leading `~` or `~user` in `path` and make
+/// it absolute, on `chan`, which has negotiated `extensions`
+pub(crate) fn expand_path(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    path: &str,
+) -> SftpChannelResult<String> {
+    if !extensions.supports(EXPAND_PATH) {
+        return Err(SftpError::OpUnsupported.into());
+    }
+
+    chan.writer()
+        .write_all(&extended_packet(REQUEST_ID, EXPAND_PATH, &[path]))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    parse_name_reply(&reply, REQUEST_ID)
+}
+
+/// Looks up the names of the users `uids` and the groups `gids` on
+/// `chan`, which has negotiated `extensions`.  Servers without the
+/// users-groups-by-id extension are asked to list `dirs` instead, and
+/// the names are taken from the long names of their entries.
+pub(crate) fn owner_names(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    uids: &[u32],
+    gids: &[u32],
+    dirs: &[&str],
+) -> SftpChannelResult<OwnerNames> {
+    if extensions.supports(USERS_GROUPS_BY_ID) {
+        chan.writer()
+            .write_all(&users_groups_by_id_packet(REQUEST_ID, uids, gids))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        return parse_users_groups_reply(&reply, REQUEST_ID, uids, gids);
+    }
+
+    let mut names = OwnerNames::default();
+    let mut id = REQUEST_ID;
+    for dir in dirs {
+        chan.writer()
+            .write_all(&string_packet(SSH_FXP_OPENDIR, id, dir.as_bytes()))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        let handle = match parse_handle_reply(&reply, id) {
+            Ok(handle) => handle,
+            // The owners of the entries of a directory that can't be
+            // listed are left unknown
+            Err(SftpChannelError::Sftp(_)) => {
+                id += 1;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        loop {
+            id += 1;
+            chan.writer()
+                .write_all(&string_packet(SSH_FXP_READDIR, id, &handle))?;
+            let reply = read_packet(&mut chan.reader(0))?;
+            match parse_readdir_reply(&reply, id)? {
+                Some(entries) => {
+                    for (long_name, uid, gid) in entries {
+                        names.add_long_name(&long_name, uid, gid);
+                    }
+                }
+                None => break,
+            }
+        }
+        id += 1;
+        close_handle(chan, id, &handle)?;
+        id += 1;
+    }
+    Ok(names)
+}
+
+/// Has the server hash the whole of the file `path` with `algorithm`,
+/// using the check-file extensions, on `chan`, which has negotiated
+/// `extensions`, returning the digest
+pub(crate) fn check_file(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    path: &str,
+    algorithm: &str,
+) -> SftpChannelResult<Vec<u8>> {
+    if extensions.supports(CHECK_FILE_NAME) {
+        chan.writer().write_all(&check_file_packet(
+            REQUEST_ID,
+            CHECK_FILE_NAME,
+            path.as_bytes(),
+            algorithm,
+        ))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        parse_check_file_reply(&reply, REQUEST_ID, algorithm)
+    } else if extensions.supports(CHECK_FILE_HANDLE) {
+        chan.writer()
+            .write_all(&open_packet(REQUEST_ID, path, SSH_FXF_READ))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        let handle = parse_handle_reply(&reply, REQUEST_ID)?;
+        let id = REQUEST_ID + 1;
+        chan.writer().write_all(&check_file_packet(
+            id,
+            CHECK_FILE_HANDLE,
+            &handle,
+            algorithm,
+        ))?;
+        let reply = read_packet(&mut chan.reader(0))?;
+        let digest = parse_check_file_reply(&reply, id, algorithm);
+        close_handle(chan, id + 1, &handle)?;
+        digest
+    } else {
+        Err(SftpError::OpUnsupported.into())
+    }
+}
+
+/// Has the server copy the data of the file `src` to `dst`, which is
+/// created or truncated, using the copy-data extension, on `chan`,
+/// which has negotiated `extensions`
+pub(crate) fn copy_data(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    src: &str,
+    dst: &str,
+) -> SftpChannelResult<()> {
+    if !extensions.supports(COPY_DATA) {
+        return Err(SftpError::OpUnsupported.into());
+    }
+
+    chan.writer()
+        .write_all(&open_packet(REQUEST_ID, src, SSH_FXF_READ))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    let read_handle = parse_handle_reply(&reply, REQUEST_ID)?;
+
+    let id = REQUEST_ID + 1;
+    chan.writer().write_all(&open_packet(
+        id,
+        dst,
+        SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC,
+    ))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    let write_handle = match parse_handle_reply(&reply, id) {
+        Ok(handle) => handle,
+        Err(err) => {
+            close_handle(chan, id + 1, &read_handle)?;
+            return Err(err);
+        }
+    };
+
+    let id = id + 1;
+    chan.writer()
+        .write_all(&copy_data_packet(id, &read_handle, &write_handle))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    let copied = parse_status_reply(&reply, id);
+    close_handle(chan, id + 1, &read_handle)?;
+    close_handle(chan, id + 2, &write_handle)?;
+    copied
+}
+
+/// Closes `handle`, which has to be done explicitly as the channel
+/// outlives the request that opened it
+fn close_handle(chan: &mut ChannelWrap, id: u32, handle: &[u8]) -> SftpChannelResult<()> {
+    chan.writer()
+        .write_all(&string_packet(SSH_FXP_CLOSE, id, handle))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    parse_status_reply(&reply, id)
+}
+
+/// Makes the request of an extension that replies with just a status
+fn status_request(
+    chan: &mut ChannelWrap,
+    extensions: &Extensions,
+    name: &str,
+    args: &[&str],
+) -> SftpChannelResult<()> {
+    if !extensions.supports(name) {
+        return Err(SftpError::OpUnsupported.into());
+    }
+
+    chan.writer()
+        .write_all(&extended_packet(REQUEST_ID, name, args))?;
+    let reply = read_packet(&mut chan.reader(0))?;
+    parse_status_reply(&reply, REQUEST_ID)
+}
+
+fn packet(ty: u8, payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(payload.len() + 5);
+    packet.extend_from_slice(&(payload.len() as u32 + 1).to_be_bytes());
+    packet.push(ty);
+    packet.extend_from_slice(payload);
+    packet
+}
+
+fn put_string(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    buf.extend_from_slice(s);
+}
+
+fn init_packet() -> Vec<u8> {
+    packet(SSH_FXP_INIT, &SFTP_VERSION.to_be_bytes())
+}
+
+fn extended_packet(id: u32, name: &str, args: &[&str]) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, name.as_bytes());
+    for arg in args {
+        put_string(&mut payload, arg.as_bytes());
+    }
+    packet(SSH_FXP_EXTENDED, &payload)
+}
+
+fn open_packet(id: u32, path: &str, flags: u32) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, path.as_bytes());
+    payload.extend_from_slice(&flags.to_be_bytes());
+    // No attributes
+    payload.extend_from_slice(&0u32.to_be_bytes());
+    packet(SSH_FXP_OPEN, &payload)
+}
+
+/// Makes a request whose only argument is `s`, such as a path or handle
+fn string_packet(ty: u8, id: u32, s: &[u8]) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, s);
+    packet(ty, &payload)
+}
+
+/// Requests the names of the users and groups with the ids `uids` and
+/// `gids`, which are each sent as a string packed with the ids
+fn users_groups_by_id_packet(id: u32, uids: &[u32], gids: &[u32]) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, USERS_GROUPS_BY_ID.as_bytes());
+    for ids in [uids, gids] {
+        let packed: Vec<u8> = ids
+            .iter()
+            .flat_map(|owner_id| owner_id.to_be_bytes())
+            .collect();
+        put_string(&mut payload, &packed);
+    }
+    packet(SSH_FXP_EXTENDED, &payload)
+}
+
+/// Requests the digest of the whole of the file named, or opened as,
+/// `target`, as a single block
+fn check_file_packet(id: u32, name: &str, target: &[u8], algorithm: &str) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, name.as_bytes());
+    put_string(&mut payload, target);
+    put_string(&mut payload, algorithm.as_bytes());
+    // The start offset, the length, where 0 means up to the end of the
+    // file, and the block size, where 0 means the whole range
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    payload.extend_from_slice(&0u32.to_be_bytes());
+    packet(SSH_FXP_EXTENDED, &payload)
+}
+
+/// Requests the copy of the whole of the file opened as `read_handle`
+/// to the start of the one opened as `write_handle`
+fn copy_data_packet(id: u32, read_handle: &[u8], write_handle: &[u8]) -> Vec<u8> {
+    let mut payload = id.to_be_bytes().to_vec();
+    put_string(&mut payload, COPY_DATA.as_bytes());
+    put_string(&mut payload, read_handle);
+    // The read offset and the length, where 0 means up to the end of
+    // the file
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    put_string(&mut payload, write_handle);
+    // The write offset
+    payload.extend_from_slice(&0u64.to_be_bytes());
+    packet(SSH_FXP_EXTENDED, &payload)
+}
+
+/// Reads a packet, returning its type followed by its payload
+fn read_packet(r: &mut dyn Read) -> SftpChannelResult<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len == 0 || len > MAX_PACKET_LEN {
+        return Err(SftpError::BadMessage.into());
+    }
+    let mut packet = vec![0u8; len];
+    r.read_exact(&mut packet)?;
+    Ok(packet)
+}
+
+/// Decodes the fields of a packet
+struct Decoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> SftpChannelResult<&'a [u8]> {
+        if self.buf.len() < n {
+            return Err(SftpError::BadMessage.into());
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> SftpChannelResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> SftpChannelResult<u32> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> SftpChannelResult<u64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn string(&mut self) -> SftpChannelResult<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// Decodes file attributes, returning just the uid and gid
+    fn attrs_owner(&mut self) -> SftpChannelResult<Option<(u32, u32)>> {
+        let flags = self.u32()?;
+        let mut owner = None;
+        if flags & SSH_FILEXFER_ATTR_SIZE != 0 {
+            self.u64()?;
+        }
+        if flags & SSH_FILEXFER_ATTR_UIDGID != 0 {
+            owner = Some((self.u32()?, self.u32()?));
+        }
+        if flags & SSH_FILEXFER_ATTR_PERMISSIONS != 0 {
+            self.u32()?;
+        }
+        if flags & SSH_FILEXFER_ATTR_ACMODTIME != 0 {
+            self.u32()?;
+            self.u32()?;
+        }
+        if flags & SSH_FILEXFER_ATTR_EXTENDED != 0 {
+            for _ in 0..self.u32()? {
+                self.string()?;
+                self.string()?;
+            }
+        }
+        Ok(owner)
+    }
+}
+
+/// Returns the extensions listed in an SSH_FXP_VERSION packet
+fn parse_version(packet: &[u8]) -> SftpChannelResult<Extensions> {
+    let mut decoder = Decoder { buf: packet };
+    if decoder.u8()? != SSH_FXP_VERSION {
+        return Err(SftpError::BadMessage.into());
+    }
+    decoder.u32()?;
+    let mut extensions = Extensions::default();
+    while !decoder.buf.is_empty() {
+        let name = decoder.string()?;
+        decoder.string()?;
+        extensions
+            .names
+            .push(String::from_utf8_lossy(name).into_owned());
+    }
+    Ok(extensions)
+}
+
+fn parse_limits_reply(packet: &[u8], id: u32) -> SftpChannelResult<Limits> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_EXTENDED_REPLY => Ok(Limits {
+            max_packet_len: decoder.u64()?,
+            max_read_len: decoder.u64()?,
+            max_write_len: decoder.u64()?,
+            max_open_handles: decoder.u64()?,
+        }),
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+fn parse_statvfs_reply(packet: &[u8], id: u32) -> SftpChannelResult<FilesystemStats> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_EXTENDED_REPLY => Ok(FilesystemStats {
+            block_size: decoder.u64()?,
+            fragment_size: decoder.u64()?,
+            blocks: decoder.u64()?,
+            blocks_free: decoder.u64()?,
+            blocks_available: decoder.u64()?,
+            files: decoder.u64()?,
+            files_free: decoder.u64()?,
+            files_available: decoder.u64()?,
+            fsid: decoder.u64()?,
+            flags: decoder.u64()?,
+            max_name_len: decoder.u64()?,
+        }),
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+fn parse_handle_reply(packet: &[u8], id: u32) -> SftpChannelResult<Vec<u8>> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_HANDLE => Ok(decoder.string()?.to_vec()),
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+/// Returns the name in the SSH_FXP_NAME reply to a request that
+/// resolves a single path
+fn parse_name_reply(packet: &[u8], id: u32) -> SftpChannelResult<String> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_NAME => {
+            if decoder.u32()? != 1 {
+                return Err(SftpError::BadMessage.into());
+            }
+            // The long name and the attributes that follow are unused
+            let name = decoder.string()?.to_vec();
+            Ok(String::from_utf8(name).map_err(into_invalid_data)?)
+        }
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+/// Returns the names of `uids` and `gids` in the reply to a
+/// users-groups-by-id request, which holds a string packed with a name,
+/// or an empty string if it is unknown, for each of the ids in turn
+fn parse_users_groups_reply(
+    packet: &[u8],
+    id: u32,
+    uids: &[u32],
+    gids: &[u32],
+) -> SftpChannelResult<OwnerNames> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_EXTENDED_REPLY => {
+            let mut names = OwnerNames::default();
+            for (ids, map) in [(uids, &mut names.users), (gids, &mut names.groups)] {
+                let mut packed = Decoder {
+                    buf: decoder.string()?,
+                };
+                for &owner_id in ids {
+                    let name = packed.string()?;
+                    if !name.is_empty() {
+                        map.insert(owner_id, String::from_utf8_lossy(name).into_owned());
+                    }
+                }
+            }
+            Ok(names)
+        }
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+/// Returns the long name, uid and gid of the entries in the reply to
+/// an SSH_FXP_READDIR request that have an owner, or None once the end
+/// of the directory was reached
+fn parse_readdir_reply(
+    packet: &[u8],
+    id: u32,
+) -> SftpChannelResult<Option<Vec<(String, u32, u32)>>> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_NAME => {
+            let count = decoder.u32()?;
+            let mut entries = vec![];
+            for _ in 0..count {
+                decoder.string()?;
+                let long_name = String::from_utf8_lossy(decoder.string()?).into_owned();
+                if let Some((uid, gid)) = decoder.attrs_owner()? {
+                    entries.push((long_name, uid, gid));
+                }
+            }
+            Ok(Some(entries))
+        }
+        SSH_FXP_STATUS => match decoder.u32()? {
+            SSH_FX_EOF => Ok(None),
+            code => Err(status_error(code)),
+        },
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+/// Returns the digest in the reply to a check-file request, which must
+/// have been made with `algorithm` alone
+fn parse_check_file_reply(packet: &[u8], id: u32, algorithm: &str) -> SftpChannelResult<Vec<u8>> {
+    let mut decoder = Decoder { buf: packet };
+    let ty = decoder.u8()?;
+    if decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match ty {
+        SSH_FXP_EXTENDED_REPLY => {
+            if decoder.string()? != CHECK_FILE.as_bytes()
+                || decoder.string()? != algorithm.as_bytes()
+                || decoder.buf.is_empty()
+            {
+                return Err(SftpError::BadMessage.into());
+            }
+            Ok(decoder.buf.to_vec())
+        }
+        SSH_FXP_STATUS => Err(status_error(decoder.u32()?)),
+        _ => Err(SftpError::BadMessage.into()),
+    }
+}
+
+/// Checks the SSH_FXP_STATUS reply to a request that returns no data
+fn parse_status_reply(packet: &[u8], id: u32) -> SftpChannelResult<()> {
+    let mut decoder = Decoder { buf: packet };
+    if decoder.u8()? != SSH_FXP_STATUS || decoder.u32()? != id {
+        return Err(SftpError::BadMessage.into());
+    }
+    match decoder.u32()? {
+        SSH_FX_OK => Ok(()),
+        code => Err(status_error(code)),
+    }
+}
+
+fn status_error(code: u32) -> SftpChannelError {
+    SftpError::from_error_code(code as i32)
+        .unwrap_or(SftpError::Failure)
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn version_packet(extensions: &[(&str, &str)]) -> Vec<u8> {
+        let mut payload = SFTP_VERSION.to_be_bytes().to_vec();
+        for (name, data) in extensions {
+            put_string(&mut payload, name.as_bytes());
+            put_string(&mut payload, data.as_bytes());
+        }
+        packet(SSH_FXP_VERSION, &payload)
+    }
+
+    fn read(packet: &[u8]) -> Vec<u8> {
+        read_packet(&mut &packet[..]).unwrap()
+    }
+
+    #[test]
+    fn request() {
+        assert_eq!(init_packet(), b"\0\0\0\x05\x01\0\0\0\x03");
+        assert_eq!(
+            extended_packet(7, STATVFS, &["/tmp"]),
+            b"\0\0\0\x24\xc8\0\0\0\x07\0\0\0\x13statvfs@openssh.com\0\0\0\x04/tmp"
+        );
+        assert_eq!(
+            extended_packet(2, HARDLINK, &["a", "b"]),
+            b"\0\0\0\x27\xc8\0\0\0\x02\0\0\0\x14hardlink@openssh.com\0\0\0\x01a\0\0\0\x01b"
+        );
+    }
+
+    #[test]
+    fn advertised() {
+        let packet = version_packet(&[("posix-rename@openssh.com", "1"), (STATVFS, "2")]);
+        let extensions = parse_version(&read(&packet)).unwrap();
+        assert!(extensions.supports(STATVFS));
+        assert!(!extensions.supports(FSYNC));
+
+        let packet = version_packet(&[(FSYNC, "1")]);
+        let extensions = parse_version(&read(&packet)).unwrap();
+        assert!(!extensions.supports(STATVFS));
+        assert!(extensions.supports(FSYNC));
+        assert!(!extensions.supports(POSIX_RENAME));
+
+        assert!(!parse_version(&read(&version_packet(&[])))
+            .unwrap()
+            .supports(STATVFS));
+    }
+
+    #[test]
+    fn reply() {
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        for value in 1..=11u64 {
+            payload.extend_from_slice(&value.to_be_bytes());
+        }
+        let stats =
+            parse_statvfs_reply(&read(&packet(SSH_FXP_EXTENDED_REPLY, &payload)), 1).unwrap();
+        assert_eq!(stats.fragment_size, 2);
+        assert_eq!(stats.max_name_len, 11);
+        assert_eq!(stats.total_bytes(), 6);
+        assert_eq!(stats.available_bytes(), 10);
+        assert!(!stats.is_read_only());
+
+        // Truncated
+        payload.pop();
+        assert!(matches!(
+            parse_statvfs_reply(&read(&packet(SSH_FXP_EXTENDED_REPLY, &payload)), 1),
+            Err(SftpChannelError::Sftp(SftpError::BadMessage))
+        ));
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(
+            extended_packet(1, LIMITS, &[]),
+            b"\0\0\0\x1b\xc8\0\0\0\x01\0\0\0\x12limits@openssh.com"
+        );
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        for value in &[262144u64, 261120, 261120, 0] {
+            payload.extend_from_slice(&value.to_be_bytes());
+        }
+        assert_eq!(
+            parse_limits_reply(&read(&packet(SSH_FXP_EXTENDED_REPLY, &payload)), 1).unwrap(),
+            Limits {
+                max_packet_len: 262144,
+                max_read_len: 261120,
+                max_write_len: 261120,
+                max_open_handles: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn check_file() {
+        assert_eq!(
+            open_packet(1, "/a", SSH_FXF_READ),
+            b"\0\0\0\x13\x03\0\0\0\x01\0\0\0\x02/a\0\0\0\x01\0\0\0\0"
+        );
+        assert_eq!(
+            check_file_packet(1, CHECK_FILE_NAME, b"/a", "sha1"),
+            b"\0\0\0\x3a\xc8\0\0\0\x01\0\0\0\x0fcheck-file-name\0\0\0\x02/a\
+              \0\0\0\x04sha1\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0"
+        );
+
+        let mut payload = 2u32.to_be_bytes().to_vec();
+        put_string(&mut payload, b"handle");
+        assert_eq!(
+            parse_handle_reply(&read(&packet(SSH_FXP_HANDLE, &payload)), 2).unwrap(),
+            b"handle"
+        );
+
+        let mut payload = 2u32.to_be_bytes().to_vec();
+        put_string(&mut payload, CHECK_FILE.as_bytes());
+        put_string(&mut payload, b"sha256");
+        payload.extend_from_slice(&[0xab; 32]);
+        let reply = read(&packet(SSH_FXP_EXTENDED_REPLY, &payload));
+        assert_eq!(
+            parse_check_file_reply(&reply, 2, "sha256").unwrap(),
+            vec![0xab; 32]
+        );
+        // The server used a different algorithm
+        assert!(matches!(
+            parse_check_file_reply(&reply, 2, "sha1"),
+            Err(SftpChannelError::Sftp(SftpError::BadMessage))
+        ));
+    }
+
+    #[test]
+    fn expand_path() {
+        assert_eq!(
+            extended_packet(1, EXPAND_PATH, &["~/a"]),
+            b"\0\0\0\x27\xc8\0\0\0\x01\0\0\0\x17expand-path@openssh.com\0\0\0\x03~/a"
+        );
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&1u32.to_be_bytes());
+        put_string(&mut payload, b"/home/user/a");
+        put_string(&mut payload, b"");
+        payload.extend_from_slice(&0u32.to_be_bytes());
+        assert_eq!(
+            parse_name_reply(&read(&packet(SSH_FXP_NAME, &payload)), 1).unwrap(),
+            "/home/user/a"
+        );
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&0u32.to_be_bytes());
+        assert!(matches!(
+            parse_name_reply(&read(&packet(SSH_FXP_NAME, &payload)), 1),
+            Err(SftpChannelError::Sftp(SftpError::BadMessage))
+        ));
+    }
+
+    #[test]
+    fn users_groups_by_id() {
+        let mut expected = 1u32.to_be_bytes().to_vec();
+        put_string(&mut expected, USERS_GROUPS_BY_ID.as_bytes());
+        put_string(&mut expected, b"\0\0\0\x01\0\0\0\x02");
+        put_string(&mut expected, b"\0\0\0\x03");
+        assert_eq!(
+            users_groups_by_id_packet(1, &[1, 2], &[3]),
+            packet(SSH_FXP_EXTENDED, &expected)
+        );
+
+        let mut users = vec![];
+        put_string(&mut users, b"root");
+        put_string(&mut users, b"");
+        let mut groups = vec![];
+        put_string(&mut groups, b"wheel");
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        put_string(&mut payload, &users);
+        put_string(&mut payload, &groups);
+        let names = parse_users_groups_reply(
+            &read(&packet(SSH_FXP_EXTENDED_REPLY, &payload)),
+            1,
+            &[0, 2],
+            &[3],
+        )
+        .unwrap();
+        assert_eq!(names.users.get(&0).map(String::as_str), Some("root"));
+        assert!(!names.users.contains_key(&2));
+        assert_eq!(names.groups.get(&3).map(String::as_str), Some("wheel"));
+    }
+
+    #[test]
+    fn long_names() {
+        let mut payload = 2u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&2u32.to_be_bytes());
+        put_string(&mut payload, b"a");
+        put_string(
+            &mut payload,
+            b"-rw-r--r--    1 wez      staff         4 Jan  1 00:00 a",
+        );
+        payload
+            .extend_from_slice(&(SSH_FILEXFER_ATTR_SIZE | SSH_FILEXFER_ATTR_UIDGID).to_be_bytes());
+        payload.extend_from_slice(&4u64.to_be_bytes());
+        payload.extend_from_slice(&501u32.to_be_bytes());
+        payload.extend_from_slice(&20u32.to_be_bytes());
+        put_string(&mut payload, b"b");
+        put_string(
+            &mut payload,
+            b"-rw-r--r--    1 1001     20            0 Jan  1 00:00 b",
+        );
+        payload.extend_from_slice(&SSH_FILEXFER_ATTR_UIDGID.to_be_bytes());
+        payload.extend_from_slice(&1001u32.to_be_bytes());
+        payload.extend_from_slice(&20u32.to_be_bytes());
+        let entries = parse_readdir_reply(&read(&packet(SSH_FXP_NAME, &payload)), 2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let mut names = OwnerNames::default();
+        for (long_name, uid, gid) in entries {
+            names.add_long_name(&long_name, uid, gid);
+        }
+        assert_eq!(names.users.get(&501).map(String::as_str), Some("wez"));
+        assert!(!names.users.contains_key(&1001));
+        assert_eq!(names.groups.get(&20).map(String::as_str), Some("staff"));
+
+        let mut payload = 3u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&SSH_FX_EOF.to_be_bytes());
+        assert!(
+            parse_readdir_reply(&read(&packet(SSH_FXP_STATUS, &payload)), 3)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn copy_data() {
+        assert_eq!(
+            open_packet(2, "/b", SSH_FXF_WRITE | SSH_FXF_CREAT | SSH_FXF_TRUNC),
+            b"\0\0\0\x13\x03\0\0\0\x02\0\0\0\x02/b\0\0\0\x1a\0\0\0\0"
+        );
+        assert_eq!(
+            copy_data_packet(3, b"h1", b"h2"),
+            b"\0\0\0\x36\xc8\0\0\0\x03\0\0\0\x09copy-data\0\0\0\x02h1\
+              \0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x02h2\0\0\0\0\0\0\0\0"
+        );
+    }
+
+    #[test]
+    fn status() {
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&2u32.to_be_bytes());
+        put_string(&mut payload, b"No such file");
+        put_string(&mut payload, b"");
+        assert!(matches!(
+            parse_statvfs_reply(&read(&packet(SSH_FXP_STATUS, &payload)), 1),
+            Err(SftpChannelError::Sftp(SftpError::NoSuchFile))
+        ));
+        assert!(matches!(
+            parse_status_reply(&read(&packet(SSH_FXP_STATUS, &payload)), 1),
+            Err(SftpChannelError::Sftp(SftpError::NoSuchFile))
+        ));
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&SSH_FX_OK.to_be_bytes());
+        put_string(&mut payload, b"Success");
+        put_string(&mut payload, b"");
+        assert!(parse_status_reply(&read(&packet(SSH_FXP_STATUS, &payload)), 1).is_ok());
+        assert!(matches!(
+            parse_status_reply(&read(&packet(SSH_FXP_STATUS, &payload)), 2),
+            Err(SftpChannelError::Sftp(SftpError::BadMessage))
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use super::ratelimit::{consume_all, pipeline_window, refund_all, RateLimit};
 use super::{Metadata, SessionRequest, SessionSender, SftpChannelResult, SftpRequest};
 use smol::channel::{bounded, Sender};
 use smol::future::FutureExt;
@@ -9,9 +10,35 @@ use std::{fmt, io};
 pub(crate) type FileId = usize;
 
 /// A file handle to an SFTP connection.
+///
+/// It implements the `AsyncRead` and `AsyncWrite` traits of the `futures`
+/// crate, which are re-exported by `smol::io`, so that it can be used with
+/// `copy`, `BufReader` and other adapters.  When the `tokio` feature is
+/// enabled, it also implements the tokio equivalents of those traits.
+///
+/// With the libssh2 backend, so that a transfer isn't held up by a
+/// network round trip for each of its reads or writes, data is read
+/// ahead of the reads, and writes are buffered until enough of them are
+/// waiting, so that several requests are in flight at once.  The number
+/// of requests is set by the `wezterm_sftp_pipeline_depth` option of the
+/// session, which defaults to 64 requests of 32 KiB; with a depth of 1,
+/// each 32 KiB request waits for the reply to the one before it.
+/// As writes are buffered, the failure of a write may only be reported
+/// by a later write, or when the file is flushed or closed.
+///
+/// The libssh backend can't keep several requests in flight, so it
+/// ignores `wezterm_sftp_pipeline_depth` and reads and writes the file
+/// as requested.
+///
+/// The reads and writes are limited by the `wezterm_sftp_rate_limit`
+/// option of the session, in bytes per second, and by the limits of
+/// [`Sftp::with_rate_limit`](crate::Sftp::with_rate_limit).  Data is
+/// paid for before it is requested, and a rate limited file reads ahead
+/// or writes behind no more than a second's worth of its lowest limit.
 pub struct File {
     pub(crate) file_id: FileId,
     tx: Option<SessionSender>,
+    pub(crate) rate_limits: Vec<RateLimit>,
     state: FileState,
 }
 
@@ -32,18 +59,23 @@ pub(crate) enum FileRequest {
     SetMetadata(SetMetadataFile, Sender<SftpChannelResult<()>>),
     Metadata(FileId, Sender<SftpChannelResult<Metadata>>),
     Fsync(FileId, Sender<SftpChannelResult<()>>),
+    Seek(FileId, u64, Sender<SftpChannelResult<()>>),
 }
 
 #[derive(Debug)]
 pub(crate) struct WriteFile {
     pub file_id: FileId,
     pub data: Vec<u8>,
+    /// If set, the most data to buffer before it is written
+    pub window: Option<usize>,
 }
 
 #[derive(Debug)]
 pub(crate) struct ReadFile {
     pub file_id: FileId,
     pub max_bytes: usize,
+    /// If set, the most data to read ahead
+    pub window: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -78,6 +110,7 @@ impl File {
         Self {
             file_id,
             tx: None,
+            rate_limits: vec![],
             state: Default::default(),
         }
     }
@@ -127,6 +160,12 @@ impl File {
     /// This function causes the remote server to synchronize the file data and metadata to disk
     /// (like fsync(2)).
     ///
+    /// This uses the `fsync@openssh.com` extension, and fails with
+    /// [`SftpError::OpUnsupported`](crate::SftpError::OpUnsupported) if the server didn't
+    /// advertise it when the sftp channel was opened, or with
+    /// [`SftpChannelError::NotImplemented`](crate::SftpChannelError::NotImplemented) when
+    /// using the libssh backend, whose bindings don't support it.
+    ///
     /// See [`ssh2::File::fsync`] for more information.
     pub async fn fsync(&self) -> SftpChannelResult<()> {
         let (reply, rx) = bounded(1);
@@ -141,6 +180,26 @@ impl File {
         let result = rx.recv().await??;
         Ok(result)
     }
+
+    /// Moves the position at which the next read or write of this
+    /// handle happens to `offset` bytes from the start of the file.
+    ///
+    /// This must not be called while a read or write through the
+    /// `AsyncRead` or `AsyncWrite` traits is in progress.
+    pub async fn seek(&self, offset: u64) -> SftpChannelResult<()> {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .as_ref()
+            .unwrap()
+            .send(SessionRequest::Sftp(SftpRequest::File(FileRequest::Seek(
+                self.file_id,
+                offset,
+                reply,
+            ))))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
 }
 
 impl smol::io::AsyncRead for File {
@@ -149,18 +208,31 @@ impl smol::io::AsyncRead for File {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        async fn read(tx: SessionSender, file_id: usize, len: usize) -> io::Result<Vec<u8>> {
-            inner_read(tx, file_id, len)
-                .await
-                .map_err(|x| io::Error::new(io::ErrorKind::Other, x))
+        async fn read(
+            tx: SessionSender,
+            file_id: usize,
+            len: usize,
+            rate_limits: Vec<RateLimit>,
+        ) -> io::Result<Vec<u8>> {
+            // Pay for the data before it is requested, so that it can't
+            // cross the wire faster than the limits allow, and get back
+            // what the read didn't use
+            let window = pipeline_window(&rate_limits);
+            let len = window.map_or(len, |window| len.min(window));
+            consume_all(&rate_limits, len).await;
+            let result = inner_read(tx, file_id, len, window).await;
+            let used = result.as_ref().map_or(0, |data| data.len());
+            refund_all(&rate_limits, len.saturating_sub(used));
+            result.map_err(|x| io::Error::new(io::ErrorKind::Other, x))
         }
         let tx = self.tx.as_ref().unwrap().clone();
         let file_id = self.file_id;
+        let rate_limits = self.rate_limits.clone();
 
         let poll = self
             .state
             .f_read
-            .get_or_insert_with(|| Box::pin(read(tx, file_id, buf.len())))
+            .get_or_insert_with(|| Box::pin(read(tx, file_id, buf.len(), rate_limits)))
             .poll(cx);
 
         if poll.is_ready() {
@@ -185,9 +257,15 @@ impl smol::io::AsyncWrite for File {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        async fn write(tx: SessionSender, file_id: usize, buf: Vec<u8>) -> io::Result<usize> {
+        async fn write(
+            tx: SessionSender,
+            file_id: usize,
+            buf: Vec<u8>,
+            rate_limits: Vec<RateLimit>,
+        ) -> io::Result<usize> {
             let n = buf.len();
-            inner_write(tx, file_id, buf)
+            consume_all(&rate_limits, n).await;
+            inner_write(tx, file_id, buf, pipeline_window(&rate_limits))
                 .await
                 .map(|_| n)
                 .map_err(|x| io::Error::new(io::ErrorKind::Other, x))
@@ -195,11 +273,12 @@ impl smol::io::AsyncWrite for File {
 
         let tx = self.tx.as_ref().unwrap().clone();
         let file_id = self.file_id;
+        let rate_limits = self.rate_limits.clone();
 
         let poll = self
             .state
             .f_write
-            .get_or_insert_with(|| Box::pin(write(tx, file_id, buf.to_vec())))
+            .get_or_insert_with(|| Box::pin(write(tx, file_id, buf.to_vec(), rate_limits)))
             .poll(cx);
 
         if poll.is_ready() {
@@ -256,11 +335,57 @@ impl smol::io::AsyncWrite for File {
     }
 }
 
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match smol::io::AsyncRead::poll_read(self, cx, buf.initialize_unfilled()) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(x)) => Poll::Ready(Err(x)),
+            Poll::Ready(Ok(n)) => {
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        smol::io::AsyncWrite::poll_write(self, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        smol::io::AsyncWrite::poll_flush(self, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        smol::io::AsyncWrite::poll_close(self, cx)
+    }
+}
+
 /// Writes some bytes to the file.
-async fn inner_write(tx: SessionSender, file_id: usize, data: Vec<u8>) -> SftpChannelResult<()> {
+async fn inner_write(
+    tx: SessionSender,
+    file_id: usize,
+    data: Vec<u8>,
+    window: Option<usize>,
+) -> SftpChannelResult<()> {
     let (reply, rx) = bounded(1);
     tx.send(SessionRequest::Sftp(SftpRequest::File(FileRequest::Write(
-        WriteFile { file_id, data },
+        WriteFile {
+            file_id,
+            data,
+            window,
+        },
         reply,
     ))))
     .await?;
@@ -276,10 +401,15 @@ async fn inner_read(
     tx: SessionSender,
     file_id: usize,
     max_bytes: usize,
+    window: Option<usize>,
 ) -> SftpChannelResult<Vec<u8>> {
     let (reply, rx) = bounded(1);
     tx.send(SessionRequest::Sftp(SftpRequest::File(FileRequest::Read(
-        ReadFile { file_id, max_bytes },
+        ReadFile {
+            file_id,
+            max_bytes,
+            window,
+        },
         reply,
     ))))
     .await?;
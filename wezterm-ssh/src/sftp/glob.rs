@@ -0,0 +1,367 @@
+use super::{Sftp, SftpChannelError, SftpChannelResult};
+use crate::sftp::error::SftpError;
+use crate::sftp::types::Metadata;
+use camino::{Utf8Path, Utf8PathBuf};
+use smol::stream::StreamExt;
+
+/// A component of a glob pattern
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    /// A name without wildcards, which is looked up rather than
+    /// found by listing its directory
+    Literal(String),
+    Wildcard(String),
+    /// `**`, which matches any number of directories
+    Recursive,
+}
+
+fn has_wildcards(component: &str) -> bool {
+    let mut escaped = false;
+    for c in component.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn unescape(component: &str) -> String {
+    let mut name = String::with_capacity(component.len());
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => name.extend(chars.next()),
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+/// Splits a pattern into the directory that it is relative to and
+/// its segments
+fn parse(pattern: &str) -> (Utf8PathBuf, Vec<Segment>) {
+    let root = if pattern.starts_with('/') {
+        Utf8PathBuf::from("/")
+    } else {
+        Utf8PathBuf::new()
+    };
+    let mut segments = vec![];
+    for component in pattern.split('/').filter(|c| !c.is_empty()) {
+        let segment = if component == "**" {
+            // `**/**` matches the same as `**`
+            if segments.last() == Some(&Segment::Recursive) {
+                continue;
+            }
+            Segment::Recursive
+        } else if has_wildcards(component) {
+            Segment::Wildcard(component.to_string())
+        } else {
+            Segment::Literal(unescape(component))
+        };
+        segments.push(segment);
+    }
+    (root, segments)
+}
+
+/// Matches `c` against the bracket expression at the start of
+/// `pattern`, returning whether it matched along with the length of
+/// the expression, or None if the expression isn't terminated
+fn match_class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let start = *pattern.get(i)?;
+        // A `]` straight after the `[` is part of the set
+        if start == ']' && !first {
+            break;
+        }
+        first = false;
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                matched |= start <= c && c <= end;
+                i += 3;
+            }
+            _ => {
+                matched |= start == c;
+                i += 1;
+            }
+        }
+    }
+    Some((matched != negated, i + 1))
+}
+
+fn match_chars(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The position of the most recent `*` and of the name that
+    // it will try to match next, should the rest of the pattern fail
+    let mut backtrack = None;
+    while n < name.len() {
+        let next = match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, n));
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => match match_class(&pattern[p..], name[n]) {
+                Some((true, len)) => Some(p + len),
+                Some((false, _)) => None,
+                // An unterminated `[` is matched literally
+                None if name[n] == '[' => Some(p + 1),
+                None => None,
+            },
+            Some('\\') if p + 1 < pattern.len() => {
+                if pattern[p + 1] == name[n] {
+                    Some(p + 2)
+                } else {
+                    None
+                }
+            }
+            Some(&c) if c == name[n] => Some(p + 1),
+            _ => None,
+        };
+        match (next, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                n += 1;
+            }
+            (None, Some((star, from))) => {
+                p = star;
+                n = from + 1;
+                backtrack = Some((star, n));
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a file name against a shell-style wildcard pattern
+fn matches(pattern: &str, name: &str) -> bool {
+    // Like the shell, wildcards don't match the `.` that starts the
+    // names of hidden files
+    if name.starts_with('.') && !pattern.starts_with('.') && !pattern.starts_with("\\.") {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_chars(&pattern, &name)
+}
+
+/// Returns true if `err` means that a path doesn't exist or can't be
+/// accessed, in which case it doesn't match rather than failing
+/// the whole glob
+fn is_inaccessible(err: &SftpChannelError) -> bool {
+    match err {
+        SftpChannelError::Sftp(
+            SftpError::NoSuchFile | SftpError::NoSuchPath | SftpError::PermissionDenied,
+        ) => true,
+        #[cfg(feature = "libssh-rs")]
+        SftpChannelError::LibSsh(libssh_rs::Error::Sftp(_)) => true,
+        _ => false,
+    }
+}
+
+impl Sftp {
+    /// Expands a shell-style pattern against the remote filesystem,
+    /// returning the matching paths along with their metadata, sorted
+    /// by path.
+    ///
+    /// `*` matches any part of a file name, `?` a single character and
+    /// `[a-z]` or `[!a-z]` a character that is or isn't in the set, while
+    /// `**` matches any number of directories, such as in `src/**/*.rs`.
+    /// Like the shell, the wildcards don't match the names of hidden
+    /// files unless the pattern starts with `.`, and `\` escapes the
+    /// wildcards.  A relative pattern is relative to the directory in
+    /// which the sftp session started.
+    ///
+    /// Directories are listed one at a time as the pattern is matched,
+    /// and only the entries that match are kept.  Directories that
+    /// can't be read are skipped, and `**` doesn't follow symlinks.
+    pub async fn glob(&self, pattern: &str) -> SftpChannelResult<Vec<(Utf8PathBuf, Metadata)>> {
+        let (root, segments) = parse(pattern);
+        let mut dirs = vec![root];
+        let mut matched = vec![];
+
+        for (idx, segment) in segments.iter().enumerate() {
+            let last = idx + 1 == segments.len();
+            let mut next = vec![];
+            for dir in dirs {
+                match segment {
+                    Segment::Literal(name) => {
+                        let path = dir.join(name);
+                        let metadata = if last {
+                            self.symlink_metadata(path.clone()).await
+                        } else {
+                            self.metadata(path.clone()).await
+                        };
+                        match metadata {
+                            Ok(metadata) if last => matched.push((path, metadata)),
+                            Ok(metadata) if metadata.is_dir() => next.push(path),
+                            Ok(_) => {}
+                            Err(err) if is_inaccessible(&err) => {}
+                            Err(err) => return Err(err),
+                        }
+                    }
+                    Segment::Wildcard(pattern) => {
+                        for (path, metadata) in
+                            self.list_dir(&dir, |name| matches(pattern, name)).await?
+                        {
+                            if last {
+                                matched.push((path, metadata));
+                            } else if self.is_dir_following_symlink(&path, &metadata).await? {
+                                next.push(path);
+                            }
+                        }
+                    }
+                    Segment::Recursive => {
+                        // Walk the tree beneath `dir`; a trailing `**`
+                        // matches everything in it, otherwise the rest
+                        // of the pattern is matched in every directory
+                        let mut pending = vec![dir.clone()];
+                        if !last {
+                            next.push(dir);
+                        }
+                        while let Some(dir) = pending.pop() {
+                            for (path, metadata) in
+                                self.list_dir(&dir, |name| !name.starts_with('.')).await?
+                            {
+                                if metadata.is_dir() {
+                                    pending.push(path.clone());
+                                    if !last {
+                                        next.push(path.clone());
+                                    }
+                                }
+                                if last {
+                                    matched.push((path, metadata));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            dirs = next;
+        }
+
+        // Several `**` can reach the same path in different ways
+        matched.sort_by(|a, b| a.0.cmp(&b.0));
+        matched.dedup_by(|a, b| a.0 == b.0);
+        Ok(matched)
+    }
+
+    /// Lists the entries of `dir` whose names satisfy `filter`, or
+    /// nothing if it can't be read
+    async fn list_dir(
+        &self,
+        dir: &Utf8Path,
+        filter: impl Fn(&str) -> bool,
+    ) -> SftpChannelResult<Vec<(Utf8PathBuf, Metadata)>> {
+        let listed = if dir.as_str().is_empty() {
+            Utf8Path::new(".")
+        } else {
+            dir
+        };
+        let entries = match self.read_dir_stream(listed.to_path_buf()).await {
+            Ok(entries) => entries,
+            Err(err) if is_inaccessible(&err) => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        smol::pin!(entries);
+
+        let mut matching = vec![];
+        while let Some(entry) = entries.next().await {
+            let (path, metadata) = match entry {
+                Ok(entry) => entry,
+                Err(err) if is_inaccessible(&err) => break,
+                Err(err) => return Err(err),
+            };
+            // Avoid the `./` that read_dir_stream adds for relative patterns
+            if let Some(name) = path.file_name() {
+                if filter(name) {
+                    matching.push((dir.join(name), metadata));
+                }
+            }
+        }
+        Ok(matching)
+    }
+
+    async fn is_dir_following_symlink(
+        &self,
+        path: &Utf8Path,
+        metadata: &Metadata,
+    ) -> SftpChannelResult<bool> {
+        if !metadata.is_symlink() {
+            return Ok(metadata.is_dir());
+        }
+        match self.metadata(path.to_path_buf()).await {
+            Ok(metadata) => Ok(metadata.is_dir()),
+            Err(err) if is_inaccessible(&err) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*.log", "server.log"));
+        assert!(!matches("*.log", ".log.log"));
+        assert!(matches(".*", ".bashrc"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(!matches("file?.txt", "file10.txt"));
+        assert!(matches("[abc]*", "build"));
+        assert!(!matches("[!abc]*", "build"));
+        assert!(matches("v[0-9].[0-9]", "v1.2"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("[-]", "-"));
+        assert!(matches("a[b", "a[b"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "x"));
+        assert!(matches("*", "ünïcode"));
+        assert!(matches("**", "anything"));
+        assert!(!matches("*.rs", "main.rs.orig"));
+    }
+
+    #[test]
+    fn segments() {
+        assert_eq!(
+            parse("/var/log/**/**/*.log"),
+            (
+                Utf8PathBuf::from("/"),
+                vec![
+                    Segment::Literal("var".to_string()),
+                    Segment::Literal("log".to_string()),
+                    Segment::Recursive,
+                    Segment::Wildcard("*.log".to_string()),
+                ]
+            )
+        );
+        assert_eq!(
+            parse("src//a\\*b/x?"),
+            (
+                Utf8PathBuf::new(),
+                vec![
+                    Segment::Literal("src".to_string()),
+                    Segment::Literal("a*b".to_string()),
+                    Segment::Wildcard("x?".to_string()),
+                ]
+            )
+        );
+    }
+}
@@ -1,19 +1,41 @@
 use super::{SessionRequest, SessionSender};
 use crate::sftp::dir::{Dir, DirRequest};
 use crate::sftp::file::{File, FileRequest};
-use crate::sftp::types::{Metadata, OpenFileType, OpenOptions, RenameOptions, WriteMode};
+use crate::sftp::types::{
+    FilePermissions, FileType, FilesystemStats, Limits, Metadata, OpenFileType, OpenOptions,
+    OwnerNames, RenameOptions, WriteMode,
+};
 use camino::Utf8PathBuf;
 use error::SftpError;
 use smol::channel::{bounded, RecvError, Sender};
+use smol::stream::Stream;
+use std::collections::VecDeque;
 use std::convert::TryInto;
 use std::io;
 use thiserror::Error;
 
+pub(crate) mod checksum;
 pub(crate) mod dir;
 pub(crate) mod error;
+pub(crate) mod extension;
 pub(crate) mod file;
+pub(crate) mod glob;
+pub(crate) mod owners;
+pub(crate) mod ratelimit;
+pub(crate) mod remove;
+pub(crate) mod tail;
+pub(crate) mod transfer;
 pub(crate) mod types;
 
+pub use checksum::HashAlgorithm;
+pub use ratelimit::RateLimit;
+pub use remove::Symlinks;
+pub use tail::TailOptions;
+pub use transfer::Resume;
+
+/// How many entries `read_dir_stream` requests at a time
+const READ_DIR_BATCH: usize = 256;
+
 fn into_invalid_data<E>(err: E) -> io::Error
 where
     E: Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -21,6 +43,24 @@ where
     io::Error::new(io::ErrorKind::InvalidData, err)
 }
 
+fn into_invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Metadata that changes none of the attributes of a file
+/// when passed to `set_metadata`
+fn unchanged_metadata() -> Metadata {
+    Metadata {
+        ty: FileType::Other,
+        permissions: None,
+        size: None,
+        uid: None,
+        gid: None,
+        accessed: None,
+        modified: None,
+    }
+}
+
 /// Represents the result of some SFTP channel operation
 pub type SftpChannelResult<T> = Result<T, SftpChannelError>;
 
@@ -55,6 +95,9 @@ pub enum SftpChannelError {
 #[derive(Clone, Debug)]
 pub struct Sftp {
     pub(crate) tx: SessionSender,
+    /// Applied to the reads and writes of the files opened with this
+    /// handle
+    pub(crate) rate_limits: Vec<RateLimit>,
 }
 
 impl Sftp {
@@ -81,9 +124,29 @@ impl Sftp {
             .await?;
         let mut result = rx.recv().await??;
         result.initialize_sender(self.tx.clone());
+        result.rate_limits = self.rate_limits.clone();
         Ok(result)
     }
 
+    /// Returns a handle to the same sftp channel whose files are also
+    /// limited to the rate of `limit`, such as to limit a single
+    /// transfer without limiting the other uses of the session:
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// use wezterm_ssh::{RateLimit, Resume};
+    /// sftp.with_rate_limit(RateLimit::new(1024 * 1024))
+    ///     .upload_file("backup.tar", "/srv/backup.tar", Resume::Never)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_rate_limit(&self, limit: RateLimit) -> Sftp {
+        let mut sftp = self.clone();
+        sftp.rate_limits.push(limit);
+        sftp
+    }
+
     /// Helper to open a file in the `Read` mode.
     pub async fn open<T, E>(&self, filename: T) -> SftpChannelResult<File>
     where
@@ -161,6 +224,67 @@ impl Sftp {
         Ok(result)
     }
 
+    /// Reads the files in a directory as the returned stream is polled,
+    /// rather than all at once like `read_dir`, so that directories with
+    /// a huge number of files can be listed without holding all of them
+    /// in memory.  The directory is closed once the stream ends or is
+    /// dropped.
+    ///
+    /// The returned paths are all joined with dirname, and the paths . and .. are
+    /// filtered out.
+    pub async fn read_dir_stream<T, E>(
+        &self,
+        filename: T,
+    ) -> SftpChannelResult<impl Stream<Item = SftpChannelResult<(Utf8PathBuf, Metadata)>>>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        struct State {
+            dirname: Utf8PathBuf,
+            /// None once the end of the directory has been reached
+            dir: Option<Dir>,
+            pending: VecDeque<(Utf8PathBuf, Metadata)>,
+        }
+
+        let dirname: Utf8PathBuf = filename.try_into().map_err(into_invalid_data)?;
+        let dir = self.open_dir(dirname.clone()).await?;
+        let state = State {
+            dirname,
+            dir: Some(dir),
+            pending: VecDeque::new(),
+        };
+
+        Ok(smol::stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(entry) = state.pending.pop_front() {
+                    return Some((Ok(entry), state));
+                }
+                let result = match &state.dir {
+                    Some(dir) => dir.read_dir_batch(READ_DIR_BATCH).await,
+                    None => return None,
+                };
+                match result {
+                    Ok(entries) => {
+                        if entries.len() < READ_DIR_BATCH {
+                            state.dir.take();
+                        }
+                        let dirname = &state.dirname;
+                        state.pending.extend(
+                            entries
+                                .into_iter()
+                                .map(|(name, metadata)| (dirname.join(name), metadata)),
+                        );
+                    }
+                    Err(err) => {
+                        state.dir.take();
+                        return Some((Err(err), state));
+                    }
+                }
+            }
+        }))
+    }
+
     /// Create a directory on the remote filesystem.
     pub async fn create_dir<T, E>(&self, filename: T, mode: i32) -> SftpChannelResult<()>
     where
@@ -232,12 +356,26 @@ impl Sftp {
         Ok(result)
     }
 
-    /// Set the metadata for a file.
+    /// Set the metadata for a file, performed by SETSTAT.
+    ///
+    /// Only the attributes that are `Some` are changed; `ty` is ignored.
+    /// The owner and group, as well as the access and modification
+    /// times, are changed together, so each pair must either be set
+    /// or left unset as a whole.
     pub async fn set_metadata<T, E>(&self, filename: T, metadata: Metadata) -> SftpChannelResult<()>
     where
         T: TryInto<Utf8PathBuf, Error = E>,
         E: Into<Box<dyn std::error::Error + Send + Sync>>,
     {
+        if metadata.uid.is_some() != metadata.gid.is_some() {
+            return Err(into_invalid_input("uid and gid must be set together").into());
+        }
+        if metadata.accessed.is_some() != metadata.modified.is_some() {
+            return Err(
+                into_invalid_input("accessed and modified times must be set together").into(),
+            );
+        }
+
         let (reply, rx) = bounded(1);
         self.tx
             .send(SessionRequest::Sftp(SftpRequest::SetMetadata(
@@ -252,6 +390,54 @@ impl Sftp {
         Ok(result)
     }
 
+    /// Change the permissions of a file to the permission bits of
+    /// `mode`, performed by chmod(2).
+    pub async fn chmod<T, E>(&self, filename: T, mode: u32) -> SftpChannelResult<()>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let metadata = Metadata {
+            permissions: Some(FilePermissions::from_unix_mode(mode)),
+            ..unchanged_metadata()
+        };
+        self.set_metadata(filename, metadata).await
+    }
+
+    /// Change the owner and group of a file, performed by chown(2).
+    pub async fn chown<T, E>(&self, filename: T, uid: u32, gid: u32) -> SftpChannelResult<()>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let metadata = Metadata {
+            uid: Some(uid),
+            gid: Some(gid),
+            ..unchanged_metadata()
+        };
+        self.set_metadata(filename, metadata).await
+    }
+
+    /// Change the access and modification times of a file, given in
+    /// seconds since the unix epoch, performed by utimes(2).
+    pub async fn set_times<T, E>(
+        &self,
+        filename: T,
+        accessed: u64,
+        modified: u64,
+    ) -> SftpChannelResult<()>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let metadata = Metadata {
+            accessed: Some(accessed),
+            modified: Some(modified),
+            ..unchanged_metadata()
+        };
+        self.set_metadata(filename, metadata).await
+    }
+
     /// Create symlink at `target` pointing at `path`.
     pub async fn symlink<T1, T2, E1, E2>(&self, path: T1, target: T2) -> SftpChannelResult<()>
     where
@@ -274,6 +460,31 @@ impl Sftp {
         Ok(result)
     }
 
+    /// Create a hard link at `dst` to the existing file at `src`.
+    ///
+    /// This uses the `hardlink@openssh.com` extension, and fails with
+    /// [`SftpError::OpUnsupported`] if the server doesn't advertise it.
+    pub async fn hardlink<T1, T2, E1, E2>(&self, src: T1, dst: T2) -> SftpChannelResult<()>
+    where
+        T1: TryInto<Utf8PathBuf, Error = E1>,
+        T2: TryInto<Utf8PathBuf, Error = E2>,
+        E1: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E2: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::Hardlink(
+                Hardlink {
+                    src: src.try_into().map_err(into_invalid_data)?,
+                    dst: dst.try_into().map_err(into_invalid_data)?,
+                },
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+
     /// Read a symlink at `path`.
     pub async fn read_link<T, E>(&self, path: T) -> SftpChannelResult<Utf8PathBuf>
     where
@@ -308,6 +519,35 @@ impl Sftp {
         Ok(result)
     }
 
+    /// Resolve `path` to an absolute path, expanding a leading `~` or
+    /// `~user` to the home directory of that user on the remote host.
+    ///
+    /// Unlike [`Sftp::canonicalize`], the path is not required to exist.
+    /// This uses the `expand-path@openssh.com` extension, and fails with
+    /// [`SftpError::OpUnsupported`] if the server doesn't advertise it.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// let config = sftp.expand_path("~/.config/app.toml").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn expand_path<T, E>(&self, path: T) -> SftpChannelResult<Utf8PathBuf>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::ExpandPath(
+                path.try_into().map_err(into_invalid_data)?,
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+
     /// Rename the filesystem object on the remote filesystem.
     pub async fn rename<T1, T2, E1, E2>(
         &self,
@@ -336,6 +576,34 @@ impl Sftp {
         Ok(result)
     }
 
+    /// Rename `src` to `dst` on the remote filesystem, atomically
+    /// replacing `dst` if it already exists, as performed by rename(2).
+    ///
+    /// Unlike [`Sftp::rename`], this doesn't fail on servers that refuse
+    /// to overwrite an existing destination.  This uses the
+    /// `posix-rename@openssh.com` extension, and fails with
+    /// [`SftpError::OpUnsupported`] if the server doesn't advertise it.
+    pub async fn posix_rename<T1, T2, E1, E2>(&self, src: T1, dst: T2) -> SftpChannelResult<()>
+    where
+        T1: TryInto<Utf8PathBuf, Error = E1>,
+        T2: TryInto<Utf8PathBuf, Error = E2>,
+        E1: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E2: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::PosixRename(
+                PosixRename {
+                    src: src.try_into().map_err(into_invalid_data)?,
+                    dst: dst.try_into().map_err(into_invalid_data)?,
+                },
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+
     /// Remove a file on the remote filesystem.
     pub async fn remove_file<T, E>(&self, file: T) -> SftpChannelResult<()>
     where
@@ -352,6 +620,58 @@ impl Sftp {
         let result = rx.recv().await??;
         Ok(result)
     }
+
+    /// Returns true if the server advertised the sftp extension `name`,
+    /// such as `fsync@openssh.com`, when the sftp session started.
+    pub async fn supports_extension(&self, name: &str) -> SftpChannelResult<bool> {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::SupportsExtension(
+                name.to_string(),
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+
+    /// Get the limits of the server on the size of reads, writes and
+    /// packets, and on the number of open files.
+    ///
+    /// These are queried with the `limits@openssh.com` extension when
+    /// the sftp session starts; this fails with
+    /// [`SftpError::OpUnsupported`] if the server doesn't advertise it.
+    /// The reads and writes of a [`File`] are split into requests that
+    /// stay within these limits.
+    pub async fn limits(&self) -> SftpChannelResult<Limits> {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::Limits(reply)))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+
+    /// Get statistics about the filesystem containing `path`, such as
+    /// its capacity and free space, performed by statvfs(3).
+    ///
+    /// This uses the `statvfs@openssh.com` extension, and fails with
+    /// [`SftpError::OpUnsupported`] if the server doesn't advertise it.
+    pub async fn statvfs<T, E>(&self, path: T) -> SftpChannelResult<FilesystemStats>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::Statvfs(
+                path.try_into().map_err(into_invalid_data)?,
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
 }
 
 #[derive(Debug)]
@@ -368,10 +688,19 @@ pub(crate) enum SftpRequest {
     SymlinkMetadata(Utf8PathBuf, Sender<SftpChannelResult<Metadata>>),
     SetMetadata(SetMetadata, Sender<SftpChannelResult<()>>),
     Symlink(Symlink, Sender<SftpChannelResult<()>>),
+    Hardlink(Hardlink, Sender<SftpChannelResult<()>>),
     ReadLink(Utf8PathBuf, Sender<SftpChannelResult<Utf8PathBuf>>),
     Canonicalize(Utf8PathBuf, Sender<SftpChannelResult<Utf8PathBuf>>),
+    ExpandPath(Utf8PathBuf, Sender<SftpChannelResult<Utf8PathBuf>>),
     Rename(Rename, Sender<SftpChannelResult<()>>),
+    PosixRename(PosixRename, Sender<SftpChannelResult<()>>),
     RemoveFile(Utf8PathBuf, Sender<SftpChannelResult<()>>),
+    Statvfs(Utf8PathBuf, Sender<SftpChannelResult<FilesystemStats>>),
+    Limits(Sender<SftpChannelResult<Limits>>),
+    SupportsExtension(String, Sender<SftpChannelResult<bool>>),
+    CheckFile(CheckFile, Sender<SftpChannelResult<Vec<u8>>>),
+    CopyData(CopyData, Sender<SftpChannelResult<()>>),
+    ResolveOwnerNames(ResolveOwnerNames, Sender<SftpChannelResult<OwnerNames>>),
 
     /// Specialized type for file-based operations
     File(FileRequest),
@@ -402,6 +731,37 @@ pub(crate) struct Symlink {
     pub target: Utf8PathBuf,
 }
 
+#[derive(Debug)]
+pub(crate) struct Hardlink {
+    pub src: Utf8PathBuf,
+    pub dst: Utf8PathBuf,
+}
+
+#[derive(Debug)]
+pub(crate) struct PosixRename {
+    pub src: Utf8PathBuf,
+    pub dst: Utf8PathBuf,
+}
+
+#[derive(Debug)]
+pub(crate) struct CheckFile {
+    pub path: Utf8PathBuf,
+    pub algorithm: HashAlgorithm,
+}
+
+#[derive(Debug)]
+pub(crate) struct CopyData {
+    pub src: Utf8PathBuf,
+    pub dst: Utf8PathBuf,
+}
+
+#[derive(Debug)]
+pub(crate) struct ResolveOwnerNames {
+    pub uids: Vec<u32>,
+    pub gids: Vec<u32>,
+    pub dirs: Vec<Utf8PathBuf>,
+}
+
 #[derive(Debug)]
 pub(crate) struct Rename {
     pub src: Utf8PathBuf,
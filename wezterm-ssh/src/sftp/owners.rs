@@ -0,0 +1,59 @@
+use super::{ResolveOwnerNames, SessionRequest, Sftp, SftpChannelResult, SftpRequest};
+use crate::sftp::types::{Metadata, OwnerNames};
+use camino::Utf8PathBuf;
+use smol::channel::bounded;
+use std::collections::BTreeSet;
+
+impl Sftp {
+    /// Looks up the names of the users and groups that own `entries`,
+    /// such as those returned by [`Sftp::read_dir`], from their `uid`
+    /// and `gid`.
+    ///
+    /// This uses the `users-groups-by-id@openssh.com` extension when the
+    /// server advertises it.  Otherwise the directories that hold the
+    /// entries are listed, and the names are taken from the long names
+    /// of their entries, which servers format like the output of `ls -l`.
+    /// Names that can't be found are left out.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// let entries = sftp.read_dir("/var/log").await?;
+    /// let names = sftp.resolve_owner_names(&entries).await?;
+    /// for (path, metadata) in &entries {
+    ///     println!("{} {}", names.owner_name(metadata).unwrap_or("?"), path);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_owner_names(
+        &self,
+        entries: &[(Utf8PathBuf, Metadata)],
+    ) -> SftpChannelResult<OwnerNames> {
+        let uids: BTreeSet<u32> = entries.iter().filter_map(|(_, md)| md.uid).collect();
+        let gids: BTreeSet<u32> = entries.iter().filter_map(|(_, md)| md.gid).collect();
+        if uids.is_empty() && gids.is_empty() {
+            return Ok(OwnerNames::default());
+        }
+        let dirs: BTreeSet<Utf8PathBuf> = entries
+            .iter()
+            .map(|(path, _)| match path.parent() {
+                Some(parent) if !parent.as_str().is_empty() => parent.to_path_buf(),
+                _ => Utf8PathBuf::from("."),
+            })
+            .collect();
+
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::ResolveOwnerNames(
+                ResolveOwnerNames {
+                    uids: uids.into_iter().collect(),
+                    gids: gids.into_iter().collect(),
+                    dirs: dirs.into_iter().collect(),
+                },
+                reply,
+            )))
+            .await?;
+        let result = rx.recv().await??;
+        Ok(result)
+    }
+}
@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limits the rate at which the data of sftp files is read and written.
+///
+/// Clones share the same budget, so a limit that is applied to several
+/// files, or to a whole session, bounds their combined rate.  Transfers
+/// may use a second's worth of the budget at once after being idle, and
+/// otherwise wait for the data that they have used to be paid back.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    bytes_per_second: u64,
+    /// The budget as of `updated`, which is negative while the data
+    /// that has been used is yet to be paid back
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Takes `len` bytes from the budget, returning how long to wait
+    /// before using them
+    fn take(&mut self, len: usize, now: Instant) -> Duration {
+        let rate = self.bytes_per_second as f64;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * rate).min(rate) - len as f64;
+        self.updated = now;
+        if self.available >= 0. {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / rate)
+        }
+    }
+
+    /// Returns `len` bytes that were taken but not used to the budget
+    fn give_back(&mut self, len: usize) {
+        self.available = (self.available + len as f64).min(self.bytes_per_second as f64);
+    }
+}
+
+impl RateLimit {
+    /// Creates a limit of `bytes_per_second`, which must be greater than 0
+    pub fn new(bytes_per_second: u64) -> Self {
+        let bytes_per_second = bytes_per_second.max(1);
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                bytes_per_second,
+                available: bytes_per_second as f64,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bucket.lock().unwrap().bytes_per_second
+    }
+
+    /// Changes the limit, including for the reads and writes of the
+    /// files that it is already applied to
+    pub fn set_bytes_per_second(&self, bytes_per_second: u64) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.bytes_per_second = bytes_per_second.max(1);
+        bucket.available = bucket.available.min(bucket.bytes_per_second as f64);
+    }
+
+    /// Waits until `len` bytes may be read or written
+    pub(crate) async fn consume(&self, len: usize) {
+        let delay = self.bucket.lock().unwrap().take(len, Instant::now());
+        if !delay.is_zero() {
+            smol::Timer::after(delay).await;
+        }
+    }
+
+    /// Returns `len` bytes that were consumed but not read or written
+    pub(crate) fn refund(&self, len: usize) {
+        self.bucket.lock().unwrap().give_back(len);
+    }
+}
+
+/// Waits until `len` bytes may be read or written under each of `limits`
+pub(crate) async fn consume_all(limits: &[RateLimit], len: usize) {
+    for limit in limits {
+        limit.consume(len).await;
+    }
+}
+
+/// Returns `len` bytes that were consumed but not used to each of `limits`
+pub(crate) fn refund_all(limits: &[RateLimit], len: usize) {
+    if len > 0 {
+        for limit in limits {
+            limit.refund(len);
+        }
+    }
+}
+
+/// Returns the most data that a file limited by `limits` may read
+/// ahead or write behind, so that the data that crosses the wire in a
+/// burst is no more than a second's worth of the lowest limit
+pub(crate) fn pipeline_window(limits: &[RateLimit]) -> Option<usize> {
+    limits
+        .iter()
+        .map(|limit| limit.bytes_per_second().min(usize::MAX as u64) as usize)
+        .min()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bucket() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            bytes_per_second: 1000,
+            available: 1000.,
+            updated: start,
+        };
+        // A second's worth is available at once
+        assert_eq!(bucket.take(1000, start), Duration::ZERO);
+        assert_eq!(bucket.take(500, start), Duration::from_millis(500));
+        // The debt is paid back over time
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.take(250, later), Duration::from_millis(250));
+        // An idle period doesn't build up more than a second's worth
+        let idle = later + Duration::from_secs(10);
+        assert_eq!(bucket.take(1000, idle), Duration::ZERO);
+        assert_eq!(bucket.take(1, idle), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn give_back() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            bytes_per_second: 1000,
+            available: 1000.,
+            updated: start,
+        };
+        assert_eq!(bucket.take(1500, start), Duration::from_millis(500));
+        bucket.give_back(1000);
+        assert_eq!(bucket.take(500, start), Duration::ZERO);
+        // No more than a second's worth is given back
+        bucket.give_back(5000);
+        assert_eq!(bucket.take(1000, start), Duration::ZERO);
+        assert_eq!(bucket.take(100, start), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn window() {
+        assert_eq!(pipeline_window(&[]), None);
+        assert_eq!(
+            pipeline_window(&[RateLimit::new(5000), RateLimit::new(2000)]),
+            Some(2000)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use super::transfer::not_a_directory;
+use super::{into_invalid_data, Sftp, SftpChannelResult};
+use camino::{Utf8Path, Utf8PathBuf};
+use smol::stream::StreamExt;
+use std::convert::TryInto;
+
+/// How `Sftp::remove_dir_all` treats the symlinks that it finds
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symlinks {
+    /// Remove the symlinks themselves, leaving what they point to alone
+    Skip,
+    /// Remove the directories that symlinks point to, along with their
+    /// contents, before removing the symlinks.  Symlinks to files are
+    /// only removed themselves, as are symlinks to directories that
+    /// are already being removed or that hold the directory being
+    /// removed.
+    Follow,
+}
+
+impl Default for Symlinks {
+    fn default() -> Self {
+        Self::Skip
+    }
+}
+
+/// Returns true if removing `target` along with its contents would
+/// overlap with removing one of `trees`
+fn overlaps(target: &Utf8Path, trees: &[Utf8PathBuf]) -> bool {
+    trees
+        .iter()
+        .any(|tree| target.starts_with(tree) || tree.starts_with(target))
+}
+
+impl Sftp {
+    /// Removes the remote directory `path` after recursively removing
+    /// its contents, with `symlinks` deciding whether the directories
+    /// that symlinks point to are removed too.
+    ///
+    /// If `path` is itself a symlink, it is treated like the symlinks
+    /// within the directory.  Removal stops at the first error, which
+    /// may leave part of the contents in place.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// use wezterm_ssh::Symlinks;
+    ///
+    /// sftp.remove_dir_all("/tmp/build", Symlinks::Skip).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_dir_all<T, E>(&self, path: T, symlinks: Symlinks) -> SftpChannelResult<()>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let path: Utf8PathBuf = path.try_into().map_err(into_invalid_data)?;
+
+        let metadata = self.symlink_metadata(path.clone()).await?;
+        let root = if metadata.is_symlink() {
+            if symlinks == Symlinks::Skip {
+                return self.remove_file(path).await;
+            }
+            if !self.metadata(path.clone()).await?.is_dir() {
+                return Err(not_a_directory(&path));
+            }
+            let target = self.canonicalize(path.clone()).await?;
+            self.remove_file(path).await?;
+            target
+        } else if metadata.is_dir() {
+            path
+        } else {
+            return Err(not_a_directory(&path));
+        };
+
+        // The directories whose contents are being removed, to avoid
+        // following symlinks into them again, or into their parents
+        let mut trees = vec![];
+        if symlinks == Symlinks::Follow {
+            trees.push(self.canonicalize(root.clone()).await?);
+        }
+
+        // Directories are removed once their contents are gone, in the
+        // reverse of the order in which they were found
+        let mut dirs = vec![root.clone()];
+        let mut pending = vec![root];
+        while let Some(dir) = pending.pop() {
+            let entries = self.read_dir_stream(dir).await?;
+            smol::pin!(entries);
+            while let Some(entry) = entries.next().await {
+                let (entry_path, metadata) = entry?;
+                if metadata.is_dir() {
+                    dirs.push(entry_path.clone());
+                    pending.push(entry_path);
+                    continue;
+                }
+
+                if metadata.is_symlink() && symlinks == Symlinks::Follow {
+                    let target = match self.metadata(entry_path.clone()).await {
+                        Ok(target) if target.is_dir() => {
+                            Some(self.canonicalize(entry_path.clone()).await?)
+                        }
+                        // Symlinks to files and dangling symlinks
+                        _ => None,
+                    };
+                    if let Some(target) = target {
+                        if overlaps(&target, &trees) {
+                            log::debug!("not following {} to {}", entry_path, target);
+                        } else {
+                            trees.push(target.clone());
+                            dirs.push(target.clone());
+                            pending.push(target);
+                        }
+                    }
+                }
+                self.remove_file(entry_path).await?;
+            }
+        }
+
+        for dir in dirs.into_iter().rev() {
+            self.remove_dir(dir).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlapping_trees() {
+        let trees = vec![Utf8PathBuf::from("/tmp/a"), Utf8PathBuf::from("/srv/b")];
+        assert!(overlaps(Utf8Path::new("/tmp/a"), &trees));
+        assert!(overlaps(Utf8Path::new("/tmp/a/c"), &trees));
+        assert!(overlaps(Utf8Path::new("/tmp"), &trees));
+        assert!(overlaps(Utf8Path::new("/"), &trees));
+        assert!(!overlaps(Utf8Path::new("/tmp/ab"), &trees));
+        assert!(!overlaps(Utf8Path::new("/srv/c"), &trees));
+    }
+}
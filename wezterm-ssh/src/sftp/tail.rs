@@ -0,0 +1,245 @@
+use super::file::File;
+use super::{into_invalid_data, Sftp, SftpChannelError, SftpChannelResult};
+use crate::sftp::error::SftpError;
+use crate::sftp::types::Metadata;
+use camino::Utf8PathBuf;
+use smol::io::AsyncReadExt;
+use smol::stream::Stream;
+use std::convert::TryInto;
+use std::time::Duration;
+
+/// The most data that is returned by each item of the stream
+const TAIL_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Options for `Sftp::tail`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TailOptions {
+    /// How long to wait before checking the file again once the end of
+    /// its data has been reached
+    pub interval: Duration,
+    /// Return the data that the file already holds, rather than only
+    /// the data that is appended to it
+    pub from_start: bool,
+}
+
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            from_start: false,
+        }
+    }
+}
+
+fn greater(a: Option<u64>, b: Option<u64>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a > b)
+}
+
+/// Returns true if `current`, the metadata of the path being followed,
+/// describes a different file than `open`, the metadata of the open
+/// file that was fetched after it.  A file that is only appended to
+/// can't have shrunk or become older in between, so a larger or more
+/// recently modified file at the path must have replaced it.
+fn replaced(current: &Metadata, open: &Metadata) -> bool {
+    greater(current.size, open.size) || greater(current.modified, open.modified)
+}
+
+/// Returns true if `err` means that the path doesn't exist, as happens
+/// for a moment while a rotated file is being replaced
+fn is_not_found(err: &SftpChannelError) -> bool {
+    match err {
+        SftpChannelError::Sftp(SftpError::NoSuchFile | SftpError::NoSuchPath) => true,
+        // libssh doesn't tell which sftp error it was
+        #[cfg(feature = "libssh-rs")]
+        SftpChannelError::LibSsh(libssh_rs::Error::Sftp(_)) => true,
+        _ => false,
+    }
+}
+
+struct Tail {
+    sftp: Sftp,
+    path: Utf8PathBuf,
+    interval: Duration,
+    /// None once the file was replaced, until the path exists again
+    file: Option<File>,
+    offset: u64,
+    /// Set once the end of the data has been reached, after which the
+    /// file is checked for changes before it is read again
+    at_end: bool,
+    buf: Vec<u8>,
+}
+
+impl Tail {
+    /// Checks the file once the end of its data has been reached,
+    /// returning false if there is nothing new to read
+    async fn check(&mut self) -> SftpChannelResult<bool> {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return Ok(true),
+        };
+        // The path is looked up before the open file, so that data that
+        // is appended in between can't make it look replaced
+        let current = match self.sftp.metadata(self.path.clone()).await {
+            Ok(current) => Some(current),
+            Err(err) if is_not_found(&err) => None,
+            Err(err) => return Err(err),
+        };
+        let open = file.metadata().await?;
+        let size = open.size.unwrap_or(0);
+        if size < self.offset {
+            log::debug!("{} was truncated, following it from the start", self.path);
+            self.offset = 0;
+        } else if size == self.offset {
+            match current {
+                Some(current) if replaced(&current, &open) => {
+                    log::debug!("{} was replaced, following the new file", self.path);
+                    self.file = None;
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            }
+        }
+        // This also resets the end of file state of the backend
+        file.seek(self.offset).await?;
+        self.at_end = false;
+        Ok(true)
+    }
+
+    /// Returns the next chunk of data, waiting for it to be appended
+    async fn next_chunk(&mut self) -> SftpChannelResult<Vec<u8>> {
+        loop {
+            if self.file.is_none() {
+                match self.sftp.open(self.path.clone()).await {
+                    Ok(file) => {
+                        self.file = Some(file);
+                        self.offset = 0;
+                        self.at_end = false;
+                    }
+                    Err(err) if is_not_found(&err) => {
+                        smol::Timer::after(self.interval).await;
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            if self.at_end {
+                if !self.check().await? {
+                    smol::Timer::after(self.interval).await;
+                }
+                continue;
+            }
+
+            let file = self.file.as_mut().unwrap();
+            let n = file.read(&mut self.buf).await?;
+            if n == 0 {
+                self.at_end = true;
+                continue;
+            }
+            self.offset += n as u64;
+            return Ok(self.buf[..n].to_vec());
+        }
+    }
+}
+
+impl Sftp {
+    /// Follows the remote file `path` like `tail -f`, returning a stream
+    /// of the data that is appended to it.
+    ///
+    /// Once the end of the data has been reached, the size of the file
+    /// is checked every `options.interval`.  A file that shrinks was
+    /// truncated, and is followed from its start.  A file that was
+    /// rotated, by being renamed or removed and replaced by another at
+    /// `path`, is read to its end before the new file is followed from
+    /// its start; the new file is noticed once it is larger, or more
+    /// recently modified, than the old one.  While nothing exists at
+    /// `path` it is checked again every `options.interval`.
+    ///
+    /// Any other error, such as being denied permission to read the
+    /// file, is returned and ends the stream, which otherwise only
+    /// ends when it is dropped.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// use smol::stream::StreamExt;
+    /// use wezterm_ssh::TailOptions;
+    ///
+    /// let log = sftp.tail("/var/log/syslog", TailOptions::default()).await?;
+    /// smol::pin!(log);
+    /// while let Some(data) = log.next().await {
+    ///     print!("{}", String::from_utf8_lossy(&data?));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tail<T, E>(
+        &self,
+        path: T,
+        options: TailOptions,
+    ) -> SftpChannelResult<impl Stream<Item = SftpChannelResult<Vec<u8>>>>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let path: Utf8PathBuf = path.try_into().map_err(into_invalid_data)?;
+        let file = self.open(path.clone()).await?;
+        let offset = if options.from_start {
+            0
+        } else {
+            let offset = file.metadata().await?.size.unwrap_or(0);
+            file.seek(offset).await?;
+            offset
+        };
+
+        let tail = Tail {
+            sftp: self.clone(),
+            path,
+            interval: options.interval,
+            file: Some(file),
+            offset,
+            at_end: false,
+            buf: vec![0; TAIL_CHUNK_SIZE],
+        };
+
+        Ok(smol::stream::unfold(Some(tail), |tail| async move {
+            let mut tail = tail?;
+            match tail.next_chunk().await {
+                Ok(data) => Some((Ok(data), Some(tail))),
+                Err(err) => Some((Err(err), None)),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sftp::types::FileType;
+
+    fn metadata(size: u64, modified: u64) -> Metadata {
+        Metadata {
+            ty: FileType::File,
+            permissions: None,
+            size: Some(size),
+            uid: None,
+            gid: None,
+            accessed: None,
+            modified: Some(modified),
+        }
+    }
+
+    #[test]
+    fn replaced_files() {
+        assert!(!replaced(&metadata(10, 100), &metadata(10, 100)));
+        // Appended to after the path was looked up
+        assert!(!replaced(&metadata(10, 100), &metadata(20, 101)));
+        assert!(replaced(&metadata(20, 100), &metadata(10, 100)));
+        assert!(replaced(&metadata(5, 101), &metadata(10, 100)));
+
+        let unknown = Metadata {
+            size: None,
+            modified: None,
+            ..metadata(0, 0)
+        };
+        assert!(!replaced(&metadata(20, 101), &unknown));
+    }
+}
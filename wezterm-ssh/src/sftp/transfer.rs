@@ -0,0 +1,786 @@
+use super::error::SftpError;
+use super::{
+    into_invalid_data, into_invalid_input, CopyData, SessionRequest, Sftp, SftpChannelError,
+    SftpChannelResult, SftpRequest,
+};
+use crate::sftp::types::{
+    FilePermissions, FileType, Metadata, OpenFileType, OpenOptions, RenameOptions, WriteMode,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use sha2::{Digest, Sha256};
+use smol::channel::bounded;
+use smol::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use smol::stream::StreamExt;
+use std::convert::TryInto;
+use std::io::{self, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The permissions of directories whose source permissions are unknown
+const DEFAULT_DIR_MODE: u32 = 0o755;
+/// The permissions of files whose source permissions are unknown
+const DEFAULT_FILE_MODE: u32 = 0o644;
+/// Directories are writable by their owner while their contents are
+/// being transferred; their own permissions are applied afterwards
+const OWNER_RWX: u32 = 0o700;
+/// The size of the reads and writes of remote files when the server
+/// doesn't report its limits; all servers are expected to accept it
+const DEFAULT_CHUNK_SIZE: usize = 32 * 1024;
+/// Bounds the memory used for the chunks, whatever the server reports
+const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+/// How much of the end of the data that a destination already holds
+/// is compared with the source by `Resume::Checksum`
+const CHECKSUM_LEN: u64 = 1024 * 1024;
+
+/// How a file transfer treats a destination that already exists, such
+/// as one left behind by a transfer that was interrupted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Resume {
+    /// Replace the destination
+    Never,
+    /// Assume that a destination that is no larger than the source
+    /// holds the start of the source, and transfer only the rest
+    Size,
+    /// Like `Size`, but first compare the SHA-256 digests of the last
+    /// part of the data in the destination and of the same range of the
+    /// source, and replace the destination if they differ.  This reads
+    /// up to 1 MiB from both ends.
+    Checksum,
+}
+
+impl Default for Resume {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+/// Returns the name of a temporary file in the same directory as
+/// `path`, which is unlikely to be used by anyone else
+fn temporary_sibling(path: &Utf8Path) -> SftpChannelResult<Utf8PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| into_invalid_input("path has no file name"))?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let nonce = format!(
+        "{}-{:x}-{}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    Ok(path.with_file_name(format!(".{}.{}.tmp", name, nonce)))
+}
+
+/// Returns the offset from which a transfer of a source of `src_len`
+/// bytes continues, given the length of the existing destination
+fn resume_offset(resume: Resume, src_len: Option<u64>, dest_len: Option<u64>) -> u64 {
+    match (resume, src_len, dest_len) {
+        (Resume::Never, _, _) => 0,
+        (_, Some(src_len), Some(dest_len)) if dest_len <= src_len => dest_len,
+        _ => 0,
+    }
+}
+
+/// Computes the SHA-256 digest of the next `len` bytes of `reader`
+async fn digest<R>(reader: &mut R, len: u64, chunk_size: usize) -> io::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; chunk_size];
+    let mut remaining = len;
+    while remaining > 0 {
+        let want = remaining.min(chunk_size as u64) as usize;
+        let n = reader.read(&mut buf[..want]).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        hasher.update(&buf[..n]);
+        remaining -= n as u64;
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Returns true if the next `len` bytes of `src` and `dest` match
+async fn same_data<S, D>(src: &mut S, dest: &mut D, len: u64, chunk_size: usize) -> io::Result<bool>
+where
+    S: AsyncRead + Unpin,
+    D: AsyncRead + Unpin,
+{
+    Ok(digest(src, len, chunk_size).await? == digest(dest, len, chunk_size).await?)
+}
+
+pub(super) fn not_a_directory(path: impl std::fmt::Display) -> SftpChannelError {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} is not a directory", path),
+    )
+    .into()
+}
+
+fn not_a_file(path: impl std::fmt::Display) -> SftpChannelError {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} is not a file", path),
+    )
+    .into()
+}
+
+fn permissions_only(ty: FileType, mode: u32) -> Metadata {
+    Metadata {
+        ty,
+        permissions: Some(FilePermissions::from_unix_mode(mode)),
+        size: None,
+        uid: None,
+        gid: None,
+        accessed: None,
+        modified: None,
+    }
+}
+
+/// Returns the unix permission bits of a local file
+#[cfg(unix)]
+fn local_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+/// Returns the unix permission bits of a local file; only the
+/// readonly attribute is available on this platform
+#[cfg(not(unix))]
+fn local_mode(metadata: &std::fs::Metadata) -> u32 {
+    let mode = if metadata.is_dir() {
+        DEFAULT_DIR_MODE
+    } else {
+        DEFAULT_FILE_MODE
+    };
+    if metadata.permissions().readonly() {
+        mode & !0o222
+    } else {
+        mode
+    }
+}
+
+#[cfg(unix)]
+async fn set_local_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    smol::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).await
+}
+
+#[cfg(not(unix))]
+async fn set_local_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut permissions = smol::fs::metadata(path).await?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    smol::fs::set_permissions(path, permissions).await
+}
+
+#[cfg(unix)]
+async fn create_local_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    let target = target.to_path_buf();
+    let link = link.to_path_buf();
+    smol::unblock(move || std::os::unix::fs::symlink(target, link)).await
+}
+
+#[cfg(not(unix))]
+async fn create_local_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    // Creating a symlink requires knowing whether its target is a
+    // directory on Windows, which a dangling link cannot tell us
+    log::warn!(
+        "not creating symlink {} -> {}: unsupported on this platform",
+        link.display(),
+        target.display()
+    );
+    Ok(())
+}
+
+/// Returns the chunk size to use for a limit reported by the server
+fn chunk_size(limit: Option<u64>) -> usize {
+    match limit {
+        Some(limit) if limit > 0 => limit.min(MAX_CHUNK_SIZE as u64) as usize,
+        _ => DEFAULT_CHUNK_SIZE,
+    }
+}
+
+/// Copies `src` to `dest` in chunks of `chunk_size` bytes, so that each
+/// read or write of a remote file is a single request of that size
+async fn copy_in_chunks<R, W>(src: &mut R, dest: &mut W, chunk_size: usize) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0u8; chunk_size];
+    loop {
+        let n = src.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        dest.write_all(&buf[..n]).await?;
+    }
+}
+
+impl Sftp {
+    /// Copies the local file `local` to `remote`, preserving its
+    /// permissions, and returns how many bytes of it were already in
+    /// `remote` and weren't transferred again.
+    ///
+    /// An existing `remote` is replaced, unless `resume` permits the
+    /// transfer to continue from the end of it.
+    pub async fn upload_file<L, R, E>(
+        &self,
+        local: L,
+        remote: R,
+        resume: Resume,
+    ) -> SftpChannelResult<u64>
+    where
+        L: AsRef<Path>,
+        R: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let local = local.as_ref();
+        let remote: Utf8PathBuf = remote.try_into().map_err(into_invalid_data)?;
+
+        let metadata = smol::fs::metadata(local).await?;
+        if !metadata.is_file() {
+            return Err(not_a_file(local.display()));
+        }
+        let chunk_size = chunk_size(self.limits().await.ok().map(|l| l.max_write_len));
+        self.upload_file_with(local, &remote, &metadata, chunk_size, resume)
+            .await
+    }
+
+    /// Copies the remote file `remote` to `local`, preserving its
+    /// permissions, and returns how many bytes of it were already in
+    /// `local` and weren't transferred again.
+    ///
+    /// An existing `local` is replaced, unless `resume` permits the
+    /// transfer to continue from the end of it.
+    pub async fn download_file<R, L, E>(
+        &self,
+        remote: R,
+        local: L,
+        resume: Resume,
+    ) -> SftpChannelResult<u64>
+    where
+        R: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+        L: AsRef<Path>,
+    {
+        let remote: Utf8PathBuf = remote.try_into().map_err(into_invalid_data)?;
+        let local = local.as_ref();
+
+        let metadata = self.metadata(remote.clone()).await?;
+        if !metadata.is_file() {
+            return Err(not_a_file(&remote));
+        }
+        let chunk_size = chunk_size(self.limits().await.ok().map(|l| l.max_read_len));
+        self.download_file_with(&remote, local, &metadata, chunk_size, resume)
+            .await
+    }
+
+    /// Writes the data read from `data` to the remote file `path`, such
+    /// that `path` either keeps its previous contents or holds all of
+    /// the new ones, even if the connection is lost part way through.
+    /// `data` may be a byte slice, or any other reader.
+    ///
+    /// The data is written to a temporary file next to `path`, which is
+    /// then renamed over it using the `posix-rename@openssh.com`
+    /// extension.  Servers that don't support it are asked for a plain
+    /// rename, and if that fails because `path` exists, `path` is
+    /// removed first; it is briefly missing in that case.  An existing
+    /// `path` keeps its permissions, but not its owner.
+    ///
+    /// The temporary file is removed if writing fails, unless that
+    /// fails too, as it would when the connection was lost.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// sftp.write_atomic("/etc/motd", &b"Welcome\n"[..]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_atomic<T, E, R>(&self, path: T, mut data: R) -> SftpChannelResult<()>
+    where
+        T: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+        R: AsyncRead + Unpin,
+    {
+        let path: Utf8PathBuf = path.try_into().map_err(into_invalid_data)?;
+        let temp = temporary_sibling(&path)?;
+        let mode = match self.metadata(path.clone()).await {
+            Ok(existing) if existing.is_file() => remote_mode(&existing, DEFAULT_FILE_MODE),
+            Ok(_) => return Err(not_a_file(&path)),
+            Err(_) => DEFAULT_FILE_MODE,
+        };
+        let chunk_size = chunk_size(self.limits().await.ok().map(|l| l.max_write_len));
+
+        let mut dest = self
+            .open_with_mode(
+                temp.clone(),
+                OpenOptions {
+                    read: false,
+                    write: Some(WriteMode::Write),
+                    mode: mode as i32,
+                    ty: OpenFileType::File,
+                },
+            )
+            .await?;
+        let written = async {
+            copy_in_chunks(&mut data, &mut dest, chunk_size).await?;
+            dest.set_metadata(permissions_only(FileType::File, mode))
+                .await?;
+            // Ensure that the data is on disk before it takes the
+            // place of `path`, where the server permits it
+            match dest.fsync().await {
+                Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => {}
+                result => result?,
+            }
+            dest.close().await?;
+            self.replace_file(&temp, &path).await
+        }
+        .await;
+
+        if written.is_err() {
+            if let Err(err) = self.remove_file(temp.clone()).await {
+                log::warn!("failed to remove {}: {}", temp, err);
+            }
+        }
+        written
+    }
+
+    /// Renames `src` over `dest`, atomically if the server supports it
+    async fn replace_file(&self, src: &Utf8Path, dest: &Utf8Path) -> SftpChannelResult<()> {
+        match self
+            .posix_rename(src.to_path_buf(), dest.to_path_buf())
+            .await
+        {
+            Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => {}
+            result => return result,
+        }
+        let err = match self
+            .rename(
+                src.to_path_buf(),
+                dest.to_path_buf(),
+                RenameOptions::default(),
+            )
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        // Servers of sftp version 3 refuse to rename over a file
+        if self.symlink_metadata(dest.to_path_buf()).await.is_err() {
+            return Err(err);
+        }
+        log::warn!(
+            "replacing {} non-atomically: posix-rename is unsupported",
+            dest
+        );
+        self.remove_file(dest.to_path_buf()).await?;
+        self.rename(
+            src.to_path_buf(),
+            dest.to_path_buf(),
+            RenameOptions::default(),
+        )
+        .await
+    }
+
+    /// Copies the remote file `src` to `dst` on the remote host,
+    /// preserving its permissions.  An existing `dst` is replaced.
+    ///
+    /// The server copies the data itself if it supports the `copy-data`
+    /// extension, as OpenSSH does since 9.0.  Otherwise the data is read
+    /// and written back, so that it makes a round trip through the client.
+    ///
+    /// ```no_run
+    /// # async fn example(sftp: wezterm_ssh::Sftp) -> wezterm_ssh::SftpChannelResult<()> {
+    /// sftp.copy("/srv/app/config.toml", "/srv/app/config.toml.orig")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy<T1, T2, E1, E2>(&self, src: T1, dst: T2) -> SftpChannelResult<()>
+    where
+        T1: TryInto<Utf8PathBuf, Error = E1>,
+        T2: TryInto<Utf8PathBuf, Error = E2>,
+        E1: Into<Box<dyn std::error::Error + Send + Sync>>,
+        E2: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let src: Utf8PathBuf = src.try_into().map_err(into_invalid_data)?;
+        let dst: Utf8PathBuf = dst.try_into().map_err(into_invalid_data)?;
+        let metadata = self.metadata(src.clone()).await?;
+        if !metadata.is_file() {
+            return Err(not_a_file(&src));
+        }
+        // Opening `dst` truncates it, which would lose the data if it is
+        // `src` by another name, such as through a symlink or `..`.
+        // A `dst` that doesn't exist can't be resolved, and isn't `src`.
+        let canonical_src = self.canonicalize(src.clone()).await?;
+        if let Ok(canonical_dst) = self.canonicalize(dst.clone()).await {
+            if canonical_src == canonical_dst {
+                return Err(into_invalid_input("cannot copy a file onto itself").into());
+            }
+        }
+        let mode = remote_mode(&metadata, DEFAULT_FILE_MODE);
+
+        let (reply, rx) = bounded(1);
+        self.tx
+            .send(SessionRequest::Sftp(SftpRequest::CopyData(
+                CopyData {
+                    src: src.clone(),
+                    dst: dst.clone(),
+                },
+                reply,
+            )))
+            .await?;
+        match rx.recv().await? {
+            Ok(()) => {
+                return self
+                    .set_metadata(dst, permissions_only(FileType::File, mode))
+                    .await;
+            }
+            Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => {
+                log::debug!("server can't copy {}, copying it through the client", src);
+            }
+            Err(err) => return Err(err),
+        }
+
+        let chunk_size = chunk_size(
+            self.limits()
+                .await
+                .ok()
+                .map(|l| l.max_read_len.min(l.max_write_len)),
+        );
+        let mut from = self.open(src).await?;
+        let mut to = self.create(dst).await?;
+        copy_in_chunks(&mut from, &mut to, chunk_size).await?;
+        to.set_metadata(permissions_only(FileType::File, mode))
+            .await?;
+        to.close().await?;
+        Ok(())
+    }
+
+    /// Recursively copies the local directory `local` to `remote`.
+    ///
+    /// `remote` and any missing intermediate directories are created, and
+    /// directories that already exist are merged into, replacing existing
+    /// files of the same name. The permissions of files and directories are
+    /// preserved, and symlinks are recreated rather than followed.
+    pub async fn upload_dir<L, R, E>(&self, local: L, remote: R) -> SftpChannelResult<()>
+    where
+        L: AsRef<Path>,
+        R: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let local = local.as_ref().to_path_buf();
+        let remote: Utf8PathBuf = remote.try_into().map_err(into_invalid_data)?;
+
+        let metadata = smol::fs::metadata(&local).await?;
+        if !metadata.is_dir() {
+            return Err(not_a_directory(local.display()));
+        }
+        self.create_remote_dir_all(&remote).await?;
+        let chunk_size = chunk_size(self.limits().await.ok().map(|l| l.max_write_len));
+
+        let mut dir_modes = vec![(remote.clone(), local_mode(&metadata))];
+        let mut pending = vec![(local, remote)];
+        while let Some((local_dir, remote_dir)) = pending.pop() {
+            let mut entries = smol::fs::read_dir(&local_dir).await?;
+            while let Some(entry) = entries.next().await {
+                let entry = entry?;
+                let local_path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_str().ok_or_else(|| {
+                    into_invalid_data(format!("{} is not valid UTF-8", local_path.display()))
+                })?;
+                let remote_path = remote_dir.join(name);
+
+                let metadata = smol::fs::symlink_metadata(&local_path).await?;
+                let file_type = metadata.file_type();
+                if file_type.is_symlink() {
+                    let target: Utf8PathBuf = smol::fs::read_link(&local_path)
+                        .await?
+                        .try_into()
+                        .map_err(into_invalid_data)?;
+                    if self.symlink_metadata(remote_path.clone()).await.is_ok() {
+                        self.remove_file(remote_path.clone()).await?;
+                    }
+                    self.symlink(target, remote_path).await?;
+                } else if file_type.is_dir() {
+                    match self.symlink_metadata(remote_path.clone()).await {
+                        Ok(existing) if existing.is_dir() => {}
+                        _ => {
+                            self.create_dir(remote_path.clone(), OWNER_RWX as i32)
+                                .await?
+                        }
+                    }
+                    dir_modes.push((remote_path.clone(), local_mode(&metadata)));
+                    pending.push((local_path, remote_path));
+                } else if file_type.is_file() {
+                    self.upload_file_with(
+                        &local_path,
+                        &remote_path,
+                        &metadata,
+                        chunk_size,
+                        Resume::Never,
+                    )
+                    .await?;
+                } else {
+                    log::warn!("skipping upload of special file {}", local_path.display());
+                }
+            }
+        }
+
+        // Apply the permissions of the directories once their contents
+        // are in place, children before their parents, as they may
+        // not be writable
+        for (dir, mode) in dir_modes.into_iter().rev() {
+            self.set_metadata(dir, permissions_only(FileType::Dir, mode))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Recursively copies the remote directory `remote` to `local`.
+    ///
+    /// `local` and any missing intermediate directories are created, and
+    /// directories that already exist are merged into, replacing existing
+    /// files of the same name. The permissions of files and directories are
+    /// preserved, and symlinks are recreated rather than followed.
+    pub async fn download_dir<R, L, E>(&self, remote: R, local: L) -> SftpChannelResult<()>
+    where
+        R: TryInto<Utf8PathBuf, Error = E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+        L: AsRef<Path>,
+    {
+        let remote: Utf8PathBuf = remote.try_into().map_err(into_invalid_data)?;
+        let local = local.as_ref().to_path_buf();
+
+        let metadata = self.metadata(remote.clone()).await?;
+        if !metadata.is_dir() {
+            return Err(not_a_directory(&remote));
+        }
+        smol::fs::create_dir_all(&local).await?;
+        let chunk_size = chunk_size(self.limits().await.ok().map(|l| l.max_read_len));
+
+        let mut dir_modes = vec![(local.clone(), remote_mode(&metadata, DEFAULT_DIR_MODE))];
+        let mut pending = vec![(remote, local)];
+        while let Some((remote_dir, local_dir)) = pending.pop() {
+            // Huge directories are listed as their entries are needed
+            let entries = self.read_dir_stream(remote_dir).await?;
+            smol::pin!(entries);
+            while let Some(entry) = entries.next().await {
+                let (remote_path, metadata) = entry?;
+                let name = match remote_path.file_name() {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let local_path = local_dir.join(name);
+
+                if metadata.is_symlink() {
+                    let target = self.read_link(remote_path).await?;
+                    if smol::fs::symlink_metadata(&local_path).await.is_ok() {
+                        smol::fs::remove_file(&local_path).await?;
+                    }
+                    create_local_symlink(target.as_std_path(), &local_path).await?;
+                } else if metadata.is_dir() {
+                    match smol::fs::symlink_metadata(&local_path).await {
+                        Ok(existing) if existing.is_dir() => {}
+                        _ => smol::fs::create_dir(&local_path).await?,
+                    }
+                    set_local_mode(&local_path, OWNER_RWX).await?;
+                    dir_modes.push((local_path.clone(), remote_mode(&metadata, DEFAULT_DIR_MODE)));
+                    pending.push((remote_path, local_path));
+                } else if metadata.is_file() {
+                    self.download_file_with(
+                        &remote_path,
+                        &local_path,
+                        &metadata,
+                        chunk_size,
+                        Resume::Never,
+                    )
+                    .await?;
+                } else {
+                    log::warn!("skipping download of special file {}", remote_path);
+                }
+            }
+        }
+
+        // Apply the permissions of the directories once their contents
+        // are in place, children before their parents, as they may
+        // not be writable
+        for (dir, mode) in dir_modes.into_iter().rev() {
+            set_local_mode(&dir, mode).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn upload_file_with(
+        &self,
+        local: &Path,
+        remote: &Utf8Path,
+        metadata: &std::fs::Metadata,
+        chunk_size: usize,
+        resume: Resume,
+    ) -> SftpChannelResult<u64> {
+        let mut src = smol::fs::File::open(local).await?;
+        let dest_len = match resume {
+            Resume::Never => None,
+            Resume::Size | Resume::Checksum => match self.metadata(remote.to_path_buf()).await {
+                Ok(existing) if existing.is_file() => existing.size,
+                _ => None,
+            },
+        };
+
+        let mut offset = resume_offset(resume, Some(metadata.len()), dest_len);
+        if offset > 0 && resume == Resume::Checksum {
+            let start = offset.saturating_sub(CHECKSUM_LEN);
+            let mut existing = self.open(remote.to_path_buf()).await?;
+            existing.seek(start).await?;
+            src.seek(SeekFrom::Start(start)).await?;
+            if !same_data(&mut src, &mut existing, offset - start, chunk_size).await? {
+                log::warn!("{} doesn't match {}, replacing it", remote, local.display());
+                offset = 0;
+            }
+        }
+        src.seek(SeekFrom::Start(offset)).await?;
+
+        let mut dest = if offset == 0 {
+            self.create(remote.to_path_buf()).await?
+        } else {
+            log::debug!("resuming upload of {} at {}", local.display(), offset);
+            let dest = self
+                .open_with_mode(
+                    remote.to_path_buf(),
+                    OpenOptions {
+                        read: false,
+                        write: Some(WriteMode::Append),
+                        mode: 0o666,
+                        ty: OpenFileType::File,
+                    },
+                )
+                .await?;
+            // For servers that ignore the append flag
+            dest.seek(offset).await?;
+            dest
+        };
+        copy_in_chunks(&mut src, &mut dest, chunk_size).await?;
+        dest.set_metadata(permissions_only(FileType::File, local_mode(metadata)))
+            .await?;
+        dest.close().await?;
+        Ok(offset)
+    }
+
+    async fn download_file_with(
+        &self,
+        remote: &Utf8Path,
+        local: &Path,
+        metadata: &Metadata,
+        chunk_size: usize,
+        resume: Resume,
+    ) -> SftpChannelResult<u64> {
+        let mut src = self.open(remote.to_path_buf()).await?;
+        let dest_len = match resume {
+            Resume::Never => None,
+            Resume::Size | Resume::Checksum => match smol::fs::metadata(local).await {
+                Ok(existing) if existing.is_file() => Some(existing.len()),
+                _ => None,
+            },
+        };
+
+        let mut offset = resume_offset(resume, metadata.size, dest_len);
+        if offset > 0 && resume == Resume::Checksum {
+            let start = offset.saturating_sub(CHECKSUM_LEN);
+            let mut existing = smol::fs::File::open(local).await?;
+            existing.seek(SeekFrom::Start(start)).await?;
+            src.seek(start).await?;
+            if !same_data(&mut src, &mut existing, offset - start, chunk_size).await? {
+                log::warn!("{} doesn't match {}, replacing it", local.display(), remote);
+                offset = 0;
+            }
+        }
+        src.seek(offset).await?;
+
+        let mut dest = if offset == 0 {
+            smol::fs::File::create(local).await?
+        } else {
+            log::debug!("resuming download of {} at {}", remote, offset);
+            smol::fs::OpenOptions::new()
+                .append(true)
+                .open(local)
+                .await?
+        };
+        copy_in_chunks(&mut src, &mut dest, chunk_size).await?;
+        dest.flush().await?;
+        drop(dest);
+        set_local_mode(local, remote_mode(metadata, DEFAULT_FILE_MODE)).await?;
+        Ok(offset)
+    }
+
+    /// Creates `dir` and any of its missing parents on the remote host
+    async fn create_remote_dir_all(&self, dir: &Utf8PathBuf) -> SftpChannelResult<()> {
+        let mut missing = vec![];
+        for ancestor in dir.ancestors() {
+            if ancestor.as_str().is_empty() {
+                break;
+            }
+            match self.metadata(ancestor.to_path_buf()).await {
+                Ok(metadata) if metadata.is_dir() => break,
+                Ok(_) => return Err(not_a_directory(ancestor)),
+                Err(_) => missing.push(ancestor.to_path_buf()),
+            }
+        }
+        for dir in missing.into_iter().rev() {
+            self.create_dir(dir, DEFAULT_DIR_MODE as i32).await?;
+        }
+        Ok(())
+    }
+}
+
+fn remote_mode(metadata: &Metadata, default: u32) -> u32 {
+    metadata
+        .permissions
+        .map(FilePermissions::to_unix_mode)
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunk_size_follows_limits() {
+        assert_eq!(chunk_size(None), DEFAULT_CHUNK_SIZE);
+        assert_eq!(chunk_size(Some(0)), DEFAULT_CHUNK_SIZE);
+        assert_eq!(chunk_size(Some(261120)), 261120);
+        assert_eq!(chunk_size(Some(u64::MAX)), MAX_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn resume_offset_requires_smaller_destination() {
+        assert_eq!(resume_offset(Resume::Never, Some(10), Some(4)), 0);
+        assert_eq!(resume_offset(Resume::Size, Some(10), Some(4)), 4);
+        assert_eq!(resume_offset(Resume::Checksum, Some(10), Some(10)), 10);
+        assert_eq!(resume_offset(Resume::Size, Some(10), Some(11)), 0);
+        assert_eq!(resume_offset(Resume::Size, Some(10), None), 0);
+        assert_eq!(resume_offset(Resume::Size, None, Some(4)), 0);
+    }
+
+    #[test]
+    fn same_data_compares_digests() {
+        smol::block_on(async {
+            let (mut src, mut dest) = (&b"abcdef"[..], &b"abcxyz"[..]);
+            assert!(same_data(&mut src, &mut dest, 3, 2).await.unwrap());
+            assert!(!same_data(&mut src, &mut dest, 3, 2).await.unwrap());
+
+            let (mut src, mut dest) = (&b"abc"[..], &b"ab"[..]);
+            assert!(same_data(&mut src, &mut dest, 3, 2).await.is_err());
+        });
+    }
+}
@@ -1,4 +1,5 @@
 use bitflags::bitflags;
+use std::collections::HashMap;
 
 bitflags! {
     struct FileTypeFlags: u32 {
@@ -192,6 +193,31 @@ impl Metadata {
     }
 }
 
+/// Represents the names of the users and groups that own some remote
+/// files, as looked up by [`Sftp::resolve_owner_names`]
+///
+/// [`Sftp::resolve_owner_names`]: crate::Sftp::resolve_owner_names
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OwnerNames {
+    /// Names of users, by uid
+    pub users: HashMap<u32, String>,
+
+    /// Names of groups, by gid
+    pub groups: HashMap<u32, String>,
+}
+
+impl OwnerNames {
+    /// Returns the name of the owner of the file, if it is known
+    pub fn owner_name(&self, metadata: &Metadata) -> Option<&str> {
+        self.users.get(&metadata.uid?).map(String::as_str)
+    }
+
+    /// Returns the name of the owning group of the file, if it is known
+    pub fn group_name(&self, metadata: &Metadata) -> Option<&str> {
+        self.groups.get(&metadata.gid?).map(String::as_str)
+    }
+}
+
 /// Represents options to provide when opening a file or directory
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct OpenOptions {
@@ -249,6 +275,87 @@ impl Default for RenameOptions {
     }
 }
 
+/// Represents statistics about a remote filesystem, as reported by
+/// statvfs(3) on the remote host
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct FilesystemStats {
+    /// Preferred block size for I/O
+    pub block_size: u64,
+
+    /// Fundamental block size, which is the unit of the block counts
+    pub fragment_size: u64,
+
+    /// Size of the filesystem, in fragments
+    pub blocks: u64,
+
+    /// Number of free fragments
+    pub blocks_free: u64,
+
+    /// Number of free fragments that are available to unprivileged users
+    pub blocks_available: u64,
+
+    /// Number of inodes
+    pub files: u64,
+
+    /// Number of free inodes
+    pub files_free: u64,
+
+    /// Number of free inodes that are available to unprivileged users
+    pub files_available: u64,
+
+    /// Filesystem ID
+    pub fsid: u64,
+
+    /// Mount flags
+    pub flags: u64,
+
+    /// Maximum length of a filename
+    pub max_name_len: u64,
+}
+
+impl FilesystemStats {
+    const READ_ONLY: u64 = 0x1;
+
+    /// Returns the size of the filesystem in bytes
+    pub fn total_bytes(&self) -> u64 {
+        self.blocks.saturating_mul(self.fragment_size)
+    }
+
+    /// Returns the number of free bytes
+    pub fn free_bytes(&self) -> u64 {
+        self.blocks_free.saturating_mul(self.fragment_size)
+    }
+
+    /// Returns the number of free bytes that are available to
+    /// unprivileged users
+    pub fn available_bytes(&self) -> u64 {
+        self.blocks_available.saturating_mul(self.fragment_size)
+    }
+
+    /// Returns true if the filesystem is mounted read-only
+    pub fn is_read_only(&self) -> bool {
+        self.flags & Self::READ_ONLY != 0
+    }
+}
+
+/// Represents the limits of a server on the requests that it accepts,
+/// as reported by the `limits@openssh.com` extension.  A limit of 0
+/// means that the server doesn't report it.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum length of a packet, including its header
+    pub max_packet_len: u64,
+
+    /// Maximum number of bytes returned by a single read
+    pub max_read_len: u64,
+
+    /// Maximum number of bytes accepted by a single write
+    pub max_write_len: u64,
+
+    /// Maximum number of files and directories that can be open at once
+    pub max_open_handles: u64,
+}
+
 /// Contains libssh2-specific implementations
 #[cfg(feature = "ssh2")]
 mod ssh2_impl {
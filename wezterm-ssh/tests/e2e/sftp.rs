@@ -4,7 +4,10 @@ use assert_fs::TempDir;
 use predicates::prelude::*;
 use rstest::*;
 use std::convert::TryInto;
-use wezterm_ssh::{FileType, SftpChannelError, SftpError, Utf8PathBuf};
+use wezterm_ssh::{
+    FileType, HashAlgorithm, Resume, SftpChannelError, SftpError, Symlinks, TailOptions,
+    Utf8PathBuf,
+};
 
 // Sftp file tests
 mod file;
@@ -74,6 +77,118 @@ async fn read_dir_should_return_list_of_directories_files_and_symlinks(
     );
 }
 
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn read_dir_stream_should_return_all_entries_across_batches(
+    #[future] session: SessionWithSshd,
+) {
+    use smol::stream::StreamExt;
+    let session: SessionWithSshd = session.await;
+
+    // More files than are requested at a time
+    let temp = TempDir::new().unwrap();
+    let mut expected = vec![];
+    for i in 0..600 {
+        let file = temp.child(format!("file{i}"));
+        file.touch().unwrap();
+        let path: Utf8PathBuf = file.path().to_path_buf().try_into().unwrap();
+        expected.push((path, "file"));
+    }
+    expected.sort_unstable();
+
+    let mut contents = session
+        .sftp()
+        .read_dir_stream(temp.path().to_path_buf())
+        .await
+        .expect("Failed to open directory")
+        .map(|entry| {
+            let (p, s) = entry.expect("Failed to read directory");
+            (p, file_type_to_str(s.ty))
+        })
+        .collect::<Vec<(Utf8PathBuf, &'static str)>>()
+        .await;
+    contents.sort_unstable();
+
+    assert_eq!(contents, expected);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn read_dir_stream_should_fail_if_path_missing(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+
+    let result = session
+        .sftp()
+        .read_dir_stream(temp.child("missing").path().to_path_buf())
+        .await;
+    assert!(result.is_err(), "Unexpectedly opened missing directory");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn glob_should_return_matching_paths_with_metadata(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    // $TEMP/a.log
+    // $TEMP/b.txt
+    // $TEMP/.hidden.log
+    // $TEMP/src/main.rs
+    // $TEMP/src/util/mod.rs
+    // $TEMP/src/util/notes.txt
+    let temp = TempDir::new().unwrap();
+    temp.child("src/util").create_dir_all().unwrap();
+    for name in [
+        "a.log",
+        "b.txt",
+        ".hidden.log",
+        "src/main.rs",
+        "src/util/mod.rs",
+        "src/util/notes.txt",
+    ] {
+        temp.child(name).touch().unwrap();
+    }
+    let root: Utf8PathBuf = temp.path().to_path_buf().try_into().unwrap();
+
+    let glob = |pattern: &str| {
+        let pattern = format!("{}/{}", root, pattern);
+        let sftp = session.sftp();
+        async move {
+            sftp.glob(&pattern)
+                .await
+                .expect("Failed to glob")
+                .into_iter()
+                .map(|(p, s)| (p, file_type_to_str(s.ty)))
+                .collect::<Vec<(Utf8PathBuf, &'static str)>>()
+        }
+    };
+
+    assert_eq!(glob("*.log").await, vec![(root.join("a.log"), "file")]);
+    assert_eq!(
+        glob("**/*.rs").await,
+        vec![
+            (root.join("src/main.rs"), "file"),
+            (root.join("src/util/mod.rs"), "file"),
+        ]
+    );
+    assert_eq!(
+        glob("src/*").await,
+        vec![
+            (root.join("src/main.rs"), "file"),
+            (root.join("src/util"), "dir"),
+        ]
+    );
+    assert_eq!(
+        glob("s?c/u[st]il/*.txt").await,
+        vec![(root.join("src/util/notes.txt"), "file")]
+    );
+    assert!(glob("missing/**/*.rs").await.is_empty());
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -193,6 +308,71 @@ async fn remove_dir_should_return_an_error_if_failed_to_remove_directory(
     file.assert(predicate::path::is_file());
 }
 
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn remove_dir_all_should_remove_a_directory_and_its_contents(
+    #[future] session: SessionWithSshd,
+) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+
+    let outside = temp.child("outside");
+    outside.create_dir_all().unwrap();
+    outside.child("file").touch().unwrap();
+
+    let dir = temp.child("dir");
+    dir.child("sub").child("nested").create_dir_all().unwrap();
+    dir.child("file").touch().unwrap();
+    dir.child("sub").child("file").touch().unwrap();
+    dir.child("link").symlink_to_dir(outside.path()).unwrap();
+    dir.child("sub")
+        .child("parent")
+        .symlink_to_dir(temp.path())
+        .unwrap();
+
+    // Symlinks are removed without touching what they point to
+    session
+        .sftp()
+        .remove_dir_all(dir.path().to_path_buf(), Symlinks::Skip)
+        .await
+        .expect("Failed to remove directory");
+    dir.assert(predicate::path::missing());
+    outside.child("file").assert(predicate::path::is_file());
+
+    // Followed symlinks remove the directories that they point to, but
+    // never a directory that holds the one being removed
+    dir.child("sub").create_dir_all().unwrap();
+    dir.child("link").symlink_to_dir(outside.path()).unwrap();
+    dir.child("sub")
+        .child("parent")
+        .symlink_to_dir(temp.path())
+        .unwrap();
+    session
+        .sftp()
+        .remove_dir_all(dir.path().to_path_buf(), Symlinks::Follow)
+        .await
+        .expect("Failed to remove directory following symlinks");
+    dir.assert(predicate::path::missing());
+    outside.assert(predicate::path::missing());
+    temp.assert(predicate::path::is_dir());
+
+    // Files are not directories
+    let file = temp.child("file");
+    file.touch().unwrap();
+    let result = session
+        .sftp()
+        .remove_dir_all(file.path().to_path_buf(), Symlinks::Skip)
+        .await;
+    assert!(
+        result.is_err(),
+        "Unexpectedly succeeded in removing file: {:?}",
+        result
+    );
+    file.assert(predicate::path::is_file());
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -493,6 +673,52 @@ async fn symlink_should_succeed_even_if_path_missing(#[future] session: SessionW
     link.assert(predicate::path::is_symlink());
 }
 
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn hardlink_should_create_another_name_for_file(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::MetadataExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.write_str("some text").unwrap();
+
+    let link = temp.child("link");
+
+    session
+        .sftp()
+        .hardlink(file.path().to_path_buf(), link.path().to_path_buf())
+        .await
+        .expect("Failed to create hardlink");
+
+    link.assert("some text");
+    let file_metadata = std::fs::metadata(file.path()).unwrap();
+    let link_metadata = std::fs::symlink_metadata(link.path()).unwrap();
+    assert!(!link_metadata.file_type().is_symlink());
+    assert_eq!(link_metadata.ino(), file_metadata.ino());
+    assert_eq!(file_metadata.nlink(), 2);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn hardlink_should_fail_if_path_missing(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    let link = temp.child("link");
+
+    let result = session
+        .sftp()
+        .hardlink(file.path().to_path_buf(), link.path().to_path_buf())
+        .await;
+    assert!(result.is_err(), "Unexpectedly created hardlink");
+    link.assert(predicate::path::missing());
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -739,6 +965,52 @@ async fn rename_should_fail_if_source_path_missing(#[future] session: SessionWit
     );
 }
 
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn posix_rename_should_replace_existing_file(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.write_str("new text").unwrap();
+
+    let dst = temp.child("dst");
+    dst.write_str("old text").unwrap();
+
+    session
+        .sftp()
+        .posix_rename(file.path().to_path_buf(), dst.path().to_path_buf())
+        .await
+        .expect("Failed to rename file");
+
+    file.assert(predicate::path::missing());
+    dst.assert("new text");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn posix_rename_should_fail_if_source_path_missing(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let missing = temp.child("missing");
+    let dst = temp.child("dst");
+    dst.write_str("old text").unwrap();
+
+    let result = session
+        .sftp()
+        .posix_rename(missing.path().to_path_buf(), dst.path().to_path_buf())
+        .await;
+    assert!(
+        result.is_err(),
+        "Rename unexpectedly succeeded with missing path: {:?}",
+        result
+    );
+    dst.assert("old text");
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -843,3 +1115,562 @@ async fn remove_file_should_fail_if_path_missing(#[future] session: SessionWithS
         result
     );
 }
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn chmod_should_change_permissions(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::PermissionsExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.write_str("contents").unwrap();
+
+    session
+        .sftp()
+        .chmod(file.path().to_path_buf(), 0o640)
+        .await
+        .expect("Failed to change permissions");
+
+    let mode = std::fs::metadata(file.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+    file.assert("contents");
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn chown_should_change_owner_and_group(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::MetadataExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.touch().unwrap();
+
+    // Without privileges, files can only be given to ourselves
+    let metadata = std::fs::metadata(file.path()).unwrap();
+    session
+        .sftp()
+        .chown(file.path().to_path_buf(), metadata.uid(), metadata.gid())
+        .await
+        .expect("Failed to change owner");
+
+    let remote = session
+        .sftp()
+        .metadata(file.path().to_path_buf())
+        .await
+        .expect("Failed to get metadata for file");
+    assert_eq!(remote.uid, Some(metadata.uid()));
+    assert_eq!(remote.gid, Some(metadata.gid()));
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn set_times_should_change_access_and_modification_times(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::MetadataExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.touch().unwrap();
+
+    session
+        .sftp()
+        .set_times(file.path().to_path_buf(), 1_000_000_000, 1_500_000_000)
+        .await
+        .expect("Failed to change times");
+
+    let metadata = std::fs::metadata(file.path()).unwrap();
+    assert_eq!(metadata.atime(), 1_000_000_000);
+    assert_eq!(metadata.mtime(), 1_500_000_000);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn set_metadata_should_fail_if_uid_is_set_without_gid(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.touch().unwrap();
+
+    let mut metadata = session
+        .sftp()
+        .metadata(file.path().to_path_buf())
+        .await
+        .expect("Failed to get metadata for file");
+    metadata.gid = None;
+
+    let result = session
+        .sftp()
+        .set_metadata(file.path().to_path_buf(), metadata)
+        .await;
+    assert!(
+        matches!(result, Err(SftpChannelError::FileIo(_))),
+        "Unexpected result: {:?}",
+        result
+    );
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn statvfs_should_report_filesystem_capacity(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+
+    let stats = session
+        .sftp()
+        .statvfs(temp.path().to_path_buf())
+        .await
+        .expect("Failed to get filesystem stats");
+    assert!(stats.fragment_size > 0);
+    assert!(stats.total_bytes() > 0);
+    assert!(stats.available_bytes() <= stats.free_bytes());
+    assert!(stats.free_bytes() <= stats.total_bytes());
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn limits_should_be_reported_if_supported(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    // limits@openssh.com is only available from OpenSSH 8.5
+    match session.sftp().limits().await {
+        Ok(limits) => {
+            assert!(limits.max_read_len > 0);
+            assert!(limits.max_write_len > 0);
+        }
+        Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => {}
+        Err(err) => panic!("Failed to get limits: {:?}", err),
+    }
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn resolve_owner_names_should_name_owner_and_group(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    temp.child("file").touch().unwrap();
+
+    let entries = session
+        .sftp()
+        .read_dir(temp.path().to_path_buf())
+        .await
+        .expect("Failed to read directory");
+
+    let names = session
+        .sftp()
+        .resolve_owner_names(&entries)
+        .await
+        .expect("Failed to resolve owner names");
+    let metadata = &entries[0].1;
+    assert!(names.owner_name(metadata).is_some(), "{:?}", names);
+    assert!(names.group_name(metadata).is_some(), "{:?}", names);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn expand_path_should_resolve_tilde_to_home_directory(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    // expand-path@openssh.com is only available from OpenSSH 8.7
+    let home = match session.sftp().expand_path("~").await {
+        Ok(home) => home,
+        Err(SftpChannelError::Sftp(SftpError::OpUnsupported)) => return,
+        Err(err) => panic!("Failed to expand path: {:?}", err),
+    };
+    assert!(home.is_absolute(), "Unexpected home directory: {}", home);
+
+    let expanded = session
+        .sftp()
+        .expand_path("~/missing")
+        .await
+        .expect("Failed to expand path");
+    assert_eq!(expanded, home.join("missing"));
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn statvfs_should_fail_if_path_missing(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+
+    let result = session
+        .sftp()
+        .statvfs(temp.child("missing").path().to_path_buf())
+        .await;
+    assert!(result.is_err(), "Unexpectedly got stats: {:?}", result);
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn upload_dir_should_copy_tree_preserving_permissions(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::PermissionsExt;
+    let session: SessionWithSshd = session.await;
+
+    // $SRC/file1
+    // $SRC/sub/script (mode 0o755)
+    // $SRC/sub/link -> script
+    let src = TempDir::new().unwrap();
+    src.child("file1").write_str("one").unwrap();
+    let script = src.child("sub").child("script");
+    script.write_str("#!/bin/sh").unwrap();
+    std::fs::set_permissions(script.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    src.child("sub")
+        .child("link")
+        .symlink_to_file("script")
+        .unwrap();
+
+    let dest = TempDir::new().unwrap();
+    let remote = dest.child("a").child("b");
+
+    session
+        .sftp()
+        .upload_dir(src.path(), remote.path().to_path_buf())
+        .await
+        .expect("Failed to upload directory");
+
+    remote.child("file1").assert("one");
+    remote.child("sub").child("script").assert("#!/bin/sh");
+    let mode = std::fs::metadata(remote.child("sub").child("script").path())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o755);
+    assert_eq!(
+        std::fs::read_link(remote.child("sub").child("link").path()).unwrap(),
+        std::path::Path::new("script")
+    );
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn download_dir_should_copy_tree_preserving_permissions(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::PermissionsExt;
+    let session: SessionWithSshd = session.await;
+
+    // $SRC/file1
+    // $SRC/sub/readonly (mode 0o444)
+    let src = TempDir::new().unwrap();
+    src.child("file1").write_str("one").unwrap();
+    let readonly = src.child("sub").child("readonly");
+    readonly.write_str("two").unwrap();
+    std::fs::set_permissions(readonly.path(), std::fs::Permissions::from_mode(0o444)).unwrap();
+
+    let dest = TempDir::new().unwrap();
+    let local = dest.child("a").child("b");
+
+    session
+        .sftp()
+        .download_dir(src.path().to_path_buf(), local.path())
+        .await
+        .expect("Failed to download directory");
+
+    local.child("file1").assert("one");
+    local.child("sub").child("readonly").assert("two");
+    let mode = std::fs::metadata(local.child("sub").child("readonly").path())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o444);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn upload_dir_should_fail_if_source_is_not_a_directory(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.touch().unwrap();
+
+    let result = session
+        .sftp()
+        .upload_dir(file.path(), temp.child("dest").path().to_path_buf())
+        .await;
+    assert!(
+        result.is_err(),
+        "Upload unexpectedly succeeded from a file: {:?}",
+        result
+    );
+    temp.child("dest").assert(predicate::path::missing());
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn upload_file_should_resume_partial_destination(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let src = temp.child("src");
+    src.write_str("0123456789").unwrap();
+    let dest = temp.child("dest");
+    // Not what the source holds, to show that only the rest is sent
+    dest.write_str("abcd").unwrap();
+
+    let resumed = session
+        .sftp()
+        .upload_file(src.path(), dest.path().to_path_buf(), Resume::Size)
+        .await
+        .expect("Failed to upload file");
+    assert_eq!(resumed, 4);
+    dest.assert("abcd456789");
+
+    let resumed = session
+        .sftp()
+        .upload_file(src.path(), dest.path().to_path_buf(), Resume::Never)
+        .await
+        .expect("Failed to upload file");
+    assert_eq!(resumed, 0);
+    dest.assert("0123456789");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn download_file_should_resume_partial_destination(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let src = temp.child("src");
+    src.write_str("0123456789").unwrap();
+    let dest = temp.child("dest");
+    dest.write_str("0123").unwrap();
+
+    let resumed = session
+        .sftp()
+        .download_file(src.path().to_path_buf(), dest.path(), Resume::Checksum)
+        .await
+        .expect("Failed to download file");
+    assert_eq!(resumed, 4);
+    dest.assert("0123456789");
+
+    // A destination that is larger than the source is replaced
+    dest.write_str("0123456789abc").unwrap();
+    let resumed = session
+        .sftp()
+        .download_file(src.path().to_path_buf(), dest.path(), Resume::Size)
+        .await
+        .expect("Failed to download file");
+    assert_eq!(resumed, 0);
+    dest.assert("0123456789");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn resume_with_checksum_should_replace_mismatched_destination(
+    #[future] session: SessionWithSshd,
+) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let src = temp.child("src");
+    src.write_str("0123456789").unwrap();
+    let dest = temp.child("dest");
+    dest.write_str("abcd").unwrap();
+
+    let resumed = session
+        .sftp()
+        .upload_file(src.path(), dest.path().to_path_buf(), Resume::Checksum)
+        .await
+        .expect("Failed to upload file");
+    assert_eq!(resumed, 0);
+    dest.assert("0123456789");
+
+    dest.write_str("abcd").unwrap();
+    let resumed = session
+        .sftp()
+        .download_file(src.path().to_path_buf(), dest.path(), Resume::Checksum)
+        .await
+        .expect("Failed to download file");
+    assert_eq!(resumed, 0);
+    dest.assert("0123456789");
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn write_atomic_should_replace_file_keeping_permissions(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::PermissionsExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let new = temp.child("new");
+    session
+        .sftp()
+        .write_atomic(new.path().to_path_buf(), &b"new file"[..])
+        .await
+        .expect("Failed to write new file");
+    new.assert("new file");
+
+    let dest = temp.child("dest");
+    dest.write_str("old text").unwrap();
+    std::fs::set_permissions(dest.path(), std::fs::Permissions::from_mode(0o640)).unwrap();
+    session
+        .sftp()
+        .write_atomic(dest.path().to_path_buf(), &b"new text"[..])
+        .await
+        .expect("Failed to replace file");
+    dest.assert("new text");
+    let mode = std::fs::metadata(dest.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+
+    // Only the two files remain
+    assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 2);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn tail_should_follow_appends_truncation_and_rotation(#[future] session: SessionWithSshd) {
+    use smol::stream::StreamExt;
+    use std::io::Write;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let log = temp.child("log");
+    log.write_str("one\n").unwrap();
+
+    let options = TailOptions {
+        interval: std::time::Duration::from_millis(10),
+        from_start: true,
+    };
+    let stream = session
+        .sftp()
+        .tail(log.path().to_path_buf(), options)
+        .await
+        .expect("Failed to tail file");
+    smol::pin!(stream);
+    let data = stream.next().await.unwrap().expect("Failed to read");
+    assert_eq!(data, b"one\n");
+
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(log.path())
+        .unwrap()
+        .write_all(b"two\n")
+        .unwrap();
+    let data = stream.next().await.unwrap().expect("Failed to read");
+    assert_eq!(data, b"two\n");
+
+    // A larger file replaces the one that was renamed away
+    std::fs::rename(log.path(), temp.child("log.1").path()).unwrap();
+    let new = temp.child("log.new");
+    new.write_str("three three three\n").unwrap();
+    std::fs::rename(new.path(), log.path()).unwrap();
+    let data = stream.next().await.unwrap().expect("Failed to read");
+    assert_eq!(data, b"three three three\n");
+
+    // Truncation
+    log.write_str("four\n").unwrap();
+    let data = stream.next().await.unwrap().expect("Failed to read");
+    assert_eq!(data, b"four\n");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn checksum_should_match_the_digest_of_the_local_file(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("file");
+    file.write_str("some file contents").unwrap();
+
+    // OpenSSH lacks the check-file extensions, so this hashes the data
+    // that it reads
+    let remote = session
+        .sftp()
+        .checksum(file.path().to_path_buf(), HashAlgorithm::Sha256)
+        .await
+        .expect("Failed to compute checksum");
+    let local = HashAlgorithm::Sha256
+        .digest(&mut smol::fs::File::open(file.path()).await.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(remote, local);
+
+    let result = session
+        .sftp()
+        .checksum(
+            temp.child("missing").path().to_path_buf(),
+            HashAlgorithm::Sha256,
+        )
+        .await;
+    assert!(
+        result.is_err(),
+        "Unexpectedly computed checksum of missing file: {:?}",
+        result
+    );
+}
+
+#[cfg(unix)]
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn copy_should_duplicate_file_keeping_permissions(#[future] session: SessionWithSshd) {
+    use std::os::unix::fs::PermissionsExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let src = temp.child("src");
+    src.write_str("some file contents").unwrap();
+    std::fs::set_permissions(src.path(), std::fs::Permissions::from_mode(0o640)).unwrap();
+    let dst = temp.child("dst");
+    dst.write_str("a longer file that is replaced").unwrap();
+
+    session
+        .sftp()
+        .copy(src.path().to_path_buf(), dst.path().to_path_buf())
+        .await
+        .expect("Failed to copy file");
+    dst.assert("some file contents");
+    src.assert("some file contents");
+    let mode = std::fs::metadata(dst.path()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+
+    let result = session
+        .sftp()
+        .copy(src.path().to_path_buf(), src.path().to_path_buf())
+        .await;
+    assert!(result.is_err(), "Unexpectedly copied file onto itself");
+    src.assert("some file contents");
+
+    // The same file by another name
+    let link = temp.child("link");
+    link.symlink_to_file(src.path()).unwrap();
+    let result = session
+        .sftp()
+        .copy(link.path().to_path_buf(), src.path().to_path_buf())
+        .await;
+    assert!(result.is_err(), "Unexpectedly copied file onto itself");
+    let result = session
+        .sftp()
+        .copy(src.path().to_path_buf(), temp.path().join(".").join("src"))
+        .await;
+    assert!(result.is_err(), "Unexpectedly copied file onto itself");
+    src.assert("some file contents");
+}
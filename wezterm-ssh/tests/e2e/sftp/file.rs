@@ -5,6 +5,8 @@ use rstest::*;
 use smol::io::{AsyncReadExt, AsyncWriteExt};
 use std::convert::TryInto;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use wezterm_ssh::RateLimit;
 
 #[rstest]
 #[smol_potat::test]
@@ -112,6 +114,164 @@ async fn should_support_async_reading(#[future] session: SessionWithSshd) {
         .expect("Failed to read file to string second time");
 }
 
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn seek_should_move_position_of_next_read(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+    file.write_str("some file contents").unwrap();
+
+    let mut remote_file = session
+        .sftp()
+        .open(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+
+    remote_file.seek(5).await.expect("Failed to seek");
+    let mut contents = String::new();
+    remote_file
+        .read_to_string(&mut contents)
+        .await
+        .expect("Failed to read file to string");
+    assert_eq!(contents, "file contents");
+}
+
+fn numbered_bytes(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn small_reads_should_return_data_read_ahead_in_order(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    // Larger than the data that is read ahead at a time
+    let expected = numbered_bytes(3 * 1024 * 1024 + 17);
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+    file.write_binary(&expected).unwrap();
+
+    let mut remote_file = session
+        .sftp()
+        .open(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+
+    let mut contents = vec![];
+    let mut buf = [0u8; 1000];
+    loop {
+        let n = remote_file.read(&mut buf).await.expect("Failed to read");
+        if n == 0 {
+            break;
+        }
+        contents.extend_from_slice(&buf[..n]);
+    }
+    assert!(contents == expected, "contents differ");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn seek_should_discard_data_read_ahead(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let expected = numbered_bytes(100_000);
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+    file.write_binary(&expected).unwrap();
+
+    let mut remote_file = session
+        .sftp()
+        .open(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+
+    let mut buf = [0u8; 10];
+    remote_file
+        .read_exact(&mut buf)
+        .await
+        .expect("Failed to read");
+    assert_eq!(&buf, &expected[..10]);
+
+    remote_file.seek(50_000).await.expect("Failed to seek");
+    remote_file
+        .read_exact(&mut buf)
+        .await
+        .expect("Failed to read");
+    assert_eq!(&buf, &expected[50_000..50_010]);
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn small_writes_should_be_written_in_order_when_flushed(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let expected = numbered_bytes(3 * 1024 * 1024 + 17);
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+
+    let mut remote_file = session
+        .sftp()
+        .create(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+
+    for chunk in expected.chunks(1000) {
+        remote_file.write_all(chunk).await.expect("Failed to write");
+    }
+    remote_file.flush().await.expect("Failed to flush");
+
+    let contents = std::fs::read(file.path()).unwrap();
+    assert!(contents == expected, "contents differ");
+}
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn rate_limit_should_slow_reads_and_writes(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let expected = numbered_bytes(250_000);
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+    file.write_binary(&expected).unwrap();
+
+    // A second's worth is used at once, and the remaining 150 KB take 1.5s
+    let sftp = session.sftp().with_rate_limit(RateLimit::new(100_000));
+    let start = Instant::now();
+    let mut remote_file = sftp
+        .open(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+    let mut contents = vec![];
+    remote_file
+        .read_to_end(&mut contents)
+        .await
+        .expect("Failed to read");
+    assert!(contents == expected, "contents differ");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    let copy = temp.child("copy");
+    let sftp = session.sftp().with_rate_limit(RateLimit::new(100_000));
+    let start = Instant::now();
+    let mut remote_file = sftp
+        .create(copy.path().to_path_buf())
+        .await
+        .expect("Failed to create remote file");
+    remote_file
+        .write_all(&expected)
+        .await
+        .expect("Failed to write");
+    remote_file.close().await.expect("Failed to close");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert!(std::fs::read(copy.path()).unwrap() == expected);
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -167,6 +327,61 @@ async fn should_support_async_flush(#[future] session: SessionWithSshd) {
         .expect("Failed to flush file second time");
 }
 
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn fsync_should_use_extension(#[future] session: SessionWithSshd) {
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let file = temp.child("test-file");
+
+    let mut remote_file = session
+        .sftp()
+        .create(file.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+    remote_file
+        .write_all(b"critical data")
+        .await
+        .expect("Failed to write to file");
+
+    let supported = session
+        .sftp()
+        .supports_extension("fsync@openssh.com")
+        .await
+        .expect("Failed to query sftp extensions");
+    let result = remote_file.fsync().await;
+
+    if !supported {
+        assert!(
+            matches!(
+                result,
+                Err(wezterm_ssh::SftpChannelError::Sftp(
+                    wezterm_ssh::SftpError::OpUnsupported
+                ))
+            ),
+            "Unexpected result: {:?}",
+            result
+        );
+        return;
+    }
+
+    // The libssh bindings can't send the extension even though
+    // the server advertised it
+    #[cfg(feature = "libssh-rs")]
+    assert!(
+        matches!(result, Err(wezterm_ssh::SftpChannelError::NotImplemented)),
+        "Unexpected result: {:?}",
+        result
+    );
+    #[cfg(not(feature = "libssh-rs"))]
+    {
+        result.expect("Failed to fsync file");
+        file.assert("critical data");
+    }
+}
+
 #[rstest]
 #[smol_potat::test]
 #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
@@ -191,3 +406,53 @@ async fn should_support_async_close(#[future] session: SessionWithSshd) {
         .await
         .expect("Failed to close file second time");
 }
+
+#[rstest]
+#[smol_potat::test]
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), ignore)]
+async fn should_support_async_copy_and_buffered_reading(#[future] session: SessionWithSshd) {
+    use smol::io::AsyncBufReadExt;
+    let session: SessionWithSshd = session.await;
+
+    let temp = TempDir::new().unwrap();
+    let src = temp.child("src");
+    let contents = (0..10_000)
+        .map(|i| format!("line {}\n", i))
+        .collect::<String>();
+    src.write_str(&contents).unwrap();
+    let dest = temp.child("dest");
+
+    let remote_src = session
+        .sftp()
+        .open(src.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+    let mut remote_dest = session
+        .sftp()
+        .create(dest.path().to_path_buf())
+        .await
+        .expect("Failed to create remote file");
+
+    let copied = smol::io::copy(remote_src, &mut remote_dest)
+        .await
+        .expect("Failed to copy file");
+    remote_dest.close().await.expect("Failed to close file");
+    assert_eq!(copied, contents.len() as u64);
+    dest.assert(contents.as_str());
+
+    let remote_dest = session
+        .sftp()
+        .open(dest.path().to_path_buf())
+        .await
+        .expect("Failed to open remote file");
+    let mut lines = smol::io::BufReader::new(remote_dest).lines();
+    let mut count = 0;
+    while let Some(line) = smol::stream::StreamExt::next(&mut lines).await {
+        assert_eq!(
+            line.expect("Failed to read line"),
+            format!("line {}", count)
+        );
+        count += 1;
+    }
+    assert_eq!(count, 10_000);
+}
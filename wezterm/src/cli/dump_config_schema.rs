@@ -0,0 +1,12 @@
+use clap::Parser;
+
+#[derive(Debug, Parser, Clone)]
+pub struct DumpConfigSchema {}
+
+impl DumpConfigSchema {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let schema = config::meta::config_schema();
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Parser;
+use codec::GetPaneSnapshot;
+use mux::pane::PaneId;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct DumpPaneState {
+    /// Specify the target pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// Write the snapshot to this file rather than to stdout
+    #[arg(long, short = 'o', value_hint=clap::ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+impl DumpPaneState {
+    pub async fn run(&self, client: Client) -> anyhow::Result<()> {
+        // Images in the snapshot are decoded into blob storage
+        wezterm_blob_leases::register_storage(Arc::new(
+            wezterm_blob_leases::simple_tempdir::SimpleTempDir::new()?,
+        ))?;
+
+        let pane_id = client.resolve_pane_id(self.pane_id).await?;
+        let response = client
+            .get_pane_snapshot(GetPaneSnapshot { pane_id })
+            .await?;
+
+        let json = serde_json::to_string_pretty(&response.snapshot)?;
+        match &self.output {
+            Some(path) => std::fs::write(path, json)?,
+            None => {
+                let mut stdout = std::io::stdout();
+                stdout.write_all(json.as_bytes())?;
+                stdout.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use mux::pane::PaneId;
+use mux::window::WindowId;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct GetConfig {
+    /// Specify the target window by its id
+    #[arg(long, conflicts_with_all=&["pane_id"])]
+    window_id: Option<WindowId>,
+    /// Specify the current pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    ///
+    /// The pane is used to figure out which window
+    /// should be queried.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// The name of the config option, such as `font_size`
+    key: String,
+}
+
+/// Returns `window_id` if it was specified, otherwise the id
+/// of the window that contains the pane `pane_id`, which
+/// defaults to the current pane
+pub async fn resolve_window_id(
+    client: &Client,
+    window_id: Option<WindowId>,
+    pane_id: Option<PaneId>,
+) -> anyhow::Result<WindowId> {
+    if let Some(window_id) = window_id {
+        return Ok(window_id);
+    }
+    let pane_id = client.resolve_pane_id(pane_id).await?;
+    let panes = client.list_panes().await?;
+    for tabroot in panes.tabs {
+        let mut cursor = tabroot.into_tree().cursor();
+
+        loop {
+            if let Some(entry) = cursor.leaf_mut() {
+                if entry.pane_id == pane_id {
+                    return Ok(entry.window_id);
+                }
+            }
+            match cursor.preorder_next() {
+                Ok(c) => cursor = c,
+                Err(_) => break,
+            }
+        }
+    }
+    anyhow::bail!("unable to resolve current window");
+}
+
+impl GetConfig {
+    pub async fn run(self, client: Client) -> anyhow::Result<()> {
+        let window_id = resolve_window_id(&client, self.window_id, self.pane_id).await?;
+        let response = client
+            .get_window_config(codec::GetWindowConfig {
+                window_id,
+                key: self.key,
+            })
+            .await?;
+        println!("{}", response.value);
+        Ok(())
+    }
+}
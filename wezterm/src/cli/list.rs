@@ -5,12 +5,16 @@ use tabout::{tabulate_output, Alignment, Column};
 use wezterm_client::client::Client;
 use wezterm_term::TerminalSize;
 
-#[derive(Debug, Parser, Clone, Copy)]
+#[derive(Debug, Parser, Clone)]
 pub struct ListCommand {
     /// Controls the output format.
     /// "table" and "json" are possible formats.
     #[arg(long = "format", default_value = "table")]
     format: CliOutputFormatKind,
+
+    /// Only list the panes that have this tag
+    #[arg(long)]
+    tag: Option<String>,
 }
 
 impl ListCommand {
@@ -24,7 +28,11 @@ impl ListCommand {
             let mut cursor = tabroot.into_tree().cursor();
 
             loop {
-                if let Some(entry) = cursor.leaf_mut() {
+                if let Some(entry) = cursor.leaf_mut().filter(|entry| {
+                    self.tag
+                        .as_ref()
+                        .map_or(true, |tag| entry.tags.contains(tag))
+                }) {
                     let window_title = panes
                         .window_titles
                         .get(&entry.window_id)
@@ -139,6 +147,7 @@ struct CliListResultItem {
     is_active: bool,
     is_zoomed: bool,
     tty_name: Option<String>,
+    tags: Vec<String>,
 }
 
 impl CliListResultItem {
@@ -157,6 +166,7 @@ impl CliListResultItem {
             is_active_pane,
             is_zoomed_pane,
             tty_name,
+            tags,
             size:
                 TerminalSize {
                     rows,
@@ -197,6 +207,7 @@ impl CliListResultItem {
             is_active: is_active_pane,
             is_zoomed: is_zoomed_pane,
             tty_name,
+            tags,
         }
     }
 }
@@ -7,6 +7,9 @@ mod activate_pane;
 mod activate_pane_direction;
 mod activate_tab;
 mod adjust_pane_size;
+mod dump_config_schema;
+mod dump_pane_state;
+mod get_config;
 mod get_pane_direction;
 mod get_text;
 mod kill_pane;
@@ -15,11 +18,16 @@ mod list_clients;
 mod move_pane_to_new_tab;
 mod proxy;
 mod rename_workspace;
+mod screenshot;
 mod send_text;
+mod set_config;
+mod set_config_profile;
+mod set_log_level;
 mod set_tab_title;
 mod set_window_title;
 mod spawn_command;
 mod split_pane;
+mod tag_pane;
 mod tls_creds;
 mod zoom_pane;
 
@@ -152,6 +160,11 @@ Outputs the pane-id for the newly created pane on success"
     #[command(name = "set-tab-title", rename_all = "kebab")]
     SetTabTitle(set_tab_title::SetTabTitle),
 
+    /// Add tags to a pane, or remove them.
+    /// Tags can be used to select panes in `list` and `send-text`.
+    #[command(name = "tag-pane", rename_all = "kebab")]
+    TagPane(tag_pane::TagPane),
+
     /// Change the title of a window
     #[command(name = "set-window-title", rename_all = "kebab")]
     SetWindowTitle(set_window_title::SetWindowTitle),
@@ -163,9 +176,49 @@ Outputs the pane-id for the newly created pane on success"
     /// Zoom, unzoom, or toggle zoom state
     #[command(name = "zoom-pane", rename_all = "kebab")]
     ZoomPane(zoom_pane::ZoomPane),
+
+    /// Change the log levels of the mux server or gui
+    #[command(name = "set-log-level", rename_all = "kebab")]
+    SetLogLevel(set_log_level::CliSetLogLevel),
+
+    /// Save a snapshot of the terminal state of a pane, for
+    /// reproducing rendering problems
+    #[command(name = "dump-pane-state", rename_all = "kebab")]
+    DumpPaneState(dump_pane_state::DumpPaneState),
+
+    /// Save an image of a pane, as rendered by the gui, to a PNG file
+    #[command(name = "screenshot", rename_all = "kebab")]
+    Screenshot(screenshot::Screenshot),
+
+    /// Print a JSON description of all of the config options,
+    /// including their types, defaults and the version in which
+    /// they were introduced
+    #[command(name = "dump-config-schema", rename_all = "kebab")]
+    DumpConfigSchema(dump_config_schema::DumpConfigSchema),
+
+    /// Print the effective value of a config option in a window, as JSON
+    #[command(name = "get-config", rename_all = "kebab")]
+    GetConfig(get_config::GetConfig),
+
+    /// Override a config option in a window, without changing the
+    /// config file.  Only options that affect the appearance of the
+    /// window, such as font_size and window_background_opacity, can
+    /// be changed
+    #[command(name = "set-config", rename_all = "kebab")]
+    SetConfig(set_config::SetConfig),
+
+    /// Activate one of the profiles defined in `config.profiles`
+    /// in a window, replacing any other profile
+    #[command(name = "set-config-profile", rename_all = "kebab")]
+    SetConfigProfile(set_config_profile::SetConfigProfile),
 }
 
 async fn run_cli_async(opts: &crate::Opt, cli: CliCommand) -> anyhow::Result<()> {
+    // This doesn't need to talk to the mux
+    if let CliSubCommand::DumpConfigSchema(cmd) = &cli.sub {
+        return cmd.run();
+    }
+
     let mut ui = mux::connui::ConnectionUI::new_headless();
     let initial = true;
 
@@ -196,9 +249,17 @@ async fn run_cli_async(opts: &crate::Opt, cli: CliCommand) -> anyhow::Result<()>
         CliSubCommand::AdjustPaneSize(cmd) => cmd.run(client).await,
         CliSubCommand::ActivateTab(cmd) => cmd.run(client).await,
         CliSubCommand::SetTabTitle(cmd) => cmd.run(client).await,
+        CliSubCommand::TagPane(cmd) => cmd.run(client).await,
         CliSubCommand::SetWindowTitle(cmd) => cmd.run(client).await,
         CliSubCommand::RenameWorkspace(cmd) => cmd.run(client).await,
         CliSubCommand::ZoomPane(cmd) => cmd.run(client).await,
+        CliSubCommand::SetLogLevel(cmd) => cmd.run(client).await,
+        CliSubCommand::DumpPaneState(cmd) => cmd.run(client).await,
+        CliSubCommand::Screenshot(cmd) => cmd.run(client).await,
+        CliSubCommand::DumpConfigSchema(cmd) => cmd.run(),
+        CliSubCommand::GetConfig(cmd) => cmd.run(client).await,
+        CliSubCommand::SetConfig(cmd) => cmd.run(client).await,
+        CliSubCommand::SetConfigProfile(cmd) => cmd.run(client).await,
     }
 }
 
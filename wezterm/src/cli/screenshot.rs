@@ -0,0 +1,27 @@
+use clap::Parser;
+use codec::CapturePane;
+use mux::pane::PaneId;
+use std::path::PathBuf;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct Screenshot {
+    /// Specify the target pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// The file to write the PNG image to
+    #[arg(long, value_hint=clap::ValueHint::FilePath)]
+    out: PathBuf,
+}
+
+impl Screenshot {
+    pub async fn run(&self, client: Client) -> anyhow::Result<()> {
+        let pane_id = client.resolve_pane_id(self.pane_id).await?;
+        let response = client.capture_pane(CapturePane { pane_id }).await?;
+        std::fs::write(&self.out, response.png)?;
+        Ok(())
+    }
+}
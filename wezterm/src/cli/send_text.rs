@@ -1,7 +1,8 @@
 use anyhow::Context;
 use clap::Parser;
+use codec::SendTextToPanes;
 use mux::pane::PaneId;
-use std::io::Read;
+use std::io::{BufRead, Read, Write};
 use wezterm_client::client::Client;
 
 #[derive(Debug, Parser, Clone)]
@@ -9,9 +10,31 @@ pub struct SendText {
     /// Specify the target pane.
     /// The default is to use the current pane based on the
     /// environment variable WEZTERM_PANE.
-    #[arg(long)]
+    #[arg(long, conflicts_with = "all_panes")]
     pane_id: Option<PaneId>,
 
+    /// Send the text to all panes, or with --match-domain and
+    /// --match-tag, to some of them.  The panes are listed and the
+    /// text is only sent once you confirm.
+    #[arg(long)]
+    all_panes: bool,
+
+    /// With --all-panes, only send to the panes of the domains whose
+    /// names match this pattern, in which `*` matches any text and `?`
+    /// any character, ignoring case.  For example `ssh:prod*`.
+    #[arg(long, requires = "all_panes")]
+    match_domain: Option<String>,
+
+    /// With --all-panes, only send to the panes that have this tag.
+    /// See `wezterm cli tag-pane`.
+    #[arg(long, requires = "all_panes")]
+    match_tag: Option<String>,
+
+    /// With --all-panes, send the text without asking for confirmation.
+    /// This is required when the text is read from stdin.
+    #[arg(long, short = 'y', requires = "all_panes")]
+    yes: bool,
+
     /// Send the text directly, rather than as a bracketed paste.
     #[arg(long)]
     no_paste: bool,
@@ -20,20 +43,26 @@ pub struct SendText {
     text: Option<String>,
 }
 
+fn read_text(text: Option<String>) -> anyhow::Result<String> {
+    match text {
+        Some(text) => Ok(text),
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .context("reading stdin")?;
+            Ok(text)
+        }
+    }
+}
+
 impl SendText {
     pub async fn run(self, client: Client) -> anyhow::Result<()> {
+        if self.all_panes {
+            return self.run_fanout(client).await;
+        }
         let pane_id = client.resolve_pane_id(self.pane_id).await?;
-
-        let data = match self.text {
-            Some(text) => text,
-            None => {
-                let mut text = String::new();
-                std::io::stdin()
-                    .read_to_string(&mut text)
-                    .context("reading stdin")?;
-                text
-            }
-        };
+        let data = read_text(self.text)?;
 
         if self.no_paste {
             client
@@ -44,9 +73,72 @@ impl SendText {
                 .await?;
         } else {
             client
-                .send_paste(codec::SendPaste { pane_id, data })
+                .send_paste(codec::SendPaste {
+                    pane_id,
+                    data,
+                    toggle_slow_paste: false,
+                })
                 .await?;
         }
         Ok(())
     }
+
+    /// Sends the text to the panes that match, which the mux resolves
+    /// so that the panes of its remote domains are included
+    async fn run_fanout(self, client: Client) -> anyhow::Result<()> {
+        if self.text.is_none() && !self.yes {
+            anyhow::bail!(
+                "the text is read from stdin, so it can't be confirmed; \
+                 pass --yes to send it to all of the matching panes"
+            );
+        }
+        let data = read_text(self.text)?;
+
+        let mut pane_ids = None;
+        if !self.yes {
+            let matched = client
+                .send_text_to_panes(SendTextToPanes {
+                    match_domain: self.match_domain.clone(),
+                    match_tag: self.match_tag.clone(),
+                    pane_ids: None,
+                    data: String::new(),
+                    paste: false,
+                    dry_run: true,
+                })
+                .await?;
+            if matched.panes.is_empty() {
+                anyhow::bail!("no panes match");
+            }
+            for pane in &matched.panes {
+                eprintln!(
+                    "{:>6}  {:<20}  {}",
+                    pane.pane_id, pane.domain_name, pane.title
+                );
+            }
+            eprint!(
+                "Send the text to these {} panes? [y/N] ",
+                matched.panes.len()
+            );
+            std::io::stderr().flush()?;
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                anyhow::bail!("cancelled");
+            }
+            pane_ids = Some(matched.panes.into_iter().map(|pane| pane.pane_id).collect());
+        }
+
+        let sent = client
+            .send_text_to_panes(SendTextToPanes {
+                match_domain: self.match_domain,
+                match_tag: self.match_tag,
+                pane_ids,
+                data,
+                paste: !self.no_paste,
+                dry_run: false,
+            })
+            .await?;
+        eprintln!("Sent the text to {} panes", sent.panes.len());
+        Ok(())
+    }
 }
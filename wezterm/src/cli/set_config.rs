@@ -0,0 +1,47 @@
+use clap::Parser;
+use mux::pane::PaneId;
+use mux::window::WindowId;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct SetConfig {
+    /// Specify the target window by its id
+    #[arg(long, conflicts_with_all=&["pane_id"])]
+    window_id: Option<WindowId>,
+    /// Specify the current pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    ///
+    /// The pane is used to figure out which window
+    /// should be changed.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// Remove the override, restoring the configured value
+    #[arg(long, conflicts_with = "value")]
+    reset: bool,
+
+    /// The name of the config option, such as `font_size`
+    key: String,
+
+    /// The new value. It is parsed as JSON, so that numbers,
+    /// booleans and tables can be specified, and is otherwise
+    /// treated as a string.
+    #[arg(required_unless_present = "reset")]
+    value: Option<String>,
+}
+
+impl SetConfig {
+    pub async fn run(self, client: Client) -> anyhow::Result<()> {
+        let window_id =
+            super::get_config::resolve_window_id(&client, self.window_id, self.pane_id).await?;
+        client
+            .set_window_config(codec::SetWindowConfig {
+                window_id,
+                key: self.key,
+                value: if self.reset { None } else { self.value },
+            })
+            .await?;
+        Ok(())
+    }
+}
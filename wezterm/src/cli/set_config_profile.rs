@@ -0,0 +1,41 @@
+use clap::Parser;
+use mux::pane::PaneId;
+use mux::window::WindowId;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct SetConfigProfile {
+    /// Specify the target window by its id
+    #[arg(long, conflicts_with_all=&["pane_id"])]
+    window_id: Option<WindowId>,
+    /// Specify the current pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    ///
+    /// The pane is used to figure out which window
+    /// should be changed.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// Deactivate the active profile, restoring the configured values
+    #[arg(long, conflicts_with = "name")]
+    reset: bool,
+
+    /// The name of an entry in `config.profiles`
+    #[arg(required_unless_present = "reset")]
+    name: Option<String>,
+}
+
+impl SetConfigProfile {
+    pub async fn run(self, client: Client) -> anyhow::Result<()> {
+        let window_id =
+            super::get_config::resolve_window_id(&client, self.window_id, self.pane_id).await?;
+        client
+            .set_window_config_profile(codec::SetWindowConfigProfile {
+                window_id,
+                profile: if self.reset { None } else { self.name },
+            })
+            .await?;
+        Ok(())
+    }
+}
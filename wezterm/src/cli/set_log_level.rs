@@ -0,0 +1,24 @@
+use clap::Parser;
+use codec::SetLogLevel;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct CliSetLogLevel {
+    /// The log level directives to apply, using the same syntax
+    /// as the WEZTERM_LOG environment variable; for example
+    /// `mux=debug` or `info,wezterm_gui=trace`.
+    /// The directives are merged with those already in effect.
+    spec: String,
+}
+
+impl CliSetLogLevel {
+    pub async fn run(&self, client: Client) -> anyhow::Result<()> {
+        let response = client
+            .set_log_level(SetLogLevel {
+                spec: self.spec.clone(),
+            })
+            .await?;
+        println!("{}", response.spec);
+        Ok(())
+    }
+}
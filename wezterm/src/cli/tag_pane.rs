@@ -0,0 +1,75 @@
+use clap::Parser;
+use mux::pane::PaneId;
+use std::collections::BTreeSet;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct TagPane {
+    /// Specify the target pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// Remove the tags from the pane, rather than adding them
+    #[arg(long, conflicts_with = "clear")]
+    remove: bool,
+
+    /// Remove all of the tags of the pane before adding the tags
+    #[arg(long)]
+    clear: bool,
+
+    /// The tags to add or remove.  Tags are case sensitive, and may not
+    /// contain commas or spaces.
+    #[arg(required_unless_present = "clear")]
+    tags: Vec<String>,
+}
+
+impl TagPane {
+    pub async fn run(self, client: Client) -> anyhow::Result<()> {
+        for tag in &self.tags {
+            mux::validate_pane_tag(tag)?;
+        }
+        let pane_id = client.resolve_pane_id(self.pane_id).await?;
+
+        let panes = client.list_panes().await?;
+        let mut existing = None;
+        for tabroot in panes.tabs {
+            let mut cursor = tabroot.into_tree().cursor();
+
+            loop {
+                if let Some(entry) = cursor.leaf_mut() {
+                    if entry.pane_id == pane_id {
+                        existing.replace(entry.tags.clone());
+                    }
+                }
+                match cursor.preorder_next() {
+                    Ok(c) => cursor = c,
+                    Err(_) => break,
+                }
+            }
+        }
+        let existing = existing.ok_or_else(|| anyhow::anyhow!("pane {pane_id} not found"))?;
+
+        let mut tags: BTreeSet<String> = if self.clear {
+            BTreeSet::new()
+        } else {
+            existing.into_iter().collect()
+        };
+        for tag in self.tags {
+            if self.remove {
+                tags.remove(&tag);
+            } else {
+                tags.insert(tag);
+            }
+        }
+
+        client
+            .set_pane_tags(codec::PaneTagsChanged {
+                pane_id,
+                tags: tags.into_iter().collect(),
+            })
+            .await?;
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@ use wezterm_gui_subcommands::*;
 
 mod asciicast;
 mod cli;
+mod ssh_keys;
 
 //    let message = "; ❤ 😍🤢\n\x1b[91;mw00t\n\x1b[37;104;m bleet\x1b[0;m.";
 
@@ -104,6 +105,12 @@ enum SubCommand {
     #[command(name = "ssh", about = "Establish an ssh session")]
     Ssh(SshCommand),
 
+    #[command(
+        name = "ssh-keys",
+        about = "Inspect ssh identities and known hosts, and test authentication"
+    )]
+    SshKeys(ssh_keys::SshKeysCommand),
+
     #[command(name = "serial", about = "Open a serial port")]
     Serial(SerialCommand),
 
@@ -116,6 +123,12 @@ enum SubCommand {
     #[command(name = "show-keys", about = "Show key assignments")]
     ShowKeys(ShowKeysCommand),
 
+    #[command(
+        name = "doctor",
+        about = "Check the configuration and environment for problems"
+    )]
+    Doctor(DoctorCommand),
+
     #[command(name = "cli", about = "Interact with experimental mux server")]
     Cli(cli::CliCommand),
 
@@ -737,11 +750,13 @@ fn run() -> anyhow::Result<()> {
         | SubCommand::BlockingStart(_)
         | SubCommand::LsFonts(_)
         | SubCommand::ShowKeys(_)
+        | SubCommand::Doctor(_)
         | SubCommand::Ssh(_)
         | SubCommand::Serial(_)
         | SubCommand::Connect(_) => delegate_to_gui(saver),
         SubCommand::ImageCat(cmd) => cmd.run(),
         SubCommand::SetCwd(cmd) => cmd.run(),
+        SubCommand::SshKeys(cmd) => cmd.run(),
         SubCommand::Cli(cli) => cli::run_cli(&opts, cli),
         SubCommand::Record(cmd) => cmd.run(init_config(&opts)?),
         SubCommand::Replay(cmd) => cmd.run(),
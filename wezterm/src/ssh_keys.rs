@@ -0,0 +1,549 @@
+use anyhow::{anyhow, Context};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+use termwiz::caps::Capabilities;
+use termwiz::cell::{unicode_column_width, AttributeChange, Intensity};
+use termwiz::color::ColorAttribute;
+use termwiz::input::{InputEvent, KeyCode, KeyEvent};
+use termwiz::lineedit::*;
+use termwiz::surface::{Change, CursorVisibility, Position};
+use termwiz::terminal::Terminal;
+use wezterm_ssh::{
+    agent_identities, configured_identities, known_hosts_files, Config, IdentityFile,
+    KnownHostEntry, KnownHostsFile, PublicKey, Session, SessionEvent,
+};
+
+#[derive(Debug, Parser, Clone)]
+pub struct SshKeysCommand {
+    /// Resolve the identity and known_hosts files using the ssh
+    /// configuration for this host.  If omitted, the settings that
+    /// apply to all hosts are used.
+    #[arg(long, value_hint=ValueHint::Hostname)]
+    host: Option<String>,
+
+    /// Print the report to stdout instead of starting the
+    /// interactive interface
+    #[arg(long)]
+    list: bool,
+}
+
+enum Row {
+    Heading(String),
+    Message(String),
+    Identity(IdentityFile),
+    AgentKey(PublicKey),
+    KnownHost {
+        path: PathBuf,
+        entry: KnownHostEntry,
+    },
+}
+
+impl Row {
+    fn render(&self) -> String {
+        fn key_summary(key: &PublicKey) -> String {
+            match &key.comment {
+                Some(comment) => format!("{} {} {}", key.key_type, key.fingerprint(), comment),
+                None => format!("{} {}", key.key_type, key.fingerprint()),
+            }
+        }
+
+        match self {
+            Self::Heading(text) => text.to_string(),
+            Self::Message(text) => format!("  {}", text),
+            Self::Identity(ident) => match (&ident.public_key, ident.exists) {
+                (_, false) => format!("  {} (not present)", ident.path.display()),
+                (Some(key), true) => format!("  {} {}", ident.path.display(), key_summary(key)),
+                (None, true) => format!("  {} (no .pub file)", ident.path.display()),
+            },
+            Self::AgentKey(key) => format!("  {}", key_summary(key)),
+            Self::KnownHost { path, entry } => {
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string());
+                let marker = match entry.marker {
+                    Some(wezterm_ssh::KnownHostMarker::CertAuthority) => "@cert-authority ",
+                    Some(wezterm_ssh::KnownHostMarker::Revoked) => "@revoked ",
+                    None => "",
+                };
+                format!(
+                    "  {}:{} {}{} {} {}",
+                    file_name,
+                    entry.line_number,
+                    marker,
+                    entry.names,
+                    entry.key.key_type,
+                    entry.key.fingerprint()
+                )
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct PromptHost {
+    history: BasicHistory,
+    echo: bool,
+}
+
+impl LineEditorHost for PromptHost {
+    fn history(&mut self) -> &mut dyn History {
+        &mut self.history
+    }
+
+    fn highlight_line(&self, line: &str, cursor_position: usize) -> (Vec<OutputElement>, usize) {
+        if self.echo {
+            (vec![OutputElement::Text(line.to_string())], cursor_position)
+        } else {
+            // Obscure passwords and passphrases
+            let placeholder = "🔑";
+            let grapheme_count = unicode_column_width(line, None);
+            let mut output = vec![];
+            for _ in 0..grapheme_count {
+                output.push(OutputElement::Text(placeholder.to_string()));
+            }
+            (
+                output,
+                unicode_column_width(placeholder, None) * cursor_position,
+            )
+        }
+    }
+}
+
+fn read_line(term: &mut dyn Terminal, prompt: &str, echo: bool) -> anyhow::Result<Option<String>> {
+    let mut editor = LineEditor::new(term);
+    let mut host = PromptHost::default();
+    host.echo = echo;
+    let mut prompt_lines = prompt.split('\n').collect::<Vec<_>>();
+    editor.set_prompt(prompt_lines.pop().unwrap_or(""));
+    for line in &prompt_lines {
+        println!("{}\r", line);
+    }
+    Ok(editor.read_line(&mut host)?)
+}
+
+struct SshKeysUi {
+    config: Config,
+    host: String,
+    rows: Vec<Row>,
+    selected: usize,
+    top: usize,
+    status: String,
+}
+
+impl SshKeysUi {
+    fn new(host: Option<String>) -> Self {
+        let mut config = Config::new();
+        config.add_default_config_files();
+        let mut ui = Self {
+            config,
+            host: host.unwrap_or_else(|| "*".to_string()),
+            rows: vec![],
+            selected: 0,
+            top: 0,
+            status: String::new(),
+        };
+        ui.reload();
+        ui
+    }
+
+    fn reload(&mut self) {
+        let resolved = self.config.for_host(&self.host);
+        let mut rows = vec![];
+
+        rows.push(Row::Heading("Identity files".to_string()));
+        let identities = configured_identities(&resolved);
+        if identities.is_empty() {
+            rows.push(Row::Message("(none configured)".to_string()));
+        }
+        for ident in identities {
+            rows.push(Row::Identity(ident));
+        }
+
+        rows.push(Row::Heading("Agent keys".to_string()));
+        match agent_identities() {
+            Ok(keys) if keys.is_empty() => {
+                rows.push(Row::Message("(agent holds no keys)".to_string()))
+            }
+            Ok(keys) => {
+                for key in keys {
+                    rows.push(Row::AgentKey(key));
+                }
+            }
+            Err(err) => rows.push(Row::Message(format!("{:#}", err))),
+        }
+
+        rows.push(Row::Heading("Known hosts".to_string()));
+        for path in known_hosts_files(&resolved) {
+            match KnownHostsFile::load(&path) {
+                Ok(file) => {
+                    for entry in file.entries() {
+                        rows.push(Row::KnownHost {
+                            path: path.clone(),
+                            entry: entry.clone(),
+                        });
+                    }
+                }
+                Err(err) => rows.push(Row::Message(format!("{:#}", err))),
+            }
+        }
+
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    fn print(&self) {
+        for row in &self.rows {
+            println!("{}", row.render());
+        }
+    }
+
+    fn render(&mut self, term: &mut dyn Terminal) -> anyhow::Result<()> {
+        let size = term.get_screen_size()?;
+        // One row for the title and one for the status line
+        let list_height = size.rows.saturating_sub(2).max(1);
+
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + list_height {
+            self.top = self.selected + 1 - list_height;
+        }
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorVisibility(CursorVisibility::Hidden),
+            AttributeChange::Reverse(true).into(),
+            Change::Text(truncate(
+                &format!(
+                    " wezterm ssh-keys for {}: \
+                     d: remove host key  t: test auth  r: reload  q: quit",
+                    self.host
+                ),
+                size.cols,
+            )),
+            AttributeChange::Reverse(false).into(),
+        ];
+
+        for (idx, row) in self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.top)
+            .take(list_height)
+        {
+            changes.push(Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(1 + idx - self.top),
+            });
+            if let Row::Heading(_) = row {
+                changes.push(AttributeChange::Intensity(Intensity::Bold).into());
+            }
+            if idx == self.selected {
+                changes.push(AttributeChange::Reverse(true).into());
+            }
+            changes.push(Change::Text(truncate(&row.render(), size.cols)));
+            changes.push(Change::AllAttributes(Default::default()));
+        }
+
+        changes.push(Change::CursorPosition {
+            x: Position::Absolute(0),
+            y: Position::Absolute(size.rows.saturating_sub(1)),
+        });
+        changes.push(Change::Text(truncate(&self.status, size.cols)));
+
+        term.render(&changes)?;
+        term.flush()?;
+        Ok(())
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let max = self.rows.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + delta).max(0).min(max) as usize;
+    }
+
+    fn remove_selected(&mut self, term: &mut dyn Terminal) -> anyhow::Result<()> {
+        let (path, entry) = match self.rows.get(self.selected) {
+            Some(Row::KnownHost { path, entry }) => (path.clone(), entry.clone()),
+            _ => {
+                self.status = "Select a known_hosts entry to remove it".to_string();
+                return Ok(());
+            }
+        };
+
+        self.status = format!(
+            "Remove {} key for {} from {}:{}? [y/n]",
+            entry.key.key_type,
+            entry.names,
+            path.display(),
+            entry.line_number
+        );
+        self.render(term)?;
+
+        let confirmed = loop {
+            match term.poll_input(None)? {
+                Some(InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('y' | 'Y'),
+                    ..
+                })) => break true,
+                Some(InputEvent::Key(_)) => break false,
+                _ => {}
+            }
+        };
+
+        if !confirmed {
+            self.status = String::new();
+            return Ok(());
+        }
+
+        let mut file = KnownHostsFile::load(&path)?;
+        // The file may have been changed by something else since we
+        // loaded it, so check that the line still holds the same key
+        // before removing it.
+        let still_present = file
+            .entries()
+            .any(|e| e.line_number == entry.line_number && e.key == entry.key);
+        if !still_present {
+            self.reload();
+            anyhow::bail!("{} changed on disk; reloaded it", path.display());
+        }
+        file.remove_line(entry.line_number);
+        file.save()?;
+        self.status = format!("Removed line {} from {}", entry.line_number, path.display());
+        self.reload();
+        Ok(())
+    }
+
+    fn test_selected(&mut self, term: &mut dyn Terminal) -> anyhow::Result<()> {
+        let connectable = match self.rows.get(self.selected) {
+            Some(Row::KnownHost { entry, .. }) => entry.names.connectable_host(),
+            _ => None,
+        };
+
+        term.exit_alternate_screen()?;
+        term.set_cooked_mode()?;
+        term.render(&[Change::CursorVisibility(CursorVisibility::Visible)])?;
+        term.flush()?;
+
+        let destination = match connectable {
+            Some((host, port)) => Some(if port == 22 {
+                host
+            } else if host.contains(':') {
+                format!("[{host}]:{port}")
+            } else {
+                format!("{host}:{port}")
+            }),
+            None => read_line(term, "Test authentication against host: ", true)?
+                .filter(|line| !line.trim().is_empty()),
+        };
+
+        let result = match destination {
+            Some(destination) => {
+                println!("Testing authentication against {destination}\r");
+                let result = test_auth(&self.config, &destination, term);
+                match &result {
+                    Ok(()) => println!("Authenticated successfully!\r"),
+                    Err(err) => println!("Authentication failed: {err:#}\r"),
+                }
+                read_line(term, "Press enter to continue", true)?;
+                result
+            }
+            None => Ok(()),
+        };
+
+        term.set_raw_mode()?;
+        term.enter_alternate_screen()?;
+
+        match result {
+            Ok(()) => self.status = "Authentication succeeded".to_string(),
+            Err(err) => self.status = format!("Authentication failed: {err:#}"),
+        }
+        // A successful connection may have added a new known_hosts entry
+        self.reload();
+        Ok(())
+    }
+
+    fn run(&mut self, term: &mut dyn Terminal) -> anyhow::Result<()> {
+        term.set_raw_mode()?;
+        term.enter_alternate_screen()?;
+
+        loop {
+            self.render(term)?;
+            let page = term.get_screen_size()?.rows.saturating_sub(2).max(1) as isize;
+
+            let key = match term.poll_input(None)? {
+                Some(InputEvent::Key(KeyEvent { key, .. })) => key,
+                _ => continue,
+            };
+
+            let res = match key {
+                KeyCode::Char('q') | KeyCode::Escape => break,
+                KeyCode::UpArrow | KeyCode::Char('k') => {
+                    self.move_by(-1);
+                    Ok(())
+                }
+                KeyCode::DownArrow | KeyCode::Char('j') => {
+                    self.move_by(1);
+                    Ok(())
+                }
+                KeyCode::PageUp => {
+                    self.move_by(-page);
+                    Ok(())
+                }
+                KeyCode::PageDown => {
+                    self.move_by(page);
+                    Ok(())
+                }
+                KeyCode::Home => {
+                    self.selected = 0;
+                    Ok(())
+                }
+                KeyCode::End => {
+                    self.selected = self.rows.len().saturating_sub(1);
+                    Ok(())
+                }
+                KeyCode::Char('r') => {
+                    self.reload();
+                    self.status = "Reloaded".to_string();
+                    Ok(())
+                }
+                KeyCode::Char('d') | KeyCode::Delete => self.remove_selected(term),
+                KeyCode::Char('t') => self.test_selected(term),
+                _ => Ok(()),
+            };
+
+            if let Err(err) = res {
+                self.status = format!("{:#}", err);
+            }
+        }
+
+        term.exit_alternate_screen()?;
+        term.set_cooked_mode()?;
+        term.render(&[Change::CursorVisibility(CursorVisibility::Visible)])?;
+        term.flush()?;
+        Ok(())
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let mut buf = [0u8; 4];
+        let w = unicode_column_width(c.encode_utf8(&mut buf), None);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        result.push(c);
+    }
+    result
+}
+
+/// Splits `destination`, of the form `[user@]host[:port]`, into its
+/// parts.  An IPv6 address is taken to have no port unless it is
+/// written as `[addr]:port`.
+fn parse_destination(destination: &str) -> anyhow::Result<(Option<&str>, &str, Option<u16>)> {
+    let (user, host) = match destination.split_once('@') {
+        Some((user, host)) => (Some(user), host),
+        None => (None, destination),
+    };
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .with_context(|| format!("invalid port in {destination}"))
+    };
+
+    let (host, port) = match host.strip_prefix('[') {
+        Some(bracketed) => match bracketed.split_once(']') {
+            Some((host, "")) => (host, None),
+            Some((host, rest)) => match rest.strip_prefix(':') {
+                Some(port) => (host, Some(parse_port(port)?)),
+                None => anyhow::bail!("expected :port after ] in {destination}"),
+            },
+            None => anyhow::bail!("missing ] in {destination}"),
+        },
+        None => match host.split_once(':') {
+            // A bare IPv6 address
+            Some((_, rest)) if rest.contains(':') => (host, None),
+            Some((host, port)) => (host, Some(parse_port(port)?)),
+            None => (host, None),
+        },
+    };
+    Ok((user, host, port))
+}
+
+/// Connect to `destination` and report whether we were able to authenticate.
+/// Any prompts required by the authentication process are presented
+/// to the user via `term`.
+fn test_auth(config: &Config, destination: &str, term: &mut dyn Terminal) -> anyhow::Result<()> {
+    let (user, host, port) = parse_destination(destination)?;
+
+    let mut ssh_config = config.for_host(host);
+    if let Some(user) = user {
+        ssh_config.insert("user".to_string(), user.to_string());
+    }
+    if let Some(port) = port {
+        ssh_config.insert("port".to_string(), port.to_string());
+    }
+
+    smol::block_on(async move {
+        let (_session, events) = Session::connect(ssh_config)?;
+
+        while let Ok(event) = events.recv().await {
+            match event {
+                SessionEvent::Banner(banner) => {
+                    if let Some(banner) = banner {
+                        println!("{}\r", banner.trim_end());
+                    }
+                }
+                SessionEvent::HostVerify(verify) => {
+                    let ok =
+                        match read_line(term, &format!("{}\nEnter [y/n]> ", verify.message), true)?
+                        {
+                            Some(line) => matches!(line.as_ref(), "y" | "Y" | "yes" | "YES"),
+                            None => false,
+                        };
+                    verify.answer(ok).await.context("send verify response")?;
+                }
+                SessionEvent::Authenticate(auth) => {
+                    if !auth.username.is_empty() {
+                        println!("Authentication for {}\r", auth.username);
+                    }
+                    if !auth.instructions.is_empty() {
+                        println!("{}\r", auth.instructions);
+                    }
+                    let mut answers = vec![];
+                    for prompt in &auth.prompts {
+                        match read_line(term, &prompt.prompt, prompt.echo)? {
+                            Some(line) => answers.push(line),
+                            None => anyhow::bail!("Authentication was cancelled"),
+                        }
+                    }
+                    auth.answer(answers).await?;
+                }
+                SessionEvent::HostVerificationFailed(failed) => {
+                    anyhow::bail!("{}", failed);
+                }
+                SessionEvent::Error(err) => {
+                    anyhow::bail!("{}", err);
+                }
+                SessionEvent::Authenticated => return Ok(()),
+            }
+        }
+
+        Err(anyhow!("ssh session ended before authenticating"))
+    })
+}
+
+impl SshKeysCommand {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let mut ui = SshKeysUi::new(self.host.clone());
+        if self.list {
+            ui.print();
+            return Ok(());
+        }
+
+        let caps = Capabilities::new_from_env()?;
+        let mut term = termwiz::terminal::new_terminal(caps)?;
+        ui.run(&mut term)
+    }
+}
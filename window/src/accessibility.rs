@@ -0,0 +1,115 @@
+//! Exposes an accessibility tree to assistive technologies, such as
+//! screen readers, via AccessKit.
+//!
+//! The tree itself is produced by the embedding application; this module
+//! is only concerned with publishing it to the platform accessibility API:
+//! AT-SPI on Linux and the BSDs, NSAccessibility on macOS and UI Automation
+//! on Windows.
+pub use accesskit;
+use accesskit::{ActionHandler, ActionRequest, TreeUpdate};
+use raw_window_handle::HasRawWindowHandle;
+
+/// We don't currently support any actions on the nodes in the tree,
+/// but log them to help with diagnosing assistive technology issues
+struct LoggingActionHandler;
+
+impl ActionHandler for LoggingActionHandler {
+    fn do_action(&mut self, request: ActionRequest) {
+        log::trace!("accessibility action request: {request:?}");
+    }
+}
+
+pub struct AccessibilityAdapter {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    adapter: accesskit_unix::Adapter,
+    #[cfg(target_os = "macos")]
+    adapter: accesskit_macos::SubclassingAdapter,
+    #[cfg(windows)]
+    adapter: accesskit_windows::SubclassingAdapter,
+}
+
+impl AccessibilityAdapter {
+    /// Create an adapter for `window`.
+    /// `initial_state` will be called to produce the initial tree
+    /// when an assistive technology first connects.
+    /// Returns `None` if accessibility is not supported on this system.
+    pub fn new(
+        window: &impl HasRawWindowHandle,
+        initial_state: impl 'static + Send + FnOnce() -> TreeUpdate,
+    ) -> Option<Self> {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            // AT-SPI is connected to the application rather than
+            // to each of its windows. The application name and toolkit
+            // are taken from the initial tree.
+            let _ = window;
+            let adapter =
+                accesskit_unix::Adapter::new(initial_state, false, Box::new(LoggingActionHandler))?;
+            Some(Self { adapter })
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let view = match window.raw_window_handle() {
+                raw_window_handle::RawWindowHandle::AppKit(handle) => handle.ns_view,
+                _ => return None,
+            };
+            // SAFETY: the view belongs to a window that is still open
+            let adapter = unsafe {
+                accesskit_macos::SubclassingAdapter::new(
+                    view,
+                    initial_state,
+                    Box::new(LoggingActionHandler),
+                )
+            };
+            Some(Self { adapter })
+        }
+        #[cfg(windows)]
+        {
+            let hwnd = match window.raw_window_handle() {
+                raw_window_handle::RawWindowHandle::Win32(handle) => handle.hwnd,
+                _ => return None,
+            };
+            let adapter = accesskit_windows::SubclassingAdapter::new(
+                accesskit_windows::HWND(hwnd as isize),
+                initial_state,
+                Box::new(LoggingActionHandler),
+            );
+            Some(Self { adapter })
+        }
+    }
+
+    /// Publish an update to the tree.
+    /// `update` is only called if an assistive technology is
+    /// currently connected, so it is cheap to call this frequently.
+    /// On unix systems the adapter only exists while the AT-SPI bus
+    /// is available, so the update is always applied there.
+    /// Returns true if the update was published.
+    pub fn update_if_active(&self, update: impl FnOnce() -> TreeUpdate) -> bool {
+        let mut published = false;
+        let factory = || {
+            published = true;
+            update()
+        };
+        #[cfg(all(unix, not(target_os = "macos")))]
+        self.adapter.update(factory());
+        #[cfg(any(target_os = "macos", windows))]
+        if let Some(events) = self.adapter.update_if_active(factory) {
+            events.raise();
+        }
+        published
+    }
+
+    /// Inform the assistive technology that the window gained
+    /// or lost the keyboard focus
+    pub fn update_window_focus_state(&self, focused: bool) {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        self.adapter.update_window_focus_state(focused);
+        #[cfg(target_os = "macos")]
+        if let Some(events) = self.adapter.update_view_focus_state(focused) {
+            events.raise();
+        }
+        // The subclassed window procedure tracks the focus itself
+        #[cfg(windows)]
+        let _ = focused;
+    }
+}
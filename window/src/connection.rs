@@ -1,11 +1,15 @@
 use crate::screen::Screens;
-use crate::{Appearance, Connection, GeometryOrigin, RequestedWindowGeometry, ResolvedGeometry};
+use crate::{
+    Appearance, Connection, GeometryOrigin, PowerState, RequestedWindowGeometry, ResolvedGeometry,
+};
 use anyhow::Result as Fallible;
 use config::keyassignment::KeyAssignment;
 use config::DimensionContext;
 use std::cell::RefCell;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 thread_local! {
     static CONN: RefCell<Option<Rc<Connection>>> = RefCell::new(None);
@@ -68,6 +72,12 @@ pub trait ConnectionOps {
         Appearance::Light
     }
 
+    /// Resolves to true if the user has asked the system to
+    /// minimize non-essential motion, such as animations.
+    fn prefers_reduced_motion(&self) -> promise::Future<bool> {
+        promise::Future::ok(false)
+    }
+
     /// Hide the application.
     /// This actions hides all of the windows of the application and switches
     /// focus away from it.
@@ -76,6 +86,45 @@ pub trait ConnectionOps {
     /// Perform the system beep/notification sound
     fn beep(&self) {}
 
+    /// Returns true if authenticate_user is implemented for this system
+    fn can_authenticate_user(&self) -> bool {
+        false
+    }
+
+    /// Asks the operating system to verify the identity of the user,
+    /// via Touch ID, Windows Hello or a password, showing `reason` in
+    /// the prompt.  Resolves to true if the user was verified.
+    fn authenticate_user(&self, _reason: &str) -> promise::Future<bool> {
+        promise::Future::err(anyhow::anyhow!(
+            "Authenticating the user is not supported on this system"
+        ))
+    }
+
+    /// Resolves to the state of the power supply of the system
+    fn power_state(&self) -> promise::Future<PowerState> {
+        promise::Future::ok(PowerState::default())
+    }
+
+    /// Resolves to true if the screen is locked or the screensaver is active
+    fn is_screen_locked(&self) -> promise::Future<bool> {
+        promise::Future::ok(false)
+    }
+
+    /// Resolves to how long it has been since the user last interacted
+    /// with the system, or None if that cannot be determined
+    fn idle_time(&self) -> promise::Future<Option<Duration>> {
+        promise::Future::ok(None)
+    }
+
+    /// Play the sound file at `path` with `volume` in the range 0.0 to 1.0.
+    /// The sound plays asynchronously.
+    fn play_sound(&self, path: &Path, _volume: f32) -> Fallible<()> {
+        anyhow::bail!(
+            "Unable to play {}: playing sounds is not supported on this system",
+            path.display()
+        );
+    }
+
     /// Returns information about the screens
     fn screens(&self) -> anyhow::Result<Screens> {
         anyhow::bail!("Unable to query screen information");
@@ -7,6 +7,7 @@ use std::any::Any;
 use std::path::PathBuf;
 use std::rc::Rc;
 use thiserror::Error;
+pub mod accessibility;
 pub mod bitmaps;
 pub use wezterm_color_types as color;
 mod configuration;
@@ -29,6 +30,22 @@ pub fn default_dpi() -> f64 {
     }
 }
 
+static WINDOW_ROLE: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Sets the role that is assigned to windows that are subsequently
+/// created, which allows window managers to distinguish between
+/// windows that share the same class.
+/// Only X11 has the concept of a window role; it is set as the
+/// WM_WINDOW_ROLE property of the window.
+pub fn set_window_role(role: &str) {
+    WINDOW_ROLE.lock().unwrap().replace(role.to_string());
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) fn window_role() -> Option<String> {
+    WINDOW_ROLE.lock().unwrap().clone()
+}
+
 mod egl;
 
 pub use bitmaps::{BitmapImage, Image};
@@ -88,6 +105,12 @@ pub enum Appearance {
     DarkHighContrast,
 }
 
+impl Appearance {
+    pub fn is_high_contrast(&self) -> bool {
+        matches!(self, Self::LightHighContrast | Self::DarkHighContrast)
+    }
+}
+
 impl std::string::ToString for Appearance {
     fn to_string(&self) -> String {
         match self {
@@ -100,6 +123,16 @@ impl std::string::ToString for Appearance {
     }
 }
 
+/// Represents the state of the power supply of the system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PowerState {
+    /// The system is running on battery power
+    pub on_battery: bool,
+    /// The user or the system has asked applications to
+    /// reduce their power consumption
+    pub low_power_mode: bool,
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct WindowState: u8 {
@@ -115,6 +148,8 @@ bitflags! {
         const ALWAYS_ON_TOP = 1<<4;
         /// Always on bottom (docked) window
         const ALWAYS_ON_BOTTOM = 1<<5;
+        /// Shown on all workspaces/virtual desktops
+        const STICKY = 1<<6;
     }
 }
 
@@ -301,6 +336,13 @@ pub trait WindowOps {
     /// windows to move themselves (not Wayland).
     fn set_window_position(&self, _coords: ScreenPoint) {}
 
+    /// Moves and/or resizes the window, relative to its current
+    /// position and size and to the screen that it is on.
+    ///
+    /// Moving the window is only implemented on backends that allow
+    /// windows to move themselves (not Wayland).
+    fn adjust_window_geometry(&self, _adjustment: screen::WindowGeometryAdjustment) {}
+
     /// inform the windowing system of the current textual
     /// cursor input location.  This is used primarily for
     /// the platform specific input method editor
@@ -315,6 +357,11 @@ pub trait WindowOps {
     /// Set window level. Depending on the environment and user preferences
     fn set_window_level(&self, _level: WindowLevel) {}
 
+    /// Show the window on all workspaces/virtual desktops, rather than
+    /// only the one that it was opened on.
+    /// This is only implemented on X11 and macOS.
+    fn set_sticky(&self, _sticky: bool) {}
+
     /// Set the icon for the window.
     /// Depending on the system this may be shown in its titlebar
     /// and/or in the task manager/task switcher
@@ -1,23 +1,55 @@
 // let () = msg_send! is a common pattern for objc
 #![allow(clippy::let_unit_value)]
 
-use super::nsstring_to_str;
 use super::window::WindowInner;
+use super::{nsstring, nsstring_to_str};
 use crate::connection::ConnectionOps;
 use crate::os::macos::app::create_app_delegate;
 use crate::screen::{ScreenInfo, Screens};
 use crate::spawn::*;
-use crate::Appearance;
+use crate::{Appearance, PowerState};
+use block::ConcreteBlock;
 use cocoa::appkit::{NSApp, NSApplication, NSApplicationActivationPolicyRegular, NSScreen};
 use cocoa::base::{id, nil};
 use cocoa::foundation::{NSArray, NSInteger};
+use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+use core_foundation::dictionary::{CFDictionaryContainsKey, CFDictionaryRef};
+use core_foundation::string::{CFString, CFStringRef};
 use objc::runtime::{Object, BOOL, YES};
 use objc::*;
 use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::atomic::AtomicUsize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[allow(non_upper_case_globals)]
+const kCGEventSourceStateCombinedSessionState: i32 = 0;
+#[allow(non_upper_case_globals)]
+const kCGAnyInputEventType: u32 = !0;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    fn CGEventSourceSecondsSinceLastEventType(source_state: i32, event_type: u32) -> f64;
+}
+
+/// Touch ID or Apple Watch, falling back to the account password
+#[allow(non_upper_case_globals)]
+const LAPolicyDeviceOwnerAuthentication: NSInteger = 2;
+
+// Ensure that the LAContext class is available
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+}
 
 pub struct Connection {
     ns_app: id,
@@ -152,6 +184,14 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn prefers_reduced_motion(&self) -> promise::Future<bool> {
+        let reduce_motion: BOOL = unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            msg_send![workspace, accessibilityDisplayShouldReduceMotion]
+        };
+        promise::Future::ok(reduce_motion == YES)
+    }
+
     fn run_message_loop(&self) -> anyhow::Result<()> {
         unsafe {
             self.ns_app.run();
@@ -172,6 +212,106 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn can_authenticate_user(&self) -> bool {
+        true
+    }
+
+    fn authenticate_user(&self, reason: &str) -> promise::Future<bool> {
+        let mut promise = promise::Promise::new();
+        let future = promise.get_future().unwrap();
+        let promise = Mutex::new(promise);
+        unsafe {
+            let context: id = msg_send![class!(LAContext), new];
+            let reason = nsstring(reason);
+            let reply = ConcreteBlock::new(move |success: BOOL, _error: id| {
+                promise.lock().unwrap().ok(success == YES);
+                let () = msg_send![context, release];
+            })
+            .copy();
+            let () = msg_send![context,
+                evaluatePolicy: LAPolicyDeviceOwnerAuthentication
+                localizedReason: *reason
+                reply: &*reply];
+        }
+        future
+    }
+
+    fn power_state(&self) -> promise::Future<PowerState> {
+        let on_battery = unsafe {
+            let snapshot = IOPSCopyPowerSourcesInfo();
+            if snapshot.is_null() {
+                false
+            } else {
+                let source = IOPSGetProvidingPowerSourceType(snapshot);
+                let on_battery = !source.is_null()
+                    && CFString::wrap_under_get_rule(source).to_string() == "Battery Power";
+                CFRelease(snapshot);
+                on_battery
+            }
+        };
+        // isLowPowerModeEnabled is only available on macOS 12 and later
+        let low_power_mode = unsafe {
+            let info: id = msg_send![class!(NSProcessInfo), processInfo];
+            let available: BOOL = msg_send![info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+            if available == YES {
+                let enabled: BOOL = msg_send![info, isLowPowerModeEnabled];
+                enabled == YES
+            } else {
+                false
+            }
+        };
+        promise::Future::ok(PowerState {
+            on_battery,
+            low_power_mode,
+        })
+    }
+
+    fn is_screen_locked(&self) -> promise::Future<bool> {
+        let locked = unsafe {
+            let session = CGSessionCopyCurrentDictionary();
+            if session.is_null() {
+                return promise::Future::ok(false);
+            }
+            let key = CFString::from_static_string("CGSSessionScreenIsLocked");
+            let locked = CFDictionaryContainsKey(session, key.as_CFTypeRef()) != 0;
+            CFRelease(session as CFTypeRef);
+            locked
+        };
+        promise::Future::ok(locked)
+    }
+
+    fn idle_time(&self) -> promise::Future<Option<Duration>> {
+        let seconds = unsafe {
+            CGEventSourceSecondsSinceLastEventType(
+                kCGEventSourceStateCombinedSessionState,
+                kCGAnyInputEventType,
+            )
+        };
+        promise::Future::ok(Duration::try_from_secs_f64(seconds).ok())
+    }
+
+    fn play_sound(&self, path: &Path, volume: f32) -> anyhow::Result<()> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("{} is not valid UTF-8", path.display()))?;
+        unsafe {
+            let file = nsstring(path_str);
+            let sound: id = msg_send![class!(NSSound), alloc];
+            let sound: id = msg_send![sound, initWithContentsOfFile:*file byReference:YES];
+            if sound.is_null() {
+                anyhow::bail!("Unable to load sound from {}", path.display());
+            }
+            let () = msg_send![sound, setVolume: volume.clamp(0., 1.)];
+            let playing: BOOL = msg_send![sound, play];
+            // The sound retains itself while it is playing
+            let () = msg_send![sound, autorelease];
+            if playing != YES {
+                anyhow::bail!("Unable to play {}", path.display());
+            }
+        }
+        Ok(())
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         let mut by_name = HashMap::new();
         let mut virtual_rect = euclid::rect(0, 0, 0, 0);
@@ -10,8 +10,8 @@ use crate::parameters::{Border, Parameters, TitleBar};
 use crate::{
     Clipboard, Connection, DeadKeyStatus, Dimensions, Handled, KeyCode, KeyEvent, Modifiers,
     MouseButtons, MouseCursor, MouseEvent, MouseEventKind, MousePress, Point, RawKeyEvent, Rect,
-    RequestedWindowGeometry, ResizeIncrement, ResolvedGeometry, ScreenPoint, Size, ULength,
-    WindowDecorations, WindowEvent, WindowEventSender, WindowOps, WindowState,
+    RequestedWindowGeometry, ResizeIncrement, ResolvedGeometry, ScreenPoint, ScreenRect, Size,
+    ULength, WindowDecorations, WindowEvent, WindowEventSender, WindowOps, WindowState,
 };
 use anyhow::{anyhow, bail, ensure};
 use async_trait::async_trait;
@@ -19,7 +19,8 @@ use cocoa::appkit::{
     self, CGFloat, NSApplication, NSApplicationActivateIgnoringOtherApps,
     NSApplicationPresentationOptions, NSBackingStoreBuffered, NSEvent, NSEventModifierFlags,
     NSOpenGLContext, NSOpenGLPixelFormat, NSPasteboard, NSRunningApplication, NSScreen, NSView,
-    NSViewHeightSizable, NSViewWidthSizable, NSWindow, NSWindowStyleMask,
+    NSViewHeightSizable, NSViewWidthSizable, NSWindow, NSWindowCollectionBehavior,
+    NSWindowStyleMask,
 };
 use cocoa::base::*;
 use cocoa::foundation::{
@@ -784,6 +785,13 @@ impl WindowOps for Window {
         });
     }
 
+    fn set_sticky(&self, sticky: bool) {
+        Connection::with_window_inner(self.id, move |inner| {
+            inner.set_sticky(sticky);
+            Ok(())
+        });
+    }
+
     fn set_inner_size(&self, width: usize, height: usize) {
         Connection::with_window_inner(self.id, move |inner| {
             inner.set_inner_size(width, height);
@@ -805,6 +813,13 @@ impl WindowOps for Window {
         });
     }
 
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        Connection::with_window_inner(self.id, move |inner| {
+            inner.adjust_window_geometry(adjustment);
+            Ok(())
+        });
+    }
+
     fn set_text_cursor_position(&self, cursor: Rect) {
         Connection::with_window_inner(self.id, move |inner| {
             inner.set_text_cursor_position(cursor);
@@ -1072,7 +1087,11 @@ impl WindowInner {
     }
 
     fn update_window_shadow(&mut self) {
-        let is_opaque = if self.config.window_background_opacity >= 1.0 {
+        // The window is made more transparent when it loses focus
+        // if inactive_window_opacity is set, so it cannot be opaque
+        let is_opaque = if self.config.window_background_opacity >= 1.0
+            && self.config.inactive_window_opacity >= 1.0
+        {
             YES
         } else {
             NO
@@ -1203,6 +1222,19 @@ impl WindowInner {
         }
     }
 
+    fn set_sticky(&mut self, sticky: bool) {
+        unsafe {
+            let mut behavior = NSWindow::collectionBehavior(*self.window);
+            behavior.set(
+                NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces,
+                sticky,
+            );
+            NSWindow::setCollectionBehavior_(*self.window, behavior);
+            // Dispatch a resize event with the updated window state
+            WindowView::did_resize(&mut **self.view, sel!(windowDidResize:), nil);
+        }
+    }
+
     fn set_inner_size(&mut self, width: usize, height: usize) {
         unsafe {
             let frame = NSView::frame(*self.view as *mut _);
@@ -1224,6 +1256,44 @@ impl WindowInner {
         set_window_position(*self.window, coords);
     }
 
+    fn adjust_window_geometry(&mut self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        unsafe {
+            let screen = NSWindow::screen(*self.window);
+            if screen.is_null() {
+                return;
+            }
+            // Work in pixels, with the origin at the top left of the
+            // primary screen, so that the adjustment is applied in the
+            // same way as on the other systems
+            let scale = NSWindow::backingScaleFactor(*self.window);
+            let primary = NSScreen::screens(nil).objectAtIndex(0);
+            let primary_height = NSScreen::frame(primary).size.height;
+            let to_screen_rect = |rect: NSRect| -> ScreenRect {
+                euclid::rect(
+                    (rect.origin.x * scale) as isize,
+                    ((primary_height - (rect.origin.y + rect.size.height)) * scale) as isize,
+                    (rect.size.width * scale) as isize,
+                    (rect.size.height * scale) as isize,
+                )
+            };
+
+            let frame = to_screen_rect(NSWindow::frame(*self.window));
+            let work_area = to_screen_rect(NSScreen::visibleFrame(screen));
+            let target = adjustment.apply(frame, work_area);
+
+            let width = target.size.width as f64 / scale;
+            let height = target.size.height as f64 / scale;
+            let rect = NSRect::new(
+                NSPoint::new(
+                    target.origin.x as f64 / scale,
+                    primary_height - (target.origin.y as f64 / scale) - height,
+                ),
+                NSSize::new(width, height),
+            );
+            self.window.setFrame_display_(rect, YES);
+        }
+    }
+
     fn set_text_cursor_position(&mut self, cursor: Rect) {
         if let Some(window_view) = WindowView::get_this(unsafe { &**self.view }) {
             window_view.inner.borrow_mut().text_cursor_position = cursor;
@@ -2833,6 +2903,19 @@ impl WindowView {
                 _ => WindowState::default(),
             };
 
+            let is_sticky = inner.window.as_ref().map_or(false, |window| {
+                let window = window.load();
+                let behavior = unsafe { NSWindow::collectionBehavior(*window) };
+                behavior.contains(
+                    NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces,
+                )
+            });
+            let sticky_state = if is_sticky {
+                WindowState::STICKY
+            } else {
+                WindowState::default()
+            };
+
             let dpi = inner
                 .window
                 .as_ref()
@@ -2849,7 +2932,7 @@ impl WindowView {
                     pixel_height: height as usize,
                     dpi,
                 },
-                window_state: screen_state | level_state,
+                window_state: screen_state | level_state | sticky_state,
                 live_resizing,
             });
         }
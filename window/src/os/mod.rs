@@ -5,6 +5,7 @@ pub use self::windows::*;
 
 #[cfg(feature = "wayland")]
 pub mod wayland;
+pub mod screensaver;
 pub mod x11;
 pub mod x_and_wayland;
 pub mod xdg_desktop_portal;
@@ -19,3 +20,4 @@ pub mod macos;
 pub use self::macos::*;
 
 pub mod parameters;
+pub mod power;
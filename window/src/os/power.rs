@@ -0,0 +1,78 @@
+#![cfg(all(unix, not(target_os = "macos")))]
+
+//! Determines the state of the power supply on Linux and the BSDs.
+//! Whether we are on battery comes from sysfs, and whether the user
+//! wants to save power comes from the active profile of
+//! power-profiles-daemon, which is what the desktop environments
+//! use to implement their power saving modes.
+
+use super::screensaver::with_timeout;
+use anyhow::Context;
+use std::path::Path;
+use std::sync::Mutex;
+use zbus::dbus_proxy;
+
+#[dbus_proxy(
+    interface = "net.hadess.PowerProfiles",
+    default_service = "net.hadess.PowerProfiles",
+    default_path = "/net/hadess/PowerProfiles"
+)]
+trait PowerProfiles {
+    #[dbus_proxy(property)]
+    fn ActiveProfile(&self) -> zbus::Result<String>;
+}
+
+lazy_static::lazy_static! {
+    static ref PROXY: Mutex<Option<PowerProfilesProxy<'static>>> = Mutex::new(None);
+}
+
+/// Returns the proxy for power-profiles-daemon, connecting to the
+/// system bus the first time that it is needed
+async fn proxy() -> anyhow::Result<PowerProfilesProxy<'static>> {
+    if let Some(proxy) = PROXY.lock().unwrap().as_ref() {
+        return Ok(proxy.clone());
+    }
+    let connection = zbus::ConnectionBuilder::system()?.build().await?;
+    let proxy = PowerProfilesProxy::new(&connection)
+        .await
+        .context("make proxy")?;
+    Ok(PROXY.lock().unwrap().get_or_insert(proxy).clone())
+}
+
+/// Returns true if the system has a mains power supply and none
+/// of them are online
+pub fn on_battery() -> bool {
+    let entries = match std::fs::read_dir("/sys/class/power_supply") {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    fn read(path: &Path, name: &str) -> String {
+        std::fs::read_to_string(path.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    let mut have_mains = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if read(&path, "type") == "Mains" {
+            if read(&path, "online") == "1" {
+                return false;
+            }
+            have_mains = true;
+        }
+    }
+    have_mains
+}
+
+/// Returns true if the power-saver profile is active
+pub async fn power_saver_profile_active() -> anyhow::Result<bool> {
+    let proxy = proxy().await?;
+    let profile = with_timeout(
+        "net.hadess.PowerProfiles.ActiveProfile",
+        proxy.ActiveProfile(),
+    )
+    .await?;
+    Ok(profile == "power-saver")
+}
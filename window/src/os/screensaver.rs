@@ -0,0 +1,94 @@
+#![cfg(all(unix, not(target_os = "macos")))]
+
+//! <https://specifications.freedesktop.org/idle-inhibit-spec/latest/>
+//!
+//! Neither X11 nor Wayland tell us whether the screen is locked, so we
+//! ask the screensaver service of the desktop environment.
+
+use anyhow::Context;
+use futures_lite::future::FutureExt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use zbus::dbus_proxy;
+
+#[dbus_proxy(
+    interface = "org.freedesktop.ScreenSaver",
+    default_service = "org.freedesktop.ScreenSaver",
+    default_path = "/org/freedesktop/ScreenSaver"
+)]
+trait ScreenSaver {
+    fn GetActive(&self) -> zbus::Result<bool>;
+
+    /// Not all desktop environments implement this
+    fn GetSessionIdleTime(&self) -> zbus::Result<u32>;
+}
+
+lazy_static::lazy_static! {
+    static ref PROXY: Mutex<Option<ScreenSaverProxy<'static>>> = Mutex::new(None);
+}
+
+/// Runs `query` on a thread of its own and resolves to its result,
+/// so that a slow or missing dbus service can't stall the gui thread
+pub(crate) fn query_in_background<F, Fut, T>(query: F) -> promise::Future<T>
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<T>>,
+    T: Send + 'static,
+{
+    let mut promise = promise::Promise::new();
+    let future = promise.get_future().unwrap();
+    std::thread::spawn(move || {
+        promise.result(promise::spawn::block_on(query()));
+    });
+    future
+}
+
+/// Returns the proxy for the screensaver service, connecting to the
+/// session bus the first time that it is needed
+async fn proxy() -> anyhow::Result<ScreenSaverProxy<'static>> {
+    if let Some(proxy) = PROXY.lock().unwrap().as_ref() {
+        return Ok(proxy.clone());
+    }
+    let connection = zbus::ConnectionBuilder::session()?.build().await?;
+    let proxy = ScreenSaverProxy::new(&connection)
+        .await
+        .context("make proxy")?;
+    Ok(PROXY.lock().unwrap().get_or_insert(proxy).clone())
+}
+
+/// Awaits a dbus call, giving up if the service doesn't respond promptly
+pub(crate) async fn with_timeout<T>(
+    what: &str,
+    future: impl std::future::Future<Output = zbus::Result<T>>,
+) -> anyhow::Result<T> {
+    future
+        .or(async {
+            async_io::Timer::after(Duration::from_secs(1)).await;
+            Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "Timed out waiting for the dbus service",
+            )
+            .into())
+        })
+        .await
+        .with_context(|| what.to_string())
+}
+
+/// Returns true if the screensaver, which locks the screen on most
+/// desktops, is active
+pub async fn get_active() -> anyhow::Result<bool> {
+    let proxy = proxy().await?;
+    with_timeout("org.freedesktop.ScreenSaver.GetActive", proxy.GetActive()).await
+}
+
+/// Returns the time since the user last interacted with the session
+pub async fn get_session_idle_time() -> anyhow::Result<Duration> {
+    let proxy = proxy().await?;
+    let seconds = with_timeout(
+        "org.freedesktop.ScreenSaver.GetSessionIdleTime",
+        proxy.GetSessionIdleTime(),
+    )
+    .await?;
+    Ok(Duration::from_secs(seconds.into()))
+}
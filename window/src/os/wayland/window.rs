@@ -404,6 +404,13 @@ impl WindowOps for WaylandWindow {
         });
     }
 
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        WaylandConnection::with_window_inner(self.0, move |inner| {
+            inner.adjust_window_geometry(adjustment);
+            Ok(())
+        });
+    }
+
     fn set_resize_increments(&self, incr: ResizeIncrement) {
         WaylandConnection::with_window_inner(self.0, move |inner| {
             inner.set_resize_increments(incr)
@@ -996,6 +1003,24 @@ impl WaylandWindowInner {
         self.events.dispatch(WindowEvent::SetInnerSizeCompleted);
     }
 
+    fn adjust_window_geometry(&mut self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        match adjustment {
+            crate::screen::WindowGeometryAdjustment::ResizeBy { .. } => {
+                // We can't know or choose where the window is placed,
+                // but we can change its size
+                let frame = euclid::rect(
+                    0,
+                    0,
+                    self.dimensions.pixel_width as isize,
+                    self.dimensions.pixel_height as isize,
+                );
+                let target = adjustment.apply(frame, frame);
+                self.set_inner_size(target.size.width as usize, target.size.height as usize);
+            }
+            _ => log::debug!("{adjustment:?} is not supported on Wayland"),
+        }
+    }
+
     fn do_paint(&mut self) -> anyhow::Result<()> {
         if self.window.is_none() {
             // We're likely in the middle of closing/destroying
@@ -1,27 +1,35 @@
 //! The connection to the GUI subsystem
-use super::{HWindow, WindowInner};
+use super::{wide_string, HWindow, WindowInner};
 use crate::connection::ConnectionOps;
 use crate::screen::{ScreenInfo, Screens};
 use crate::spawn::*;
-use crate::{Appearance, ScreenRect};
+use crate::{Appearance, PowerState, ScreenRect};
 use anyhow::Context;
 use config::ConfigHandle;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
+use std::path::Path;
 use std::ptr::null_mut;
 use std::rc::Rc;
+use std::time::Duration;
 use winapi::shared::minwindef::*;
 use winapi::shared::windef::*;
 use winapi::shared::winerror::{ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS};
+use winapi::um::mmeapi::waveOutSetVolume;
+use winapi::um::playsoundapi::{PlaySoundW, SND_ASYNC, SND_FILENAME, SND_NODEFAULT};
 use winapi::um::shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
-use winapi::um::winbase::INFINITE;
+use winapi::um::sysinfoapi::GetTickCount;
+use winapi::um::winbase::{GetSystemPowerStatus, INFINITE, SYSTEM_POWER_STATUS};
 use winapi::um::wingdi::{
     DEVMODEW, DISPLAY_DEVICEW, DM_DISPLAYFREQUENCY, QDC_ONLY_ACTIVE_PATHS, QDC_VIRTUAL_MODE_AWARE,
 };
 use winapi::um::winnt::HANDLE;
 use winapi::um::winuser::*;
+use windows::Security::Credentials::UI::{
+    UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+};
 use windows::Win32::Devices::Display::{
     DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
     DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
@@ -63,6 +71,19 @@ impl ConnectionOps for Connection {
         get_appearance()
     }
 
+    fn prefers_reduced_motion(&self) -> promise::Future<bool> {
+        let mut animations_enabled: BOOL = TRUE;
+        let ok = unsafe {
+            SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                &mut animations_enabled as *mut BOOL as *mut _,
+                0,
+            )
+        };
+        promise::Future::ok(ok != 0 && animations_enabled == FALSE)
+    }
+
     fn name(&self) -> String {
         "Windows".to_string()
     }
@@ -100,6 +121,104 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn can_authenticate_user(&self) -> bool {
+        // Windows Hello may not be set up, or there may be no device
+        // that can verify the user.  The check blocks, so it is only
+        // made once.
+        lazy_static::lazy_static! {
+            static ref AVAILABLE: bool = (|| -> anyhow::Result<bool> {
+                let availability = UserConsentVerifier::CheckAvailabilityAsync()?.get()?;
+                Ok(availability == UserConsentVerifierAvailability::Available)
+            })()
+            .unwrap_or_else(|err| {
+                log::warn!("Unable to check for Windows Hello: {:#}", err);
+                false
+            });
+        }
+        *AVAILABLE
+    }
+
+    fn authenticate_user(&self, reason: &str) -> promise::Future<bool> {
+        let mut promise = promise::Promise::new();
+        let future = promise.get_future().unwrap();
+        let reason = reason.to_string();
+        // Waiting for the verification blocks, so do it away
+        // from the gui thread
+        std::thread::spawn(move || {
+            let result = (|| -> anyhow::Result<bool> {
+                let verification = UserConsentVerifier::RequestVerificationAsync(reason.as_str())?;
+                Ok(verification.get()? == UserConsentVerificationResult::Verified)
+            })();
+            promise.result(result);
+        });
+        future
+    }
+
+    fn power_state(&self) -> promise::Future<PowerState> {
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return promise::Future::ok(PowerState::default());
+        }
+        promise::Future::ok(PowerState {
+            on_battery: status.ACLineStatus == 0,
+            // This is the SystemStatusFlag field, which is 1 while
+            // battery saver is on
+            low_power_mode: status.Reserved1 == 1,
+        })
+    }
+
+    fn is_screen_locked(&self) -> promise::Future<bool> {
+        let locked = unsafe {
+            // The input desktop switches to the secure desktop while
+            // the workstation is locked, and we can't switch to it
+            let desktop = OpenInputDesktop(0, FALSE, DESKTOP_SWITCHDESKTOP);
+            if desktop.is_null() {
+                return promise::Future::ok(true);
+            }
+            let locked = SwitchDesktop(desktop) == 0;
+            CloseDesktop(desktop);
+            locked
+        };
+        promise::Future::ok(locked)
+    }
+
+    fn idle_time(&self) -> promise::Future<Option<Duration>> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        let idle = unsafe {
+            if GetLastInputInfo(&mut info) == 0 {
+                return promise::Future::ok(None);
+            }
+            let now = GetTickCount();
+            Duration::from_millis(now.wrapping_sub(info.dwTime) as u64)
+        };
+        promise::Future::ok(Some(idle))
+    }
+
+    fn play_sound(&self, path: &Path, volume: f32) -> anyhow::Result<()> {
+        let wide_path = wide_string(&path.to_string_lossy());
+        // The low and high words hold the volume of the left
+        // and right channels respectively
+        let channel = (volume.clamp(0., 1.) * 0xffff as f32) as DWORD;
+        unsafe {
+            // PlaySound has no volume parameter of its own, but plays
+            // through the default wave device, whose volume applies
+            // only to this process
+            waveOutSetVolume(null_mut(), channel | (channel << 16));
+            if PlaySoundW(
+                wide_path.as_ptr(),
+                null_mut(),
+                SND_FILENAME | SND_ASYNC | SND_NODEFAULT,
+            ) == 0
+            {
+                anyhow::bail!("Unable to play {}", path.display());
+            }
+        }
+        Ok(())
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         let mut info = ScreenInfoHelper::new()?;
         info.enumerate();
@@ -9,6 +9,7 @@ use crate::{
 };
 use anyhow::{bail, Context};
 use async_trait::async_trait;
+use config::window::WindowLevel;
 use config::{ConfigHandle, ImePreeditRendering, SystemBackdrop};
 use lazy_static::lazy_static;
 use promise::Future;
@@ -126,6 +127,9 @@ pub(crate) struct WindowInner {
     config: ConfigHandle,
     paint_throttled: bool,
     invalidated: bool,
+    /// The level that was most recently set; windows that have been
+    /// sent to the bottom of the z-order are not marked as such
+    window_level: WindowLevel,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -327,7 +331,7 @@ impl WindowInner {
 
             self.events.dispatch(WindowEvent::Resized {
                 dimensions: current_dims,
-                window_state: get_window_state(self.hwnd.0),
+                window_state: self.window_state(),
                 live_resizing: self.in_size_move,
             });
         }
@@ -335,6 +339,43 @@ impl WindowInner {
         !same
     }
 
+    fn window_state(&self) -> WindowState {
+        let level = match self.window_level {
+            WindowLevel::AlwaysOnTop => WindowState::ALWAYS_ON_TOP,
+            WindowLevel::AlwaysOnBottom => WindowState::ALWAYS_ON_BOTTOM,
+            WindowLevel::Normal => WindowState::default(),
+        };
+        get_window_state(self.hwnd.0) | level
+    }
+
+    fn set_window_level(&mut self, level: WindowLevel) {
+        let insert_after = match level {
+            WindowLevel::AlwaysOnTop => HWND_TOPMOST,
+            WindowLevel::AlwaysOnBottom => HWND_BOTTOM,
+            WindowLevel::Normal => HWND_NOTOPMOST,
+        };
+        unsafe {
+            SetWindowPos(
+                self.hwnd.0,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE | SWP_NOOWNERZORDER,
+            );
+        }
+        self.window_level = level;
+        // Dispatch a resize event with the updated window state
+        if let Some(dimensions) = self.last_size {
+            self.events.dispatch(WindowEvent::Resized {
+                dimensions,
+                window_state: self.window_state(),
+                live_resizing: false,
+            });
+        }
+    }
+
     fn apply_decoration(&mut self) {
         let hwnd = self.hwnd.0;
         schedule_apply_decoration(hwnd, self.config.window_decorations);
@@ -542,6 +583,7 @@ impl Window {
             config: config.clone(),
             paint_throttled: false,
             invalidated: true,
+            window_level: WindowLevel::Normal,
         }));
 
         // Careful: `raw` owns a ref to inner, but there is no Drop impl
@@ -654,6 +696,49 @@ impl WindowInner {
         .detach();
     }
 
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        let hwnd = self.hwnd.0;
+        promise::spawn::spawn(async move {
+            unsafe {
+                let mut rect = RECT {
+                    left: 0,
+                    bottom: 0,
+                    right: 0,
+                    top: 0,
+                };
+                GetWindowRect(hwnd, &mut rect);
+                let frame: ScreenRect = euclid::rect(
+                    rect.left as isize,
+                    rect.top as isize,
+                    rect_width(&rect) as isize,
+                    rect_height(&rect) as isize,
+                );
+
+                let mut mi: MONITORINFOEXW = std::mem::zeroed();
+                mi.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+                let mon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+                GetMonitorInfoW(mon, &mut mi as *mut MONITORINFOEXW as *mut MONITORINFO);
+                let work_area: ScreenRect = euclid::rect(
+                    mi.rcWork.left as isize,
+                    mi.rcWork.top as isize,
+                    rect_width(&mi.rcWork) as isize,
+                    rect_height(&mi.rcWork) as isize,
+                );
+
+                let target = adjustment.apply(frame, work_area);
+                MoveWindow(
+                    hwnd,
+                    target.origin.x as i32,
+                    target.origin.y as i32,
+                    target.size.width as i32,
+                    target.size.height as i32,
+                    1,
+                );
+            }
+        })
+        .detach();
+    }
+
     fn set_title(&mut self, title: &str) {
         let title = wide_string(title);
         unsafe {
@@ -947,6 +1032,20 @@ impl WindowOps for Window {
         });
     }
 
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        Connection::with_window_inner(self.0, move |inner| {
+            inner.adjust_window_geometry(adjustment);
+            Ok(())
+        });
+    }
+
+    fn set_window_level(&self, level: WindowLevel) {
+        Connection::with_window_inner(self.0, move |inner| {
+            inner.set_window_level(level);
+            Ok(())
+        });
+    }
+
     fn get_clipboard(&self, _clipboard: Clipboard) -> Future<String> {
         Future::result(
             clipboard_win::get_clipboard_string()
@@ -42,9 +42,13 @@ pub struct XConnection {
     pub atom_state_maximized_horz: Atom,
     pub atom_state_hidden: Atom,
     pub atom_state_fullscreen: Atom,
+    pub atom_state_above: Atom,
+    pub atom_state_below: Atom,
+    pub atom_state_sticky: Atom,
     pub atom_net_wm_state: Atom,
     pub atom_motif_wm_hints: Atom,
     pub atom_net_wm_pid: Atom,
+    pub atom_wm_window_role: Atom,
     pub atom_net_wm_name: Atom,
     pub atom_net_wm_icon: Atom,
     pub atom_net_move_resize_window: Atom,
@@ -52,6 +56,8 @@ pub struct XConnection {
     pub atom_net_supported: Atom,
     pub atom_net_supporting_wm_check: Atom,
     pub atom_net_active_window: Atom,
+    pub atom_net_workarea: Atom,
+    pub atom_net_current_desktop: Atom,
     pub(crate) xrm: RefCell<HashMap<String, String>>,
     pub(crate) windows: RefCell<HashMap<xcb::x::Window, Arc<Mutex<XWindowInner>>>>,
     should_terminate: RefCell<bool>,
@@ -508,6 +514,43 @@ impl XConnection {
         Ok(screens)
     }
 
+    /// Returns the `_NET_WORKAREA` of the current desktop: the area that
+    /// the window manager doesn't reserve for panels and docks.
+    /// It spans all of the monitors.
+    pub(crate) fn get_work_area(&self) -> Option<ScreenRect> {
+        let desktop = self
+            .send_and_wait_request(&xcb::x::GetProperty {
+                delete: false,
+                window: self.root,
+                property: self.atom_net_current_desktop,
+                r#type: xcb::x::ATOM_CARDINAL,
+                long_offset: 0,
+                long_length: 1,
+            })
+            .ok()
+            .and_then(|reply| reply.value::<u32>().first().copied())
+            .unwrap_or(0);
+        let reply = self
+            .send_and_wait_request(&xcb::x::GetProperty {
+                delete: false,
+                window: self.root,
+                property: self.atom_net_workarea,
+                r#type: xcb::x::ATOM_CARDINAL,
+                long_offset: desktop * 4,
+                long_length: 4,
+            })
+            .ok()?;
+        match reply.value::<u32>() {
+            [x, y, width, height] => Some(euclid::rect(
+                *x as isize,
+                *y as isize,
+                *width as isize,
+                *height as isize,
+            )),
+            _ => None,
+        }
+    }
+
     fn process_xcb_event(&self, event: &xcb::Event) -> anyhow::Result<()> {
         match event {
             // Following stuff is not obvious at all.
@@ -619,9 +662,13 @@ impl XConnection {
         let atom_state_maximized_horz = Self::intern_atom(&conn, "_NET_WM_STATE_MAXIMIZED_HORZ")?;
         let atom_state_hidden = Self::intern_atom(&conn, "_NET_WM_STATE_HIDDEN")?;
         let atom_state_fullscreen = Self::intern_atom(&conn, "_NET_WM_STATE_FULLSCREEN")?;
+        let atom_state_above = Self::intern_atom(&conn, "_NET_WM_STATE_ABOVE")?;
+        let atom_state_below = Self::intern_atom(&conn, "_NET_WM_STATE_BELOW")?;
+        let atom_state_sticky = Self::intern_atom(&conn, "_NET_WM_STATE_STICKY")?;
         let atom_net_wm_state = Self::intern_atom(&conn, "_NET_WM_STATE")?;
         let atom_motif_wm_hints = Self::intern_atom(&conn, "_MOTIF_WM_HINTS")?;
         let atom_net_wm_pid = Self::intern_atom(&conn, "_NET_WM_PID")?;
+        let atom_wm_window_role = Self::intern_atom(&conn, "WM_WINDOW_ROLE")?;
         let atom_net_wm_name = Self::intern_atom(&conn, "_NET_WM_NAME")?;
         let atom_net_wm_icon = Self::intern_atom(&conn, "_NET_WM_ICON")?;
         let atom_net_move_resize_window = Self::intern_atom(&conn, "_NET_MOVERESIZE_WINDOW")?;
@@ -629,6 +676,8 @@ impl XConnection {
         let atom_net_supported = Self::intern_atom(&conn, "_NET_SUPPORTED")?;
         let atom_net_supporting_wm_check = Self::intern_atom(&conn, "_NET_SUPPORTING_WM_CHECK")?;
         let atom_net_active_window = Self::intern_atom(&conn, "_NET_ACTIVE_WINDOW")?;
+        let atom_net_workarea = Self::intern_atom(&conn, "_NET_WORKAREA")?;
+        let atom_net_current_desktop = Self::intern_atom(&conn, "_NET_CURRENT_DESKTOP")?;
 
         let has_randr = conn.active_extensions().any(|e| e == xcb::Extension::RandR);
 
@@ -740,15 +789,21 @@ impl XConnection {
             atom_state_maximized_horz,
             atom_state_hidden,
             atom_state_fullscreen,
+            atom_state_above,
+            atom_state_below,
+            atom_state_sticky,
             atom_net_wm_state,
             atom_motif_wm_hints,
             atom_net_wm_pid,
+            atom_wm_window_role,
             atom_net_wm_name,
             atom_net_move_resize_window,
             atom_net_wm_moveresize,
             atom_net_supported,
             atom_net_supporting_wm_check,
             atom_net_active_window,
+            atom_net_workarea,
+            atom_net_current_desktop,
             atom_net_wm_icon,
             keyboard,
             kbd_ev,
@@ -10,6 +10,7 @@ use crate::{
 };
 use anyhow::{anyhow, Context as _};
 use async_trait::async_trait;
+use config::window::WindowLevel;
 use config::ConfigHandle;
 use promise::{Future, Promise};
 use raw_window_handle::{
@@ -456,17 +457,8 @@ impl XWindowInner {
                 height as isize,
             );
             let screen = screens
-                .by_name
-                .values()
-                .filter_map(|screen| {
-                    screen
-                        .rect
-                        .intersection(&window_rect)
-                        .map(|r| (screen, r.area()))
-                })
-                .max_by_key(|s| s.1)
-                .ok_or_else(|| anyhow::anyhow!("window is not in any screen"))?
-                .0;
+                .containing(&window_rect)
+                .ok_or_else(|| anyhow::anyhow!("window is not in any screen"))?;
 
             if let Some(value) = self.config.dpi_by_screen.get(&screen.name).copied() {
                 dpi = value;
@@ -962,6 +954,12 @@ impl XWindowInner {
                 window_state |= WindowState::MAXIMIZED;
             } else if s == conn.atom_state_hidden.resource_id() {
                 window_state |= WindowState::HIDDEN;
+            } else if s == conn.atom_state_above.resource_id() {
+                window_state |= WindowState::ALWAYS_ON_TOP;
+            } else if s == conn.atom_state_below.resource_id() {
+                window_state |= WindowState::ALWAYS_ON_BOTTOM;
+            } else if s == conn.atom_state_sticky.resource_id() {
+                window_state |= WindowState::STICKY;
             }
         }
 
@@ -1009,6 +1007,33 @@ impl XWindowInner {
         )
     }
 
+    fn set_window_level(&mut self, level: WindowLevel) -> anyhow::Result<()> {
+        let conn = self.conn();
+        let (above, below) = match level {
+            WindowLevel::AlwaysOnTop => (true, false),
+            WindowLevel::AlwaysOnBottom => (false, true),
+            WindowLevel::Normal => (false, false),
+        };
+        self.set_wm_state(
+            NetWmStateAction::with_bool(above),
+            conn.atom_state_above,
+            None,
+        )?;
+        self.set_wm_state(
+            NetWmStateAction::with_bool(below),
+            conn.atom_state_below,
+            None,
+        )
+    }
+
+    fn set_sticky(&mut self, sticky: bool) -> anyhow::Result<()> {
+        self.set_wm_state(
+            NetWmStateAction::with_bool(sticky),
+            self.conn().atom_state_sticky,
+            None,
+        )
+    }
+
     fn set_fullscreen_hint(&mut self, enable: bool) -> anyhow::Result<()> {
         self.set_wm_state(
             NetWmStateAction::with_bool(enable),
@@ -1245,6 +1270,16 @@ impl XWindow {
             data: &[unsafe { libc::getpid() as u32 }],
         })?;
 
+        if let Some(role) = crate::window_role() {
+            conn.send_request_no_reply(&xcb::x::ChangeProperty {
+                mode: PropMode::Replace,
+                window: window_id,
+                property: conn.atom_wm_window_role,
+                r#type: xcb::x::ATOM_STRING,
+                data: role.as_bytes(),
+            })?;
+        }
+
         conn.send_request_no_reply(&xcb::x::ChangeProperty {
             mode: PropMode::Replace,
             window: window_id,
@@ -1502,6 +1537,63 @@ impl XWindowInner {
         });
     }
 
+    fn adjust_window_geometry(
+        &mut self,
+        adjustment: crate::screen::WindowGeometryAdjustment,
+    ) -> anyhow::Result<()> {
+        if self.dragging {
+            return Ok(());
+        }
+        let conn = self.conn();
+        let coords = conn
+            .send_and_wait_request(&xcb::x::TranslateCoordinates {
+                src_window: self.window_id,
+                dst_window: conn.root,
+                src_x: 0,
+                src_y: 0,
+            })
+            .context("querying window coordinates")?;
+        let frame: ScreenRect = euclid::rect(
+            coords.dst_x().into(),
+            coords.dst_y().into(),
+            self.width as isize,
+            self.height as isize,
+        );
+        let screens = conn.get_cached_screens()?;
+        let screen = screens
+            .containing(&frame)
+            .map(|screen| screen.rect)
+            .unwrap_or(screens.virtual_rect);
+        let work_area = crate::screen::usable_area(screen, conn.get_work_area());
+        let target = adjustment.apply(frame, work_area);
+
+        // As for set_window_position, ask the window manager to do
+        // this so that it can account for the frame
+        conn.send_request_no_reply_log(&xcb::x::SendEvent {
+            propagate: true,
+            destination: xcb::x::SendEventDest::Window(conn.root),
+            event_mask: xcb::x::EventMask::SUBSTRUCTURE_REDIRECT
+                | xcb::x::EventMask::SUBSTRUCTURE_NOTIFY,
+            event: &xcb::x::ClientMessageEvent::new(
+                self.window_id,
+                conn.atom_net_move_resize_window,
+                xcb::x::ClientMessageData::Data32([
+                    xcb::x::Gravity::Static as u32 |
+            1<<12 | // normal program
+            xcb_util::MOVE_RESIZE_WINDOW_X
+                | xcb_util::MOVE_RESIZE_WINDOW_Y
+                | xcb_util::MOVE_RESIZE_WINDOW_WIDTH
+                | xcb_util::MOVE_RESIZE_WINDOW_HEIGHT,
+                    target.origin.x as u32,
+                    target.origin.y as u32,
+                    target.size.width as u32,
+                    target.size.height as u32,
+                ]),
+            ),
+        });
+        Ok(())
+    }
+
     /// Change the title for the window manager
     fn set_title(&mut self, title: &str) {
         if title == self.title {
@@ -1708,6 +1800,14 @@ impl WindowOps for XWindow {
         });
     }
 
+    fn set_window_level(&self, level: WindowLevel) {
+        XConnection::with_window_inner(self.0, move |inner| inner.set_window_level(level));
+    }
+
+    fn set_sticky(&self, sticky: bool) {
+        XConnection::with_window_inner(self.0, move |inner| inner.set_sticky(sticky));
+    }
+
     fn config_did_change(&self, config: &ConfigHandle) {
         let config = config.clone();
         XConnection::with_window_inner(self.0, move |inner| {
@@ -1789,6 +1889,12 @@ impl WindowOps for XWindow {
         });
     }
 
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        XConnection::with_window_inner(self.0, move |inner| {
+            inner.adjust_window_geometry(adjustment)
+        });
+    }
+
     fn set_text_cursor_position(&self, cursor: Rect) {
         XConnection::with_window_inner(self.0, move |inner| {
             inner.set_text_cursor_position(cursor);
@@ -9,17 +9,22 @@ use crate::os::x11::connection::XConnection;
 use crate::os::x11::window::XWindow;
 use crate::screen::Screens;
 use crate::{
-    Appearance, Clipboard, MouseCursor, Rect, RequestedWindowGeometry, ResizeIncrement,
+    Appearance, Clipboard, MouseCursor, PowerState, Rect, RequestedWindowGeometry, ResizeIncrement,
     ScreenPoint, WindowEvent, WindowOps,
 };
+use anyhow::Context;
 use async_trait::async_trait;
+use config::window::WindowLevel;
 use config::ConfigHandle;
 use promise::*;
 use raw_window_handle::{
     HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
 };
 use std::any::Any;
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::rc::Rc;
+use std::time::Duration;
 use wezterm_font::FontConfiguration;
 
 pub enum Connection {
@@ -159,6 +164,14 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn prefers_reduced_motion(&self) -> promise::Future<bool> {
+        crate::os::screensaver::query_in_background(|| async {
+            Ok(crate::os::xdg_desktop_portal::get_reduced_motion()
+                .await
+                .unwrap_or(false))
+        })
+    }
+
     fn beep(&self) {
         match self {
             Self::X11(x) => x.beep(),
@@ -167,6 +180,31 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn play_sound(&self, path: &Path, volume: f32) -> anyhow::Result<()> {
+        play_sound_via_sound_server(path, volume)
+    }
+
+    fn power_state(&self) -> promise::Future<PowerState> {
+        crate::os::screensaver::query_in_background(|| async {
+            Ok(PowerState {
+                on_battery: crate::os::power::on_battery(),
+                low_power_mode: crate::os::power::power_saver_profile_active()
+                    .await
+                    .unwrap_or(false),
+            })
+        })
+    }
+
+    fn is_screen_locked(&self) -> promise::Future<bool> {
+        crate::os::screensaver::query_in_background(crate::os::screensaver::get_active)
+    }
+
+    fn idle_time(&self) -> promise::Future<Option<Duration>> {
+        crate::os::screensaver::query_in_background(|| async {
+            Ok(crate::os::screensaver::get_session_idle_time().await.ok())
+        })
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         match self {
             Self::X11(x) => x.screens(),
@@ -176,6 +214,44 @@ impl ConnectionOps for Connection {
     }
 }
 
+/// Neither X11 nor Wayland play sounds, so we hand the file to the
+/// sound server.  `paplay` works with both PulseAudio and pipewire's
+/// pulse compatibility layer; `pw-play` covers pipewire systems that
+/// don't have the pulse tools installed.
+fn play_sound_via_sound_server(path: &Path, volume: f32) -> anyhow::Result<()> {
+    let volume = volume.clamp(0., 1.);
+    let candidates = [
+        // paplay volume is linear, where 65536 is 100%
+        ("paplay", format!("--volume={}", (volume * 65536.) as u32)),
+        ("pw-play", format!("--volume={volume}")),
+    ];
+
+    for (program, volume_arg) in candidates {
+        match Command::new(program)
+            .arg(volume_arg)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(mut child) => {
+                // Reap the child when it completes
+                std::thread::spawn(move || child.wait());
+                return Ok(());
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("running {program} {}", path.display()))
+            }
+        }
+    }
+    anyhow::bail!(
+        "Unable to play {}: neither paplay nor pw-play is installed",
+        path.display()
+    );
+}
+
 impl Window {
     pub async fn new_window<F>(
         class_name: &str,
@@ -386,6 +462,30 @@ impl WindowOps for Window {
         }
     }
 
+    fn set_window_level(&self, level: WindowLevel) {
+        match self {
+            Self::X11(x) => x.set_window_level(level),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.set_window_level(level),
+        }
+    }
+
+    fn set_sticky(&self, sticky: bool) {
+        match self {
+            Self::X11(x) => x.set_sticky(sticky),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.set_sticky(sticky),
+        }
+    }
+
+    fn adjust_window_geometry(&self, adjustment: crate::screen::WindowGeometryAdjustment) {
+        match self {
+            Self::X11(x) => x.adjust_window_geometry(adjustment),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.adjust_window_geometry(adjustment),
+        }
+    }
+
     fn set_text_cursor_position(&self, cursor: Rect) {
         match self {
             Self::X11(x) => x.set_text_cursor_position(cursor),
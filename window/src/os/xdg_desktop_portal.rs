@@ -51,6 +51,9 @@ impl CachedAppearance {
 
 struct State {
     appearance: CachedAppearance,
+    /// Whether the desktop has disabled animations; `None` until
+    /// we've tried to query it
+    reduced_motion: Option<Option<bool>>,
     subscribe_running: bool,
     last_update: Instant,
 }
@@ -59,6 +62,7 @@ lazy_static::lazy_static! {
   static ref STATE: Mutex<State> = Mutex::new(
           State {
               appearance: CachedAppearance::Unknown,
+              reduced_motion: None,
               subscribe_running: false,
               last_update: Instant::now(),
           }
@@ -139,6 +143,39 @@ pub async fn get_appearance() -> anyhow::Result<Option<Appearance>> {
     }
 }
 
+/// The portal doesn't define a standard setting for this, but it does
+/// relay the GNOME settings, which are also honored by other desktops
+const ANIMATIONS_NAMESPACE: &str = "org.gnome.desktop.interface";
+const ANIMATIONS_KEY: &str = "enable-animations";
+
+fn value_to_reduced_motion(value: &OwnedValue) -> Option<bool> {
+    value.downcast_ref::<bool>().map(|enabled| !*enabled)
+}
+
+/// Returns true if the desktop environment has been configured
+/// to disable animations.
+/// The setting is only read once, as changes to it are delivered
+/// by the signal loop.  The state isn't locked while it is read,
+/// so that the gui thread doesn't wait for the portal.
+pub async fn get_reduced_motion() -> Option<bool> {
+    if let Some(reduced_motion) = STATE.lock().unwrap().reduced_motion {
+        return reduced_motion;
+    }
+
+    let reduced_motion = match read_setting(ANIMATIONS_NAMESPACE, ANIMATIONS_KEY).await {
+        Ok(value) => value_to_reduced_motion(&value),
+        Err(err) => {
+            log::debug!("get_reduced_motion: {err:#}");
+            None
+        }
+    };
+    *STATE
+        .lock()
+        .unwrap()
+        .reduced_motion
+        .get_or_insert(reduced_motion)
+}
+
 pub async fn run_signal_loop(stream: &mut SettingChangedStream<'_>) -> Result<(), anyhow::Error> {
     // query appearance again as it might have changed without us knowing
     if let Ok(value) =
@@ -156,6 +193,11 @@ pub async fn run_signal_loop(stream: &mut SettingChangedStream<'_>) -> Result<()
 
     while let Some(signal) = stream.next().await {
         let args = signal.args()?;
+        if args.namespace == ANIMATIONS_NAMESPACE && args.key == ANIMATIONS_KEY {
+            let reduced_motion = value_to_reduced_motion(&args.value);
+            STATE.lock().unwrap().reduced_motion.replace(reduced_motion);
+            continue;
+        }
         if args.namespace == "org.freedesktop.appearance" && args.key == "color-scheme" {
             if let Ok(appearance) = value_to_appearance(args.value) {
                 let mut state = STATE.lock().unwrap();
@@ -1,4 +1,5 @@
 use crate::ScreenRect;
+use config::window::WindowSnapPosition;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -17,3 +18,131 @@ pub struct ScreenInfo {
     pub max_fps: Option<usize>,
     pub effective_dpi: Option<f64>,
 }
+
+impl Screens {
+    /// Returns the screen that holds the largest part of `rect`
+    pub fn containing(&self, rect: &ScreenRect) -> Option<&ScreenInfo> {
+        self.by_name
+            .values()
+            .filter_map(|screen| screen.rect.intersection(rect).map(|r| (screen, r.area())))
+            .max_by_key(|s| s.1)
+            .map(|s| s.0)
+    }
+}
+
+/// Returns the part of `screen` that windows may occupy, given the work
+/// area that the system reports, if any, which excludes the space that
+/// is reserved by panels and docks.
+/// On X11 the work area spans all of the monitors, so it is clipped to
+/// the monitor.
+pub fn usable_area(screen: ScreenRect, work_area: Option<ScreenRect>) -> ScreenRect {
+    work_area
+        .and_then(|work_area| screen.intersection(&work_area))
+        .unwrap_or(screen)
+}
+
+/// The smallest size, in pixels, that a window may be resized to
+/// by a `WindowGeometryAdjustment`
+const MIN_WINDOW_SIZE: isize = 64;
+
+/// Describes a change to the position and size of a window that is
+/// made from the keyboard, rather than by dragging it with the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowGeometryAdjustment {
+    MoveBy { dx: isize, dy: isize },
+    ResizeBy { dx: isize, dy: isize },
+    Snap(WindowSnapPosition),
+}
+
+impl WindowGeometryAdjustment {
+    /// Computes the new bounds of a window that currently occupies
+    /// `frame`, on a screen whose usable area is `work_area`
+    pub fn apply(&self, frame: ScreenRect, work_area: ScreenRect) -> ScreenRect {
+        match *self {
+            Self::MoveBy { dx, dy } => frame.translate(euclid::vec2(dx, dy)),
+            Self::ResizeBy { dx, dy } => euclid::rect(
+                frame.origin.x,
+                frame.origin.y,
+                (frame.size.width + dx).max(MIN_WINDOW_SIZE),
+                (frame.size.height + dy).max(MIN_WINDOW_SIZE),
+            ),
+            Self::Snap(position) => match position.region() {
+                Some((left, top, width, height)) => {
+                    let scale = |value: f64, size: isize| (value * size as f64).round() as isize;
+                    euclid::rect(
+                        work_area.origin.x + scale(left, work_area.size.width),
+                        work_area.origin.y + scale(top, work_area.size.height),
+                        scale(width, work_area.size.width),
+                        scale(height, work_area.size.height),
+                    )
+                }
+                None => euclid::rect(
+                    work_area.origin.x + (work_area.size.width - frame.size.width) / 2,
+                    work_area.origin.y + (work_area.size.height - frame.size.height) / 2,
+                    frame.size.width,
+                    frame.size.height,
+                ),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rect(x: isize, y: isize, width: isize, height: isize) -> ScreenRect {
+        euclid::rect(x, y, width, height)
+    }
+
+    #[test]
+    fn usable_area_is_clipped_to_the_screen() {
+        let screen = rect(1920, 0, 1920, 1080);
+        assert_eq!(usable_area(screen, None), screen);
+        // A work area spanning two monitors, with a 30px panel at the top
+        assert_eq!(
+            usable_area(screen, Some(rect(0, 30, 3840, 1050))),
+            rect(1920, 30, 1920, 1050)
+        );
+        // A work area that doesn't overlap the screen is ignored
+        assert_eq!(usable_area(screen, Some(rect(0, 0, 1920, 1080))), screen);
+    }
+
+    #[test]
+    fn move_and_resize() {
+        let work_area = rect(0, 30, 1920, 1050);
+        let frame = rect(100, 100, 800, 600);
+        assert_eq!(
+            WindowGeometryAdjustment::MoveBy { dx: -10, dy: 20 }.apply(frame, work_area),
+            rect(90, 120, 800, 600)
+        );
+        assert_eq!(
+            WindowGeometryAdjustment::ResizeBy { dx: 50, dy: -100 }.apply(frame, work_area),
+            rect(100, 100, 850, 500)
+        );
+        assert_eq!(
+            WindowGeometryAdjustment::ResizeBy {
+                dx: -1000,
+                dy: -1000
+            }
+            .apply(frame, work_area),
+            rect(100, 100, MIN_WINDOW_SIZE, MIN_WINDOW_SIZE)
+        );
+    }
+
+    #[test]
+    fn snap_within_work_area() {
+        let work_area = rect(1920, 30, 1920, 1050);
+        let frame = rect(2000, 100, 800, 600);
+        let snap = |position| WindowGeometryAdjustment::Snap(position).apply(frame, work_area);
+        assert_eq!(
+            snap(WindowSnapPosition::LeftHalf),
+            rect(1920, 30, 960, 1050)
+        );
+        assert_eq!(
+            snap(WindowSnapPosition::BottomRightQuarter),
+            rect(2880, 555, 960, 525)
+        );
+        assert_eq!(snap(WindowSnapPosition::Center), rect(2480, 255, 800, 600));
+    }
+}